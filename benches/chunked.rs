@@ -0,0 +1,30 @@
+//! A few gliders on a 4096x4096 board, stepped with each kind of storage:
+//! `cargo bench --bench chunked`.
+
+use std::time::Instant;
+
+use gol::{ChunkedTiles, DenseBool, GameOfLife, GridPos, GridStorage, PackedWords, Pattern};
+
+const SIZE: (usize, usize) = (4096, 4096);
+const GENERATIONS: u32 = 20;
+
+fn run<S: GridStorage>(name: &str) {
+    let mut game = GameOfLife::<S>::empty(SIZE);
+    let glider = Pattern::named("glider").unwrap();
+    for i in 0..8 {
+        game.insert_pattern(&glider, GridPos(100 + i * 480, 200 + i * 450));
+    }
+    let start = Instant::now();
+    let mut population = 0;
+    for _ in 0..GENERATIONS {
+        population = game.tick();
+    }
+    let per_step = start.elapsed() / GENERATIONS;
+    println!("{name:>12}: {per_step:>12.2?} a generation, population {population}");
+}
+
+fn main() {
+    run::<DenseBool>("DenseBool");
+    run::<PackedWords>("PackedWords");
+    run::<ChunkedTiles>("ChunkedTiles");
+}
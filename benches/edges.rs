@@ -0,0 +1,36 @@
+//! A random soup on a 2048x2048 board, stepped under each edge mode, with
+//! how long each takes next to a bounded board: `cargo bench --bench edges`.
+
+use std::time::{Duration, Instant};
+
+use gol::{EdgeMode, GameOfLife};
+
+const SIZE: (usize, usize) = (2048, 2048);
+const GENERATIONS: u32 = 20;
+
+/// The time a generation took on average, and the population at the end.
+fn run(edges: EdgeMode) -> (Duration, usize) {
+    let mut game = GameOfLife::new_empty(SIZE);
+    game.fill(0.3, 1);
+    game.set_edge_mode(edges);
+    let start = Instant::now();
+    let mut population = 0;
+    for _ in 0..GENERATIONS {
+        population = game.tick();
+    }
+    (start.elapsed() / GENERATIONS, population)
+}
+
+fn main() {
+    // The first run also warms up, so bounded is timed again in turn.
+    let (bounded, _) = run(EdgeMode::Bounded);
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+        let (per_step, population) = run(edges);
+        let ratio = per_step.as_secs_f64() / bounded.as_secs_f64();
+        let name = edges.to_string();
+        println!(
+            "{name:>9}: {per_step:>12.2?} a generation ({ratio:.2}x bounded), \
+             population {population}"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! A random soup on a very wide board and on a very tall one, stepped with
+//! row-major and column-major storage: `cargo bench --bench layout`.
+
+use std::time::Instant;
+
+use gol::{ColumnMajor, DenseBool, GameOfLife, GridStorage};
+
+const GENERATIONS: u32 = 20;
+
+fn run<S: GridStorage>(name: &str, size: (usize, usize)) {
+    let mut soup = GameOfLife::new_empty(size);
+    soup.fill(0.3, 1);
+    let mut game = soup.to_storage::<S>();
+    let start = Instant::now();
+    let mut population = 0;
+    for _ in 0..GENERATIONS {
+        population = game.tick();
+    }
+    let per_step = start.elapsed() / GENERATIONS;
+    let (w, h) = size;
+    println!("{name:>12} {w:>7}x{h:<7}: {per_step:>12.2?} a generation, population {population}");
+}
+
+fn main() {
+    for size in [(1 << 20, 8), (8, 1 << 20)] {
+        run::<DenseBool>("DenseBool", size);
+        run::<ColumnMajor>("ColumnMajor", size);
+    }
+}
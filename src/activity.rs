@@ -0,0 +1,140 @@
+use std::{fs, io, path::Path};
+
+use crate::{capture::encode_png, GameOfLife, GridStorage};
+
+/// The heat map's colors, evenly spaced from a cell alive the fewest
+/// generations to one alive the most. Cells never alive are black.
+const GRADIENT: [[u8; 4]; 4] = [
+    [64, 0, 96, 255],
+    [200, 30, 30, 255],
+    [255, 160, 0, 255],
+    [255, 255, 220, 255],
+];
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// Starts counting, for every cell, the generations it is alive after
+    /// each step. Counting costs nothing until it is on. Going back to an
+    /// earlier generation doesn't take away what was counted since.
+    pub fn enable_activity(&mut self) {
+        if self.activity.is_none() {
+            self.activity = Some(vec![0; self.cell_count()]);
+        }
+    }
+
+    /// Stops counting and forgets the counts.
+    pub fn disable_activity(&mut self) {
+        self.activity = None;
+    }
+
+    pub fn has_activity(&self) -> bool {
+        self.activity.is_some()
+    }
+
+    /// How many steps left each cell alive, row-major, since counting began
+    /// or was last [reset](Self::reset_activity); empty while it is off.
+    pub fn activity_map(&self) -> &[u32] {
+        self.activity.as_deref().unwrap_or_default()
+    }
+
+    /// Counts from nothing again, as for a new board.
+    pub fn reset_activity(&mut self) {
+        if let Some(activity) = &mut self.activity {
+            activity.fill(0);
+        }
+    }
+
+    /// Adds the board as it is now to the counts, if they are on.
+    pub(crate) fn count_activity(&mut self) {
+        let Some(mut activity) = self.activity.take() else {
+            return;
+        };
+        let (w, h) = self.size();
+        let mut row = vec![false; w];
+        for y in 0..h {
+            self.storage().read_row(y, &mut row);
+            for (count, &alive) in activity[y * w..(y + 1) * w].iter_mut().zip(&row) {
+                *count += alive as u32;
+            }
+        }
+        self.activity = Some(activity);
+    }
+
+    /// The counts as a heat map, one RGBA pixel a cell, row-major: black
+    /// where nothing was ever alive, then through [`GRADIENT`] up to the
+    /// busiest cell. Empty while counting is off.
+    pub fn activity_rgba(&self) -> Vec<u8> {
+        let map = self.activity_map();
+        let most = map.iter().copied().max().unwrap_or(0);
+        map.iter().flat_map(|&count| heat(count, most)).collect()
+    }
+
+    /// Writes the heat map of [`activity_rgba`](Self::activity_rgba) to
+    /// `path` as a PNG, turning counting on first if it is off.
+    pub fn save_activity_png(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.enable_activity();
+        fs::write(path, encode_png(self.size(), &self.activity_rgba()))
+    }
+}
+
+/// The color of a cell alive for `count` generations, where the most any
+/// cell was is `most`.
+fn heat(count: u32, most: u32) -> [u8; 4] {
+    if count == 0 {
+        return [0, 0, 0, 255];
+    }
+    let stops = GRADIENT.len() - 1;
+    // The least busy cell gets the first color, the busiest the last.
+    let t = (count - 1) as f64 / (most - 1).max(1) as f64 * stops as f64;
+    let at = (t as usize).min(stops - 1);
+    std::array::from_fn(|c| {
+        let (from, to) = (GRADIENT[at][c] as f64, GRADIENT[at + 1][c] as f64);
+        (from + (to - from) * (t - at as f64)).round() as u8
+    })
+}
+
+#[test]
+fn test_blinker_activity() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::from_ascii(".....\n.....\n.###.\n.....\n.....");
+    assert!(game.activity_map().is_empty());
+    game.enable_activity();
+    for _ in 0..5 {
+        game.step();
+    }
+    // Three steps vertical, two horizontal; the middle is always alive.
+    let expected = [
+        0, 0, 0, 0, 0, //
+        0, 0, 3, 0, 0, //
+        0, 2, 5, 2, 0, //
+        0, 0, 3, 0, 0, //
+        0, 0, 0, 0, 0, //
+    ];
+    assert_eq!(game.activity_map(), expected);
+
+    // Edits count for nothing until a step, and a reset starts again.
+    game.set(GridPos(0, 0), true);
+    assert_eq!(game.activity_map()[0], 0);
+    game.reset_activity();
+    assert!(game.activity_map().iter().all(|&count| count == 0));
+    game.disable_activity();
+    assert!(game.activity_map().is_empty());
+}
+
+#[test]
+fn test_activity_heat_map() {
+    assert_eq!(heat(0, 9), [0, 0, 0, 255]);
+    assert_eq!(heat(1, 9), GRADIENT[0]);
+    assert_eq!(heat(9, 9), GRADIENT[3]);
+    assert_eq!(heat(1, 1), GRADIENT[0]);
+    let mut game = GameOfLife::from_ascii("...\n###\n...");
+    game.enable_activity();
+    game.step();
+    game.step();
+    let rgba = game.activity_rgba();
+    assert_eq!(rgba.len(), 9 * 4);
+    // The middle, alive both steps, is the busiest.
+    assert_eq!(rgba[4 * 4..5 * 4], GRADIENT[3]);
+    assert_eq!(rgba[4..8], GRADIENT[0]);
+    assert_eq!(rgba[..4], [0, 0, 0, 255]);
+}
@@ -0,0 +1,92 @@
+use std::{fmt, str::FromStr};
+
+use termwiz::color::ColorAttribute;
+
+use crate::{palette::parse_color, GridPos};
+
+/// A label pinned to a cell, drawn over the board but never read by the
+/// rules: a note such as `gun #1`, or a marker such as an arrow along a
+/// glider's lane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// The cell it is pinned to.
+    pub pos: GridPos,
+    /// One line, never empty.
+    pub text: String,
+    /// A color as a theme file names one, such as `yellow` or `#ff8000`.
+    pub color: String,
+}
+
+impl Annotation {
+    /// The color a note is drawn in unless it is given one.
+    pub const COLOR: &'static str = "yellow";
+
+    /// `text` pinned at `pos`, in `color`, which must be a color a theme file
+    /// could name.
+    pub fn new(pos: GridPos, text: &str, color: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.is_empty() || text.contains('\n') {
+            return Err(format!("a note is one line of text, got {text:?}"));
+        }
+        parse_color(color)?;
+        Ok(Self {
+            pos,
+            text: text.to_string(),
+            color: color.to_ascii_lowercase(),
+        })
+    }
+
+    /// The color to draw it in.
+    pub fn attribute(&self) -> ColorAttribute {
+        parse_color(&self.color).unwrap_or_default()
+    }
+}
+
+/// `X,Y COLOR TEXT`, as [`FromStr`] reads it.
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let GridPos(x, y) = self.pos;
+        write!(f, "{x},{y} {} {}", self.color, self.text)
+    }
+}
+
+impl FromStr for Annotation {
+    type Err = String;
+
+    /// A note as written in a file, such as `12,4 yellow gun #1`: everything
+    /// after the color is the text, spaces and all.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("bad note {s:?}, expected e.g. 12,4 yellow gun #1");
+        let (pos, rest) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let (color, text) = rest.trim_start().split_once(' ').ok_or_else(invalid)?;
+        let pos = pos
+            .split_once(',')
+            .and_then(|(x, y)| Some(GridPos(x.parse().ok()?, y.parse().ok()?)))
+            .ok_or_else(invalid)?;
+        Self::new(pos, text, color)
+    }
+}
+
+#[test]
+fn test_annotations_read_back_as_written() {
+    let note = Annotation::new(GridPos(12, 4), " gun #1 ", "Yellow").unwrap();
+    assert_eq!(note.to_string(), "12,4 yellow gun #1");
+    assert_eq!(note.to_string().parse(), Ok(note));
+    let arrow: Annotation = "0,30 #ff8000   → glider lane".parse().unwrap();
+    assert_eq!(
+        (arrow.pos, arrow.text.as_str()),
+        (GridPos(0, 30), "→ glider lane")
+    );
+    assert_eq!(arrow.to_string().parse(), Ok(arrow));
+
+    for bad in [
+        "12,4 yellow",
+        "12,4 yellow  ",
+        "12 yellow eater",
+        "a,4 red x",
+        "1,1 mauve x",
+    ] {
+        assert!(bad.parse::<Annotation>().is_err(), "{bad:?}");
+    }
+    assert!(Annotation::new(GridPos(0, 0), "two\nlines", Annotation::COLOR).is_err());
+}
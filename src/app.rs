@@ -0,0 +1,557 @@
+use std::{error::Error, fmt, path::PathBuf, time::Duration};
+
+use crate::{
+    frontend::{
+        drawable_area, grown, Frontend, Options, Renderer, Scale, Summary, DELAY, MAX_DELAY,
+    },
+    macrocell, rle,
+    scene::Transform,
+    EdgeMode, GameOfLife, GridPos, Pattern, Rule,
+};
+
+/// The exit code for a run that wanted a terminal and was not given one it
+/// could use, apart from the codes [`crate::exit_code`] reports a run's end
+/// with.
+pub const NO_TERMINAL: u8 = 3;
+
+/// The exit code for a command line that couldn't be read, as sysexits'
+/// `EX_USAGE`: an unknown argument, or a value that doesn't parse.
+pub const USAGE: u8 = 64;
+
+/// The exit code for anything else that went wrong, as sysexits'
+/// `EX_SOFTWARE`, so that no failure reads as a way a run ended.
+pub const FAILED: u8 = 70;
+
+/// A command line that couldn't be read, exiting with [`USAGE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage(pub String);
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for Usage {}
+
+/// The exit code for a run that stopped at `err`: [`USAGE`] if it was the
+/// command line's fault and [`FAILED`] otherwise.
+pub fn failure_code(err: &(dyn Error + 'static)) -> u8 {
+    match err.is::<Usage>() {
+        true => USAGE,
+        false => FAILED,
+    }
+}
+
+/// What the program does with a command line, given whether stdin and
+/// stdout are terminals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launch {
+    /// Checks the engines against each other; plain text.
+    Verify,
+    /// Runs a soup until it settles; plain text.
+    Headless,
+    /// Runs many soups for their statistics; plain text.
+    Stats,
+    /// Finds out what the terminal can do and which flags suit it; plain
+    /// text, asking what it can of a terminal if there is one.
+    Doctor,
+    /// Times the engines and keeps the delay this machine keeps up with;
+    /// plain text.
+    Tune,
+    /// The interactive game.
+    Play,
+    /// The interactive game, with no terminal to play it on. It says so
+    /// rather than drawing into a pipe, and exits with [`NO_TERMINAL`].
+    NoTerminal { stdin: bool, stdout: bool },
+}
+
+impl Launch {
+    /// What to do with `first`, the first argument if there is one. Only
+    /// the game needs a terminal; the subcommands that print text run
+    /// wherever their output goes. `--headless` is taken for `headless`.
+    pub fn new(first: Option<&str>, stdin: bool, stdout: bool) -> Self {
+        match first {
+            Some("verify") => Launch::Verify,
+            Some("headless" | "--headless") => Launch::Headless,
+            Some("stats") => Launch::Stats,
+            Some("doctor") => Launch::Doctor,
+            Some("tune") => Launch::Tune,
+            _ if stdin && stdout => Launch::Play,
+            _ => Launch::NoTerminal { stdin, stdout },
+        }
+    }
+
+    /// Whether `first` is the subcommand this launches, to be taken off the
+    /// arguments before they are parsed.
+    pub fn consumes(&self, first: Option<&str>) -> bool {
+        matches!(
+            self,
+            Launch::Verify | Launch::Headless | Launch::Stats | Launch::Doctor | Launch::Tune
+        ) && first.is_some()
+    }
+
+    /// What to tell the user when there is no terminal, if there isn't.
+    pub fn message(&self) -> Option<String> {
+        let &Launch::NoTerminal { stdin, stdout } = self else {
+            return None;
+        };
+        let missing = match (stdin, stdout) {
+            (false, false) => "stdin and stdout are not terminals",
+            (true, false) => "stdout is not a terminal",
+            _ => "stdin is not a terminal",
+        };
+        Some(format!(
+            "{missing}, so the game has nowhere to be played; \
+             try `headless` for a run that prints its outcome instead"
+        ))
+    }
+}
+
+/// What to tell the user when there is a terminal but the game can't be
+/// set up on it, for `err`: its description can't be found, or it can't be
+/// put in raw mode or switched to the alternate screen. `term` is `$TERM`,
+/// if it is set, which is what usually needs fixing.
+pub fn terminal_failure(err: &dyn fmt::Display, term: Option<&str>) -> String {
+    let hint = match term {
+        None | Some("") => "TERM isn't set; set it to the terminal's type, such as xterm-256color",
+        Some("dumb") => "TERM is dumb, which can't draw the game; set it to the terminal's type",
+        Some(term) => &format!("check that TERM={term} is right for this terminal"),
+    };
+    format!(
+        "could not set up the terminal: {err}; {hint}, \
+         or try `headless` for a run that prints its outcome instead"
+    )
+}
+
+/// The most cells a board fitted to the screen has before [`oversize`] does
+/// something about it, unless told otherwise: a quarter of a million steps
+/// and draws at the default pace on most machines.
+pub const CELL_BUDGET: usize = 250_000;
+
+/// What [`oversize`] goes on besides the screen: the budget and what the
+/// user asked for that it mustn't undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// The most cells a board can have before it counts as too big; 0 for
+    /// no limit.
+    pub cells: usize,
+    /// The board's width and height where given, rather than fitted to the
+    /// screen.
+    pub size: (Option<usize>, Option<usize>),
+    /// The delay between generations, if one was given rather than left to
+    /// the default or to tuning.
+    pub delay: Option<Duration>,
+}
+
+/// A board too big for its [`Budget`], and what is done about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Oversize {
+    pub cells: usize,
+    pub budget: usize,
+    pub mitigation: Mitigation,
+}
+
+/// What is done about a board too big for its [`Budget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mitigation {
+    /// Draw a cell per character rather than several, for a board of
+    /// `cells`.
+    Coarser { cells: (usize, usize) },
+    /// Step no more often than every `delay`, in proportion to the cells
+    /// past the budget.
+    Slower { delay: Duration },
+    /// Leave the board as the user asked for it, saying why it may be slow.
+    Warn,
+}
+
+/// What to do about the board drawn at `footprint` on a screen of
+/// `terminal` characters, if it has more cells than `budget` allows; `None`
+/// if it doesn't. `coarser` is a footprint of fewer cells the board could
+/// be drawn at instead, taken if it has fewer cells and the user didn't give
+/// both the width and height. Failing that, a delay left to the default is
+/// lengthened, and a delay that was given is only warned about.
+pub fn oversize(
+    terminal: (usize, usize),
+    footprint: Scale,
+    border: bool,
+    coarser: Option<Scale>,
+    budget: &Budget,
+) -> Option<Oversize> {
+    let fit = |scale: Scale| {
+        let (w, h) = scale.cells_in(drawable_area(terminal, border));
+        (budget.size.0.unwrap_or(w), budget.size.1.unwrap_or(h))
+    };
+    let (w, h) = fit(footprint);
+    let cells = w * h;
+    if budget.cells == 0 || cells <= budget.cells {
+        return None;
+    }
+    let sized = budget.size.0.is_some() && budget.size.1.is_some();
+    let coarser = coarser
+        .filter(|_| !sized)
+        .map(fit)
+        .filter(|&(w, h)| w * h < cells);
+    let mitigation = match (coarser, budget.delay) {
+        (Some(cells), _) => Mitigation::Coarser { cells },
+        (None, None) => {
+            let delay = DELAY.mul_f64(cells as f64 / budget.cells as f64);
+            Mitigation::Slower {
+                delay: delay.min(MAX_DELAY),
+            }
+        }
+        (None, Some(_)) => Mitigation::Warn,
+    };
+    Some(Oversize {
+        cells,
+        budget: budget.cells,
+        mitigation,
+    })
+}
+
+impl fmt::Display for Oversize {
+    /// A notice saying what was done and how to have it otherwise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { cells, budget, .. } = self;
+        write!(
+            f,
+            "a board of {cells} cells is past the budget of {budget}: "
+        )?;
+        match self.mitigation {
+            Mitigation::Coarser { cells: (w, h) } => write!(
+                f,
+                "drawing a cell per character, {w}x{h} (--cell-budget 0 draws them all)"
+            ),
+            Mitigation::Slower { delay } => write!(
+                f,
+                "stepping every {} ms (--delay or --cell-budget 0 overrides it)",
+                delay.as_millis()
+            ),
+            Mitigation::Warn => write!(
+                f,
+                "expect it to run slowly; try --renderer block or a smaller --width and --height"
+            ),
+        }
+    }
+}
+
+/// Where the board [`run_app`] runs comes from.
+pub enum Source {
+    /// This board, as it is: its size, rule and edges are kept.
+    Game(Box<GameOfLife>),
+    /// Nothing but dead cells.
+    Empty,
+    /// A random soup, each cell live with probability `density`.
+    Soup { density: f64, seed: u64 },
+    /// A library pattern, by [`Pattern::named`] name, in the middle.
+    Pattern(String),
+    /// An RLE or macrocell (`.mc`) file's pattern, in the middle.
+    File(PathBuf),
+}
+
+/// What [`run_app`] runs and how.
+pub struct AppConfig {
+    pub source: Source,
+    /// The size of the board on screen, what fits on the renderer's screen
+    /// when not given; the board is bigger by any margin in the options.
+    /// Unused for [`Source::Game`].
+    pub size: Option<(usize, usize)>,
+    /// Unused for [`Source::Game`].
+    pub rule: Rule,
+    /// Unused for [`Source::Game`].
+    pub edges: EdgeMode,
+    /// Turns and flips, in order, for the pattern of a [`Source::Pattern`]
+    /// or [`Source::File`] before it is stamped.
+    pub transforms: Vec<Transform>,
+    pub options: Options,
+}
+
+impl AppConfig {
+    /// Runs `source` with the default rule, edges and options.
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            size: None,
+            rule: Rule::default(),
+            edges: EdgeMode::default(),
+            transforms: Vec::new(),
+            options: Options::default(),
+        }
+    }
+
+    /// Keeps `margin` cells of the board off screen on every side, making
+    /// the board that much bigger than the part shown.
+    pub fn with_margin(mut self, margin: usize) -> Self {
+        self.options.margin = margin;
+        self
+    }
+}
+
+impl Source {
+    /// The board, `size` cells unless it brings its own, and a warning if
+    /// its pattern, put through `transforms`, didn't fit.
+    pub fn build(
+        self,
+        size: (usize, usize),
+        transforms: &[Transform],
+    ) -> Result<(GameOfLife, Option<String>), String> {
+        let (name, pattern) = match self {
+            Source::Game(game) => return Ok((*game, None)),
+            Source::Empty => return Ok((GameOfLife::new_empty(size), None)),
+            Source::Soup { density, seed } => {
+                let mut game = GameOfLife::new_empty(size);
+                game.fill(density, seed);
+                return Ok((game, None));
+            }
+            Source::Pattern(name) => {
+                let pattern = Pattern::named(&name);
+                let pattern = pattern.ok_or_else(|| format!("unknown pattern {name:?}"))?;
+                (name, pattern)
+            }
+            Source::File(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("{}: {err}", path.display()))?;
+                let decode = match path.extension() {
+                    Some(ext) if ext == "mc" => macrocell::decode,
+                    _ => rle::decode,
+                };
+                (path.display().to_string(), decode(&text)?.0)
+            }
+        };
+        let pattern = pattern.transformed(transforms);
+        let mut game = GameOfLife::new_empty(size);
+        let (pw, ph) = pattern.size();
+        let origin = GridPos(size.0.saturating_sub(pw) / 2, size.1.saturating_sub(ph) / 2);
+        let warning = game.insert_pattern(&pattern, origin).warning(&name);
+        Ok((game, warning))
+    }
+}
+
+/// Builds the board `config` describes and runs it on `renderer` until the
+/// user quits or a limit in its options is reached.
+pub fn run_app<R: Renderer>(
+    renderer: &mut R,
+    config: AppConfig,
+) -> Result<Summary, Box<dyn Error>> {
+    let prebuilt = matches!(config.source, Source::Game(_));
+    let size = grown(
+        config.size.unwrap_or(renderer.size()),
+        config.options.margin,
+    );
+    let (mut game, warning) = config.source.build(size, &config.transforms)?;
+    if !prebuilt {
+        game.set_rule(config.rule);
+        game.set_edge_mode(config.edges);
+    }
+    let mut options = config.options;
+    options.notices.extend(warning);
+    renderer.run(&mut game, &options)
+}
+
+#[test]
+fn test_run_app_runs_the_given_board() {
+    use termwiz::terminal::buffered::BufferedTerminal;
+
+    use crate::{
+        frontend::{BlockRenderer, Offscreen},
+        Settled,
+    };
+
+    let screen = BufferedTerminal::new(Offscreen { size: (30, 12) }).unwrap();
+    let mut renderer = BlockRenderer::new(screen).unwrap();
+    let mut game = GameOfLife::new_empty((8, 8));
+    game.insert_pattern(&Pattern::named("blinker").unwrap(), GridPos(2, 2));
+    let mut config = AppConfig::new(Source::Game(Box::new(game)));
+    config.options.generations = Some(3);
+    config.options.activity = true;
+    let summary = run_app(&mut renderer, config).unwrap();
+    assert_eq!((summary.generations, summary.population), (3, 3));
+    assert_eq!(
+        (summary.peak_population, summary.settled),
+        (3, Some(Settled::Cycle(2)))
+    );
+    assert_eq!(summary.populations, [(0, 3), (1, 3), (2, 3), (3, 3)]);
+    let (size, heat_map) = summary.activity.unwrap();
+    assert_eq!((size, heat_map.len()), ((8, 8), 8 * 8 * 4));
+
+    let board = |source: Source| source.build((8, 6), &[]).unwrap().0;
+    assert_eq!(board(Source::Empty).population(), 0);
+    assert_eq!(board(Source::Pattern("block".into())).population(), 4);
+    assert!(board(Source::Pattern("block".into())).get(GridPos(3, 2)));
+    assert!(Source::Pattern("no-such-pattern".into())
+        .build((8, 6), &[])
+        .is_err());
+    let (_, warning) = Source::Pattern("gosper-glider-gun".into())
+        .build((8, 6), &[])
+        .unwrap();
+    assert!(warning.unwrap().starts_with("gosper-glider-gun clipped: "));
+    assert!(Source::File("/nonexistent.rle".into())
+        .build((8, 6), &[])
+        .is_err());
+    let soup = |seed| board(Source::Soup { density: 0.5, seed }).fingerprint();
+    assert_eq!(soup(1), soup(1));
+    // A glider turned a half turn points up and to the left, 3x3 in the
+    // middle of the board.
+    let turned = [Transform::Rotate(2)];
+    let (glider, _) = Source::Pattern("glider".into())
+        .build((8, 6), &turned)
+        .unwrap();
+    let cells = [(2, 1), (3, 1), (4, 1), (2, 2), (3, 3)];
+    assert!(cells.iter().all(|&(x, y)| glider.get(GridPos(x, y))));
+    assert_eq!(glider.population(), 5);
+
+    // A block lives forever under the default rule, but not under one
+    // without survivals.
+    let mut config = AppConfig::new(Source::Pattern("block".into()));
+    config.rule = "B3/S".parse().unwrap();
+    config.options.generations = Some(1);
+    let summary = run_app(&mut renderer, config).unwrap();
+    assert_eq!(summary.population, 0);
+    assert_eq!(
+        (summary.peak_population, summary.settled),
+        (4, Some(Settled::Died))
+    );
+}
+
+#[test]
+fn test_oversize_picks_a_mitigation_the_user_allows() {
+    let braille = Scale::braille(2.0 / 3.0);
+    let block = Scale::block(1.0);
+    let budget = Budget {
+        cells: CELL_BUDGET,
+        size: (None, None),
+        delay: None,
+    };
+    // An ordinary terminal is well within it either way.
+    assert_eq!(
+        oversize((200, 60), braille, false, Some(block), &budget),
+        None
+    );
+    // A huge one draws a cell per character instead of per dot.
+    let huge = oversize((500, 140), braille, false, Some(block), &budget).unwrap();
+    assert_eq!((huge.cells, huge.budget), (1000 * 420, CELL_BUDGET));
+    assert_eq!(huge.mitigation, Mitigation::Coarser { cells: (500, 140) });
+    let notice = huge.to_string();
+    assert!(notice.starts_with("a board of 420000 cells is past the budget of 250000: drawing"));
+    // With a border, the frame's characters don't count.
+    let framed = oversize((500, 140), braille, true, Some(block), &budget).unwrap();
+    assert_eq!(framed.mitigation, Mitigation::Coarser { cells: (498, 138) });
+
+    // Without a coarser footprint, the default delay stretches, as far as
+    // the slowest there is.
+    let slower = oversize((500, 140), braille, false, None, &budget).unwrap();
+    assert_eq!(
+        slower.mitigation,
+        Mitigation::Slower {
+            delay: DELAY.mul_f64(1.68)
+        }
+    );
+    let vast = oversize((2000, 500), braille, false, None, &budget).unwrap();
+    assert_eq!(vast.mitigation, Mitigation::Slower { delay: MAX_DELAY });
+    // A delay that was asked for is kept.
+    let fixed = Budget {
+        delay: Some(DELAY),
+        ..budget
+    };
+    let warned = oversize((500, 140), braille, false, None, &fixed).unwrap();
+    assert_eq!(warned.mitigation, Mitigation::Warn);
+    assert!(warned.to_string().contains("--renderer block"));
+
+    // A board sized by hand counts as given, and is never drawn coarser.
+    let given = Budget {
+        size: (Some(1000), Some(1000)),
+        ..budget
+    };
+    assert_eq!(
+        oversize((80, 24), block, false, Some(block), &given)
+            .unwrap()
+            .cells,
+        1_000_000
+    );
+    let kept = oversize((500, 140), braille, false, Some(block), &given).unwrap();
+    assert!(matches!(kept.mitigation, Mitigation::Slower { .. }));
+    let small = Budget {
+        size: (Some(100), Some(100)),
+        ..budget
+    };
+    assert_eq!(
+        oversize((500, 140), braille, false, Some(block), &small),
+        None
+    );
+    // Half given still fits the other half to the screen.
+    let wide = Budget {
+        size: (Some(1000), None),
+        ..budget
+    };
+    let coarser = oversize((500, 140), braille, false, Some(block), &wide).unwrap();
+    assert_eq!(
+        coarser.mitigation,
+        Mitigation::Coarser { cells: (1000, 140) }
+    );
+    // No budget, no limit.
+    let unlimited = Budget { cells: 0, ..budget };
+    assert_eq!(
+        oversize((2000, 500), braille, false, None, &unlimited),
+        None
+    );
+}
+
+#[test]
+fn test_launch_needs_a_terminal_only_to_play() {
+    assert_eq!(Launch::new(None, true, true), Launch::Play);
+    assert_eq!(Launch::new(Some("--rule"), true, true), Launch::Play);
+    let piped = Launch::new(None, true, false);
+    assert_eq!(
+        piped,
+        Launch::NoTerminal {
+            stdin: true,
+            stdout: false
+        }
+    );
+    assert!(piped
+        .message()
+        .unwrap()
+        .starts_with("stdout is not a terminal, "));
+    assert!(!piped.consumes(None));
+    let supervised = Launch::new(Some("--width"), false, false);
+    assert!(supervised
+        .message()
+        .unwrap()
+        .starts_with("stdin and stdout are not"));
+    for first in ["headless", "--headless"] {
+        let launch = Launch::new(Some(first), false, false);
+        assert_eq!((launch, launch.message()), (Launch::Headless, None));
+        assert!(launch.consumes(Some(first)));
+    }
+    assert_eq!(Launch::new(Some("verify"), true, false), Launch::Verify);
+    assert_eq!(Launch::new(Some("stats"), false, true), Launch::Stats);
+    assert_eq!(Launch::new(Some("doctor"), false, false), Launch::Doctor);
+    assert!(Launch::Doctor.consumes(Some("doctor")));
+    assert_eq!(Launch::new(Some("tune"), true, true), Launch::Tune);
+    assert!(Launch::Tune.consumes(Some("tune")));
+    assert!(!Launch::Play.consumes(Some("--clock")));
+
+    let failure = |term| terminal_failure(&"no terminfo entry", term);
+    assert!(failure(None).starts_with("could not set up the terminal: no terminfo entry; TERM "));
+    assert!(failure(Some("")).contains("TERM isn't set"));
+    assert!(failure(Some("dumb")).contains("TERM is dumb"));
+    assert!(failure(Some("vt52x")).contains("check that TERM=vt52x is right"));
+    let instead = "try `headless` for a run that prints its outcome instead";
+    assert!(failure(Some("xterm")).ends_with(instead));
+}
+
+#[test]
+fn test_failure_code_tells_usage_from_other_failures() {
+    use crate::Settled;
+
+    let usage: Box<dyn Error> = Box::new(Usage("unknown argument: --wdith".into()));
+    assert_eq!(failure_code(&*usage), USAGE);
+    assert_eq!(usage.to_string(), "unknown argument: --wdith");
+    let io: Box<dyn Error> = Box::new(std::io::Error::other("disk full"));
+    assert_eq!(failure_code(&*io), FAILED);
+    // Neither reads as a way a run ended.
+    let outcomes = [None, Some(Settled::Died), Some(Settled::Cycle(2))].map(crate::exit_code);
+    for code in [USAGE, FAILED, NO_TERMINAL, crate::query::ASSERTION_FAILED] {
+        assert!(!outcomes.contains(&code));
+    }
+}
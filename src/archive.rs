@@ -0,0 +1,260 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::GameOfLife;
+
+/// What a frame archive starts with, before its version.
+const MAGIC: &[u8; 8] = b"GOLFRAME";
+
+/// The version written now.
+const VERSION: u32 = 1;
+
+/// Bytes before the first frame: the magic, the version and the size.
+const HEADER: u64 = 8 + 4 + 4 + 4;
+
+fn invalid(why: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, why.into())
+}
+
+/// Records a run in the `.gol` frame archive: a header giving the board's
+/// size, then every generation in turn, each its number and its cells
+/// packed as [`GameOfLife::pack_into`] packs them. Every frame is the same
+/// size, so a reader finds any generation without reading those before it.
+#[derive(Debug)]
+pub struct FrameWriter<W: Write> {
+    out: W,
+    size: (usize, usize),
+    /// The generation written last.
+    last: Option<u64>,
+    words: Vec<u64>,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Starts an archive of boards of `size` in `out`.
+    pub fn new(mut out: W, size: (usize, usize)) -> io::Result<Self> {
+        let dimension = |n: usize| {
+            u32::try_from(n).map_err(|_| invalid(format!("{n} cells is too big to archive")))
+        };
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&dimension(size.0)?.to_le_bytes())?;
+        out.write_all(&dimension(size.1)?.to_le_bytes())?;
+        Ok(Self {
+            out,
+            size,
+            last: None,
+            words: Vec::new(),
+        })
+    }
+
+    /// Adds `game` as it is now, which must be the generation after the one
+    /// added last, on a board of the archive's size.
+    pub fn push(&mut self, game: &GameOfLife) -> io::Result<()> {
+        let generation = game.generation();
+        if game.size() != self.size {
+            let ((w, h), (gw, gh)) = (self.size, game.size());
+            return Err(invalid(format!("a {gw}x{gh} board in a {w}x{h} archive")));
+        }
+        if let Some(last) = self.last.filter(|&last| last + 1 != generation) {
+            return Err(invalid(format!(
+                "generation {generation} can't follow {last}"
+            )));
+        }
+        game.pack_into(&mut self.words);
+        self.out.write_all(&generation.to_le_bytes())?;
+        for word in &self.words {
+            self.out.write_all(&word.to_le_bytes())?;
+        }
+        self.last = Some(generation);
+        Ok(())
+    }
+
+    /// Flushes what is written and hands back where it went.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Reads a `.gol` frame archive a frame at a time, so that however long
+/// the recording, only one frame is held. As an iterator it yields each
+/// frame's cells, row-major, from wherever it was last
+/// [`seek`](Self::seek)ed to.
+#[derive(Debug)]
+pub struct FrameReader<R: Read + Seek> {
+    input: R,
+    size: (usize, usize),
+    /// Whole frames in the archive, a torn last one left out.
+    frames: u64,
+    /// The generation of the first frame, if there is one.
+    first: Option<u64>,
+    /// The next frame to be read, counting from 0.
+    next: u64,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read + Seek> FrameReader<R> {
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut header = [0; HEADER as usize];
+        input.rewind()?;
+        input
+            .read_exact(&mut header)
+            .map_err(|_| invalid("too short for a frame archive"))?;
+        if &header[..8] != MAGIC {
+            return Err(invalid("not a frame archive"));
+        }
+        let number = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+        if number(8) != VERSION {
+            return Err(invalid(format!(
+                "frame archive version {} is unknown",
+                number(8)
+            )));
+        }
+        let size = (number(12) as usize, number(16) as usize);
+        let length = input.seek(SeekFrom::End(0))?;
+        let mut reader = Self {
+            input,
+            size,
+            frames: 0,
+            first: None,
+            next: 0,
+            bytes: Vec::new(),
+        };
+        reader.frames = (length - HEADER) / reader.frame_bytes();
+        if reader.frames > 0 {
+            reader.input.seek(SeekFrom::Start(HEADER))?;
+            let mut generation = [0; 8];
+            reader.input.read_exact(&mut generation)?;
+            reader.first = Some(u64::from_le_bytes(generation));
+        }
+        reader.input.seek(SeekFrom::Start(HEADER))?;
+        Ok(reader)
+    }
+
+    fn frame_bytes(&self) -> u64 {
+        8 + 8 * (self.size.0 * self.size.1).div_ceil(64) as u64
+    }
+
+    /// The size of the boards recorded.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// How many frames there are.
+    pub fn len(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    /// The generations recorded, first and last.
+    pub fn generations(&self) -> Option<(u64, u64)> {
+        self.first.map(|first| (first, first + self.frames - 1))
+    }
+
+    /// The generation the next frame read shows, if there is one.
+    pub fn next_generation(&self) -> Option<u64> {
+        self.first
+            .filter(|_| self.next < self.frames)
+            .map(|first| first + self.next)
+    }
+
+    /// Makes `generation` the next frame read, by where it must be rather
+    /// than by reading up to it. A generation outside those recorded is an
+    /// error, and leaves the reader where it was.
+    pub fn seek(&mut self, generation: u64) -> io::Result<()> {
+        let index = match self.generations() {
+            Some((first, last)) if (first..=last).contains(&generation) => generation - first,
+            _ => return Err(invalid(format!("generation {generation} isn't recorded"))),
+        };
+        self.input
+            .seek(SeekFrom::Start(HEADER + index * self.frame_bytes()))?;
+        self.next = index;
+        Ok(())
+    }
+
+    /// Reads the next frame into `cells`, returning its generation, or
+    /// `None` past the last one.
+    pub fn read_into(&mut self, cells: &mut Vec<bool>) -> io::Result<Option<u64>> {
+        let Some(expected) = self.next_generation() else {
+            return Ok(None);
+        };
+        self.bytes.resize(self.frame_bytes() as usize, 0);
+        self.input.read_exact(&mut self.bytes)?;
+        self.next += 1;
+        let (generation, words) = self.bytes.split_at(8);
+        let generation = u64::from_le_bytes(generation.try_into().unwrap());
+        if generation != expected {
+            return Err(invalid(format!(
+                "found generation {generation} for {expected}"
+            )));
+        }
+        cells.clear();
+        cells.extend(words.chunks_exact(8).flat_map(|word| {
+            let word = u64::from_le_bytes(word.try_into().unwrap());
+            (0..64).map(move |bit| word >> bit & 1 != 0)
+        }));
+        cells.truncate(self.size.0 * self.size.1);
+        Ok(Some(generation))
+    }
+}
+
+impl<R: Read + Seek> Iterator for FrameReader<R> {
+    type Item = io::Result<Vec<bool>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cells = Vec::new();
+        self.read_into(&mut cells)
+            .transpose()
+            .map(|read| read.map(|_| cells))
+    }
+}
+
+#[test]
+fn test_frame_archive_streams_and_seeks() {
+    use std::io::Cursor;
+
+    // Wide enough that a frame doesn't end on a word.
+    let mut game = GameOfLife::new((70, 9));
+    game.step();
+    let mut frames = Vec::new();
+    let mut writer = FrameWriter::new(Cursor::new(Vec::new()), game.size()).unwrap();
+    for _ in 0..6 {
+        writer.push(&game).unwrap();
+        frames.push(game.surface().to_vec());
+        game.step();
+    }
+    // Generations are kept in order, with none missing.
+    game.step();
+    assert!(writer.push(&game).is_err());
+    assert!(writer.push(&GameOfLife::new_empty((4, 4))).is_err());
+    let mut bytes = writer.finish().unwrap().into_inner();
+
+    let mut reader = FrameReader::new(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!((reader.size(), reader.len()), ((70, 9), 6));
+    assert_eq!(reader.generations(), Some((1, 6)));
+    assert_eq!(reader.next_generation(), Some(1));
+    let read = reader.by_ref().collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(read, frames);
+    assert_eq!(reader.next_generation(), None);
+    // Seeking goes straight to a frame, backwards or forwards.
+    reader.seek(4).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), frames[3]);
+    reader.seek(1).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), frames[0]);
+    assert!(reader.seek(7).is_err() && reader.seek(0).is_err());
+    assert_eq!(reader.next_generation(), Some(2));
+
+    // A frame cut short by a crash is left out.
+    bytes.truncate(bytes.len() - 3);
+    let reader = FrameReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.generations(), Some((1, 5)));
+    assert!(FrameReader::new(Cursor::new(b"GOLFRAMA".to_vec())).is_err());
+    let empty = FrameWriter::new(Cursor::new(Vec::new()), (3, 3))
+        .unwrap()
+        .finish()
+        .unwrap();
+    let mut empty = FrameReader::new(Cursor::new(empty.into_inner())).unwrap();
+    assert!(empty.is_empty() && empty.next().is_none());
+}
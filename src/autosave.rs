@@ -0,0 +1,249 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{Frame, GameOfLife};
+
+/// How many autosaves are kept, each new one replacing the oldest.
+const SLOTS: usize = 2;
+
+/// Where state kept between runs goes: `$XDG_STATE_HOME/gol`, or
+/// `~/.local/state/gol` without it. `None` without a home directory either.
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("gol"))
+}
+
+/// Writes `contents` to `path` so that it holds either what it held before or
+/// all of `contents`, never part of them, however the process ends: they go
+/// to a temporary file beside it, which then replaces it in one rename.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_then_rename(path, contents, |from, to| fs::rename(from, to))
+}
+
+/// [`write_atomic`], with the rename left to `rename`.
+fn write_then_rename(
+    path: &Path,
+    contents: &[u8],
+    rename: impl FnOnce(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    // On disk before it takes the old file's place.
+    file.sync_all()?;
+    drop(file);
+    rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// How often, and where, a running board is saved so that a crash loses
+/// little of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Autosave {
+    pub dir: PathBuf,
+    /// Save once the board is this many generations on from the last save...
+    pub generations: u64,
+    /// ...or this long after it, whichever comes first.
+    pub interval: Duration,
+}
+
+impl Autosave {
+    /// Saves into `dir` every 500 generations or 30 seconds.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            generations: 500,
+            interval: Duration::from_secs(30),
+        }
+    }
+
+    fn slot(&self, i: usize) -> PathBuf {
+        self.dir.join(format!("autosave-{i}.rle"))
+    }
+
+    /// The newest autosave that reads back, if there is one. A clean quit
+    /// leaves none behind, so any there is was left by a run that didn't
+    /// get to quit.
+    pub fn recoverable(&self) -> Option<Frame> {
+        let mut saves = (0..SLOTS)
+            .filter_map(|i| {
+                let path = self.slot(i);
+                let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+                // Saves are written whole, so they don't disagree with
+                // themselves.
+                let text = fs::read_to_string(&path).ok()?;
+                let (frame, _) = Frame::last_of_multi_rle(&text).ok()?;
+                Some((modified, frame))
+            })
+            .collect::<Vec<_>>();
+        // Saves made too close together to tell apart by time are told
+        // apart by how far along they are.
+        saves.sort_by_key(|(modified, frame)| (*modified, frame.generation));
+        saves.pop().map(|(_, frame)| frame)
+    }
+
+    /// Removes every autosave.
+    pub fn discard(&self) -> io::Result<()> {
+        for i in 0..SLOTS {
+            match fs::remove_file(self.slot(i)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Saves a board now and then as it runs, as an [`Autosave`] says, taking
+/// turns between the files so that one of them is whole even if a save is
+/// cut short.
+#[derive(Debug)]
+pub struct Autosaver {
+    config: Autosave,
+    /// The slot the next save goes to.
+    next: usize,
+    /// The generation saved last, and when; the start until the first save.
+    last: (u64, Instant),
+    /// The save being written, off the thread the board runs on.
+    worker: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Autosaver {
+    /// Starts counting towards the first save from `game` as it is.
+    pub fn new(config: Autosave, game: &GameOfLife) -> Self {
+        Self {
+            config,
+            next: 0,
+            last: (game.generation(), Instant::now()),
+            worker: None,
+        }
+    }
+
+    /// Starts saving `game` if a save is due and the last one is done,
+    /// returning what went wrong with the last one if anything did.
+    pub fn tick(&mut self, game: &GameOfLife, now: Instant) -> Option<String> {
+        let mut failed = None;
+        if self
+            .worker
+            .as_ref()
+            .is_some_and(|worker| worker.is_finished())
+        {
+            failed = match self.worker.take().map(JoinHandle::join) {
+                Some(Ok(Ok(()))) => None,
+                Some(Ok(Err(err))) => Some(format!("Autosave failed: {err}")),
+                _ => Some("Autosave failed".to_string()),
+            };
+        }
+        let (generation, at) = self.last;
+        let due = game.generation().abs_diff(generation) >= self.config.generations
+            || now.duration_since(at) >= self.config.interval;
+        if self.worker.is_some() || !due {
+            return failed;
+        }
+        self.last = (game.generation(), now);
+        // Copied now; encoded and written on the worker.
+        let frame = Frame::of(game);
+        let (dir, path) = (self.config.dir.clone(), self.config.slot(self.next));
+        self.next = (self.next + 1) % SLOTS;
+        self.worker = Some(std::thread::spawn(move || {
+            fs::create_dir_all(&dir)?;
+            write_atomic(&path, frame.to_rle().as_bytes())
+        }));
+        failed
+    }
+
+    /// Waits for the save under way, then removes every autosave: the run
+    /// ended cleanly, so there is nothing to recover.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        self.config.discard()
+    }
+}
+
+#[test]
+fn test_autosaves_take_turns_and_recover_the_newest() {
+    let config = Autosave {
+        generations: 2,
+        interval: Duration::from_secs(3600),
+        ..Autosave::new(crate::testing::scratch_dir("autosave"))
+    };
+    assert_eq!(config.recoverable(), None);
+    let mut game = GameOfLife::new((8, 8));
+    let mut saver = Autosaver::new(config.clone(), &game);
+    let now = Instant::now();
+    let wait = |saver: &mut Autosaver| {
+        if let Some(worker) = saver.worker.take() {
+            worker.join().unwrap().unwrap();
+        }
+    };
+    // Nothing is due until two generations on.
+    game.step();
+    assert_eq!(saver.tick(&game, now), None);
+    assert!(saver.worker.is_none());
+    for _ in 0..3 {
+        game.step();
+        game.step();
+        saver.tick(&game, now);
+        wait(&mut saver);
+    }
+    // Three saves in two files: the third replaced the first.
+    let entries = fs::read_dir(&config.dir).unwrap();
+    let mut files = entries
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, ["autosave-0.rle", "autosave-1.rle"]);
+    let recovered = config.recoverable().unwrap();
+    assert_eq!(recovered, Frame::of(&game));
+    assert_eq!(recovered.generation, 7);
+
+    // Time alone makes a save due too.
+    saver.tick(&game, now + Duration::from_secs(3600));
+    assert!(saver.worker.is_some());
+
+    // A clean finish leaves nothing to recover.
+    saver.finish().unwrap();
+    assert_eq!(config.recoverable(), None);
+    fs::remove_dir_all(&config.dir).unwrap();
+}
+
+#[test]
+fn test_interrupted_writes_leave_the_old_file() {
+    let dir = crate::testing::scratch_dir("atomic");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("autosave-0.rle");
+    write_atomic(&path, b"old").unwrap();
+    // Dying between writing the new contents and renaming them into place.
+    let died = || io::Error::other("killed");
+    assert!(write_then_rename(&path, b"new", |_, _| Err(died())).is_err());
+    assert_eq!(fs::read(&path).unwrap(), b"old");
+    assert!(!dir.join("autosave-0.rle.tmp").exists());
+    write_atomic(&path, b"new").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"new");
+
+    // What a kill mid-write leaves, half a temporary file and an unreadable
+    // save, is passed over for the save that reads back.
+    let config = Autosave::new(dir.clone());
+    let game = GameOfLife::new((5, 5));
+    fs::write(dir.join("autosave-1.rle"), game.to_rle()).unwrap();
+    fs::write(dir.join("autosave-1.rle.tmp"), "x = 5, y").unwrap();
+    assert!(config.recoverable().is_none());
+    // Saves from before metadata had a layout still read back.
+    let text = format!("#C generation 0\n{}", game.to_rle());
+    fs::write(dir.join("autosave-1.rle"), text).unwrap();
+    assert_eq!(config.recoverable(), Some(Frame::of(&game)));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,536 @@
+use std::{
+    fs,
+    io::{self, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread::JoinHandle,
+};
+
+use crate::{
+    raster::{render_bands, render_image, ImageOptions},
+    Frame, GameOfLife, GridView, Theme,
+};
+
+/// How many captures may wait on the disk; any more are dropped.
+const QUEUE: usize = 8;
+
+/// Which generations of a running board are saved as images, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub dir: PathBuf,
+    /// Save each generation that is a multiple of this.
+    pub every: u64,
+}
+
+impl Capture {
+    /// Saves every 100th generation into `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, every: 100 }
+    }
+}
+
+/// The file generation `generation` is captured to, numbered with at least
+/// six digits so that a directory of them lists in order.
+pub fn frame_name(generation: u64) -> String {
+    format!("gen_{generation:06}.png")
+}
+
+/// What came of a run's captures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureReport {
+    pub saved: u64,
+    /// Captures let go because the disk fell behind.
+    pub dropped: u64,
+    /// Captures that could not be written.
+    pub failed: u64,
+}
+
+impl CaptureReport {
+    /// One line saying how many frames went into `dir`, and how many didn't.
+    pub fn line(&self, dir: &Path) -> String {
+        let mut line = format!("captured {} frames in {}", self.saved, dir.display());
+        if self.dropped > 0 {
+            line += &format!(", dropped {} the disk couldn't keep up with", self.dropped);
+        }
+        if self.failed > 0 {
+            line += &format!(", {} could not be written", self.failed);
+        }
+        line
+    }
+}
+
+/// Saves a running board as a PNG every so often, as a [`Capture`] says. The
+/// images are encoded and written on a worker thread, so a slow disk never
+/// holds up stepping: captures it can't keep up with are dropped instead.
+#[derive(Debug)]
+pub struct Capturer {
+    config: Capture,
+    /// Snapshots on their way to the worker, until the capturer finishes.
+    queue: Option<SyncSender<Frame>>,
+    /// Why writes failed, as the worker finds out.
+    failures: Receiver<String>,
+    /// Counts the frames it saved.
+    worker: Option<JoinHandle<u64>>,
+    /// The generation captured last, so that it isn't captured twice.
+    last: Option<u64>,
+    dropped: u64,
+    failed: u64,
+}
+
+impl Capturer {
+    /// Starts the worker, making the directory the captures go in.
+    pub fn new(config: Capture) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let (queue, frames) = mpsc::sync_channel::<Frame>(QUEUE);
+        let (report, failures) = mpsc::channel();
+        let dir = config.dir.clone();
+        let worker = std::thread::spawn(move || {
+            let mut saved = 0;
+            let mut rgba = Vec::new();
+            for frame in frames {
+                let path = dir.join(frame_name(frame.generation));
+                match fs::write(&path, encode_frame(&frame, &mut rgba)) {
+                    Ok(()) => saved += 1,
+                    Err(err) => {
+                        let _ = report.send(format!("Could not write {}: {err}", path.display()));
+                    }
+                }
+            }
+            saved
+        });
+        Ok(Self {
+            config,
+            queue: Some(queue),
+            failures,
+            worker: Some(worker),
+            last: None,
+            dropped: 0,
+            failed: 0,
+        })
+    }
+
+    pub fn config(&self) -> &Capture {
+        &self.config
+    }
+
+    /// Hands a copy of `game` to the worker if its generation is one to
+    /// capture, returning what went wrong with an earlier capture if
+    /// anything did.
+    pub fn observe(&mut self, game: &GameOfLife) -> Option<String> {
+        let failed = self.failures.try_recv().ok();
+        self.failed += failed.is_some() as u64;
+        let generation = game.generation();
+        if generation % self.config.every.max(1) != 0 || self.last == Some(generation) {
+            return failed;
+        }
+        self.last = Some(generation);
+        let queued = self
+            .queue
+            .as_ref()
+            .map(|queue| queue.try_send(Frame::of(game)));
+        if !matches!(queued, Some(Ok(()))) {
+            self.dropped += 1;
+        }
+        failed
+    }
+
+    /// Waits for the captures already handed over to be written, and says
+    /// what came of them all.
+    pub fn finish(mut self) -> CaptureReport {
+        // The worker stops once the queue it drains is closed.
+        drop(self.queue.take());
+        let saved = self.worker.take().and_then(|worker| worker.join().ok());
+        CaptureReport {
+            saved: saved.unwrap_or(0),
+            dropped: self.dropped,
+            failed: self.failed + self.failures.try_iter().count() as u64,
+        }
+    }
+}
+
+/// `frame` as a PNG, one pixel per cell, white on black, drawn through
+/// `rgba`.
+fn encode_frame(frame: &Frame, rgba: &mut Vec<u8>) -> Vec<u8> {
+    let mut game = GameOfLife::new_empty(frame.size);
+    game.restore(frame);
+    render_image(&game, &ImageOptions::for_theme(Theme::Dark), rgba);
+    encode_png(game.size(), rgba)
+}
+
+/// Row-major RGBA pixels, `size` of them, as a PNG. The image data is
+/// stored rather than compressed, which keeps the encoder small; boards
+/// make small images anyway.
+pub fn encode_png((w, h): (usize, usize), rgba: &[u8]) -> Vec<u8> {
+    // Each row starts with the filter it was put through: none.
+    let mut raw = Vec::with_capacity((w * 4 + 1) * h);
+    for row in rgba.chunks_exact(w * 4).take(h) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    // A zlib stream of stored deflate blocks, of at most 64 KiB each.
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((w as u32).to_be_bytes());
+    header.extend((h as u32).to_be_bytes());
+    // 8 bits a channel of RGBA, and the only methods there are.
+    header.extend([8, 6, 0, 0, 0]);
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// A PNG written as its rows come, so that only the rows in hand are held
+/// rather than the whole image. Rows are stored uncompressed as in
+/// [`encode_png`], each batch given to [`push`](Self::push) in its own
+/// chunk.
+pub struct PngStream<W: Write> {
+    out: W,
+    width: usize,
+    /// Rows still to come.
+    rows_left: usize,
+    /// Whether the zlib stream has been started, in the first chunk.
+    started: bool,
+    adler: (u32, u32),
+    /// The chunk being put together, kept to reuse its allocation.
+    chunk: Vec<u8>,
+}
+
+impl<W: Write> PngStream<W> {
+    /// Starts an image of `size` pixels in `out`.
+    pub fn new(mut out: W, (w, h): (usize, usize)) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((w as u32).to_be_bytes());
+        header.extend((h as u32).to_be_bytes());
+        header.extend([8, 6, 0, 0, 0]);
+        let mut start = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut start, b"IHDR", &header);
+        out.write_all(&start)?;
+        Ok(Self {
+            out,
+            width: w,
+            rows_left: h,
+            started: false,
+            adler: (1, 0),
+            chunk: Vec::new(),
+        })
+    }
+
+    /// Writes whole rows of RGBA pixels, the next after those already
+    /// written. More rows than the image has, or part of one, is an error.
+    pub fn push(&mut self, rgba: &[u8]) -> io::Result<()> {
+        let stride = self.width * 4;
+        let rows = match stride {
+            0 => 0,
+            _ => rgba.len() / stride,
+        };
+        if rows * stride != rgba.len() || rows > self.rows_left {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "rows don't fit the image",
+            ));
+        }
+        self.rows_left -= rows;
+        let mut raw = Vec::with_capacity((stride + 1) * rows);
+        for row in rgba.chunks_exact(stride.max(1)).take(rows) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        self.adler = adler32_update(self.adler, &raw);
+        let mut idat = self.start();
+        for block in raw.chunks(0xffff) {
+            // Never final: an empty final block is left to finish.
+            idat.push(0);
+            let len = block.len() as u16;
+            idat.extend(len.to_le_bytes());
+            idat.extend((!len).to_le_bytes());
+            idat.extend_from_slice(block);
+        }
+        self.write_chunk(b"IDAT", &idat)
+    }
+
+    /// Ends the image, which must have had all its rows, and hands back
+    /// where it went.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.rows_left > 0 {
+            let missing = format!("the image is {} rows short", self.rows_left);
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, missing));
+        }
+        let (a, b) = self.adler;
+        let mut end = self.start();
+        end.extend([1, 0, 0, 0xff, 0xff]);
+        end.extend(((b << 16) | a).to_be_bytes());
+        self.write_chunk(b"IDAT", &end)?;
+        self.write_chunk(b"IEND", &[])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// The start of an IDAT chunk's data: the zlib header, if it is the
+    /// first.
+    fn start(&mut self) -> Vec<u8> {
+        match std::mem::replace(&mut self.started, true) {
+            true => Vec::new(),
+            false => vec![0x78, 0x01],
+        }
+    }
+
+    fn write_chunk(&mut self, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+        self.chunk.clear();
+        chunk(&mut self.chunk, kind, data);
+        self.out.write_all(&self.chunk)
+    }
+}
+
+/// Writes `grid` to `path` as a PNG drawn with `opts`, `cells` rows of cells
+/// at a time through [`render_bands`], so a huge board never needs its
+/// whole image in memory. After each band `progress` is told the percentage
+/// written; if it breaks, the export stops, the partial file is removed and
+/// an [`Interrupted`](io::ErrorKind::Interrupted) error returned. A file
+/// that fails part way is removed as well.
+pub fn export_png(
+    path: &Path,
+    grid: &dyn GridView,
+    opts: &ImageOptions,
+    cells: usize,
+    mut progress: impl FnMut(u8) -> ControlFlow<()>,
+) -> io::Result<()> {
+    let file = io::BufWriter::new(fs::File::create(path)?);
+    let size = opts.image_size(grid);
+    let written = PngStream::new(file, size).and_then(|mut png| {
+        let flow = render_bands(grid, opts, cells, |band| {
+            if let Err(err) = png.push(band.rgba) {
+                return ControlFlow::Break(err);
+            }
+            let done = (band.top + band.rgba.len() / (size.0 * 4).max(1)) * 100;
+            match progress((done / size.1.max(1)) as u8) {
+                ControlFlow::Continue(()) => ControlFlow::Continue(()),
+                ControlFlow::Break(()) => {
+                    ControlFlow::Break(io::Error::new(io::ErrorKind::Interrupted, "cancelled"))
+                }
+            }
+        });
+        match flow {
+            ControlFlow::Continue(()) => png.finish().map(drop),
+            ControlFlow::Break(err) => Err(err),
+        }
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    written
+}
+
+/// Appends a PNG chunk: its length, kind, data and checksum.
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = adler32_update((1, 0), data);
+    (b << 16) | a
+}
+
+/// The two sums of an Adler-32 checksum, carried on over more `data`.
+fn adler32_update((mut a, mut b): (u32, u32), data: &[u8]) -> (u32, u32) {
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (a, b)
+}
+
+#[test]
+fn test_png_encoding() {
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    let png = encode_png((2, 1), &[255, 0, 0, 255, 0, 0, 255, 255]);
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x02\0\0\0\x01\x08\x06"));
+    assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    // A row of a filter byte and two pixels, stored in one final block.
+    let idat = &png[33..];
+    assert_eq!(&idat[4..8], b"IDAT");
+    assert_eq!(&idat[8..15], [0x78, 0x01, 1, 9, 0, !9, !0]);
+
+    // Big images are split into blocks, only the last marked final.
+    let rgba = vec![0; 200 * 100 * 4];
+    let png = encode_png((200, 100), &rgba);
+    let length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+    assert_eq!(length, 2 + 2 * 5 + 100 * (200 * 4 + 1) + 4);
+    assert_eq!(png[41 + 2], 0);
+}
+
+#[test]
+fn test_png_streams_band_by_band() {
+    let mut game = GameOfLife::new_empty((40, 30));
+    game.fill(0.4, 7);
+    let opts = ImageOptions {
+        scale: 4,
+        ..ImageOptions::for_theme(Theme::Light)
+    };
+    let mut rgba = Vec::new();
+    render_image(&game, &opts, &mut rgba);
+    let whole = encode_png((160, 120), &rgba);
+
+    let mut png = PngStream::new(Vec::new(), (160, 120)).unwrap();
+    let _ = render_bands::<()>(&game, &opts, 4, |band| {
+        png.push(band.rgba).unwrap();
+        ControlFlow::Continue(())
+    });
+    let streamed = png.finish().unwrap();
+    assert_eq!(streamed[..33], whole[..33]);
+    assert!(streamed.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    let ((raw, biggest), (expected, _)) = (
+        crate::testing::stored_rows(&streamed),
+        crate::testing::stored_rows(&whole),
+    );
+    assert_eq!(raw, expected);
+    // No chunk holds more than a band of rows and the blocks around them.
+    let band: usize = 16 * (160 * 4 + 1);
+    assert!(biggest <= band + 2 + 5 * band.div_ceil(0xffff), "{biggest}");
+
+    let mut png = PngStream::new(Vec::new(), (2, 2)).unwrap();
+    assert!(png.push(&[0; 7]).is_err());
+    assert!(png.push(&[0; 24]).is_err());
+    png.push(&[0; 8]).unwrap();
+    assert_eq!(
+        png.finish().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn test_export_png_reports_progress_and_cancels_cleanly() {
+    let path = std::env::temp_dir().join(format!("gol-export-{}.png", std::process::id()));
+    let mut game = GameOfLife::new_empty((10, 8));
+    game.fill(0.5, 2);
+    let opts = ImageOptions::for_theme(Theme::Dark);
+    let mut seen = Vec::new();
+    export_png(&path, &game, &opts, 3, |percent| {
+        seen.push(percent);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(seen, [37, 75, 100]);
+    let mut rgba = Vec::new();
+    render_image(&game, &opts, &mut rgba);
+    let expected = crate::testing::stored_rows(&encode_png((10, 8), &rgba)).0;
+    assert_eq!(
+        crate::testing::stored_rows(&fs::read(&path).unwrap()).0,
+        expected
+    );
+
+    // Cancelling part way leaves no file behind.
+    let err = export_png(&path, &game, &opts, 3, |percent| match percent {
+        100 => ControlFlow::Continue(()),
+        _ => ControlFlow::Break(()),
+    })
+    .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_captures_follow_the_cadence() {
+    use crate::GridPos;
+
+    assert_eq!(frame_name(0), "gen_000000.png");
+    assert_eq!(frame_name(50), "gen_000050.png");
+    assert_eq!(frame_name(1_234_567), "gen_1234567.png");
+
+    let dir = std::env::temp_dir().join(format!("gol-capture-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let config = Capture {
+        every: 2,
+        ..Capture::new(dir.clone())
+    };
+    let mut capturer = Capturer::new(config).unwrap();
+    let mut game = GameOfLife::new_empty((6, 6));
+    game.insert_pattern(&crate::Pattern::named("glider").unwrap(), GridPos(1, 1));
+    let outcome = crate::run_until_settled_observing(
+        &mut game,
+        &mut crate::CycleDetector::new(4),
+        5,
+        |game| assert_eq!(capturer.observe(game), None),
+    );
+    assert_eq!(outcome, None);
+    // A generation seen again isn't captured again.
+    capturer.observe(&game);
+    let report = capturer.finish();
+    assert_eq!(
+        report,
+        CaptureReport {
+            saved: 3,
+            dropped: 0,
+            failed: 0
+        }
+    );
+    let mut files = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(
+        files,
+        ["gen_000000.png", "gen_000002.png", "gen_000004.png"]
+    );
+    let png = fs::read(dir.join("gen_000004.png")).unwrap();
+    assert_eq!(&png[16..24], [0, 0, 0, 6, 0, 0, 0, 6]);
+    fs::remove_dir_all(&dir).unwrap();
+
+    // With the worker gone quiet, captures past the queue's room are dropped.
+    let (queue, _frames) = mpsc::sync_channel(1);
+    let (_report, failures) = mpsc::channel();
+    let mut stalled = Capturer {
+        config: Capture {
+            every: 1,
+            ..Capture::new(dir.clone())
+        },
+        queue: Some(queue),
+        failures,
+        worker: None,
+        last: None,
+        dropped: 0,
+        failed: 0,
+    };
+    for _ in 0..3 {
+        stalled.observe(&game);
+        game.step();
+    }
+    let report = stalled.finish();
+    assert_eq!((report.saved, report.dropped), (0, 2));
+    assert!(report
+        .line(&dir)
+        .ends_with(", dropped 2 the disk couldn't keep up with"));
+}
@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{GameOfLife, GridPos, Rect, Rule};
+
+/// Small objects recognized by [`GameOfLife::census`], one phase each.
+const KNOWN: &[(&str, &[&str])] = &[
+    ("block", &["##", "##"]),
+    ("blinker", &["###"]),
+    ("beehive", &[".##.", "#..#", ".##."]),
+    ("loaf", &[".##.", "#..#", ".#.#", "..#."]),
+    ("boat", &["##.", "#.#", ".#."]),
+    ("tub", &[".#.", "#.#", ".#."]),
+    ("glider", &[".#.", "..#", "###"]),
+];
+
+/// The longest period among [`KNOWN`] objects; every phase up to it is
+/// registered so oscillators and gliders match in any phase.
+const MAX_PERIOD: usize = 4;
+
+/// The longest period checked when looking for spaceships.
+const MAX_SHIP_PERIOD: usize = 8;
+
+/// Groups larger than this are not checked for being spaceships.
+const MAX_SHIP_CELLS: usize = 64;
+
+type Shape = Vec<(i32, i32)>;
+
+/// Counts of the known small objects on a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Census {
+    /// How many of each known object were found, in a fixed order.
+    pub objects: Vec<(&'static str, usize)>,
+    /// Bounding boxes of the groups of cells that matched no known object.
+    pub unidentified: Vec<Rect>,
+}
+
+impl Census {
+    pub fn count(&self, name: &str) -> usize {
+        self.objects
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(0, |&(_, c)| c)
+    }
+}
+
+/// A group of cells that reappears translated after a few generations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spaceship {
+    pub bounds: Rect,
+    /// Generations it takes to reappear.
+    pub period: usize,
+    /// How far it has moved when it reappears.
+    pub displacement: (i32, i32),
+}
+
+impl Spaceship {
+    /// The speed and direction in the usual notation, e.g. `c/4 diag`.
+    pub fn velocity_label(&self) -> String {
+        let (dx, dy) = (self.displacement.0.abs(), self.displacement.1.abs());
+        let distance = dx.max(dy) as usize;
+        let divisor = gcd(distance, self.period);
+        let speed = match (distance / divisor, self.period / divisor) {
+            (1, 1) => "c".to_string(),
+            (1, p) => format!("c/{p}"),
+            (d, 1) => format!("{d}c"),
+            (d, p) => format!("{d}c/{p}"),
+        };
+        let direction = if dx == 0 || dy == 0 {
+            "orth"
+        } else if dx == dy {
+            "diag"
+        } else {
+            "oblique"
+        };
+        format!("{speed} {direction}")
+    }
+}
+
+impl GameOfLife {
+    /// Finds the groups of cells that, simulated on their own under the
+    /// board's rule, reappear translated within a few generations. The board
+    /// itself is not modified.
+    pub fn spaceships(&self) -> Vec<Spaceship> {
+        let rule = self.rule();
+        self.components(2 * rule.range())
+            .into_iter()
+            .filter(|component| component.len() <= MAX_SHIP_CELLS)
+            .filter_map(|component| {
+                let (period, displacement) = recurrence(&component, MAX_SHIP_PERIOD, &rule)?;
+                if displacement == (0, 0) {
+                    return None;
+                }
+                Some(Spaceship {
+                    bounds: bounds(&component),
+                    period,
+                    displacement,
+                })
+            })
+            .collect()
+    }
+
+    /// Takes a census of the small objects on the board.
+    ///
+    /// Live cells are grouped into objects when they are within two cells of
+    /// each other, so the phases of an oscillator stay one group. Each group is
+    /// compared against the built-in dictionary regardless of position,
+    /// rotation, reflection and phase. Objects that sit too close together
+    /// merge into one group and are reported as unidentified.
+    pub fn census(&self) -> Census {
+        let known = dictionary();
+        let mut objects = KNOWN.iter().map(|&(name, _)| (name, 0)).collect::<Vec<_>>();
+        let mut unidentified = Vec::new();
+
+        for component in self.components(2) {
+            match known.get(&canonical(&component)) {
+                Some(&i) => objects[i].1 += 1,
+                None => unidentified.push(bounds(&component)),
+            }
+        }
+
+        Census {
+            objects,
+            unidentified,
+        }
+    }
+
+    /// Groups of live cells where every cell is within `reach` cells of
+    /// another cell of the group; two is a one-cell halo on each side.
+    fn components(&self, reach: usize) -> Vec<Shape> {
+        let (w, h) = self.size();
+        let mut seen = vec![false; w * h];
+        let mut components = Vec::new();
+        for start in 0..self.cell_count() {
+            if seen[start] || !self.surface()[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut queue = VecDeque::from([self.coords(start)]);
+            while let Some(GridPos(x, y)) = queue.pop_front() {
+                component.push((x as i32, y as i32));
+                for j in y.saturating_sub(reach)..(y + reach + 1).min(h) {
+                    for i in x.saturating_sub(reach)..(x + reach + 1).min(w) {
+                        let index = self.index(GridPos(i, j));
+                        if !seen[index] && self.get(GridPos(i, j)) {
+                            seen[index] = true;
+                            queue.push_back(GridPos(i, j));
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+}
+
+/// The bounding box of a non-empty shape with non-negative coordinates.
+fn bounds(shape: &[(i32, i32)]) -> Rect {
+    let x0 = shape.iter().map(|c| c.0).min().unwrap_or(0);
+    let y0 = shape.iter().map(|c| c.1).min().unwrap_or(0);
+    let x1 = shape.iter().map(|c| c.0).max().unwrap_or(0);
+    let y1 = shape.iter().map(|c| c.1).max().unwrap_or(0);
+    Rect {
+        x: x0 as usize,
+        y: y0 as usize,
+        width: (x1 - x0 + 1) as usize,
+        height: (y1 - y0 + 1) as usize,
+    }
+}
+
+/// The first generation, up to `max_period`, at which `shape` on an unbounded
+/// plane under `rule` reappears exactly (same orientation), and how far it
+/// moved.
+pub(crate) fn recurrence(
+    shape: &[(i32, i32)],
+    max_period: usize,
+    rule: &Rule,
+) -> Option<(usize, (i32, i32))> {
+    let origin = |cells: &[(i32, i32)]| {
+        let x0 = cells.iter().map(|c| c.0).min()?;
+        let y0 = cells.iter().map(|c| c.1).min()?;
+        let mut normalized = cells
+            .iter()
+            .map(|&(x, y)| (x - x0, y - y0))
+            .collect::<Shape>();
+        normalized.sort_unstable();
+        Some(((x0, y0), normalized))
+    };
+    let (start, initial) = origin(shape)?;
+    let mut cells = shape.to_vec();
+    for period in 1..=max_period {
+        cells = step_unbounded(&cells, rule);
+        let (at, normalized) = origin(&cells)?;
+        if normalized == initial {
+            return Some((period, (at.0 - start.0, at.1 - start.1)));
+        }
+    }
+    None
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Maps the canonical form of every phase of every known object to its index
+/// in [`KNOWN`].
+fn dictionary() -> HashMap<Shape, usize> {
+    let conway = Rule::default();
+    let mut known = HashMap::new();
+    for (i, &(_, rows)) in KNOWN.iter().enumerate() {
+        let mut shape = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect::<Shape>();
+        for _ in 0..MAX_PERIOD {
+            known.insert(canonical(&shape), i);
+            shape = step_unbounded(&shape, &conway);
+        }
+    }
+    known
+}
+
+/// The smallest of the eight rotations and reflections of `shape`, translated
+/// to the origin and sorted.
+fn canonical(shape: &[(i32, i32)]) -> Shape {
+    (0..8)
+        .map(|symmetry| {
+            let mut cells = shape
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = if symmetry & 4 != 0 { (y, x) } else { (x, y) };
+                    let x = if symmetry & 1 != 0 { -x } else { x };
+                    let y = if symmetry & 2 != 0 { -y } else { y };
+                    (x, y)
+                })
+                .collect::<Shape>();
+            let x0 = cells.iter().map(|c| c.0).min().unwrap_or(0);
+            let y0 = cells.iter().map(|c| c.1).min().unwrap_or(0);
+            for cell in &mut cells {
+                *cell = (cell.0 - x0, cell.1 - y0);
+            }
+            cells.sort_unstable();
+            cells
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// One generation of `shape` under `rule` on an unbounded plane. Only cells
+/// next to a live one are considered, so rules with birth on 0 don't fill the
+/// plane.
+fn step_unbounded(shape: &[(i32, i32)], rule: &Rule) -> Shape {
+    let live = shape.iter().copied().collect::<HashSet<_>>();
+    let mut counts = live
+        .iter()
+        .map(|&cell| (cell, 0))
+        .collect::<HashMap<_, usize>>();
+    for &(x, y) in shape {
+        for (dx, dy) in rule.neighbor_offsets() {
+            *counts.entry((x + dx as i32, y + dy as i32)).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|&(cell, n)| rule.next(live.contains(&cell), n))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+#[test]
+fn test_census_mixed_board() {
+    use crate::Pattern;
+
+    let mut game = GameOfLife::new_empty((30, 20));
+    game.insert_pattern(&Pattern::from_ascii("##\n##"), GridPos(1, 1));
+    game.insert_pattern(&Pattern::from_ascii("##\n##"), GridPos(1, 6));
+    // A vertical blinker, i.e. the other phase from the dictionary's.
+    game.insert_pattern(&Pattern::from_ascii("#\n#\n#"), GridPos(8, 1));
+    game.insert_pattern(&Pattern::from_ascii(".##.\n#..#\n.##."), GridPos(14, 1));
+    // A glider flying north-west, rotated from the dictionary's.
+    game.insert_pattern(&Pattern::from_ascii("##.\n#.#\n#.."), GridPos(22, 1));
+    game.insert_pattern(&Pattern::from_ascii(".#.\n#.#\n##."), GridPos(8, 8));
+    // An R-pentomino is not in the dictionary.
+    game.insert_pattern(&Pattern::from_ascii(".##\n##.\n.#."), GridPos(20, 12));
+
+    let census = game.census();
+    assert_eq!(census.count("block"), 2);
+    assert_eq!(census.count("blinker"), 1);
+    assert_eq!(census.count("beehive"), 1);
+    assert_eq!(census.count("glider"), 1);
+    assert_eq!(census.count("boat"), 1);
+    assert_eq!(census.count("tub"), 0);
+    assert_eq!(
+        census.unidentified,
+        vec![Rect {
+            x: 20,
+            y: 12,
+            width: 3,
+            height: 3
+        }]
+    );
+}
+
+#[test]
+fn test_spaceships_velocity() {
+    use crate::Pattern;
+
+    let mut game = GameOfLife::new_empty((40, 20));
+    game.insert_pattern(&Pattern::from_ascii(".#.\n..#\n###"), GridPos(2, 2));
+    // Lightweight spaceship.
+    game.insert_pattern(
+        &Pattern::from_ascii(".#..#\n#....\n#...#\n####."),
+        GridPos(20, 10),
+    );
+    game.insert_pattern(&Pattern::from_ascii("###"), GridPos(30, 2));
+
+    let ships = game.spaceships();
+    assert_eq!(ships.len(), 2);
+    assert_eq!(ships[0].velocity_label(), "c/4 diag");
+    assert_eq!(ships[0].displacement, (1, 1));
+    assert_eq!(ships[1].velocity_label(), "c/2 orth");
+    assert_eq!(
+        ships[1].bounds,
+        Rect {
+            x: 20,
+            y: 10,
+            width: 5,
+            height: 4
+        }
+    );
+}
+
+#[test]
+fn test_spaceships_follow_the_board_rule() {
+    use crate::Pattern;
+
+    let mut game = GameOfLife::new_empty((20, 20));
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(2, 2));
+    assert_eq!(game.spaceships().len(), 1);
+
+    // HighLife keeps the glider; Life without Death only ever grows it.
+    game.set_rule(Rule::parse("B36/S23").unwrap());
+    assert_eq!(game.spaceships()[0].velocity_label(), "c/4 diag");
+    game.set_rule(Rule::parse("B3/S012345678").unwrap());
+    assert!(game.spaceships().is_empty());
+}
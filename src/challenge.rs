@@ -0,0 +1,470 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    autosave::write_atomic, territory::PlaceError, CycleDetector, GameOfLife, GridPos, Pattern,
+};
+
+/// The longest period a challenge run recognizes as settled.
+const MAX_PERIOD: usize = 30;
+
+/// How many high scores are kept for each budget and way of scoring.
+pub const KEPT: usize = 10;
+
+/// What a challenge run scores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scoring {
+    /// Generations until the board dies out or settles.
+    #[default]
+    Lifetime,
+    /// The most live cells on the board at once.
+    Peak,
+}
+
+impl Scoring {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scoring::Lifetime => "lifetime",
+            Scoring::Peak => "peak",
+        }
+    }
+
+    /// What a score of this kind counts.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Scoring::Lifetime => "generations",
+            Scoring::Peak => "cells at the peak",
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lifetime" | "generations" => Ok(Scoring::Lifetime),
+            "peak" | "population" => Ok(Scoring::Peak),
+            _ => Err(format!("scoring must be lifetime or peak, got {s:?}")),
+        }
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Rules of a challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChallengeConfig {
+    /// Live cells that may be placed before the run.
+    pub budget: usize,
+    pub scoring: Scoring,
+    /// Generations a run is cut off at, however long it would have lasted.
+    pub generations: u64,
+}
+
+impl Default for ChallengeConfig {
+    fn default() -> Self {
+        Self {
+            budget: 100,
+            scoring: Scoring::default(),
+            generations: 10_000,
+        }
+    }
+}
+
+/// Where a challenge is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Cells are being placed; the board is stopped.
+    Placing,
+    /// The board runs until it dies out, settles or is cut off.
+    Running,
+    /// The run is over with this score.
+    Over(u64),
+}
+
+/// A one-player challenge: place a budget of live cells on an empty board,
+/// then let it run, scored by how long it lasts or how big it gets.
+pub struct Challenge {
+    game: GameOfLife,
+    config: ChallengeConfig,
+    stage: Stage,
+    /// The board as placed, kept from the start of the run for a retry.
+    layout: Option<GameOfLife>,
+    detector: CycleDetector,
+    start: u64,
+    peak: usize,
+}
+
+impl Challenge {
+    /// Starts a challenge on a blank board.
+    pub fn new(size: (usize, usize), config: ChallengeConfig) -> Self {
+        let game = GameOfLife::new_empty(size);
+        Self {
+            start: game.generation(),
+            game,
+            config,
+            stage: Stage::Placing,
+            layout: None,
+            detector: CycleDetector::new(MAX_PERIOD),
+            peak: 0,
+        }
+    }
+
+    pub fn game(&self) -> &GameOfLife {
+        &self.game
+    }
+
+    pub fn config(&self) -> ChallengeConfig {
+        self.config
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// The board as it was placed, once a run has started.
+    pub fn layout(&self) -> Option<&GameOfLife> {
+        self.layout.as_ref()
+    }
+
+    /// Cells that may still be placed. Erasing a cell gives it back.
+    pub fn remaining(&self) -> usize {
+        match self.stage {
+            Stage::Placing => self.config.budget.saturating_sub(self.game.population()),
+            _ => 0,
+        }
+    }
+
+    /// Generations run so far.
+    pub fn generations(&self) -> u64 {
+        self.game.generation() - self.start
+    }
+
+    /// The most live cells seen so far in the run.
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Places a live cell.
+    pub fn place(&mut self, pos: GridPos) -> Result<(), PlaceError> {
+        self.check(pos)?;
+        if self.game.get(pos) {
+            return Err(PlaceError::Occupied);
+        }
+        if self.remaining() == 0 {
+            return Err(PlaceError::BudgetExhausted);
+        }
+        self.game.set(pos, true);
+        Ok(())
+    }
+
+    /// Erases a placed cell, giving it back to the budget.
+    pub fn erase(&mut self, pos: GridPos) -> Result<(), PlaceError> {
+        self.check(pos)?;
+        self.game.set(pos, false);
+        Ok(())
+    }
+
+    /// Stamps `pattern` with its top-left at `origin`, clipped to the board,
+    /// returning how many cells it added. Either all of them fit the budget
+    /// or none are placed.
+    pub fn stamp(&mut self, pattern: &Pattern, origin: GridPos) -> Result<usize, PlaceError> {
+        if self.stage != Stage::Placing {
+            return Err(PlaceError::NotPlacing);
+        }
+        let (w, h) = self.game.size();
+        let cells = pattern
+            .cells()
+            .iter()
+            .map(|&(x, y)| (origin.0 + x, origin.1 + y))
+            .filter(|&(x, y)| x < w && y < h && !self.game.get(GridPos(x, y)))
+            .collect::<Vec<_>>();
+        if cells.len() > self.remaining() {
+            return Err(PlaceError::BudgetExhausted);
+        }
+        for &(x, y) in &cells {
+            self.game.set(GridPos(x, y), true);
+        }
+        Ok(cells.len())
+    }
+
+    /// Spends what is left of the budget on dead cells picked at random from
+    /// `seed`, returning how many it placed.
+    pub fn fill(&mut self, seed: u64) -> Result<usize, PlaceError> {
+        if self.stage != Stage::Placing {
+            return Err(PlaceError::NotPlacing);
+        }
+        let mut dead = (0..self.game.cell_count())
+            .filter(|&i| !self.game.surface()[i])
+            .collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (picked, _) = dead.partial_shuffle(&mut rng, self.remaining());
+        for &i in picked.iter() {
+            let pos = self.game.coords(i);
+            self.game.set(pos, true);
+        }
+        Ok(picked.len())
+    }
+
+    /// Erases every placed cell.
+    pub fn clear(&mut self) {
+        if self.stage == Stage::Placing {
+            self.game = GameOfLife::new_empty(self.game.size());
+            self.start = self.game.generation();
+        }
+    }
+
+    /// Starts the run, after which nothing more can be placed.
+    pub fn go(&mut self) {
+        if self.stage == Stage::Placing {
+            self.layout = Some(self.game.clone());
+            self.peak = self.game.population();
+            self.detector.reset();
+            self.stage = Stage::Running;
+            self.settle();
+        }
+    }
+
+    /// Advances one generation while running, ending the run once the board
+    /// dies out or settles, or at the generation limit.
+    pub fn tick(&mut self) {
+        if self.stage != Stage::Running {
+            return;
+        }
+        self.game.step();
+        self.peak = self.peak.max(self.game.population());
+        self.settle();
+    }
+
+    /// Back to placing, with the layout of the last run or, when `fresh`,
+    /// on a blank board.
+    pub fn retry(&mut self, fresh: bool) {
+        let size = self.game.size();
+        self.game = match self.layout.take() {
+            Some(layout) if !fresh => layout,
+            _ => GameOfLife::new_empty(size),
+        };
+        self.start = self.game.generation();
+        self.peak = 0;
+        self.stage = Stage::Placing;
+    }
+
+    /// Ends the run if the board has died out or settled, or reached the
+    /// limit.
+    fn settle(&mut self) {
+        let settled = self.detector.observe(&self.game).is_some();
+        if settled || self.generations() >= self.config.generations {
+            self.stage = Stage::Over(match self.config.scoring {
+                Scoring::Lifetime => self.generations(),
+                Scoring::Peak => self.peak as u64,
+            });
+        }
+    }
+
+    fn check(&self, pos: GridPos) -> Result<(), PlaceError> {
+        if self.stage != Stage::Placing {
+            return Err(PlaceError::NotPlacing);
+        }
+        let (GridPos(x, y), (w, h)) = (pos, self.game.size());
+        if x >= w || y >= h {
+            return Err(PlaceError::OutOfBounds);
+        }
+        Ok(())
+    }
+}
+
+/// A score kept in [`HighScores`], and the layout that got it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub score: u64,
+    pub path: PathBuf,
+}
+
+/// The best challenge runs, kept as RLE files of their layouts so that they
+/// can be shared: `challenge-100-lifetime-00001234-1700000000.rle` scored
+/// 1234 on a budget of 100, and was saved at that second. Runs saved in the
+/// same second have a count after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScores {
+    dir: PathBuf,
+}
+
+impl HighScores {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The scores kept for `config`'s budget and scoring, best first.
+    pub fn list(&self, config: ChallengeConfig) -> Vec<HighScore> {
+        let prefix = format!("challenge-{}-{}-", config.budget, config.scoring);
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut scores = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_name()?.to_str()?;
+                let rest = name.strip_prefix(&prefix)?.strip_suffix(".rle")?;
+                let (score, _) = rest.split_once('-')?;
+                Some(HighScore {
+                    score: score.parse().ok()?,
+                    path,
+                })
+            })
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
+        scores
+    }
+
+    /// Keeps a finished run's layout if its score makes the table, dropping
+    /// what then falls off the end. Returns where it went, or `None` if it
+    /// didn't make it, or the challenge isn't over.
+    pub fn record(&self, challenge: &Challenge) -> io::Result<Option<PathBuf>> {
+        let (Stage::Over(score), Some(layout)) = (challenge.stage(), challenge.layout()) else {
+            return Ok(None);
+        };
+        let config = challenge.config();
+        let mut scores = self.list(config);
+        if scores.len() >= KEPT && scores[KEPT - 1].score >= score {
+            return Ok(None);
+        }
+        fs::create_dir_all(&self.dir)?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let name = format!(
+            "challenge-{}-{}-{score:08}-{stamp}",
+            config.budget, config.scoring
+        );
+        // Runs recorded within the same second are told apart by a count.
+        let path = (0..)
+            .map(|n| match n {
+                0 => self.dir.join(format!("{name}.rle")),
+                n => self.dir.join(format!("{name}-{n}.rle")),
+            })
+            .find(|path| !path.exists())
+            .expect("some name is free");
+        let text = format!(
+            "#C A {} challenge layout, scoring {score} {}\n{}",
+            config.scoring,
+            config.scoring.unit(),
+            layout.to_rle()
+        );
+        write_atomic(&path, text.as_bytes())?;
+        scores.push(HighScore {
+            score,
+            path: path.clone(),
+        });
+        scores.sort_by_key(|kept| std::cmp::Reverse(kept.score));
+        for dropped in scores.iter().skip(KEPT) {
+            let _ = fs::remove_file(&dropped.path);
+        }
+        Ok(Some(path))
+    }
+}
+
+#[test]
+fn test_challenge_budget_and_lifetime() {
+    let config = ChallengeConfig {
+        budget: 4,
+        ..ChallengeConfig::default()
+    };
+    let mut challenge = Challenge::new((10, 10), config);
+    challenge.place(GridPos(1, 1)).unwrap();
+    assert_eq!(challenge.place(GridPos(1, 1)), Err(PlaceError::Occupied));
+    assert_eq!(
+        challenge.place(GridPos(10, 1)),
+        Err(PlaceError::OutOfBounds)
+    );
+    // A blinker fits the three cells left; a block no longer would.
+    let blinker = Pattern::named("blinker").unwrap();
+    assert_eq!(challenge.stamp(&blinker, GridPos(4, 5)), Ok(3));
+    assert_eq!(challenge.remaining(), 0);
+    assert_eq!(
+        challenge.place(GridPos(0, 0)),
+        Err(PlaceError::BudgetExhausted)
+    );
+    // Erasing gives a cell back, and a fill spends it.
+    challenge.erase(GridPos(1, 1)).unwrap();
+    let block = Pattern::named("block").unwrap();
+    assert_eq!(
+        challenge.stamp(&block, GridPos(0, 0)),
+        Err(PlaceError::BudgetExhausted)
+    );
+    assert_eq!(challenge.fill(7), Ok(1));
+    challenge.clear();
+    assert_eq!(challenge.stamp(&blinker, GridPos(4, 5)), Ok(3));
+
+    challenge.go();
+    assert_eq!(challenge.stage(), Stage::Running);
+    assert_eq!(challenge.place(GridPos(0, 0)), Err(PlaceError::NotPlacing));
+    // The blinker is seen repeating two generations in.
+    while challenge.stage() == Stage::Running {
+        challenge.tick();
+    }
+    assert_eq!(challenge.stage(), Stage::Over(2));
+    assert_eq!(challenge.peak(), 3);
+
+    // A retry starts over from the same three cells.
+    challenge.retry(false);
+    assert_eq!(
+        (challenge.stage(), challenge.game().population()),
+        (Stage::Placing, 3)
+    );
+    assert_eq!(challenge.remaining(), 1);
+    challenge.retry(true);
+    assert_eq!(challenge.game().population(), 0);
+}
+
+#[test]
+fn test_high_scores_keep_the_best_layouts() {
+    let dir = std::env::temp_dir().join(format!("gol-challenge-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let scores = HighScores::new(dir.clone());
+    let config = ChallengeConfig {
+        budget: 3,
+        scoring: Scoring::Peak,
+        generations: 5,
+    };
+    assert!(scores.list(config).is_empty());
+    let mut challenge = Challenge::new((8, 8), config);
+    assert_eq!(scores.record(&challenge).unwrap(), None);
+    challenge
+        .stamp(&Pattern::named("blinker").unwrap(), GridPos(2, 2))
+        .unwrap();
+    challenge.go();
+    while challenge.stage() == Stage::Running {
+        challenge.tick();
+    }
+    for _ in 0..KEPT {
+        assert!(scores.record(&challenge).unwrap().is_some());
+    }
+    let kept = scores.list(config);
+    assert_eq!(kept.len(), KEPT);
+    assert!(kept.iter().all(|kept| kept.score == 3));
+    // A table full of threes has no room for another three.
+    assert_eq!(scores.record(&challenge).unwrap(), None);
+    // Other budgets and scorings keep tables of their own.
+    assert!(scores.list(ChallengeConfig::default()).is_empty());
+    let text = fs::read_to_string(&kept[0].path).unwrap();
+    assert!(text.starts_with("#C A peak challenge layout, scoring 3 cells at the peak\n"));
+    let (layout, _) = crate::rle::decode(&text).unwrap();
+    assert_eq!(layout.cells().len(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+/// The most points a [`Series`] keeps; past it, every other one is let go.
+const MAX_POINTS: usize = 2048;
+
+/// The size of the chart [`svg`] draws, and the room left around its plot
+/// for the labels.
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 320.0;
+const LEFT: f64 = 60.0;
+const RIGHT: f64 = 20.0;
+const TOP: f64 = 20.0;
+const BOTTOM: f64 = 40.0;
+
+/// A population over a run, however long: evenly spaced samples of it, no
+/// more than [`MAX_POINTS`], ever further apart as the run goes on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    /// `(generation, population)`, in the order they were taken.
+    points: Vec<(u64, usize)>,
+    /// Samples offered since the last one kept.
+    skipped: u64,
+    /// Keep one sample in this many.
+    every: u64,
+}
+
+impl Default for Series {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            skipped: 0,
+            every: 1,
+        }
+    }
+}
+
+impl Series {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offers the population of `generation`, which is kept if it falls on
+    /// the spacing.
+    pub fn push(&mut self, generation: u64, population: usize) {
+        if self.skipped + 1 < self.every && !self.points.is_empty() {
+            self.skipped += 1;
+            return;
+        }
+        self.skipped = 0;
+        self.points.push((generation, population));
+        if self.points.len() > MAX_POINTS {
+            let mut i = 0;
+            self.points.retain(|_| {
+                i += 1;
+                i % 2 == 1
+            });
+            self.every *= 2;
+        }
+    }
+
+    pub fn points(&self) -> &[(u64, usize)] {
+        &self.points
+    }
+}
+
+/// A line chart of `points`, `(generation, population)`, as an SVG
+/// document: the population up the side, from none to the most there
+/// were, against the generation along the bottom.
+pub fn svg(points: &[(u64, usize)]) -> String {
+    let first = points
+        .iter()
+        .map(|&(generation, _)| generation)
+        .min()
+        .unwrap_or(0);
+    let last = points
+        .iter()
+        .map(|&(generation, _)| generation)
+        .max()
+        .unwrap_or(0);
+    let peak = points
+        .iter()
+        .map(|&(_, population)| population)
+        .max()
+        .unwrap_or(0);
+    let (across, up) = (WIDTH - LEFT - RIGHT, HEIGHT - TOP - BOTTOM);
+    let (bottom, right) = (HEIGHT - BOTTOM, WIDTH - RIGHT);
+    // A run of one generation, or of nothing alive, still has axes to draw.
+    let x = |generation: u64| {
+        LEFT + across * (generation - first) as f64 / (last - first).max(1) as f64
+    };
+    let y = |population: usize| bottom - up * population as f64 / peak.max(1) as f64;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" \
+         viewBox=\"0 0 {WIDTH} {HEIGHT}\">"
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#
+    );
+    let _ = writeln!(out, r#"<g stroke="black" stroke-width="1">"#);
+    let _ = writeln!(
+        out,
+        r#"<line x1="{LEFT}" y1="{bottom}" x2="{right}" y2="{bottom}"/>"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<line x1="{LEFT}" y1="{TOP}" x2="{LEFT}" y2="{bottom}"/>"#
+    );
+    let _ = writeln!(out, "</g>");
+    let _ = writeln!(
+        out,
+        r#"<g font-family="sans-serif" font-size="12" fill="black">"#
+    );
+    let label = |out: &mut String, (x, y): (f64, f64), anchor: &str, text: &str| {
+        let _ = writeln!(
+            out,
+            r#"<text x="{x}" y="{y}" text-anchor="{anchor}">{text}</text>"#
+        );
+    };
+    label(&mut out, (LEFT, bottom + 16.0), "start", &first.to_string());
+    label(&mut out, (right, bottom + 16.0), "end", &last.to_string());
+    label(
+        &mut out,
+        (LEFT + across / 2.0, HEIGHT - 8.0),
+        "middle",
+        "generation",
+    );
+    label(&mut out, (LEFT - 6.0, bottom), "end", "0");
+    label(&mut out, (LEFT - 6.0, TOP + 4.0), "end", &peak.to_string());
+    let (side, middle) = (16.0, TOP + up / 2.0);
+    let _ = writeln!(
+        out,
+        "<text x=\"{side}\" y=\"{middle}\" text-anchor=\"middle\" \
+         transform=\"rotate(-90 {side} {middle})\">population</text>"
+    );
+    let _ = writeln!(out, "</g>");
+    let line = points
+        .iter()
+        .map(|&(generation, population)| format!("{:.1},{:.1}", x(generation), y(population)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = writeln!(
+        out,
+        r#"<polyline fill="none" stroke="steelblue" stroke-width="1.5" points="{line}"/>"#
+    );
+    out.push_str("</svg>\n");
+    out
+}
+
+#[test]
+fn test_series_thins_out_evenly() {
+    let mut series = Series::new();
+    for generation in 0..(MAX_POINTS as u64 * 4) {
+        series.push(generation, generation as usize);
+    }
+    let points = series.points();
+    assert!(points.len() <= MAX_POINTS && points.len() > MAX_POINTS / 2);
+    assert_eq!(points[0], (0, 0));
+    // What is left is evenly spread.
+    let step = points[1].0 - points[0].0;
+    assert!(points.windows(2).all(|pair| pair[1].0 - pair[0].0 == step));
+    assert_eq!(step, 4);
+}
+
+#[test]
+fn test_svg_plots_the_series() {
+    let chart = svg(&[(10, 0), (20, 50), (30, 100)]);
+    assert!(chart.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(chart.ends_with("</svg>\n"));
+    // The first point is at the bottom left, the peak at the top right.
+    assert!(chart.contains(r#"points="60.0,280.0 340.0,150.0 620.0,20.0""#));
+    assert!(chart.contains(">10</text>") && chart.contains(">30</text>"));
+    assert!(chart.contains(">100</text>"));
+    // Nothing to plot still makes a chart.
+    assert!(svg(&[]).contains(r#"points="""#));
+    assert!(svg(&[(5, 0)]).contains(r#"points="60.0,280.0""#));
+}
@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long a run has been going, not counting the time it spent paused, and
+/// how many generations it has stepped forward. Times are handed in rather
+/// than read, so a clock can be driven by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunClock {
+    /// When the run started, by the wall clock.
+    started_at: SystemTime,
+    /// Running time before the current stretch.
+    banked: Duration,
+    /// When the current stretch of running started, unless paused.
+    since: Option<Instant>,
+    generations: u64,
+}
+
+impl RunClock {
+    /// A clock running from `now`, which the wall clock calls `wall`.
+    pub fn start(now: Instant, wall: SystemTime) -> Self {
+        Self {
+            started_at: wall,
+            banked: Duration::ZERO,
+            since: Some(now),
+            generations: 0,
+        }
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.since.is_none()
+    }
+
+    /// Stops the clock at `now`, if it is running.
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(since) = self.since.take() {
+            self.banked += now.saturating_duration_since(since);
+        }
+    }
+
+    /// Starts the clock again from `now`, if it is paused.
+    pub fn resume(&mut self, now: Instant) {
+        self.since.get_or_insert(now);
+    }
+
+    /// Pauses or resumes the clock at `now` to match `paused`.
+    pub fn set_paused(&mut self, paused: bool, now: Instant) {
+        match paused {
+            true => self.pause(now),
+            false => self.resume(now),
+        }
+    }
+
+    /// Counts `n` more generations stepped forward. Going back through
+    /// history doesn't take any off, so the rate never goes below zero.
+    pub fn count(&mut self, n: u64) {
+        self.generations += n;
+    }
+
+    pub fn generations(&self) -> u64 {
+        self.generations
+    }
+
+    /// The time spent running by `now`.
+    pub fn lap(&self, now: Instant) -> Duration {
+        let stretch = self.since.map(|since| now.saturating_duration_since(since));
+        self.banked + stretch.unwrap_or_default()
+    }
+
+    /// Generations stepped per second of running by `now`, however fast or
+    /// slow they were asked for along the way. Zero until any time has run.
+    pub fn rate(&self, now: Instant) -> f64 {
+        let secs = self.lap(now).as_secs_f64();
+        if secs > 0.0 {
+            self.generations as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// The running time and rate by `now`, for a status bar: `12.3 s, 45.6 gen/s`.
+    pub fn status(&self, now: Instant) -> String {
+        format!(
+            "{:.1} s, {:.1} gen/s",
+            self.lap(now).as_secs_f64(),
+            self.rate(now)
+        )
+    }
+
+    /// When the run started and how it has gone by `now`, for a comment in
+    /// a file it wrote.
+    pub fn provenance(&self, now: Instant) -> String {
+        format!(
+            "started {}, {}",
+            utc_stamp(self.started_at),
+            self.status(now)
+        )
+    }
+}
+
+/// `time` in UTC, to the second: `2024-02-29 13:05:09 UTC`. Times before
+/// 1970 are given as 1970 began.
+pub fn utc_stamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // Days since 1970 to a civil date, counting in 400-year eras starting
+    // on the 1st of March, so that leap days fall at the end of a year.
+    let days = days + 719_468;
+    let (era, day) = (days / 146_097, days % 146_097);
+    let year = (day - day / 1_460 + day / 36_524 - day / 146_096) / 365;
+    let day_of_year = day - (365 * year + year / 4 - year / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year + era * 400 + (month <= 2) as u64;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[test]
+fn test_run_clock_leaves_out_pauses() {
+    let t0 = Instant::now();
+    let at = |secs| t0 + Duration::from_secs(secs);
+    let mut clock = RunClock::start(t0, UNIX_EPOCH);
+    assert_eq!(clock.rate(t0), 0.0);
+    clock.count(100);
+    assert_eq!(clock.lap(at(10)), Duration::from_secs(10));
+    // Ten seconds paused count for nothing, however often it is paused.
+    clock.pause(at(10));
+    clock.pause(at(15));
+    assert!(clock.is_paused());
+    assert_eq!(clock.lap(at(20)), Duration::from_secs(10));
+    clock.resume(at(20));
+    clock.resume(at(25));
+    // Stepping faster for the next ten seconds raises the average.
+    clock.count(300);
+    assert_eq!(clock.lap(at(30)), Duration::from_secs(20));
+    assert_eq!(clock.rate(at(30)), 20.0);
+    clock.set_paused(true, at(30));
+    assert_eq!(clock.status(at(90)), "20.0 s, 20.0 gen/s");
+    // A time from before it last resumed doesn't run it backwards.
+    clock.set_paused(false, at(40));
+    assert_eq!(clock.lap(at(35)), Duration::from_secs(20));
+    assert_eq!(
+        clock.provenance(at(40)),
+        "started 1970-01-01 00:00:00 UTC, 20.0 s, 20.0 gen/s"
+    );
+}
+
+#[test]
+fn test_utc_stamps() {
+    let stamp = |secs| utc_stamp(UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(stamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(stamp(951_782_400), "2000-02-29 00:00:00 UTC");
+    assert_eq!(stamp(1_709_211_909), "2024-02-29 13:05:09 UTC");
+    assert_eq!(stamp(1_735_689_599), "2024-12-31 23:59:59 UTC");
+    assert_eq!(stamp(4_107_542_400), "2100-03-01 00:00:00 UTC");
+    assert_eq!(utc_stamp(UNIX_EPOCH - Duration::from_secs(1)), stamp(0));
+}
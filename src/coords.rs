@@ -0,0 +1,274 @@
+use crate::{Pattern, Rect};
+
+/// A cell of a board: columns from the left, rows down from the top, never
+/// negative. Like [`WorldPos`] and [`ScreenPos`], x counts to the right and
+/// y down, as on the terminal and in every pattern format read here; no
+/// conversion between them flips an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct GridPos(pub usize, pub usize);
+
+/// A cell of pattern or universe space, any distance either way of the
+/// origin: x to the right, y down. A board sits in it with its top-left cell
+/// at some origin, and formats that list cells around a center rather than
+/// from a corner, such as Life 1.06, are in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct WorldPos(pub i64, pub i64);
+
+/// A terminal character: columns from the left, rows down from the top. A
+/// board is drawn at a [`Scale`] in an area of the screen, scrolled so that
+/// an offset cell is drawn first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct ScreenPos(pub u16, pub u16);
+
+/// How grid cells map onto terminal characters: each block of `cells` cells
+/// is drawn by a block of `chars` characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scale {
+    pub cells: (usize, usize),
+    pub chars: (usize, usize),
+}
+
+impl Scale {
+    /// A [`BlockRenderer`](crate::frontend::BlockRenderer) mapping for
+    /// characters `aspect` times as wide as they are tall: one character down
+    /// per cell, and as many across as make the cell closest to square.
+    pub fn block(aspect: f64) -> Self {
+        let across = (1.0 / aspect).round().clamp(1.0, 8.0) as usize;
+        Self {
+            cells: (1, 1),
+            chars: (across, 1),
+        }
+    }
+
+    /// A [`BrailleRenderer`](crate::frontend::BrailleRenderer) mapping for
+    /// characters `aspect` times as wide as they are tall: two dots across
+    /// per character, and three or four down, whichever makes the dots
+    /// closest to square.
+    pub fn braille(aspect: f64) -> Self {
+        // A dot is half a character wide and a row of the character tall.
+        let squareness = |rows: f64| (aspect / 2.0 * rows).ln().abs();
+        let rows = if squareness(4.0) < squareness(3.0) {
+            4
+        } else {
+            3
+        };
+        Self {
+            cells: (2, rows),
+            chars: (1, 1),
+        }
+    }
+
+    /// How many cells fit in `area`, counting only whole blocks.
+    pub fn cells_in(&self, area: Rect) -> (usize, usize) {
+        (
+            area.width / self.chars.0 * self.cells.0,
+            area.height / self.chars.1 * self.cells.1,
+        )
+    }
+
+    /// How many characters across and down show `cells` cells, in as few
+    /// blocks as hold them all.
+    pub fn chars_for(&self, cells: (usize, usize)) -> (usize, usize) {
+        (
+            cells.0.div_ceil(self.cells.0) * self.chars.0,
+            cells.1.div_ceil(self.cells.1) * self.chars.1,
+        )
+    }
+}
+
+impl GridPos {
+    /// Where the cell is in the world, for a board whose top-left cell is at
+    /// `origin`.
+    pub fn to_world(self, origin: WorldPos) -> WorldPos {
+        WorldPos(origin.0 + self.0 as i64, origin.1 + self.1 as i64)
+    }
+
+    /// The top-left character of the block drawing the cell, when `area` of
+    /// the screen shows the board at `scale` scrolled to `offset`. `None` if
+    /// the cell is scrolled out of `area`. Not checked against the board.
+    pub fn to_screen(self, area: Rect, scale: Scale, offset: GridPos) -> Option<ScreenPos> {
+        let (cells, chars) = (scale.cells, scale.chars);
+        let col = self.0.checked_sub(offset.0)? / cells.0;
+        let row = self.1.checked_sub(offset.1)? / cells.1;
+        if col >= area.width / chars.0 || row >= area.height / chars.1 {
+            return None;
+        }
+        let (x, y) = (area.x + col * chars.0, area.y + row * chars.1);
+        Some(ScreenPos(x.try_into().ok()?, y.try_into().ok()?))
+    }
+}
+
+impl WorldPos {
+    /// The cell of a board whose top-left cell is at `origin`. `None` left
+    /// of or above the board; not checked against its size.
+    pub fn to_grid(self, origin: WorldPos) -> Option<GridPos> {
+        let x = usize::try_from(self.0.checked_sub(origin.0)?).ok()?;
+        let y = usize::try_from(self.1.checked_sub(origin.1)?).ok()?;
+        Some(GridPos(x, y))
+    }
+}
+
+impl ScreenPos {
+    /// The top-left of the cells the character shows, when `area` of the
+    /// screen shows a board at `scale` scrolled to `offset`: the inverse of
+    /// [`GridPos::to_screen`] for the first cell of each block. `None`
+    /// outside `area`, or in the characters left over past its last whole
+    /// block. Not checked against the board.
+    pub fn to_grid(self, area: Rect, scale: Scale, offset: GridPos) -> Option<GridPos> {
+        let (x, y) = (self.0 as usize, self.1 as usize);
+        let (cells, chars) = (scale.cells, scale.chars);
+        let col = x.checked_sub(area.x)? / chars.0;
+        let row = y.checked_sub(area.y)? / chars.1;
+        if col >= area.width / chars.0 || row >= area.height / chars.1 {
+            return None;
+        }
+        Some(GridPos(col * cells.0 + offset.0, row * cells.1 + offset.1))
+    }
+}
+
+impl From<(usize, usize)> for GridPos {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self(x, y)
+    }
+}
+
+impl From<GridPos> for (usize, usize) {
+    fn from(pos: GridPos) -> Self {
+        (pos.0, pos.1)
+    }
+}
+
+impl Pattern {
+    /// A pattern of live cells given anywhere in the world, as a format that
+    /// lists them around a center does, along with where its top-left corner
+    /// is; [`world_cells`](Self::world_cells) gives them back. The box is
+    /// the cells' bounding box, and is empty at the world origin if there
+    /// are none.
+    pub fn from_world(cells: &[WorldPos]) -> (Self, WorldPos) {
+        let corner = cells
+            .iter()
+            .copied()
+            .reduce(|a, b| WorldPos(a.0.min(b.0), a.1.min(b.1)))
+            .unwrap_or_default();
+        let cells = cells.iter().filter_map(|cell| cell.to_grid(corner));
+        (
+            Self::new(cells.map(<(usize, usize)>::from).collect()),
+            corner,
+        )
+    }
+
+    /// The live cells in the world, with the pattern's top-left corner at
+    /// `origin`.
+    pub fn world_cells(&self, origin: WorldPos) -> Vec<WorldPos> {
+        self.cells()
+            .iter()
+            .map(|&cell| GridPos::from(cell).to_world(origin))
+            .collect()
+    }
+}
+
+#[test]
+fn test_grid_and_world_convert_both_ways() {
+    let origin = WorldPos(-10, 4);
+    assert_eq!(GridPos(0, 0).to_world(origin), origin);
+    assert_eq!(GridPos(12, 3).to_world(origin), WorldPos(2, 7));
+    assert_eq!(WorldPos(2, 7).to_grid(origin), Some(GridPos(12, 3)));
+    // Left of or above the board is no cell of it.
+    assert_eq!(WorldPos(-11, 7).to_grid(origin), None);
+    assert_eq!(WorldPos(0, 3).to_grid(origin), None);
+    assert_eq!(WorldPos(i64::MIN, 0).to_grid(WorldPos(1, 0)), None);
+    for x in 0..5 {
+        for y in 0..5 {
+            let cell = GridPos(x, y);
+            assert_eq!(cell.to_world(origin).to_grid(origin), Some(cell));
+        }
+    }
+    assert_eq!(<(usize, usize)>::from(GridPos::from((3, 8))), (3, 8));
+}
+
+#[test]
+fn test_grid_and_screen_convert_both_ways() {
+    let area = Rect {
+        x: 1,
+        y: 1,
+        width: 10,
+        height: 6,
+    };
+    // Two characters across per cell, scrolled three cells right.
+    let block = Scale::block(0.5);
+    let offset = GridPos(3, 0);
+    assert_eq!(
+        ScreenPos(1, 1).to_grid(area, block, offset),
+        Some(GridPos(3, 0))
+    );
+    assert_eq!(
+        ScreenPos(2, 1).to_grid(area, block, offset),
+        Some(GridPos(3, 0))
+    );
+    assert_eq!(
+        ScreenPos(3, 6).to_grid(area, block, offset),
+        Some(GridPos(4, 5))
+    );
+    assert_eq!(
+        GridPos(4, 5).to_screen(area, block, offset),
+        Some(ScreenPos(3, 6))
+    );
+    // The frame, and past the area, show no cell; neither do cells scrolled
+    // out of it.
+    assert_eq!(ScreenPos(0, 1).to_grid(area, block, offset), None);
+    assert_eq!(ScreenPos(11, 1).to_grid(area, block, offset), None);
+    assert_eq!(ScreenPos(1, 7).to_grid(area, block, offset), None);
+    assert_eq!(GridPos(2, 0).to_screen(area, block, offset), None);
+    assert_eq!(GridPos(8, 0).to_screen(area, block, offset), None);
+    assert_eq!(GridPos(3, 6).to_screen(area, block, offset), None);
+
+    // Eight cells a character: each lands in the block it is drawn in, and
+    // that block's first character shows the block's first cell.
+    let braille = Scale::braille(0.5);
+    assert_eq!(
+        braille,
+        Scale {
+            cells: (2, 4),
+            chars: (1, 1)
+        }
+    );
+    for x in 0..20 {
+        for y in 0..24 {
+            let cell = GridPos(x, y);
+            let Some(at) = cell.to_screen(area, braille, GridPos::default()) else {
+                assert!(x >= 20 || y >= 24);
+                continue;
+            };
+            let first = at.to_grid(area, braille, GridPos::default()).unwrap();
+            assert_eq!(first, GridPos(x / 2 * 2, y / 4 * 4));
+        }
+    }
+    for x in 1..11 {
+        for y in 1..7 {
+            let at = ScreenPos(x, y);
+            let cell = at.to_grid(area, block, offset).unwrap();
+            // An odd column is the second character of its block.
+            assert_eq!(
+                cell.to_screen(area, block, offset),
+                Some(ScreenPos(x - (x + 1) % 2, y))
+            );
+        }
+    }
+}
+
+#[test]
+fn test_patterns_convert_to_and_from_world_cells() {
+    // A glider listed around its center, as a Life 1.06 file has it, y down.
+    let listed = [(0, -1), (1, 0), (-1, 1), (0, 1), (1, 1)].map(|(x, y)| WorldPos(x, y));
+    let (glider, corner) = Pattern::from_world(&listed);
+    assert_eq!(glider, Pattern::named("glider").unwrap());
+    assert_eq!(corner, WorldPos(-1, -1));
+    let mut cells = glider.world_cells(corner);
+    cells.sort_by_key(|&WorldPos(x, y)| (y, x));
+    assert_eq!(cells, listed);
+    assert_eq!(glider.world_cells(WorldPos(5, 5))[0], WorldPos(6, 5));
+    assert_eq!(
+        Pattern::from_world(&[]),
+        (Pattern::default(), WorldPos(0, 0))
+    );
+}
@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+
+use crate::{game::checksum_words, GameOfLife};
+
+/// How a board settled down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Settled {
+    /// Every cell died.
+    Died,
+    /// The board came back to how it was this many generations ago: 1 for
+    /// still lifes, more for oscillators.
+    Cycle(usize),
+}
+
+/// Notices when a board has settled, by remembering its last few
+/// generations along with their checksums.
+#[derive(Debug, Clone)]
+pub struct CycleDetector {
+    max_period: usize,
+    /// Checksums and packed cells, newest last.
+    recent: VecDeque<(u64, Vec<u64>)>,
+    /// The cells of the generation forgotten last, for the next to reuse.
+    spare: Vec<u64>,
+}
+
+impl CycleDetector {
+    /// A detector for cycles of up to `max_period` generations.
+    pub fn new(max_period: usize) -> Self {
+        Self {
+            max_period,
+            recent: VecDeque::with_capacity(max_period + 1),
+            spare: Vec::new(),
+        }
+    }
+
+    /// Records the board's current generation and says whether it has
+    /// settled. Generations are told apart by checksum, and only one with
+    /// the same checksum has its cells compared.
+    pub fn observe(&mut self, game: &GameOfLife) -> Option<Settled> {
+        if game.population() == 0 {
+            return Some(Settled::Died);
+        }
+        let mut cells = std::mem::take(&mut self.spare);
+        game.pack_into(&mut cells);
+        let checksum = checksum_words(game.size(), &cells);
+        let period = self
+            .recent
+            .iter()
+            .rev()
+            .position(|(seen, seen_cells)| *seen == checksum && *seen_cells == cells)
+            .map(|back| back + 1);
+        if self.recent.len() == self.max_period {
+            if let Some((_, oldest)) = self.recent.pop_front() {
+                self.spare = oldest;
+            }
+        }
+        self.recent.push_back((checksum, cells));
+        period.map(Settled::Cycle)
+    }
+
+    /// Forgets the generations seen so far, e.g. when the board is reseeded.
+    pub fn reset(&mut self) {
+        self.recent.clear();
+    }
+}
+
+/// Steps `game` until `detector` sees it settle, for at most `generations`
+/// steps. `None` means it was still changing at the limit.
+pub fn run_until_settled(
+    game: &mut GameOfLife,
+    detector: &mut CycleDetector,
+    generations: u64,
+) -> Option<Settled> {
+    run_until_settled_observing(game, detector, generations, |_| {})
+}
+
+/// Runs like [`run_until_settled`], showing `each` every generation on the
+/// way, the first and the last included.
+pub fn run_until_settled_observing(
+    game: &mut GameOfLife,
+    detector: &mut CycleDetector,
+    generations: u64,
+    mut each: impl FnMut(&GameOfLife),
+) -> Option<Settled> {
+    for _ in 0..generations {
+        each(game);
+        if let Some(settled) = detector.observe(game) {
+            return Some(settled);
+        }
+        game.step();
+    }
+    each(game);
+    detector.observe(game)
+}
+
+/// The exit code reporting how a run ended, for scripts: 0 if the board
+/// settled into a still life or oscillator, 1 if it died out and 2 if it was
+/// still changing. The other codes the program exits with are
+/// [`NO_TERMINAL`](crate::app::NO_TERMINAL) (3),
+/// [`ASSERTION_FAILED`](crate::query::ASSERTION_FAILED) (4),
+/// [`USAGE`](crate::app::USAGE) (64) and [`FAILED`](crate::app::FAILED) (70).
+pub fn exit_code(outcome: Option<Settled>) -> u8 {
+    match outcome {
+        Some(Settled::Cycle(_)) => 0,
+        Some(Settled::Died) => 1,
+        None => 2,
+    }
+}
+
+#[test]
+fn test_cycle_detection() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::new_empty((6, 6));
+    let mut detector = CycleDetector::new(4);
+    assert_eq!(detector.observe(&game), Some(Settled::Died));
+
+    // A blinker repeats every two generations.
+    game.set(GridPos(1, 2), true);
+    game.set(GridPos(2, 2), true);
+    game.set(GridPos(3, 2), true);
+    assert_eq!(detector.observe(&game), None);
+    game.step();
+    assert_eq!(detector.observe(&game), None);
+    game.step();
+    assert_eq!(detector.observe(&game), Some(Settled::Cycle(2)));
+
+    // A detector too short for the period never sees it.
+    let mut short = CycleDetector::new(1);
+    for _ in 0..4 {
+        game.step();
+        assert_eq!(short.observe(&game), None);
+    }
+    // ...but does see a block stand still.
+    short.reset();
+    game.fill(0.0, 0);
+    for (x, y) in [(0, 4), (1, 4), (0, 5), (1, 5)] {
+        game.set(GridPos(x, y), true);
+    }
+    assert_eq!(short.observe(&game), None);
+    game.step();
+    assert_eq!(short.observe(&game), Some(Settled::Cycle(1)));
+}
+
+#[test]
+fn test_run_until_settled() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::new_empty((8, 8));
+    for (x, y) in [(1, 1), (2, 1), (3, 1)] {
+        game.set(GridPos(x, y), true);
+    }
+    let outcome = run_until_settled(&mut game, &mut CycleDetector::new(4), 10);
+    assert_eq!(outcome, Some(Settled::Cycle(2)));
+    assert_eq!(game.generation(), 2);
+    assert_eq!(exit_code(outcome), 0);
+
+    // A lone pair of cells dies in one step.
+    game = GameOfLife::new_empty((8, 8));
+    game.set(GridPos(4, 4), true);
+    game.set(GridPos(5, 4), true);
+    let outcome = run_until_settled(&mut game, &mut CycleDetector::new(4), 10);
+    assert_eq!((outcome, exit_code(outcome)), (Some(Settled::Died), 1));
+
+    // A glider on an 8x8 torus only comes back after 32 generations, so a
+    // short run stops while it is still going.
+    game = GameOfLife::new_empty((8, 8));
+    game.set_edge_mode(crate::EdgeMode::Toroidal);
+    game.insert_pattern(&crate::Pattern::named("glider").unwrap(), GridPos(0, 0));
+    let outcome = run_until_settled(&mut game, &mut CycleDetector::new(4), 10);
+    assert_eq!((outcome, exit_code(outcome)), (None, 2));
+    assert_eq!(game.generation(), 10);
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::SimObserver;
+
+/// How far back [`Diagnostics`] averages over.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Time spent in each part of one frame of the run loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameTimes {
+    pub step: Duration,
+    pub render: Duration,
+    pub flush: Duration,
+    /// Generations stepped since the frame before.
+    pub steps: u32,
+    /// Ticks that were due but skipped because the loop fell too far behind
+    /// to catch up.
+    pub dropped: u32,
+}
+
+/// Averages of [`FrameTimes`] over the last second.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    pub step: Duration,
+    pub render: Duration,
+    pub flush: Duration,
+    /// Frames actually run per second.
+    pub fps: f64,
+    /// Generations actually stepped per second.
+    pub tps: f64,
+    /// Total ticks dropped in the last second.
+    pub dropped: u32,
+}
+
+/// What a [`SimObserver`] was told about the steps it watched, added up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepTally {
+    pub steps: u32,
+    /// Time spent stepping.
+    pub time: Duration,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+impl SimObserver for StepTally {
+    fn on_step_end(&mut self, _: u64, duration: Duration, births: usize, deaths: usize) {
+        self.steps += 1;
+        self.time += duration;
+        self.births += births;
+        self.deaths += deaths;
+    }
+}
+
+/// Rolling frame timings, kept for the last second of frames.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    frames: VecDeque<(Instant, FrameTimes)>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a frame that finished at `now`, forgetting frames that have
+    /// fallen out of the window.
+    pub fn record(&mut self, times: FrameTimes, now: Instant) {
+        self.frames.push_back((now, times));
+        while let Some(&(at, _)) = self.frames.front() {
+            if now.duration_since(at) <= WINDOW {
+                break;
+            }
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        let n = self.frames.len() as u32;
+        if n == 0 {
+            return FrameStats::default();
+        }
+        let sum = |part: fn(&FrameTimes) -> Duration| {
+            self.frames.iter().map(|(_, t)| part(t)).sum::<Duration>() / n
+        };
+        // The rate between the first and last frame, so it is meaningful
+        // before a whole window has been recorded.
+        let span = match (self.frames.front(), self.frames.back()) {
+            (Some(&(first, _)), Some(&(last, _))) => last.duration_since(first),
+            _ => Duration::ZERO,
+        };
+        // The first frame's steps came before the span began.
+        let steps = self
+            .frames
+            .iter()
+            .skip(1)
+            .map(|(_, t)| t.steps as f64)
+            .sum::<f64>();
+        let per_second = |count: f64| match span.is_zero() {
+            true => 0.0,
+            false => count / span.as_secs_f64(),
+        };
+        FrameStats {
+            step: sum(|t| t.step),
+            render: sum(|t| t.render),
+            flush: sum(|t| t.flush),
+            fps: per_second((n - 1) as f64),
+            tps: per_second(steps),
+            dropped: self.frames.iter().map(|(_, t)| t.dropped).sum(),
+        }
+    }
+}
+
+#[test]
+fn test_diagnostics_rolling_window() {
+    let t0 = Instant::now();
+    let ms = Duration::from_millis;
+    let mut diagnostics = Diagnostics::new();
+    assert_eq!(diagnostics.stats(), FrameStats::default());
+    let frame = |step| FrameTimes {
+        step: ms(step),
+        render: ms(2),
+        flush: ms(1),
+        steps: 1,
+        dropped: 0,
+    };
+    // A slow frame that then falls out of the window.
+    diagnostics.record(
+        FrameTimes {
+            dropped: 3,
+            ..frame(40)
+        },
+        t0,
+    );
+    for i in 1..=20 {
+        diagnostics.record(frame(4), t0 + ms(500 + i * 50));
+    }
+    let stats = diagnostics.stats();
+    assert_eq!(stats.step, ms(4));
+    assert_eq!(stats.render, ms(2));
+    assert_eq!(stats.dropped, 0);
+    assert!((stats.fps - 20.0).abs() < 1e-9);
+    assert!((stats.tps - 20.0).abs() < 1e-9);
+
+    // Catching up steps several generations a frame.
+    let mut diagnostics = Diagnostics::new();
+    for i in 0..=10 {
+        let steps = if i % 2 == 0 { 3 } else { 1 };
+        diagnostics.record(FrameTimes { steps, ..frame(4) }, t0 + ms(i * 100));
+    }
+    let stats = diagnostics.stats();
+    assert!((stats.fps - 10.0).abs() < 1e-9);
+    assert!((stats.tps - 20.0).abs() < 1e-9);
+}
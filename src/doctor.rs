@@ -0,0 +1,381 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use termwiz::{
+    caps::{Capabilities, ColorLevel},
+    input::{InputEvent, KeyCode, MouseButtons},
+    surface::Change,
+    terminal::{buffered::BufferedTerminal, ScreenSize, Terminal},
+};
+
+use crate::{
+    frontend::{query_background, query_terminal, BlockRenderer, BrailleRenderer, Offscreen},
+    Theme,
+};
+
+/// How long the terminal has to answer a query. Most answer at once, and
+/// the ones that don't never will.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long the user has to answer a question or click.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Braille of every density, which a font without braille shows as boxes
+/// or question marks.
+const BRAILLE_SAMPLE: &str = "⠁⠃⠇⡇⣇⣧⣷⣿ ⢀⣀⣤⣶⣿ ⠉⠛⠿";
+
+/// Sextants of every density, asked about when braille doesn't show: the
+/// glyphs with the same packing that a font is next most likely to have.
+const SEXTANT_SAMPLE: &str = "🬀🬂🬆🬎🬝 🬋🬎🬹 🬞🬭🬸🬻";
+
+/// A kitty graphics query for an image that is never shown, which only a
+/// terminal with the protocol answers. The device attributes query after
+/// it is answered by every terminal, so its reply marks the end.
+const KITTY_QUERY: &str = "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\";
+const DEVICE_ATTRIBUTES: &str = "\x1b[c";
+
+/// The sixel graphics attribute in a reply to [`DEVICE_ATTRIBUTES`].
+const SIXEL: u32 = 4;
+
+/// How much the measured aspect of a character may be off from what a
+/// renderer takes it to be before `--aspect` is worth giving.
+const ASPECT_TOLERANCE: f64 = 0.1;
+
+/// What one probe of the terminal came to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Yes,
+    No,
+    /// Nothing came back in time.
+    TimedOut,
+    /// Not tried, or passed over by the user.
+    Skipped,
+}
+
+impl Check {
+    fn of(yes: bool) -> Self {
+        if yes {
+            Check::Yes
+        } else {
+            Check::No
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Check::Yes => "yes",
+            Check::No => "no",
+            Check::TimedOut => "no answer in time",
+            Check::Skipped => "skipped",
+        })
+    }
+}
+
+/// Everything `doctor` found out about the terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Findings {
+    /// `$TERM`, if it is set.
+    pub term: Option<String>,
+    /// The colors the terminal's description and environment promise;
+    /// `None` without a description at all.
+    pub colors: Option<ColorLevel>,
+    /// Whether the user could see the braille sample.
+    pub braille: Check,
+    /// Whether the user could see the sextant sample, asked only without
+    /// braille.
+    pub sextant: Check,
+    /// Whether a click made it back from the terminal.
+    pub mouse: Check,
+    pub sixel: Check,
+    pub kitty: Check,
+    /// The width of a character over its height, where the terminal gives
+    /// its size in pixels.
+    pub aspect: Option<f64>,
+    /// The theme that suits the background the terminal reported.
+    pub background: Option<Theme>,
+}
+
+impl Findings {
+    /// What can be known without asking the terminal or the user anything:
+    /// all that there is to go on without a terminal to ask.
+    pub fn from_env(term: Option<String>, caps: Option<&Capabilities>) -> Self {
+        Self {
+            term,
+            colors: caps.map(Capabilities::color_level),
+            braille: Check::Skipped,
+            sextant: Check::Skipped,
+            mouse: Check::Skipped,
+            sixel: Check::Skipped,
+            kitty: Check::Skipped,
+            aspect: None,
+            background: None,
+        }
+    }
+
+    /// One `name: finding` line each.
+    pub fn report(&self) -> Vec<String> {
+        let colors = match self.colors {
+            Some(ColorLevel::TrueColor) => "truecolor",
+            Some(ColorLevel::TwoFiftySix) => "256",
+            Some(ColorLevel::Sixteen) => "16",
+            None => "none known: no description of the terminal",
+        };
+        let aspect = match self.aspect {
+            Some(aspect) => format!("{aspect:.2} (width over height)"),
+            None => "unknown".to_string(),
+        };
+        let background = match self.background {
+            Some(theme) => theme.to_string(),
+            None => "unknown".to_string(),
+        };
+        vec![
+            format!("TERM: {}", self.term.as_deref().unwrap_or("not set")),
+            format!("colors: {colors}"),
+            format!("braille: {}", self.braille),
+            format!("sextants: {}", self.sextant),
+            format!("mouse: {}", self.mouse),
+            format!("sixel: {}", self.sixel),
+            format!("kitty graphics: {}", self.kitty),
+            format!("character aspect: {aspect}"),
+            format!("background: {background}"),
+        ]
+    }
+
+    /// The flags that suit the terminal, each with why.
+    pub fn suggestions(&self) -> Vec<(String, &'static str)> {
+        let mut flags = Vec::new();
+        // Sextants pack cells as three-row braille does.
+        let braille = self.braille == Check::Yes || self.sextant == Check::Yes;
+        match (self.braille, self.sextant) {
+            (Check::Yes, _) => {
+                flags.push(("--renderer braille".into(), "braille shows, and is finer"))
+            }
+            (Check::No, Check::Yes) => flags.push((
+                "--glyphs sextant".into(),
+                "the font has no braille, but sextants are as fine",
+            )),
+            (Check::No, _) => flags.push(("--renderer block".into(), "the font has no braille")),
+            _ => {}
+        }
+        // Each renderer takes characters to have an aspect of its own.
+        let (assumed, why) = match braille {
+            true => (
+                BrailleRenderer::<Offscreen>::ASPECT,
+                "dots come out evenly spaced",
+            ),
+            false => (BlockRenderer::<Offscreen>::ASPECT, "cells come out square"),
+        };
+        let off = |aspect: &f64| (aspect / assumed - 1.0).abs() > ASPECT_TOLERANCE;
+        if let Some(aspect) = self.aspect.filter(off) {
+            flags.push((format!("--aspect {aspect:.2}"), why));
+        }
+        match self.background {
+            Some(theme) => flags.push((
+                format!("--theme {theme}"),
+                "saves asking the terminal each run",
+            )),
+            None => flags.push((
+                "--theme dark".to_string(),
+                "or light: which the background is isn't known",
+            )),
+        }
+        flags
+    }
+}
+
+/// The attributes in a reply to a device attributes query, such as
+/// `ESC [ ? 62 ; 4 ; 22 c`, once the whole of it has come. Whatever came
+/// before it is passed over.
+pub fn parse_device_attributes(reply: &str) -> Option<Vec<u32>> {
+    let start = reply.rfind("\x1b[?")?;
+    let body = reply[start + 3..].strip_suffix('c')?;
+    body.split(';')
+        .map(|attribute| attribute.parse().ok())
+        .collect()
+}
+
+/// Whether `reply` has the answer to [`KITTY_QUERY`] in it.
+fn kitty_replied(reply: &str) -> bool {
+    reply.contains("\x1b_Gi=31;OK")
+}
+
+/// The width of a character over its height, from a screen size that has
+/// pixels in it.
+pub fn cell_aspect(size: ScreenSize) -> Option<f64> {
+    if size.cols == 0 || size.rows == 0 || size.xpixel == 0 || size.ypixel == 0 {
+        return None;
+    }
+    let width = size.xpixel as f64 / size.cols as f64;
+    let height = size.ypixel as f64 / size.rows as f64;
+    Some(width / height)
+}
+
+/// Asks the terminal, and the user if `interactive`, what it can do. Every
+/// question waits a while at most, so a terminal that never answers only
+/// slows it down. Leaves the terminal in cooked mode.
+pub fn probe<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    term: Option<String>,
+    caps: &Capabilities,
+    interactive: bool,
+) -> Findings {
+    let mut findings = Findings::from_env(term, Some(caps));
+    findings.background = query_background(screen, QUERY_TIMEOUT).map(Theme::for_background);
+    let request = format!("{KITTY_QUERY}{DEVICE_ATTRIBUTES}");
+    let replied = query_terminal(screen, &request, QUERY_TIMEOUT, |reply| {
+        parse_device_attributes(reply).map(|attributes| (attributes, kitty_replied(reply)))
+    });
+    (findings.sixel, findings.kitty) = match replied {
+        Some((attributes, kitty)) => (Check::of(attributes.contains(&SIXEL)), Check::of(kitty)),
+        None => (Check::TimedOut, Check::TimedOut),
+    };
+    let terminal = screen.terminal();
+    findings.aspect = terminal.get_screen_size().ok().and_then(cell_aspect);
+    if interactive {
+        say(terminal, &format!("{BRAILLE_SAMPLE}\r\n"));
+        findings.braille = ask(
+            terminal,
+            "Do you see dots above, not boxes or question marks?",
+        );
+        if findings.braille == Check::No {
+            say(terminal, &format!("\r\n{SEXTANT_SAMPLE}\r\n"));
+            let question = "Do you see blocks above, not boxes or question marks?";
+            findings.sextant = ask(terminal, question);
+        }
+        findings.mouse = if caps.mouse_reporting() {
+            click(terminal)
+        } else {
+            Check::No
+        };
+    }
+    let _ = terminal.set_cooked_mode();
+    findings
+}
+
+fn say<T: Terminal>(terminal: &mut T, text: &str) {
+    let _ = terminal.render(&[Change::Text(text.to_string())]);
+    let _ = terminal.flush();
+}
+
+/// Asks `question` and waits for y or n. Escape or q passes it over.
+fn ask<T: Terminal>(terminal: &mut T, question: &str) -> Check {
+    say(terminal, &format!("{question} [y/n] "));
+    let answer = wait_for(terminal, |event| match event {
+        InputEvent::Key(key) => match key.key {
+            KeyCode::Char('y' | 'Y') => Some(Check::Yes),
+            KeyCode::Char('n' | 'N') => Some(Check::No),
+            KeyCode::Char('q') | KeyCode::Escape => Some(Check::Skipped),
+            _ => None,
+        },
+        _ => None,
+    });
+    say(terminal, "\r\n");
+    answer
+}
+
+/// Asks the user to click, which shows mouse reports come back. Any key
+/// passes it over.
+fn click<T: Terminal>(terminal: &mut T) -> Check {
+    say(
+        terminal,
+        "Click anywhere in this window, or press a key to skip. ",
+    );
+    let clicked = wait_for(terminal, |event| match event {
+        InputEvent::Mouse(mouse) if mouse.mouse_buttons != MouseButtons::NONE => Some(Check::Yes),
+        InputEvent::Key(_) => Some(Check::Skipped),
+        _ => None,
+    });
+    say(terminal, "\r\n");
+    clicked
+}
+
+/// Reads input until `answer` makes something of an event, for at most
+/// [`ANSWER_TIMEOUT`].
+fn wait_for<T: Terminal>(terminal: &mut T, answer: impl Fn(InputEvent) -> Option<Check>) -> Check {
+    let deadline = Instant::now() + ANSWER_TIMEOUT;
+    loop {
+        let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+            return Check::TimedOut;
+        };
+        match terminal.poll_input(Some(left)) {
+            Ok(Some(event)) => {
+                if let Some(check) = answer(event) {
+                    return check;
+                }
+            }
+            Ok(None) => {}
+            Err(_) => return Check::Skipped,
+        }
+    }
+}
+
+#[test]
+fn test_device_attributes_and_aspect() {
+    assert_eq!(
+        parse_device_attributes("\x1b[?62;4;22c"),
+        Some(vec![62, 4, 22])
+    );
+    let kitty = "\x1b_Gi=31;OK\x1b\\\x1b[?62;22c";
+    assert_eq!(parse_device_attributes(kitty), Some(vec![62, 22]));
+    assert!(kitty_replied(kitty));
+    assert!(!kitty_replied("\x1b[?62;22c"));
+    // Not all there yet, or not a reply at all.
+    assert_eq!(parse_device_attributes("\x1b[?62;4"), None);
+    assert_eq!(parse_device_attributes("\x1b[?62;xc"), None);
+    assert_eq!(parse_device_attributes("62;4c"), None);
+
+    let size = |xpixel, ypixel| ScreenSize {
+        cols: 80,
+        rows: 24,
+        xpixel,
+        ypixel,
+    };
+    assert_eq!(cell_aspect(size(640, 384)), Some(0.5));
+    assert_eq!(cell_aspect(size(0, 0)), None);
+}
+
+#[test]
+fn test_suggestions_follow_the_findings() {
+    let flags = |findings: &Findings| -> Vec<String> {
+        findings
+            .suggestions()
+            .into_iter()
+            .map(|(flag, _)| flag)
+            .collect()
+    };
+    let mut findings = Findings::from_env(None, None);
+    assert_eq!(flags(&findings), ["--theme dark"]);
+    assert!(findings.report().contains(&"TERM: not set".to_string()));
+    assert!(findings.report().contains(&"braille: skipped".to_string()));
+
+    // Braille that shows is finer; its dots need the measured aspect.
+    findings.braille = Check::Yes;
+    findings.aspect = Some(0.5);
+    findings.background = Some(Theme::Light);
+    assert_eq!(
+        flags(&findings),
+        ["--renderer braille", "--aspect 0.50", "--theme light"]
+    );
+    // Near enough to what braille assumes needs no --aspect.
+    findings.aspect = Some(0.65);
+    assert_eq!(flags(&findings), ["--renderer braille", "--theme light"]);
+
+    findings.braille = Check::No;
+    findings.mouse = Check::TimedOut;
+    assert_eq!(
+        flags(&findings),
+        ["--renderer block", "--aspect 0.65", "--theme light"]
+    );
+    // Sextants that show stand in for braille, at braille's aspect.
+    findings.sextant = Check::Yes;
+    assert_eq!(flags(&findings), ["--glyphs sextant", "--theme light"]);
+    assert!(findings.report().contains(&"sextants: yes".to_string()));
+    findings.sextant = Check::No;
+    let report = findings.report();
+    assert!(report.contains(&"mouse: no answer in time".to_string()));
+    assert!(report.contains(&"character aspect: 0.65 (width over height)".to_string()));
+}
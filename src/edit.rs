@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use crate::{GameOfLife, GridPos, GridStorage, Pattern, Rect};
+
+/// A change to a board's cells, made all at once by
+/// [`GameOfLife::apply_edit`] between generations, so that whatever watches
+/// the board sees it before or after and never half done. Undo keeps the
+/// inverses it hands back, and a [`Server`](crate::serve::Server) sends
+/// observers what changed on the board as one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Sets each cell in turn, later entries for the same cell winning.
+    /// Cells off the board are passed over.
+    Cells(Vec<(GridPos, bool)>),
+    /// Refills `rect` at random, as [`GameOfLife::fill_rect`] does.
+    Region { rect: Rect, density: f64, seed: u64 },
+    /// Writes `pattern` at `origin`, as [`GameOfLife::insert_pattern`] does.
+    Pattern { pattern: Pattern, origin: GridPos },
+    /// Kills every cell.
+    Clear,
+    /// Refills the whole board at random, as [`GameOfLife::fill`] does.
+    Randomize { seed: u64, density: f64 },
+}
+
+impl Edit {
+    /// The edit that turns `before` into `after`, a board of the same size:
+    /// the cells that differ, as `after` has them.
+    pub fn between<S: GridStorage, T: GridStorage>(
+        before: &GameOfLife<S>,
+        after: &GameOfLife<T>,
+    ) -> Self {
+        let cells = (0..before.cell_count())
+            .map(|i| before.coords(i))
+            .filter_map(|pos| {
+                let cell = after.get(pos);
+                (before.get(pos) != cell).then_some((pos, cell))
+            });
+        Edit::Cells(cells.collect())
+    }
+
+    /// Whether applying this changes nothing, as for the inverse of an edit
+    /// that found the board as it left it.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Edit::Cells(cells) if cells.is_empty())
+    }
+}
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// Makes `edit`, returning the edit that undoes it: the cells it changed,
+    /// as they were. Cells the mask forces keep their state, as they do under
+    /// [`set`](Self::set), and so are never in the inverse.
+    pub fn apply_edit(&mut self, edit: &Edit) -> Edit {
+        match edit {
+            Edit::Cells(cells) => self.set_cells(cells),
+            Edit::Region {
+                rect,
+                density,
+                seed,
+            } => self.rewrite(|game| game.fill_rect(*rect, *density, *seed)),
+            Edit::Pattern { pattern, origin } => self.rewrite(|game| {
+                game.insert_pattern(pattern, *origin);
+            }),
+            Edit::Clear => self.rewrite(|game| {
+                for i in 0..game.cell_count() {
+                    let pos = game.coords(i);
+                    game.set(pos, false);
+                }
+            }),
+            Edit::Randomize { seed, density } => self.rewrite(|game| game.fill(*density, *seed)),
+        }
+    }
+
+    /// Makes a change that may touch any cell, returning those it changed,
+    /// as they were.
+    fn rewrite(&mut self, change: impl FnOnce(&mut Self)) -> Edit {
+        let before: Vec<bool> = (0..self.cell_count()).map(|i| self.get_index(i)).collect();
+        change(self);
+        let changed = before
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, was)| (self.get_index(i) != was).then_some((self.coords(i), was)));
+        Edit::Cells(changed.collect())
+    }
+
+    /// Sets `cells` in order, returning each that ended up changed once, as
+    /// it was before the first of its entries.
+    fn set_cells(&mut self, cells: &[(GridPos, bool)]) -> Edit {
+        let (w, h) = self.size();
+        let mut seen = HashSet::new();
+        let mut before = Vec::new();
+        for &(pos, value) in cells.iter().filter(|(pos, _)| pos.0 < w && pos.1 < h) {
+            if seen.insert(pos) {
+                before.push((pos, self.get(pos)));
+            }
+            self.set(pos, value);
+        }
+        before.retain(|&(pos, was)| self.get(pos) != was);
+        Edit::Cells(before)
+    }
+
+    fn get_index(&self, i: usize) -> bool {
+        let pos = self.coords(i);
+        self.get(pos)
+    }
+}
+
+#[test]
+fn test_edits_round_trip_through_their_inverses() {
+    use crate::{testing, Mask, MaskCell};
+    use rand::Rng;
+
+    testing::check(|rng| {
+        let mut game = testing::game(rng);
+        let (w, h) = game.size();
+        let cells = (0..rng.gen_range(0..40))
+            .map(|_| {
+                let pos = GridPos(rng.gen_range(0..w + 2), rng.gen_range(0..h + 2));
+                (pos, rng.gen())
+            })
+            .collect();
+        let rect = Rect {
+            x: rng.gen_range(0..w),
+            y: rng.gen_range(0..h),
+            width: 5,
+            height: 4,
+        };
+        let glider = Pattern::named("glider").unwrap();
+        let edits = [
+            Edit::Cells(cells),
+            Edit::Region {
+                rect,
+                density: rng.gen(),
+                seed: rng.gen(),
+            },
+            Edit::Pattern {
+                pattern: glider,
+                origin: GridPos(rect.x, rect.y),
+            },
+            Edit::Clear,
+            Edit::Randomize {
+                seed: rng.gen(),
+                density: rng.gen(),
+            },
+        ];
+        for edit in &edits {
+            let (fingerprint, population) = (game.fingerprint(), game.population());
+            let mut edited = game.clone();
+            let inverse = edited.apply_edit(edit);
+            let Edit::Cells(changed) = &inverse else {
+                panic!("{inverse:?}")
+            };
+            // The inverse names just the cells that changed, each once.
+            let mut flipped = edited.clone();
+            flipped
+                .surface_mut()
+                .iter_mut()
+                .zip(game.surface())
+                .for_each(|(a, b)| *a ^= b);
+            assert_eq!(flipped.population(), changed.len(), "{edit:?}");
+            let killed = changed.iter().filter(|&&(_, was)| was).count();
+            assert_eq!(
+                edited.population() + killed,
+                population + changed.len() - killed
+            );
+
+            let redo = edited.apply_edit(&inverse);
+            assert_eq!(edited.fingerprint(), fingerprint, "{edit:?}");
+            assert_eq!(edited.population(), population);
+            // Undoing the undo makes the edit again.
+            edited.apply_edit(&redo);
+            game.apply_edit(edit);
+            assert_eq!(edited.fingerprint(), game.fingerprint(), "{edit:?}");
+        }
+    });
+
+    // Diffing two boards gives the edit from one to the other.
+    let mut game = GameOfLife::new_empty((5, 5));
+    let mut shifted = GameOfLife::from_ascii(".....\n..#..\n..#..\n..#..\n.....");
+    let between = Edit::between(&shifted, &game);
+    assert_eq!(
+        between,
+        Edit::Cells(vec![
+            (GridPos(2, 1), false),
+            (GridPos(2, 2), false),
+            (GridPos(2, 3), false)
+        ])
+    );
+    shifted.translate(1, 0);
+    game.apply_edit(&Edit::between(&game.clone(), &shifted));
+    assert_eq!(game.surface(), shifted.surface());
+
+    // The same cell twice ends as the last says, and only counts as changed
+    // if that differs from how it started.
+    let mut game = GameOfLife::new_empty((4, 4));
+    let inverse = game.apply_edit(&Edit::Cells(vec![
+        (GridPos(1, 1), true),
+        (GridPos(1, 1), false),
+        (GridPos(2, 2), true),
+    ]));
+    assert_eq!(game.population(), 1);
+    assert_eq!(inverse, Edit::Cells(vec![(GridPos(2, 2), false)]));
+    game.apply_edit(&inverse);
+    assert_eq!(game.population(), 0);
+    // Forced cells neither change nor show up in the inverse.
+    let mut mask = Mask::new((4, 4));
+    mask.set(0, 0, MaskCell::ForcedAlive);
+    game.set_mask(Some(mask));
+    assert!(game.apply_edit(&Edit::Clear).is_empty());
+    assert!(game.get(GridPos(0, 0)));
+}
@@ -0,0 +1,203 @@
+use std::{
+    io::{self, Write},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+};
+
+use crate::Settled;
+
+/// Something notable that happened to the board on show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Every cell died by this generation.
+    Extinction { generation: u64 },
+    /// The board started repeating with this period by this generation.
+    Stabilized { generation: u64, period: usize },
+}
+
+impl Event {
+    /// What `settled` means for a board at `generation`.
+    pub fn of(settled: Settled, generation: u64) -> Self {
+        match settled {
+            Settled::Died => Event::Extinction { generation },
+            Settled::Cycle(period) => Event::Stabilized { generation, period },
+        }
+    }
+
+    /// One line saying what happened, made of nothing but numbers and
+    /// fixed words, for a command to be handed.
+    pub fn message(&self) -> String {
+        match self {
+            Event::Extinction { generation } => format!("died out by generation {generation}"),
+            Event::Stabilized {
+                generation,
+                period: 1,
+            } => {
+                format!("became a still life by generation {generation}")
+            }
+            Event::Stabilized { generation, period } => {
+                format!("settled with period {period} by generation {generation}")
+            }
+        }
+    }
+
+    /// An OSC 9 sequence asking the terminal to raise a desktop notification
+    /// with the [message](Self::message).
+    pub fn osc9(&self) -> String {
+        format!("\x1b]9;life {}\x1b\\", self.message())
+    }
+}
+
+/// Gives feedback on [`Event`]s as they happen.
+pub trait Notifier {
+    fn notify(&mut self, event: Event);
+}
+
+/// Keeps the events instead, so that tests can see which fired.
+impl Notifier for Vec<Event> {
+    fn notify(&mut self, event: Event) {
+        self.push(event);
+    }
+}
+
+/// How events are fed back to the user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Feedback {
+    /// Not at all.
+    #[default]
+    Off,
+    /// By ringing the terminal's bell.
+    Bell,
+    /// By a desktop notification, which the terminal raises when sent an
+    /// [OSC 9](Event::osc9) between frames.
+    Desktop,
+    /// By running a program with these arguments, then the event's
+    /// [message](Event::message).
+    Command(Vec<String>),
+}
+
+impl Feedback {
+    pub fn notifier(&self) -> Box<dyn Notifier> {
+        match self {
+            // Desktop notifications go out with the frames, from whatever
+            // draws them.
+            Feedback::Off | Feedback::Desktop => Box::new(Silent),
+            Feedback::Bell => Box::new(Bell),
+            Feedback::Command(command) => Box::new(Spawn::new(command.clone())),
+        }
+    }
+}
+
+impl FromStr for Feedback {
+    type Err = String;
+
+    /// `off`, `bell`, `desktop`, or else a command split at whitespace, such as
+    /// `notify-send Life`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" | "none" => Ok(Feedback::Off),
+            "bell" => Ok(Feedback::Bell),
+            "desktop" => Ok(Feedback::Desktop),
+            "" => Err("feedback must be off, bell, desktop or a command, got nothing".to_string()),
+            command => Ok(Feedback::Command(
+                command.split_whitespace().map(str::to_string).collect(),
+            )),
+        }
+    }
+}
+
+/// Ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Notifier for Silent {
+    fn notify(&mut self, _: Event) {}
+}
+
+/// Rings the bell of the terminal on stdout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bell;
+
+impl Notifier for Bell {
+    fn notify(&mut self, _: Event) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+    }
+}
+
+/// Starts a program for each event without waiting for it, handing it the
+/// event's message as its last argument rather than through a shell.
+#[derive(Debug)]
+pub struct Spawn {
+    command: Vec<String>,
+    /// Programs started that haven't been seen to finish yet.
+    running: Vec<Child>,
+}
+
+impl Spawn {
+    /// Runs `command`'s first word with the rest as its first arguments.
+    pub fn new(command: Vec<String>) -> Self {
+        Self {
+            command,
+            running: Vec::new(),
+        }
+    }
+}
+
+impl Notifier for Spawn {
+    fn notify(&mut self, event: Event) {
+        // Finished programs are reaped so they don't linger.
+        self.running
+            .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+        let Some((program, args)) = self.command.split_first() else {
+            return;
+        };
+        let child = Command::new(program)
+            .args(args)
+            .arg(event.message())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        // A program that can't be started gives no feedback, and nothing
+        // more can be done about it while the board is on show.
+        if let Ok(child) = child {
+            self.running.push(child);
+        }
+    }
+}
+
+#[test]
+fn test_feedback_parsing_and_messages() {
+    assert_eq!("off".parse(), Ok(Feedback::Off));
+    assert_eq!("bell".parse(), Ok(Feedback::Bell));
+    assert_eq!("desktop".parse(), Ok(Feedback::Desktop));
+    let command = Feedback::Command(vec!["notify-send".into(), "Life".into()]);
+    assert_eq!(" notify-send  Life ".parse(), Ok(command));
+    assert!("".parse::<Feedback>().is_err());
+
+    assert_eq!(
+        Event::of(Settled::Died, 7).message(),
+        "died out by generation 7"
+    );
+    let still = Event::of(Settled::Cycle(1), 9);
+    assert_eq!(still.message(), "became a still life by generation 9");
+    let blinker = Event::of(Settled::Cycle(2), 12);
+    assert_eq!(
+        blinker,
+        Event::Stabilized {
+            generation: 12,
+            period: 2
+        }
+    );
+    assert_eq!(blinker.message(), "settled with period 2 by generation 12");
+    assert_eq!(
+        blinker.osc9(),
+        "\x1b]9;life settled with period 2 by generation 12\x1b\\"
+    );
+
+    // A program that isn't there is shrugged off.
+    let mut spawn = Spawn::new(vec!["/nonexistent/notifier".into()]);
+    spawn.notify(still);
+    assert!(spawn.running.is_empty());
+}
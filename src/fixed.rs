@@ -0,0 +1,196 @@
+use crate::{game::reflect, EdgeMode, GameOfLife, GridPos, Rule, MAX_RANGE};
+
+/// The most neighbors a rule can give a cell: all of the largest square.
+const MAX_NEIGHBORS: usize = (2 * MAX_RANGE + 1).pow(2) - 1;
+
+/// A board of `W` by `H` cells fixed when it is compiled, for boards small
+/// and known ahead of time. Its cells live inline, so it never allocates,
+/// even to step. It gets, sets and steps like a [`GameOfLife`], under the
+/// same [`Rule`]s and [`EdgeMode`]s, but has no mask or tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstGame<const W: usize, const H: usize> {
+    cells: [[bool; W]; H],
+    rule: Rule,
+    edges: EdgeMode,
+    generation: u64,
+}
+
+impl<const W: usize, const H: usize> ConstGame<W, H> {
+    /// A board of dead cells under Conway's rule, with nothing past its
+    /// edges.
+    pub const fn new() -> Self {
+        const {
+            assert!(
+                W > 0 && H > 0,
+                "a board is at least one cell across and down"
+            )
+        };
+        Self {
+            cells: [[false; W]; H],
+            rule: Rule::CONWAY,
+            edges: EdgeMode::Bounded,
+            generation: 0,
+        }
+    }
+
+    /// Width and height in cells.
+    pub const fn size(&self) -> (usize, usize) {
+        (W, H)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    pub fn edge_mode(&self) -> EdgeMode {
+        self.edges
+    }
+
+    pub fn set_edge_mode(&mut self, edges: EdgeMode) {
+        self.edges = edges;
+    }
+
+    /// # Panics
+    ///
+    /// If `(x, y)` is off the board.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.cells[y][x]
+    }
+
+    /// # Panics
+    ///
+    /// If `(x, y)` is off the board.
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        self.cells[y][x] = value;
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().flatten().filter(|&&cell| cell).count()
+    }
+
+    pub fn count_neighbors(&self, x: usize, y: usize) -> usize {
+        let (offsets, n) = self.offsets();
+        self.count_with(&offsets[..n], x, y)
+    }
+
+    /// Advances one generation under the board's [`Rule`].
+    pub fn step(&mut self) {
+        let (offsets, n) = self.offsets();
+        let mut next = [[false; W]; H];
+        for (y, row) in next.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let neighbors = self.count_with(&offsets[..n], x, y);
+                *cell = self.rule.next(self.cells[y][x], neighbors);
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    /// The same board as a [`GameOfLife`], which can be resized, masked and
+    /// shown.
+    pub fn to_game(&self) -> GameOfLife {
+        let mut game = GameOfLife::new_empty((W, H));
+        game.set_rule(self.rule);
+        game.set_edge_mode(self.edges);
+        for (y, row) in self.cells.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                game.set(GridPos(x, y), cell);
+            }
+        }
+        game
+    }
+
+    /// The rule's neighbor offsets, in an array rather than the `Vec` of
+    /// [`Rule::offsets`], and how many of them there are.
+    fn offsets(&self) -> ([(isize, isize); MAX_NEIGHBORS], usize) {
+        let mut offsets = [(0, 0); MAX_NEIGHBORS];
+        let mut n = 0;
+        for (slot, offset) in offsets.iter_mut().zip(self.rule.neighbor_offsets()) {
+            *slot = offset;
+            n += 1;
+        }
+        (offsets, n)
+    }
+
+    fn count_with(&self, offsets: &[(isize, isize)], x: usize, y: usize) -> usize {
+        let alive = |&(dx, dy): &(isize, isize)| match self.edges {
+            EdgeMode::Bounded => {
+                let i = x.checked_add_signed(dx).filter(|&i| i < W);
+                let j = y.checked_add_signed(dy).filter(|&j| j < H);
+                i.zip(j).is_some_and(|(i, j)| self.cells[j][i])
+            }
+            EdgeMode::Toroidal => {
+                let i = (x as isize + dx).rem_euclid(W as isize) as usize;
+                let j = (y as isize + dy).rem_euclid(H as isize) as usize;
+                self.cells[j][i]
+            }
+            EdgeMode::Mirror => {
+                self.cells[reflect(y as isize + dy, H)][reflect(x as isize + dx, W)]
+            }
+        };
+        offsets.iter().filter(|offset| alive(offset)).count()
+    }
+}
+
+impl<const W: usize, const H: usize> Default for ConstGame<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_const_game_steps_like_the_dynamic_one() {
+    use crate::Pattern;
+
+    let mut fixed = ConstGame::<9, 7>::new();
+    let glider = Pattern::named("glider").unwrap();
+    for &(x, y) in glider.cells() {
+        fixed.set(x + 1, y + 1, true);
+    }
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+        for rule in ["B3/S23", "B36/S23", "R2,C0,M0,S2..4,B3..4,NN"] {
+            let mut fixed = fixed;
+            fixed.set_rule(rule.parse().unwrap());
+            fixed.set_edge_mode(edges);
+            let mut game = fixed.to_game();
+            assert_eq!(
+                fixed.count_neighbors(2, 2),
+                game.count_neighbors(GridPos(2, 2))
+            );
+            for _ in 0..30 {
+                fixed.step();
+                game.step();
+                assert_eq!(
+                    fixed.to_game().surface(),
+                    game.surface(),
+                    "{rule}, {edges:?}"
+                );
+            }
+            assert_eq!(
+                (fixed.generation(), fixed.population()),
+                (30, game.population())
+            );
+        }
+    }
+    // A glider on a torus comes back where it started, having gone all the
+    // way around.
+    let mut torus = ConstGame::<8, 8>::default();
+    for &(x, y) in glider.cells() {
+        torus.set(x, y, true);
+    }
+    torus.set_edge_mode(EdgeMode::Toroidal);
+    let start = torus;
+    for _ in 0..32 {
+        torus.step();
+    }
+    assert_eq!(torus.cells, start.cells);
+}
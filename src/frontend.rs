@@ -0,0 +1,7331 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    io::{self, Read, Seek},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use braille::BRAILLE;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use termwiz::{
+    caps::Capabilities,
+    cell::AttributeChange,
+    color::{AnsiColor, ColorAttribute, SrgbaTuple},
+    input::{InputEvent, KeyCode, Modifiers, MouseButtons, MouseEvent},
+    surface::{Change, CursorVisibility, SequenceNo, Surface},
+    terminal::{buffered::BufferedTerminal, Terminal},
+};
+
+use crate::{
+    archive::FrameReader,
+    autosave::{Autosave, Autosaver},
+    capture::{encode_png, Capture, CaptureReport, Capturer},
+    challenge::{Challenge, HighScores, Stage},
+    chart::{self, Series},
+    feedback::{Event, Feedback, Notifier},
+    palette::parse_color,
+    parse_background_reply,
+    scene::Transform,
+    serve::{Feed, Server},
+    stats::{self, SoupConfig},
+    territory::{Phase, Player, Territory},
+    Annotation, Axis, CellInfo, Census, CycleDetector, Diagnostics, Divergence, EdgeMode, Edit,
+    Frame, FrameStats, FrameTimes, GameOfLife, GridPos, GridView, History, Injector, Palette,
+    Pattern, Rect, Rule, ScreenPos, Settled, Spaceship, StepTally, Theme, NEIGHBORS,
+};
+
+mod bindings;
+mod offscreen;
+mod picker;
+mod session;
+mod tabs;
+
+pub use self::{
+    bindings::{default_bindings, Action, KeyPreset},
+    offscreen::Offscreen,
+    session::{SEARCH_BUDGET, SEARCH_SOUPS},
+};
+pub use crate::coords::Scale;
+
+use self::{bindings::key_name, picker::picker_lines, session::Session, tabs::Tabs};
+
+/// The delay between generations until the user changes it.
+pub const DELAY: Duration = Duration::from_millis(50);
+
+/// The fastest and slowest tick delays speed changes go to.
+const MIN_DELAY: Duration = Duration::from_millis(5);
+pub(crate) const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Generations between spaceship scans while the annotations are shown.
+const SHIP_SCAN_INTERVAL: u64 = 16;
+
+/// How long the terminal size has to stay put before a resize is applied.
+const RESIZE_SETTLE: Duration = Duration::from_millis(100);
+
+/// Start and end the terminal's synchronized output mode (DECSET 2026), in
+/// which it holds back what it is sent and then shows it all at once.
+const BEGIN_SYNC: &str = "\x1b[?2026h";
+const END_SYNC: &str = "\x1b[?2026l";
+
+/// Save the terminal's title on its title stack, and put it back (XTWINOPS
+/// 22 and 23). Terminals without a stack ignore both.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// The quickest the terminal's title is changed, however fast the board is.
+const TITLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// [`MASKED`] and [`SEAM`] for [`Theme::Light`]: light greys instead.
+const LIGHT_MASKED: ColorAttribute = ColorAttribute::PaletteIndex(252);
+const LIGHT_SEAM: ColorAttribute = ColorAttribute::PaletteIndex(254);
+
+/// Background of cells the mask holds at a fixed state: a dark grey from the
+/// 256-color palette.
+const MASKED: ColorAttribute = ColorAttribute::PaletteIndex(236);
+
+/// Background of the outermost cells of a torus, where it wraps around.
+const SEAM: ColorAttribute = ColorAttribute::PaletteIndex(234);
+
+/// Color of the frame drawn around the world.
+const FRAME: ColorAttribute = ColorAttribute::PaletteIndex(240);
+
+/// Holds back a rapidly changing value until it has stopped changing for a
+/// while, e.g. the stream of sizes reported while a window is being dragged.
+#[derive(Debug)]
+pub struct Debounce<T> {
+    window: Duration,
+    pending: Option<(T, Instant)>,
+}
+
+impl<T> Debounce<T> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: None,
+        }
+    }
+
+    /// Replaces any pending value and restarts the quiet period.
+    pub fn push(&mut self, value: T, now: Instant) {
+        self.pending = Some((value, now));
+    }
+
+    /// Time left until the pending value settles, if there is one.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .as_ref()
+            .map(|(_, at)| self.window.saturating_sub(now.duration_since(*at)))
+    }
+
+    /// Takes the pending value once it has been quiet for the whole window.
+    pub fn settled(&mut self, now: Instant) -> Option<T> {
+        match self.remaining(now) {
+            Some(left) if left.is_zero() => self.pending.take().map(|(value, _)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps the terminal's title to the board on show, changing it no more
+/// than once a [`TITLE_INTERVAL`].
+#[derive(Debug, Default)]
+struct Retitle {
+    /// The title put up last, and when.
+    shown: Option<(String, Instant)>,
+}
+
+impl Retitle {
+    /// `title`, if it is to be put up now: it isn't the one up already, and
+    /// none was put up in the last [`TITLE_INTERVAL`].
+    fn due(&mut self, title: String, now: Instant) -> Option<String> {
+        if let Some((shown, at)) = &self.shown {
+            if *shown == title || now.duration_since(*at) < TITLE_INTERVAL {
+                return None;
+            }
+        }
+        self.shown = Some((title.clone(), now));
+        Some(title)
+    }
+}
+
+/// The terminal's title for a board at `generation` with `population` live
+/// cells, such as `life — gen 12,345 (pop 4,210)`.
+fn window_title(generation: u64, population: usize) -> String {
+    format!(
+        "life — gen {} (pop {})",
+        grouped(generation),
+        grouped(population as u64)
+    )
+}
+
+/// `n` with its digits in threes, split by commas.
+fn grouped(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// The screen characters a brush `radius` characters across from `at`
+/// covers: a disc, cut off at the top and left of the screen.
+fn brush(at: ScreenPos, radius: usize) -> impl Iterator<Item = ScreenPos> {
+    let r = radius as isize;
+    let shift = |from: u16, by: isize| u16::try_from(from as isize + by).ok();
+    (-r..=r)
+        .flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+        .filter(move |&(dx, dy)| dx * dx + dy * dy <= r * r)
+        .filter_map(move |(dx, dy)| Some(ScreenPos(shift(at.0, dx)?, shift(at.1, dy)?)))
+}
+
+/// The brush of `radius` in words, for telling the user it changed.
+fn brush_name(radius: usize) -> String {
+    match radius {
+        0 => "a single character".to_string(),
+        _ => format!("{radius} characters across from the middle"),
+    }
+}
+
+/// Turns the left button's presses, drags and releases over the board into
+/// what to paint: a click without moving toggles the character under the
+/// pointer, and a drag paints every character it crosses the opposite of how
+/// the first one was, so it either draws or erases throughout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stroke {
+    state: Option<StrokeState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StrokeState {
+    /// Pressed on this character and not moved off it yet.
+    Pressed(ScreenPos),
+    /// Dragging, painting cells alive or dead.
+    Painting(bool),
+    /// Pressed for something else, such as stamping a pattern, so nothing is
+    /// painted until the button is let go.
+    Held,
+}
+
+impl Stroke {
+    /// Handles the pointer at the screen character `at`, with the button
+    /// `down` or not, and returns the characters to paint and whether alive.
+    /// `alive` says whether a character shows any live cells.
+    pub fn pointer(
+        &mut self,
+        at: ScreenPos,
+        down: bool,
+        alive: impl Fn(ScreenPos) -> bool,
+    ) -> Vec<(ScreenPos, bool)> {
+        match (self.state, down) {
+            (None, true) => {
+                self.state = Some(StrokeState::Pressed(at));
+                Vec::new()
+            }
+            (Some(StrokeState::Pressed(first)), true) if first == at => Vec::new(),
+            (Some(StrokeState::Pressed(first)), true) => {
+                let value = !alive(first);
+                self.state = Some(StrokeState::Painting(value));
+                vec![(first, value), (at, value)]
+            }
+            (Some(StrokeState::Painting(value)), true) => vec![(at, value)],
+            (Some(StrokeState::Held), true) => Vec::new(),
+            (Some(StrokeState::Pressed(first)), false) => {
+                self.state = None;
+                vec![(first, !alive(first))]
+            }
+            (_, false) => {
+                self.state = None;
+                Vec::new()
+            }
+        }
+    }
+
+    /// Ignores the rest of the current press.
+    pub fn hold(&mut self) {
+        self.state = Some(StrokeState::Held);
+    }
+
+    /// Handles the button, `down` or not, for something other than painting,
+    /// returning whether it was just pressed; the rest of the press is
+    /// ignored.
+    pub fn press(&mut self, down: bool) -> bool {
+        let pressed = down && self.state.is_none();
+        self.state = down.then_some(StrokeState::Held);
+        pressed
+    }
+
+    /// Whether a drag is under way, so that what it paints next belongs
+    /// with what it painted already.
+    pub fn painting(&self) -> bool {
+        matches!(self.state, Some(StrokeState::Painting(_)))
+    }
+}
+
+/// Keys closer together than this are taken to be pasted rather than typed.
+const BURST_GAP: Duration = Duration::from_millis(5);
+
+/// Tells typed keys from the flood a terminal sends for pasted text when it
+/// doesn't mark pastes as such, a key at a time and all at once. Those that
+/// mark them hand over the whole paste as one event instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Burst {
+    /// When the last key came.
+    last: Option<Instant>,
+    /// Whether a key of this burst has been held back already.
+    held: bool,
+}
+
+impl Burst {
+    /// Notes a key that came at `now`, returning whether it is part of a
+    /// burst: whether it came hard on the heels of the key before. The first
+    /// key of a paste can't be told from a typed one.
+    fn key(&mut self, now: Instant) -> bool {
+        let burst = self
+            .last
+            .is_some_and(|last| now.saturating_duration_since(last) < BURST_GAP);
+        self.last = Some(now);
+        if !burst {
+            self.held = false;
+        }
+        burst
+    }
+
+    /// Notes that a key of the burst was held back, returning whether it was
+    /// the first, so that the user is told once a burst.
+    fn hold(&mut self) -> bool {
+        !std::mem::replace(&mut self.held, true)
+    }
+}
+
+/// A library pattern picked to stamp with the next click, as the wheel has
+/// turned and flipped it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stamp {
+    name: &'static str,
+    pattern: Pattern,
+    /// The cell of the pattern that goes under the pointer, which stays
+    /// under it as the pattern turns: at first its top-left.
+    anchor: (usize, usize),
+}
+
+impl Stamp {
+    fn new(name: &'static str, pattern: Pattern) -> Self {
+        Self {
+            name,
+            pattern,
+            anchor: (0, 0),
+        }
+    }
+
+    /// Turns or flips the pattern about the cell under the pointer.
+    fn transform(&mut self, transform: Transform) {
+        self.anchor = Pattern::transform_cell(self.pattern.size(), self.anchor, &[transform]);
+        self.pattern = self.pattern.transformed(&[transform]);
+    }
+
+    /// What to insert into a board of `size` with `edges`, and where, to
+    /// put the anchor on the cell `at`, along with how many live cells
+    /// that leaves off the board past its top or left edge. A torus takes
+    /// them round to the other side instead.
+    fn placement(
+        &self,
+        at: GridPos,
+        size: (usize, usize),
+        edges: EdgeMode,
+    ) -> (Pattern, GridPos, usize) {
+        let (GridPos(x, y), (ax, ay)) = (at, self.anchor);
+        if edges == EdgeMode::Toroidal {
+            let (w, h) = size;
+            let origin = GridPos((x + w - ax % w) % w, (y + h - ay % h) % h);
+            return (self.pattern.clone(), origin, 0);
+        }
+        // Rows and columns that would be above or left of the board.
+        let (dx, dy) = (ax.saturating_sub(x), ay.saturating_sub(y));
+        let (pw, ph) = self.pattern.size();
+        let cells = self
+            .pattern
+            .cells()
+            .iter()
+            .filter(|&&(cx, cy)| cx >= dx && cy >= dy);
+        let cells = cells.map(|&(cx, cy)| (cx - dx, cy - dy)).collect();
+        let cropped = Pattern::with_size((pw.saturating_sub(dx), ph.saturating_sub(dy)), cells);
+        let clipped = self.pattern.cells().len() - cropped.cells().len();
+        (
+            cropped,
+            GridPos(x.saturating_sub(ax), y.saturating_sub(ay)),
+            clipped,
+        )
+    }
+
+    /// The board cells the pattern's live cells would land on, for showing
+    /// where a click would stamp it.
+    fn ghost(&self, at: GridPos, size: (usize, usize), edges: EdgeMode) -> Vec<GridPos> {
+        let (pattern, GridPos(ox, oy), _) = self.placement(at, size, edges);
+        let (w, h) = size;
+        let cells = pattern
+            .cells()
+            .iter()
+            .map(|&(x, y)| GridPos(ox + x, oy + y));
+        match edges {
+            EdgeMode::Toroidal => cells.map(|GridPos(x, y)| GridPos(x % w, y % h)).collect(),
+            _ => cells.filter(|&GridPos(x, y)| x < w && y < h).collect(),
+        }
+    }
+}
+
+/// Shortest time between two frames, however often something asks for one.
+const RENDER_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Shortest time between two frames in reduced motion, however fast the
+/// board steps.
+const REDUCED_MOTION_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Something that needs the screen drawn again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The board stepped.
+    Step,
+    /// Cells were edited by hand.
+    Edit,
+    /// Anything else on screen changed: an overlay, the status, an animation.
+    View,
+}
+
+/// The reasons a frame is drawn for: all those since the last one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dirty {
+    pub step: bool,
+    pub edit: bool,
+    pub view: bool,
+}
+
+/// Folds requests to draw the screen into at most one frame per
+/// [`RENDER_INTERVAL`], counting both.
+#[derive(Debug, Default)]
+pub struct RenderGate {
+    pending: Option<Dirty>,
+    last: Option<Instant>,
+    /// Frames are kept at least this far apart too.
+    interval: Duration,
+    /// Frames drawn.
+    pub renders: u64,
+    /// Requests served by a frame that was already pending.
+    pub coalesced: u64,
+}
+
+impl RenderGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks for a frame, or adds `reason` to the one pending.
+    pub fn mark(&mut self, reason: Reason) {
+        if self.pending.is_some() {
+            self.coalesced += 1;
+        }
+        let dirty = self.pending.get_or_insert_with(Dirty::default);
+        match reason {
+            Reason::Step => dirty.step = true,
+            Reason::Edit => dirty.edit = true,
+            Reason::View => dirty.view = true,
+        }
+    }
+
+    /// Keeps frames at least `interval` apart, where that is longer than
+    /// [`RENDER_INTERVAL`].
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Time left until the pending frame may be drawn, if there is one.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.pending?;
+        Some(self.last.map_or(Duration::ZERO, |last| {
+            RENDER_INTERVAL
+                .max(self.interval)
+                .saturating_sub(now.duration_since(last))
+        }))
+    }
+
+    /// Takes the pending frame's reasons once it may be drawn, counting it as
+    /// drawn at `now`.
+    pub fn take(&mut self, now: Instant) -> Option<Dirty> {
+        if !self.remaining(now)?.is_zero() {
+            return None;
+        }
+        self.last = Some(now);
+        self.renders += 1;
+        self.pending.take()
+    }
+}
+
+/// The most ticks run back to back to catch up after the loop fell behind.
+/// Any more that are due are dropped and the schedule starts over from then.
+const MAX_CATCH_UP: u32 = 5;
+
+/// When the board steps: on a fixed schedule, each tick falling due one
+/// period after the previous one was due rather than after it ran, so time
+/// spent drawing and handling input doesn't add up into a slower rate.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    next_tick: Instant,
+    /// Ticks dropped since last taken.
+    dropped: u32,
+}
+
+impl Scheduler {
+    /// The first tick falls due `period` after `now`.
+    pub fn new(now: Instant, period: Duration) -> Self {
+        Self {
+            next_tick: now + period,
+            dropped: 0,
+        }
+    }
+
+    /// How many ticks of `period` have fallen due by `now`, moving the
+    /// schedule past them. A shorter period than the last takes effect at
+    /// once, and a longer one from the next tick on.
+    pub fn due(&mut self, now: Instant, period: Duration) -> u32 {
+        self.next_tick = self.next_tick.min(now + period);
+        let Some(behind) = now.checked_duration_since(self.next_tick) else {
+            return 0;
+        };
+        let due = behind.as_nanos() / period.as_nanos().max(1) + 1;
+        if due > MAX_CATCH_UP as u128 {
+            self.dropped += (due - MAX_CATCH_UP as u128).min(u32::MAX as u128) as u32;
+            self.next_tick = now + period;
+            return MAX_CATCH_UP;
+        }
+        self.next_tick += period * due as u32;
+        due as u32
+    }
+
+    /// Time left from `now` until the next tick.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.next_tick.saturating_duration_since(now)
+    }
+
+    /// Starts the schedule over from `now`, forgetting any ticks due, e.g.
+    /// after time stood still.
+    pub fn restart(&mut self, now: Instant, period: Duration) {
+        self.next_tick = now + period;
+    }
+
+    /// Ticks dropped since last asked.
+    pub fn take_dropped(&mut self) -> u32 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+/// How long a message such as the result of an export stays on screen.
+const NOTICE: Duration = Duration::from_secs(3);
+
+/// How many past messages [`Action::Messages`] shows.
+const NOTICE_LOG: usize = 8;
+
+/// The widest the brush the mouse wheel sizes goes, in characters from its
+/// middle.
+const MAX_BRUSH: usize = 8;
+
+/// The density a sweep starts from, and the highest it goes to before
+/// starting over.
+const SWEEP_START: f64 = 0.05;
+const SWEEP_END: f64 = 0.95;
+
+/// The longest oscillator period a sweep recognizes as settled.
+const SWEEP_MAX_PERIOD: usize = 32;
+
+/// The longest period the board on show is recognized as settling into, for
+/// the [`Summary`].
+const OUTCOME_MAX_PERIOD: usize = 32;
+
+/// A sweep gives up on a soup that hasn't settled after this many
+/// generations.
+const SWEEP_MAX_GENERATIONS: u64 = 10_000;
+
+/// How long the optional fade-out on quit takes, and in how many frames.
+const FADE_OUT: Duration = Duration::from_millis(500);
+const FADE_OUT_FRAMES: u32 = 10;
+
+/// How often frames are drawn between generations in smooth mode.
+pub const SMOOTH_FRAME: Duration = Duration::from_millis(33);
+
+/// How bright the freshest trail of a dead cell is drawn, from 0 to 1.
+const TRAIL_BRIGHTNESS: f64 = 0.5;
+
+/// How bright the dots over empty parts of the board are, from 0 to 1.
+const GRID_BRIGHTNESS: f64 = 0.12;
+
+/// Color of cells born in the last step while births and deaths flash.
+const BORN: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Yellow as u8);
+
+/// How bright the mark a cell leaves the frame after it dies is, from 0 to 1.
+const DIED_BRIGHTNESS: f64 = 0.35;
+
+/// The color of a cell alive now that was dead at the bookmark being diffed
+/// against.
+const APPEARED: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Fuchsia as u8);
+
+/// The color of the mark where a cell alive at the bookmark is now dead.
+const VANISHED: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Purple as u8);
+
+/// The background of the cell under the inspector's cursor.
+const CURSOR: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Blue as u8);
+
+/// The background of cells the next step kills, while the inspector shows
+/// what it will do; those it brings to life are on [`BORN`].
+const DYING: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Maroon as u8);
+
+/// The background of where a picked stamp would land.
+const GHOST: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Teal as u8);
+
+/// The background of cells whose neighbors the two counts disagree on.
+const MISMATCH: ColorAttribute = ColorAttribute::PaletteIndex(AnsiColor::Red as u8);
+
+/// How far left and right move the startup menu's density, and the density it
+/// starts at.
+const MENU_DENSITY_STEP: f64 = 0.05;
+const MENU_DENSITY: f64 = 0.3;
+
+/// How smooth mode shows the change from one generation to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smooth {
+    /// Dying cells fade out and newborn ones fade in, through shades of grey.
+    Fade,
+    /// Cells switch halfway through the tick, for terminals without the
+    /// shades to fade through.
+    Midpoint,
+    /// Each changing cell switches at its own moment through the tick, so
+    /// that a share of them goes over each frame, as if the rule were applied
+    /// to a few cells at a time. The board itself still steps whole.
+    Dissolve,
+}
+
+/// Modes for seeing the board more easily. Each overrides the options and
+/// toggles it constrains, as [`Effective`] resolves them, rather than
+/// changing them, so turning it off brings back what was asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Access {
+    /// Live cells in the full foreground color, black on white or white on
+    /// black as the theme suits, and none of the dim shades: no trails, no
+    /// dead-cell grid, no palette colors and no fading between generations.
+    pub high_contrast: bool,
+    /// Nothing that moves more than it must: frames at most four a second,
+    /// without smooth steps, flashes or the fade-out on quitting.
+    pub reduced_motion: bool,
+}
+
+/// What is drawn and how, once the [`Access`] modes have overridden the
+/// options and toggles that they constrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effective {
+    pub smooth: Option<Smooth>,
+    pub flash: bool,
+    pub fade_out: bool,
+    pub trail: bool,
+    pub grid: bool,
+    /// Live cells take their colors from the palette and tags, rather than
+    /// all being the foreground color.
+    pub colors: bool,
+    /// The shortest time between frames.
+    pub interval: Duration,
+}
+
+impl Effective {
+    /// What `options` and the toggles in `view` come to under the modes in
+    /// `view`.
+    pub fn resolve(options: &Options, view: &View) -> Self {
+        let Access {
+            high_contrast,
+            reduced_motion,
+        } = view.access;
+        let smooth = match options.smooth {
+            _ if reduced_motion => None,
+            // Switching at the midpoint needs no shades of grey, nor does
+            // dissolving.
+            Some(Smooth::Dissolve) => Some(Smooth::Dissolve),
+            Some(_) if high_contrast => Some(Smooth::Midpoint),
+            smooth => smooth,
+        };
+        Self {
+            smooth,
+            flash: view.flash && !reduced_motion,
+            fade_out: options.fade_out && !reduced_motion,
+            trail: view.trail.is_some() && !high_contrast,
+            grid: view.grid && !high_contrast,
+            colors: !high_contrast,
+            interval: match reduced_motion {
+                true => REDUCED_MOTION_INTERVAL,
+                false => RENDER_INTERVAL,
+            },
+        }
+    }
+}
+
+/// Settings for a density [`Sweep`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepConfig {
+    /// How much the density rises from one soup to the next.
+    pub step: f64,
+    /// Seed of the first soup; each one after uses the next.
+    pub seed: u64,
+}
+
+/// How one soup of a [`Sweep`] went, enough to replay it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoupRecord {
+    pub seed: u64,
+    pub density: f64,
+    /// Generations until it settled, or until the sweep gave up on it.
+    pub generations: u64,
+    /// `None` if the sweep gave up before it settled.
+    pub outcome: Option<Settled>,
+}
+
+impl SoupRecord {
+    /// Column names for [`csv`](Self::csv).
+    pub const CSV_HEADER: &'static str = "seed,density,generations,outcome";
+
+    /// One comma-separated line: seed, density, generations and `died`,
+    /// `period N` or `unsettled`.
+    pub fn csv(&self) -> String {
+        let outcome = match self.outcome {
+            Some(Settled::Died) => "died".to_string(),
+            Some(Settled::Cycle(period)) => format!("period {period}"),
+            None => "unsettled".to_string(),
+        };
+        format!(
+            "{},{:.2},{},{outcome}",
+            self.seed, self.density, self.generations
+        )
+    }
+}
+
+/// Settings for a [`Screensaver`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreensaverConfig {
+    /// Soups tried for each pick.
+    pub candidates: usize,
+    /// Generations each is run before it is judged.
+    pub burn_in: u64,
+    /// How likely each cell is to start alive.
+    pub density: f64,
+    /// Seed of the first soup tried; each one after uses the next.
+    pub seed: u64,
+}
+
+impl Default for ScreensaverConfig {
+    fn default() -> Self {
+        Self {
+            candidates: 8,
+            burn_in: 200,
+            density: 0.35,
+            seed: 0,
+        }
+    }
+}
+
+/// Keeps a lively board on show: soups are tried out of sight, each run
+/// through a burn-in, and the [`liveliest`](stats::liveliest) shown as its
+/// burn-in left it. The board is picked again in the background each time
+/// the one on show dies out or settles, staying on show until the next one
+/// is ready.
+#[derive(Debug)]
+pub struct Screensaver {
+    config: ScreensaverConfig,
+    /// How many picks came before the next one.
+    picks: u64,
+    detector: CycleDetector,
+    /// The pick being made out of sight.
+    pending: Option<Receiver<Option<(u64, GameOfLife, f64)>>>,
+    /// The seed and score of the board on show.
+    shown: Option<(u64, f64)>,
+}
+
+impl Screensaver {
+    pub fn new(config: ScreensaverConfig) -> Self {
+        Self {
+            config,
+            picks: 0,
+            detector: CycleDetector::new(OUTCOME_MAX_PERIOD),
+            pending: None,
+            shown: None,
+        }
+    }
+
+    /// The seed and score of the board on show, once one has been picked.
+    pub fn shown(&self) -> Option<(u64, f64)> {
+        self.shown
+    }
+
+    /// Puts the liveliest of the next soups on `game`, waiting for them.
+    pub fn pick(&mut self, game: &mut GameOfLife) {
+        let picked = stats::liveliest(&self.soups(game), threads());
+        self.show(game, picked);
+    }
+
+    /// Looks at `game` after a step: puts a pick on it once one is ready,
+    /// or starts one if it has settled.
+    pub fn advance(&mut self, game: &mut GameOfLife) {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(picked) => self.show(game, picked),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+            return;
+        }
+        if self.detector.observe(game).is_some() {
+            let soups = self.soups(game);
+            let (send, receive) = mpsc::channel();
+            // Nobody is left to tell if the app has gone.
+            std::thread::spawn(move || {
+                let _ = send.send(stats::liveliest(&soups, threads()));
+            });
+            self.pending = Some(receive);
+        }
+    }
+
+    /// The soups of the next pick, on `game`'s size, rule and edges.
+    fn soups(&mut self, game: &GameOfLife) -> SoupConfig {
+        let candidates = self.config.candidates.max(1);
+        let first = self.picks * candidates as u64;
+        self.picks += 1;
+        SoupConfig {
+            rule: game.rule(),
+            edges: game.edge_mode(),
+            size: game.size(),
+            density: self.config.density,
+            generations: self.config.burn_in,
+            seed: self.config.seed.wrapping_add(first),
+            soups: candidates,
+        }
+    }
+
+    fn show(&mut self, game: &mut GameOfLife, picked: Option<(u64, GameOfLife, f64)>) {
+        self.pending = None;
+        // The board on show keeps its generation count going.
+        let picked = picked.filter(|(_, board, _)| board.size() == game.size());
+        if let Some((seed, board, score)) = picked {
+            game.surface_mut().copy_from_slice(board.surface());
+            self.shown = Some((seed, score));
+        }
+        self.detector.reset();
+    }
+}
+
+/// Threads to pick a [`Screensaver`]'s soups with, and to search them
+/// with: one for each core.
+fn threads() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Watches soups of rising density: each time the board dies out or
+/// settles into still lifes and oscillators, it is reseeded a little denser,
+/// starting over from [`SWEEP_START`] after passing [`SWEEP_END`].
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    config: SweepConfig,
+    /// How many soups came before this one.
+    soup: u64,
+    /// How many steps above the start the density is.
+    level: usize,
+    detector: CycleDetector,
+    /// The generation the current soup was seeded at.
+    started: u64,
+}
+
+impl Sweep {
+    pub fn new(config: SweepConfig) -> Self {
+        Self {
+            config,
+            soup: 0,
+            level: 0,
+            detector: CycleDetector::new(SWEEP_MAX_PERIOD),
+            started: 0,
+        }
+    }
+
+    /// The density of the current soup.
+    pub fn density(&self) -> f64 {
+        SWEEP_START + self.level as f64 * self.config.step
+    }
+
+    /// The seed of the current soup.
+    pub fn seed(&self) -> u64 {
+        self.config.seed.wrapping_add(self.soup)
+    }
+
+    /// Fills `game` with the current soup.
+    pub fn reseed(&mut self, game: &mut GameOfLife) {
+        game.apply_edit(&Edit::Randomize {
+            seed: self.seed(),
+            density: self.density(),
+        });
+        self.started = game.generation();
+        self.detector.reset();
+    }
+
+    /// Looks at `game` after a step. Once the soup has settled, or run for
+    /// [`SWEEP_MAX_GENERATIONS`], moves on to the next one, reseeds `game`
+    /// with it, and returns how the last one went.
+    pub fn advance(&mut self, game: &mut GameOfLife) -> Option<SoupRecord> {
+        let generations = game.generation() - self.started;
+        let outcome = self.detector.observe(game);
+        if outcome.is_none() && generations < SWEEP_MAX_GENERATIONS {
+            return None;
+        }
+        let record = SoupRecord {
+            seed: self.seed(),
+            density: self.density(),
+            generations,
+            outcome,
+        };
+        self.soup += 1;
+        self.level += 1;
+        // A little slack so rounding doesn't skip the last density.
+        if self.density() > SWEEP_END + 1e-9 {
+            self.level = 0;
+        }
+        self.reseed(game);
+        Some(record)
+    }
+}
+
+/// Settings for a [`Frontend`] session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Clear the board over a short animation when quitting.
+    pub fade_out: bool,
+    /// What each key does; keys that aren't bound are ignored.
+    pub bindings: HashMap<KeyCode, Action>,
+    /// The preset [`Action::NextKeys`] moves on from.
+    pub keys: KeyPreset,
+    /// Generations kept for [`Action::DumpHistory`].
+    pub history: usize,
+    /// Where [`Action::DumpHistory`] writes.
+    pub history_path: PathBuf,
+    /// Where [`Action::ExportImage`] writes.
+    pub image_path: PathBuf,
+    /// The colors live cells are drawn in.
+    pub palette: Palette,
+    /// Shade the outermost cells of a torus so the seam is visible.
+    pub seam: bool,
+    /// The smallest terminal, in characters, worth drawing the world in;
+    /// below it only a warning is shown.
+    pub min_size: (usize, usize),
+    /// Reseed at rising densities each time the board settles, logging each
+    /// soup to stderr.
+    pub sweep: Option<SweepConfig>,
+    /// Show the liveliest of a few soups, picking again whenever the one on
+    /// show settles.
+    pub screensaver: Option<ScreensaverConfig>,
+    /// Animate each generation into the next over the tick instead of
+    /// jumping to it.
+    pub smooth: Option<Smooth>,
+    /// How many generations a dead cell leaves a trail for while trails are
+    /// shown; at least 1.
+    pub trail: u32,
+    /// The delay between generations to start with.
+    pub delay: Duration,
+    /// Slow down when a lot changes at once, from the start.
+    pub slow_motion: Option<SlowMotion>,
+    /// Bring cells to life along an edge after every step.
+    pub injector: Option<Injector>,
+    /// Save the board on show now and then, removing the saves on a clean
+    /// quit.
+    pub autosave: Option<Autosave>,
+    /// Save the board on show as an image every so many generations.
+    pub capture: Option<Capture>,
+    /// Stream the board on show to observers connecting to a Unix socket
+    /// here, for `--observe` to watch.
+    pub serve: Option<PathBuf>,
+    /// Keep stepping the universes in tabs not on show, each at its own
+    /// speed, rather than pausing them.
+    pub background_tabs: bool,
+    /// Quit after running this long.
+    pub run_for: Option<Duration>,
+    /// Quit after stepping this many generations.
+    pub generations: Option<u64>,
+    /// Quit once the board's [`generation`](GameOfLife::generation) counter
+    /// reaches this, however far along it started.
+    pub max_generation: Option<u64>,
+    /// Warnings to show as the run starts, about how the board was set up.
+    pub notices: Vec<String>,
+    /// Notes pinned to the board it starts with, such as a scene's.
+    pub annotations: Vec<Annotation>,
+    /// Whether the terminal's background is dark or light.
+    pub theme: Theme,
+    /// Send each frame as one synchronized update so it can't tear. Only for
+    /// terminals that [support it](supports_sync): others may print the
+    /// sequences or hang on to the frame.
+    pub sync: bool,
+    /// Show the running time and generations per second in the status bar.
+    pub clock: bool,
+    /// How the board on show dying out or settling is fed back.
+    pub feedback: Feedback,
+    /// The accessibility modes to start in.
+    pub access: Access,
+    /// Cells of the board kept off screen on every side, for patterns to
+    /// spread into before the board's edge affects them.
+    pub margin: usize,
+    /// Count the generations each cell is alive over the run, for a heat
+    /// map of where things happened.
+    pub activity: bool,
+    /// Keep the terminal's title to the generation and population of the
+    /// board on show, putting back the title it had on leaving.
+    pub title: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            fade_out: false,
+            bindings: default_bindings(),
+            keys: KeyPreset::Default,
+            history: 256,
+            history_path: "history.rle".into(),
+            image_path: "board.png".into(),
+            palette: Palette::default(),
+            seam: false,
+            min_size: (20, 10),
+            sweep: None,
+            screensaver: None,
+            smooth: None,
+            trail: 4,
+            delay: DELAY,
+            slow_motion: None,
+            injector: None,
+            autosave: None,
+            capture: None,
+            serve: None,
+            background_tabs: false,
+            run_for: None,
+            generations: None,
+            max_generation: None,
+            notices: Vec::new(),
+            annotations: Vec::new(),
+            theme: Theme::default(),
+            sync: false,
+            clock: false,
+            feedback: Feedback::Off,
+            access: Access::default(),
+            margin: 0,
+            activity: false,
+            title: false,
+        }
+    }
+}
+
+/// Slowing the ticks down for a while after a step changes a lot of cells,
+/// such as when big objects collide, so it can be watched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlowMotion {
+    /// Cells a step has to change to count as a burst.
+    pub threshold: usize,
+    /// How many times longer ticks get.
+    pub factor: f64,
+    /// How long ticks stay slow after a burst. They take as long again to
+    /// speed back up.
+    pub duration: Duration,
+}
+
+impl Default for SlowMotion {
+    fn default() -> Self {
+        Self {
+            threshold: 200,
+            factor: 4.0,
+            duration: Duration::from_secs(3),
+        }
+    }
+}
+
+impl SlowMotion {
+    /// The time between ticks `since` the last burst, given the `target` the
+    /// speed is otherwise set to: `factor` times it at first, then easing
+    /// back to it.
+    pub fn delay(&self, target: Duration, since: Option<Duration>) -> Duration {
+        let Some(since) = since else {
+            return target;
+        };
+        let ramp = self.duration.as_secs_f64();
+        let t = match since.as_secs_f64() - ramp {
+            held if held <= 0.0 => 1.0,
+            // Smoothstep, so the speed doesn't jump at either end.
+            ramping if ramping < ramp => {
+                let x = 1.0 - ramping / ramp;
+                x * x * (3.0 - 2.0 * x)
+            }
+            _ => 0.0,
+        };
+        target.mul_f64(1.0 + (self.factor.max(1.0) - 1.0) * t)
+    }
+
+    /// Whether ticks are still slowed `since` the last burst.
+    pub fn active(&self, since: Option<Duration>) -> bool {
+        since.is_some_and(|since| since < self.duration * 2)
+    }
+}
+
+/// How much a message for the user matters, which sets its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn color(self) -> ColorAttribute {
+        match self {
+            Level::Info => ColorAttribute::Default,
+            Level::Warn => AnsiColor::Yellow.into(),
+            Level::Error => AnsiColor::Red.into(),
+        }
+    }
+
+    /// What a message at this level starts with when listed.
+    fn prefix(self) -> &'static str {
+        match self {
+            Level::Info => "",
+            Level::Warn => "warning: ",
+            Level::Error => "error: ",
+        }
+    }
+}
+
+/// Passing messages for the user, such as where an export went. The newest
+/// is shown for [`NOTICE`], cutting short any shown before it, and the last
+/// [`NOTICE_LOG`] are kept to look back on.
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    /// Newest last.
+    log: VecDeque<(Level, String)>,
+    /// When the newest message stops being shown, while it is.
+    until: Option<Instant>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: String, now: Instant) {
+        if self.log.len() == NOTICE_LOG {
+            self.log.pop_front();
+        }
+        self.log.push_back((level, message));
+        self.until = Some(now + NOTICE);
+    }
+
+    /// The message shown at `now`, if any.
+    pub fn current(&self, now: Instant) -> Option<(Level, &str)> {
+        match (self.until, self.log.back()) {
+            (Some(until), Some((level, message))) if now < until => Some((*level, message)),
+            _ => None,
+        }
+    }
+
+    /// How long the message shown at `now` has left, so the screen can be
+    /// redrawn without it on time and no later.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.current(now)?;
+        self.until.map(|until| until - now)
+    }
+
+    /// Stops showing a message whose time is up at `now`, and says whether
+    /// there was one, so the screen can be redrawn without it.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let expired = self.until.is_some_and(|until| now >= until);
+        if expired {
+            self.until = None;
+        }
+        expired
+    }
+
+    /// The kept messages, newest first.
+    pub fn recent(&self) -> impl Iterator<Item = (Level, &str)> {
+        self.log
+            .iter()
+            .rev()
+            .map(|(level, message)| (*level, message.as_str()))
+    }
+}
+
+/// How far a [`Frontend`] session got, for the report printed after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// Generations stepped forward; stepping back doesn't take any off.
+    pub generations: u64,
+    /// Live cells at the end.
+    pub population: usize,
+    /// The most live cells the board on show had, at the start or after a
+    /// step.
+    pub peak_population: usize,
+    /// How the board on show had settled as of its last step, if it had.
+    pub settled: Option<Settled>,
+    /// What came of capturing frames, if they were.
+    pub captures: Option<CaptureReport>,
+    /// When the run started, by the wall clock.
+    pub started_at: SystemTime,
+    /// Time spent running, not paused.
+    pub elapsed: Duration,
+    /// `(generation, population)` of the board on show as it started and
+    /// after its steps, thinned out evenly over a long run.
+    pub populations: Vec<(u64, usize)>,
+    /// The size of the board on show and its
+    /// [activity](GameOfLife::activity_rgba) heat map, if it was counted.
+    pub activity: Option<((usize, usize), Vec<u8>)>,
+}
+
+impl Summary {
+    /// One line giving the generations run, the final population, the average
+    /// ticks per second and the `seed` random fills were made from.
+    pub fn report(&self, seed: u64) -> String {
+        let (secs, tps) = (self.elapsed.as_secs_f64(), self.rate());
+        format!(
+            "{} generations in {secs:.1} s ({tps:.1} tps), final population {}, seed {seed}",
+            self.generations, self.population
+        )
+    }
+
+    /// Writes the [populations](Self::populations) to `path` as an SVG line
+    /// chart.
+    pub fn save_population_svg(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, chart::svg(&self.populations))
+    }
+
+    /// Writes the activity heat map to `path` as a PNG.
+    pub fn save_activity_png(&self, path: &Path) -> io::Result<()> {
+        let Some((size, rgba)) = &self.activity else {
+            return Err(io::Error::other("activity wasn't counted"));
+        };
+        std::fs::write(path, encode_png(*size, rgba))
+    }
+
+    /// Generations per second of running; zero if no time went by.
+    fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.generations as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// A table of what the run came to, for soup searching: the generations
+    /// run, the peak and final populations, how the board settled and the
+    /// `seed` random fills were made from.
+    pub fn stats(&self, seed: u64) -> String {
+        let outcome = match self.settled {
+            Some(Settled::Died) => "died out".to_string(),
+            Some(Settled::Cycle(1)) => "still life".to_string(),
+            Some(Settled::Cycle(period)) => format!("oscillating with period {period}"),
+            None => "still changing".to_string(),
+        };
+        [
+            format!("generations       {}", self.generations),
+            format!("peak population   {}", self.peak_population),
+            format!("final population  {}", self.population),
+            format!("outcome           {outcome}"),
+            format!(
+                "running time      {:.1} s, {:.1} gen/s",
+                self.elapsed.as_secs_f64(),
+                self.rate()
+            ),
+            format!("started           {}", crate::utc_stamp(self.started_at)),
+            format!("seed              {seed}"),
+        ]
+        .join("\n")
+    }
+}
+
+/// Where `:save` writes the board unless told otherwise.
+pub const SAVE_PATH: &str = "board.rle";
+
+/// Something typed at the command prompt, such as `:rule B36/S23`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// What a key could do too: `:q`, `:help`, `:pause`, `:step`, `:clear`
+    /// and `:reseed`.
+    Action(Action),
+    /// `:rule B36/S23`
+    Rule(Rule),
+    /// `:load glider.rle` clears the board for the pattern in the file,
+    /// centered, and takes its rule.
+    Load(PathBuf),
+    /// `:save`, or `:w`, writes the board as RLE to the file named, or
+    /// [`SAVE_PATH`].
+    Save(Option<PathBuf>),
+    /// `:note gun #1` pins a note to the cell under the cursor or the
+    /// pointer.
+    Note(String),
+    /// `:set density 0.4` reseeds at that density from then on; `:set delay
+    /// 50` waits that many milliseconds between generations; `:set
+    /// note-color red` draws new notes in red.
+    SetDensity(f64),
+    SetDelay(Duration),
+    SetNoteColor(String),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    /// The command in `s`, with or without the `:` in front.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix(':').unwrap_or(s);
+        let mut words = s.split_whitespace();
+        let name = words.next().ok_or("no command")?;
+        let args = words.collect::<Vec<_>>();
+        let takes = |n: usize, usage: &str| match args.len() == n {
+            true => Ok(()),
+            false => Err(format!("usage: :{usage}")),
+        };
+        let action = |action| takes(0, name).map(|()| Command::Action(action));
+        match name {
+            "q" | "quit" => action(Action::Quit),
+            "help" => action(Action::Help),
+            "pause" => action(Action::TogglePause),
+            "step" => action(Action::Step),
+            "clear" => action(Action::Clear),
+            "reseed" => action(Action::Reseed),
+            "rule" => {
+                takes(1, "rule B3/S23")?;
+                Ok(Command::Rule(Rule::parse(args[0])?))
+            }
+            "load" => {
+                takes(1, "load FILE")?;
+                Ok(Command::Load(args[0].into()))
+            }
+            "note" => match s[name.len()..].trim() {
+                "" => Err("usage: :note TEXT".to_string()),
+                text => Ok(Command::Note(text.to_string())),
+            },
+            "save" | "w" => match args[..] {
+                [] => Ok(Command::Save(None)),
+                [path] => Ok(Command::Save(Some(path.into()))),
+                _ => Err(format!("usage: :{name} [FILE]")),
+            },
+            "set" => match args[..] {
+                ["density", value] => match value.parse::<f64>() {
+                    Ok(density) if (0.0..=1.0).contains(&density) => {
+                        Ok(Command::SetDensity(density))
+                    }
+                    _ => Err(format!("density must be from 0 to 1, got {value:?}")),
+                },
+                ["delay", value] => match value.parse::<u64>() {
+                    Ok(ms) => {
+                        let delay = Duration::from_millis(ms).clamp(MIN_DELAY, MAX_DELAY);
+                        Ok(Command::SetDelay(delay))
+                    }
+                    _ => Err(format!(
+                        "delay must be a number of milliseconds, got {value:?}"
+                    )),
+                },
+                ["note-color", value] => {
+                    parse_color(value)?;
+                    Ok(Command::SetNoteColor(value.to_ascii_lowercase()))
+                }
+                [setting, _] => Err(format!(
+                    "unknown setting {setting:?}, expected density, delay or note-color"
+                )),
+                _ => {
+                    Err("usage: :set density 0.4, :set delay 50 or :set note-color red".to_string())
+                }
+            },
+            _ => Err(format!("unknown command {name:?}")),
+        }
+    }
+}
+
+pub trait Frontend {
+    /// Runs until the user quits or a limit in `options` is reached.
+    fn run(&mut self, game: &mut GameOfLife, options: &Options) -> Result<Summary, Box<dyn Error>>;
+}
+
+/// The part of the world that is currently on screen, plus overlay toggles.
+#[derive(Debug, Clone, Default)]
+pub struct View {
+    /// Top-left world cell shown at the top-left of the screen.
+    pub offset: GridPos,
+    pub minimap: bool,
+    /// The census being shown, taken when the overlay was opened.
+    pub census: Option<Census>,
+    /// Spaceships being annotated, and the generation they were found in.
+    pub spaceships: Option<(Vec<Spaceship>, u64)>,
+    /// Frame timings, collected only while the overlay is shown.
+    pub diagnostics: Option<Diagnostics>,
+    /// Shade the seam of a toroidal world.
+    pub seam: bool,
+    /// Generations since each cell was last alive, kept only while trails
+    /// are shown.
+    pub trail: Option<Vec<u32>>,
+    /// Flash the cells each step brings to life or kills, for a frame.
+    pub flash: bool,
+    /// Tint the cells that differ from the bookmark.
+    pub diff: bool,
+    /// Draw only the live cells next to a dead one, so that the edges of
+    /// dense regions stand out instead of a solid field.
+    pub outline: bool,
+    /// Dot the characters showing only dead cells, faintly, so that the
+    /// extent of a sparse board can be seen.
+    pub grid: bool,
+    /// Mark the cells whose [neighbor counts](GameOfLife::neighbor_mismatches)
+    /// come out differently counted the two ways.
+    pub check_neighbors: bool,
+    /// The cell the inspector's cursor is on, while it is open.
+    pub cursor: Option<GridPos>,
+    /// Leave the notes pinned to the board undrawn.
+    pub hide_notes: bool,
+    /// Cells on every side of the world that are never shown.
+    pub margin: usize,
+    /// The accessibility modes on now.
+    pub access: Access,
+    /// The background colors are picked to suit.
+    pub theme: Theme,
+}
+
+impl View {
+    /// Moves the view by `delta` cells, keeping it within a world of `world` cells
+    /// shown through a screen of `visible` cells.
+    pub fn pan(&mut self, delta: (isize, isize), world: (usize, usize), visible: (usize, usize)) {
+        self.offset.0 = self.offset.0.saturating_add_signed(delta.0);
+        self.offset.1 = self.offset.1.saturating_add_signed(delta.1);
+        self.clamp(world, visible);
+    }
+
+    pub fn clamp(&mut self, world: (usize, usize), visible: (usize, usize)) {
+        let (right, bottom) = self.extent(world);
+        let m = self.margin;
+        self.offset.0 = self.offset.0.min(right.saturating_sub(visible.0)).max(m);
+        self.offset.1 = self.offset.1.min(bottom.saturating_sub(visible.1)).max(m);
+    }
+
+    /// How far across and down a world of `world` cells is shown: all of it
+    /// but the [margin](Self::margin) past its right and bottom.
+    pub fn extent(&self, world: (usize, usize)) -> (usize, usize) {
+        (
+            world.0.saturating_sub(self.margin),
+            world.1.saturating_sub(self.margin),
+        )
+    }
+}
+
+pub trait Renderer {
+    type Term: Terminal;
+
+    fn screen(&mut self) -> &mut BufferedTerminal<Self::Term>;
+
+    /// The screen's size in characters.
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Number of grid cells that fit on screen, inside the frame if there is
+    /// one.
+    fn size(&self) -> (usize, usize) {
+        self.cells_for(self.dimensions())
+    }
+
+    /// How grid cells map onto terminal characters: the renderer's cell
+    /// footprint. Sizing, the minimum screen for a board and the mapping
+    /// between characters and cells all follow from it and the
+    /// [`border`](Self::border), so a renderer's footprint is set here and
+    /// nowhere else.
+    fn scale(&self) -> Scale;
+
+    /// Number of grid cells that would fit on a screen of `dimensions`
+    /// characters.
+    fn cells_for(&self, dimensions: (usize, usize)) -> (usize, usize) {
+        self.scale()
+            .cells_in(drawable_area(dimensions, self.border()))
+    }
+
+    /// The smallest screen, in characters, that shows a world of `cells`
+    /// cells whole.
+    fn chars_for(&self, cells: (usize, usize)) -> (usize, usize) {
+        let (w, h) = self.scale().chars_for(cells);
+        let frame = if self.border() { 2 } else { 0 };
+        (w + frame, h + frame)
+    }
+
+    /// Whether a frame is drawn around the world, taking up the outermost
+    /// characters of the screen.
+    fn border(&self) -> bool {
+        false
+    }
+
+    /// Draws whatever the renderer was given to draw over each frame of a
+    /// [`Frontend`] run, just before it is flushed. Nothing by default.
+    fn draw_overlay(&mut self, _game: &GameOfLife) {}
+
+    /// Where on screen the world is drawn, in terminal characters.
+    fn area(&mut self) -> Rect {
+        let border = self.border();
+        drawable_area(self.screen().dimensions(), border)
+    }
+
+    fn render(&mut self, grid: &dyn GridView, view: &View) {
+        self.render_tinted(grid, view, &|_, _| ColorAttribute::Default);
+    }
+
+    /// Renders like [`render`](Self::render), drawing each character in the
+    /// color `tint` gives for a live cell the character shows.
+    fn render_tinted(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+        let area = self.area();
+        if self.border() {
+            draw_frame(self.screen(), area);
+        }
+        self.render_region(grid, view, area, tint);
+    }
+
+    /// Renders the top-left of `grid` into `rect`, in terminal characters,
+    /// leaving the rest of the screen alone.
+    fn render_into(&mut self, grid: &dyn GridView, rect: Rect) {
+        self.render_region(grid, &View::default(), rect, &|_, _| {
+            ColorAttribute::Default
+        });
+    }
+
+    /// Renders like [`render_tinted`](Self::render_tinted), but only into
+    /// `region` of the screen, whose top-left character shows the view's
+    /// offset. `region` is in terminal characters and must fit on screen.
+    fn render_region(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        region: Rect,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    );
+
+    /// The cells of a world of size `world` shown by the terminal character
+    /// at `at`; none for the frame.
+    fn cells_under(&self, world: (usize, usize), view: &View, at: ScreenPos) -> Vec<GridPos> {
+        let area = drawable_area(self.dimensions(), self.border());
+        cells_at(area, self.scale(), world, view, at)
+    }
+
+    /// The cell of a world of size `world` under the terminal character at
+    /// `at`, such as where a mouse event happened: the top-left of those it
+    /// shows. `None` off the world, in the frame or past the last whole block
+    /// of characters, rather than the nearest cell.
+    fn screen_to_grid(&self, world: (usize, usize), view: &View, at: ScreenPos) -> Option<GridPos> {
+        self.cells_under(world, view, at).first().copied()
+    }
+
+    /// The terminal characters showing `cell` of a world of size `world`,
+    /// the other way from [`screen_to_grid`](Self::screen_to_grid); `None`
+    /// if it isn't on screen.
+    fn grid_to_screen(&self, world: (usize, usize), view: &View, cell: GridPos) -> Option<Rect> {
+        let area = drawable_area(self.dimensions(), self.border());
+        chars_at(area, self.scale(), world, view, cell)
+    }
+
+    /// The cells painting the character at `at` alive or dead sets: all of
+    /// those under it.
+    fn paint(
+        &self,
+        game: &GameOfLife,
+        view: &View,
+        at: ScreenPos,
+        value: bool,
+    ) -> Vec<(GridPos, bool)> {
+        let under = self.cells_under(game.size(), view, at);
+        under.into_iter().map(|pos| (pos, value)).collect()
+    }
+}
+
+impl<R: Renderer + ?Sized> Renderer for &mut R {
+    type Term = R::Term;
+
+    fn screen(&mut self) -> &mut BufferedTerminal<Self::Term> {
+        (**self).screen()
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (**self).dimensions()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (**self).size()
+    }
+
+    fn scale(&self) -> Scale {
+        (**self).scale()
+    }
+
+    fn cells_for(&self, dimensions: (usize, usize)) -> (usize, usize) {
+        (**self).cells_for(dimensions)
+    }
+
+    fn chars_for(&self, cells: (usize, usize)) -> (usize, usize) {
+        (**self).chars_for(cells)
+    }
+
+    fn border(&self) -> bool {
+        (**self).border()
+    }
+
+    fn draw_overlay(&mut self, game: &GameOfLife) {
+        (**self).draw_overlay(game);
+    }
+
+    fn area(&mut self) -> Rect {
+        (**self).area()
+    }
+
+    fn render_tinted(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+        (**self).render_tinted(grid, view, tint);
+    }
+
+    fn render_region(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        region: Rect,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+        (**self).render_region(grid, view, region, tint);
+    }
+
+    fn cells_under(&self, world: (usize, usize), view: &View, at: ScreenPos) -> Vec<GridPos> {
+        (**self).cells_under(world, view, at)
+    }
+
+    fn screen_to_grid(&self, world: (usize, usize), view: &View, at: ScreenPos) -> Option<GridPos> {
+        (**self).screen_to_grid(world, view, at)
+    }
+
+    fn grid_to_screen(&self, world: (usize, usize), view: &View, cell: GridPos) -> Option<Rect> {
+        (**self).grid_to_screen(world, view, cell)
+    }
+}
+
+/// A [`Frontend`] session driven from outside, so that a program with its
+/// own terminal and event loop can show a world in part of its screen. The
+/// host hands it input events as they come, [`tick`](Self::tick)s it at
+/// least as often as [`timeout`](Self::timeout) asks, and copies frames out
+/// with [`render_into`](Self::render_into). [`Frontend::run`] is an app
+/// driven by a loop of its own.
+pub struct App<R: Renderer> {
+    renderer: R,
+    game: GameOfLife,
+    options: Options,
+    session: Session,
+    tabs: Tabs,
+    autosave: Option<Autosaver>,
+    capture: Option<Capturer>,
+    server: Option<Server>,
+    /// Whether the run ended by choice, rather than by losing the terminal.
+    clean: bool,
+    resize: Debounce<(usize, usize)>,
+    scheduler: Scheduler,
+    /// Steps run since the last frame was drawn...
+    unframed: u32,
+    /// ...and how long they took, while the timings are shown.
+    step_time: Option<Duration>,
+    gate: RenderGate,
+    started: Instant,
+    stepped: u64,
+    /// The most live cells seen on the board on show.
+    peak: usize,
+    /// The population of the board on show over the run.
+    populations: Series,
+    /// Watches the board on show for it settling, as of its last step.
+    detector: CycleDetector,
+    settled: Option<Settled>,
+    /// Told when the board on show settles, as [`Options::feedback`] says.
+    notifier: Box<dyn Notifier>,
+    /// Events for [`Feedback::Desktop`] not yet sent, for going out after
+    /// the next frame rather than in the middle of one.
+    desktop: Vec<Event>,
+    title: Retitle,
+}
+
+impl App<BlockRenderer<Offscreen>> {
+    /// Runs `game` as `options` say, drawn in blocks on a screen of its own
+    /// that [`render_into`](Self::render_into) copies out of.
+    pub fn new(game: GameOfLife, options: Options) -> Result<Self, Box<dyn Error>> {
+        let (cols, rows) = FALLBACK_TERMINAL;
+        let screen = BufferedTerminal::new(Offscreen { size: (cols, rows) })?;
+        Ok(Self::with_renderer(
+            BlockRenderer::new(screen)?,
+            game,
+            options,
+        ))
+    }
+}
+
+impl<R: Renderer> App<R> {
+    /// Runs `game` as `options` say, drawn by `renderer` on its screen.
+    pub fn with_renderer(renderer: R, mut game: GameOfLife, options: Options) -> Self {
+        let mut session = Session::new(&options);
+        session.annotations = options.annotations.clone();
+        if options.activity {
+            game.enable_activity();
+        }
+        for notice in &options.notices {
+            session.notify(Level::Warn, notice.clone());
+        }
+        let autosave = options
+            .autosave
+            .clone()
+            .map(|config| Autosaver::new(config, &game));
+        let mut capture = options.capture.clone().and_then(|config| {
+            let dir = config.dir.display().to_string();
+            Capturer::new(config)
+                .inspect_err(|err| session.notify(Level::Error, format!("{dir}: {err}")))
+                .ok()
+        });
+        let server = options.serve.as_ref().and_then(|path| {
+            Server::bind(path)
+                .inspect_err(|err| {
+                    session.notify(Level::Error, format!("{}: {err}", path.display()))
+                })
+                .ok()
+        });
+        if let Some(sweep) = &mut session.sweep {
+            sweep.reseed(&mut game);
+            eprintln!("# {}", session.clock.provenance(Instant::now()));
+            eprintln!("{}", SoupRecord::CSV_HEADER);
+        }
+        if let Some(screensaver) = &mut session.screensaver {
+            screensaver.pick(&mut game);
+        }
+        session.history.push(&game);
+        // The board as it starts is the first capture.
+        if let Some(capturer) = &mut capture {
+            capturer.observe(&game);
+        }
+        let now = Instant::now();
+        let scheduler = Scheduler::new(now, session.tick_delay());
+        let peak = game.population();
+        let mut populations = Series::new();
+        populations.push(game.generation(), peak);
+        let notifier = options.feedback.notifier();
+        Self {
+            renderer,
+            game,
+            options,
+            session,
+            tabs: Tabs::new(),
+            autosave,
+            capture,
+            server,
+            clean: true,
+            resize: Debounce::new(RESIZE_SETTLE),
+            scheduler,
+            unframed: 0,
+            step_time: None,
+            gate: RenderGate::new(),
+            started: now,
+            stepped: 0,
+            peak,
+            populations,
+            detector: CycleDetector::new(OUTCOME_MAX_PERIOD),
+            settled: None,
+            notifier,
+            desktop: Vec::new(),
+            title: Retitle::default(),
+        }
+    }
+
+    /// The board on show.
+    pub fn game(&self) -> &GameOfLife {
+        &self.game
+    }
+
+    /// Acts on `event`, breaking once the user has quit. Mouse events are
+    /// taken to be relative to the top-left of the app's screen.
+    pub fn handle_event(&mut self, event: InputEvent) -> Result<ControlFlow<()>, Box<dyn Error>> {
+        let Self {
+            renderer,
+            game,
+            options,
+            session,
+            tabs,
+            resize,
+            gate,
+            ..
+        } = self;
+        let burst = matches!(event, InputEvent::Key(_)) && session.burst.key(Instant::now());
+        match event {
+            // Ctrl-L, as elsewhere, redraws a garbled screen in full.
+            InputEvent::Key(k)
+                if k.key == KeyCode::Char('l') && k.modifiers.contains(Modifiers::CTRL) =>
+            {
+                renderer.screen().repaint()?;
+            }
+            InputEvent::Key(_) | InputEvent::Paste(_) if session.help.is_some() => {
+                session.dismiss_help(renderer)?;
+            }
+            InputEvent::Paste(text) => {
+                session.paste(&text);
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if session.command.is_some() => {
+                if let Some(line) = session.command_key(k.key) {
+                    session.run_command(&line, renderer, game, options)?;
+                    if session.quit {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if tabs.key(k.key, k.modifiers, game, session, options) => {
+                session.view.clamp(game.size(), renderer.size());
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if session.export_key(k.key) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) if session.search_key(k.key) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) if session.scrub_key(k.key, game) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) if session.picker_key(k.key) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) if session.rule_key(k.key, game) => {
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if session.region_key(k.key, game) => {
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if session.shift_key(k.key, k.modifiers, game) => {
+                gate.mark(Reason::Edit);
+            }
+            InputEvent::Key(k) if session.note_key(k.key) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) if session.cursor_key(k.key, game, renderer.size()) => {
+                gate.mark(Reason::View);
+            }
+            InputEvent::Key(k) => {
+                if let Some(&Action::NextKeys) = options.bindings.get(&k.key) {
+                    options.keys = options.keys.next();
+                    options.bindings = options.keys.bindings();
+                    let message = format!("Switched to the {} keys", options.keys.name());
+                    session.notify(Level::Info, message);
+                    gate.mark(Reason::View);
+                } else if let Some(&action) = options.bindings.get(&k.key) {
+                    // Pasted text mustn't quit or wipe the board a key at a time.
+                    if burst && matches!(action, Action::Quit | Action::Clear) {
+                        if session.burst.hold() {
+                            let message = format!(
+                                "Didn't {} for a key that looks pasted",
+                                action.description()
+                            );
+                            session.notify(Level::Warn, message);
+                            gate.mark(Reason::View);
+                        }
+                        return Ok(ControlFlow::Continue(()));
+                    }
+                    session.handle_action(action, renderer, game, options)?;
+                    if session.quit {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                    gate.mark(Reason::View);
+                }
+            }
+            InputEvent::Resized { cols, rows } => {
+                resize.push((cols, rows), Instant::now());
+            }
+            // Clicks and drags on the scrubber's bar pick a generation.
+            InputEvent::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons,
+                ..
+            }) if mouse_buttons.contains(MouseButtons::LEFT)
+                && session.scrub.is_some()
+                && y as usize + 1 == renderer.screen().dimensions().1 =>
+            {
+                let cols = renderer.screen().dimensions().0;
+                let index = scrub_index(x as usize, session.history.len(), cols);
+                session.scrub_to(index, game);
+                gate.mark(Reason::View);
+            }
+            // While a stamp is picked, the wheel turns it clockwise when
+            // rolled up, and with shift flips it, left to right when rolled
+            // up and top to bottom when rolled down.
+            InputEvent::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons,
+                modifiers,
+            }) if mouse_buttons.contains(MouseButtons::VERT_WHEEL) && session.stamp.is_some() => {
+                let up = mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE);
+                let transform = match (modifiers.contains(Modifiers::SHIFT), up) {
+                    (false, true) => Transform::Rotate(1),
+                    (false, false) => Transform::Rotate(3),
+                    (true, true) => Transform::Flip(Axis::X),
+                    (true, false) => Transform::Flip(Axis::Y),
+                };
+                if let Some(stamp) = &mut session.stamp {
+                    stamp.transform(transform);
+                }
+                session.pointer = Some(ScreenPos(x, y));
+                gate.mark(Reason::View);
+            }
+            // The wheel sizes the brush: up for bigger.
+            InputEvent::Mouse(MouseEvent { mouse_buttons, .. })
+                if mouse_buttons.contains(MouseButtons::VERT_WHEEL) =>
+            {
+                let brush = match mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                    true => (session.brush + 1).min(MAX_BRUSH),
+                    false => session.brush.saturating_sub(1),
+                };
+                if brush != session.brush {
+                    session.brush = brush;
+                    session.notify(Level::Info, format!("Brush {}", brush_name(brush)));
+                    gate.mark(Reason::View);
+                }
+            }
+            InputEvent::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons,
+                ..
+            }) => {
+                let at = ScreenPos(x, y);
+                let down = mouse_buttons.contains(MouseButtons::LEFT);
+                session.pointer = Some(at);
+                let stamp = down.then(|| session.stamp.take()).flatten();
+                if let Some(stamp) = stamp {
+                    let under = renderer.screen_to_grid(game.size(), &session.view, at);
+                    if let Some(under) = under {
+                        let placement = stamp.placement(under, game.size(), game.edge_mode());
+                        let (pattern, origin, clipped) = placement;
+                        let mut placed = game.placement(&pattern, origin);
+                        placed.clipped += clipped;
+                        if let Some(warning) = placed.warning(stamp.name) {
+                            session.notify(Level::Warn, warning);
+                        }
+                        session.edit(game, &Edit::Pattern { pattern, origin });
+                    }
+                    session.stroke.hold();
+                    gate.mark(Reason::Edit);
+                } else if session.stamp.is_some() {
+                    // The stamp's outline follows the pointer.
+                    gate.mark(Reason::View);
+                } else if session.annotating {
+                    if session.stroke.press(down) {
+                        session.note_click(renderer, game, at);
+                        gate.mark(Reason::View);
+                    }
+                } else {
+                    let view = &session.view;
+                    let alive = |at| {
+                        let under = renderer.cells_under(game.size(), view, at);
+                        under.iter().any(|&cell| game.get(cell))
+                    };
+                    let continued = session.stroke.painting();
+                    let strokes = session.stroke.pointer(at, down, alive);
+                    let radius = session.brush;
+                    let painted = strokes
+                        .iter()
+                        .flat_map(|&(at, value)| brush(at, radius).map(move |at| (at, value)))
+                        .flat_map(|(at, value)| renderer.paint(game, view, at, value))
+                        .collect();
+                    if !strokes.is_empty() {
+                        let inverse = game.apply_edit(&Edit::Cells(painted));
+                        session.remember_stroke(inverse, continued);
+                        gate.mark(Reason::Edit);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Steps the board as far as is due by `now`, and keeps up with
+    /// everything else that happens in time, breaking once a limit in the
+    /// options is reached or the user has quit.
+    pub fn tick(&mut self, now: Instant) -> ControlFlow<()> {
+        let options = &self.options;
+        let limited = self.session.quit
+            || options
+                .generations
+                .is_some_and(|limit| self.stepped >= limit)
+            || options
+                .max_generation
+                .is_some_and(|limit| self.game.generation() >= limit)
+            || options
+                .run_for
+                .is_some_and(|limit| now.duration_since(self.started) >= limit);
+        if limited {
+            return ControlFlow::Break(());
+        }
+        if let Some(size) = self.resize.settled(now) {
+            self.resized(size);
+        }
+        let Self {
+            game,
+            options,
+            session,
+            tabs,
+            scheduler,
+            gate,
+            ..
+        } = self;
+        let saved = self
+            .autosave
+            .as_mut()
+            .and_then(|saver| saver.tick(game, now));
+        if let Some(failed) = saved {
+            session.notify(Level::Error, failed);
+        }
+        if session.poll_search(game) {
+            gate.mark(Reason::Edit);
+        }
+        let tick_delay = session.tick_delay();
+        // What holds the board still stops the clock too.
+        let still = session.paused || session.help.is_some() || session.scrub.is_some();
+        session.clock.set_paused(still, now);
+        // Everything stands still under the help overlay.
+        if session.help.is_some() {
+            scheduler.restart(now, tick_delay);
+            return ControlFlow::Continue(());
+        }
+        let due = scheduler.due(now, tick_delay);
+        let tick = due > 0 || session.step;
+        // Stepping is only watched while the timings are shown or slow
+        // motion looks out for bursts.
+        let watching = session.view.diagnostics.is_some() || session.slow_motion.is_some();
+        let mut tally = watching.then(StepTally::default);
+        // The scrubber holds the board at the generation it shows.
+        let steps = match (session.scrub, session.paused) {
+            (Some(_), _) => 0,
+            (None, true) => std::mem::take(&mut session.step) as u64,
+            (None, false) => due.max(1) as u64,
+        };
+        // Catching up stops short of the limits.
+        let steps = [
+            options
+                .generations
+                .map(|limit| limit.saturating_sub(self.stepped)),
+            options
+                .max_generation
+                .map(|limit| limit.saturating_sub(game.generation())),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(steps, u64::min);
+        let effective = Effective::resolve(options, &session.view);
+        if tick && steps > 0 {
+            for _ in 0..steps {
+                if effective.smooth.is_some() {
+                    session.transition = Some((Frame::of(game), Instant::now()));
+                }
+                if effective.flash {
+                    session.flash_from = Some(game.surface().to_vec());
+                }
+                match &mut tally {
+                    Some(tally) => {
+                        let before = tally.births + tally.deaths;
+                        game.tick_observed(tally);
+                        let changed = tally.births + tally.deaths - before;
+                        if session
+                            .slow_motion
+                            .is_some_and(|slow| changed >= slow.threshold)
+                        {
+                            session.spike = Some(Instant::now());
+                        }
+                    }
+                    None => game.step(),
+                }
+                session.after_step(game, options);
+                self.peak = self.peak.max(game.population());
+                self.populations.push(game.generation(), game.population());
+                let settled = self.detector.observe(game);
+                if let (None, Some(outcome)) = (self.settled, settled) {
+                    let event = Event::of(outcome, game.generation());
+                    self.notifier.notify(event);
+                    if options.feedback == Feedback::Desktop {
+                        self.desktop.push(event);
+                    }
+                }
+                self.settled = settled;
+                let failed = self
+                    .capture
+                    .as_mut()
+                    .and_then(|capture| capture.observe(game));
+                if let Some(failed) = failed {
+                    session.notify(Level::Error, failed);
+                }
+            }
+            self.stepped += steps;
+            self.unframed += steps as u32;
+            gate.mark(Reason::Step);
+        } else if tick {
+            // Paused, the frame is still redrawn each tick for what
+            // changes around the board, like notices coming and going.
+            gate.mark(Reason::View);
+        }
+        tabs.step_background(options);
+        // Edits since the last tick go out with the steps.
+        if let Some(server) = &mut self.server {
+            server.publish(game);
+        }
+        if let (Some(tally), Some(_)) = (tally, &session.view.diagnostics) {
+            *self.step_time.get_or_insert_default() += tally.time;
+        }
+        if session.transition.is_some() || session.notices.expire(now) {
+            gate.mark(Reason::View);
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// How long from `now` the app can be left before it next needs a
+    /// [`tick`](Self::tick), if no event comes first.
+    pub fn timeout(&self, now: Instant) -> Duration {
+        let tick_delay = self.session.tick_delay();
+        let delay = self.scheduler.remaining(now).min(tick_delay);
+        let delay = self
+            .resize
+            .remaining(now)
+            .map_or(delay, |left| left.min(delay));
+        let delay = self.options.run_for.map_or(delay, |limit| {
+            limit
+                .saturating_sub(now.duration_since(self.started))
+                .min(delay)
+        });
+        // The screen is redrawn as a message's time runs out.
+        let delay = self
+            .session
+            .notices
+            .remaining(now)
+            .map_or(delay, |left| left.min(delay));
+        // A transition on show is drawn at its own frame rate.
+        let delay = match self.session.transition {
+            Some(_) => delay.min(SMOOTH_FRAME),
+            None => delay,
+        };
+        // A frame held back for drawing too soon after the last is drawn
+        // as soon as it may be.
+        self.gate
+            .remaining(now)
+            .map_or(delay, |left| left.min(delay))
+    }
+
+    /// Draws a frame on the renderer's screen and flushes it, if one is due,
+    /// returning whether it did.
+    pub fn draw(&mut self) -> Result<bool, Box<dyn Error>> {
+        let Self {
+            renderer,
+            game,
+            options,
+            session,
+            tabs,
+            scheduler,
+            gate,
+            ..
+        } = self;
+        let effective = Effective::resolve(options, &session.view);
+        gate.set_interval(effective.interval);
+        let Some(reasons) = gate.take(Instant::now()) else {
+            return Ok(false);
+        };
+        // ...and while the screen is too small to show anything useful.
+        let dimensions = renderer.screen().dimensions();
+        if let Some(lines) = too_small_lines(dimensions, options.min_size) {
+            let screen = renderer.screen();
+            screen.add_change(Change::ClearScreen(ColorAttribute::Default));
+            let top = (dimensions.1 - lines.len()) / 2;
+            for (i, line) in lines.iter().enumerate() {
+                let left = (dimensions.0 - line.chars().count()) / 2;
+                let plain = |c| (c, ColorAttribute::Default, ColorAttribute::Default);
+                write_row(screen, (left, top + i), line.chars().map(plain));
+            }
+            screen.flush()?;
+            return Ok(true);
+        }
+        session.poll_export();
+        let tick_delay = session.tick_delay();
+        let stamp = || session.view.diagnostics.is_some().then(Instant::now);
+        let rendering = stamp();
+        let mut times = FrameTimes {
+            steps: std::mem::take(&mut self.unframed),
+            dropped: scheduler.take_dropped(),
+            ..FrameTimes::default()
+        };
+        let view = &mut session.view;
+        if let Some((ships, found)) = &mut view.spaceships {
+            if reasons.edit || game.generation() - *found >= SHIP_SCAN_INTERVAL {
+                *ships = game.spaceships();
+                *found = game.generation();
+            }
+        }
+        let palette = &options.palette;
+        let ages = &session.ages;
+        let width = game.size().0;
+        #[cfg(feature = "tags")]
+        let tags = &*game;
+        let theme = view.theme;
+        let tint = |x, y| {
+            if !effective.colors {
+                return shade(1.0, theme);
+            }
+            #[cfg(feature = "tags")]
+            if let Some(tag) = tags.tag(GridPos(x, y)) {
+                return tag_color(tag);
+            }
+            match ages.get(x + y * width) {
+                Some(&age) if palette.has_ages() => palette.age(age),
+                _ => palette.state(1),
+            }
+        };
+        let phase = session.transition.as_ref().map(|(previous, at)| {
+            (
+                previous,
+                at.elapsed().as_secs_f64() / tick_delay.as_secs_f64(),
+            )
+        });
+        match (phase, effective.smooth) {
+            // The board moved some other way since, e.g. back through
+            // history or to a new size, so there is nothing to animate.
+            (Some((previous, _)), _)
+                if previous.generation + 1 != game.generation() || previous.size != game.size() =>
+            {
+                session.transition = None;
+                renderer.render_tinted(game, view, &tint);
+            }
+            // Without shades to fade through, show whichever side of the
+            // midpoint it is.
+            (Some((previous, phase)), Some(Smooth::Midpoint)) if phase < 1.0 => {
+                if phase < 0.5 {
+                    let mut shown = game.clone();
+                    shown.restore(previous);
+                    renderer.render_tinted(&shown, view, &tint);
+                } else {
+                    renderer.render_tinted(game, view, &tint);
+                }
+            }
+            (Some((previous, phase)), Some(Smooth::Dissolve)) if phase < 1.0 => {
+                renderer.render_tinted(&dissolved(previous, game, phase), view, &tint);
+            }
+            (Some((previous, phase)), Some(smooth)) if phase < 1.0 => {
+                // Both generations' live cells, each drawn as strongly as
+                // it is faded in.
+                let mut shown = game.clone();
+                for (i, &alive) in previous.cells.iter().enumerate() {
+                    let pos = game.coords(i);
+                    if alive {
+                        shown.set(pos, true);
+                    }
+                }
+                let blended = |x, y| {
+                    let was = previous.cells[game.index(GridPos(x, y))];
+                    match fade(was, game.get(GridPos(x, y)), phase, smooth) {
+                        alpha if alpha >= 1.0 => tint(x, y),
+                        alpha => shade(alpha, view.theme),
+                    }
+                };
+                renderer.render_tinted(&shown, view, &blended);
+            }
+            // The last frame of a transition shows the board as it is.
+            (Some(_), _) => {
+                session.transition = None;
+                renderer.render_tinted(game, view, &tint);
+            }
+            (None, _) => renderer.render_tinted(game, view, &tint),
+        }
+        if effective.grid {
+            draw_grid(renderer, game, view);
+        }
+        if let Some(trail) = view.trail.as_ref().filter(|_| effective.trail) {
+            draw_trail(renderer, game, view, trail, options.trail);
+        }
+        // Shown for this frame only, stepped or not.
+        if let Some(before) = session.flash_from.take().filter(|_| effective.flash) {
+            draw_flash(renderer, game, view, &before);
+        }
+        if let Some((bookmark, divergence)) = session.bookmark.as_mut().filter(|_| view.diff) {
+            if bookmark.diverge(game, divergence) {
+                draw_divergence(renderer, game, view, divergence);
+            } else {
+                let ((w, h), (bw, bh)) = (game.size(), bookmark.size);
+                let message = format!("The board is {w}x{h} now, not {bw}x{bh}; diff off");
+                session.notices.push(Level::Warn, message, Instant::now());
+                view.diff = false;
+            }
+        }
+        if let Some(cursor) = view.cursor {
+            draw_preview(renderer, game, view);
+            draw_cursor(renderer, game, view, cursor);
+        }
+        if view.check_neighbors {
+            let mismatches = game
+                .neighbor_mismatches()
+                .into_iter()
+                .map(|i| game.coords(i));
+            draw_marks(
+                renderer,
+                game,
+                view,
+                &mismatches.collect::<Vec<_>>(),
+                MISMATCH,
+            );
+        }
+        if let (Some(stamp), Some(at)) = (&session.stamp, session.pointer) {
+            if let Some(under) = renderer.screen_to_grid(game.size(), view, at) {
+                let ghost = stamp.ghost(under, game.size(), game.edge_mode());
+                draw_marks(renderer, game, view, &ghost, GHOST);
+            }
+        }
+        if let Some((ships, found)) = &view.spaceships {
+            let (area, scale) = (renderer.area(), renderer.scale());
+            let elapsed = game.generation() - found;
+            draw_ship_labels(renderer.screen(), ships, elapsed, view, area, scale);
+        }
+        if !view.hide_notes {
+            draw_notes(renderer, game, view, &session.annotations);
+        }
+        if view.minimap {
+            let visible = renderer.size();
+            draw_minimap(renderer.screen(), game, view, visible);
+        }
+        if let Some(census) = &view.census {
+            draw_box(renderer.screen(), &census_lines(census));
+        }
+        if let Some(diagnostics) = &view.diagnostics {
+            let lines = diagnostics_lines(&diagnostics.stats(), session.delay, gate);
+            draw_box(renderer.screen(), &lines);
+        }
+        if let Some(prompt) = tabs.prompt {
+            draw_box(renderer.screen(), &prompt.lines());
+        }
+        if let Some(highlight) = session.picker {
+            let rows = renderer.screen().dimensions().1;
+            draw_box(
+                renderer.screen(),
+                &picker_lines(highlight, rows.saturating_sub(2)),
+            );
+        }
+        if let Some(editor) = session.rule_editor {
+            draw_box(
+                renderer.screen(),
+                &rule_editor_lines(game.rule(), editor.cursor),
+            );
+        }
+        if let Some(line) = &session.command {
+            let rows = renderer.screen().dimensions().1;
+            let plain = |c| (c, ColorAttribute::Default, ColorAttribute::Default);
+            let prompt = format!(":{line}");
+            write_row(
+                renderer.screen(),
+                (0, rows.saturating_sub(1)),
+                prompt.chars().map(plain),
+            );
+        } else if let Some(index) = session.scrub {
+            draw_scrubber(renderer.screen(), &session.history, index);
+        } else {
+            let mut status = Vec::new();
+            if tabs.len() > 1 {
+                status.push(format!("[{}/{}]", tabs.active + 1, tabs.len()));
+            }
+            if let Some(GridPos(x, y)) = view.cursor {
+                status.push(inspect_status((x, y), game.inspect(GridPos(x, y))));
+            }
+            if let Some(fill) = &session.region_fill {
+                status.push(fill.status());
+            }
+            if session.annotating {
+                status.push("click a note to delete it, elsewhere to add one; escape stops".into());
+            }
+            if let Some(sweep) = &session.sweep {
+                status.push(format!(
+                    "density {:.2}, seed {}",
+                    sweep.density(),
+                    sweep.seed()
+                ));
+            }
+            if let Some((seed, score)) = session.screensaver.as_ref().and_then(Screensaver::shown) {
+                status.push(format!("liveliest soup, seed {seed}, scoring {score:.0}"));
+            }
+            if let Some(injector) = &session.injector {
+                status.push(format!(
+                    "injecting {}/gen at the {} edge, {} so far",
+                    injector.rate, injector.edge, injector.injected
+                ));
+            }
+            if let Some(search) = &session.search {
+                let tried = search.tried.load(Ordering::Relaxed);
+                status.push(format!("searching soups, {tried} of {SEARCH_SOUPS} scored"));
+            }
+            if let Some(export) = &session.image_export {
+                let percent = export.percent.load(Ordering::Relaxed);
+                status.push(format!("writing {}, {percent}%", export.path.display()));
+            }
+            if view.flash {
+                status.push("flashing births and deaths".to_string());
+            }
+            if let Some((bookmark, divergence)) = session.bookmark.as_ref().filter(|_| view.diff) {
+                let (appeared, vanished) = divergence.counts();
+                let generation = bookmark.generation;
+                status.push(format!(
+                    "{appeared} born, {vanished} gone since gen {generation}"
+                ));
+            }
+            if view.margin > 0 && at_edge(game) {
+                status.push("cells have reached the edge past the margin".to_string());
+            }
+            let since = session.spike.map(|spike| spike.elapsed());
+            if session.slow_motion.is_some_and(|slow| slow.active(since)) {
+                status.push("slow motion".to_string());
+            }
+            if options.clock {
+                status.push(session.clock.status(Instant::now()));
+            }
+            if !status.is_empty() {
+                let status = format!(" {} ", status.join(" | "));
+                let rows = renderer.screen().dimensions().1;
+                let plain = |c| (c, ColorAttribute::Default, ColorAttribute::Default);
+                let bottom = (0, rows.saturating_sub(1));
+                write_row(renderer.screen(), bottom, status.chars().map(plain));
+            }
+        }
+        if session.messages {
+            draw_box(renderer.screen(), &message_lines(&session.notices));
+        }
+        if let Some((level, message)) = session.notices.current(Instant::now()) {
+            // A toast in the top-right corner.
+            let cols = renderer.screen().dimensions().0;
+            let toast = format!(" {message} ");
+            let left = cols.saturating_sub(toast.chars().count());
+            let color = |c| (c, level.color(), ColorAttribute::Default);
+            write_row(
+                renderer.screen(),
+                (left, 0),
+                toast.chars().take(cols).map(color),
+            );
+        }
+        renderer.draw_overlay(game);
+        if options.title {
+            let title = window_title(game.generation(), game.population());
+            if let Some(title) = self.title.due(title, Instant::now()) {
+                renderer.screen().add_change(Change::Title(title));
+            }
+        }
+        let flushing = session.view.diagnostics.is_some().then(Instant::now);
+        flush_frame(renderer.screen(), options.sync)?;
+        if !self.desktop.is_empty() {
+            let terminal = renderer.screen().terminal();
+            for event in self.desktop.drain(..) {
+                terminal.render(&[Change::Text(event.osc9())])?;
+            }
+            terminal.flush()?;
+        }
+        if let (Some(diagnostics), Some(step), Some(rendering), Some(flushing)) = (
+            &mut session.view.diagnostics,
+            self.step_time.take(),
+            rendering,
+            flushing,
+        ) {
+            let now = Instant::now();
+            times.step = step;
+            times.render = flushing - rendering;
+            times.flush = now - flushing;
+            diagnostics.record(times, now);
+        }
+        Ok(true)
+    }
+
+    /// Draws a frame if one is due and copies the app's screen into `rect`
+    /// of `surface`, first resizing the screen to fit `rect` if it doesn't.
+    pub fn render_into(&mut self, surface: &mut Surface, rect: Rect) -> Result<(), Box<dyn Error>> {
+        let size = (rect.width.max(1), rect.height.max(1));
+        if self.renderer.screen().dimensions() != size {
+            self.resized(size);
+        }
+        self.draw()?;
+        surface.draw_from_screen(self.renderer.screen(), rect.x, rect.y);
+        Ok(())
+    }
+
+    /// Follows the screen to `cols` by `rows` characters.
+    fn resized(&mut self, (cols, rows): (usize, usize)) {
+        // A world that tracks the screen follows it to the new size; an
+        // explicitly sized world just gets a new view.
+        let margin = self.options.margin;
+        let tracks_screen = self.game.size() == grown(self.renderer.size(), margin);
+        self.renderer.screen().resize(cols, rows);
+        if tracks_screen {
+            self.game.resize(grown(self.renderer.size(), margin));
+        }
+        self.session
+            .view
+            .clamp(self.game.size(), self.renderer.size());
+        // The saved frame no longer fits; the next frame redraws it all.
+        self.session.help = None;
+        self.gate.mark(Reason::View);
+    }
+
+    /// Ends the run, handing back the board on show at the start and what
+    /// the run came to.
+    pub fn finish(self) -> (GameOfLife, Summary) {
+        let Self {
+            mut game,
+            mut session,
+            tabs,
+            autosave,
+            capture,
+            clean,
+            stepped,
+            peak,
+            populations,
+            settled,
+            ..
+        } = self;
+        tabs.finish(&mut game, &mut session);
+        // What is worth recovering is left behind when the terminal is lost.
+        if let (Some(saver), true) = (autosave, clean) {
+            if let Err(err) = saver.finish() {
+                eprintln!("could not remove the autosaves: {err}");
+            }
+        }
+        // Don't cut a history dump or an image short by exiting.
+        session.wait_for_exports();
+        let summary = Summary {
+            generations: stepped,
+            population: game.population(),
+            peak_population: peak,
+            settled,
+            captures: capture.map(Capturer::finish),
+            started_at: session.clock.started_at(),
+            elapsed: session.clock.lap(Instant::now()),
+            populations: populations.points().to_vec(),
+            activity: game
+                .has_activity()
+                .then(|| (game.size(), game.activity_rgba())),
+        };
+        (game, summary)
+    }
+}
+
+impl<R: Renderer> Frontend for R {
+    fn run(&mut self, game: &mut GameOfLife, options: &Options) -> Result<Summary, Box<dyn Error>> {
+        enter_screen(self.screen())?;
+        if options.title {
+            self.screen()
+                .terminal()
+                .render(&[Change::Text(PUSH_TITLE.to_string())])?;
+        }
+        let board = std::mem::replace(game, GameOfLife::new_empty((1, 1)));
+        let mut app = App::with_renderer(&mut *self, board, options.clone());
+        while app.tick(Instant::now()).is_continue() {
+            app.draw()?;
+            let timeout = app.timeout(Instant::now());
+            match app.renderer.screen().terminal().poll_input(Some(timeout)) {
+                Ok(Some(event)) => {
+                    if app.handle_event(event)?.is_break() {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(_) => {
+                    app.clean = false;
+                    break;
+                }
+            }
+        }
+        let (board, summary) = app.finish();
+        *game = board;
+        if options.title {
+            restore_title(self.screen())?;
+        }
+        leave_screen(self.screen())?;
+        Ok(summary)
+    }
+}
+
+/// Counts another generation since each dead cell of `game` was alive, and
+/// none for the live ones. Cells are taken to have always been dead when
+/// tracking starts, so there is no trail until something dies.
+fn track_deaths(dead_for: &mut Vec<u32>, game: &GameOfLife) {
+    if dead_for.len() != game.cell_count() {
+        *dead_for = vec![u32::MAX; game.cell_count()];
+    }
+    for (dead, &alive) in dead_for.iter_mut().zip(game.surface()) {
+        *dead = if alive { 0 } else { dead.saturating_add(1) };
+    }
+}
+
+/// `size` with `margin` more cells on every side.
+pub fn grown(size: (usize, usize), margin: usize) -> (usize, usize) {
+    (size.0 + 2 * margin, size.1 + 2 * margin)
+}
+
+/// Whether a cell on the outermost ring of `game` is alive, so that the
+/// edge of the board has begun to affect what happens in it.
+fn at_edge(game: &GameOfLife) -> bool {
+    let (w, h) = game.size();
+    if w == 0 || h == 0 {
+        return false;
+    }
+    (0..w).any(|x| game.get(GridPos(x, 0)) || game.get(GridPos(x, h - 1)))
+        || (0..h).any(|y| game.get(GridPos(0, y)) || game.get(GridPos(w - 1, y)))
+}
+
+/// Dots every character that shows dead cells only, faintly; characters
+/// past the edge of the board are left blank.
+fn draw_grid<R: Renderer>(renderer: &mut R, game: &GameOfLife, view: &View) {
+    let dot = (
+        '·',
+        shade(GRID_BRIGHTNESS, view.theme),
+        ColorAttribute::Default,
+    );
+    let area = renderer.area();
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let cells = renderer.cells_under(game.size(), view, ScreenPos(x as u16, y as u16));
+            if !cells.is_empty() && !cells.iter().any(|&cell| game.get(cell)) {
+                write_row(renderer.screen(), (x, y), std::iter::once(dot));
+            }
+        }
+    }
+}
+
+/// Dots every character that shows no live cells but some that died within
+/// the last `length` generations, dimmer the longer ago they died.
+fn draw_trail<R: Renderer>(
+    renderer: &mut R,
+    game: &GameOfLife,
+    view: &View,
+    dead_for: &[u32],
+    length: u32,
+) {
+    if dead_for.len() != game.cell_count() {
+        return;
+    }
+    let length = length.max(1);
+    let area = renderer.area();
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let cells = renderer.cells_under(game.size(), view, ScreenPos(x as u16, y as u16));
+            if cells.iter().any(|&cell| game.get(cell)) {
+                continue;
+            }
+            let freshest = cells
+                .iter()
+                .map(|&GridPos(x, y)| dead_for[game.index(GridPos(x, y))])
+                .min();
+            if let Some(dead) = freshest.filter(|&dead| dead <= length) {
+                let left = (length + 1 - dead) as f64 / length as f64;
+                let shade = shade(TRAIL_BRIGHTNESS * left, view.theme);
+                let dot = ('·', shade, ColorAttribute::Default);
+                write_row(renderer.screen(), (x, y), std::iter::once(dot));
+            }
+        }
+    }
+}
+
+/// What the last step did to a cell, for flashing births and deaths. A
+/// character showing several cells flashes as the greatest of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Flash {
+    Died,
+    Born,
+}
+
+/// How a cell that was alive or not before a step changed in it, if it did.
+fn flash(was: bool, is: bool) -> Option<Flash> {
+    match (was, is) {
+        (true, false) => Some(Flash::Died),
+        (false, true) => Some(Flash::Born),
+        _ => None,
+    }
+}
+
+/// Redraws the characters showing cells born since the board was `before`
+/// in [`BORN`], and marks those left empty by deaths with a dim dot. A birth
+/// outranks the cells that merely stayed alive, which outrank a death.
+fn draw_flash<R: Renderer>(renderer: &mut R, game: &GameOfLife, view: &View, before: &[bool]) {
+    if before.len() != game.cell_count() {
+        return;
+    }
+    let area = renderer.area();
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let cells = renderer.cells_under(game.size(), view, ScreenPos(x as u16, y as u16));
+            let live = cells.iter().any(|&cell| game.get(cell));
+            let change = cells
+                .iter()
+                .filter_map(|&cell| flash(before[game.index(cell)], game.get(cell)))
+                .max();
+            let screen = renderer.screen();
+            let shown = match change {
+                Some(Flash::Born) => {
+                    let cell = &screen.screen_cells()[y][x];
+                    let glyph = cell.str().chars().next().unwrap_or(' ');
+                    (glyph, BORN, cell.attrs().background())
+                }
+                Some(Flash::Died) if !live => (
+                    '·',
+                    shade(DIED_BRIGHTNESS, view.theme),
+                    ColorAttribute::Default,
+                ),
+                _ => continue,
+            };
+            write_row(screen, (x, y), std::iter::once(shown));
+        }
+    }
+}
+
+/// Tints the live cells that were dead at the bookmark, and marks the dead
+/// characters where a cell was alive then.
+fn draw_divergence<R: Renderer>(
+    renderer: &mut R,
+    game: &GameOfLife,
+    view: &View,
+    divergence: &Divergence,
+) {
+    let area = renderer.area();
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let cells = renderer.cells_under(game.size(), view, ScreenPos(x as u16, y as u16));
+            let any = |f: &dyn Fn(usize, usize) -> bool| cells.iter().any(|&GridPos(x, y)| f(x, y));
+            let screen = renderer.screen();
+            let shown = if any(&|x, y| divergence.appeared(x, y)) {
+                let cell = &screen.screen_cells()[y][x];
+                let glyph = cell.str().chars().next().unwrap_or(' ');
+                (glyph, APPEARED, cell.attrs().background())
+            } else if any(&|x, y| divergence.vanished(x, y))
+                && !any(&|x, y| game.get(GridPos(x, y)))
+            {
+                ('·', VANISHED, ColorAttribute::Default)
+            } else {
+                continue;
+            };
+            write_row(screen, (x, y), std::iter::once(shown));
+        }
+    }
+}
+
+/// Shades the characters where the next step will bring a cell to life, or
+/// failing that kill one, worked out from `game` without stepping it.
+fn draw_preview<R: Renderer>(renderer: &mut R, game: &GameOfLife, view: &View) {
+    let next = game.preview_next();
+    let area = renderer.area();
+    for y in area.y..area.y + area.height {
+        for x in area.x..area.x + area.width {
+            let cells = renderer.cells_under(game.size(), view, ScreenPos(x as u16, y as u16));
+            let changes = |alive| {
+                cells
+                    .iter()
+                    .any(|&cell| game.get(cell) == alive && next.get(cell.0, cell.1) != alive)
+            };
+            let background = match (changes(false), changes(true)) {
+                (true, _) => BORN,
+                (false, true) => DYING,
+                (false, false) => continue,
+            };
+            let screen = renderer.screen();
+            let cell = &screen.screen_cells()[y][x];
+            let glyph = cell.str().chars().next().unwrap_or(' ');
+            let shown = (glyph, cell.attrs().foreground(), background);
+            write_row(screen, (x, y), std::iter::once(shown));
+        }
+    }
+}
+
+/// Highlights the characters showing the cell at `cursor`, if any do.
+fn draw_cursor<R: Renderer>(renderer: &mut R, game: &GameOfLife, view: &View, cursor: GridPos) {
+    let Some(chars) = renderer.grid_to_screen(game.size(), view, cursor) else {
+        return;
+    };
+    for y in chars.y..chars.y + chars.height {
+        for x in chars.x..chars.x + chars.width {
+            let screen = renderer.screen();
+            let cell = &screen.screen_cells()[y][x];
+            let glyph = cell.str().chars().next().unwrap_or(' ');
+            let shown = (glyph, cell.attrs().foreground(), CURSOR);
+            write_row(screen, (x, y), std::iter::once(shown));
+        }
+    }
+}
+
+/// Shades the characters showing `cells` with `background`, such as where
+/// a stamp would land.
+fn draw_marks<R: Renderer>(
+    renderer: &mut R,
+    game: &GameOfLife,
+    view: &View,
+    cells: &[GridPos],
+    background: ColorAttribute,
+) {
+    for &pos in cells {
+        let Some(chars) = renderer.grid_to_screen(game.size(), view, pos) else {
+            continue;
+        };
+        for y in chars.y..chars.y + chars.height {
+            for x in chars.x..chars.x + chars.width {
+                let screen = renderer.screen();
+                let cell = &screen.screen_cells()[y][x];
+                let glyph = cell.str().chars().next().unwrap_or(' ');
+                let shown = (glyph, cell.attrs().foreground(), background);
+                write_row(screen, (x, y), std::iter::once(shown));
+            }
+        }
+    }
+}
+
+/// The inspector's line about the cell at `at`, such as `(3, 4) dead, 3
+/// neighbors, born next`.
+fn inspect_status((x, y): (usize, usize), info: CellInfo) -> String {
+    let state = |alive| if alive { "alive" } else { "dead" };
+    let plural = if info.neighbors == 1 { "" } else { "s" };
+    let fate = match (info.state, info.next) {
+        (true, true) => "survives",
+        (true, false) => "dies",
+        (false, true) => "born next",
+        (false, false) => "stays dead",
+    };
+    format!(
+        "({x}, {y}) {}, {} neighbor{plural}, {fate}",
+        state(info.state),
+        info.neighbors
+    )
+}
+
+/// How strongly to draw a cell `phase` of the way, from 0 to 1, through the
+/// tick from a generation where it was alive or not to the next: 1 for solid,
+/// 0 for not at all.
+fn fade(was: bool, is: bool, phase: f64, smooth: Smooth) -> f64 {
+    match (was, is, smooth) {
+        (true, true, _) => 1.0,
+        (false, false, _) => 0.0,
+        (_, _, Smooth::Midpoint) if (phase < 0.5) == was => 1.0,
+        (_, _, Smooth::Midpoint) => 0.0,
+        (true, false, Smooth::Fade) => 1.0 - phase,
+        (false, true, Smooth::Fade) => phase,
+        // Drawn cell by cell, by [`dissolved`].
+        (_, _, Smooth::Dissolve) => is as u8 as f64,
+    }
+}
+
+/// The board `phase` of the way through dissolving from `previous` into
+/// `game`: each cell that changed between them shows its new state once the
+/// phase is past the cell's own [moment](dissolve_moment) in the tick.
+fn dissolved(previous: &Frame, game: &GameOfLife, phase: f64) -> GameOfLife {
+    let mut shown = game.clone();
+    shown.restore(previous);
+    for (i, &was) in previous.cells.iter().enumerate() {
+        let GridPos(x, y) = game.coords(i);
+        if game.get(GridPos(x, y)) != was && dissolve_moment(x, y) < phase {
+            shown.set(GridPos(x, y), !was);
+        }
+    }
+    shown
+}
+
+/// When, from 0 to 1 through a tick, the cell at `(x, y)` goes over while
+/// dissolving: scattered evenly and the same every tick, so the cells going
+/// over in a frame are spread across the board.
+fn dissolve_moment(x: usize, y: usize) -> f64 {
+    let mut z = (x as u64) << 32 ^ y as u64;
+    z = (z ^ z >> 30).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ z >> 27).wrapping_mul(0x94d049bb133111eb);
+    (z ^ z >> 31) as f64 / 2f64.powi(64)
+}
+
+/// A grey `alpha` (from 0 to 1) of the way from the background `theme` is
+/// for to the opposite, from the 256-color palette's greyscale ramp where
+/// true color isn't supported.
+fn shade(alpha: f64, theme: Theme) -> ColorAttribute {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let brightness = match theme {
+        Theme::Dark => alpha,
+        Theme::Light => 1.0 - alpha,
+    };
+    let level = brightness as f32;
+    let index = 232 + (brightness * 23.0).round() as u8;
+    ColorAttribute::TrueColorWithPaletteFallback(SrgbaTuple(level, level, level, 1.0), index)
+}
+
+/// The backgrounds of masked cells and of the seam, as suit `theme`.
+fn backgrounds(theme: Theme) -> (ColorAttribute, ColorAttribute) {
+    match theme {
+        Theme::Dark => (MASKED, SEAM),
+        Theme::Light => (LIGHT_MASKED, LIGHT_SEAM),
+    }
+}
+
+/// The color cells tagged `tag` are drawn in; there are only a few, so far
+/// apart tags may share one.
+#[cfg(feature = "tags")]
+fn tag_color(tag: u8) -> ColorAttribute {
+    const TEAMS: [AnsiColor; 6] = [
+        AnsiColor::Red,
+        AnsiColor::Lime,
+        AnsiColor::Blue,
+        AnsiColor::Yellow,
+        AnsiColor::Fuchsia,
+        AnsiColor::Aqua,
+    ];
+    TEAMS[tag as usize % TEAMS.len()].into()
+}
+
+/// Ages every live cell of `game` by a generation and resets the dead ones.
+fn age_cells(ages: &mut Vec<u32>, game: &GameOfLife) {
+    if ages.len() != game.cell_count() {
+        *ages = vec![0; game.cell_count()];
+    }
+    for (age, &alive) in ages.iter_mut().zip(game.surface()) {
+        *age = if alive { age.saturating_add(1) } else { 0 };
+    }
+}
+
+/// Kills the live cells in a random order over [`FADE_OUT`], rendering each
+/// step, until the board is empty. The order is seeded from the generation so a
+/// given board always fades the same way.
+fn fade_out<R: Renderer>(
+    renderer: &mut R,
+    game: &mut GameOfLife,
+    view: &View,
+) -> Result<(), Box<dyn Error>> {
+    let mut live = (0..game.cell_count())
+        .filter(|&i| game.surface()[i])
+        .collect::<Vec<_>>();
+    live.shuffle(&mut StdRng::seed_from_u64(game.generation()));
+    let total = live.len();
+    for frame in 1..=FADE_OUT_FRAMES {
+        let keep = total - total * frame as usize / FADE_OUT_FRAMES as usize;
+        for i in live.drain(keep..) {
+            let pos = game.coords(i);
+            game.set(pos, false);
+        }
+        renderer.render(game, view);
+        renderer.screen().flush()?;
+        std::thread::sleep(FADE_OUT / FADE_OUT_FRAMES);
+    }
+    Ok(())
+}
+
+/// Flushes what has been drawn, as one synchronized update if `sync`.
+fn flush_frame<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    sync: bool,
+) -> Result<(), Box<dyn Error>> {
+    if !sync {
+        screen.flush()?;
+        return Ok(());
+    }
+    screen
+        .terminal()
+        .render(&[Change::Text(BEGIN_SYNC.to_string())])?;
+    // Ended even if the frame fails, so the terminal doesn't hold on to it.
+    let flushed = screen.flush();
+    screen
+        .terminal()
+        .render(&[Change::Text(END_SYNC.to_string())])?;
+    screen.terminal().flush()?;
+    Ok(flushed?)
+}
+
+/// Whether the terminal `caps` describes supports synchronized output: its
+/// terminfo entry has the `Sync` capability, or it is a terminal known to
+/// support it whose entry doesn't say.
+pub fn supports_sync(caps: &Capabilities) -> bool {
+    let var = |name| std::env::var(name).ok();
+    caps.terminfo_db()
+        .is_some_and(|db| db.raw("Sync").is_some())
+        || known_sync_terminal(var("TERM").as_deref(), var("TERM_PROGRAM").as_deref())
+}
+
+fn known_sync_terminal(term: Option<&str>, program: Option<&str>) -> bool {
+    const TERMS: [&str; 5] = [
+        "xterm-kitty",
+        "foot",
+        "alacritty",
+        "contour",
+        "xterm-ghostty",
+    ];
+    matches!(program, Some("WezTerm" | "iTerm.app" | "ghostty"))
+        || term.is_some_and(|term| TERMS.iter().any(|known| term.starts_with(known)))
+}
+
+/// Asks the terminal for its background color with an OSC 11 query, waiting
+/// at most `timeout` for the reply: most terminals answer at once, and some
+/// never do. Leaves the terminal in raw mode.
+pub fn query_background<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    timeout: Duration,
+) -> Option<(f64, f64, f64)> {
+    query_terminal(screen, "\x1b]11;?\x1b\\", timeout, parse_background_reply)
+}
+
+/// Sends the terminal `request` and reads what comes back until `parse`
+/// makes sense of it, waiting at most `timeout`. Leaves the terminal in raw
+/// mode.
+pub fn query_terminal<T: Terminal, R>(
+    screen: &mut BufferedTerminal<T>,
+    request: &str,
+    timeout: Duration,
+    parse: impl Fn(&str) -> Option<R>,
+) -> Option<R> {
+    let terminal = screen.terminal();
+    terminal.set_raw_mode().ok()?;
+    terminal.render(&[Change::Text(request.to_string())]).ok()?;
+    terminal.flush().ok()?;
+    let deadline = Instant::now() + timeout;
+    // The reply comes back as if typed, a key at a time.
+    let mut reply = String::new();
+    loop {
+        let left = deadline.checked_duration_since(Instant::now())?;
+        let Some(InputEvent::Key(key)) = terminal.poll_input(Some(left)).ok()? else {
+            continue;
+        };
+        if key.modifiers.contains(Modifiers::ALT) || key.key == KeyCode::Escape {
+            reply.push('\x1b');
+        }
+        match key.key {
+            KeyCode::Char(c) if key.modifiers.contains(Modifiers::CTRL) => {
+                reply.push(char::from(c as u8 & 0x1f));
+            }
+            KeyCode::Char(c) => reply.push(c),
+            _ => {}
+        }
+        if let Some(parsed) = parse(&reply) {
+            return Some(parsed);
+        }
+    }
+}
+
+/// Puts back the title the terminal had before [`PUSH_TITLE`], or where it
+/// keeps no stack of them, leaves it blank for the terminal's own.
+fn restore_title<T: Terminal>(screen: &mut BufferedTerminal<T>) -> Result<(), Box<dyn Error>> {
+    screen.add_change(Change::Title(String::new()));
+    screen.flush()?;
+    screen
+        .terminal()
+        .render(&[Change::Text(POP_TITLE.to_string())])?;
+    screen.terminal().flush()?;
+    Ok(())
+}
+
+fn enter_screen<T: Terminal>(screen: &mut BufferedTerminal<T>) -> Result<(), Box<dyn Error>> {
+    screen.terminal().enter_alternate_screen()?;
+    screen.terminal().set_raw_mode()?;
+    screen.add_change(Change::CursorVisibility(CursorVisibility::Hidden));
+    Ok(())
+}
+
+fn leave_screen<T: Terminal>(screen: &mut BufferedTerminal<T>) -> Result<(), Box<dyn Error>> {
+    screen.terminal().exit_alternate_screen()?;
+    screen.add_change(Change::CursorVisibility(CursorVisibility::Visible));
+    Ok(())
+}
+
+/// Runs two games side by side until `q` is pressed, e.g. the same soup under
+/// two rules. Each gets half of the screen, with its rule, generation and
+/// population on the bottom row, and the second how many cells differ from
+/// the first.
+pub fn run_compare<R: Renderer>(
+    renderer: &mut R,
+    mut games: [&mut GameOfLife; 2],
+) -> Result<(), Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let mut last_tick = Instant::now();
+    loop {
+        let delay = DELAY.saturating_sub(last_tick.elapsed());
+        match renderer.screen().terminal().poll_input(Some(delay)) {
+            Ok(Some(evt)) => match evt {
+                termwiz::input::InputEvent::Key(k) if k.key == KeyCode::Char('q') => break,
+                termwiz::input::InputEvent::Resized { cols, rows } => {
+                    renderer.screen().resize(cols, rows);
+                }
+                _ => {}
+            },
+            Ok(None) => {}
+            Err(_) => break,
+        }
+        if last_tick.elapsed() >= DELAY {
+            for game in &mut games {
+                game.step();
+            }
+            last_tick = Instant::now();
+        }
+
+        let (cols, rows) = renderer.screen().dimensions();
+        // Two halves with a one-column divider between them.
+        let half = cols.saturating_sub(1) / 2;
+        let [first, second] = [games[0].surface(), games[1].surface()];
+        let differ = first.iter().zip(second).filter(|(a, b)| a != b).count();
+        for (i, game) in games.iter().enumerate() {
+            let rect = Rect {
+                x: i * (half + 1),
+                y: 0,
+                width: half,
+                height: rows.saturating_sub(1),
+            };
+            renderer.render_into(&**game, rect);
+            let mut label = format!(
+                " {}  gen {}  pop {}",
+                game.rule(),
+                game.generation(),
+                game.population()
+            );
+            if i == 1 {
+                label += &format!("  differs by {differ}");
+            }
+            let label = label.chars().chain(std::iter::repeat(' ')).take(half);
+            write_row(
+                renderer.screen(),
+                (rect.x, rows.saturating_sub(1)),
+                label.map(|c| (c, ColorAttribute::Default, ColorAttribute::Default)),
+            );
+        }
+        let screen = renderer.screen();
+        for y in 0..rows {
+            screen.add_change(Change::CursorPosition {
+                x: termwiz::surface::Position::Absolute(half),
+                y: termwiz::surface::Position::Absolute(y),
+            });
+            screen.add_change("│");
+        }
+        screen.flush()?;
+    }
+    leave_screen(renderer.screen())
+}
+
+fn player_color(player: Player) -> ColorAttribute {
+    match player {
+        Player::One => ColorAttribute::PaletteIndex(AnsiColor::Red as u8),
+        Player::Two => ColorAttribute::PaletteIndex(AnsiColor::Blue as u8),
+    }
+}
+
+/// Runs a two-player territory game. The players take turns placing cells with
+/// the mouse and pass the turn with `turn_key`; once both have placed, the
+/// simulation runs and the final score is shown until `q` is pressed.
+pub fn run_territory<R: Renderer>(
+    renderer: &mut R,
+    territory: &mut Territory,
+    turn_key: KeyCode,
+) -> Result<(), Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let view = View::default();
+    let mut last_tick = Instant::now();
+    loop {
+        let delay = DELAY.saturating_sub(last_tick.elapsed());
+        match renderer.screen().terminal().poll_input(Some(delay)) {
+            Ok(Some(evt)) => match evt {
+                termwiz::input::InputEvent::Key(k) if k.key == KeyCode::Char('q') => break,
+                termwiz::input::InputEvent::Key(k) if k.key == turn_key => territory.end_turn(),
+                termwiz::input::InputEvent::Resized { cols, rows } => {
+                    renderer.screen().resize(cols, rows);
+                }
+                termwiz::input::InputEvent::Mouse(MouseEvent {
+                    x,
+                    y,
+                    mouse_buttons,
+                    ..
+                }) if mouse_buttons.contains(MouseButtons::LEFT) => {
+                    let world = territory.game().size();
+                    for pos in renderer.cells_under(world, &view, ScreenPos(x, y)) {
+                        // Stop at the first cell that can't be placed because
+                        // the budget ran out or the turn changed hands.
+                        let before = territory.phase();
+                        match territory.place(pos) {
+                            Err(crate::territory::PlaceError::Occupied) => {}
+                            Err(_) => break,
+                            Ok(()) if territory.phase() != before => break,
+                            Ok(()) => {}
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(None) => {}
+            Err(_) => break,
+        }
+        if territory.phase() == Phase::Running && last_tick.elapsed() >= DELAY {
+            territory.tick();
+            last_tick = Instant::now();
+        }
+
+        let tint = |x, y| {
+            territory
+                .owner(GridPos(x, y))
+                .map_or(ColorAttribute::Default, player_color)
+        };
+        renderer.render_tinted(territory.game(), &view, &tint);
+        let status = territory_status(territory, turn_key);
+        let screen = renderer.screen();
+        let (_, rows) = screen.dimensions();
+        write_row(
+            screen,
+            (0, rows.saturating_sub(1)),
+            status
+                .chars()
+                .map(|c| (c, ColorAttribute::Default, ColorAttribute::Default)),
+        );
+        screen.add_change(Change::ClearToEndOfLine(ColorAttribute::Default));
+        if let Phase::Over(winner) = territory.phase() {
+            let (one, two) = (
+                territory.population(Player::One),
+                territory.population(Player::Two),
+            );
+            let headline = match winner {
+                Some(Player::One) => "Player 1 wins!".to_string(),
+                Some(Player::Two) => "Player 2 wins!".to_string(),
+                None => "It's a draw!".to_string(),
+            };
+            draw_box(
+                screen,
+                &[
+                    headline,
+                    format!("{one} to {two}"),
+                    String::new(),
+                    "q to quit".into(),
+                ],
+            );
+        }
+        screen.flush()?;
+    }
+    leave_screen(renderer.screen())
+}
+
+/// Runs a population-cap challenge. Cells are placed with the left mouse
+/// button and erased with the right; `g` stamps a glider where the mouse last
+/// was, `f` fills what is left of the budget at random and `c` clears. Space
+/// starts the run, and once it is over its score is shown, kept in `scores`
+/// if it makes the table, until the layout is retried with `r`, a fresh one
+/// started with `n`, or `q` is pressed.
+pub fn run_challenge<R: Renderer>(
+    renderer: &mut R,
+    challenge: &mut Challenge,
+    scores: Option<&HighScores>,
+) -> Result<(), Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let view = View::default();
+    let glider = Pattern::named("glider").unwrap_or_default();
+    let mut last_tick = Instant::now();
+    let mut pointer = GridPos(0, 0);
+    // What came of the run just over: the best score before it, and where
+    // its layout was kept if it was.
+    let mut result: Option<(Option<u64>, Option<PathBuf>)> = None;
+    loop {
+        let delay = DELAY.saturating_sub(last_tick.elapsed());
+        let stage = challenge.stage();
+        match renderer.screen().terminal().poll_input(Some(delay)) {
+            Ok(Some(evt)) => match evt {
+                InputEvent::Key(k) => match (k.key, stage) {
+                    (KeyCode::Char('q'), _) => break,
+                    (KeyCode::Char(' '), Stage::Placing) => challenge.go(),
+                    (KeyCode::Char('f'), Stage::Placing) => {
+                        let _ = challenge.fill(rand::random());
+                    }
+                    (KeyCode::Char('g'), Stage::Placing) => {
+                        let _ = challenge.stamp(&glider, pointer);
+                    }
+                    (KeyCode::Char('c'), Stage::Placing) => challenge.clear(),
+                    (KeyCode::Char(c @ ('r' | 'n')), Stage::Over(_)) => {
+                        challenge.retry(c == 'n');
+                        result = None;
+                    }
+                    _ => {}
+                },
+                InputEvent::Resized { cols, rows } => renderer.screen().resize(cols, rows),
+                InputEvent::Mouse(MouseEvent {
+                    x,
+                    y,
+                    mouse_buttons,
+                    ..
+                }) => {
+                    let world = challenge.game().size();
+                    let cells = renderer.cells_under(world, &view, ScreenPos(x, y));
+                    pointer = cells.first().copied().unwrap_or(pointer);
+                    for pos in cells {
+                        if mouse_buttons.contains(MouseButtons::LEFT) {
+                            let _ = challenge.place(pos);
+                        } else if mouse_buttons.contains(MouseButtons::RIGHT) {
+                            let _ = challenge.erase(pos);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(None) => {}
+            Err(_) => break,
+        }
+        if challenge.stage() == Stage::Running && last_tick.elapsed() >= DELAY {
+            challenge.tick();
+            last_tick = Instant::now();
+        }
+        if let (Stage::Over(_), None) = (challenge.stage(), &result) {
+            let best =
+                scores.and_then(|scores| Some(scores.list(challenge.config()).first()?.score));
+            let kept = scores.and_then(|scores| scores.record(challenge).ok().flatten());
+            result = Some((best, kept));
+        }
+
+        renderer.render(challenge.game(), &view);
+        let status = challenge_status(challenge);
+        let screen = renderer.screen();
+        let (_, rows) = screen.dimensions();
+        write_row(
+            screen,
+            (0, rows.saturating_sub(1)),
+            status
+                .chars()
+                .map(|c| (c, ColorAttribute::Default, ColorAttribute::Default)),
+        );
+        screen.add_change(Change::ClearToEndOfLine(ColorAttribute::Default));
+        if let (Stage::Over(score), Some((best, kept))) = (challenge.stage(), &result) {
+            let unit = challenge.config().scoring.unit();
+            let mut lines = vec![format!("Score: {score} {unit}")];
+            lines.push(match best {
+                Some(best) if *best >= score => format!("Best so far: {best}"),
+                Some(_) => "A new best!".to_string(),
+                None => "The first score kept".to_string(),
+            });
+            if let Some(kept) = kept {
+                lines.push(format!("Layout kept in {}", kept.display()));
+            }
+            lines.extend([
+                String::new(),
+                "r to retry, n for a new layout, q to quit".into(),
+            ]);
+            draw_box(screen, &lines);
+        }
+        screen.flush()?;
+    }
+    leave_screen(renderer.screen())
+}
+
+/// What the placing or running challenge is at, for its status bar.
+fn challenge_status(challenge: &Challenge) -> String {
+    let config = challenge.config();
+    match challenge.stage() {
+        Stage::Placing => format!(
+            "{} of {} cells left | click to place, right-click to erase, g glider, \
+             f fill, c clear, space to go",
+            challenge.remaining(),
+            config.budget
+        ),
+        Stage::Running => format!(
+            "generation {} of at most {} | population {}, peak {}",
+            challenge.generations(),
+            config.generations,
+            challenge.game().population(),
+            challenge.peak()
+        ),
+        Stage::Over(score) => format!("over | {score} {}", config.scoring.unit()),
+    }
+}
+
+/// Plays back the recording `reader` streams, a generation every
+/// [`DELAY`], reading only the frame on show. Space pauses and resumes, the
+/// left and right arrows step a generation back or on, up and down jump 100,
+/// home and end go to the first and last, and `q` quits.
+pub fn run_replay<R: Renderer, I: Read + Seek>(
+    renderer: &mut R,
+    reader: &mut FrameReader<I>,
+) -> Result<(), Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let view = View::default();
+    let mut board = GameOfLife::new_empty(reader.size());
+    let mut cells = Vec::new();
+    let mut playing = true;
+    let mut last_tick = Instant::now();
+    let mut show = |reader: &mut FrameReader<I>, board: &mut GameOfLife| -> io::Result<_> {
+        let generation = reader.read_into(&mut cells)?;
+        if generation.is_some() {
+            board.surface_mut().copy_from_slice(&cells);
+        }
+        Ok(generation)
+    };
+    let mut shown = show(reader, &mut board)?;
+    loop {
+        let delay = DELAY.saturating_sub(last_tick.elapsed());
+        match renderer.screen().terminal().poll_input(Some(delay)) {
+            Ok(Some(InputEvent::Key(k))) => match k.key {
+                KeyCode::Char('q') => break,
+                KeyCode::Char(' ') => playing = !playing,
+                key => {
+                    let target = shown
+                        .zip(reader.generations())
+                        .and_then(|(shown, span)| replay_target(key, shown, span));
+                    if let Some(target) = target {
+                        reader.seek(target)?;
+                        shown = show(reader, &mut board)?.or(shown);
+                    }
+                }
+            },
+            Ok(Some(InputEvent::Resized { cols, rows })) => renderer.screen().resize(cols, rows),
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        if playing && last_tick.elapsed() >= DELAY {
+            match show(reader, &mut board)? {
+                Some(generation) => shown = Some(generation),
+                None => playing = false,
+            }
+            last_tick = Instant::now();
+        }
+
+        renderer.render(&board, &view);
+        let status = replay_status(shown, reader.generations(), playing);
+        let screen = renderer.screen();
+        let (_, rows) = screen.dimensions();
+        write_row(
+            screen,
+            (0, rows.saturating_sub(1)),
+            status
+                .chars()
+                .map(|c| (c, ColorAttribute::Default, ColorAttribute::Default)),
+        );
+        screen.add_change(Change::ClearToEndOfLine(ColorAttribute::Default));
+        screen.flush()?;
+    }
+    leave_screen(renderer.screen())
+}
+
+/// Shows the board `feed` streams from a game running elsewhere, as it
+/// changes, until `q`. The arrows pan; nothing else touches the board.
+pub fn run_observe<R: Renderer, I: Read>(
+    renderer: &mut R,
+    feed: &mut Feed<I>,
+) -> Result<(), Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let mut view = View::default();
+    let mut board = GameOfLife::new_empty(renderer.size());
+    loop {
+        let visible = renderer.size();
+        let pan = (
+            (visible.0 / 8).max(1) as isize,
+            (visible.1 / 8).max(1) as isize,
+        );
+        match renderer.screen().terminal().poll_input(Some(DELAY)) {
+            Ok(Some(InputEvent::Key(k))) => match k.key {
+                KeyCode::Char('q') => break,
+                KeyCode::LeftArrow => view.pan((-pan.0, 0), board.size(), visible),
+                KeyCode::RightArrow => view.pan((pan.0, 0), board.size(), visible),
+                KeyCode::UpArrow => view.pan((0, -pan.1), board.size(), visible),
+                KeyCode::DownArrow => view.pan((0, pan.1), board.size(), visible),
+                _ => {}
+            },
+            Ok(Some(InputEvent::Resized { cols, rows })) => renderer.screen().resize(cols, rows),
+            Ok(_) => {}
+            Err(_) => break,
+        }
+        if feed.poll()? {
+            if board.size() != feed.size() {
+                board = GameOfLife::new_empty(feed.size());
+                view.clamp(board.size(), renderer.size());
+            }
+            board.surface_mut().copy_from_slice(feed.cells());
+        }
+
+        renderer.render(&board, &view);
+        let status = observe_status(feed.generation(), feed.closed());
+        let screen = renderer.screen();
+        let (_, rows) = screen.dimensions();
+        write_row(
+            screen,
+            (0, rows.saturating_sub(1)),
+            status
+                .chars()
+                .map(|c| (c, ColorAttribute::Default, ColorAttribute::Default)),
+        );
+        screen.add_change(Change::ClearToEndOfLine(ColorAttribute::Default));
+        screen.flush()?;
+    }
+    leave_screen(renderer.screen())
+}
+
+/// What an observer is watching, for its status bar.
+fn observe_status(generation: Option<u64>, closed: bool) -> String {
+    match (generation, closed) {
+        (None, false) => "waiting for the board | q to quit".to_string(),
+        (None, true) => "the game hung up before sending a board | q to quit".to_string(),
+        (Some(generation), false) => {
+            format!("observing gen {generation} | arrows pan | q to quit")
+        }
+        (Some(generation), true) => format!("the game hung up at gen {generation} | q to quit"),
+    }
+}
+
+/// The generation `key` goes to in a replay at `shown` of those `first` to
+/// `last`, if it moves at all.
+fn replay_target(key: KeyCode, shown: u64, (first, last): (u64, u64)) -> Option<u64> {
+    let target = match key {
+        KeyCode::LeftArrow => shown.saturating_sub(1),
+        KeyCode::RightArrow => shown + 1,
+        KeyCode::DownArrow => shown.saturating_sub(100),
+        KeyCode::UpArrow => shown + 100,
+        KeyCode::Home => first,
+        KeyCode::End => last,
+        _ => return None,
+    };
+    Some(target.clamp(first, last)).filter(|&target| target != shown)
+}
+
+/// Where a replay is, for its status bar.
+fn replay_status(shown: Option<u64>, generations: Option<(u64, u64)>, playing: bool) -> String {
+    let (Some(shown), Some((first, last))) = (shown, generations) else {
+        return "nothing recorded | q to quit".to_string();
+    };
+    let state = match playing {
+        true => "playing",
+        false if shown == last => "at the end",
+        false => "paused",
+    };
+    format!(
+        "generation {shown} of {first}-{last}, {state} | space plays, arrows step, \
+         home/end jump, q quits"
+    )
+}
+
+/// What was picked in the startup menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    /// A random soup of this density.
+    Soup(f64),
+    /// A library pattern, by name, on an empty board.
+    Pattern(&'static str),
+    /// The newest generation in the history file.
+    Resume,
+    Quit,
+}
+
+/// The startup menu, apart from drawing it: which choice is selected and how
+/// each of them is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu {
+    selected: usize,
+    density: f64,
+    /// Index into the library.
+    pattern: usize,
+    /// Whether there is a history file to resume from.
+    resumable: bool,
+}
+
+impl Menu {
+    pub fn new(resumable: bool) -> Self {
+        Self {
+            selected: 0,
+            density: MENU_DENSITY,
+            pattern: 0,
+            resumable,
+        }
+    }
+
+    fn choices(&self) -> usize {
+        if self.resumable {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Handles a key: up and down move between the choices, left and right
+    /// set the selected one, enter picks it and escape or `q` quits.
+    pub fn key(&mut self, key: KeyCode) -> Option<Choice> {
+        let patterns = Pattern::names().count() as isize;
+        match key {
+            KeyCode::UpArrow => self.selected = self.selected.saturating_sub(1),
+            KeyCode::DownArrow => self.selected = (self.selected + 1).min(self.choices() - 1),
+            KeyCode::LeftArrow | KeyCode::RightArrow => {
+                let by = if key == KeyCode::LeftArrow { -1 } else { 1 };
+                match self.selected {
+                    0 => {
+                        let density = self.density + by as f64 * MENU_DENSITY_STEP;
+                        let density = density.clamp(MENU_DENSITY_STEP, 1.0 - MENU_DENSITY_STEP);
+                        // Rounded so repeated steps land on whole percentages.
+                        self.density = (density * 100.0).round() / 100.0;
+                    }
+                    1 => self.pattern = (self.pattern as isize + by).rem_euclid(patterns) as usize,
+                    _ => {}
+                }
+            }
+            KeyCode::Enter => {
+                return Some(match self.selected {
+                    0 => Choice::Soup(self.density),
+                    1 => Choice::Pattern(Pattern::names().nth(self.pattern).unwrap_or("glider")),
+                    _ => Choice::Resume,
+                })
+            }
+            KeyCode::Escape | KeyCode::Char('q') => return Some(Choice::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// The menu as lines of text, the selected choice marked.
+    pub fn lines(&self) -> Vec<String> {
+        let pattern = Pattern::names().nth(self.pattern).unwrap_or_default();
+        let mut choices = vec![
+            format!("random soup  < {:.0}% >", self.density * 100.0),
+            format!("pattern      < {pattern} >"),
+        ];
+        if self.resumable {
+            choices.push("resume last session".into());
+        }
+        let mut lines = choices
+            .into_iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let marker = if i == self.selected { '>' } else { ' ' };
+                format!("{marker} {choice}")
+            })
+            .collect::<Vec<_>>();
+        lines.push(String::new());
+        lines.push("up/down to choose, left/right to change, enter to start".into());
+        lines
+    }
+}
+
+/// Shows the startup menu until something is picked.
+pub fn run_menu<R: Renderer>(renderer: &mut R, resumable: bool) -> Result<Choice, Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let mut menu = Menu::new(resumable);
+    let choice = loop {
+        let screen = renderer.screen();
+        screen.add_change(Change::ClearScreen(ColorAttribute::Default));
+        draw_box(screen, &menu.lines());
+        screen.flush()?;
+        match screen.terminal().poll_input(None) {
+            Ok(Some(termwiz::input::InputEvent::Key(k))) => {
+                if let Some(choice) = menu.key(k.key) {
+                    break choice;
+                }
+            }
+            Ok(Some(termwiz::input::InputEvent::Resized { cols, rows })) => {
+                screen.resize(cols, rows);
+            }
+            Ok(_) => {}
+            Err(_) => break Choice::Quit,
+        }
+    };
+    leave_screen(renderer.screen())?;
+    Ok(choice)
+}
+
+/// Asks the question in `lines` until a key is pressed, returning whether it
+/// was y.
+pub fn run_confirm<R: Renderer>(
+    renderer: &mut R,
+    lines: &[String],
+) -> Result<bool, Box<dyn Error>> {
+    enter_screen(renderer.screen())?;
+    let yes = loop {
+        let screen = renderer.screen();
+        screen.add_change(Change::ClearScreen(ColorAttribute::Default));
+        draw_box(screen, lines);
+        screen.flush()?;
+        match screen.terminal().poll_input(None) {
+            Ok(Some(termwiz::input::InputEvent::Key(k))) => break k.key == KeyCode::Char('y'),
+            Ok(Some(termwiz::input::InputEvent::Resized { cols, rows })) => {
+                screen.resize(cols, rows);
+            }
+            Ok(_) => {}
+            Err(_) => break false,
+        }
+    };
+    leave_screen(renderer.screen())?;
+    Ok(yes)
+}
+
+fn territory_status(territory: &Territory, turn_key: KeyCode) -> String {
+    let scores = format!(
+        "P1 {} (left {}) | P2 {} (left {})",
+        territory.population(Player::One),
+        territory.budget(Player::One),
+        territory.population(Player::Two),
+        territory.budget(Player::Two),
+    );
+    match territory.phase() {
+        Phase::Placing(player) => {
+            let n = if player == Player::One { 1 } else { 2 };
+            format!("{scores} | player {n}: click to place, {turn_key:?} ends turn")
+        }
+        Phase::Running => format!("{scores} | {} generations left", territory.remaining()),
+        Phase::Over(_) => scores,
+    }
+}
+
+/// Draws a downsampled overview of the whole world in the top-right corner, with
+/// the part of the world currently on screen highlighted. Does nothing when the
+/// whole world already fits on screen.
+fn draw_minimap<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    game: &GameOfLife,
+    view: &View,
+    visible: (usize, usize),
+) {
+    let world = game.size();
+    if world.0 <= visible.0 && world.1 <= visible.1 {
+        return;
+    }
+    let (cols, rows) = screen.dimensions();
+    // Each character shows two minimap pixels stacked vertically, and the map
+    // takes at most a quarter of the screen in each direction (minus the frame).
+    let max = (
+        (cols / 4).saturating_sub(2),
+        (rows / 4).saturating_sub(2) * 2,
+    );
+    if max.0 == 0 || max.1 < 2 {
+        return;
+    }
+    let scale = (world.0 as f64 / max.0 as f64).max(world.1 as f64 / max.1 as f64);
+    let map = (
+        ((world.0 as f64 / scale).ceil() as usize).clamp(1, max.0),
+        ((world.1 as f64 / scale).ceil() as usize).clamp(2, max.1) & !1,
+    );
+    let pixels = game.downsample(map);
+
+    // The on-screen region, in minimap pixels.
+    let to_map = |v: usize, w: usize, m: usize| v * m / w.max(1);
+    let region = (
+        to_map(view.offset.0, world.0, map.0),
+        to_map(view.offset.1, world.1, map.1),
+        to_map(view.offset.0 + visible.0.min(world.0), world.0, map.0).max(1),
+        to_map(view.offset.1 + visible.1.min(world.1), world.1, map.1).max(1),
+    );
+
+    let left = cols - map.0 - 2;
+    let mut line = String::new();
+    let move_to = |screen: &mut BufferedTerminal<T>, y: usize| {
+        screen.add_change(Change::CursorPosition {
+            x: termwiz::surface::Position::Absolute(left),
+            y: termwiz::surface::Position::Absolute(y),
+        });
+    };
+
+    move_to(screen, 0);
+    screen.add_change(format!("┌{}┐", "─".repeat(map.0)));
+    for row in 0..map.1 / 2 {
+        move_to(screen, row + 1);
+        screen.add_change("│");
+        for x in 0..map.0 {
+            let top = pixels[x + row * 2 * map.0];
+            let bottom = pixels[x + (row * 2 + 1) * map.0];
+            let in_view =
+                (region.0..region.2).contains(&x) && row * 2 < region.3 && row * 2 + 1 >= region.1;
+            line.clear();
+            line.push(match (top, bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+            let background = if in_view {
+                ColorAttribute::PaletteIndex(AnsiColor::Grey as u8)
+            } else {
+                ColorAttribute::Default
+            };
+            screen.add_change(Change::Attribute(AttributeChange::Background(background)));
+            screen.add_change(line.as_str());
+        }
+        screen.add_change(Change::Attribute(AttributeChange::Background(
+            ColorAttribute::Default,
+        )));
+        screen.add_change("│");
+    }
+    move_to(screen, map.1 / 2 + 1);
+    screen.add_change(format!("└{}┘", "─".repeat(map.0)));
+}
+
+/// Labels each spaceship with its velocity just above where it is expected to
+/// be `elapsed` generations after it was found. The world is drawn in `area`
+/// at `scale`.
+fn draw_ship_labels<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    ships: &[Spaceship],
+    elapsed: u64,
+    view: &View,
+    area: Rect,
+    scale: Scale,
+) {
+    let (cols, rows) = (area.x + area.width, area.y + area.height);
+    screen.add_change(Change::Attribute(AttributeChange::Foreground(
+        ColorAttribute::PaletteIndex(AnsiColor::Yellow as u8),
+    )));
+    for ship in ships {
+        let travelled = (elapsed / ship.period as u64) as i64;
+        let x = ship.bounds.x as i64 + ship.displacement.0 as i64 * travelled;
+        let y = ship.bounds.y as i64 + ship.displacement.1 as i64 * travelled;
+        let (cells, chars) = (scale.cells, scale.chars);
+        let col = (x - view.offset.0 as i64).div_euclid(cells.0.max(1) as i64) * chars.0 as i64;
+        let row = (y - view.offset.1 as i64).div_euclid(cells.1.max(1) as i64) * chars.1 as i64 - 1;
+        let (col, row) = (col + area.x as i64, row + area.y as i64);
+        let label = ship.velocity_label();
+        let (left, top) = (area.x as i64, area.y as i64);
+        if col < left || row < top || col as usize + label.len() > cols || row as usize >= rows {
+            continue;
+        }
+        screen.add_change(Change::CursorPosition {
+            x: termwiz::surface::Position::Absolute(col as usize),
+            y: termwiz::surface::Position::Absolute(row as usize),
+        });
+        screen.add_change(label);
+    }
+    screen.add_change(Change::Attribute(AttributeChange::Foreground(
+        ColorAttribute::Default,
+    )));
+}
+
+/// Longest a note is drawn, in characters, so that none hides much of the
+/// board.
+const NOTE_WIDTH: usize = 16;
+
+/// Rows a note is moved down at most to keep clear of those drawn before it.
+const NOTE_DROP: usize = 2;
+
+/// A note as drawn: which of them it is, the screen character its text
+/// starts at, and the text, cut short to fit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NoteLabel {
+    index: usize,
+    at: (usize, usize),
+    text: String,
+}
+
+/// Lays out the notes whose cells `place` puts on screen, at the character
+/// it gives, within `area`. Long notes are cut short with an ellipsis, and a
+/// note that would run into one before it goes down a row or two; one that
+/// still can't, or that has no room at all, is left out.
+fn note_labels(
+    notes: &[Annotation],
+    area: Rect,
+    place: impl Fn(GridPos) -> Option<(usize, usize)>,
+) -> Vec<NoteLabel> {
+    let (right, bottom) = (area.x + area.width, area.y + area.height);
+    let mut labels: Vec<NoteLabel> = Vec::new();
+    for (index, note) in notes.iter().enumerate() {
+        let Some((col, row)) = place(note.pos) else {
+            continue;
+        };
+        let room = right.saturating_sub(col).min(NOTE_WIDTH);
+        let text = match note.text.chars().count() {
+            len if len <= room => note.text.clone(),
+            _ if room == 0 => continue,
+            _ => note.text.chars().take(room - 1).chain(['…']).collect(),
+        };
+        let end = col + text.chars().count();
+        let clear = |row: usize| {
+            labels.iter().all(|label| {
+                let (left, width) = (label.at.0, label.text.chars().count());
+                label.at.1 != row || left + width <= col || end <= left
+            })
+        };
+        if let Some(row) = (row..bottom.min(row + NOTE_DROP + 1)).find(|&row| clear(row)) {
+            labels.push(NoteLabel {
+                index,
+                at: (col, row),
+                text,
+            });
+        }
+    }
+    labels
+}
+
+/// Which note is drawn over the screen character `at`, as laid out.
+fn note_under(labels: &[NoteLabel], at: ScreenPos) -> Option<usize> {
+    let (x, y) = (at.0 as usize, at.1 as usize);
+    let over = |label: &&NoteLabel| {
+        label.at.1 == y && (label.at.0..label.at.0 + label.text.chars().count()).contains(&x)
+    };
+    labels.iter().find(over).map(|label| label.index)
+}
+
+/// The notes laid out over the board as `renderer` shows it.
+fn placed_notes<R: Renderer>(
+    renderer: &mut R,
+    game: &GameOfLife,
+    view: &View,
+    notes: &[Annotation],
+) -> Vec<NoteLabel> {
+    let area = renderer.area();
+    note_labels(notes, area, |pos| {
+        let chars = renderer.grid_to_screen(game.size(), view, pos)?;
+        Some((chars.x, chars.y))
+    })
+}
+
+/// Draws the notes pinned to the board over it, each in its color. They
+/// are drawn afresh each frame, so the rows under them are redrawn once
+/// they move or go.
+fn draw_notes<R: Renderer>(renderer: &mut R, game: &GameOfLife, view: &View, notes: &[Annotation]) {
+    for label in placed_notes(renderer, game, view, notes) {
+        let color = notes[label.index].attribute();
+        let text = label
+            .text
+            .chars()
+            .map(|c| (c, color, ColorAttribute::Default));
+        write_row(renderer.screen(), label.at, text);
+    }
+}
+
+/// The message log overlay: the kept messages, newest first.
+fn message_lines(notices: &Notifications) -> Vec<String> {
+    let mut lines = notices
+        .recent()
+        .map(|(level, message)| format!("{}{message}", level.prefix()))
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        lines.push("no messages yet".into());
+    }
+    lines
+}
+
+fn census_lines(census: &Census) -> Vec<String> {
+    let mut lines = vec!["Census".to_string(), String::new()];
+    lines.extend(
+        census
+            .objects
+            .iter()
+            .filter(|&&(_, count)| count > 0)
+            .map(|(name, count)| format!("{name:<14}{count:>5}")),
+    );
+    lines.push(format!(
+        "{:<14}{:>5}",
+        "unidentified",
+        census.unidentified.len()
+    ));
+    lines
+}
+
+/// Draws `lines` in a frame centered on the screen, clearing what's beneath.
+fn diagnostics_lines(stats: &FrameStats, delay: Duration, gate: &RenderGate) -> Vec<String> {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    vec![
+        format!("step    {:7.2} ms", ms(stats.step)),
+        format!("render  {:7.2} ms", ms(stats.render)),
+        format!("flush   {:7.2} ms", ms(stats.flush)),
+        format!(
+            "tps     {:5.1} / {:.0}",
+            stats.tps,
+            1.0 / delay.as_secs_f64()
+        ),
+        format!("fps     {:5.1}", stats.fps),
+        format!("dropped {:7}", stats.dropped),
+        format!("frames  {:7} ({} coalesced)", gate.renders, gate.coalesced),
+    ]
+}
+
+/// The rule editor: a box for each count of live neighbors that could bring
+/// a cell to life, and each that could keep it alive, ticked if the rule
+/// does, with the one under the cursor marked.
+fn rule_editor_lines(rule: Rule, (count, survival): (usize, bool)) -> Vec<String> {
+    let row = |name: &str, alive: bool| {
+        let boxes = (0..=8).map(|n| {
+            let tick = if rule.next(alive, n) { 'x' } else { ' ' };
+            match (n, alive) == (count, survival) {
+                true => format!(">{tick}<"),
+                false => format!("[{tick}]"),
+            }
+        });
+        format!("{name:<9}{}", boxes.collect::<String>())
+    };
+    let counts = (0..=8).map(|n| format!(" {n} ")).collect::<String>();
+    vec![
+        format!("Rule {rule}"),
+        String::new(),
+        format!("{:<9}{counts}", ""),
+        row("birth", false),
+        row("survival", true),
+        String::new(),
+        "space toggles, enter keeps, escape reverts".to_string(),
+    ]
+}
+
+/// One line per action, listing the keys bound to it.
+fn help_lines(bindings: &HashMap<KeyCode, Action>) -> Vec<String> {
+    let mut actions = bindings.values().copied().collect::<Vec<_>>();
+    actions.sort_unstable();
+    actions.dedup();
+    let keys = actions
+        .iter()
+        .map(|&action| {
+            let mut keys = bindings
+                .iter()
+                .filter(|&(_, &a)| a == action)
+                .map(|(&key, _)| key_name(key))
+                .collect::<Vec<_>>();
+            keys.sort_unstable();
+            keys.join(" ")
+        })
+        .collect::<Vec<_>>();
+    let width = keys.iter().map(|k| k.chars().count()).max().unwrap_or(0);
+    let mut lines = actions
+        .iter()
+        .zip(keys)
+        .map(|(action, keys)| format!("{keys:>width$}  {}", action.description()))
+        .collect::<Vec<_>>();
+    lines.push(format!("{:>width$}  redraw the whole screen", "^L"));
+    lines.push(format!("{:>width$}  open a universe in a new tab", "^T"));
+    lines.push(format!(
+        "{:>width$}  show the next tab, or with shift the one before",
+        "tab"
+    ));
+    lines.push(format!("{:>width$}  close this tab", "^W"));
+    lines.push(format!(
+        "{:>width$}  shift the board while paused, with any arrow",
+        "⇧↑"
+    ));
+    lines.push(String::new());
+    lines.push("press any key".into());
+    lines
+}
+
+fn draw_box<T: Terminal>(screen: &mut BufferedTerminal<T>, lines: &[String]) {
+    let (cols, rows) = screen.dimensions();
+    let inner = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 2;
+    if inner + 2 > cols || lines.len() + 2 > rows {
+        return;
+    }
+    let left = (cols - inner - 2) / 2;
+    let top = (rows - lines.len() - 2) / 2;
+    let move_to = |screen: &mut BufferedTerminal<T>, y: usize| {
+        screen.add_change(Change::CursorPosition {
+            x: termwiz::surface::Position::Absolute(left),
+            y: termwiz::surface::Position::Absolute(y),
+        });
+    };
+    move_to(screen, top);
+    screen.add_change(format!("┌{}┐", "─".repeat(inner)));
+    for (i, line) in lines.iter().enumerate() {
+        move_to(screen, top + i + 1);
+        let pad = inner - 1 - line.chars().count();
+        screen.add_change(format!("│ {line}{}│", " ".repeat(pad)));
+    }
+    move_to(screen, top + lines.len() + 1);
+    screen.add_change(format!("└{}┘", "─".repeat(inner)));
+}
+
+/// A character on screen: itself, and its foreground and background colors.
+type Styled = (char, ColorAttribute, ColorAttribute);
+
+/// The rows of the world a renderer drew last frame, so that a row that comes
+/// out the same next frame is left alone: no cursor move, no text. A row
+/// anything else wrote to since, such as an overlay, is drawn again, and so
+/// is every row once the region moves or the screen is resized.
+#[derive(Debug, Default)]
+struct DrawnRows {
+    region: Rect,
+    dimensions: (usize, usize),
+    rows: Vec<Vec<Styled>>,
+    /// Whether each row's line on screen changed after the last frame.
+    touched: Vec<bool>,
+    /// The screen's sequence number as the last frame was finished.
+    seqno: SequenceNo,
+}
+
+impl DrawnRows {
+    /// Starts a frame of `region` on `screen`.
+    fn begin<T: Terminal>(&mut self, screen: &BufferedTerminal<T>, region: Rect) {
+        if (region, screen.dimensions()) != (self.region, self.dimensions) {
+            self.region = region;
+            self.dimensions = screen.dimensions();
+            self.rows = vec![Vec::new(); region.height];
+        }
+        let lines = screen.screen_lines();
+        self.touched = (region.y..region.y + region.height)
+            .map(|y| {
+                lines
+                    .get(y)
+                    .is_none_or(|line| line.changed_since(self.seqno))
+            })
+            .collect();
+    }
+
+    /// Writes `row` as row `y` of the region, unless it is on screen already.
+    fn write<T: Terminal>(&mut self, screen: &mut BufferedTerminal<T>, y: usize, row: Vec<Styled>) {
+        if !self.touched[y] && self.rows[y] == row {
+            return;
+        }
+        write_row(
+            screen,
+            (self.region.x, self.region.y + y),
+            row.iter().copied(),
+        );
+        self.rows[y] = row;
+    }
+
+    fn end<T: Terminal>(&mut self, screen: &BufferedTerminal<T>) {
+        self.seqno = screen.current_seqno();
+    }
+}
+
+/// Something a renderer draws over each frame, such as a legend or markers,
+/// given the screen with the frame drawn on it and the board it shows.
+pub type Overlay<T> = Box<dyn FnMut(&mut BufferedTerminal<T>, &GameOfLife)>;
+
+pub struct BlockRenderer<T: Terminal> {
+    screen: BufferedTerminal<T>,
+    border: bool,
+    scale: Scale,
+    drawn: DrawnRows,
+    overlay: Option<Overlay<T>>,
+}
+
+impl<T: Terminal> BlockRenderer<T> {
+    /// Characters treated as square unless told otherwise: one per cell.
+    pub const ASPECT: f64 = 1.0;
+
+    pub fn new(screen: BufferedTerminal<T>) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            screen,
+            border: false,
+            scale: Scale::block(Self::ASPECT),
+            drawn: DrawnRows::default(),
+            overlay: None,
+        })
+    }
+
+    /// Draws cells as wide as suits characters `aspect` times as wide as they
+    /// are tall, e.g. [`CELL_ASPECT`].
+    pub fn with_aspect(mut self, aspect: f64) -> Self {
+        self.scale = Scale::block(aspect);
+        self
+    }
+
+    /// Draws a frame around the world, or stops drawing it.
+    pub fn with_border(mut self, border: bool) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Draws `overlay` over each frame, once everything else is drawn.
+    pub fn with_overlay(
+        mut self,
+        overlay: impl FnMut(&mut BufferedTerminal<T>, &GameOfLife) + 'static,
+    ) -> Self {
+        self.overlay = Some(Box::new(overlay));
+        self
+    }
+}
+
+impl<T: Terminal> Renderer for BlockRenderer<T> {
+    type Term = T;
+
+    fn screen(&mut self) -> &mut BufferedTerminal<T> {
+        &mut self.screen
+    }
+
+    fn draw_overlay(&mut self, game: &GameOfLife) {
+        if let Some(overlay) = &mut self.overlay {
+            overlay(&mut self.screen, game);
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.screen.dimensions()
+    }
+
+    fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    fn border(&self) -> bool {
+        self.border
+    }
+
+    fn render_region(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        region: Rect,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+        let (gw, gh) = view.extent(grid.size());
+        let chars = self.scale.chars;
+        // Characters past the last whole cell stay blank.
+        let (across, down) = (region.width / chars.0, region.height / chars.1);
+        let (masked_bg, seam_bg) = backgrounds(view.theme);
+        self.drawn.begin(&self.screen, region);
+        for y in 0..region.height {
+            let row = (0..region.width).map(|x| {
+                let (cx, cy) = (x / chars.0, y / chars.1);
+                let (gx, gy) = (cx + view.offset.0, cy + view.offset.1);
+                let inside = cx < across && cy < down && gx < gw && gy < gh;
+                let background = if inside && grid.is_forced(gx, gy) {
+                    masked_bg
+                } else if inside && on_seam(grid, view, gx, gy) {
+                    seam_bg
+                } else {
+                    ColorAttribute::Default
+                };
+                if inside && shown(grid, view, gx, gy) {
+                    ('█', tint(gx, gy), background)
+                } else {
+                    (' ', ColorAttribute::Default, background)
+                }
+            });
+            self.drawn.write(&mut self.screen, y, row.collect());
+        }
+        self.drawn.end(&self.screen);
+    }
+}
+
+pub struct BrailleRenderer<T: Terminal> {
+    screen: BufferedTerminal<T>,
+    border: bool,
+    scale: Scale,
+    glyphs: Glyphs,
+    /// What the scale was chosen for, to choose again for other glyphs.
+    aspect: f64,
+    drawn: DrawnRows,
+    overlay: Option<Overlay<T>>,
+}
+
+impl<T: Terminal> BrailleRenderer<T> {
+    /// The aspect three rows of dots per character suit, used unless told
+    /// otherwise.
+    pub const ASPECT: f64 = 2.0 / 3.0;
+
+    pub fn new(screen: BufferedTerminal<T>) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            screen,
+            border: false,
+            scale: Scale::braille(Self::ASPECT),
+            glyphs: Glyphs::Braille,
+            aspect: Self::ASPECT,
+            drawn: DrawnRows::default(),
+            overlay: None,
+        })
+    }
+
+    /// Uses as many rows of dots per character as make the squarest dots on
+    /// characters `aspect` times as wide as they are tall.
+    pub fn with_aspect(mut self, aspect: f64) -> Self {
+        self.aspect = aspect;
+        self.scale = self.glyphs.scale(aspect);
+        self
+    }
+
+    /// Packs cells into `glyphs` rather than braille, as many to a
+    /// character as they show.
+    pub fn with_glyphs(mut self, glyphs: Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self.scale = glyphs.scale(self.aspect);
+        self
+    }
+
+    /// Gives back the terminal, to draw on some other way.
+    pub fn into_screen(self) -> BufferedTerminal<T> {
+        self.screen
+    }
+
+    /// Draws a frame around the world, or stops drawing it.
+    pub fn with_border(mut self, border: bool) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Draws `overlay` over each frame, once everything else is drawn.
+    pub fn with_overlay(
+        mut self,
+        overlay: impl FnMut(&mut BufferedTerminal<T>, &GameOfLife) + 'static,
+    ) -> Self {
+        self.overlay = Some(Box::new(overlay));
+        self
+    }
+}
+
+impl<T: Terminal> Renderer for BrailleRenderer<T> {
+    type Term = T;
+
+    fn screen(&mut self) -> &mut BufferedTerminal<T> {
+        &mut self.screen
+    }
+
+    fn draw_overlay(&mut self, game: &GameOfLife) {
+        if let Some(overlay) = &mut self.overlay {
+            overlay(&mut self.screen, game);
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.screen.dimensions()
+    }
+
+    fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    fn border(&self) -> bool {
+        self.border
+    }
+
+    fn render_region(
+        &mut self,
+        grid: &dyn GridView,
+        view: &View,
+        region: Rect,
+        tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+        let (w, h) = (region.width, region.height);
+        let (gw, gh) = view.extent(grid.size());
+        // Up to two dots across and four down per char.
+        let (cw, ch) = self.scale.cells;
+        // Each char's dots, row by row, two to a row, as glyphs are looked up.
+        let mut groups = vec![vec![[false; 8]; w]; h];
+        // The first live cell of each group, which decides its color.
+        let mut first = vec![vec![None; w]; h];
+        // How many cells of each group the mask holds at a fixed state.
+        let mut masked = vec![vec![0; w]; h];
+        // Whether any cell of each group is on the seam.
+        let mut seam = vec![vec![false; w]; h];
+
+        for y in 0..(h * ch).min(gh.saturating_sub(view.offset.1)) {
+            for x in 0..(w * cw).min(gw.saturating_sub(view.offset.0)) {
+                let (gx, gy) = (x + view.offset.0, y + view.offset.1);
+                let (col, row) = (x / cw, y / ch);
+                let live = shown(grid, view, gx, gy);
+                groups[row][col][(y % ch) * 2 + x % cw] = live;
+                if live && first[row][col].is_none() {
+                    first[row][col] = Some((gx, gy));
+                }
+                if grid.is_forced(gx, gy) {
+                    masked[row][col] += 1;
+                }
+                seam[row][col] |= on_seam(grid, view, gx, gy);
+            }
+        }
+
+        let rows = groups.into_iter().zip(first).zip(masked).zip(seam);
+        let (masked_bg, seam_bg) = backgrounds(view.theme);
+        self.drawn.begin(&self.screen, region);
+        for (y, (((row, first), masked), seam)) in rows.enumerate() {
+            let cells = row.into_iter().zip(first).zip(masked).zip(seam);
+            let row = cells.map(|(((cell, first), masked), seam)| {
+                let char = self.glyphs.glyph(cell);
+                let color = first.map_or(ColorAttribute::Default, |(x, y)| tint(x, y));
+                // A char is shaded as masked when at least half its cells are.
+                let background = if masked * 2 >= cw * ch {
+                    masked_bg
+                } else if seam {
+                    seam_bg
+                } else {
+                    ColorAttribute::Default
+                };
+                (char, color, background)
+            });
+            self.drawn.write(&mut self.screen, y, row.collect());
+        }
+        self.drawn.end(&self.screen);
+    }
+}
+
+/// A renderer that draws no cells, laid out like a [`BlockRenderer`], for
+/// timing how much of a frame is spent on everything but drawing the world.
+/// Overlays and the status bar are still drawn.
+pub struct NullRenderer<T: Terminal> {
+    screen: BufferedTerminal<T>,
+    border: bool,
+    scale: Scale,
+}
+
+impl<T: Terminal> NullRenderer<T> {
+    pub fn new(screen: BufferedTerminal<T>) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            screen,
+            border: false,
+            scale: Scale::block(BlockRenderer::<T>::ASPECT),
+        })
+    }
+
+    /// Sizes the world as a [`BlockRenderer`] for characters `aspect` times as
+    /// wide as they are tall would.
+    pub fn with_aspect(mut self, aspect: f64) -> Self {
+        self.scale = Scale::block(aspect);
+        self
+    }
+
+    /// Leaves room for a frame around the world, or stops leaving it.
+    pub fn with_border(mut self, border: bool) -> Self {
+        self.border = border;
+        self
+    }
+}
+
+impl<T: Terminal> Renderer for NullRenderer<T> {
+    type Term = T;
+
+    fn screen(&mut self) -> &mut BufferedTerminal<T> {
+        &mut self.screen
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.screen.dimensions()
+    }
+
+    fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    fn border(&self) -> bool {
+        self.border
+    }
+
+    /// Draws nothing, not even the frame.
+    fn render_tinted(
+        &mut self,
+        _grid: &dyn GridView,
+        _view: &View,
+        _tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+    }
+
+    fn render_region(
+        &mut self,
+        _grid: &dyn GridView,
+        _view: &View,
+        _region: Rect,
+        _tint: &dyn Fn(usize, usize) -> ColorAttribute,
+    ) {
+    }
+}
+
+/// The smallest terminal, in characters, worth sizing a world for. Smaller
+/// reports come from terminals still setting up, or from none at all.
+pub const MIN_TERMINAL: (usize, usize) = (10, 4);
+
+/// The terminal size a world is sized for when the real one never reports a
+/// usable size.
+pub const FALLBACK_TERMINAL: (usize, usize) = (80, 24);
+
+/// How long [`startup_dimensions`] keeps asking for a usable size.
+const STARTUP_WAIT: Duration = Duration::from_millis(500);
+
+/// Whether a terminal of `dimensions` characters is at least
+/// [`MIN_TERMINAL`].
+pub fn usable_dimensions(dimensions: (usize, usize)) -> bool {
+    dimensions.0 >= MIN_TERMINAL.0 && dimensions.1 >= MIN_TERMINAL.1
+}
+
+/// The terminal's size in characters, asking again for a short while if it
+/// is smaller than [`MIN_TERMINAL`]; `None` if it is still too small then.
+pub fn startup_dimensions<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+) -> Result<Option<(usize, usize)>, Box<dyn Error>> {
+    let started = Instant::now();
+    while !usable_dimensions(screen.dimensions()) {
+        if started.elapsed() >= STARTUP_WAIT {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        screen.check_for_resize()?;
+    }
+    Ok(Some(screen.dimensions()))
+}
+
+/// The part of a screen of `dimensions` characters the world is drawn in: all
+/// of it, or what is left inside a one-character frame. A screen too small to
+/// hold anything inside a frame doesn't get one.
+pub fn drawable_area(dimensions: (usize, usize), border: bool) -> Rect {
+    let (cols, rows) = dimensions;
+    if border && cols > 2 && rows > 2 {
+        Rect {
+            x: 1,
+            y: 1,
+            width: cols - 2,
+            height: rows - 2,
+        }
+    } else {
+        Rect {
+            x: 0,
+            y: 0,
+            width: cols,
+            height: rows,
+        }
+    }
+}
+
+/// The width of a typical terminal character over its height.
+pub const CELL_ASPECT: f64 = 0.5;
+
+/// The characters a [`BrailleRenderer`] packs several cells into, for fonts
+/// that lack braille. Each after braille needs less of the font and shows
+/// fewer cells a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Glyphs {
+    /// Braille dots, two across and three or four down.
+    #[default]
+    Braille,
+    /// The sextants of Symbols for Legacy Computing, two across and three
+    /// down.
+    Sextant,
+    /// Quadrant blocks, two across and two down.
+    Quadrant,
+    /// `'`, `.` and `:`, one across and two down.
+    Ascii,
+}
+
+/// Quadrant blocks by their cells: bit 0 the top left, 1 the top right, 2
+/// the bottom left and 3 the bottom right.
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// ASCII for a column of two cells: bit 0 the top, 1 the bottom.
+const ASCII_PAIRS: [char; 4] = [' ', '\'', '.', ':'];
+
+impl Glyphs {
+    /// The glyphs to use unless told otherwise, going by `$TERM`: consoles
+    /// whose fonts are known to stop at ASCII get it, and everything else
+    /// braille.
+    pub fn guess(term: Option<&str>) -> Self {
+        match term {
+            Some("linux" | "dumb" | "cons25") => Self::Ascii,
+            Some(term) if term.starts_with("vt") => Self::Ascii,
+            _ => Self::Braille,
+        }
+    }
+
+    /// How these glyphs pack cells into characters `aspect` times as wide
+    /// as they are tall; only braille has rows enough to choose from.
+    pub fn scale(self, aspect: f64) -> Scale {
+        let cells = match self {
+            Self::Braille => return Scale::braille(aspect),
+            Self::Sextant => (2, 3),
+            Self::Quadrant => (2, 2),
+            Self::Ascii => (1, 2),
+        };
+        Scale {
+            cells,
+            chars: (1, 1),
+        }
+    }
+
+    /// The character for a block of cells, given row by row, two to a row,
+    /// as laid out by [`scale`](Self::scale); cells past the block are
+    /// ignored.
+    pub fn glyph(self, dots: [bool; 8]) -> char {
+        let bits = |count: usize| {
+            dots[..count]
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (i, &on)| bits | (on as usize) << i)
+        };
+        match self {
+            Self::Braille => {
+                let [a, b, c, d, e, f, g, h] = dots.map(usize::from);
+                BRAILLE[a][b][c][d][e][f][g][h]
+            }
+            Self::Sextant => sextant(bits(6)),
+            Self::Quadrant => QUADRANTS[bits(4)],
+            Self::Ascii => ASCII_PAIRS[dots[0] as usize | (dots[2] as usize) << 1],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Braille => "braille",
+            Self::Sextant => "sextant",
+            Self::Quadrant => "quadrant",
+            Self::Ascii => "ascii",
+        }
+    }
+}
+
+/// The sextant showing `bits`, bit 0 the top left, 1 the top right and so
+/// on down. The four blocks that were in Unicode already aren't among the
+/// sextants: empty, full and the two halves.
+fn sextant(bits: usize) -> char {
+    const LEFT: usize = 0b010101;
+    const RIGHT: usize = 0b101010;
+    match bits {
+        0 => ' ',
+        0b111111 => '█',
+        LEFT => '▌',
+        RIGHT => '▐',
+        _ => {
+            let skipped = (bits > LEFT) as usize + (bits > RIGHT) as usize;
+            char::from_u32(0x1fb00 + (bits - 1 - skipped) as u32).unwrap_or('?')
+        }
+    }
+}
+
+impl FromStr for Glyphs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "braille" => Ok(Self::Braille),
+            "sextant" => Ok(Self::Sextant),
+            "quadrant" => Ok(Self::Quadrant),
+            "ascii" => Ok(Self::Ascii),
+            _ => Err(format!(
+                "--glyphs must be braille, sextant, quadrant or ascii, got {s:?}"
+            )),
+        }
+    }
+}
+
+/// The cells of a world of size `world` shown by the terminal character at
+/// `at`, when `area` of the screen shows it at `scale`. There are none
+/// outside `area`, or in the characters left over past its last whole block.
+fn cells_at(
+    area: Rect,
+    scale: Scale,
+    world: (usize, usize),
+    view: &View,
+    at: ScreenPos,
+) -> Vec<GridPos> {
+    let Some(GridPos(col, row)) = at.to_grid(area, scale, view.offset) else {
+        return vec![];
+    };
+    let cells = scale.cells;
+    let (right, bottom) = view.extent(world);
+    (row..(row + cells.1).min(bottom))
+        .flat_map(|y| (col..(col + cells.0).min(right)).map(move |x| GridPos(x, y)))
+        .collect()
+}
+
+/// The characters showing `cell` of a world of size `world`, laid out as for
+/// [`cells_at`]: a block of `scale.chars` of them. None for a cell off the
+/// world, or scrolled out of `area`.
+fn chars_at(
+    area: Rect,
+    scale: Scale,
+    world: (usize, usize),
+    view: &View,
+    cell: GridPos,
+) -> Option<Rect> {
+    let (right, bottom) = view.extent(world);
+    if cell.0 >= right || cell.1 >= bottom {
+        return None;
+    }
+    let ScreenPos(x, y) = cell.to_screen(area, scale, view.offset)?;
+    Some(Rect {
+        x: x as usize,
+        y: y as usize,
+        width: scale.chars.0,
+        height: scale.chars.1,
+    })
+}
+
+/// Whether the cell at `(x, y)` is drawn alive: if it is, unless the view
+/// outlines and it is [inside](on_outline) a live region.
+fn shown(grid: &dyn GridView, view: &View, x: usize, y: usize) -> bool {
+    match view.outline {
+        true => on_outline(grid, x, y),
+        false => grid.get(x, y),
+    }
+}
+
+/// Whether the cell at `(x, y)` is alive and has a dead cell among the eight
+/// around it. Past an edge that doesn't wrap, every cell counts as dead.
+fn on_outline(grid: &dyn GridView, x: usize, y: usize) -> bool {
+    let (w, h) = grid.size();
+    grid.get(x, y)
+        && NEIGHBORS.iter().any(|&(dx, dy)| {
+            let (i, j) = match grid.wraps() {
+                true => (
+                    (x as isize + dx).rem_euclid(w as isize) as usize,
+                    (y as isize + dy).rem_euclid(h as isize) as usize,
+                ),
+                false => {
+                    let i = x.checked_add_signed(dx).filter(|&i| i < w);
+                    let j = y.checked_add_signed(dy).filter(|&j| j < h);
+                    match i.zip(j) {
+                        Some(at) => at,
+                        None => return true,
+                    }
+                }
+            };
+            !grid.get(i, j)
+        })
+}
+
+/// Whether the cell at `(x, y)` should be shaded as part of a torus's seam.
+fn on_seam(grid: &dyn GridView, view: &View, x: usize, y: usize) -> bool {
+    let (w, h) = grid.size();
+    view.seam && grid.wraps() && (x == 0 || y == 0 || x + 1 == w || y + 1 == h)
+}
+
+/// The warning shown instead of the world on a screen of `dimensions`
+/// characters, if it is smaller than `min`, word-wrapped to fit as much of it
+/// as will.
+fn too_small_lines(dimensions: (usize, usize), min: (usize, usize)) -> Option<Vec<String>> {
+    let (cols, rows) = dimensions;
+    if cols >= min.0 && rows >= min.1 {
+        return None;
+    }
+    let message = format!("terminal too small (need ≥ {}x{})", min.0, min.1);
+    let mut lines = Vec::<String>::new();
+    for word in message.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= cols => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.chars().take(cols).collect()),
+        }
+    }
+    lines.truncate(rows);
+    Some(lines)
+}
+
+/// The column of a `cols`-wide scrubber bar that stands for frame `index` of
+/// `len`, spreading the frames evenly from the left edge to the right.
+fn scrub_column(index: usize, len: usize, cols: usize) -> usize {
+    if len <= 1 || cols <= 1 {
+        return cols.saturating_sub(1);
+    }
+    let (index, last, right) = (index.min(len - 1), len - 1, cols - 1);
+    (index * right + last / 2) / last
+}
+
+/// The frame of `len` that a click in column `x` of a `cols`-wide scrubber
+/// bar picks: the one whose column is nearest.
+fn scrub_index(x: usize, len: usize, cols: usize) -> usize {
+    if len <= 1 || cols <= 1 {
+        return len.saturating_sub(1);
+    }
+    let (x, last, right) = (x.min(cols - 1), len - 1, cols - 1);
+    (x * last + right / 2) / right
+}
+
+/// Draws the scrubber: a bar along the bottom row standing for the buffered
+/// generations, with a marker on frame `index`, labeled on the row above.
+fn draw_scrubber<T: Terminal>(screen: &mut BufferedTerminal<T>, history: &History, index: usize) {
+    let (cols, rows) = screen.dimensions();
+    if rows < 2 || cols == 0 {
+        return;
+    }
+    let marker = scrub_column(index, history.len(), cols);
+    let highlight = ColorAttribute::PaletteIndex(AnsiColor::Yellow as u8);
+    let bar = (0..cols).map(|x| {
+        if x == marker {
+            ('█', highlight, ColorAttribute::Default)
+        } else {
+            ('─', FRAME, ColorAttribute::Default)
+        }
+    });
+    write_row(screen, (0, rows - 1), bar);
+    let generation = history.get(index).map_or(0, |frame| frame.generation);
+    let label = format!(
+        " generation {generation} ({}/{}) ",
+        index + 1,
+        history.len()
+    );
+    let width = label.chars().count().min(cols);
+    let left = marker.saturating_sub(width / 2).min(cols - width);
+    let label = label.chars().take(width);
+    write_row(
+        screen,
+        (left, rows - 2),
+        label.map(|c| (c, highlight, ColorAttribute::Default)),
+    );
+}
+
+/// Draws a dim frame in the characters just around `area`, if there is room.
+fn draw_frame<T: Terminal>(screen: &mut BufferedTerminal<T>, area: Rect) {
+    if area.x == 0 || area.y == 0 {
+        return;
+    }
+    let dim = |c| (c, FRAME, ColorAttribute::Default);
+    let edge = |left, right| {
+        std::iter::once(left)
+            .chain(std::iter::repeat_n('─', area.width))
+            .chain(std::iter::once(right))
+            .map(dim)
+    };
+    let (left, right) = (area.x - 1, area.x + area.width);
+    write_row(screen, (left, area.y - 1), edge('┌', '┐'));
+    for y in area.y..area.y + area.height {
+        write_row(screen, (left, y), std::iter::once(dim('│')));
+        write_row(screen, (right, y), std::iter::once(dim('│')));
+    }
+    write_row(screen, (left, area.y + area.height), edge('└', '┘'));
+}
+
+/// Writes a row of characters starting at `at`, with their foreground and
+/// background colors, switching colors only where they change along the row.
+fn write_row<T: Terminal>(
+    screen: &mut BufferedTerminal<T>,
+    at: (usize, usize),
+    row: impl Iterator<Item = (char, ColorAttribute, ColorAttribute)>,
+) {
+    screen.add_change(Change::CursorPosition {
+        x: termwiz::surface::Position::Absolute(at.0),
+        y: termwiz::surface::Position::Absolute(at.1),
+    });
+    let mut buf = String::new();
+    let mut current = (ColorAttribute::Default, ColorAttribute::Default);
+    for (char, foreground, background) in row {
+        if (foreground, background) != current {
+            if !buf.is_empty() {
+                screen.add_change(std::mem::take(&mut buf));
+            }
+            if foreground != current.0 {
+                screen.add_change(Change::Attribute(AttributeChange::Foreground(foreground)));
+            }
+            if background != current.1 {
+                screen.add_change(Change::Attribute(AttributeChange::Background(background)));
+            }
+            current = (foreground, background);
+        }
+        buf.push(char);
+    }
+    screen.add_change(buf);
+    if current.0 != ColorAttribute::Default {
+        screen.add_change(Change::Attribute(AttributeChange::Foreground(
+            ColorAttribute::Default,
+        )));
+    }
+    if current.1 != ColorAttribute::Default {
+        screen.add_change(Change::Attribute(AttributeChange::Background(
+            ColorAttribute::Default,
+        )));
+    }
+}
+
+#[test]
+fn test_help_lists_every_binding() {
+    let lines = help_lines(&default_bindings());
+    assert_eq!(lines[0], "    ?  show this help");
+    assert!(lines.contains(&"  + =  speed up".to_string()));
+    assert!(lines.contains(&"space  pause or resume".to_string()));
+    assert!(lines.contains(&"   F3  toggle frame timings".to_string()));
+    assert!(lines.contains(&"   ^L  redraw the whole screen".to_string()));
+    assert_eq!(lines.last().unwrap(), "press any key");
+}
+
+#[test]
+fn test_view_pan_clamps_to_world() {
+    let mut view = View::default();
+    view.pan((-5, 3), (100, 50), (40, 20));
+    assert_eq!(view.offset, GridPos(0, 3));
+    view.pan((500, 500), (100, 50), (40, 20));
+    assert_eq!(view.offset, GridPos(60, 30));
+}
+
+#[test]
+fn test_debounce_applies_last_value_once_quiet() {
+    let t0 = Instant::now();
+    let ms = Duration::from_millis;
+    let mut debounce = Debounce::new(ms(100));
+    debounce.push((80, 24), t0);
+    debounce.push((90, 30), t0 + ms(40));
+    assert_eq!(debounce.settled(t0 + ms(120)), None);
+    assert_eq!(debounce.remaining(t0 + ms(120)), Some(ms(20)));
+    assert_eq!(debounce.settled(t0 + ms(140)), Some((90, 30)));
+    assert_eq!(debounce.settled(t0 + ms(300)), None);
+    assert_eq!(debounce.remaining(t0 + ms(300)), None);
+}
+
+#[test]
+fn test_screen_to_world_mapping() {
+    let view = View {
+        offset: GridPos(3, 1),
+        ..View::default()
+    };
+    let world = (40, 30);
+    let plain = drawable_area((10, 6), false);
+    assert_eq!((plain.x, plain.y, plain.width, plain.height), (0, 0, 10, 6));
+    assert_eq!(
+        cells_at(plain, Scale::block(1.0), world, &view, ScreenPos(0, 0)),
+        vec![GridPos(3, 1)]
+    );
+    assert_eq!(
+        cells_at(plain, Scale::block(1.0), world, &view, ScreenPos(9, 5)),
+        vec![GridPos(12, 6)]
+    );
+    assert!(cells_at(plain, Scale::block(1.0), world, &view, ScreenPos(10, 0)).is_empty());
+
+    let framed = drawable_area((10, 6), true);
+    assert_eq!(
+        (framed.x, framed.y, framed.width, framed.height),
+        (1, 1, 8, 4)
+    );
+    assert!(cells_at(framed, Scale::block(1.0), world, &view, ScreenPos(0, 3)).is_empty());
+    assert!(cells_at(framed, Scale::block(1.0), world, &view, ScreenPos(9, 3)).is_empty());
+    assert!(cells_at(framed, Scale::block(1.0), world, &view, ScreenPos(4, 5)).is_empty());
+    assert_eq!(
+        cells_at(framed, Scale::block(1.0), world, &view, ScreenPos(1, 1)),
+        vec![GridPos(3, 1)]
+    );
+    let cells = [(5, 1), (6, 1), (5, 2), (6, 2), (5, 3), (6, 3)].map(GridPos::from);
+    assert_eq!(
+        cells_at(
+            framed,
+            Scale::braille(2.0 / 3.0),
+            world,
+            &view,
+            ScreenPos(2, 1)
+        ),
+        cells
+    );
+    // Clipped where the world ends.
+    assert_eq!(
+        cells_at(
+            framed,
+            Scale::braille(2.0 / 3.0),
+            (6, 2),
+            &view,
+            ScreenPos(2, 1)
+        ),
+        vec![GridPos(5, 1)]
+    );
+    // No room for a frame on a tiny screen.
+    assert_eq!(drawable_area((2, 2), true), drawable_area((2, 2), false));
+}
+
+#[test]
+fn test_screen_and_grid_mappings_agree() {
+    let screen = || BufferedTerminal::new(Offscreen { size: (23, 11) }).unwrap();
+    let world = (50, 40);
+    let renderers = |border| -> Vec<Box<dyn Renderer<Term = Offscreen>>> {
+        let (block, braille) = (BlockRenderer::new(screen()), BrailleRenderer::new(screen()));
+        let (wide, tall) = (BlockRenderer::new(screen()), BrailleRenderer::new(screen()));
+        let null = NullRenderer::new(screen())
+            .unwrap()
+            .with_aspect(CELL_ASPECT);
+        vec![
+            Box::new(block.unwrap().with_border(border)),
+            Box::new(wide.unwrap().with_aspect(CELL_ASPECT).with_border(border)),
+            Box::new(braille.unwrap().with_border(border)),
+            Box::new(tall.unwrap().with_aspect(CELL_ASPECT).with_border(border)),
+            Box::new(null.with_border(border)),
+        ]
+    };
+    for border in [false, true] {
+        for offset in [GridPos(0, 0), GridPos(7, 3), GridPos(45, 38)] {
+            let view = View {
+                offset,
+                ..View::default()
+            };
+            for renderer in renderers(border) {
+                let scale = renderer.scale();
+                let case = format!("{scale:?}, border {border}, offset {offset:?}");
+                // Every character maps to the first cell it shows, or to none.
+                for y in 0..11 {
+                    for x in 0..23 {
+                        let cell = renderer.screen_to_grid(world, &view, ScreenPos(x, y));
+                        let under = renderer.cells_under(world, &view, ScreenPos(x, y));
+                        let (x, y) = (x as usize, y as usize);
+                        assert_eq!(cell, under.first().copied(), "{case}: ({x}, {y})");
+                        let Some(GridPos(cx, cy)) = cell else {
+                            continue;
+                        };
+                        let chars = renderer
+                            .grid_to_screen(world, &view, GridPos(cx, cy))
+                            .unwrap();
+                        let inside = (chars.x..chars.x + chars.width).contains(&x)
+                            && (chars.y..chars.y + chars.height).contains(&y);
+                        assert!(inside, "{case}: ({x}, {y}) in {chars:?}");
+                    }
+                }
+                // Every cell on screen maps to the block of characters
+                // showing it, and only those.
+                let size = renderer.size();
+                for y in 0..world.1 {
+                    for x in 0..world.0 {
+                        let shown = (offset.0..offset.0 + size.0).contains(&x)
+                            && (offset.1..offset.1 + size.1).contains(&y);
+                        let chars = renderer.grid_to_screen(world, &view, GridPos(x, y));
+                        assert_eq!(chars.is_some(), shown, "{case}: ({x}, {y})");
+                        let Some(chars) = chars else {
+                            continue;
+                        };
+                        assert_eq!((chars.width, chars.height), scale.chars, "{case}");
+                        for cy in chars.y..chars.y + chars.height {
+                            for cx in chars.x..chars.x + chars.width {
+                                let at = ScreenPos(cx as u16, cy as u16);
+                                let under = renderer.cells_under(world, &view, at);
+                                let case = format!("{case}: ({x}, {y}) at {cx}");
+                                assert!(under.contains(&GridPos(x, y)), "{case}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let [block, wide, braille, tall, _] = renderers(true).try_into().ok().unwrap();
+    let view = View {
+        offset: GridPos(7, 3),
+        ..View::default()
+    };
+    // The frame, and the character left over past the last whole cell of
+    // two characters, show nothing.
+    assert_eq!(block.screen_to_grid(world, &view, ScreenPos(0, 0)), None);
+    assert_eq!(
+        block.screen_to_grid(world, &view, ScreenPos(1, 1)),
+        Some(GridPos(7, 3))
+    );
+    assert_eq!(
+        wide.screen_to_grid(world, &view, ScreenPos(20, 1)),
+        Some(GridPos(16, 3))
+    );
+    assert_eq!(wide.screen_to_grid(world, &view, ScreenPos(21, 1)), None);
+    assert_eq!(
+        wide.grid_to_screen(world, &view, GridPos(8, 3)),
+        Some(Rect {
+            x: 3,
+            y: 1,
+            width: 2,
+            height: 1
+        })
+    );
+    assert_eq!(
+        braille.screen_to_grid(world, &view, ScreenPos(2, 2)),
+        Some(GridPos(9, 6))
+    );
+    assert_eq!(
+        tall.screen_to_grid(world, &view, ScreenPos(2, 2)),
+        Some(GridPos(9, 7))
+    );
+    assert_eq!(
+        tall.grid_to_screen(world, &view, GridPos(10, 10)),
+        tall.grid_to_screen(world, &view, GridPos(9, 7))
+    );
+    // Off the world rather than clamped to its edge, and above the view.
+    let bottom = View {
+        offset: GridPos(45, 38),
+        ..View::default()
+    };
+    assert_eq!(
+        block.screen_to_grid(world, &bottom, ScreenPos(5, 2)),
+        Some(GridPos(49, 39))
+    );
+    assert_eq!(block.screen_to_grid(world, &bottom, ScreenPos(6, 2)), None);
+    assert_eq!(block.screen_to_grid(world, &bottom, ScreenPos(5, 3)), None);
+    assert_eq!(block.grid_to_screen(world, &bottom, GridPos(44, 39)), None);
+    assert_eq!(block.grid_to_screen(world, &bottom, GridPos(50, 39)), None);
+}
+
+#[test]
+fn test_too_small_warning_wraps_to_fit() {
+    assert_eq!(too_small_lines((20, 10), (20, 10)), None);
+    assert_eq!(
+        too_small_lines((40, 9), (20, 10)).unwrap(),
+        ["terminal too small (need ≥ 20x10)"]
+    );
+    assert_eq!(
+        too_small_lines((12, 10), (20, 10)).unwrap(),
+        ["terminal too", "small (need", "≥ 20x10)"]
+    );
+    // Long words are cut, and lines past the bottom dropped.
+    assert_eq!(
+        too_small_lines((6, 2), (20, 10)).unwrap(),
+        ["termin", "too"]
+    );
+    assert!(too_small_lines((0, 0), (20, 10)).unwrap().is_empty());
+}
+
+#[test]
+fn test_scrubber_mapping() {
+    for cols in [1, 7, 80, 203] {
+        for len in [1, 2, 5, 80, 256] {
+            assert_eq!(scrub_column(len - 1, len, cols), cols - 1);
+            assert_eq!(scrub_index(cols - 1, len, cols), len - 1);
+            assert_eq!(
+                scrub_index(0, len, cols),
+                if cols > 1 { 0 } else { len - 1 }
+            );
+            // Every frame's own column picks it back when each gets a column.
+            if len <= cols {
+                for index in 0..len {
+                    assert_eq!(
+                        scrub_index(scrub_column(index, len, cols), len, cols),
+                        index
+                    );
+                }
+            }
+            // Further right never picks an older frame.
+            let picks = (0..cols)
+                .map(|x| scrub_index(x, len, cols))
+                .collect::<Vec<_>>();
+            assert!(picks.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+    // A history with only a few frames yet spreads them across the bar.
+    assert_eq!(scrub_column(1, 3, 81), 40);
+    assert_eq!(scrub_index(30, 3, 81), 1);
+    assert_eq!(scrub_index(500, 3, 81), 2);
+}
+
+#[test]
+fn test_aspect_correction_mapping() {
+    assert_eq!(Scale::block(1.0).chars, (1, 1));
+    assert_eq!(Scale::block(CELL_ASPECT).chars, (2, 1));
+    assert_eq!(Scale::block(0.3).chars, (3, 1));
+    assert_eq!(Scale::braille(2.0 / 3.0).cells, (2, 3));
+    assert_eq!(Scale::braille(CELL_ASPECT).cells, (2, 4));
+
+    // Two characters per cell: a 9-column area holds four whole cells, and the
+    // ninth column shows none.
+    let area = drawable_area((9, 4), false);
+    let wide = Scale::block(CELL_ASPECT);
+    assert_eq!(wide.cells_in(area), (4, 4));
+    let view = View {
+        offset: GridPos(10, 0),
+        ..View::default()
+    };
+    assert_eq!(
+        cells_at(area, wide, (20, 4), &view, ScreenPos(2, 1)),
+        vec![GridPos(11, 1)]
+    );
+    assert_eq!(
+        cells_at(area, wide, (20, 4), &view, ScreenPos(3, 1)),
+        vec![GridPos(11, 1)]
+    );
+    assert!(cells_at(area, wide, (20, 4), &view, ScreenPos(8, 1)).is_empty());
+
+    // Four rows of dots per character.
+    let tall = Scale::braille(CELL_ASPECT);
+    assert_eq!(tall.cells_in(area), (18, 16));
+    let under = cells_at(area, tall, (40, 40), &View::default(), ScreenPos(1, 2));
+    assert_eq!(under.len(), 8);
+    assert_eq!((under[0], under[7]), (GridPos(2, 8), GridPos(3, 11)));
+}
+
+#[test]
+fn test_sweep_moves_through_densities() {
+    let config = SweepConfig {
+        step: 0.3,
+        seed: 40,
+    };
+    let mut sweep = Sweep::new(config);
+    let mut game = GameOfLife::new((10, 10));
+    sweep.reseed(&mut game);
+    let mut records = Vec::new();
+    while records.len() < 5 {
+        game.step();
+        records.extend(sweep.advance(&mut game));
+    }
+    let densities = records
+        .iter()
+        .map(|record| record.density)
+        .collect::<Vec<_>>();
+    let expected = [0.05, 0.35, 0.65, 0.95, 0.05];
+    assert!(
+        densities
+            .iter()
+            .zip(expected)
+            .all(|(d, e)| (d - e).abs() < 1e-9),
+        "{densities:?}"
+    );
+    assert!(records.iter().map(|record| record.seed).eq(40..45));
+    assert!(records.iter().all(|record| record.outcome.is_some()));
+
+    // Each record replays: the same seed and density settle the same way.
+    let first = records[1];
+    let mut replay = GameOfLife::new((10, 10));
+    replay.fill(first.density, first.seed);
+    let mut detector = CycleDetector::new(SWEEP_MAX_PERIOD);
+    let mut generations = 0;
+    let outcome = loop {
+        replay.step();
+        generations += 1;
+        if let Some(outcome) = detector.observe(&replay) {
+            break outcome;
+        }
+    };
+    assert_eq!(
+        (Some(outcome), generations),
+        (first.outcome, first.generations)
+    );
+    assert_eq!(first.csv().split(',').next(), Some("41"));
+}
+
+#[test]
+fn test_smooth_fade_schedule() {
+    // Survivors stay solid and empty cells stay empty all through the tick.
+    for phase in [0.0, 0.3, 0.7, 1.0] {
+        for smooth in [Smooth::Fade, Smooth::Midpoint] {
+            assert_eq!(fade(true, true, phase, smooth), 1.0);
+            assert_eq!(fade(false, false, phase, smooth), 0.0);
+        }
+    }
+    assert_eq!(fade(true, false, 0.25, Smooth::Fade), 0.75);
+    assert_eq!(fade(false, true, 0.25, Smooth::Fade), 0.25);
+    // Without fading, births and deaths both happen halfway.
+    assert_eq!(fade(true, false, 0.4, Smooth::Midpoint), 1.0);
+    assert_eq!(fade(false, true, 0.4, Smooth::Midpoint), 0.0);
+    assert_eq!(fade(true, false, 0.5, Smooth::Midpoint), 0.0);
+    assert_eq!(fade(false, true, 0.5, Smooth::Midpoint), 1.0);
+}
+
+#[test]
+fn test_dissolving_goes_over_a_share_of_the_cells_at_a_time() {
+    let mut game = GameOfLife::new_empty((80, 60));
+    game.fill(0.4, 12);
+    let previous = Frame::of(&game);
+    game.step();
+    let changed = (0..game.cell_count()).filter(|&i| previous.cells[i] != game.surface()[i]);
+    let changed = changed.collect::<Vec<_>>();
+    assert!(changed.len() > 500);
+    assert_eq!(dissolved(&previous, &game, 0.0).surface(), previous.cells);
+    assert_eq!(dissolved(&previous, &game, 1.0).surface(), game.surface());
+    let mut before = dissolved(&previous, &game, 0.0);
+    for quarter in 1..4 {
+        let phase = quarter as f64 / 4.0;
+        let shown = dissolved(&previous, &game, phase);
+        let over = changed
+            .iter()
+            .filter(|&&i| shown.surface()[i] == game.surface()[i])
+            .count();
+        let share = over as f64 / changed.len() as f64;
+        assert!((share - phase).abs() < 0.06, "{share} over at {phase}");
+        // Cells that went over stay over, and only changing cells move.
+        for i in 0..game.cell_count() {
+            if before.surface()[i] == game.surface()[i] {
+                assert_eq!(shown.surface()[i], game.surface()[i]);
+            }
+        }
+        before = shown;
+    }
+    let grey = |alpha| match shade(alpha, Theme::Dark) {
+        ColorAttribute::TrueColorWithPaletteFallback(_, index) => index,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!((grey(0.0), grey(1.0), grey(2.0)), (232, 255, 255));
+    // On a light background live cells are the dark ones.
+    let light = match shade(1.0, Theme::Light) {
+        ColorAttribute::TrueColorWithPaletteFallback(_, index) => index,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(light, 232);
+}
+
+#[test]
+fn test_trail_tracks_deaths() {
+    let mut game = GameOfLife::new_empty((5, 5));
+    let mut dead_for = Vec::new();
+    track_deaths(&mut dead_for, &game);
+    assert!(dead_for.iter().all(|&dead| dead == u32::MAX));
+
+    // The ends of a blinker die as it turns; its middle lives on.
+    for x in 1..4 {
+        game.set(GridPos(x, 2), true);
+    }
+    track_deaths(&mut dead_for, &game);
+    game.step();
+    track_deaths(&mut dead_for, &game);
+    assert_eq!(dead_for[game.index(GridPos(1, 2))], 1);
+    assert_eq!(dead_for[game.index(GridPos(2, 2))], 0);
+    assert_eq!(dead_for[game.index(GridPos(2, 1))], 0);
+    game.step();
+    track_deaths(&mut dead_for, &game);
+    assert_eq!(dead_for[game.index(GridPos(2, 1))], 1);
+    assert_eq!(dead_for[game.index(GridPos(1, 2))], 0);
+    assert_eq!(dead_for[game.index(GridPos(0, 0))], u32::MAX);
+}
+
+#[test]
+fn test_grid_dots_dead_cells_within_the_board() {
+    let headless = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    let mut game = GameOfLife::new_empty((6, 2));
+    game.set(GridPos(2, 1), true);
+    // Bigger than the board, so that its edges show.
+    let mut renderer = BlockRenderer::new(headless((10, 3))).unwrap();
+    let view = View {
+        grid: true,
+        ..View::default()
+    };
+    renderer.render(&game, &view);
+    draw_grid(&mut renderer, &game, &view);
+    let screen = renderer.screen().screen_chars_to_string();
+    assert_eq!(screen, "······    \n··█···    \n          \n");
+}
+
+#[test]
+fn test_preview_shades_the_next_step() {
+    let headless = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    let game = GameOfLife::from_ascii(".....\n.....\n.###.\n.....\n.....");
+    let mut renderer = BlockRenderer::new(headless((5, 5))).unwrap();
+    let view = View::default();
+    renderer.render(&game, &view);
+    draw_preview(&mut renderer, &game, &view);
+    let lines = renderer.screen().screen_lines();
+    let background = |x, y: usize| {
+        lines[y]
+            .visible_cells()
+            .nth(x)
+            .unwrap()
+            .attrs()
+            .background()
+    };
+    assert_eq!((background(2, 1), background(2, 3)), (BORN, BORN));
+    assert_eq!((background(1, 2), background(3, 2)), (DYING, DYING));
+    // The middle lives on, and nothing else changes.
+    assert_eq!(background(2, 2), ColorAttribute::Default);
+    assert_eq!(background(0, 0), ColorAttribute::Default);
+    assert_eq!(game.generation(), 0);
+}
+
+#[test]
+fn test_render_through_viewport() {
+    use crate::Viewport;
+    let headless = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    let mut game = GameOfLife::new((30, 20));
+    game.fill(0.4, 7);
+    let offset = GridPos(5, 3);
+
+    // The board panned by the view, and a viewport cut from the same spot,
+    // draw the same characters.
+    let mut direct = BlockRenderer::new(headless((12, 6))).unwrap();
+    let view = View {
+        offset,
+        ..View::default()
+    };
+    direct.render(&game, &view);
+    let mut cut = BlockRenderer::new(headless((12, 6))).unwrap();
+    let rect = Rect {
+        x: offset.0,
+        y: offset.1,
+        width: 12,
+        height: 12,
+    };
+    cut.render(&Viewport::new(&game, rect), &View::default());
+    let screen = direct.screen().screen_chars_to_string();
+    assert!(screen.contains('█'));
+    assert_eq!(screen, cut.screen().screen_chars_to_string());
+
+    let mut braille = BrailleRenderer::new(headless((6, 4))).unwrap();
+    braille.render(&game, &view);
+    let mut cut = BrailleRenderer::new(headless((6, 4))).unwrap();
+    cut.render(&Viewport::new(&game, rect), &View::default());
+    assert_eq!(
+        braille.screen().screen_chars_to_string(),
+        cut.screen().screen_chars_to_string()
+    );
+}
+
+#[test]
+fn test_unchanged_rows_are_not_redrawn() {
+    let screen = BufferedTerminal::new(Offscreen { size: (10, 6) }).unwrap();
+    let mut braille = BrailleRenderer::new(screen).unwrap();
+    let ch = braille.scale().cells.1;
+    let mut game = GameOfLife::new_empty((20, 20));
+    game.insert_pattern(&Pattern::named("block").unwrap(), GridPos(1, 1));
+    game.insert_pattern(&Pattern::named("blinker").unwrap(), GridPos(4, 9));
+    // The lines written to by the next frame.
+    let written = |braille: &mut BrailleRenderer<_>, game: &GameOfLife| {
+        let seqno = braille.screen().current_seqno();
+        braille.render(game, &View::default());
+        let lines = braille.screen().screen_lines();
+        (0..lines.len())
+            .filter(|&y| lines[y].changed_since(seqno))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(written(&mut braille, &game), (0..6).collect::<Vec<_>>());
+    // A still frame writes nothing.
+    assert_eq!(written(&mut braille, &game), []);
+    // A blinker turning rewrites just the rows it spans: standing up, rows 8
+    // to 10.
+    game.step();
+    assert_eq!(
+        written(&mut braille, &game),
+        (8 / ch..=10 / ch).collect::<Vec<_>>()
+    );
+
+    // A row drawn over since is drawn again, even if the world didn't change.
+    let plain = |c| (c, ColorAttribute::Default, ColorAttribute::Default);
+    write_row(braille.screen(), (0, 4), "toast".chars().map(plain));
+    assert_eq!(written(&mut braille, &game), [4]);
+    // So is every row after a resize.
+    braille.screen().resize(10, 5);
+    assert_eq!(written(&mut braille, &game), (0..5).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_flash_categorizes_blinker() {
+    let mut game = GameOfLife::new_empty((5, 5));
+    for x in 1..4 {
+        game.set(GridPos(x, 2), true);
+    }
+    let before = game.surface().to_vec();
+    game.step();
+    let changed = |kind| {
+        let cells = (0..game.cell_count()).map(|i| game.coords(i));
+        cells
+            .filter(|&pos| flash(before[game.index(pos)], game.get(pos)) == Some(kind))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(changed(Flash::Born), [GridPos(2, 1), GridPos(2, 3)]);
+    assert_eq!(changed(Flash::Died), [GridPos(1, 2), GridPos(3, 2)]);
+    assert_eq!(flash(true, true), None);
+
+    // In braille, a birth outranks the survivor and the death sharing its
+    // character, and a death only marks a character left empty.
+    let screen = BufferedTerminal::new(Offscreen { size: (4, 2) }).unwrap();
+    let mut braille = BrailleRenderer::new(screen).unwrap();
+    braille.render(&game, &View::default());
+    draw_flash(&mut braille, &game, &View::default(), &before);
+    let cells = braille.screen().screen_cells();
+    assert_eq!(cells[0][1].attrs().foreground(), BORN);
+    assert_eq!(cells[1][1].attrs().foreground(), BORN);
+    assert_eq!(cells[0][0].str(), "·");
+}
+
+#[test]
+fn test_render_gate_coalesces_bursts() {
+    let start = Instant::now();
+    let mut gate = RenderGate::new();
+    assert_eq!(gate.take(start), None);
+    // A burst of a hundred edits over 50 ms is drawn a handful of times, and
+    // every edit but the first of each frame joins one already pending.
+    for i in 0..100 {
+        gate.mark(Reason::Edit);
+        gate.take(start + Duration::from_micros(500 * i));
+    }
+    assert!(gate.renders <= 4, "{} renders", gate.renders);
+    assert_eq!(gate.coalesced, 100 - gate.renders - 1);
+
+    // What is still pending is drawn once the interval is up, with every
+    // reason since the last frame.
+    let later = start + Duration::from_millis(100);
+    gate.mark(Reason::View);
+    let dirty = gate.take(later).unwrap();
+    assert!(dirty.edit && dirty.view && !dirty.step);
+    assert_eq!(gate.remaining(later), None);
+}
+
+#[test]
+fn test_access_modes_override_what_they_constrain() {
+    let options = Options {
+        smooth: Some(Smooth::Fade),
+        fade_out: true,
+        palette: Palette::builtin("life-age").unwrap(),
+        ..Options::default()
+    };
+    let mut view = View {
+        flash: true,
+        grid: true,
+        trail: Some(Vec::new()),
+        ..View::default()
+    };
+    let asked = Effective::resolve(&options, &view);
+    assert_eq!(asked.smooth, Some(Smooth::Fade));
+    assert!(asked.flash && asked.fade_out && asked.trail && asked.grid && asked.colors);
+    assert_eq!(asked.interval, RENDER_INTERVAL);
+
+    // High contrast drops every dim shade, fading included.
+    view.access.high_contrast = true;
+    let contrast = Effective::resolve(&options, &view);
+    assert_eq!(contrast.smooth, Some(Smooth::Midpoint));
+    assert!(!contrast.trail && !contrast.grid && !contrast.colors);
+    assert!(contrast.flash && contrast.fade_out);
+    // Dissolving has no shades to drop.
+    let dissolving = Options {
+        smooth: Some(Smooth::Dissolve),
+        ..options.clone()
+    };
+    assert_eq!(
+        Effective::resolve(&dissolving, &view).smooth,
+        Some(Smooth::Dissolve)
+    );
+
+    // Reduced motion stills the animations and slows the frames.
+    view.access = Access {
+        high_contrast: false,
+        reduced_motion: true,
+    };
+    let still = Effective::resolve(&options, &view);
+    assert_eq!(still.smooth, None);
+    assert!(!still.flash && !still.fade_out && still.trail && still.colors);
+    assert_eq!(still.interval, REDUCED_MOTION_INTERVAL);
+    // Turning a mode off brings back what was asked for.
+    view.access = Access::default();
+    assert_eq!(Effective::resolve(&options, &view), asked);
+
+    // The gate keeps frames that far apart.
+    let start = Instant::now();
+    let mut gate = RenderGate::new();
+    gate.set_interval(REDUCED_MOTION_INTERVAL);
+    gate.mark(Reason::Step);
+    assert!(gate.take(start).is_some());
+    gate.mark(Reason::Step);
+    assert!(gate.take(start + Duration::from_millis(100)).is_none());
+    assert!(gate.take(start + REDUCED_MOTION_INTERVAL).is_some());
+}
+
+#[test]
+fn test_high_contrast_toggles_at_runtime() {
+    use termwiz::input::KeyEvent;
+
+    let mut game = GameOfLife::new_empty((20, 10));
+    game.set(GridPos(1, 1), true);
+    let palette = Palette::parse("1 = red").unwrap();
+    let red = palette.state(1);
+    let mut app = App::new(
+        game,
+        Options {
+            palette,
+            ..Options::default()
+        },
+    )
+    .unwrap();
+    let drawn = |app: &mut App<BlockRenderer<Offscreen>>| {
+        std::thread::sleep(RENDER_INTERVAL);
+        app.gate.mark(Reason::Edit);
+        assert!(app.draw().unwrap());
+        app.renderer.screen().screen_lines()[1]
+            .visible_cells()
+            .nth(1)
+            .unwrap()
+            .attrs()
+            .clone()
+    };
+    assert_eq!(drawn(&mut app).foreground(), red);
+    let key = InputEvent::Key(KeyEvent {
+        key: KeyCode::Char('C'),
+        modifiers: Modifiers::NONE,
+    });
+    assert!(app.handle_event(key).unwrap().is_continue());
+    assert!(app.session.view.access.high_contrast);
+    assert_eq!(drawn(&mut app).foreground(), shade(1.0, Theme::Dark));
+}
+
+#[test]
+fn test_neighbor_check_marks_nothing_on_a_correct_count() {
+    use termwiz::input::KeyEvent;
+
+    let mut game = GameOfLife::new_empty((30, 12));
+    game.fill(0.4, 3);
+    let mut app = App::new(game, Options::default()).unwrap();
+    let key = InputEvent::Key(KeyEvent {
+        key: KeyCode::Function(4),
+        modifiers: Modifiers::NONE,
+    });
+    assert!(app.handle_event(key).unwrap().is_continue());
+    assert!(app.session.view.check_neighbors);
+    std::thread::sleep(RENDER_INTERVAL);
+    app.gate.mark(Reason::Edit);
+    assert!(app.draw().unwrap());
+    let marked = |app: &mut App<BlockRenderer<Offscreen>>| {
+        let lines = app.renderer.screen().screen_lines();
+        let cells = lines
+            .iter()
+            .flat_map(|line| line.visible_cells().collect::<Vec<_>>());
+        cells
+            .filter(|cell| cell.attrs().background() == MISMATCH)
+            .count()
+    };
+    assert_eq!(marked(&mut app), 0);
+    // Where a disagreement would be shown.
+    let view = app.session.view.clone();
+    draw_marks(
+        &mut app.renderer,
+        &app.game,
+        &view,
+        &[GridPos(4, 2)],
+        MISMATCH,
+    );
+    assert_eq!(marked(&mut app), 1);
+}
+
+#[test]
+fn test_density_control_keeps_or_puts_back_the_region() {
+    use termwiz::input::KeyEvent;
+
+    let mut game = GameOfLife::new_empty((40, 12));
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(3, 3));
+    let before = game.surface().to_vec();
+    let mut app = App::new(game, Options::default()).unwrap();
+    let press = |app: &mut App<_>, key| {
+        let event = InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+        app.game().surface().to_vec()
+    };
+    // It opens refilled at 50%, holding the board still.
+    let half = press(&mut app, KeyCode::Char('D'));
+    assert!(app.session.paused);
+    assert_ne!(half, before);
+    assert!(app
+        .session
+        .region_fill
+        .as_ref()
+        .unwrap()
+        .status()
+        .starts_with("density 50% ["));
+    // The same density always shows the same soup.
+    let more = press(&mut app, KeyCode::RightArrow);
+    assert_ne!(more, half);
+    assert_eq!(press(&mut app, KeyCode::LeftArrow), half);
+    // Other keys go nowhere while it is open.
+    assert_eq!(press(&mut app, KeyCode::Char('c')), half);
+    // Escape puts the region back as it was.
+    assert_eq!(press(&mut app, KeyCode::Escape), before);
+    assert!(app.session.region_fill.is_none() && !app.session.paused);
+
+    // Enter keeps the refill.
+    press(&mut app, KeyCode::Char('D'));
+    for _ in 0..30 {
+        press(&mut app, KeyCode::LeftArrow);
+    }
+    let status = app.session.region_fill.as_ref().unwrap().status();
+    assert!(status.starts_with("density 5% [=------------------]"));
+    let kept = press(&mut app, KeyCode::Enter);
+    assert!(app.session.region_fill.is_none());
+    assert_ne!(kept, before);
+}
+
+#[test]
+fn test_menu_navigation() {
+    let mut menu = Menu::new(false);
+    assert!(menu.lines()[0].starts_with("> random soup  < 30% >"));
+    for _ in 0..3 {
+        assert_eq!(menu.key(KeyCode::RightArrow), None);
+    }
+    assert_eq!(menu.key(KeyCode::Enter), Some(Choice::Soup(0.45)));
+    // The density stays off the extremes.
+    for _ in 0..30 {
+        menu.key(KeyCode::LeftArrow);
+    }
+    assert_eq!(menu.key(KeyCode::Enter), Some(Choice::Soup(0.05)));
+
+    // Without a history file there is nothing below the patterns.
+    menu.key(KeyCode::DownArrow);
+    menu.key(KeyCode::DownArrow);
+    assert!(menu.lines()[1].starts_with("> pattern"));
+    menu.key(KeyCode::LeftArrow);
+    let last = Pattern::names().last().unwrap();
+    assert_eq!(menu.key(KeyCode::Enter), Some(Choice::Pattern(last)));
+    assert_eq!(menu.key(KeyCode::Char('q')), Some(Choice::Quit));
+
+    let mut resumable = Menu::new(true);
+    resumable.key(KeyCode::DownArrow);
+    resumable.key(KeyCode::DownArrow);
+    assert_eq!(resumable.lines()[2], "> resume last session");
+    assert_eq!(resumable.key(KeyCode::Enter), Some(Choice::Resume));
+}
+
+#[test]
+fn test_summary_report() {
+    let summary = Summary {
+        generations: 1200,
+        population: 532,
+        peak_population: 845,
+        settled: Some(Settled::Cycle(2)),
+        captures: None,
+        started_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_211_909),
+        elapsed: Duration::from_secs(30),
+        populations: vec![(0, 845), (1200, 532)],
+        activity: None,
+    };
+    assert_eq!(
+        summary.report(42),
+        "1200 generations in 30.0 s (40.0 tps), final population 532, seed 42"
+    );
+    let instant = Summary {
+        elapsed: Duration::ZERO,
+        ..summary.clone()
+    };
+    assert!(instant.report(0).contains("(0.0 tps)"));
+    let stats = summary.stats(42);
+    assert!(stats.starts_with("generations       1200\npeak population   845\n"));
+    assert!(stats.contains("\noutcome           oscillating with period 2\n"));
+    assert!(stats.contains("\nrunning time      30.0 s, 40.0 gen/s\n"));
+    assert!(stats.contains("\nstarted           2024-02-29 13:05:09 UTC\n"));
+    assert!(stats.ends_with("\nseed              42"));
+    let died = Summary {
+        settled: Some(Settled::Died),
+        ..summary
+    };
+    assert!(died.stats(0).contains("outcome           died out"));
+}
+
+#[test]
+fn test_click_toggles_and_drag_paints() {
+    let mut live = std::collections::HashSet::new();
+    let mut stroke = Stroke::default();
+    let mut feed = |live: &mut std::collections::HashSet<ScreenPos>, at, down| {
+        let painted = stroke.pointer(at, down, |at| live.contains(&at));
+        for &(at, value) in &painted {
+            if value {
+                live.insert(at);
+            } else {
+                live.remove(&at);
+            }
+        }
+        painted
+    };
+    // A click, even with the button reported held a few times, toggles once
+    // it is let go.
+    assert!(feed(&mut live, ScreenPos(2, 2), true).is_empty());
+    assert!(feed(&mut live, ScreenPos(2, 2), true).is_empty());
+    assert_eq!(
+        feed(&mut live, ScreenPos(2, 2), false),
+        [(ScreenPos(2, 2), true)]
+    );
+    assert_eq!(feed(&mut live, ScreenPos(2, 2), true), []);
+    assert_eq!(
+        feed(&mut live, ScreenPos(2, 2), false),
+        [(ScreenPos(2, 2), false)]
+    );
+
+    // Starting on a dead character, a drag draws over everything, live or not.
+    live.insert(ScreenPos(4, 0));
+    feed(&mut live, ScreenPos(3, 0), true);
+    assert_eq!(
+        feed(&mut live, ScreenPos(4, 0), true),
+        [(ScreenPos(3, 0), true), (ScreenPos(4, 0), true)]
+    );
+    feed(&mut live, ScreenPos(5, 0), true);
+    assert!(feed(&mut live, ScreenPos(5, 0), false).is_empty());
+    assert_eq!(live.len(), 3);
+
+    // Starting on a live one, it erases.
+    feed(&mut live, ScreenPos(4, 0), true);
+    feed(&mut live, ScreenPos(5, 0), true);
+    feed(&mut live, ScreenPos(6, 0), true);
+    feed(&mut live, ScreenPos(6, 0), false);
+    assert_eq!(live.into_iter().collect::<Vec<_>>(), [ScreenPos(3, 0)]);
+
+    // A held press paints nothing, even when it moves.
+    stroke.hold();
+    assert!(stroke.pointer(ScreenPos(1, 1), true, |_| false).is_empty());
+    assert!(stroke.pointer(ScreenPos(1, 1), false, |_| false).is_empty());
+}
+
+#[test]
+fn test_slow_motion_ramps_back() {
+    let slow = SlowMotion {
+        threshold: 10,
+        factor: 4.0,
+        duration: Duration::from_secs(2),
+    };
+    let target = Duration::from_millis(50);
+    let at = |secs: f64| slow.delay(target, Some(Duration::from_secs_f64(secs)));
+    assert_eq!(slow.delay(target, None), target);
+    assert_eq!(at(0.0), target * 4);
+    assert_eq!(at(1.9), target * 4);
+    // Halfway back up to speed, the delay is halfway between.
+    assert_eq!(at(3.0), target.mul_f64(2.5));
+    assert!(at(2.5) > at(3.0) && at(3.0) > at(3.5) && at(3.5) > target);
+    assert_eq!(at(4.0), target);
+    assert_eq!(at(60.0), target);
+    assert!(slow.active(Some(Duration::from_secs(3))));
+    assert!(!slow.active(Some(Duration::from_secs(4))));
+    assert!(!slow.active(None));
+}
+
+#[test]
+fn test_null_renderer_draws_nothing() {
+    let headless = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    let mut game = GameOfLife::new((12, 6));
+    game.fill(0.5, 3);
+    let mut null = NullRenderer::new(headless((12, 6)))
+        .unwrap()
+        .with_border(true);
+    null.render(&game, &View::default());
+    assert!(!null.screen().screen_chars_to_string().contains(['█', '─']));
+    // The world is laid out as a block renderer would lay it out.
+    let block = BlockRenderer::new(headless((12, 6)))
+        .unwrap()
+        .with_border(true);
+    assert_eq!(null.size(), block.size());
+    let cells = null.cells_under(game.size(), &View::default(), ScreenPos(3, 2));
+    assert_eq!(
+        cells,
+        block.cells_under(game.size(), &View::default(), ScreenPos(3, 2))
+    );
+}
+
+#[test]
+fn test_tiny_terminals() {
+    assert!(usable_dimensions(MIN_TERMINAL) && usable_dimensions(FALLBACK_TERMINAL));
+    for tiny in [(0, 0), (2, 1), (80, 3), (9, 24)] {
+        assert!(!usable_dimensions(tiny), "{tiny:?}");
+        assert!(too_small_lines(tiny, Options::default().min_size).is_some());
+    }
+    let lines = too_small_lines((0, 0), (20, 10)).unwrap();
+    assert!(lines.is_empty());
+
+    // A terminal that never grows is given up on; one already big enough
+    // isn't waited for.
+    let mut screen = BufferedTerminal::new(Offscreen { size: (2, 1) }).unwrap();
+    assert_eq!(startup_dimensions(&mut screen).unwrap(), None);
+    let mut screen = BufferedTerminal::new(Offscreen { size: MIN_TERMINAL }).unwrap();
+    assert_eq!(startup_dimensions(&mut screen).unwrap(), Some(MIN_TERMINAL));
+
+    // At the minimum size, framed or not, every renderer still has room for
+    // a world and draws it.
+    let mut game = GameOfLife::new_empty((40, 40));
+    game.fill(1.0, 0);
+    for border in [false, true] {
+        let screen = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+        let mut block = BlockRenderer::new(screen(MIN_TERMINAL))
+            .unwrap()
+            .with_border(border);
+        let mut braille = BrailleRenderer::new(screen(MIN_TERMINAL))
+            .unwrap()
+            .with_border(border);
+        assert!(block.size().0 > 0 && block.size().1 > 0);
+        assert!(braille.size().0 > 0 && braille.size().1 > 0);
+        block.render(&game, &View::default());
+        braille.render(&game, &View::default());
+        assert!(block.screen().screen_chars_to_string().contains('█'));
+        let view = View::default();
+        assert_eq!(
+            block
+                .cells_under(game.size(), &view, ScreenPos(0, 0))
+                .is_empty(),
+            border
+        );
+        // Below it, nothing panics either.
+        let mut tiny = BlockRenderer::new(screen((1, 1)))
+            .unwrap()
+            .with_border(border);
+        tiny.render(&game, &View::default());
+        assert!(tiny
+            .cells_under(game.size(), &view, ScreenPos(5, 5))
+            .is_empty());
+    }
+}
+
+#[test]
+fn test_notifications_expire_and_are_kept() {
+    let t0 = Instant::now();
+    let mut notices = Notifications::default();
+    assert_eq!((notices.current(t0), notices.remaining(t0)), (None, None));
+    assert_eq!(message_lines(&notices), ["no messages yet"]);
+
+    notices.push(Level::Info, "saved".into(), t0);
+    assert_eq!(notices.current(t0), Some((Level::Info, "saved")));
+    let second = Duration::from_secs(1);
+    assert_eq!(notices.remaining(t0 + second), Some(NOTICE - second));
+    // A newer message takes over, with its own full time on screen.
+    let t1 = t0 + Duration::from_secs(2);
+    notices.push(Level::Error, "invalid rule".into(), t1);
+    assert_eq!(
+        notices.current(t1 + Duration::from_secs(2)),
+        Some((Level::Error, "invalid rule"))
+    );
+    assert!(!notices.expire(t1 + NOTICE - Duration::from_millis(1)));
+    assert!(notices.expire(t1 + NOTICE));
+    assert!(!notices.expire(t1 + NOTICE * 2));
+    assert_eq!(notices.current(t1 + NOTICE), None);
+    assert_eq!(notices.remaining(t1 + NOTICE), None);
+
+    // Only the last few are kept, newest first.
+    for i in 0..NOTICE_LOG {
+        notices.push(Level::Warn, format!("warning {i}"), t1);
+    }
+    let lines = message_lines(&notices);
+    assert_eq!(lines.len(), NOTICE_LOG);
+    assert_eq!(lines[0], format!("warning: warning {}", NOTICE_LOG - 1));
+}
+
+#[test]
+fn test_known_sync_terminals() {
+    assert!(known_sync_terminal(Some("xterm-256color"), Some("WezTerm")));
+    assert!(known_sync_terminal(Some("xterm-kitty"), None));
+    assert!(known_sync_terminal(Some("foot-extra"), None));
+    assert!(!known_sync_terminal(
+        Some("xterm-256color"),
+        Some("Apple_Terminal")
+    ));
+    assert!(!known_sync_terminal(Some("linux"), None));
+    assert!(!known_sync_terminal(None, None));
+}
+
+#[test]
+fn test_scheduler_keeps_the_rate() {
+    use rand::Rng;
+
+    let ms = Duration::from_millis;
+    let period = ms(50);
+    let start = Instant::now();
+    // A loop that takes up to 30 ms besides waiting for the next tick, over
+    // a simulated minute, still steps 20 times a second.
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut scheduler = Scheduler::new(start, period);
+    let (mut now, mut ticks) = (start, 0);
+    while now < start + Duration::from_secs(60) {
+        now += scheduler.remaining(now);
+        ticks += scheduler.due(now, period);
+        now += ms(rng.gen_range(0..30));
+    }
+    assert!((1199..=1200).contains(&ticks), "{ticks}");
+    assert_eq!(scheduler.take_dropped(), 0);
+
+    // A late loop catches up on what it missed...
+    let mut scheduler = Scheduler::new(start, period);
+    assert_eq!(scheduler.due(start + ms(49), period), 0);
+    assert_eq!(scheduler.due(start + ms(160), period), 3);
+    assert_eq!(scheduler.remaining(start + ms(160)), ms(40));
+    // ...up to a point, after which it drops the rest and starts over.
+    assert_eq!(scheduler.due(start + ms(1200), period), MAX_CATCH_UP);
+    assert_eq!(scheduler.take_dropped(), 21 - MAX_CATCH_UP);
+    assert_eq!(scheduler.take_dropped(), 0);
+    assert_eq!(scheduler.remaining(start + ms(1200)), period);
+
+    // Speeding up takes effect without waiting out the slower tick.
+    let mut scheduler = Scheduler::new(start, Duration::from_secs(1));
+    assert_eq!(scheduler.due(start + ms(10), period), 0);
+    assert_eq!(scheduler.remaining(start + ms(10)), period);
+    assert_eq!(scheduler.due(start + ms(60), period), 1);
+}
+
+#[test]
+fn test_outline_leaves_out_the_insides_of_regions() {
+    let screen = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    let mut game = GameOfLife::new_empty((6, 6));
+    game.fill(1.0, 0);
+    game.set(GridPos(5, 5), false);
+    let outline = View {
+        outline: true,
+        ..View::default()
+    };
+    let mut block = BlockRenderer::new(screen((6, 6))).unwrap();
+    block.render(&game, &outline);
+    let text = block.screen().screen_chars_to_string();
+    let rows = text.lines().map(str::trim_end).collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        ["██████", "█    █", "█    █", "█    █", "█   ██", "█████"]
+    );
+    // Filled as usual without it.
+    block.render(&game, &View::default());
+    assert_eq!(
+        block.screen().screen_chars_to_string().matches('█').count(),
+        35
+    );
+
+    // On a torus the edges meet, so the only outline is around the hole,
+    // which the opposite edges touch too.
+    game.set_edge_mode(crate::EdgeMode::Toroidal);
+    assert!(on_outline(&game, 0, 0) && on_outline(&game, 4, 0) && !on_outline(&game, 5, 5));
+    assert!(!on_outline(&game, 0, 2) && !on_outline(&game, 2, 2));
+    game.set_edge_mode(crate::EdgeMode::Bounded);
+    assert!(on_outline(&game, 0, 2));
+
+    // A braille character lights only the dots on the outline: the top row
+    // and left column of its block.
+    let mut braille = BrailleRenderer::new(screen((3, 2))).unwrap();
+    braille.render(&game, &outline);
+    let first = braille
+        .screen()
+        .screen_chars_to_string()
+        .chars()
+        .next()
+        .unwrap();
+    assert_eq!(first, BRAILLE[1][1][1][0][1][0][0][0]);
+}
+
+#[test]
+fn test_overlay_draws_over_every_frame() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let screen = BufferedTerminal::new(Offscreen { size: (30, 12) }).unwrap();
+    let log = seen.clone();
+    let mut renderer = BlockRenderer::new(screen)
+        .unwrap()
+        .with_overlay(move |screen, game| {
+            log.borrow_mut().push(game.generation());
+            screen.add_change(Change::CursorPosition {
+                x: termwiz::surface::Position::Absolute(0),
+                y: termwiz::surface::Position::Absolute(0),
+            });
+            screen.add_change("@");
+        });
+    let mut game = GameOfLife::new_empty((30, 11));
+    game.insert_pattern(&Pattern::named("blinker").unwrap(), GridPos(4, 3));
+    let options = Options {
+        generations: Some(3),
+        ..Options::default()
+    };
+    renderer.run(&mut game, &options).unwrap();
+    // Drawn last, over the world, on each frame with the board it shows.
+    let seen = seen.borrow();
+    assert_eq!(seen.last(), Some(&3));
+    assert!(seen.windows(2).all(|pair| pair[0] <= pair[1]), "{seen:?}");
+    assert!(renderer.screen().screen_chars_to_string().starts_with('@'));
+}
+
+#[test]
+fn test_margin_is_kept_off_screen() {
+    let mut game = GameOfLife::new_empty((20, 12));
+    game.set(GridPos(3, 3), true);
+    // In the margin, so never shown.
+    game.set(GridPos(2, 2), true);
+    let mut app = App::new(
+        game,
+        Options {
+            margin: 3,
+            ..Options::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(app.session.view.offset, GridPos(3, 3));
+    let drawn = |app: &mut App<BlockRenderer<Offscreen>>| {
+        std::thread::sleep(RENDER_INTERVAL);
+        app.gate.mark(Reason::Edit);
+        assert!(app.draw().unwrap());
+        app.renderer.screen().screen_chars_to_string()
+    };
+    let screen = drawn(&mut app);
+    assert!(screen.starts_with('█'));
+    assert_eq!(screen.matches('█').count(), 1);
+    assert!(!screen.contains("edge past the margin"));
+    // Panning stays inside the margin too.
+    app.session
+        .view
+        .pan((-5, 20), app.game.size(), app.renderer.size());
+    assert_eq!(app.session.view.offset, GridPos(3, 3));
+
+    // The pointer is over the cells shown, not the margin.
+    for mouse_buttons in [MouseButtons::LEFT, MouseButtons::NONE] {
+        let click = MouseEvent {
+            x: 5,
+            y: 1,
+            mouse_buttons,
+            modifiers: Modifiers::NONE,
+        };
+        assert!(app
+            .handle_event(InputEvent::Mouse(click))
+            .unwrap()
+            .is_continue());
+    }
+    assert!(app.game().get(GridPos(8, 4)));
+
+    // Reaching the true edge is warned of.
+    app.game.set(GridPos(0, 5), true);
+    assert!(drawn(&mut app).contains("cells have reached the edge past the margin"));
+}
+
+#[test]
+fn test_app_draws_into_a_host_surface() {
+    use termwiz::input::KeyEvent;
+
+    let key = |c| {
+        InputEvent::Key(KeyEvent {
+            key: KeyCode::Char(c),
+            modifiers: Modifiers::NONE,
+        })
+    };
+    let mut game = GameOfLife::new_empty((10, 4));
+    game.insert_pattern(&Pattern::named("blinker").unwrap(), GridPos(3, 1));
+    let mut app = App::new(game, Options::default()).unwrap();
+    // The host's own surface, with something of its own in the corner.
+    let mut surface = Surface::new(40, 14);
+    surface.add_change("host");
+    let rect = Rect {
+        x: 5,
+        y: 2,
+        width: 30,
+        height: 12,
+    };
+    let rows = |surface: &Surface| {
+        let text = surface.screen_chars_to_string();
+        text.lines()
+            .map(|line| line.trim_end().to_string())
+            .collect::<Vec<_>>()
+    };
+    app.render_into(&mut surface, rect).unwrap();
+    let drawn = rows(&surface);
+    assert_eq!(drawn[0], "host");
+    assert_eq!(drawn[2..5], ["", "        ███", ""]);
+
+    // Stepped once by hand, the blinker turns upright.
+    assert!(app.handle_event(key('s')).unwrap().is_continue());
+    let later = Instant::now() + Duration::from_secs(1);
+    assert!(app.tick(later).is_continue());
+    assert_eq!(app.game().generation(), 1);
+    std::thread::sleep(RENDER_INTERVAL);
+    app.render_into(&mut surface, rect).unwrap();
+    assert_eq!(rows(&surface)[2..5], ["         █"; 3]);
+    // Paused by the step, it stays put however long it's left.
+    assert!(app.tick(later + Duration::from_secs(5)).is_continue());
+    assert_eq!(app.game().generation(), 1);
+    assert!(app.timeout(later) <= DELAY);
+
+    assert!(app.handle_event(key('q')).unwrap().is_break());
+    let (game, summary) = app.finish();
+    assert_eq!((game.population(), summary.generations), (3, 1));
+}
+
+#[test]
+fn test_rule_editor_applies_live_and_reverts() {
+    use termwiz::input::KeyEvent;
+
+    let mut app = App::new(GameOfLife::new_empty((40, 12)), Options::default()).unwrap();
+    let mut press = |key| {
+        let event = InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+        app.game().rule().to_string()
+    };
+    press(KeyCode::Char('e'));
+    // B0 can't be ticked; every other box toggles as soon as it is.
+    assert_eq!(press(KeyCode::Char(' ')), "B3/S23");
+    for _ in 0..6 {
+        press(KeyCode::RightArrow);
+    }
+    assert_eq!(press(KeyCode::Char(' ')), "B36/S23");
+    press(KeyCode::DownArrow);
+    press(KeyCode::LeftArrow);
+    assert_eq!(press(KeyCode::Char(' ')), "B36/S235");
+    let lines = rule_editor_lines("B36/S235".parse().unwrap(), (5, true));
+    assert_eq!(lines[0], "Rule B36/S235");
+    assert_eq!(lines[3], "birth    [ ][ ][ ][x][ ][ ][x][ ][ ]");
+    assert_eq!(lines[4], "survival [ ][ ][x][x][ ]>x<[ ][ ][ ]");
+    // Escape goes back to the rule the editor opened on, and closes it.
+    assert_eq!(press(KeyCode::Escape), "B3/S23");
+    assert_eq!(press(KeyCode::Char(' ')), "B3/S23");
+    // Enter keeps the changes.
+    press(KeyCode::Char('e'));
+    press(KeyCode::DownArrow);
+    assert_eq!(press(KeyCode::Char(' ')), "B3/S023");
+    press(KeyCode::Enter);
+    assert_eq!(press(KeyCode::Char(' ')), "B3/S023");
+}
+
+#[test]
+fn test_command_parsing() {
+    let parse = |s: &str| s.parse::<Command>();
+    assert_eq!(parse(":q"), Ok(Command::Action(Action::Quit)));
+    assert_eq!(parse("  step "), Ok(Command::Action(Action::Step)));
+    assert_eq!(
+        parse(":rule B36/S23"),
+        Ok(Command::Rule("B36/S23".parse().unwrap()))
+    );
+    assert_eq!(parse(":load foo.rle"), Ok(Command::Load("foo.rle".into())));
+    assert_eq!(parse(":save"), Ok(Command::Save(None)));
+    assert_eq!(
+        parse(":w out.rle"),
+        Ok(Command::Save(Some("out.rle".into())))
+    );
+    assert_eq!(parse(":set density 0.4"), Ok(Command::SetDensity(0.4)));
+    assert_eq!(parse(":set delay 1"), Ok(Command::SetDelay(MIN_DELAY)));
+    assert_eq!(
+        parse(":set delay 70"),
+        Ok(Command::SetDelay(Duration::from_millis(70)))
+    );
+    assert_eq!(parse(":"), Err("no command".to_string()));
+    assert_eq!(parse(":quit now"), Err("usage: :quit".to_string()));
+    assert_eq!(parse(":rule"), Err("usage: :rule B3/S23".to_string()));
+    assert_eq!(parse(":save a b"), Err("usage: :save [FILE]".to_string()));
+    assert!(parse(":rule B3/Sx").is_err());
+    assert!(parse(":set density 1.5")
+        .unwrap_err()
+        .starts_with("density must be"));
+    assert!(parse(":set speed 3")
+        .unwrap_err()
+        .starts_with("unknown setting"));
+    assert_eq!(
+        parse(":frobnicate"),
+        Err("unknown command \"frobnicate\"".to_string())
+    );
+}
+
+#[test]
+fn test_command_prompt_runs_commands() {
+    use termwiz::input::KeyEvent;
+
+    let options = Options {
+        bindings: KeyPreset::Vim.bindings(),
+        ..Options::default()
+    };
+    let mut app = App::new(GameOfLife::new_empty((40, 12)), options).unwrap();
+    let mut type_command = |line: &str| {
+        let keys = line.chars().map(KeyCode::Char);
+        for key in std::iter::once(KeyCode::Char(':')).chain(keys) {
+            let event = InputEvent::Key(KeyEvent {
+                key,
+                modifiers: Modifiers::NONE,
+            });
+            assert!(app.handle_event(event).unwrap().is_continue());
+        }
+        let event = InputEvent::Key(KeyEvent {
+            key: KeyCode::Enter,
+            modifiers: Modifiers::NONE,
+        });
+        let flow = app.handle_event(event).unwrap();
+        (flow.is_continue(), app.game().rule().to_string())
+    };
+    assert_eq!(type_command("rule B36/S23"), (true, "B36/S23".to_string()));
+    // A command that doesn't parse leaves the board as it was.
+    assert_eq!(type_command("rule nonsense"), (true, "B36/S23".to_string()));
+    // While the prompt is open, keys are typed rather than acted on.
+    assert_eq!(type_command("rule B3/S23q"), (true, "B36/S23".to_string()));
+    assert_eq!(type_command(""), (true, "B36/S23".to_string()));
+    assert_eq!(type_command("q"), (false, "B36/S23".to_string()));
+}
+
+#[test]
+fn test_wheel_sizes_the_brush() {
+    assert_eq!(
+        brush(ScreenPos(4, 4), 0).collect::<Vec<_>>(),
+        [ScreenPos(4, 4)]
+    );
+    assert_eq!(brush(ScreenPos(4, 4), 1).count(), 5);
+    assert_eq!(brush(ScreenPos(4, 4), 2).count(), 13);
+    // Past the top and left of the screen there is nothing to paint.
+    let edge = [ScreenPos(0, 0), ScreenPos(1, 0), ScreenPos(0, 1)];
+    assert_eq!(brush(ScreenPos(0, 0), 1).collect::<Vec<_>>(), edge);
+
+    let mut app = App::new(GameOfLife::new_empty((40, 20)), Options::default()).unwrap();
+    let mut mouse = |(x, y), mouse_buttons| {
+        let event = InputEvent::Mouse(MouseEvent {
+            x,
+            y,
+            mouse_buttons,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+        (app.session.brush, app.game().population())
+    };
+    let down = MouseButtons::VERT_WHEEL;
+    let up = down | MouseButtons::WHEEL_POSITIVE;
+    assert_eq!(mouse((0, 0), down), (0, 0));
+    for _ in 0..MAX_BRUSH + 2 {
+        mouse((0, 0), up);
+    }
+    assert_eq!(mouse((0, 0), down), (MAX_BRUSH - 1, 0));
+    while mouse((0, 0), down).0 > 1 {}
+    // A drag paints the brush's width all along: two discs of five
+    // characters, overlapping by two.
+    mouse((10, 5), MouseButtons::LEFT);
+    mouse((11, 5), MouseButtons::LEFT);
+    let (_, painted) = mouse((11, 5), MouseButtons::NONE);
+    let one = App::new(GameOfLife::new_empty((40, 20)), Options::default()).unwrap();
+    let per_char = one
+        .renderer
+        .cells_under((40, 20), &one.session.view, ScreenPos(10, 5))
+        .len();
+    assert_eq!(painted, 8 * per_char);
+}
+
+#[test]
+fn test_settling_is_fed_back_once() {
+    use std::{cell::RefCell, rc::Rc};
+    use termwiz::input::KeyEvent;
+
+    struct Recorder(Rc<RefCell<Vec<Event>>>);
+    impl Notifier for Recorder {
+        fn notify(&mut self, event: Event) {
+            self.0.borrow_mut().notify(event);
+        }
+    }
+    let step = InputEvent::Key(KeyEvent {
+        key: KeyCode::Char('s'),
+        modifiers: Modifiers::NONE,
+    });
+    let run = |game: GameOfLife, steps: u64| {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut app = App::new(game, Options::default()).unwrap();
+        app.notifier = Box::new(Recorder(events.clone()));
+        let mut now = Instant::now();
+        for _ in 0..steps {
+            assert!(app.handle_event(step.clone()).unwrap().is_continue());
+            now += Duration::from_secs(1);
+            assert!(app.tick(now).is_continue());
+        }
+        events.take()
+    };
+    let mut block = GameOfLife::new_empty((8, 8));
+    block.insert_pattern(&Pattern::named("block").unwrap(), GridPos(2, 2));
+    assert_eq!(
+        run(block, 4),
+        [Event::Stabilized {
+            generation: 2,
+            period: 1
+        }]
+    );
+    let mut lonely = GameOfLife::new_empty((8, 8));
+    lonely.set(GridPos(4, 4), true);
+    assert_eq!(run(lonely, 3), [Event::Extinction { generation: 1 }]);
+}
+
+#[test]
+fn test_challenge_status() {
+    use crate::challenge::ChallengeConfig;
+
+    let mut challenge = Challenge::new((8, 8), ChallengeConfig::default());
+    challenge.place(GridPos(3, 3)).unwrap();
+    assert!(challenge_status(&challenge).starts_with("99 of 100 cells left | click to place"));
+    challenge.go();
+    let status = challenge_status(&challenge);
+    assert_eq!(
+        status,
+        "generation 0 of at most 10000 | population 1, peak 1"
+    );
+    challenge.tick();
+    assert_eq!(challenge_status(&challenge), "over | 1 generations");
+}
+
+#[test]
+fn test_replay_keys_and_status() {
+    let span = (10, 500);
+    assert_eq!(replay_target(KeyCode::RightArrow, 10, span), Some(11));
+    assert_eq!(replay_target(KeyCode::LeftArrow, 10, span), None);
+    assert_eq!(replay_target(KeyCode::UpArrow, 450, span), Some(500));
+    assert_eq!(replay_target(KeyCode::DownArrow, 80, span), Some(10));
+    assert_eq!(replay_target(KeyCode::Home, 80, span), Some(10));
+    assert_eq!(replay_target(KeyCode::End, 500, span), None);
+    assert_eq!(replay_target(KeyCode::Char('x'), 80, span), None);
+
+    let status = replay_status(Some(12), Some(span), true);
+    assert!(
+        status.starts_with("generation 12 of 10-500, playing | space plays"),
+        "{status}"
+    );
+    assert!(replay_status(Some(500), Some(span), false).contains("at the end"));
+    assert_eq!(
+        replay_status(None, None, true),
+        "nothing recorded | q to quit"
+    );
+}
+
+#[test]
+fn test_a_served_app_streams_its_board() {
+    use termwiz::input::KeyEvent;
+
+    let path = std::env::temp_dir().join(format!("gol-serve-{}.sock", std::process::id()));
+    let options = Options {
+        serve: Some(path.clone()),
+        ..Options::default()
+    };
+    let game = GameOfLife::from_ascii(".....\n..#..\n..#..\n..#..\n.....");
+    let mut app = App::new(game, options).unwrap();
+    let mut feed = Feed::connect(&path).unwrap();
+    assert!(!feed.poll().unwrap(), "nothing is sent before a tick");
+    let status = observe_status(feed.generation(), feed.closed());
+    assert_eq!(status, "waiting for the board | q to quit");
+
+    assert!(app.tick(Instant::now()).is_continue());
+    assert!(feed.poll().unwrap());
+    assert_eq!(
+        feed.board().unwrap().fingerprint(),
+        app.game().fingerprint()
+    );
+    // Edits go out with the next tick, as steps do.
+    let clear = KeyEvent {
+        key: KeyCode::Char('c'),
+        modifiers: Modifiers::NONE,
+    };
+    assert!(app
+        .handle_event(InputEvent::Key(clear))
+        .unwrap()
+        .is_continue());
+    assert!(app.tick(Instant::now()).is_continue());
+    assert!(feed.poll().unwrap());
+    assert_eq!(feed.board().unwrap().population(), 0);
+
+    // Finishing hangs up and takes the socket with it.
+    let generation = app.game().generation();
+    app.finish();
+    assert!(!path.exists());
+    feed.poll().unwrap();
+    assert!(feed.closed());
+    let status = observe_status(feed.generation(), feed.closed());
+    assert_eq!(
+        status,
+        format!("the game hung up at gen {generation} | q to quit")
+    );
+}
+
+#[test]
+fn test_screensaver_picks_again_once_settled() {
+    let config = ScreensaverConfig {
+        candidates: 3,
+        burn_in: 20,
+        ..ScreensaverConfig::default()
+    };
+    let mut game = GameOfLife::new_empty((16, 16));
+    game.set_edge_mode(EdgeMode::Toroidal);
+    let mut screensaver = Screensaver::new(config);
+    screensaver.pick(&mut game);
+    let (seed, score) = screensaver.shown().unwrap();
+    assert!(seed < 3 && score > 0.0);
+    let soups = SoupConfig {
+        rule: game.rule(),
+        edges: game.edge_mode(),
+        size: game.size(),
+        density: config.density,
+        generations: config.burn_in,
+        seed,
+        soups: 1,
+    };
+    assert_eq!(
+        game.fingerprint(),
+        stats::burn_in(&soups, seed).0.fingerprint()
+    );
+
+    // Emptied, the board settles at once, and the next soups are tried
+    // while it stays on show.
+    game.surface_mut().fill(false);
+    for _ in 0..1000 {
+        screensaver.advance(&mut game);
+        if screensaver.shown() != Some((seed, score)) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let (next, _) = screensaver.shown().unwrap();
+    assert!((3..6).contains(&next));
+    assert!(game.population() > 0);
+}
+
+#[test]
+fn test_stamp_turns_about_the_pointer() {
+    // An L three wide and two tall, so turning it changes its box.
+    let ell = Pattern::from_ascii("###\n#..");
+    let mut stamp = Stamp::new("ell", ell.clone());
+    let (size, at) = ((40, 20), GridPos(10, 10));
+    let ghost = |stamp: &Stamp, at| {
+        let mut cells = stamp.ghost(at, size, EdgeMode::Bounded);
+        cells.sort_unstable();
+        cells
+    };
+    let mut cells = ell
+        .cells()
+        .iter()
+        .map(|&(x, y)| GridPos(10 + x, 10 + y))
+        .collect::<Vec<_>>();
+    cells.sort_unstable();
+    assert_eq!(ghost(&stamp, at), cells);
+    // A quarter turn clockwise about the top-left cell, which stays put:
+    // the foot of the L now hangs to the left of it.
+    stamp.transform(Transform::Rotate(1));
+    assert_eq!(stamp.anchor, (1, 0));
+    assert_eq!(
+        ghost(&stamp, at),
+        [
+            GridPos(9, 10),
+            GridPos(10, 10),
+            GridPos(10, 11),
+            GridPos(10, 12)
+        ]
+    );
+    // Whatever it goes through, the cell it was held by stays under the
+    // pointer, and a full turn puts it back.
+    for transform in [
+        Transform::Flip(Axis::Y),
+        Transform::Flip(Axis::Y),
+        Transform::Rotate(3),
+    ] {
+        stamp.transform(transform);
+        assert!(ghost(&stamp, at).contains(&at), "{transform:?}");
+    }
+    assert_eq!(stamp, Stamp::new("ell", ell));
+    // Past the top left edge the ghost is cut short, and stamping there says
+    // how much was lost; a torus takes it round instead.
+    stamp.transform(Transform::Rotate(1));
+    assert_eq!(
+        ghost(&stamp, GridPos(0, 0)),
+        [GridPos(0, 0), GridPos(0, 1), GridPos(0, 2)]
+    );
+    let (cropped, origin, clipped) = stamp.placement(GridPos(0, 0), size, EdgeMode::Bounded);
+    assert_eq!(
+        (cropped.size(), origin, clipped),
+        ((1, 3), GridPos(0, 0), 1)
+    );
+    let mut wrapped = stamp.ghost(GridPos(0, 0), size, EdgeMode::Toroidal);
+    wrapped.sort_unstable();
+    assert_eq!(
+        wrapped,
+        [GridPos(0, 0), GridPos(0, 1), GridPos(0, 2), GridPos(39, 0)]
+    );
+}
+
+#[test]
+fn test_wheel_turns_a_picked_stamp() {
+    let mut app = App::new(GameOfLife::new_empty((40, 20)), Options::default()).unwrap();
+    let glider = Pattern::named("glider").unwrap();
+    app.session.stamp = Some(Stamp::new("glider", glider.clone()));
+    let mouse = |app: &mut App<_>, mouse_buttons, modifiers| {
+        let event = InputEvent::Mouse(MouseEvent {
+            x: 10,
+            y: 5,
+            mouse_buttons,
+            modifiers,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    let up = MouseButtons::VERT_WHEEL | MouseButtons::WHEEL_POSITIVE;
+    mouse(&mut app, up, Modifiers::NONE);
+    mouse(&mut app, up, Modifiers::SHIFT);
+    // The wheel went to the stamp, not the brush.
+    assert_eq!(app.session.brush, 0);
+    let stamp = app.session.stamp.clone().unwrap();
+    let turned = glider.rotated(1).flipped(Axis::X);
+    assert_eq!(stamp.pattern, turned);
+    assert_eq!(stamp.anchor, (0, 0));
+    mouse(&mut app, MouseButtons::LEFT, Modifiers::NONE);
+    mouse(&mut app, MouseButtons::NONE, Modifiers::NONE);
+    assert!(app.session.stamp.is_none());
+    let under = app
+        .renderer
+        .screen_to_grid((40, 20), &app.session.view, ScreenPos(10, 5))
+        .unwrap();
+    let stamped = app.game().extract(Rect {
+        x: under.0,
+        y: under.1,
+        width: 3,
+        height: 3,
+    });
+    assert_eq!(stamped, turned);
+    assert_eq!(app.game().population(), 5);
+}
+
+#[test]
+fn test_burst_tells_pasted_keys_from_typed_ones() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut burst = Burst::default();
+    // Typed keys, however quick the typist, are further apart than a paste.
+    assert!(!burst.key(at(0)));
+    assert!(!burst.key(at(40)));
+    // A paste's keys come all at once, and are told about once.
+    assert!(burst.key(at(41)));
+    assert!(burst.hold());
+    assert!(burst.key(at(41)));
+    assert!(!burst.hold());
+    // A pause ends the burst, and the next one is told about again.
+    assert!(!burst.key(at(500)));
+    assert!(burst.key(at(501)));
+    assert!(burst.hold());
+}
+
+#[test]
+fn test_pastes_go_where_they_fit() {
+    use termwiz::input::KeyEvent;
+
+    let mut app = App::new(GameOfLife::new_empty((40, 20)), Options::default()).unwrap();
+    let paste = |app: &mut App<_>, text: &str| {
+        let event = InputEvent::Paste(text.to_string());
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    // Stray keys in a paste don't quit or clear, nor go anywhere else.
+    paste(&mut app, "quick, clear the cache");
+    assert!(app.session.stamp.is_none() && app.session.command.is_none());
+    let notice = app.session.notices.current(Instant::now());
+    assert_eq!(
+        notice,
+        Some((Level::Warn, "Ignored a paste of 22 characters"))
+    );
+
+    // One line is typed into the open command line.
+    app.session.command = Some("rule ".to_string());
+    paste(&mut app, "B36/S23\n");
+    assert_eq!(app.session.command.as_deref(), Some("rule B36/S23"));
+
+    // An RLE pattern is picked to stamp, from the command line or not.
+    paste(
+        &mut app,
+        "#N glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n",
+    );
+    assert!(app.session.command.is_none());
+    let stamp = app.session.stamp.clone().unwrap();
+    assert_eq!(
+        stamp.pattern.cells(),
+        Pattern::named("glider").unwrap().cells()
+    );
+
+    // A key hard on the heels of another, as in a paste without markers,
+    // can't quit.
+    app.session.burst.last = Some(Instant::now());
+    let q = InputEvent::Key(KeyEvent {
+        key: KeyCode::Char('q'),
+        modifiers: Modifiers::NONE,
+    });
+    assert!(app.handle_event(q).unwrap().is_continue());
+    assert!(!app.session.quit);
+}
+
+#[test]
+fn test_window_title_changes_at_most_once_a_second() {
+    assert_eq!(window_title(12_345, 4_210), "life — gen 12,345 (pop 4,210)");
+    assert_eq!(window_title(0, 999), "life — gen 0 (pop 999)");
+    assert_eq!(grouped(1_000_000), "1,000,000");
+    assert_eq!(grouped(100_000), "100,000");
+
+    let mut title = Retitle::default();
+    let start = Instant::now();
+    assert_eq!(
+        title.due(window_title(1, 5), start),
+        Some(window_title(1, 5))
+    );
+    // Too soon, then the same as is up already.
+    assert_eq!(
+        title.due(window_title(2, 5), start + TITLE_INTERVAL / 2),
+        None
+    );
+    assert_eq!(
+        title.due(window_title(1, 5), start + TITLE_INTERVAL * 2),
+        None
+    );
+    let later = start + TITLE_INTERVAL;
+    assert_eq!(
+        title.due(window_title(3, 6), later),
+        Some(window_title(3, 6))
+    );
+    assert_eq!(
+        title.due(window_title(4, 6), later + Duration::from_millis(999)),
+        None
+    );
+}
+
+#[test]
+fn test_title_and_notifications_go_out_between_frames() {
+    use self::offscreen::idle_waker;
+    use termwiz::terminal::{ScreenSize, TerminalWaker};
+
+    /// An offscreen terminal that keeps the titles and escapes sent to it.
+    struct Recording {
+        written: Vec<String>,
+    }
+    impl Terminal for Recording {
+        fn set_raw_mode(&mut self) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn set_cooked_mode(&mut self) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn enter_alternate_screen(&mut self) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn exit_alternate_screen(&mut self) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn get_screen_size(&mut self) -> termwiz::Result<ScreenSize> {
+            Ok(ScreenSize {
+                cols: 20,
+                rows: 10,
+                xpixel: 0,
+                ypixel: 0,
+            })
+        }
+        fn set_screen_size(&mut self, _: ScreenSize) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn render(&mut self, changes: &[Change]) -> termwiz::Result<()> {
+            for change in changes {
+                match change {
+                    Change::Title(title) => self.written.push(format!("title {title}")),
+                    Change::Text(text) if text.starts_with('\x1b') => {
+                        self.written.push(text.clone());
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        fn flush(&mut self) -> termwiz::Result<()> {
+            Ok(())
+        }
+        fn poll_input(&mut self, wait: Option<Duration>) -> termwiz::Result<Option<InputEvent>> {
+            std::thread::sleep(wait.unwrap_or_default().min(Duration::from_millis(5)));
+            Ok(None)
+        }
+        fn waker(&self) -> TerminalWaker {
+            idle_waker()
+        }
+    }
+    let screen = BufferedTerminal::new(Recording {
+        written: Vec::new(),
+    })
+    .unwrap();
+    let mut renderer = BlockRenderer::new(screen).unwrap();
+    let mut game = GameOfLife::new_empty((8, 8));
+    game.insert_pattern(&Pattern::named("block").unwrap(), GridPos(2, 2));
+    let options = Options {
+        title: true,
+        feedback: Feedback::Desktop,
+        generations: Some(6),
+        delay: Duration::from_millis(30),
+        ..Options::default()
+    };
+    renderer.run(&mut game, &options).unwrap();
+    let written = &renderer.screen().terminal().written;
+
+    // The title is saved first and put back last; the run is too short for
+    // it to change more than once in between.
+    assert_eq!(written.first().map(String::as_str), Some(PUSH_TITLE));
+    assert_eq!(
+        written[written.len() - 2..],
+        ["title ".to_string(), POP_TITLE.to_string()]
+    );
+    let titles: Vec<_> = written
+        .iter()
+        .filter(|line| line.starts_with("title life"))
+        .collect();
+    assert_eq!(titles.len(), 1);
+    assert!(titles[0].ends_with("(pop 4)"), "{titles:?}");
+    let settled = Event::Stabilized {
+        generation: 2,
+        period: 1,
+    }
+    .osc9();
+    assert_eq!(
+        written.iter().filter(|line| **line == settled).count(),
+        1,
+        "{written:?}"
+    );
+}
+
+#[test]
+fn test_diff_tints_what_changed_since_the_bookmark() {
+    use termwiz::surface::line::Line;
+
+    use termwiz::input::KeyEvent;
+
+    let game = GameOfLife::from_ascii(".....\n.###.\n.....");
+    let mut app = App::new(game, Options::default()).unwrap();
+    let press = |app: &mut App<BlockRenderer<Offscreen>>, c| {
+        let event = InputEvent::Key(KeyEvent {
+            key: KeyCode::Char(c),
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    // What is drawn where the board is: live cells as they are tinted, and
+    // dead ones as blank or marked.
+    let drawn = |app: &mut App<BlockRenderer<Offscreen>>| {
+        std::thread::sleep(RENDER_INTERVAL);
+        app.gate.mark(Reason::Edit);
+        assert!(app.draw().unwrap());
+        let lines = app.renderer.screen().screen_lines();
+        let row = |line: &Line| {
+            let cells = line.visible_cells().take(5);
+            let cell = |cell: termwiz::surface::line::CellRef| match cell.str() {
+                "█" if cell.attrs().foreground() == APPEARED => '+',
+                "█" => '#',
+                "·" if cell.attrs().foreground() == VANISHED => '-',
+                _ => '.',
+            };
+            cells.map(cell).collect::<String>()
+        };
+        lines[..3]
+            .iter()
+            .map(|line| row(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    // Nothing to diff against yet.
+    press(&mut app, 'z');
+    assert!(!app.session.view.diff);
+    press(&mut app, 'B');
+    press(&mut app, 'z');
+    assert_eq!(drawn(&mut app), ".....\n.###.\n.....");
+
+    app.session.paused = true;
+    app.game.step();
+    assert_eq!(drawn(&mut app), "..+..\n.-#-.\n..+..");
+    let status = app.renderer.screen().screen_chars_to_string();
+    assert!(status.contains("2 born, 2 gone since gen 0"), "{status}");
+    // Off, it draws as ever.
+    press(&mut app, 'z');
+    assert_eq!(drawn(&mut app), "..#..\n..#..\n..#..");
+
+    // A bookmark of another size can't be diffed against.
+    press(&mut app, 'z');
+    app.game.resize((6, 3));
+    drawn(&mut app);
+    assert!(!app.session.view.diff);
+    let (_, notice) = app.session.notices.current(Instant::now()).unwrap();
+    assert_eq!(notice, "The board is 6x3 now, not 5x3; diff off");
+}
+
+#[test]
+fn test_keys_and_the_mouse_drive_the_controls() {
+    use termwiz::input::{KeyEvent, MouseEvent};
+
+    let game = GameOfLife::from_ascii("......\n.###..\n......\n......");
+    let mut app = App::new(game, Options::default()).unwrap();
+    let key = |c| {
+        InputEvent::Key(KeyEvent {
+            key: KeyCode::Char(c),
+            modifiers: Modifiers::NONE,
+        })
+    };
+    let press = |app: &mut App<BlockRenderer<Offscreen>>, c| {
+        assert!(app.handle_event(key(c)).unwrap().is_continue());
+    };
+    press(&mut app, ' ');
+    assert!(app.session.paused);
+    press(&mut app, ' ');
+    assert!(!app.session.paused);
+
+    press(&mut app, '+');
+    assert_eq!(app.session.delay, DELAY / 2);
+    for _ in 0..20 {
+        press(&mut app, '+');
+    }
+    assert_eq!(app.session.delay, MIN_DELAY);
+    for _ in 0..40 {
+        press(&mut app, '-');
+    }
+    assert_eq!(app.session.delay, MAX_DELAY);
+
+    // Clicking and dragging draws; the board keeps what it had.
+    for (x, y, mouse_buttons) in [(4, 3, MouseButtons::LEFT), (5, 3, MouseButtons::LEFT)]
+        .into_iter()
+        .chain([(5, 3, MouseButtons::NONE)])
+    {
+        let event = MouseEvent {
+            x,
+            y,
+            mouse_buttons,
+            modifiers: Modifiers::NONE,
+        };
+        assert!(app
+            .handle_event(InputEvent::Mouse(event))
+            .unwrap()
+            .is_continue());
+    }
+    assert!(app.game().get(GridPos(4, 3)) && app.game().get(GridPos(5, 3)));
+    assert_eq!(app.game().population(), 5);
+
+    // Typed, not pasted, since clearing or quitting among keys too quick
+    // to be typed is held back.
+    std::thread::sleep(BURST_GAP);
+    press(&mut app, 'c');
+    assert_eq!(app.game().population(), 0);
+    std::thread::sleep(BURST_GAP);
+    assert!(app.handle_event(key('q')).unwrap().is_break());
+}
+
+#[test]
+fn test_exporting_an_image_runs_in_the_background() {
+    use termwiz::input::KeyEvent;
+
+    let path = std::env::temp_dir().join(format!("gol-board-{}.png", std::process::id()));
+    let options = Options {
+        image_path: path.clone(),
+        ..Options::default()
+    };
+    let mut app = App::new(GameOfLife::from_ascii("....\n.##.\n.##.\n...."), options).unwrap();
+    let press = |app: &mut App<BlockRenderer<Offscreen>>, key| {
+        let event = InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    // Escape means nothing to an export that isn't running.
+    assert!(!app.session.export_key(KeyCode::Escape));
+    press(&mut app, KeyCode::Char('E'));
+    let export = app.session.image_export.as_ref().unwrap();
+    while !export.worker.is_finished() {
+        std::thread::yield_now();
+    }
+    assert_eq!(export.percent.load(Ordering::Relaxed), 100);
+    app.session.poll_export();
+    assert!(app.session.image_export.is_none());
+    let wrote = format!("Wrote the board to {}", path.display());
+    assert_eq!(
+        app.session.notices.current(Instant::now()),
+        Some((Level::Info, wrote.as_str()))
+    );
+    let png = std::fs::read(&path).unwrap();
+    assert_eq!(&png[16..24], [0, 0, 0, 4, 0, 0, 0, 4]);
+
+    // Escape stops one that is, which leaves nothing behind unless it was
+    // already done.
+    std::fs::remove_file(&path).unwrap();
+    press(&mut app, KeyCode::Char('E'));
+    assert!(app.session.export_key(KeyCode::Escape));
+    match app
+        .session
+        .image_export
+        .take()
+        .unwrap()
+        .worker
+        .join()
+        .unwrap()
+    {
+        Ok(()) => std::fs::remove_file(&path).unwrap(),
+        Err(err) => assert_eq!(
+            (err.kind(), path.exists()),
+            (io::ErrorKind::Interrupted, false)
+        ),
+    }
+}
+
+#[test]
+fn test_finding_a_soup_searches_in_the_background() {
+    use termwiz::input::KeyEvent;
+
+    let mut app = App::new(GameOfLife::new_empty((16, 12)), Options::default()).unwrap();
+    let press = |app: &mut App<BlockRenderer<Offscreen>>, key| {
+        let event = InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    // Escape means nothing to a search that isn't running.
+    assert!(!app.session.search_key(KeyCode::Escape));
+    press(&mut app, KeyCode::Char('!'));
+    let search = app.session.search.as_ref().unwrap();
+    let config = search.config.clone();
+    assert_eq!((config.size, config.soups), ((16, 12), SEARCH_SOUPS));
+    while !search.worker.is_finished() {
+        std::thread::yield_now();
+    }
+    let tried = search.tried.load(Ordering::Relaxed);
+    assert!(app.tick(Instant::now()).is_continue());
+    assert!(app.session.search.is_none() && app.session.paused);
+    // The board is the best soup as it started, as seeded.
+    let notice = app
+        .session
+        .notices
+        .current(Instant::now())
+        .unwrap()
+        .1
+        .to_string();
+    let seed = notice
+        .strip_prefix("Found seed ")
+        .unwrap()
+        .split(',')
+        .next()
+        .unwrap();
+    let seed: u64 = seed.parse().unwrap();
+    assert!(
+        notice.ends_with(&format!("the best of {tried} soups")),
+        "{notice}"
+    );
+    let mut expected = GameOfLife::new_empty((16, 12));
+    expected.fill(config.density, seed);
+    assert_eq!(app.game().surface(), expected.surface());
+    let score = stats::score_soup(&config, seed);
+    assert!(notice.contains(&format!("scoring {score:.0},")), "{notice}");
+    // The board it went on can be taken back.
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.game().population(), 0);
+
+    // Escape abandons one that is running, which leaves the board alone.
+    press(&mut app, KeyCode::Char('!'));
+    let cancel = app.session.search.as_ref().unwrap().cancel.clone();
+    press(&mut app, KeyCode::Escape);
+    assert!(app.session.search.is_none() && cancel.load(Ordering::Relaxed));
+    let stopped = app.session.notices.current(Instant::now());
+    assert_eq!(stopped, Some((Level::Info, "Stopped searching soups")));
+    assert!(app.tick(Instant::now()).is_continue());
+    assert_eq!(app.game().population(), 0);
+}
+
+#[test]
+fn test_footprints_decide_sizing_mapping_and_fit() {
+    let screen = || BufferedTerminal::new(Offscreen { size: (80, 24) }).unwrap();
+    let renderers = |border| -> Vec<Box<dyn Renderer<Term = Offscreen>>> {
+        vec![
+            Box::new(BlockRenderer::new(screen()).unwrap().with_border(border)),
+            Box::new(
+                BlockRenderer::new(screen())
+                    .unwrap()
+                    .with_aspect(0.3)
+                    .with_border(border),
+            ),
+            Box::new(BrailleRenderer::new(screen()).unwrap().with_border(border)),
+            Box::new(
+                BrailleRenderer::new(screen())
+                    .unwrap()
+                    .with_aspect(0.3)
+                    .with_border(border),
+            ),
+            Box::new(NullRenderer::new(screen()).unwrap().with_border(border)),
+        ]
+    };
+    let footprints: Vec<_> = renderers(false)
+        .iter()
+        .map(|renderer| renderer.scale())
+        .collect();
+    let scale = |cells, chars| Scale { cells, chars };
+    let expected = [
+        scale((1, 1), (1, 1)),
+        scale((1, 1), (3, 1)),
+        scale((2, 3), (1, 1)),
+        scale((2, 4), (1, 1)),
+        scale((1, 1), (1, 1)),
+    ];
+    assert_eq!(footprints, expected);
+    for border in [false, true] {
+        let inset = if border { 1 } else { 0 };
+        for renderer in renderers(border) {
+            let Scale { cells, chars } = renderer.scale();
+            let case = format!("{cells:?} in {chars:?}, border {border}");
+            // The grid is as many whole blocks as the screen holds.
+            let (cols, rows) = (80 - 2 * inset, 24 - 2 * inset);
+            let size = (cols / chars.0 * cells.0, rows / chars.1 * cells.1);
+            assert_eq!(renderer.size(), size, "{case}");
+            assert_eq!(renderer.cells_for((80, 24)), size, "{case}");
+            // A block's characters show its cells, however the screen lays out.
+            let world = (500, 500);
+            for (x, y) in [
+                (inset, inset),
+                (inset + 7, inset + 5),
+                (inset + 41, inset + 19),
+            ] {
+                let (col, row) = ((x - inset) / chars.0, (y - inset) / chars.1);
+                let cell = GridPos(col * cells.0, row * cells.1);
+                let at = ScreenPos(x as u16, y as u16);
+                let at = renderer.screen_to_grid(world, &View::default(), at);
+                assert_eq!(at, Some(cell), "{case}: ({x}, {y})");
+            }
+            // The screen the grid needs is the one it was sized from, less
+            // what is left over past the last whole block; a cell more needs
+            // a bigger one.
+            let (need_w, need_h) = renderer.chars_for(size);
+            assert!(
+                need_w <= 80 && need_h <= 24 && 80 - need_w < chars.0,
+                "{case}"
+            );
+            assert_eq!(renderer.cells_for((need_w, need_h)), size, "{case}");
+            let (wider, taller) = (
+                renderer.chars_for((size.0 + 1, size.1)),
+                (size.0, size.1 + 1),
+            );
+            assert!(wider.0 > 80 && renderer.chars_for(taller).1 > 24, "{case}");
+        }
+    }
+    // A glider's three by three cells take three characters each way in
+    // blocks and two by one in braille, and a frame adds two more.
+    let framed = renderers(true);
+    assert_eq!(
+        (framed[0].chars_for((3, 3)), framed[2].chars_for((3, 3))),
+        ((5, 5), (4, 3))
+    );
+}
+
+#[test]
+fn test_glyph_tables() {
+    // Dots row by row, two to a row.
+    let dots = |on: &[usize]| std::array::from_fn(|i| on.contains(&i));
+    assert_eq!(Glyphs::Braille.glyph(dots(&[])), '⠀');
+    assert_eq!(Glyphs::Braille.glyph(dots(&[0])), '⠁');
+    assert_eq!(Glyphs::Braille.glyph(dots(&[0, 1, 2, 3, 4, 5, 6, 7])), '⣿');
+    // Every sextant is its own character, in Unicode's order.
+    let sextants: Vec<char> = (0..64).map(sextant).collect();
+    assert_eq!(&sextants[..4], [' ', '🬀', '🬁', '🬂']);
+    assert_eq!([sextants[21], sextants[42], sextants[63]], ['▌', '▐', '█']);
+    assert_eq!([sextants[20], sextants[22], sextants[62]], ['🬓', '🬔', '🬻']);
+    let mut distinct = sextants.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 64);
+    assert_eq!(Glyphs::Sextant.glyph(dots(&[1, 2, 5])), '🬤');
+    // Rows past a glyph's block don't change it.
+    assert_eq!(Glyphs::Sextant.glyph(dots(&[0, 6, 7])), '🬀');
+    assert_eq!(Glyphs::Quadrant.glyph(dots(&[0, 3])), '▚');
+    assert_eq!(Glyphs::Quadrant.glyph(dots(&[2, 3, 4])), '▄');
+    assert_eq!(Glyphs::Ascii.glyph(dots(&[0])), '\'');
+    assert_eq!(Glyphs::Ascii.glyph(dots(&[2, 1])), '.');
+    assert_eq!(Glyphs::Ascii.glyph(dots(&[0, 2])), ':');
+
+    assert_eq!("sextant".parse(), Ok(Glyphs::Sextant));
+    assert!("emoji".parse::<Glyphs>().is_err());
+    assert_eq!(Glyphs::guess(Some("linux")), Glyphs::Ascii);
+    assert_eq!(Glyphs::guess(Some("vt220")), Glyphs::Ascii);
+    assert_eq!(Glyphs::guess(Some("xterm-256color")), Glyphs::Braille);
+    assert_eq!(Glyphs::guess(None), Glyphs::Braille);
+}
+
+#[test]
+fn test_fallback_glyphs_keep_the_grid_consistent() {
+    let headless = |size| BufferedTerminal::new(Offscreen { size }).unwrap();
+    // A glider, drawn in each set of glyphs on a 6x4 screen.
+    let game = GameOfLife::from_ascii(".#....\n..#...\n###...\n......\n......\n......");
+    let drawn = |glyphs| {
+        let mut renderer = BrailleRenderer::new(headless((6, 4)))
+            .unwrap()
+            .with_glyphs(glyphs);
+        renderer.render(&game, &View::default());
+        let text = renderer.screen().screen_chars_to_string();
+        (renderer.size(), renderer.scale().cells, text)
+    };
+    let (size, cells, text) = drawn(Glyphs::Sextant);
+    assert_eq!((size, cells), ((12, 12), (2, 3)));
+    assert!(text.starts_with("🬯🬓    \n"), "{text:?}");
+    let (size, cells, text) = drawn(Glyphs::Quadrant);
+    assert_eq!((size, cells), ((12, 8), (2, 2)));
+    assert!(text.starts_with("▝▖    \n▀▘    \n"), "{text:?}");
+    let (size, cells, text) = drawn(Glyphs::Ascii);
+    assert_eq!((size, cells), ((6, 8), (1, 2)));
+    assert!(text.starts_with(" '.   \n'''   \n"), "{text:?}");
+    // Each downgrade shows fewer cells, but the screen and mouse still agree
+    // with the size it reports.
+    for glyphs in [
+        Glyphs::Braille,
+        Glyphs::Sextant,
+        Glyphs::Quadrant,
+        Glyphs::Ascii,
+    ] {
+        for aspect in [CELL_ASPECT, BrailleRenderer::<Offscreen>::ASPECT] {
+            let renderer = BrailleRenderer::new(headless((6, 4)))
+                .unwrap()
+                .with_aspect(aspect)
+                .with_glyphs(glyphs);
+            let Scale { cells, .. } = renderer.scale();
+            assert_eq!(renderer.size(), (6 * cells.0, 4 * cells.1), "{glyphs:?}");
+            let last = renderer.screen_to_grid((100, 100), &View::default(), ScreenPos(5, 3));
+            assert_eq!(last, Some(GridPos(5 * cells.0, 3 * cells.1)), "{glyphs:?}");
+        }
+    }
+}
+
+#[test]
+fn test_undo_takes_back_edits_a_drag_at_a_time() {
+    use termwiz::input::KeyEvent;
+
+    let game = GameOfLife::from_ascii("......\n..#...\n..#...\n..#...");
+    let mut app = App::new(game, Options::default()).unwrap();
+    let press = |app: &mut App<BlockRenderer<Offscreen>>, key| {
+        let event = InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    let mouse = |app: &mut App<BlockRenderer<Offscreen>>, x, mouse_buttons| {
+        let event = MouseEvent {
+            x,
+            y: 0,
+            mouse_buttons,
+            modifiers: Modifiers::NONE,
+        };
+        assert!(app
+            .handle_event(InputEvent::Mouse(event))
+            .unwrap()
+            .is_continue());
+    };
+    let board = |app: &App<BlockRenderer<Offscreen>>| app.game().surface().to_vec();
+    let start = board(&app);
+    for x in 0..4 {
+        mouse(&mut app, x, MouseButtons::LEFT);
+    }
+    mouse(&mut app, 3, MouseButtons::NONE);
+    let drawn = board(&app);
+    assert_ne!(drawn, start);
+    press(&mut app, KeyCode::Char('c'));
+    assert_eq!(app.game().population(), 0);
+    press(&mut app, KeyCode::Char('r'));
+    assert!(app.game().population() > 0);
+
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.game().population(), 0);
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(board(&app), drawn);
+    // The whole drag goes at once.
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(board(&app), start);
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(
+        app.session.notices.current(Instant::now()),
+        Some((Level::Info, "Nothing to undo"))
+    );
+}
+
+#[test]
+fn test_notes_are_laid_out_clear_of_each_other() {
+    let note = |pos, text: &str| Annotation::new(pos, text, Annotation::COLOR).unwrap();
+    let notes = [
+        note(GridPos(2, 1), "gun #1"),
+        // Would run into the first, so goes down a row.
+        note(GridPos(4, 1), "eater"),
+        note(GridPos(0, 5), "a label far longer than any note should be"),
+        note(GridPos(9, 3), "edge"),
+        note(GridPos(30, 30), "off screen"),
+        // Nowhere left to go on the bottom row.
+        note(GridPos(0, 7), "lane"),
+        note(GridPos(1, 7), "lane"),
+    ];
+    let area = Rect {
+        x: 0,
+        y: 0,
+        width: 12,
+        height: 8,
+    };
+    let place = |GridPos(x, y)| (x < 12 && y < 8).then_some((x, y));
+    let labels = note_labels(&notes, area, place);
+    let laid: Vec<_> = labels
+        .iter()
+        .map(|l| (l.index, l.at, l.text.as_str()))
+        .collect();
+    assert_eq!(
+        laid,
+        [
+            (0, (2, 1), "gun #1"),
+            (1, (4, 2), "eater"),
+            (2, (0, 5), "a label far…"),
+            (3, (9, 3), "ed…"),
+            (5, (0, 7), "lane"),
+        ]
+    );
+    assert_eq!(note_under(&labels, ScreenPos(7, 1)), Some(0));
+    assert_eq!(note_under(&labels, ScreenPos(8, 1)), None);
+    assert_eq!(note_under(&labels, ScreenPos(4, 2)), Some(1));
+    assert_eq!(note_under(&labels, ScreenPos(11, 3)), Some(3));
+    assert_eq!(note_under(&labels, ScreenPos(0, 6)), None);
+}
+
+#[test]
+fn test_notes_are_pinned_deleted_and_saved() {
+    use termwiz::input::KeyEvent;
+
+    let mut app = App::new(GameOfLife::new_empty((40, 12)), Options::default()).unwrap();
+    let key = |key| {
+        InputEvent::Key(KeyEvent {
+            key,
+            modifiers: Modifiers::NONE,
+        })
+    };
+    let type_keys = |app: &mut App<_>, keys: &str| {
+        for c in keys.chars() {
+            assert!(app
+                .handle_event(key(KeyCode::Char(c)))
+                .unwrap()
+                .is_continue());
+        }
+        assert!(app.handle_event(key(KeyCode::Enter)).unwrap().is_continue());
+    };
+    let drawn = |app: &mut App<BlockRenderer<Offscreen>>| {
+        std::thread::sleep(RENDER_INTERVAL);
+        app.gate.mark(Reason::View);
+        assert!(app.draw().unwrap());
+        app.renderer.screen().screen_chars_to_string()
+    };
+    // The inspector's cursor says where; notes aren't touched by stepping.
+    assert!(app
+        .handle_event(key(KeyCode::Char('i')))
+        .unwrap()
+        .is_continue());
+    assert!(app
+        .handle_event(key(KeyCode::Char('a')))
+        .unwrap()
+        .is_continue());
+    type_keys(&mut app, "gun #1");
+    assert!(app
+        .handle_event(key(KeyCode::Escape))
+        .unwrap()
+        .is_continue());
+    let cursor = app.session.view.cursor.unwrap();
+    assert_eq!(app.session.annotations[0].pos, cursor);
+    assert_eq!(app.session.annotations[0].text, "gun #1");
+    assert_eq!(app.session.command, None);
+    assert!(drawn(&mut app).contains("gun #1"));
+    // Hiding them redraws the rows they covered.
+    assert!(app
+        .handle_event(key(KeyCode::Char('A')))
+        .unwrap()
+        .is_continue());
+    assert!(!drawn(&mut app).contains("gun #1"));
+    assert!(app
+        .handle_event(key(KeyCode::Char('A')))
+        .unwrap()
+        .is_continue());
+
+    // While annotating, a click off any note starts one there, and a click on
+    // one deletes it rather than painting.
+    assert!(app
+        .handle_event(key(KeyCode::Char('i')))
+        .unwrap()
+        .is_continue());
+    assert!(app
+        .handle_event(key(KeyCode::Char('a')))
+        .unwrap()
+        .is_continue());
+    assert!(app
+        .handle_event(key(KeyCode::Escape))
+        .unwrap()
+        .is_continue());
+    let at = app
+        .renderer
+        .grid_to_screen((40, 12), &app.session.view, GridPos(3, 2))
+        .unwrap();
+    let click = |app: &mut App<_>, (x, y): (usize, usize), down| {
+        let mouse_buttons = if down {
+            MouseButtons::LEFT
+        } else {
+            MouseButtons::NONE
+        };
+        let (x, y) = (x as u16, y as u16);
+        let modifiers = Modifiers::NONE;
+        let event = InputEvent::Mouse(MouseEvent {
+            x,
+            y,
+            mouse_buttons,
+            modifiers,
+        });
+        assert!(app.handle_event(event).unwrap().is_continue());
+    };
+    click(&mut app, (at.x, at.y), true);
+    click(&mut app, (at.x, at.y), false);
+    let (renderer, game, options) = (&mut app.renderer, &mut app.game, &app.options);
+    app.session
+        .run_command(":set note-color red", renderer, game, options)
+        .unwrap();
+    type_keys(&mut app, "eater");
+    assert_eq!(app.session.annotations[1].to_string(), "3,2 red eater");
+    assert_eq!(app.game().population(), 0);
+
+    let path = std::env::temp_dir().join(format!("gol-notes-{}.rle", std::process::id()));
+    let save = format!(":save {}", path.display());
+    let (renderer, game, options) = (&mut app.renderer, &mut app.game, &app.options);
+    app.session
+        .run_command(&save, renderer, game, options)
+        .unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("#C annotation = 3,2 red eater\n"), "{text}");
+
+    let notes = &app.session.annotations;
+    let first = placed_notes(&mut app.renderer, &app.game, &app.session.view, notes);
+    click(&mut app, first[0].at, true);
+    click(&mut app, first[0].at, false);
+    assert_eq!(app.session.annotations.len(), 1);
+    assert_eq!(app.session.annotations[0].text, "eater");
+    // Escape stops annotating, and clicks paint again.
+    assert!(app
+        .handle_event(key(KeyCode::Escape))
+        .unwrap()
+        .is_continue());
+    click(&mut app, (at.x, at.y), true);
+    click(&mut app, (at.x, at.y), false);
+    assert_eq!(app.game().population(), 1);
+
+    // Loading the file brings back its notes in place of the board's.
+    let mut loaded = App::new(GameOfLife::new_empty((40, 12)), Options::default()).unwrap();
+    let load = format!(":load {}", path.display());
+    let (renderer, game, options) = (&mut loaded.renderer, &mut loaded.game, &loaded.options);
+    loaded
+        .session
+        .run_command(&load, renderer, game, options)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let notes: Vec<_> = loaded
+        .session
+        .annotations
+        .iter()
+        .map(|note| note.to_string())
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            format!("{},{} yellow gun #1", cursor.0, cursor.1),
+            "3,2 red eater".into()
+        ]
+    );
+}
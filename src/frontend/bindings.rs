@@ -0,0 +1,354 @@
+use std::{collections::HashMap, str::FromStr};
+
+use termwiz::input::KeyCode;
+
+/// Something the user can do from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    /// Shows the key bindings until the next key press.
+    Help,
+    Quit,
+    TogglePause,
+    /// Pauses and advances a single generation.
+    Step,
+    /// Kills every cell.
+    Clear,
+    /// Fills the board at random again.
+    Reseed,
+    /// Brings a few dead cells to life at random, keeping the live ones.
+    Sprinkle,
+    /// Halves the delay between generations.
+    SpeedUp,
+    /// Doubles the delay between generations.
+    SlowDown,
+    ToggleMinimap,
+    ToggleSpaceships,
+    ToggleCensus,
+    ToggleDiagnostics,
+    /// Shows or hides where cells died recently.
+    ToggleTrail,
+    /// Turns flashing the cells born and killed by each step on or off.
+    ToggleFlash,
+    /// Turns drawing only the outlines of live regions on or off.
+    ToggleOutline,
+    /// Shows or hides a faint dot over every stretch of dead cells.
+    ToggleGrid,
+    /// Opens the [density control](super::session::RegionFill) over the cells on screen.
+    FillRegion,
+    /// Turns on or off counting every cell's neighbors both the quick way
+    /// and one by one, each frame, and marking where they disagree: a check
+    /// on the quick count that should never mark anything.
+    ToggleNeighborCheck,
+    /// Turns [high contrast](super::Access::high_contrast) on or off.
+    ToggleHighContrast,
+    /// Turns [reduced motion](super::Access::reduced_motion) on or off.
+    ToggleReducedMotion,
+    /// Turns slowing down for bursts of activity on or off.
+    ToggleSlowMotion,
+    /// Goes on to the next [`EdgeMode`](crate::EdgeMode): bounded, toroidal, mirror.
+    CycleEdges,
+    /// Injects [`INJECT_STEP`](super::session::INJECT_STEP) more cells per generation at the edge, while
+    /// injecting.
+    InjectMore,
+    /// Injects [`INJECT_STEP`](super::session::INJECT_STEP) fewer cells per generation, down to none.
+    InjectLess,
+    /// Opens or closes the inspector, whose cursor the arrows move.
+    Inspect,
+    /// Shows or hides the last few messages.
+    Messages,
+    /// Pauses and opens the picker of library patterns to stamp.
+    Library,
+    /// Opens the rule editor, whose boxes the arrows move between.
+    EditRule,
+    /// Opens the prompt for a [`Command`](super::Command).
+    Command,
+    /// Switches to the next [`KeyPreset`].
+    NextKeys,
+    /// Writes the recent generations to a multi-frame RLE file.
+    DumpHistory,
+    /// Opens or closes the history scrubber.
+    Scrub,
+    /// Pauses and goes back [`STEP_BACK`](super::session::STEP_BACK) generations.
+    StepBack,
+    /// Puts the generation on the board aside to diff later ones against.
+    Bookmark,
+    /// Turns tinting the cells that differ from the bookmark on or off.
+    ToggleDiff,
+    /// Writes the board to a PNG in the background; escape cancels it.
+    ExportImage,
+    /// Takes back the last edit to the board, up to [`UNDO_DEPTH`](super::session::UNDO_DEPTH) of them.
+    Undo,
+    /// Opens the prompt for a note on the cell under the cursor or the
+    /// pointer, and goes on annotating: clicking a note deletes it, clicking
+    /// anywhere else starts a note there, and escape stops.
+    Annotate,
+    /// Shows or hides the notes pinned to the board.
+    ToggleNotes,
+    /// Scores [`SEARCH_SOUPS`](super::SEARCH_SOUPS) soups in the background for up to
+    /// [`SEARCH_BUDGET`](super::SEARCH_BUDGET), then puts the best on the board, paused; escape
+    /// cancels.
+    FindInteresting,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+}
+
+impl Action {
+    /// What the action does, as listed in the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Help => "show this help",
+            Action::Quit => "quit",
+            Action::TogglePause => "pause or resume",
+            Action::Step => "step one generation",
+            Action::Clear => "clear the board",
+            Action::Reseed => "fill the board at random",
+            Action::Sprinkle => "sprinkle live cells among the dead, keeping the live ones",
+            Action::SpeedUp => "speed up",
+            Action::SlowDown => "slow down",
+            Action::ToggleMinimap => "toggle the minimap",
+            Action::ToggleSpaceships => "label spaceships",
+            Action::ToggleCensus => "toggle the census",
+            Action::ToggleDiagnostics => "toggle frame timings",
+            Action::ToggleTrail => "toggle trails of dead cells",
+            Action::ToggleFlash => "flash births and deaths",
+            Action::ToggleOutline => "outline dense regions instead of filling them",
+            Action::ToggleGrid => "dot dead cells faintly, to show the board's extent",
+            Action::ToggleNeighborCheck => "mark in red where the neighbor counts disagree",
+            Action::FillRegion => "refill what's on screen at a density you pick (arrows)",
+            Action::ToggleHighContrast => "draw in high contrast, without dim shades",
+            Action::ToggleReducedMotion => "cut down on animation and the frame rate",
+            Action::ToggleSlowMotion => "slow down when a lot changes at once",
+            Action::CycleEdges => "switch the edges: dead, wrapping or mirrored",
+            Action::InjectMore => "inject more cells at the edge",
+            Action::InjectLess => "inject fewer cells at the edge",
+            Action::Inspect => "inspect cells under a cursor (arrows move it)",
+            Action::Messages => "show recent messages",
+            Action::Library => "pick a pattern to stamp with a click",
+            Action::EditRule => "edit the rule (arrows, space toggles, escape reverts)",
+            Action::Command => "type a command: rule, load, save, set, note, q",
+            Action::NextKeys => "switch to the next set of keys",
+            Action::DumpHistory => "write recent history to a file",
+            Action::Scrub => "scrub through history (arrows, enter resumes)",
+            Action::StepBack => "go back 10 generations",
+            Action::Bookmark => "bookmark this generation to diff against",
+            Action::ToggleDiff => "tint the cells that differ from the bookmark",
+            Action::ExportImage => "write the board to a PNG (escape cancels)",
+            Action::Undo => "undo the last edit to the board",
+            Action::Annotate => "pin a note here; then clicks add and delete notes",
+            Action::ToggleNotes => "show or hide the notes",
+            Action::FindInteresting => "search soups for one worth watching (escape cancels)",
+            Action::PanLeft => "pan left",
+            Action::PanRight => "pan right",
+            Action::PanUp => "pan up",
+            Action::PanDown => "pan down",
+        }
+    }
+}
+
+/// The built-in key bindings.
+pub fn default_bindings() -> HashMap<KeyCode, Action> {
+    HashMap::from([
+        (KeyCode::Char('?'), Action::Help),
+        (KeyCode::Char('q'), Action::Quit),
+        (KeyCode::Char(' '), Action::TogglePause),
+        (KeyCode::Char('s'), Action::Step),
+        (KeyCode::Char('c'), Action::Clear),
+        (KeyCode::Char('r'), Action::Reseed),
+        (KeyCode::Char('S'), Action::Sprinkle),
+        (KeyCode::Char('+'), Action::SpeedUp),
+        (KeyCode::Char('='), Action::SpeedUp),
+        (KeyCode::Char('-'), Action::SlowDown),
+        (KeyCode::Char('m'), Action::ToggleMinimap),
+        (KeyCode::Char('M'), Action::ToggleMinimap),
+        (KeyCode::Char('v'), Action::ToggleSpaceships),
+        (KeyCode::Char('o'), Action::ToggleCensus),
+        (KeyCode::Function(3), Action::ToggleDiagnostics),
+        (KeyCode::Function(4), Action::ToggleNeighborCheck),
+        (KeyCode::Char('t'), Action::ToggleTrail),
+        (KeyCode::Char('f'), Action::ToggleFlash),
+        (KeyCode::Char('b'), Action::ToggleOutline),
+        (KeyCode::Char('d'), Action::ToggleGrid),
+        (KeyCode::Char('D'), Action::FillRegion),
+        (KeyCode::Char('C'), Action::ToggleHighContrast),
+        (KeyCode::Char('R'), Action::ToggleReducedMotion),
+        (KeyCode::Char('x'), Action::CycleEdges),
+        (KeyCode::Char('w'), Action::ToggleSlowMotion),
+        (KeyCode::Char(']'), Action::InjectMore),
+        (KeyCode::Char('['), Action::InjectLess),
+        (KeyCode::Char('i'), Action::Inspect),
+        (KeyCode::Char('n'), Action::Messages),
+        (KeyCode::Char('p'), Action::Library),
+        (KeyCode::Char('e'), Action::EditRule),
+        (KeyCode::Char('H'), Action::DumpHistory),
+        (KeyCode::Char('G'), Action::Scrub),
+        (KeyCode::Char('U'), Action::StepBack),
+        (KeyCode::Char('B'), Action::Bookmark),
+        (KeyCode::Char('z'), Action::ToggleDiff),
+        (KeyCode::Char('E'), Action::ExportImage),
+        (KeyCode::Char('u'), Action::Undo),
+        (KeyCode::Char('a'), Action::Annotate),
+        (KeyCode::Char('A'), Action::ToggleNotes),
+        (KeyCode::Char('!'), Action::FindInteresting),
+        (KeyCode::LeftArrow, Action::PanLeft),
+        (KeyCode::RightArrow, Action::PanRight),
+        (KeyCode::UpArrow, Action::PanUp),
+        (KeyCode::DownArrow, Action::PanDown),
+        (KeyCode::Function(2), Action::NextKeys),
+    ])
+}
+
+/// A whole set of key bindings to pick from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyPreset {
+    /// The [`default_bindings`].
+    #[default]
+    Default,
+    /// The defaults, with hjkl panning and `:` opening the command prompt.
+    Vim,
+    /// Every key on the right of the keyboard, to leave the left hand on
+    /// the mouse: ijkl pan, space pauses, `.` steps and `P` quits.
+    LeftHanded,
+}
+
+impl KeyPreset {
+    /// The preset after this one, coming back round to the first.
+    pub fn next(self) -> Self {
+        match self {
+            KeyPreset::Default => KeyPreset::Vim,
+            KeyPreset::Vim => KeyPreset::LeftHanded,
+            KeyPreset::LeftHanded => KeyPreset::Default,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyPreset::Default => "default",
+            KeyPreset::Vim => "vim",
+            KeyPreset::LeftHanded => "left-handed",
+        }
+    }
+
+    pub fn bindings(self) -> HashMap<KeyCode, Action> {
+        let mut bindings = default_bindings();
+        match self {
+            KeyPreset::Default => {}
+            KeyPreset::Vim => bindings.extend([
+                (KeyCode::Char('h'), Action::PanLeft),
+                (KeyCode::Char('j'), Action::PanDown),
+                (KeyCode::Char('k'), Action::PanUp),
+                (KeyCode::Char('l'), Action::PanRight),
+                (KeyCode::Char(':'), Action::Command),
+            ]),
+            KeyPreset::LeftHanded => {
+                bindings.retain(|key, _| !matches!(key, KeyCode::Char(_)));
+                bindings.extend(
+                    [
+                        ('?', Action::Help),
+                        ('P', Action::Quit),
+                        (' ', Action::TogglePause),
+                        ('.', Action::Step),
+                        (',', Action::StepBack),
+                        ('Y', Action::Clear),
+                        ('y', Action::Reseed),
+                        ('<', Action::Sprinkle),
+                        ('=', Action::SpeedUp),
+                        ('+', Action::SpeedUp),
+                        ('-', Action::SlowDown),
+                        ('m', Action::ToggleMinimap),
+                        ('u', Action::ToggleSpaceships),
+                        ('o', Action::ToggleCensus),
+                        ('h', Action::ToggleTrail),
+                        ('n', Action::ToggleFlash),
+                        ('N', Action::ToggleSlowMotion),
+                        ('I', Action::ToggleOutline),
+                        ('L', Action::ToggleGrid),
+                        ('/', Action::FillRegion),
+                        ('U', Action::ToggleHighContrast),
+                        ('M', Action::ToggleReducedMotion),
+                        ('K', Action::CycleEdges),
+                        (']', Action::InjectMore),
+                        ('[', Action::InjectLess),
+                        (';', Action::Inspect),
+                        ('\'', Action::Messages),
+                        ('p', Action::Library),
+                        ('O', Action::EditRule),
+                        ('H', Action::DumpHistory),
+                        ('J', Action::Scrub),
+                        ('{', Action::Bookmark),
+                        ('}', Action::ToggleDiff),
+                        ('\\', Action::ExportImage),
+                        ('(', Action::Undo),
+                        (')', Action::Annotate),
+                        ('*', Action::ToggleNotes),
+                        ('&', Action::FindInteresting),
+                        ('i', Action::PanUp),
+                        ('j', Action::PanLeft),
+                        ('k', Action::PanDown),
+                        ('l', Action::PanRight),
+                    ]
+                    .map(|(c, action)| (KeyCode::Char(c), action)),
+                );
+            }
+        }
+        bindings
+    }
+}
+
+impl FromStr for KeyPreset {
+    type Err = String;
+
+    /// Any case is accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "default" => Ok(KeyPreset::Default),
+            "vim" => Ok(KeyPreset::Vim),
+            "left" | "left-handed" => Ok(KeyPreset::LeftHanded),
+            _ => Err(format!(
+                "unknown keys {s:?}, expected default, vim or left-handed"
+            )),
+        }
+    }
+}
+
+pub(super) fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Function(n) => format!("F{n}"),
+        KeyCode::LeftArrow => "←".into(),
+        KeyCode::RightArrow => "→".into(),
+        KeyCode::UpArrow => "↑".into(),
+        KeyCode::DownArrow => "↓".into(),
+        key => format!("{key:?}"),
+    }
+}
+
+#[test]
+fn test_key_presets_bind_every_action() {
+    assert_eq!("Left-Handed".parse(), Ok(KeyPreset::LeftHanded));
+    assert!("emacs".parse::<KeyPreset>().is_err());
+    let mut preset = KeyPreset::Default;
+    for _ in 0..3 {
+        let bindings = preset.bindings();
+        let defaults = default_bindings();
+        for action in defaults.values() {
+            assert!(
+                bindings.values().any(|a| a == action),
+                "{preset:?} lacks {action:?}"
+            );
+        }
+        let prompts = bindings.values().any(|&a| a == Action::Command);
+        assert_eq!(prompts, preset == KeyPreset::Vim);
+        preset = preset.next();
+    }
+    assert_eq!(preset, KeyPreset::Default);
+    let left = KeyPreset::LeftHanded.bindings();
+    assert_eq!(left[&KeyCode::Char('P')], Action::Quit);
+    assert_eq!(left.get(&KeyCode::Char('q')), None);
+    assert_eq!(
+        KeyPreset::Vim.bindings()[&KeyCode::Char('h')],
+        Action::PanLeft
+    );
+}
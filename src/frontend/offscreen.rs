@@ -0,0 +1,102 @@
+use std::{error::Error, fs::OpenOptions, sync::OnceLock, time::Duration};
+
+use termwiz::{
+    caps::{Capabilities, ProbeHints},
+    input::InputEvent,
+    surface::Change,
+    terminal::{ScreenSize, Terminal, TerminalWaker, UnixTerminal},
+};
+
+/// A terminal of a fixed size that draws nowhere and is never typed into,
+/// for a renderer whose screen is only ever read: an [`App`](super::App)'s, copied into
+/// a host's surface, or one under test.
+pub struct Offscreen {
+    pub size: (usize, usize),
+}
+
+impl Terminal for Offscreen {
+    fn set_raw_mode(&mut self) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn set_cooked_mode(&mut self) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn enter_alternate_screen(&mut self) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn exit_alternate_screen(&mut self) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn get_screen_size(&mut self) -> termwiz::Result<ScreenSize> {
+        Ok(ScreenSize {
+            cols: self.size.0,
+            rows: self.size.1,
+            xpixel: 0,
+            ypixel: 0,
+        })
+    }
+
+    fn set_screen_size(&mut self, size: ScreenSize) -> termwiz::Result<()> {
+        self.size = (size.cols, size.rows);
+        Ok(())
+    }
+
+    fn render(&mut self, _changes: &[Change]) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> termwiz::Result<()> {
+        Ok(())
+    }
+
+    fn poll_input(&mut self, _wait: Option<Duration>) -> termwiz::Result<Option<InputEvent>> {
+        Ok(None)
+    }
+
+    /// A waker that wakes nothing, as [`poll_input`](Self::poll_input)
+    /// never waits.
+    fn waker(&self) -> TerminalWaker {
+        idle_waker()
+    }
+}
+
+/// A waker whose wake is seen by nobody. termwiz only makes wakers for real
+/// terminals, so this is the waker of one on a pseudoterminal of its own,
+/// opened the first time one is asked for and kept for the rest of the run.
+pub(super) fn idle_waker() -> TerminalWaker {
+    static WAKER: OnceLock<TerminalWaker> = OnceLock::new();
+    WAKER
+        .get_or_init(|| {
+            let open = || -> Result<UnixTerminal, Box<dyn Error>> {
+                let pty = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open("/dev/ptmx")?;
+                let caps = Capabilities::new_with_hints(ProbeHints::default())?;
+                Ok(UnixTerminal::new_with(caps, &pty, &pty)?)
+            };
+            // Every unix with a terminal to play in has pseudoterminals.
+            let terminal = open().expect("could not open a pseudoterminal for a waker");
+            let waker = terminal.waker();
+            // Dropping the terminal would close the pipe the waker writes
+            // into, and reset a screen nobody sees.
+            std::mem::forget(terminal);
+            waker
+        })
+        .clone()
+}
+
+#[test]
+fn test_offscreen_hands_out_a_waker_that_wakes_nothing() {
+    use termwiz::terminal::buffered::BufferedTerminal;
+
+    let mut screen = BufferedTerminal::new(Offscreen { size: (4, 2) }).unwrap();
+    let waker = screen.terminal().waker();
+    waker.wake().unwrap();
+    screen.terminal().waker().wake().unwrap();
+    assert_eq!(screen.terminal().poll_input(None).unwrap(), None);
+}
@@ -0,0 +1,97 @@
+use termwiz::input::KeyCode;
+
+use crate::Pattern;
+
+use super::{session::Session, Level, Stamp};
+
+/// Size of the thumbnail the pattern picker shows, in characters.
+const THUMBNAIL: (usize, usize) = (12, 4);
+
+/// The pattern picker for at most `height` rows: as much of the library as
+/// fits, scrolled to keep the highlighted pattern in view and marked, with its
+/// size and population, beside a thumbnail of the highlighted pattern.
+pub(super) fn picker_lines(highlight: usize, height: usize) -> Vec<String> {
+    let patterns = Pattern::names()
+        .map(|name| (name, Pattern::named(name).unwrap_or_default()))
+        .collect::<Vec<_>>();
+    let entries = patterns
+        .iter()
+        .map(|(name, pattern)| {
+            let (w, h) = pattern.size();
+            format!("{name}  {w}x{h}, {} cells", pattern.cells().len())
+        })
+        .collect::<Vec<_>>();
+    let width = entries
+        .iter()
+        .map(|entry| entry.chars().count())
+        .max()
+        .unwrap_or(0);
+    let thumbnail = patterns
+        .get(highlight)
+        .map_or_else(Vec::new, |(_, pattern)| pattern.thumbnail(THUMBNAIL));
+    // One row goes to the hint at the bottom.
+    let rows = height.saturating_sub(1).clamp(1, entries.len().max(1));
+    let first = highlight
+        .saturating_sub(rows - 1)
+        .min(entries.len().saturating_sub(rows));
+    let mut lines = entries
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .enumerate()
+        .map(|(row, (i, entry))| {
+            let marker = if i == highlight { '>' } else { ' ' };
+            let picture = thumbnail.get(row).map_or("", String::as_str);
+            format!("{marker} {entry:<width$}  {picture}")
+        })
+        .collect::<Vec<_>>();
+    lines.push("up/down to move, enter to pick, esc to close".into());
+    lines
+}
+
+impl Session {
+    /// Handles `key` if the pattern picker is open, returning whether it did.
+    /// Every key is taken while it is open.
+    pub(super) fn picker_key(&mut self, key: KeyCode) -> bool {
+        let Some(highlight) = self.picker else {
+            return false;
+        };
+        let last = Pattern::names().count().saturating_sub(1);
+        match key {
+            KeyCode::UpArrow => self.picker = Some(highlight.saturating_sub(1)),
+            KeyCode::DownArrow => self.picker = Some((highlight + 1).min(last)),
+            KeyCode::Enter => {
+                self.picker = None;
+                if let Some(name) = Pattern::names().nth(highlight) {
+                    self.stamp = Pattern::named(name).map(|pattern| Stamp::new(name, pattern));
+                    let message = format!(
+                        "Click to stamp a {name}; the wheel turns it, and with shift flips it"
+                    );
+                    self.notify(Level::Info, message);
+                }
+            }
+            KeyCode::Escape => self.picker = None,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[test]
+fn test_picker_scrolls_to_highlight() {
+    let count = Pattern::names().count();
+    let all = picker_lines(0, 100);
+    assert_eq!(all.len(), count + 1);
+    assert!(all[0].starts_with("> block  2x2, 4 cells"));
+    // The block's thumbnail sits beside the first entries.
+    assert!(all[0].contains("  ⠛⠀"));
+
+    // Too short for the whole library, it scrolls to keep the highlighted
+    // pattern, the last, on screen.
+    let last = picker_lines(count - 1, 4);
+    assert_eq!(last.len(), 4);
+    assert!(last[2].starts_with("> gosper-glider-gun  36x9, 36 cells"));
+    assert!(!last.iter().any(|line| line.contains("block")));
+    assert_eq!(picker_lines(2, 0).len(), 2);
+}
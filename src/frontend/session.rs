@@ -0,0 +1,1162 @@
+use std::{
+    error::Error,
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+use termwiz::{
+    input::{KeyCode, Modifiers},
+    surface::line::Line,
+};
+
+use crate::{
+    capture::export_png,
+    metadata::Metadata,
+    raster::ImageOptions,
+    rle,
+    stats::{self, SoupConfig},
+    Annotation, Bookmark, Diagnostics, Divergence, EdgeMode, Edit, Frame, GameOfLife, GridPos,
+    History, Injector, Neighborhood, Pattern, Rect, Rule, RunClock, ScreenPos, INIT_DENSITY,
+};
+
+use super::{
+    age_cells, bindings::Action, draw_box, fade_out, help_lines, note_under, placed_notes, threads,
+    track_deaths, Burst, Command, Effective, Level, Notifications, Options, Renderer, Screensaver,
+    SlowMotion, Stamp, Stroke, Sweep, View, MAX_DELAY, MIN_DELAY, SAVE_PATH,
+};
+
+/// How many generations [`Action::StepBack`] goes back.
+pub(super) const STEP_BACK: usize = 10;
+
+/// How many edits [`Action::Undo`] can take back.
+pub(super) const UNDO_DEPTH: usize = 64;
+
+/// The share of dead cells [`Action::Sprinkle`] brings to life.
+const SPRINKLE_DENSITY: f64 = 0.05;
+
+/// How much [`Action::InjectMore`] and [`Action::InjectLess`] change the
+/// injection rate by, in cells per generation.
+pub(super) const INJECT_STEP: f64 = 0.5;
+
+/// Rows of cells drawn at a time when exporting an image.
+const EXPORT_BAND: usize = 64;
+
+/// A board being written to a PNG in the background.
+#[derive(Debug)]
+pub(super) struct ImageExport {
+    pub(super) path: PathBuf,
+    pub(super) worker: JoinHandle<io::Result<()>>,
+    /// How much of the image is written, in percent.
+    pub(super) percent: Arc<AtomicU8>,
+    /// Set to stop the export, which removes what it wrote.
+    cancel: Arc<AtomicBool>,
+}
+
+/// Soups [`Action::FindInteresting`] scores at most.
+pub const SEARCH_SOUPS: usize = 300;
+
+/// How long [`Action::FindInteresting`] looks for, at most.
+pub const SEARCH_BUDGET: Duration = Duration::from_secs(3);
+
+/// Generations a soup being searched gets to settle in.
+const SEARCH_GENERATIONS: u64 = 1000;
+
+/// Soups being scored in the background for one worth watching.
+#[derive(Debug)]
+pub(super) struct SoupSearch {
+    /// What is searched, for the best seed to be filled in with.
+    pub(super) config: SoupConfig,
+    pub(super) worker: JoinHandle<Option<(u64, f64)>>,
+    /// How many soups have been scored.
+    pub(super) tried: Arc<AtomicUsize>,
+    /// Set to abandon the search.
+    pub(super) cancel: Arc<AtomicBool>,
+}
+
+/// State of an [`App`](super::App) that actions change.
+#[derive(Debug)]
+pub(super) struct Session {
+    pub(super) view: View,
+    pub(super) paused: bool,
+    /// A single generation was asked for while paused.
+    pub(super) step: bool,
+    pub(super) delay: Duration,
+    pub(super) quit: bool,
+    pub(super) history: History,
+    /// A history dump being written in the background, and what came of it.
+    export: Option<JoinHandle<Result<String, String>>>,
+    pub(super) image_export: Option<ImageExport>,
+    pub(super) search: Option<SoupSearch>,
+    pub(super) notices: Notifications,
+    /// The last few messages are listed.
+    pub(super) messages: bool,
+    /// The screen as it was under the help overlay, while it is open.
+    pub(super) help: Option<Vec<Line>>,
+    /// How many generations each cell has been alive, kept only when the
+    /// palette colors by age.
+    pub(super) ages: Vec<u32>,
+    /// The history frame on show while the scrubber is open.
+    pub(super) scrub: Option<usize>,
+    pub(super) sweep: Option<Sweep>,
+    pub(super) screensaver: Option<Screensaver>,
+    /// In smooth mode, the generation before the one on the board and when
+    /// the board stepped on from it.
+    pub(super) transition: Option<(Frame, Instant)>,
+    /// While births and deaths flash, the board before the last step, until
+    /// the frame after it has shown the difference.
+    pub(super) flash_from: Option<Vec<bool>>,
+    /// The generation diffed against, and where the board differs from it as
+    /// of the last frame.
+    pub(super) bookmark: Option<(Bookmark, Divergence)>,
+    /// The highlighted library pattern while the picker is open.
+    pub(super) picker: Option<usize>,
+    pub(super) rule_editor: Option<RuleEditor>,
+    pub(super) region_fill: Option<RegionFill>,
+    /// The line typed at the command prompt so far, while it is open.
+    pub(super) command: Option<String>,
+    /// The density reseeding fills the board at, once a command sets one.
+    density: Option<f64>,
+    /// The pattern the next click stamps, once one is picked.
+    pub(super) stamp: Option<Stamp>,
+    /// The character the mouse was last seen over, for showing the stamp.
+    pub(super) pointer: Option<ScreenPos>,
+    /// How many characters from the pointer painting reaches, as the mouse
+    /// wheel sets it; 0 paints just the one under it.
+    pub(super) brush: usize,
+    /// The drawing or erasing under way with the mouse.
+    pub(super) stroke: Stroke,
+    /// Keys arriving faster than they can be typed.
+    pub(super) burst: Burst,
+    /// Slowing down for bursts of activity, while it is on.
+    pub(super) slow_motion: Option<SlowMotion>,
+    /// When the last burst was.
+    pub(super) spike: Option<Instant>,
+    pub(super) injector: Option<Injector>,
+    /// How long this universe has run, which stops while it stands still.
+    pub(super) clock: RunClock,
+    /// The edits that take back the latest ones to the board, the latest
+    /// last.
+    undo: Vec<Edit>,
+    /// Notes pinned to the board, in the order they were added.
+    pub(super) annotations: Vec<Annotation>,
+    /// Clicks add and delete notes rather than paint.
+    pub(super) annotating: bool,
+    /// The cell the note being typed at the prompt goes on.
+    note_at: Option<GridPos>,
+    /// The color new notes are drawn in, as `:set note-color` names it.
+    note_color: String,
+}
+
+impl Session {
+    pub(super) fn new(options: &Options) -> Self {
+        Self {
+            view: View {
+                offset: GridPos(options.margin, options.margin),
+                seam: options.seam,
+                theme: options.theme,
+                margin: options.margin,
+                access: options.access,
+                ..View::default()
+            },
+            paused: false,
+            step: false,
+            delay: options.delay,
+            quit: false,
+            history: History::new(options.history),
+            export: None,
+            image_export: None,
+            search: None,
+            notices: Notifications::default(),
+            messages: false,
+            help: None,
+            ages: Vec::new(),
+            scrub: None,
+            sweep: options.sweep.map(Sweep::new),
+            screensaver: options.screensaver.map(Screensaver::new),
+            transition: None,
+            flash_from: None,
+            bookmark: None,
+            picker: None,
+            rule_editor: None,
+            region_fill: None,
+            command: None,
+            density: None,
+            stamp: None,
+            pointer: None,
+            brush: 0,
+            stroke: Stroke::default(),
+            burst: Burst::default(),
+            slow_motion: options.slow_motion,
+            spike: None,
+            injector: options.injector.clone(),
+            clock: RunClock::start(Instant::now(), SystemTime::now()),
+            undo: Vec::new(),
+            annotations: Vec::new(),
+            annotating: false,
+            note_at: None,
+            note_color: Annotation::COLOR.to_string(),
+        }
+    }
+
+    /// Closes the help overlay, putting back the frame it covered.
+    pub(super) fn dismiss_help<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(saved) = self.help.take() {
+            let screen = renderer.screen();
+            let changes = screen.diff_lines(saved.iter().collect());
+            screen.add_changes(changes);
+            screen.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Opens the scrubber on the newest generation, or closes it, going back
+    /// to where it was opened.
+    fn toggle_scrub(&mut self, game: &mut GameOfLife) {
+        if self.scrub.take().is_some() {
+            if let Some(newest) = self.history.frames().last() {
+                game.restore(newest);
+            }
+            return;
+        }
+        // The board may have been edited since it was last recorded.
+        if self.history.frames().last() != Some(&Frame::of(game)) {
+            self.history.push(game);
+        }
+        self.scrub = self.history.len().checked_sub(1);
+    }
+
+    /// Shows the buffered generation at `index`, or the newest if there are
+    /// fewer.
+    pub(super) fn scrub_to(&mut self, index: usize, game: &mut GameOfLife) {
+        let index = index.min(self.history.len().saturating_sub(1));
+        if let Some(frame) = self.history.get(index) {
+            game.restore(frame);
+            self.scrub = Some(index);
+        }
+    }
+
+    /// Handles `key` if the scrubber is open and uses it, returning whether it
+    /// did.
+    pub(super) fn scrub_key(&mut self, key: KeyCode, game: &mut GameOfLife) -> bool {
+        let Some(index) = self.scrub else {
+            return false;
+        };
+        match key {
+            KeyCode::LeftArrow => self.scrub_to(index.saturating_sub(1), game),
+            KeyCode::RightArrow => self.scrub_to(index + 1, game),
+            KeyCode::Escape => self.toggle_scrub(game),
+            // Carry on from here; the generations after it are stale.
+            KeyCode::Enter => {
+                self.history.truncate(index + 1);
+                self.scrub = None;
+                self.paused = false;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handles `key` if the rule editor is open, returning whether it did.
+    /// Every key is taken while it is open, and each toggle changes the rule
+    /// `game` runs by at once.
+    pub(super) fn rule_key(&mut self, key: KeyCode, game: &mut GameOfLife) -> bool {
+        let Some(RuleEditor {
+            original,
+            cursor: (mut count, mut survival),
+        }) = self.rule_editor
+        else {
+            return false;
+        };
+        let mut rule = game.rule();
+        match key {
+            KeyCode::LeftArrow => count = count.saturating_sub(1),
+            KeyCode::RightArrow => count = (count + 1).min(8),
+            KeyCode::UpArrow | KeyCode::DownArrow => survival = !survival,
+            // Every dead cell would come alive at once, all over the board.
+            KeyCode::Char(' ') if !survival && count == 0 && !rule.next(false, 0) => {
+                self.notify(Level::Warn, "B0 rules aren't supported".to_string());
+            }
+            KeyCode::Char(' ') => {
+                rule.toggle(survival, count);
+                game.set_rule(rule);
+            }
+            KeyCode::Enter => {
+                self.rule_editor = None;
+                return true;
+            }
+            KeyCode::Escape => {
+                game.set_rule(original);
+                self.rule_editor = None;
+                return true;
+            }
+            _ => {}
+        }
+        self.rule_editor = Some(RuleEditor {
+            original,
+            cursor: (count, survival),
+        });
+        true
+    }
+
+    /// Takes `text` that the terminal says was pasted, as one piece rather
+    /// than a key at a time: onto the command line if it is open and the
+    /// text is one line, else as a pattern to stamp if it is RLE, and
+    /// otherwise not at all.
+    pub(super) fn paste(&mut self, text: &str) {
+        let text = text.trim_end();
+        if let Some(line) = self.command.as_mut().filter(|_| !text.contains('\n')) {
+            line.extend(text.chars().filter(|c| !c.is_control()));
+            return;
+        }
+        match rle::decode(text) {
+            Ok((pattern, _)) => {
+                self.command = None;
+                self.picker = None;
+                self.stamp = Some(Stamp::new("pasted pattern", pattern));
+                let message = "Click to stamp the pasted pattern; the wheel turns it, \
+                               and with shift flips it";
+                self.notify(Level::Info, message.to_string());
+            }
+            Err(_) => {
+                let message = format!("Ignored a paste of {} characters", text.chars().count());
+                self.notify(Level::Warn, message);
+            }
+        }
+    }
+
+    /// Handles `key` if the density control is open, returning whether it
+    /// did. Every key is taken while it is open, and each change of density
+    /// refills the region at once, from the seed it opened with.
+    pub(super) fn region_key(&mut self, key: KeyCode, game: &mut GameOfLife) -> bool {
+        let Some(fill) = &mut self.region_fill else {
+            return false;
+        };
+        match key {
+            KeyCode::LeftArrow => fill.steps = fill.steps.saturating_sub(1).max(1),
+            KeyCode::RightArrow => fill.steps = (fill.steps + 1).min(REGION_STEPS),
+            KeyCode::Enter | KeyCode::Escape => {
+                let (pattern, origin) = (fill.original.clone(), GridPos(fill.rect.x, fill.rect.y));
+                let original = Edit::Pattern { pattern, origin };
+                self.paused = fill.paused;
+                self.region_fill = None;
+                match key {
+                    KeyCode::Escape => drop(game.apply_edit(&original)),
+                    _ => self.remember(original),
+                }
+                return true;
+            }
+            _ => return true,
+        }
+        let (rect, density, seed) = (fill.rect, fill.density(), fill.seed);
+        game.apply_edit(&Edit::Region {
+            rect,
+            density,
+            seed,
+        });
+        true
+    }
+
+    /// Edits the open command line with `key`, handing the line back once
+    /// enter runs it. Escape, or backspace on an empty line, closes it.
+    pub(super) fn command_key(&mut self, key: KeyCode) -> Option<String> {
+        let line = self.command.as_mut()?;
+        match key {
+            KeyCode::Char(c) => line.push(c),
+            KeyCode::Backspace if !line.is_empty() => {
+                line.pop();
+            }
+            KeyCode::Backspace | KeyCode::Escape => {
+                self.command = None;
+                self.note_at = None;
+            }
+            KeyCode::Enter => return self.command.take(),
+            _ => {}
+        }
+        None
+    }
+
+    /// Runs the command in `line`, telling the user what came of it. A
+    /// command that can't be parsed or fails is shown as an error.
+    pub(super) fn run_command<R: Renderer>(
+        &mut self,
+        line: &str,
+        renderer: &mut R,
+        game: &mut GameOfLife,
+        options: &Options,
+    ) -> Result<(), Box<dyn Error>> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let done = match line.parse::<Command>() {
+            Ok(Command::Action(action)) => {
+                return self.handle_action(action, renderer, game, options);
+            }
+            Ok(Command::Rule(rule)) => {
+                game.set_rule(rule);
+                Ok(format!("Rule {rule}"))
+            }
+            Ok(Command::Load(path)) => self.load(&path, game),
+            Ok(Command::Note(text)) => {
+                let pos = self
+                    .note_at
+                    .take()
+                    .unwrap_or_else(|| self.note_spot(renderer, game));
+                Annotation::new(pos, &text, &self.note_color).map(|note| {
+                    self.annotations.push(note);
+                    self.view.hide_notes = false;
+                    format!("Pinned a note to {},{}", pos.0, pos.1)
+                })
+            }
+            Ok(Command::Save(path)) => {
+                let path = path.unwrap_or_else(|| SAVE_PATH.into());
+                let frame = Frame::of(game);
+                let annotations = self.annotations.clone();
+                let metadata = Metadata {
+                    annotations,
+                    ..frame.metadata()
+                };
+                let text = rle::encode_with(&metadata, frame.size, |x, y| game.get(GridPos(x, y)));
+                match std::fs::write(&path, text) {
+                    Ok(()) => Ok(format!("Saved the board to {}", path.display())),
+                    Err(err) => Err(format!("Could not write {}: {err}", path.display())),
+                }
+            }
+            Ok(Command::SetDensity(density)) => {
+                self.density = Some(density);
+                Ok(format!("Reseeding at density {density}"))
+            }
+            Ok(Command::SetDelay(delay)) => {
+                self.delay = delay;
+                Ok(format!("{} ms between generations", delay.as_millis()))
+            }
+            Ok(Command::SetNoteColor(color)) => {
+                let message = format!("New notes are {color}");
+                self.note_color = color;
+                Ok(message)
+            }
+            Err(err) => Err(err),
+        };
+        match done {
+            Ok(message) => self.notify(Level::Info, message),
+            Err(message) => self.notify(Level::Error, message),
+        }
+        Ok(())
+    }
+
+    /// Clears `game` for the pattern in the RLE file at `path`, centered,
+    /// under the file's rule, and its edges if it gives them. Its notes take
+    /// the place of those on the board, moved with the pattern.
+    fn load(&mut self, path: &Path, game: &mut GameOfLife) -> Result<String, String> {
+        let error = |err: String| format!("{}: {err}", path.display());
+        let text = std::fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+        let (pattern, metadata, conflicts) = rle::decode_with_metadata(&text).map_err(error)?;
+        for conflict in conflicts {
+            self.notify(Level::Warn, error(conflict.to_string()));
+        }
+        game.apply_edit(&Edit::Clear);
+        // What undoes edits to the old board has no meaning on the new one.
+        self.undo.clear();
+        game.set_rule(metadata.rule.unwrap_or_default());
+        if let Some(edges) = metadata.edges {
+            game.set_edge_mode(edges);
+        }
+        let ((w, h), (pw, ph)) = (game.size(), pattern.size());
+        let origin = GridPos(w.saturating_sub(pw) / 2, h.saturating_sub(ph) / 2);
+        let name = path.display().to_string();
+        if let Some(warning) = game.placement(&pattern, origin).warning(&name) {
+            self.notify(Level::Warn, warning);
+        }
+        game.apply_edit(&Edit::Pattern { pattern, origin });
+        self.annotations = metadata.annotations;
+        for note in &mut self.annotations {
+            note.pos = GridPos(note.pos.0 + origin.0, note.pos.1 + origin.1);
+        }
+        self.annotations
+            .retain(|note| note.pos.0 < w && note.pos.1 < h);
+        Ok(format!("Loaded {name}"))
+    }
+
+    /// Where a note typed now goes: on the inspector's cursor while it is
+    /// open, else under the pointer, else in the middle of what is on screen.
+    fn note_spot<R: Renderer>(&self, renderer: &R, game: &GameOfLife) -> GridPos {
+        let view = &self.view;
+        let pointer = self
+            .pointer
+            .and_then(|at| renderer.screen_to_grid(game.size(), view, at));
+        view.cursor.or(pointer).unwrap_or_else(|| {
+            let (visible, (w, h)) = (renderer.size(), game.size());
+            let x = (view.offset.0 + visible.0 / 2).min(w - 1);
+            let y = (view.offset.1 + visible.1 / 2).min(h - 1);
+            GridPos(x, y)
+        })
+    }
+
+    /// Handles a click on the screen character `at` while annotating: deletes
+    /// the note drawn there, or else opens the prompt for a note on the cell
+    /// there.
+    pub(super) fn note_click<R: Renderer>(
+        &mut self,
+        renderer: &mut R,
+        game: &GameOfLife,
+        at: ScreenPos,
+    ) {
+        let labels = placed_notes(renderer, game, &self.view, &self.annotations);
+        let under = renderer.cells_under(game.size(), &self.view, at);
+        if let Some(index) = note_under(&labels, at) {
+            let note = self.annotations.remove(index);
+            self.notify(Level::Info, format!("Deleted the note {:?}", note.text));
+        } else if let Some(&cell) = under.first() {
+            self.note_at = Some(cell);
+            self.command = Some("note ".to_string());
+        }
+    }
+
+    /// Handles `key` if it is escape while annotating, which stops, returning
+    /// whether it did.
+    pub(super) fn note_key(&mut self, key: KeyCode) -> bool {
+        let stop = self.annotating && key == KeyCode::Escape;
+        self.annotating &= !stop;
+        stop
+    }
+
+    /// Handles `key` if it is an arrow held with shift while paused, which
+    /// moves every cell on the board one cell that way, returning whether it
+    /// did.
+    pub(super) fn shift_key(
+        &mut self,
+        key: KeyCode,
+        modifiers: Modifiers,
+        game: &mut GameOfLife,
+    ) -> bool {
+        if !self.paused || !modifiers.contains(Modifiers::SHIFT) {
+            return false;
+        }
+        let (dx, dy) = match key {
+            KeyCode::LeftArrow => (-1, 0),
+            KeyCode::RightArrow => (1, 0),
+            KeyCode::UpArrow => (0, -1),
+            KeyCode::DownArrow => (0, 1),
+            _ => return false,
+        };
+        let mut moved = game.clone();
+        moved.shift(dx, dy);
+        self.edit(game, &Edit::between(game, &moved));
+        true
+    }
+
+    /// Handles `key` if the inspector is open and uses it, returning whether
+    /// it did. The arrows move the cursor, panning to keep it on the
+    /// `visible` part of the board, and escape closes the inspector.
+    pub(super) fn cursor_key(
+        &mut self,
+        key: KeyCode,
+        game: &GameOfLife,
+        visible: (usize, usize),
+    ) -> bool {
+        let Some(GridPos(x, y)) = self.view.cursor else {
+            return false;
+        };
+        let (w, h) = game.size();
+        let (x, y) = match key {
+            KeyCode::LeftArrow => (x.saturating_sub(1), y),
+            KeyCode::RightArrow => ((x + 1).min(w - 1), y),
+            KeyCode::UpArrow => (x, y.saturating_sub(1)),
+            KeyCode::DownArrow => (x, (y + 1).min(h - 1)),
+            KeyCode::Escape => {
+                self.view.cursor = None;
+                return true;
+            }
+            _ => return false,
+        };
+        self.view.cursor = Some(GridPos(x, y));
+        let offset = &mut self.view.offset;
+        offset.0 = offset.0.min(x).max((x + 1).saturating_sub(visible.0));
+        offset.1 = offset.1.min(y).max((y + 1).saturating_sub(visible.1));
+        true
+    }
+
+    /// The time between ticks, stretched by slow motion after a burst.
+    pub(super) fn tick_delay(&self) -> Duration {
+        match self.slow_motion {
+            Some(slow) => slow.delay(self.delay, self.spike.map(|spike| spike.elapsed())),
+            None => self.delay,
+        }
+    }
+
+    pub(super) fn notify(&mut self, level: Level, message: String) {
+        self.notices.push(level, message, Instant::now());
+    }
+
+    /// Makes `edit` on `game`, keeping what undoes it.
+    pub(super) fn edit(&mut self, game: &mut GameOfLife, edit: &Edit) {
+        let inverse = game.apply_edit(edit);
+        self.remember(inverse);
+    }
+
+    /// Keeps `inverse` as the edit [`Action::Undo`] makes next, unless it
+    /// would change nothing. Past [`UNDO_DEPTH`], the oldest is forgotten.
+    fn remember(&mut self, inverse: Edit) {
+        if inverse.is_empty() {
+            return;
+        }
+        if self.undo.len() == UNDO_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(inverse);
+    }
+
+    /// Keeps what undoes a drag so far: with what undoes the rest of it if
+    /// it `continued` one, so that a drag is undone all at once.
+    pub(super) fn remember_stroke(&mut self, inverse: Edit, continued: bool) {
+        match (self.undo.last_mut(), inverse) {
+            (Some(Edit::Cells(earlier)), Edit::Cells(mut cells)) if continued => {
+                // The earlier cells are as they were before the drag, so
+                // they win by coming last.
+                cells.append(earlier);
+                *earlier = cells;
+            }
+            (_, inverse) => self.remember(inverse),
+        }
+    }
+
+    /// Reports on finished background exports, if there are any.
+    pub(super) fn poll_export(&mut self) {
+        if self
+            .export
+            .as_ref()
+            .is_some_and(|export| export.is_finished())
+        {
+            match self.export.take().map(JoinHandle::join) {
+                Some(Ok(Ok(message))) => self.notify(Level::Info, message),
+                Some(Ok(Err(message))) => self.notify(Level::Error, message),
+                _ => self.notify(Level::Error, "History export failed".to_string()),
+            }
+        }
+        if self
+            .image_export
+            .as_ref()
+            .is_some_and(|export| export.worker.is_finished())
+        {
+            let Some(ImageExport { path, worker, .. }) = self.image_export.take() else {
+                return;
+            };
+            let path = path.display();
+            match worker.join() {
+                Ok(Ok(())) => self.notify(Level::Info, format!("Wrote the board to {path}")),
+                Ok(Err(err)) if err.kind() == io::ErrorKind::Interrupted => {
+                    self.notify(Level::Info, format!("Stopped writing {path}"))
+                }
+                Ok(Err(err)) => self.notify(Level::Error, format!("Could not write {path}: {err}")),
+                Err(_) => self.notify(Level::Error, "Image export failed".to_string()),
+            }
+        }
+    }
+
+    /// Waits for the exports in the background to finish, so that exiting
+    /// doesn't cut them short.
+    pub(super) fn wait_for_exports(&mut self) {
+        if let Some(export) = self.export.take() {
+            let _ = export.join();
+        }
+        if let Some(export) = self.image_export.take() {
+            let _ = export.worker.join();
+        }
+    }
+
+    /// Puts the best soup of a finished search on `game`, paused to be
+    /// watched from its first generation, returning whether it did. Should
+    /// the board have changed rule or size meanwhile, the seed is only told.
+    pub(super) fn poll_search(&mut self, game: &mut GameOfLife) -> bool {
+        if !self
+            .search
+            .as_ref()
+            .is_some_and(|search| search.worker.is_finished())
+        {
+            return false;
+        }
+        let Some(SoupSearch {
+            config,
+            worker,
+            tried,
+            ..
+        }) = self.search.take()
+        else {
+            return false;
+        };
+        let tried = tried.load(Ordering::Relaxed);
+        let (seed, score) = match worker.join() {
+            Ok(Some(best)) => best,
+            Ok(None) => {
+                let budget = SEARCH_BUDGET.as_secs();
+                self.notify(
+                    Level::Info,
+                    format!("No soup settled or ran out in {budget}s"),
+                );
+                return false;
+            }
+            Err(_) => {
+                self.notify(Level::Error, "The soup search failed".to_string());
+                return false;
+            }
+        };
+        let found = format!("seed {seed}, scoring {score:.0}, the best of {tried} soups");
+        if (game.rule(), game.size()) != (config.rule, config.size) {
+            self.notify(
+                Level::Info,
+                format!("The board changed while searching; {found}"),
+            );
+            return false;
+        }
+        self.edit(
+            game,
+            &Edit::Randomize {
+                seed,
+                density: config.density,
+            },
+        );
+        game.reset_activity();
+        self.paused = true;
+        self.notify(Level::Info, format!("Found {found}"));
+        true
+    }
+
+    /// Handles `key` if soups are being searched and it is escape, which
+    /// abandons the search, returning whether it did. The soup being scored
+    /// is left to finish out of sight.
+    pub(super) fn search_key(&mut self, key: KeyCode) -> bool {
+        match (&self.search, key) {
+            (Some(search), KeyCode::Escape) => {
+                search.cancel.store(true, Ordering::Relaxed);
+                self.search = None;
+                self.notify(Level::Info, "Stopped searching soups".to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles `key` if an image is being exported and it is escape, which
+    /// stops the export, returning whether it did.
+    pub(super) fn export_key(&mut self, key: KeyCode) -> bool {
+        match (&self.image_export, key) {
+            (Some(export), KeyCode::Escape) => {
+                export.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(super) fn handle_action<R: Renderer>(
+        &mut self,
+        action: Action,
+        renderer: &mut R,
+        game: &mut GameOfLife,
+        options: &Options,
+    ) -> Result<(), Box<dyn Error>> {
+        let view = &mut self.view;
+        let visible = renderer.size();
+        let pan = (
+            (visible.0 / 8).max(1) as isize,
+            (visible.1 / 8).max(1) as isize,
+        );
+        match action {
+            Action::Help => {
+                let screen = renderer.screen();
+                let saved = screen
+                    .screen_lines()
+                    .into_iter()
+                    .map(|line| line.into_owned());
+                self.help = Some(saved.collect());
+                draw_box(screen, &help_lines(&options.bindings));
+                screen.flush()?;
+            }
+            Action::Quit => {
+                if Effective::resolve(options, view).fade_out {
+                    fade_out(renderer, game, view)?;
+                }
+                self.quit = true;
+            }
+            Action::TogglePause => self.paused = !self.paused,
+            Action::Step => {
+                self.paused = true;
+                self.step = true;
+            }
+            Action::Clear => {
+                self.edit(game, &Edit::Clear);
+                game.reset_activity();
+            }
+            Action::Reseed => {
+                let density = self.density.unwrap_or(INIT_DENSITY);
+                self.edit(
+                    game,
+                    &Edit::Randomize {
+                        seed: rand::random(),
+                        density,
+                    },
+                );
+                game.reset_activity();
+            }
+            Action::Sprinkle => {
+                let mut sprinkled = game.clone();
+                sprinkled.sprinkle(SPRINKLE_DENSITY, rand::random());
+                self.edit(game, &Edit::between(game, &sprinkled));
+            }
+            Action::SpeedUp => self.delay = (self.delay / 2).max(MIN_DELAY),
+            Action::SlowDown => self.delay = (self.delay * 2).min(MAX_DELAY),
+            Action::ToggleMinimap => view.minimap = !view.minimap,
+            Action::ToggleSpaceships => {
+                view.spaceships = match view.spaceships {
+                    Some(_) => None,
+                    None => Some((game.spaceships(), game.generation())),
+                };
+            }
+            Action::ToggleCensus => {
+                view.census = match view.census {
+                    Some(_) => None,
+                    None => Some(game.census()),
+                };
+            }
+            Action::ToggleDiagnostics => {
+                view.diagnostics = match view.diagnostics {
+                    Some(_) => None,
+                    None => Some(Diagnostics::new()),
+                };
+            }
+            Action::ToggleFlash => view.flash = !view.flash,
+            Action::Bookmark => {
+                self.bookmark = Some((Bookmark::of(game), Divergence::default()));
+                let message = format!("Bookmarked generation {}", game.generation());
+                self.notify(Level::Info, message);
+            }
+            Action::ToggleDiff => match self.bookmark {
+                Some(_) => view.diff = !view.diff,
+                None => self.notify(Level::Warn, "Bookmark a generation first".to_string()),
+            },
+            Action::ToggleOutline => view.outline = !view.outline,
+            Action::ToggleGrid => view.grid = !view.grid,
+            Action::FillRegion => {
+                let (offset, (right, bottom)) = (view.offset, view.extent(game.size()));
+                let rect = Rect {
+                    x: offset.0,
+                    y: offset.1,
+                    width: visible.0.min(right.saturating_sub(offset.0)),
+                    height: visible.1.min(bottom.saturating_sub(offset.1)),
+                };
+                let fill = RegionFill {
+                    rect,
+                    original: game.extract(rect),
+                    steps: REGION_STEPS / 2 + 1,
+                    seed: rand::random(),
+                    paused: self.paused,
+                };
+                game.apply_edit(&Edit::Region {
+                    rect,
+                    density: fill.density(),
+                    seed: fill.seed,
+                });
+                // The board holds still while the density is picked.
+                self.paused = true;
+                self.region_fill = Some(fill);
+            }
+            Action::ToggleNeighborCheck => view.check_neighbors = !view.check_neighbors,
+            Action::ToggleHighContrast => view.access.high_contrast = !view.access.high_contrast,
+            Action::ToggleReducedMotion => {
+                view.access.reduced_motion = !view.access.reduced_motion;
+            }
+            Action::CycleEdges => {
+                let (edges, message) = match game.edge_mode() {
+                    EdgeMode::Bounded => (EdgeMode::Toroidal, "The edges wrap around"),
+                    EdgeMode::Toroidal => (EdgeMode::Mirror, "The edges mirror the board"),
+                    EdgeMode::Mirror => (EdgeMode::Bounded, "Everything past the edges is dead"),
+                };
+                game.set_edge_mode(edges);
+                self.notify(Level::Info, message.to_string());
+            }
+            Action::InjectMore | Action::InjectLess => {
+                if let Some(injector) = &mut self.injector {
+                    injector.rate = match action {
+                        Action::InjectMore => injector.rate + INJECT_STEP,
+                        _ => (injector.rate - INJECT_STEP).max(0.0),
+                    };
+                }
+            }
+            Action::Inspect => {
+                view.cursor = match view.cursor {
+                    Some(_) => None,
+                    // In the middle of what is on screen.
+                    None => {
+                        let (w, h) = game.size();
+                        let x = (view.offset.0 + visible.0 / 2).min(w - 1);
+                        let y = (view.offset.1 + visible.1 / 2).min(h - 1);
+                        Some(GridPos(x, y))
+                    }
+                };
+            }
+            Action::Messages => self.messages = !self.messages,
+            Action::ToggleSlowMotion => {
+                self.slow_motion = match self.slow_motion {
+                    Some(_) => None,
+                    None => Some(options.slow_motion.unwrap_or_default()),
+                };
+                self.spike = None;
+            }
+            Action::ToggleTrail => {
+                view.trail = match view.trail {
+                    Some(_) => None,
+                    None => Some(Vec::new()),
+                };
+            }
+            Action::DumpHistory => {
+                if self.export.is_some() {
+                    return Ok(());
+                }
+                // Encoding and writing happen off the UI thread, on a copy.
+                let history = self.history.clone();
+                let path = options.history_path.clone();
+                let provenance = self.clock.provenance(Instant::now());
+                self.export = Some(std::thread::spawn(move || {
+                    let n = history.len();
+                    let text = format!("#C {provenance}\n{}", history.to_multi_rle(n));
+                    match std::fs::write(&path, text) {
+                        Ok(()) => Ok(format!("Wrote {n} generations to {}", path.display())),
+                        Err(err) => Err(format!("Could not write {}: {err}", path.display())),
+                    }
+                }));
+                self.notify(Level::Info, "Writing history...".to_string());
+            }
+            Action::Undo => match self.undo.pop() {
+                Some(edit) => drop(game.apply_edit(&edit)),
+                None => self.notify(Level::Info, "Nothing to undo".to_string()),
+            },
+            Action::ExportImage => {
+                if self.image_export.is_some() {
+                    return Ok(());
+                }
+                // Drawn and written a band at a time off the UI thread, on a
+                // copy, so a huge board neither stalls the screen nor needs
+                // its whole image in memory.
+                let board = game.clone();
+                let path = options.image_path.clone();
+                let opts = ImageOptions::for_theme(options.theme);
+                let (percent, cancel) = (Arc::<AtomicU8>::default(), Arc::<AtomicBool>::default());
+                let (done, stop, to) = (percent.clone(), cancel.clone(), path.clone());
+                let worker = std::thread::spawn(move || {
+                    export_png(&to, &board, &opts, EXPORT_BAND, |percent| {
+                        done.store(percent, Ordering::Relaxed);
+                        match stop.load(Ordering::Relaxed) {
+                            true => ControlFlow::Break(()),
+                            false => ControlFlow::Continue(()),
+                        }
+                    })
+                });
+                self.image_export = Some(ImageExport {
+                    path,
+                    worker,
+                    percent,
+                    cancel,
+                });
+            }
+            Action::FindInteresting => {
+                if self.search.is_some() {
+                    return Ok(());
+                }
+                let config = SoupConfig {
+                    rule: game.rule(),
+                    edges: game.edge_mode(),
+                    size: game.size(),
+                    density: self.density.unwrap_or(INIT_DENSITY),
+                    generations: SEARCH_GENERATIONS,
+                    seed: rand::random(),
+                    soups: SEARCH_SOUPS,
+                };
+                let (tried, cancel) = (Arc::<AtomicUsize>::default(), Arc::<AtomicBool>::default());
+                let (done, stop, soups) = (tried.clone(), cancel.clone(), config.clone());
+                // A core is left over for the screen.
+                let spare = threads().saturating_sub(1).max(1);
+                let worker = std::thread::spawn(move || {
+                    let deadline = Instant::now() + SEARCH_BUDGET;
+                    let progress = |tried| done.store(tried, Ordering::Relaxed);
+                    stats::search(&soups, spare, deadline, &stop, &progress)
+                });
+                self.search = Some(SoupSearch {
+                    config,
+                    worker,
+                    tried,
+                    cancel,
+                });
+            }
+            Action::Scrub => self.toggle_scrub(game),
+            Action::Library => {
+                self.paused = true;
+                self.picker = Some(0);
+            }
+            Action::EditRule => {
+                let rule = game.rule();
+                if rule.range() == 1 && rule.neighborhood() == Neighborhood::Moore {
+                    self.rule_editor = Some(RuleEditor {
+                        original: rule,
+                        cursor: (0, false),
+                    });
+                } else {
+                    self.notify(
+                        Level::Warn,
+                        format!("Only B/S rules can be edited, not {rule}"),
+                    );
+                }
+            }
+            Action::StepBack => {
+                self.paused = true;
+                let back = self.history.step_back_n(game, STEP_BACK);
+                let message = format!("Back {back} generations, to {}", game.generation());
+                self.notify(Level::Info, message);
+            }
+            Action::Command => self.command = Some(String::new()),
+            Action::Annotate => {
+                self.note_at = Some(self.note_spot(renderer, game));
+                self.command = Some("note ".to_string());
+                self.annotating = true;
+                self.view.hide_notes = false;
+            }
+            Action::ToggleNotes => {
+                view.hide_notes = !view.hide_notes;
+                // Notes that aren't drawn can't be clicked.
+                self.annotating &= !self.view.hide_notes;
+            }
+            // Switching keys changes the options, which the app does.
+            Action::NextKeys => {}
+            Action::PanLeft => view.pan((-pan.0, 0), game.size(), visible),
+            Action::PanRight => view.pan((pan.0, 0), game.size(), visible),
+            Action::PanUp => view.pan((0, -pan.1), game.size(), visible),
+            Action::PanDown => view.pan((0, pan.1), game.size(), visible),
+        }
+        Ok(())
+    }
+
+    /// Keeps up with a step `game` just took: injects cells, moves the sweep
+    /// on, and records the new generation.
+    pub(super) fn after_step(&mut self, game: &mut GameOfLife, options: &Options) {
+        if let Some(injector) = &mut self.injector {
+            injector.inject(game);
+        }
+        if let Some(record) = self.sweep.as_mut().and_then(|sweep| sweep.advance(game)) {
+            eprintln!("{}", record.csv());
+        }
+        if let Some(screensaver) = &mut self.screensaver {
+            screensaver.advance(game);
+        }
+        self.history.push(game);
+        self.clock.count(1);
+        if options.palette.has_ages() {
+            age_cells(&mut self.ages, game);
+        }
+        if let Some(trail) = &mut self.view.trail {
+            track_deaths(trail, game);
+        }
+    }
+}
+
+/// The rule editor, while it is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RuleEditor {
+    /// The rule it was opened on, which escape goes back to.
+    original: Rule,
+    /// The count whose box the cursor is on, in the survival row or not.
+    pub(super) cursor: (usize, bool),
+}
+
+/// How many densities the density control offers, in 5% steps from 5% to
+/// 95%.
+const REGION_STEPS: u32 = 19;
+
+/// The density control, while it is open: a region of the board refilled
+/// at a density picked with the arrows, until enter keeps it or escape puts
+/// back what was there. Every refill is from one seed, so going back to a
+/// density brings back the soup it made.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct RegionFill {
+    rect: Rect,
+    /// The region as it was when the control opened.
+    original: Pattern,
+    /// The density, in 5% steps.
+    steps: u32,
+    seed: u64,
+    /// Whether the board was paused before the control paused it.
+    paused: bool,
+}
+
+impl RegionFill {
+    fn density(&self) -> f64 {
+        self.steps as f64 * 0.05
+    }
+
+    /// The control for the status bar, the density shown as a bar too.
+    pub(super) fn status(&self) -> String {
+        let bar = (1..=REGION_STEPS).map(|step| if step <= self.steps { '=' } else { '-' });
+        format!(
+            "density {}% [{}], arrows change it, enter keeps, escape puts back",
+            self.steps * 5,
+            bar.collect::<String>()
+        )
+    }
+}
+
+#[test]
+fn test_inspector_cursor_follows_the_arrows() {
+    use super::inspect_status;
+
+    let mut session = Session::new(&Options::default());
+    let mut game = GameOfLife::new_empty((10, 10));
+    for x in 1..4 {
+        game.set(GridPos(x, 2), true);
+    }
+    // Closed, the arrows are left to pan.
+    assert!(!session.cursor_key(KeyCode::LeftArrow, &game, (4, 4)));
+    session.view.cursor = Some(GridPos(2, 2));
+    assert_eq!(
+        inspect_status((2, 2), game.inspect(GridPos(2, 2))),
+        "(2, 2) alive, 2 neighbors, survives"
+    );
+    assert!(session.cursor_key(KeyCode::UpArrow, &game, (4, 4)));
+    let GridPos(x, y) = session.view.cursor.unwrap();
+    assert_eq!(
+        inspect_status((x, y), game.inspect(GridPos(x, y))),
+        "(2, 1) dead, 3 neighbors, born next"
+    );
+    // Moving off the side of the screen pans along, but not off the board.
+    for _ in 0..12 {
+        session.cursor_key(KeyCode::RightArrow, &game, (4, 4));
+    }
+    assert_eq!(
+        (session.view.cursor, session.view.offset),
+        (Some(GridPos(9, 1)), GridPos(6, 0))
+    );
+    assert!(!session.cursor_key(KeyCode::Char('x'), &game, (4, 4)));
+    assert!(session.cursor_key(KeyCode::Escape, &game, (4, 4)));
+    assert_eq!(session.view.cursor, None);
+}
+
+#[test]
+fn test_shift_arrows_move_the_board_while_paused() {
+    let mut session = Session::new(&Options::default());
+    let mut game = GameOfLife::new_empty((4, 4));
+    game.set(GridPos(0, 0), true);
+    // Running, or without shift, the arrows are left to pan.
+    assert!(!session.shift_key(KeyCode::RightArrow, Modifiers::SHIFT, &mut game));
+    session.paused = true;
+    assert!(!session.shift_key(KeyCode::RightArrow, Modifiers::NONE, &mut game));
+    assert!(session.shift_key(KeyCode::RightArrow, Modifiers::SHIFT, &mut game));
+    assert!(session.shift_key(KeyCode::DownArrow, Modifiers::SHIFT, &mut game));
+    assert!(game.get(GridPos(1, 1)) && game.population() == 1);
+    assert!(!session.shift_key(KeyCode::Char('a'), Modifiers::SHIFT, &mut game));
+}
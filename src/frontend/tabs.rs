@@ -0,0 +1,396 @@
+use std::time::Instant;
+
+use termwiz::input::{KeyCode, Modifiers};
+
+use crate::GameOfLife;
+
+use super::{session::Session, Level, Options};
+
+/// A universe open in a tab but not on show.
+struct Universe {
+    game: GameOfLife,
+    session: Session,
+    /// When it last stepped, to step it in the background at its own speed.
+    last_step: Instant,
+}
+
+/// A question a tab key asks before it does anything, whose answer is the
+/// next key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TabPrompt {
+    /// What a new universe starts with.
+    New,
+    /// Whether to close the universe on show.
+    Close,
+}
+
+impl TabPrompt {
+    pub(super) fn lines(self) -> Vec<String> {
+        let lines: &[&str] = match self {
+            TabPrompt::New => &[
+                "New universe",
+                "",
+                "s  a random soup",
+                "e  an empty board",
+                "p  a library pattern, stamped with a click",
+                "",
+                "any other key cancels",
+            ],
+            TabPrompt::Close => &[
+                "Close this universe?",
+                "",
+                "y  close it",
+                "any other key keeps it",
+            ],
+        };
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+}
+
+/// The universes open in an [`App`](super::App), switched between like
+/// tabs. Each has its own board, and with it its own rule, and its own
+/// [`Session`]: speed, history and view. The one on show is the app's own
+/// board and session, and its slot here is empty.
+pub(super) struct Tabs {
+    slots: Vec<Option<Universe>>,
+    pub(super) active: usize,
+    /// The tab of the board the loop started with, until it is closed.
+    home: Option<usize>,
+    pub(super) prompt: Option<TabPrompt>,
+}
+
+impl Tabs {
+    pub(super) fn new() -> Self {
+        Self {
+            slots: vec![None],
+            active: 0,
+            home: Some(0),
+            prompt: None,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Handles `key` if it opens, closes or switches tabs, or answers the
+    /// open prompt, returning whether it did. `game` and `session` are the
+    /// universe on show, and become whichever is on show after.
+    pub(super) fn key(
+        &mut self,
+        key: KeyCode,
+        modifiers: Modifiers,
+        game: &mut GameOfLife,
+        session: &mut Session,
+        options: &Options,
+    ) -> bool {
+        if let Some(prompt) = self.prompt.take() {
+            let (w, h) = game.size();
+            let mut new = GameOfLife::new_empty((w, h));
+            new.set_rule(game.rule());
+            new.set_edge_mode(game.edge_mode());
+            match (prompt, key) {
+                (TabPrompt::New, KeyCode::Char('s')) => {
+                    new.init();
+                    self.open(new, game, session, options);
+                }
+                (TabPrompt::New, KeyCode::Char('e')) => self.open(new, game, session, options),
+                (TabPrompt::New, KeyCode::Char('p')) => {
+                    self.open(new, game, session, options);
+                    session.paused = true;
+                    session.picker = Some(0);
+                }
+                (TabPrompt::Close, KeyCode::Char('y')) => self.close(game, session),
+                // Anything else is a no.
+                _ => {}
+            }
+            return true;
+        }
+        let ctrl = modifiers.contains(Modifiers::CTRL);
+        match key {
+            KeyCode::Char('t') if ctrl => self.prompt = Some(TabPrompt::New),
+            KeyCode::Char('w') if ctrl && self.len() > 1 => self.prompt = Some(TabPrompt::Close),
+            KeyCode::Char('w') if ctrl => {
+                session.notify(Level::Warn, "The last universe can't be closed".to_string());
+            }
+            KeyCode::Tab if self.len() > 1 => {
+                let n = self.len();
+                let to = match modifiers.contains(Modifiers::SHIFT) {
+                    true => (self.active + n - 1) % n,
+                    false => (self.active + 1) % n,
+                };
+                self.switch(to, game, session);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Opens `new` in a tab after the one on show and shows it.
+    fn open(
+        &mut self,
+        new: GameOfLife,
+        game: &mut GameOfLife,
+        session: &mut Session,
+        options: &Options,
+    ) {
+        let mut universe = Universe {
+            session: Session::new(options),
+            game: new,
+            last_step: Instant::now(),
+        };
+        universe.session.history.push(&universe.game);
+        let at = self.active + 1;
+        self.slots.insert(at, Some(universe));
+        if let Some(home) = &mut self.home {
+            if *home >= at {
+                *home += 1;
+            }
+        }
+        self.switch(at, game, session);
+    }
+
+    /// Puts the universe on show away in its tab and shows tab `to`.
+    fn switch(&mut self, to: usize, game: &mut GameOfLife, session: &mut Session) {
+        let Some(mut next) = self.slots[to].take() else {
+            return;
+        };
+        std::mem::swap(game, &mut next.game);
+        std::mem::swap(session, &mut next.session);
+        next.last_step = Instant::now();
+        self.slots[self.active] = Some(next);
+        self.active = to;
+        // Made for a frame of the other universe.
+        session.flash_from = None;
+    }
+
+    /// Closes the universe on show, showing the next one, or the one before
+    /// it if it was the last. The last universe stays open.
+    fn close(&mut self, game: &mut GameOfLife, session: &mut Session) {
+        if self.len() < 2 {
+            return;
+        }
+        let closing = self.active;
+        let to = if closing + 1 < self.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch(to, game, session);
+        if let Some(mut closed) = self.slots.remove(closing) {
+            closed.session.wait_for_exports();
+        }
+        if to > closing {
+            self.active -= 1;
+        }
+        self.home = match self.home {
+            Some(home) if home == closing => None,
+            Some(home) if home > closing => Some(home - 1),
+            home => home,
+        };
+    }
+
+    /// Steps every universe not on show that is due to, unless background
+    /// stepping is off. Each goes at its own speed, though no faster than the
+    /// loop comes round.
+    pub(super) fn step_background(&mut self, options: &Options) {
+        if !options.background_tabs {
+            return;
+        }
+        for universe in self.slots.iter_mut().flatten() {
+            let session = &mut universe.session;
+            if session.paused || universe.last_step.elapsed() < session.tick_delay() {
+                continue;
+            }
+            universe.last_step = Instant::now();
+            universe.game.step();
+            session.after_step(&mut universe.game, options);
+        }
+    }
+
+    /// Shows the board the loop started with again, if its tab is still
+    /// open, and waits for every tab's history export to finish.
+    pub(super) fn finish(mut self, game: &mut GameOfLife, session: &mut Session) {
+        if let Some(home) = self.home {
+            self.switch(home, game, session);
+        }
+        for universe in self.slots.iter_mut().flatten() {
+            universe.session.wait_for_exports();
+        }
+    }
+}
+
+#[test]
+fn test_tabs_open_switch_and_close() {
+    use std::time::Duration;
+
+    use super::DELAY;
+    use crate::{GridPos, Pattern};
+
+    let options = Options::default();
+    let mut tabs = Tabs::new();
+    let mut game = GameOfLife::new_empty((6, 6));
+    game.set(GridPos(1, 1), true);
+    let mut session = Session::new(&options);
+    session.delay = Duration::from_millis(80);
+    let key = |tabs: &mut Tabs, game: &mut _, session: &mut _, key, modifiers| {
+        tabs.key(key, modifiers, game, session, &options)
+    };
+    // Tab does nothing, and ^W won't close, while there is only one universe.
+    assert!(!key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Tab,
+        Modifiers::NONE
+    ));
+    assert!(key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('w'),
+        Modifiers::CTRL
+    ));
+    assert_eq!((tabs.len(), tabs.prompt), (1, None));
+    // A plain t is left for the trail.
+    assert!(!key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('t'),
+        Modifiers::NONE
+    ));
+
+    // ^T asks what to start with; e opens an empty board, on show at once.
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('t'),
+        Modifiers::CTRL,
+    );
+    assert_eq!(tabs.prompt, Some(TabPrompt::New));
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('e'),
+        Modifiers::NONE,
+    );
+    assert_eq!((tabs.len(), tabs.active, game.population()), (2, 1, 0));
+    assert_eq!(session.delay, DELAY);
+    // p opens another, with the picker up; escape is no answer at all.
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('t'),
+        Modifiers::CTRL,
+    );
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('p'),
+        Modifiers::NONE,
+    );
+    assert!(session.paused && session.picker == Some(0));
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('t'),
+        Modifiers::CTRL,
+    );
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Escape,
+        Modifiers::NONE,
+    );
+    assert_eq!((tabs.len(), tabs.active, tabs.prompt), (3, 2, None));
+
+    // Tab goes round, shift-tab back, each universe as it was left.
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Tab,
+        Modifiers::NONE,
+    );
+    assert_eq!((tabs.active, game.population()), (0, 1));
+    assert_eq!(session.delay, Duration::from_millis(80));
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Tab,
+        Modifiers::SHIFT,
+    );
+    assert_eq!(tabs.active, 2);
+    assert!(session.picker.is_some());
+
+    // ^W asks first, and only y closes.
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('w'),
+        Modifiers::CTRL,
+    );
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('n'),
+        Modifiers::NONE,
+    );
+    assert_eq!(tabs.len(), 3);
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Tab,
+        Modifiers::NONE,
+    );
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('w'),
+        Modifiers::CTRL,
+    );
+    key(
+        &mut tabs,
+        &mut game,
+        &mut session,
+        KeyCode::Char('y'),
+        Modifiers::NONE,
+    );
+    // The first tab went, and the empty board after it is first now.
+    assert_eq!((tabs.len(), tabs.active, tabs.home), (2, 0, None));
+    assert!(session.picker.is_none());
+
+    // Background tabs stand still unless asked to keep stepping.
+    let mut game = GameOfLife::new_empty((6, 6));
+    let mut session = Session::new(&options);
+    let mut tabs = Tabs::new();
+    let mut blinker = GameOfLife::new_empty((6, 6));
+    blinker.insert_pattern(&Pattern::named("blinker").unwrap(), GridPos(1, 1));
+    tabs.open(blinker, &mut game, &mut session, &options);
+    tabs.switch(0, &mut game, &mut session);
+    let stepped = |tabs: &Tabs| tabs.slots[1].as_ref().unwrap().game.generation();
+    tabs.slots[1].as_mut().unwrap().last_step -= Duration::from_secs(1);
+    tabs.step_background(&options);
+    assert_eq!(stepped(&tabs), 0);
+    tabs.step_background(&Options {
+        background_tabs: true,
+        ..Options::default()
+    });
+    assert_eq!(stepped(&tabs), 1);
+    // Finishing goes back to the board the loop started with.
+    tabs.switch(1, &mut game, &mut session);
+    tabs.finish(&mut game, &mut session);
+    assert_eq!(game.population(), 0);
+}
@@ -0,0 +1,2298 @@
+use std::{fmt, str::FromStr, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    scene::Transform, DenseBool, Frame, GridPos, GridStorage, KernelRule, Mask, MaskCell, Pattern,
+    Rule, SimObserver,
+};
+
+/// The share of cells [`GameOfLife::init`] brings to life.
+pub const INIT_DENSITY: f64 = 0.7;
+
+/// An axis-aligned rectangle of cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Offsets of the eight cells around a cell, the neighbors under most rules.
+/// Neighbor lookups go through the rule's [`Rule::offsets`], which are these
+/// for them, so the definitions can't drift apart.
+pub const NEIGHBORS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// What lies beyond the edges of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeMode {
+    /// Everything outside the board is dead.
+    #[default]
+    Bounded,
+    /// The board wraps around, so opposite edges are neighbors. On a board one
+    /// or two cells across, several offsets wrap onto the same cell (possibly
+    /// the cell itself), and it is counted once for each.
+    Toroidal,
+    /// The board is reflected in its edges, the edge cells included: one
+    /// step past an edge is the edge cell itself, two steps the cell inside
+    /// it, and likewise across corners. So a cell on the edge is one of its
+    /// own neighbors, and the board runs as one corner of a board twice the
+    /// size, mirrored both ways, would on a torus.
+    Mirror,
+}
+
+/// The name [`FromStr`] reads first: `bounded`, `toroidal` or `mirror`.
+impl fmt::Display for EdgeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            EdgeMode::Bounded => "bounded",
+            EdgeMode::Toroidal => "toroidal",
+            EdgeMode::Mirror => "mirror",
+        })
+    }
+}
+
+/// `i` reflected into `0..n`, as [`EdgeMode::Mirror`] reflects it: `-1` is
+/// `0` and `n` is `n - 1`, again and again for offsets longer than `n`.
+pub(crate) fn reflect(i: isize, n: usize) -> usize {
+    let m = i.rem_euclid(2 * n as isize) as usize;
+    if m < n {
+        m
+    } else {
+        2 * n - 1 - m
+    }
+}
+
+impl FromStr for EdgeMode {
+    type Err = String;
+
+    /// Any case is accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bounded" => Ok(EdgeMode::Bounded),
+            "toroidal" | "torus" | "wrap" => Ok(EdgeMode::Toroidal),
+            "mirror" | "mirrored" | "reflect" => Ok(EdgeMode::Mirror),
+            _ => Err(format!(
+                "unknown edge mode {s:?}, expected bounded, toroidal or mirror"
+            )),
+        }
+    }
+}
+
+/// How [`GameOfLife::merge`] combines each cell of the board merged in with
+/// the one under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeMode {
+    /// Alive if either is.
+    #[default]
+    Or,
+    /// Alive if both are.
+    And,
+    /// Alive if exactly one is.
+    Xor,
+    /// As the board merged in has it.
+    Replace,
+}
+
+impl MergeMode {
+    /// The cell `here` becomes with `there` merged onto it.
+    pub fn apply(self, here: bool, there: bool) -> bool {
+        match self {
+            MergeMode::Or => here || there,
+            MergeMode::And => here && there,
+            MergeMode::Xor => here != there,
+            MergeMode::Replace => there,
+        }
+    }
+}
+
+/// What [`GameOfLife::inspect`] finds out about a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellInfo {
+    /// Whether it is alive.
+    pub state: bool,
+    /// Its live neighbors, as [`GameOfLife::count_neighbors`] counts them.
+    pub neighbors: usize,
+    /// Whether it will be alive next generation.
+    pub next: bool,
+}
+
+/// How much of a pattern [`GameOfLife::insert_pattern`] got onto the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlacementResult {
+    /// Live cells of the pattern that landed on the board.
+    pub placed: usize,
+    /// Live cells that fell off it and were dropped.
+    pub clipped: usize,
+}
+
+impl PlacementResult {
+    /// A warning about placing the pattern `name`, if any of it was dropped.
+    pub fn warning(&self, name: &str) -> Option<String> {
+        (self.clipped > 0).then(|| {
+            let clipped = self.clipped;
+            format!("{name} clipped: {clipped} cells off-grid — try a larger terminal")
+        })
+    }
+}
+
+/// A direction across the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Axis {
+    /// Left to right.
+    #[default]
+    X,
+    /// Top to bottom.
+    Y,
+}
+
+impl FromStr for Axis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Axis::X),
+            "y" => Ok(Axis::Y),
+            _ => Err(format!("unknown axis {s:?}, expected x or y")),
+        }
+    }
+}
+
+/// A board and the rules it runs by, keeping its cells in `S`.
+#[derive(Clone)]
+pub struct GameOfLife<S: GridStorage = DenseBool> {
+    size: (usize, usize),
+    grid: S,
+    generation: u64,
+    mask: Option<Mask>,
+    edges: EdgeMode,
+    rule: Rule,
+    /// The rule's [`Rule::offsets`], kept so lookups don't rebuild them.
+    offsets: Vec<(isize, isize)>,
+    /// Followed in place of `rule` when set; see
+    /// [`set_kernel_rule`](Self::set_kernel_rule).
+    pub(crate) kernel: Option<KernelRule>,
+    /// Where [`step`](Self::step) counts neighbors, kept between steps so it
+    /// isn't reallocated each time.
+    counts: Vec<u8>,
+    /// Row-major like `grid`; see [`enable_tags`](Self::enable_tags).
+    #[cfg(feature = "tags")]
+    pub(crate) tags: Option<Vec<u8>>,
+    /// What the last random fill was seeded with.
+    seed: Option<u64>,
+    /// Row-major like `grid`; see [`enable_activity`](Self::enable_activity).
+    pub(crate) activity: Option<Vec<u32>>,
+    observer: Observer,
+}
+
+/// The [`SimObserver`] a board was given, if any. Clones of the board go
+/// unobserved. Observers are `Send` so that boards still are.
+#[derive(Default)]
+struct Observer(Option<Box<dyn SimObserver + Send>>);
+
+impl Clone for Observer {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl GameOfLife {
+    /// A randomly filled board. Dimensions of zero are raised to one, so a
+    /// board always has at least one cell.
+    pub fn new(size: (usize, usize)) -> Self {
+        let mut new = Self::new_empty(size);
+        new.init();
+        new
+    }
+
+    /// A board of dead cells, sized as by [`new`](Self::new).
+    pub fn new_empty(size: (usize, usize)) -> Self {
+        Self::empty(size)
+    }
+
+    /// A board just big enough for the picture in `art`, drawn as
+    /// [`Pattern::from_ascii`] reads it, such as `"...\n###\n..."` for a
+    /// blinker with a dead cell around it. To stamp art into a board that
+    /// already exists, [`insert_pattern`](Self::insert_pattern) it.
+    pub fn from_ascii(art: &str) -> Self {
+        let pattern = Pattern::from_ascii(art);
+        let mut game = Self::new_empty(pattern.size());
+        game.insert_pattern(&pattern, GridPos(0, 0));
+        game
+    }
+
+    /// Every cell, row-major: the cell at `(x, y)` is at
+    /// [`index(x, y)`](Self::index).
+    pub fn surface(&self) -> &[bool] {
+        self.grid.as_slice()
+    }
+
+    /// Every cell, row-major as in [`surface`](Self::surface), to change in
+    /// bulk. The slice always holds `size().0 * size().1` cells; its length
+    /// can't change. Writes bypass the mask, so forced cells may be changed
+    /// until the next step puts them back.
+    pub fn surface_mut(&mut self) -> &mut [bool] {
+        self.grid.as_mut_slice()
+    }
+}
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// A board of dead cells kept in `S`, sized as by
+    /// [`new`](GameOfLife::new).
+    pub fn empty(size: (usize, usize)) -> Self {
+        let size = (size.0.max(1), size.1.max(1));
+        Self {
+            size,
+            grid: S::new(size),
+            generation: 0,
+            mask: None,
+            edges: EdgeMode::default(),
+            rule: Rule::default(),
+            offsets: Rule::default().offsets(),
+            kernel: None,
+            counts: Vec::new(),
+            #[cfg(feature = "tags")]
+            tags: None,
+            seed: None,
+            activity: None,
+            observer: Observer::default(),
+        }
+    }
+
+    /// Refills the board at random, from a seed drawn from the system's
+    /// entropy and kept as the [`seed`](Self::seed) to fill it the same way
+    /// again.
+    pub fn init(&mut self) {
+        self.fill(INIT_DENSITY, rand::random());
+    }
+
+    /// The seed of the last random fill, however it was made, if there was
+    /// one: passing it back to the same fill on a board of the same size
+    /// brings the board back. Editing cells afterwards leaves it be.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Refills the board at random from `seed`, each cell live with
+    /// probability `density`.
+    pub fn fill(&mut self, density: f64, seed: u64) {
+        self.fill_gradient(density, density, Axis::X, seed);
+    }
+
+    /// Refills the cells of `rect` at random from `seed`, each live with
+    /// probability `density`, and leaves the rest of the board as it was;
+    /// the part of `rect` off the board is passed over. Every cell's draw
+    /// comes from the seed alone, so with the same seed a higher density
+    /// keeps the cells a lower one made live and adds to them.
+    pub fn fill_rect(&mut self, rect: Rect, density: f64, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (w, h) = self.size;
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                let draw: f64 = rng.gen();
+                if x < w && y < h {
+                    self.set(GridPos(x, y), draw < density);
+                }
+            }
+        }
+    }
+
+    /// Brings dead cells to life at random from `seed`, each with
+    /// probability `density`, and leaves live ones as they are: new
+    /// activity that keeps what is already on the board.
+    pub fn sprinkle(&mut self, density: f64, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for i in 0..self.cell_count() {
+            let pos = self.coords(i);
+            let draw: f64 = rng.gen();
+            if draw < density && !self.get(pos) {
+                self.set(pos, true);
+            }
+        }
+    }
+
+    /// Refills the board with exactly `count` live cells at distinct random
+    /// positions from `seed`, for comparing layouts of the same population.
+    /// Cells the mask forces keep their state and aren't picked, so fails if
+    /// fewer than `count` cells are free.
+    pub fn fill_exact(&mut self, count: usize, seed: u64) -> Result<(), String> {
+        let mut free = (0..self.cell_count())
+            .filter(|&i| {
+                let pos = self.coords(i);
+                !self.is_forced(pos)
+            })
+            .collect::<Vec<_>>();
+        if count > free.len() {
+            let (w, h) = self.size;
+            return Err(format!("{count} live cells don't fit on a {w}x{h} board"));
+        }
+        // A Fisher–Yates shuffle, stopped once the first `count` are picked.
+        let mut rng = StdRng::seed_from_u64(seed);
+        for i in 0..count {
+            let j = rng.gen_range(i..free.len());
+            free.swap(i, j);
+        }
+        for (n, &i) in free.iter().enumerate() {
+            let pos = self.coords(i);
+            self.set(pos, n < count);
+        }
+        self.seed = Some(seed);
+        Ok(())
+    }
+
+    /// Refills the board at random from `seed`, each cell live with a
+    /// probability that runs linearly from `from_density` at the start of
+    /// `axis` to `to_density` at its end.
+    pub fn fill_gradient(&mut self, from_density: f64, to_density: f64, axis: Axis, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (w, h) = self.size;
+        let last = match axis {
+            Axis::X => w - 1,
+            Axis::Y => h - 1,
+        };
+        for y in 0..h {
+            for x in 0..w {
+                let along = match axis {
+                    Axis::X => x,
+                    Axis::Y => y,
+                };
+                let t = if last == 0 {
+                    0.0
+                } else {
+                    along as f64 / last as f64
+                };
+                let density = from_density + (to_density - from_density) * t;
+                self.set(GridPos(x, y), rng.gen_bool(density.clamp(0.0, 1.0)));
+            }
+        }
+        self.seed = Some(seed);
+    }
+
+    /// Refills the board with a maze from `seed`: corridors of dead cells
+    /// between live walls one cell thick, laid out by recursive division, so
+    /// that every dead cell can be reached from every other. Each choice is a
+    /// hash of the seed and how many choices came before it rather than a
+    /// draw from a random generator, so a seed and a size always give the
+    /// same maze.
+    pub fn fill_maze(&mut self, seed: u64) {
+        let (w, h) = self.size;
+        for y in 0..h {
+            for x in 0..w {
+                self.set(GridPos(x, y), false);
+            }
+        }
+        self.seed = Some(seed);
+        let mut choices = 0u64;
+        let mut pick = |n: usize| {
+            choices += 1;
+            (mix(seed ^ mix(choices)) % n as u64) as usize
+        };
+        // Open chambers as (x, y, width, height), starting on even cells, so
+        // that walls run along odd rows and columns and the gaps in them on
+        // even ones, which no later wall can close.
+        let mut chambers = vec![(0, 0, w, h)];
+        while let Some((x, y, cw, ch)) = chambers.pop() {
+            if cw < 3 || ch < 3 {
+                continue;
+            }
+            let across = match cw.cmp(&ch) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Greater => false,
+                std::cmp::Ordering::Equal => pick(2) == 0,
+            };
+            if across {
+                let row = y + 1 + 2 * pick((ch - 1) / 2);
+                let gap = x + 2 * pick(cw.div_ceil(2));
+                for cx in (x..x + cw).filter(|&cx| cx != gap) {
+                    self.set(GridPos(cx, row), true);
+                }
+                chambers.push((x, y, cw, row - y));
+                chambers.push((x, row + 1, cw, y + ch - row - 1));
+            } else {
+                let column = x + 1 + 2 * pick((cw - 1) / 2);
+                let gap = y + 2 * pick(ch.div_ceil(2));
+                for cy in (y..y + ch).filter(|&cy| cy != gap) {
+                    self.set(GridPos(column, cy), true);
+                }
+                chambers.push((x, y, column - x, ch));
+                chambers.push((column + 1, y, x + cw - column - 1, ch));
+            }
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Number of generations stepped since the game was created or the
+    /// counter was last reset.
+    ///
+    /// Only stepping ([`step`](Self::step), [`tick`](Self::tick) and
+    /// [`step_with_fn`](Self::step_with_fn)) advances it, and only
+    /// [`reset_generation`](Self::reset_generation) and
+    /// [`restore`](Self::restore) set it otherwise. Editing, filling, loading
+    /// patterns, resizing and translating leave it alone, so a pattern can be
+    /// dropped into a running board without restarting its clock.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Sets the generation counter back to zero, leaving the cells alone.
+    pub fn reset_generation(&mut self) {
+        self.generation = 0;
+    }
+
+    /// Changes the grid size, keeping the cells that are inside both the old
+    /// and new bounds. Newly exposed cells start dead. As in
+    /// [`new`](Self::new), dimensions of zero are raised to one.
+    pub fn resize(&mut self, size: (usize, usize)) {
+        let size = (size.0.max(1), size.1.max(1));
+        let mut grid = S::new(size);
+        for y in 0..self.size.1.min(size.1) {
+            for x in 0..self.size.0.min(size.0) {
+                grid.set(x, y, self.get(GridPos(x, y)));
+            }
+        }
+        #[cfg(feature = "tags")]
+        if let Some(tags) = &mut self.tags {
+            let mut resized = vec![0; size.0 * size.1];
+            for y in 0..self.size.1.min(size.1) {
+                for x in 0..self.size.0.min(size.0) {
+                    resized[x + y * size.0] = tags[x + y * self.size.0];
+                }
+            }
+            *tags = resized;
+        }
+        if let Some(activity) = &mut self.activity {
+            let mut resized = vec![0; size.0 * size.1];
+            for y in 0..self.size.1.min(size.1) {
+                for x in 0..self.size.0.min(size.0) {
+                    resized[x + y * size.0] = activity[x + y * self.size.0];
+                }
+            }
+            *activity = resized;
+        }
+        self.size = size;
+        self.grid = grid;
+        if let Some(mask) = &mut self.mask {
+            mask.resize(size);
+        }
+    }
+
+    /// Puts the board back as it was in `frame`: its size, rule, cells and
+    /// generation. Cells the mask forces keep their forced state.
+    pub fn restore(&mut self, frame: &Frame) {
+        if self.size != frame.size {
+            self.resize(frame.size);
+        }
+        self.set_rule(frame.rule);
+        self.set_edge_mode(frame.edges);
+        self.generation = frame.generation;
+        for (i, &cell) in frame.cells.iter().enumerate() {
+            let pos = self.coords(i);
+            self.set(pos, cell);
+        }
+    }
+
+    pub fn edge_mode(&self) -> EdgeMode {
+        self.edges
+    }
+
+    pub fn set_edge_mode(&mut self, edges: EdgeMode) {
+        self.edges = edges;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Where the cells are kept.
+    pub fn storage(&self) -> &S {
+        &self.grid
+    }
+
+    /// Changes the rule [`step`](Self::step) and [`tick`](Self::tick) follow.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.offsets = rule.offsets();
+        self.kernel = None;
+    }
+
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// Restricts where cells can live, or lifts the restriction with `None`.
+    /// Forced cells take their state immediately.
+    ///
+    /// # Panics
+    ///
+    /// If the mask is not the same size as the board.
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        if let Some(mask) = &mask {
+            assert_eq!(mask.size(), self.size, "mask size must match the board");
+        }
+        self.mask = mask;
+        self.apply_mask();
+    }
+
+    /// Whether the mask holds the cell at `pos` at a fixed state.
+    pub fn is_forced(&self, pos: GridPos) -> bool {
+        self.mask
+            .as_ref()
+            .is_some_and(|mask| mask.get(pos.0, pos.1) != MaskCell::Free)
+    }
+
+    fn apply_mask(&mut self) {
+        let Some(mask) = &self.mask else {
+            return;
+        };
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                if let Some(state) = mask.get(x, y).forced() {
+                    self.grid.set(x, y, state);
+                }
+            }
+        }
+    }
+
+    /// Total number of cells on the board.
+    pub fn cell_count(&self) -> usize {
+        self.size.0 * self.size.1
+    }
+
+    /// Position in [`surface`](Self::surface) of the cell at `pos`.
+    ///
+    /// The coordinates are not checked; out-of-range input gives a meaningless
+    /// (possibly out-of-bounds) index.
+    pub fn index(&self, pos: GridPos) -> usize {
+        pos.0 + pos.1 * self.size.0
+    }
+
+    /// The cell at `index` in [`surface`](Self::surface); the inverse of
+    /// [`index`](Self::index). Not checked against the cell count.
+    pub fn coords(&self, index: usize) -> GridPos {
+        GridPos(index % self.size.0, index / self.size.0)
+    }
+
+    /// Sets the cell at `pos`; cells forced by the mask are left as they are.
+    pub fn set(&mut self, pos: GridPos, value: bool) {
+        if self.is_forced(pos) {
+            return;
+        }
+        self.grid.set(pos.0, pos.1, value);
+    }
+
+    pub fn get(&self, pos: GridPos) -> bool {
+        self.grid.get(pos.0, pos.1)
+    }
+
+    /// The cell at `(x, y)` taken modulo the board's size, so that any
+    /// coordinates, negative or past the edge, land on a cell. This always
+    /// wraps, whatever the [`EdgeMode`].
+    pub fn get_wrapped(&self, x: isize, y: isize) -> bool {
+        let (w, h) = self.size;
+        self.grid.get(
+            x.rem_euclid(w as isize) as usize,
+            y.rem_euclid(h as isize) as usize,
+        )
+    }
+
+    /// The same board, cells and all, kept in `T` instead.
+    pub fn to_storage<T: GridStorage>(&self) -> GameOfLife<T> {
+        let mut grid = T::new(self.size);
+        let mut row = vec![false; self.size.0];
+        for y in 0..self.size.1 {
+            self.grid.read_row(y, &mut row);
+            for (x, &cell) in row.iter().enumerate() {
+                grid.set(x, y, cell);
+            }
+        }
+        GameOfLife {
+            size: self.size,
+            grid,
+            generation: self.generation,
+            mask: self.mask.clone(),
+            edges: self.edges,
+            rule: self.rule,
+            offsets: self.offsets.clone(),
+            kernel: self.kernel.clone(),
+            counts: Vec::new(),
+            #[cfg(feature = "tags")]
+            tags: self.tags.clone(),
+            seed: self.seed,
+            activity: self.activity.clone(),
+            observer: Observer::default(),
+        }
+    }
+
+    /// Calls `f` with each row of cells in turn, top to bottom.
+    fn for_each_row(&self, mut f: impl FnMut(&[bool])) {
+        let mut row = vec![false; self.size.0];
+        for y in 0..self.size.1 {
+            self.grid.read_row(y, &mut row);
+            f(&row);
+        }
+    }
+
+    /// Copies the cells inside `rect` into a pattern the size of `rect`. The
+    /// part of `rect` outside the board reads as dead.
+    pub fn extract(&self, rect: Rect) -> Pattern {
+        let (w, h) = self.size;
+        let cells = (rect.y..(rect.y + rect.height).min(h))
+            .flat_map(|y| (rect.x..(rect.x + rect.width).min(w)).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(GridPos(x, y)))
+            .map(|(x, y)| (x - rect.x, y - rect.y))
+            .collect();
+        Pattern::with_size((rect.width, rect.height), cells)
+    }
+
+    /// Writes `pattern` with its top-left corner at `origin`, replacing
+    /// everything in its box: cells the pattern doesn't list become dead. The
+    /// part that falls off the board wraps around under
+    /// [`EdgeMode::Toroidal`] and is dropped otherwise.
+    pub fn insert_pattern(&mut self, pattern: &Pattern, origin: GridPos) -> PlacementResult {
+        self.place(pattern, origin, self.edges == EdgeMode::Toroidal)
+    }
+
+    /// How much of `pattern` [`insert_pattern`](Self::insert_pattern) would
+    /// get onto the board at `origin`, without placing it.
+    pub fn placement(&self, pattern: &Pattern, origin: GridPos) -> PlacementResult {
+        let (w, h) = self.size;
+        let on_board = |&&(x, y): &&(usize, usize)| origin.0 + x < w && origin.1 + y < h;
+        let placed = match self.edges {
+            EdgeMode::Toroidal => pattern.cells().len(),
+            _ => pattern.cells().iter().filter(on_board).count(),
+        };
+        PlacementResult {
+            placed,
+            clipped: pattern.cells().len() - placed,
+        }
+    }
+
+    /// [`insert_pattern`](Self::insert_pattern), wrapping what falls off the
+    /// board if `wrap` and dropping it if not.
+    fn place(&mut self, pattern: &Pattern, origin: GridPos, wrap: bool) -> PlacementResult {
+        let (w, h) = self.size;
+        let (pw, ph) = pattern.size();
+        let on_board = |x: usize, y: usize| match wrap {
+            true => Some((x % w, y % h)),
+            false => (x < w && y < h).then_some((x, y)),
+        };
+        // A box wider than the board wraps onto itself; no cell needs
+        // clearing twice.
+        let (pw, ph) = if wrap {
+            (pw.min(w), ph.min(h))
+        } else {
+            (pw, ph)
+        };
+        for y in origin.1..origin.1 + ph {
+            for x in origin.0..origin.0 + pw {
+                if let Some((x, y)) = on_board(x, y) {
+                    self.set(GridPos(x, y), false);
+                }
+            }
+        }
+        let mut result = PlacementResult::default();
+        for &(x, y) in pattern.cells() {
+            match on_board(origin.0 + x, origin.1 + y) {
+                Some((x, y)) => {
+                    self.set(GridPos(x, y), true);
+                    result.placed += 1;
+                }
+                None => result.clipped += 1,
+            }
+        }
+        result
+    }
+
+    /// Combines every cell of `other` with the cell it lands on when its
+    /// top-left corner is put at `origin`, as `mode` says. Cells outside where
+    /// the two overlap are left alone, and cells the mask forces keep their
+    /// state.
+    pub fn merge<T: GridStorage>(
+        &mut self,
+        other: &GameOfLife<T>,
+        origin: GridPos,
+        mode: MergeMode,
+    ) {
+        let (w, h) = self.size;
+        let (ow, oh) = other.size();
+        for y in origin.1..(origin.1 + oh).min(h) {
+            for x in origin.0..(origin.0 + ow).min(w) {
+                let there = other.get(GridPos(x - origin.0, y - origin.1));
+                self.set(GridPos(x, y), mode.apply(self.get(GridPos(x, y)), there));
+            }
+        }
+    }
+
+    /// Clears the board and stamps the library pattern `name` (see
+    /// [`Pattern::named`]), put through `transforms`, at every multiple of
+    /// `spacing`, starting from the top-left corner. Copies that run off the
+    /// edge are clipped. A spacing smaller than the pattern is raised to its
+    /// size so copies don't overlap.
+    pub fn tile_pattern(
+        &mut self,
+        name: &str,
+        spacing: (usize, usize),
+        transforms: &[Transform],
+    ) -> Result<(), String> {
+        let pattern = Pattern::named(name).ok_or_else(|| format!("unknown pattern {name:?}"))?;
+        let pattern = pattern.transformed(transforms);
+        let (pw, ph) = pattern.size();
+        let (sx, sy) = (spacing.0.max(pw).max(1), spacing.1.max(ph).max(1));
+        for i in 0..self.cell_count() {
+            let pos = self.coords(i);
+            self.set(pos, false);
+        }
+        let (w, h) = self.size;
+        for y in (0..h).step_by(sy) {
+            for x in (0..w).step_by(sx) {
+                self.place(&pattern, GridPos(x, y), false);
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the board, wraps its edges and seeds it with `count` Gosper
+    /// glider guns, one in the middle of each slot of an even grid, each
+    /// turned to fire towards the middle of the board so their streams of
+    /// gliders meet and, wrapping around, keep meeting. Each gun is then put
+    /// through `transforms`, so that a half turn, say, has them all fire
+    /// away from the middle. Fails if the slots are too small for the guns.
+    pub fn init_with_glider_guns(
+        &mut self,
+        count: usize,
+        transforms: &[Transform],
+    ) -> Result<(), String> {
+        let gun = Pattern::named("gosper-glider-gun").ok_or("no glider gun in the library")?;
+        let (w, h) = self.size;
+        let cols = (1..=count).find(|cols| cols * cols >= count).unwrap_or(1);
+        let rows = count.div_ceil(cols).max(1);
+        let slot = (w / cols, h / rows);
+        let (gw, gh) = gun.size();
+        // A turned gun is as wide as it was tall.
+        if slot.0 < gw.max(gh) || slot.1 < gw.max(gh) {
+            return Err(format!(
+                "a {w}x{h} board is too small for {count} glider guns"
+            ));
+        }
+        for i in 0..self.cell_count() {
+            let pos = self.coords(i);
+            self.set(pos, false);
+        }
+        self.set_edge_mode(EdgeMode::Toroidal);
+        for i in 0..count {
+            let center = (
+                i % cols * slot.0 + slot.0 / 2,
+                i / cols * slot.1 + slot.1 / 2,
+            );
+            // The gun fires down and to the right; each quarter turn
+            // clockwise swings its aim round by one corner.
+            let turns = match (center.0 < w / 2, center.1 <= h / 2) {
+                (true, true) => 0,
+                (false, true) => 1,
+                (false, false) => 2,
+                (true, false) => 3,
+            };
+            let gun = gun.rotated(turns).transformed(transforms);
+            let (gw, gh) = gun.size();
+            self.insert_pattern(&gun, GridPos(center.0 - gw / 2, center.1 - gh / 2));
+        }
+        Ok(())
+    }
+
+    /// Moves every cell by `(dx, dy)`. Under [`EdgeMode::Toroidal`] cells
+    /// wrap around; otherwise, mirrored edges included, those pushed off the
+    /// board are lost and the cells left behind are dead.
+    pub fn translate(&mut self, dx: isize, dy: isize) {
+        let (w, h) = self.size;
+        let mut grid = S::new(self.size);
+        #[cfg(feature = "tags")]
+        let mut tags = self.tags.as_ref().map(|tags| vec![0; tags.len()]);
+        for y in 0..h {
+            for x in 0..w {
+                if !self.get(GridPos(x, y)) {
+                    continue;
+                }
+                let to = match self.edges {
+                    EdgeMode::Bounded | EdgeMode::Mirror => x
+                        .checked_add_signed(dx)
+                        .filter(|&i| i < w)
+                        .zip(y.checked_add_signed(dy).filter(|&j| j < h)),
+                    EdgeMode::Toroidal => Some((
+                        (x as isize + dx).rem_euclid(w as isize) as usize,
+                        (y as isize + dy).rem_euclid(h as isize) as usize,
+                    )),
+                };
+                if let Some((i, j)) = to {
+                    grid.set(i, j, true);
+                    #[cfg(feature = "tags")]
+                    if let (Some(moved), Some(tags)) = (&mut tags, &self.tags) {
+                        moved[i + j * w] = tags[x + y * w];
+                    }
+                }
+            }
+        }
+        self.grid = grid;
+        #[cfg(feature = "tags")]
+        {
+            self.tags = tags;
+        }
+        self.apply_mask();
+    }
+
+    /// Slides the whole board by `(dx, dy)`, for moving what is on it into
+    /// place. Follows the [`EdgeMode`] as [`translate`](Self::translate)
+    /// does: cells wrap around a torus and are discarded past any other edge.
+    pub fn shift(&mut self, dx: isize, dy: isize) {
+        self.translate(dx, dy);
+    }
+
+    /// Shrinks the grid to `size` by OR-ing together the block of cells that
+    /// maps onto each output cell, so small structures stay visible.
+    pub fn downsample(&self, size: (usize, usize)) -> Vec<bool> {
+        let mut out = vec![false; size.0 * size.1];
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                if self.get(GridPos(x, y)) {
+                    out[x * size.0 / self.size.0 + y * size.1 / self.size.1 * size.0] = true;
+                }
+            }
+        }
+        out
+    }
+
+    /// Writes the board into `buf` as row-major RGBA pixels, one per cell, in
+    /// `live` or `dead`. `buf` must hold exactly `cell_count() * 4` bytes.
+    pub fn render_rgba(&self, buf: &mut [u8], live: [u8; 4], dead: [u8; 4]) -> Result<(), String> {
+        let (w, h) = self.size;
+        if buf.len() != self.cell_count() * 4 {
+            return Err(format!(
+                "RGBA buffer for a {w}x{h} board needs {} bytes, got {}",
+                self.cell_count() * 4,
+                buf.len()
+            ));
+        }
+        let mut pixels = buf.chunks_exact_mut(4);
+        self.for_each_row(|row| {
+            for (&alive, pixel) in row.iter().zip(pixels.by_ref()) {
+                pixel.copy_from_slice(if alive { &live } else { &dead });
+            }
+        });
+        Ok(())
+    }
+
+    /// The cells at each of the rule's neighbor offsets from `pos` that
+    /// exist under the board's [`EdgeMode`].
+    pub fn neighbors(&self, pos: GridPos) -> impl Iterator<Item = GridPos> + '_ {
+        let (GridPos(x, y), (w, h)) = (pos, self.size);
+        self.offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| match self.edges {
+                EdgeMode::Bounded => {
+                    let i = x.checked_add_signed(dx).filter(|&i| i < w)?;
+                    let j = y.checked_add_signed(dy).filter(|&j| j < h)?;
+                    Some(GridPos(i, j))
+                }
+                EdgeMode::Toroidal => Some(GridPos(
+                    (x as isize + dx).rem_euclid(w as isize) as usize,
+                    (y as isize + dy).rem_euclid(h as isize) as usize,
+                )),
+                EdgeMode::Mirror => Some(GridPos(
+                    reflect(x as isize + dx, w),
+                    reflect(y as isize + dy, h),
+                )),
+            })
+    }
+
+    pub fn count_neighbors(&self, pos: GridPos) -> usize {
+        self.neighbors(pos).filter(|&cell| self.get(cell)).count()
+    }
+
+    /// The cell at `pos`, how many live neighbors it has and what that, the
+    /// rule and the mask make of it next generation.
+    pub fn inspect(&self, pos: GridPos) -> CellInfo {
+        let state = self.get(pos);
+        let neighbors = self.count_neighbors(pos);
+        let forced = self
+            .mask
+            .as_ref()
+            .and_then(|mask| mask.get(pos.0, pos.1).forced());
+        let next = || match &self.kernel {
+            Some(rule) => rule.next(state, self.weighted_sum(rule.kernel(), pos)),
+            None => self.rule.next(state, neighbors),
+        };
+        CellInfo {
+            state,
+            neighbors,
+            next: forced.unwrap_or_else(next),
+        }
+    }
+
+    /// Every cell's live-neighbor count, row-major as in
+    /// [`index`](Self::index), as [`count_neighbors`](Self::count_neighbors)
+    /// would give them.
+    pub fn neighbor_counts(&self) -> Vec<u8> {
+        let mut counts = Vec::new();
+        self.neighbor_counts_into(&mut counts);
+        counts
+    }
+
+    /// The cells, by index, where [`neighbor_counts`](Self::neighbor_counts)
+    /// disagrees with counting each cell's neighbors one by one, as
+    /// [`count_neighbors`](Self::count_neighbors) does. There are none unless
+    /// the quicker count is wrong. Slow, for checking it while it changes.
+    pub fn neighbor_mismatches(&self) -> Vec<usize> {
+        let counts = self.neighbor_counts();
+        (0..self.cell_count())
+            .filter(|&i| {
+                let pos = self.coords(i);
+                counts[i] as usize != self.count_neighbors(pos)
+            })
+            .collect()
+    }
+
+    /// Like [`neighbor_counts`](Self::neighbor_counts), into `counts`, which
+    /// is resized to fit and can be reused from call to call.
+    ///
+    /// Each row of the neighborhood is a run of cells centered on the cell's
+    /// column, so the count is a sum of runs, each read off a running total
+    /// of its row. Every row is read once.
+    pub fn neighbor_counts_into(&self, counts: &mut Vec<u8>) {
+        let (w, h) = self.size;
+        counts.clear();
+        counts.resize(w * h, 0);
+        let reach = self
+            .offsets
+            .iter()
+            .map(|&(_, dy)| dy.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        // How far across each row of the neighborhood reaches, by dy + reach.
+        let mut spans = vec![None; 2 * reach + 1];
+        for &(dx, dy) in &self.offsets {
+            let span: &mut Option<usize> = &mut spans[(reach as isize + dy) as usize];
+            *span = Some(span.unwrap_or(0).max(dx.unsigned_abs()));
+        }
+        let mut row = vec![false; w];
+        // A mirrored row repeats itself and its reflection, so runs are read
+        // off it as off a wrapping row twice as long.
+        let period = match self.edges {
+            EdgeMode::Mirror => 2 * w,
+            _ => w,
+        };
+        // Live cells among the first i of the row, run twice over so runs
+        // that wrap are read off in one go.
+        let mut totals = vec![0u32; 2 * period + 1];
+        let mut sources = Vec::new();
+        for j in 0..h {
+            self.grid.read_row(j, &mut row);
+            for i in 0..2 * period {
+                totals[i + 1] = totals[i] + row[reflect((i % period) as isize, w)] as u32;
+            }
+            let live = totals[period];
+            let run = |x: usize, k: usize| match self.edges {
+                EdgeMode::Bounded => totals[(x + k + 1).min(w)] - totals[x.saturating_sub(k)],
+                // A run longer than the board goes round it whole first.
+                EdgeMode::Toroidal | EdgeMode::Mirror => {
+                    let (laps, rest) = ((2 * k + 1) / period, (2 * k + 1) % period);
+                    let start = (x + period - k % period) % period;
+                    laps as u32 * live + totals[start + rest] - totals[start]
+                }
+            };
+            for (i, &span) in spans.iter().enumerate() {
+                let Some(k) = span else {
+                    continue;
+                };
+                // Row j is row y + dy of the neighborhood of row y, for
+                // each y it is; a mirrored row may be that of several.
+                let dy = i as isize - reach as isize;
+                sources.clear();
+                match self.edges {
+                    EdgeMode::Bounded => {
+                        sources.extend(j.checked_add_signed(-dy).filter(|&y| y < h))
+                    }
+                    EdgeMode::Toroidal => {
+                        sources.push((j as isize - dy).rem_euclid(h as isize) as usize)
+                    }
+                    EdgeMode::Mirror => {
+                        sources.extend((0..h).filter(|&y| reflect(y as isize + dy, h) == j))
+                    }
+                }
+                for &y in &sources {
+                    let out = &mut counts[y * w..(y + 1) * w];
+                    for (x, count) in out.iter_mut().enumerate() {
+                        // A cell isn't its own neighbor, except by reflection.
+                        let own = dy == 0 && row[x];
+                        *count += (run(x, k) - own as u32) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    /// A cheap hash of the cells (FNV-1a over the live-cell bits), equal for
+    /// equal boards.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = 0xcbf29ce484222325;
+        self.for_each_row(|row| {
+            for &cell in row {
+                hash = (hash ^ cell as u64).wrapping_mul(0x100000001b3);
+            }
+        });
+        hash
+    }
+
+    /// Replaces `words` with the cells, row by row, 64 to a word with the
+    /// first in the lowest bit. The last word's spare bits are zero.
+    pub fn pack_into(&self, words: &mut Vec<u64>) {
+        words.clear();
+        words.resize(self.cell_count().div_ceil(64), 0);
+        let mut i = 0;
+        self.for_each_row(|row| {
+            for &cell in row {
+                words[i / 64] |= (cell as u64) << (i % 64);
+                i += 1;
+            }
+        });
+    }
+
+    /// A hash of the cells, quicker than [`GameOfLife::fingerprint`] for
+    /// being taken over them packed a word at a time. Every cell counts in
+    /// its place, so two boards with any cell different, or of different
+    /// sizes, can collide only by chance.
+    pub fn checksum(&self) -> u64 {
+        let mut words = Vec::new();
+        self.pack_into(&mut words);
+        checksum_words(self.size, &words)
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        let mut population = 0;
+        self.for_each_row(|row| population += row.iter().filter(|&&cell| cell).count());
+        population
+    }
+
+    /// Whether any cell in `rect` is alive. The part of `rect` off the board
+    /// has nothing in it.
+    pub fn any_live_in(&self, rect: Rect) -> bool {
+        let (w, h) = self.size;
+        let (right, bottom) = ((rect.x + rect.width).min(w), (rect.y + rect.height).min(h));
+        (rect.y..bottom).any(|y| (rect.x..right).any(|x| self.get(GridPos(x, y))))
+    }
+
+    /// The number of live cells in `rect`, the part of it off the board
+    /// counting for none. Only the rows `rect` crosses are read.
+    pub fn population_in(&self, rect: Rect) -> usize {
+        let (w, h) = self.size;
+        let (right, bottom) = ((rect.x + rect.width).min(w), (rect.y + rect.height).min(h));
+        if rect.x >= right {
+            return 0;
+        }
+        let mut row = vec![false; w];
+        (rect.y..bottom)
+            .map(|y| {
+                self.grid.read_row(y, &mut row);
+                row[rect.x..right].iter().filter(|&&cell| cell).count()
+            })
+            .sum()
+    }
+
+    /// Steps until `predicate` holds for the board, checking it before each
+    /// step and after the last, and returns the generation it held at. Gives
+    /// up with `None` once `max_steps` steps haven't made it hold.
+    pub fn step_until(&mut self, max_steps: u64, predicate: impl Fn(&Self) -> bool) -> Option<u64> {
+        for _ in 0..max_steps {
+            if predicate(self) {
+                return Some(self.generation);
+            }
+            self.step();
+        }
+        predicate(self).then_some(self.generation)
+    }
+
+    /// Advances one generation under the board's [`Rule`].
+    pub fn step(&mut self) {
+        self.tick();
+    }
+
+    /// Advances one generation like [`step`](Self::step) and returns the new
+    /// [`population`](Self::population), counted during the step.
+    pub fn tick(&mut self) -> usize {
+        match self.observer.0 {
+            Some(_) => self.tick_observed(&mut ()),
+            None => self.tick_unobserved(),
+        }
+    }
+
+    /// Has `observer` told about each step the board takes from now on
+    /// through [`step`](Self::step), [`tick`](Self::tick) or
+    /// [`step_with_fn`](Self::step_with_fn), replacing any observer it had.
+    pub fn set_observer(&mut self, observer: Box<dyn SimObserver + Send>) {
+        self.observer.0 = Some(observer);
+    }
+
+    /// Stops observing the board, handing back the observer it had.
+    pub fn take_observer(&mut self) -> Option<Box<dyn SimObserver + Send>> {
+        self.observer.0.take()
+    }
+
+    /// Advances one generation like [`tick`](Self::tick), telling `observer`
+    /// about it as well as the board's own observer, if it has one. Births
+    /// and deaths are counted as the next generation is written, so an
+    /// observed step goes a row at a time even on storage that could step
+    /// itself faster.
+    pub fn tick_observed(&mut self, observer: &mut dyn SimObserver) -> usize {
+        let stepped = self.observed(observer, |game| {
+            let stepped = match game.kernel {
+                Some(_) => game.advance_weighted(),
+                None => {
+                    let rule = game.rule;
+                    game.advance(|cell, neighbors| rule.next(cell, neighbors))
+                }
+            };
+            game.count_activity();
+            stepped
+        });
+        stepped.population
+    }
+
+    /// Takes the step `step` makes, telling `observer` and the board's own
+    /// observer, if it has one, when it starts and what it did.
+    fn observed(
+        &mut self,
+        observer: &mut dyn SimObserver,
+        step: impl FnOnce(&mut Self) -> Stepped,
+    ) -> Stepped {
+        let mut own = self.observer.0.take();
+        let generation = self.generation;
+        observer.on_step_start(generation);
+        if let Some(own) = &mut own {
+            own.on_step_start(generation);
+        }
+        let started = Instant::now();
+        let stepped = step(self);
+        let duration = started.elapsed();
+        let Stepped { births, deaths, .. } = stepped;
+        observer.on_step_end(self.generation, duration, births, deaths);
+        if let Some(own) = &mut own {
+            own.on_step_end(self.generation, duration, births, deaths);
+        }
+        self.observer.0 = own;
+        stepped
+    }
+
+    fn tick_unobserved(&mut self) -> usize {
+        if self.kernel.is_some() {
+            let population = self.advance_weighted().population;
+            self.count_activity();
+            return population;
+        }
+        let rule = self.rule;
+        // Storage stepping itself knows nothing of masks and tags.
+        #[cfg(feature = "tags")]
+        let tagged = self.tags.is_some();
+        #[cfg(not(feature = "tags"))]
+        let tagged = false;
+        if self.mask.is_none() && !tagged {
+            if let Some(population) = self.grid.step_rule(rule, self.edges) {
+                self.generation += 1;
+                self.count_activity();
+                return population;
+            }
+        }
+        let population = self
+            .advance(|cell, neighbors| rule.next(cell, neighbors))
+            .population;
+        self.count_activity();
+        population
+    }
+
+    /// Advances one generation using `f` as the transition rule instead of
+    /// the board's. `f` receives the current state of a cell and its live neighbor
+    /// count, and returns the cell's next state. The board's observer, if it
+    /// has one, is told about the step.
+    pub fn step_with_fn(&mut self, f: impl Fn(bool, usize) -> bool) {
+        let step = |game: &mut Self| {
+            let stepped = game.advance(f);
+            game.count_activity();
+            stepped
+        };
+        match self.observer.0 {
+            Some(_) => self.observed(&mut (), step),
+            None => step(self),
+        };
+    }
+
+    /// Steps with `f`, keeping cells forced by the mask at their state.
+    fn advance(&mut self, f: impl Fn(bool, usize) -> bool) -> Stepped {
+        let mut counts = std::mem::take(&mut self.counts);
+        self.neighbor_counts_into(&mut counts);
+        let population = self.advance_by(|i, cell| f(cell, counts[i] as usize));
+        self.counts = counts;
+        population
+    }
+
+    /// Steps with `next`, given each cell's index and state, keeping cells
+    /// forced by the mask at their state.
+    pub(crate) fn advance_by(&mut self, next_state: impl Fn(usize, bool) -> bool) -> Stepped {
+        let (w, h) = self.size;
+        let mut row = vec![false; w];
+        let mut next = vec![false; w];
+        #[cfg(feature = "tags")]
+        let mut next_cells = self
+            .tags
+            .as_ref()
+            .map(|_| Vec::with_capacity(self.cell_count()));
+        let mut stepped = Stepped::default();
+        for y in 0..h {
+            self.grid.read_row(y, &mut row);
+            for (x, alive) in next.iter_mut().enumerate() {
+                let forced = self.mask.as_ref().and_then(|mask| mask.get(x, y).forced());
+                *alive = forced.unwrap_or_else(|| next_state(y * w + x, row[x]));
+                stepped.population += *alive as usize;
+                stepped.births += (*alive && !row[x]) as usize;
+                stepped.deaths += (row[x] && !*alive) as usize;
+            }
+            #[cfg(feature = "tags")]
+            if let Some(cells) = &mut next_cells {
+                cells.extend_from_slice(&next);
+            }
+            self.grid.set_next_row(y, &next);
+        }
+        #[cfg(feature = "tags")]
+        {
+            self.tags = next_cells.and_then(|next| self.next_tags(&next));
+        }
+        self.grid.swap_buffers();
+        self.generation += 1;
+        stepped
+    }
+}
+
+/// What a step left on the board, counted as it was taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Stepped {
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+/// The [`GameOfLife::checksum`] of a board of `size` packed into `words`.
+/// Each step of FNV-1a maps the hash so far one to one, so a change to any
+/// one word always shows in the result.
+pub fn checksum_words(size: (usize, usize), words: &[u64]) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for word in [size.0 as u64, size.1 as u64].iter().chain(words) {
+        hash = (hash ^ word).wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The splitmix64 finalizer, which scatters nearby numbers far apart.
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[test]
+fn test_count_neighbors() {
+    let mut game = GameOfLife::new_empty((3, 3));
+    game.set(GridPos(0, 0), true);
+    game.set(GridPos(1, 0), true);
+    game.set(GridPos(2, 0), true);
+
+    game.set(GridPos(0, 1), true);
+    game.set(GridPos(1, 1), true);
+    game.set(GridPos(2, 1), true);
+    let neighbors = GameOfLife::count_neighbors(&game, GridPos(1, 1));
+    assert_eq!(neighbors, 5);
+}
+
+#[test]
+fn test_inspect_foretells_the_next_generation() {
+    let mut game = GameOfLife::new_empty((5, 5));
+    for x in 1..4 {
+        game.set(GridPos(x, 2), true);
+    }
+    let info = |game: &GameOfLife, x, y| {
+        let info = game.inspect(GridPos(x, y));
+        (info.state, info.neighbors, info.next)
+    };
+    // A blinker's middle survives, its ends die and the cells beside its
+    // middle are born.
+    assert_eq!(info(&game, 2, 2), (true, 2, true));
+    assert_eq!(info(&game, 1, 2), (true, 1, false));
+    assert_eq!(info(&game, 2, 1), (false, 3, true));
+    assert_eq!(info(&game, 0, 1), (false, 1, false));
+    let next = game.clone();
+    game.step();
+    assert!((0..25).all(|i| {
+        let pos = game.coords(i);
+        next.inspect(pos).next == game.get(pos)
+    }));
+
+    // A wall stays dead whatever its neighbors.
+    let mut walled = next.clone();
+    let mut mask = Mask::new(walled.size());
+    mask.set(2, 1, MaskCell::ForcedDead);
+    walled.set_mask(Some(mask));
+    assert_eq!(info(&walled, 2, 1), (false, 3, false));
+}
+
+#[test]
+fn test_merge_modes_over_overlapping_rectangles() {
+    // A 4x4 board with its left half alive, and a 4x2 board with its top row
+    // alive merged on at (2, 1), hanging off the right edge.
+    let (mut base, mut other) = (GameOfLife::new_empty((4, 4)), GameOfLife::new_empty((4, 2)));
+    for y in 0..4 {
+        base.set(GridPos(0, y), true);
+        base.set(GridPos(1, y), true);
+    }
+    for x in 0..4 {
+        other.set(GridPos(x, 0), true);
+    }
+    let merged = |mode| {
+        let mut game = base.clone();
+        game.merge(&other, GridPos(2, 1), mode);
+        (0..4)
+            .map(|y| {
+                let row = (0..4).map(|x| if game.get(GridPos(x, y)) { '#' } else { '.' });
+                row.collect::<String>()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(merged(MergeMode::Or), ["##..", "####", "##..", "##.."]);
+    assert_eq!(merged(MergeMode::And), ["##..", "##..", "##..", "##.."]);
+
+    // Merged onto the live half, the modes part ways.
+    let merged = |mode| {
+        let mut game = base.clone();
+        game.merge(&other, GridPos(1, 2), mode);
+        (0..4)
+            .map(|y| {
+                (0..4)
+                    .map(|x| game.get(GridPos(x, y)) as u8)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let row = |cells: [u8; 4]| cells.to_vec();
+    assert_eq!(merged(MergeMode::Or)[2], row([1, 1, 1, 1]));
+    assert_eq!(merged(MergeMode::And)[2], row([1, 1, 0, 0]));
+    assert_eq!(merged(MergeMode::Xor)[2], row([1, 0, 1, 1]));
+    // Replace kills what lies under the dead row beneath, too.
+    assert_eq!(merged(MergeMode::Replace)[3], row([1, 0, 0, 0]));
+    assert_eq!(merged(MergeMode::And)[3], row([1, 0, 0, 0]));
+    assert_eq!(merged(MergeMode::Or)[3], row([1, 1, 0, 0]));
+    // Rows the other board doesn't reach are untouched.
+    assert!(merged(MergeMode::Replace)[..2]
+        .iter()
+        .all(|row| *row == [1, 1, 0, 0]));
+}
+
+#[test]
+fn test_downsample_keeps_small_structures() {
+    let mut game = GameOfLife::new_empty((8, 6));
+    game.set(GridPos(7, 5), true);
+    let map = game.downsample((4, 2));
+    assert_eq!(map.iter().filter(|&&c| c).count(), 1);
+    assert!(map[3 + 4]);
+}
+
+#[test]
+fn test_step_with_fn() {
+    let mut game = GameOfLife::new_empty((5, 5));
+    game.set(GridPos(2, 2), true);
+    game.set(GridPos(3, 2), true);
+    // Seeds (B2/S): every live cell dies, cells with exactly two neighbors are born.
+    game.step_with_fn(|_, neighbors| neighbors == 2);
+    let live = (0..5)
+        .flat_map(|y| (0..5).map(move |x| (x, y)))
+        .filter(|&(x, y)| game.get(GridPos(x, y)))
+        .collect::<Vec<_>>();
+    assert_eq!(live, vec![(2, 1), (3, 1), (2, 3), (3, 3)]);
+}
+
+#[test]
+fn test_resize_keeps_overlap() {
+    let mut game = GameOfLife::new((4, 3));
+    let before = game.surface().to_vec();
+    game.resize((6, 2));
+    assert_eq!(game.size(), (6, 2));
+    for y in 0..2 {
+        for x in 0..6 {
+            assert_eq!(game.get(GridPos(x, y)), x < 4 && before[x + y * 4]);
+        }
+    }
+}
+
+#[test]
+fn test_index_coords_roundtrip() {
+    let game = GameOfLife::new((7, 3));
+    assert_eq!(game.cell_count(), 21);
+    assert_eq!(game.index(GridPos(2, 1)), 9);
+    for i in 0..game.cell_count() {
+        let GridPos(x, y) = game.coords(i);
+        assert!(x < 7 && y < 3);
+        assert_eq!(game.index(GridPos(x, y)), i);
+    }
+}
+
+#[test]
+fn test_mask_wall_acts_like_edge() {
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let mut walled = GameOfLife::new_empty((20, 10));
+    let mut wall = Mask::new((20, 10));
+    for y in 0..10 {
+        for x in 10..20 {
+            wall.set(x, y, MaskCell::ForcedDead);
+        }
+    }
+    walled.set_mask(Some(wall));
+    let mut bounded = GameOfLife::new_empty((10, 10));
+    for (x, y) in glider {
+        walled.set(GridPos(x + 4, y + 1), true);
+        bounded.set(GridPos(x + 4, y + 1), true);
+    }
+    // Setting a masked cell has no effect.
+    walled.set(GridPos(15, 5), true);
+    assert!(!walled.get(GridPos(15, 5)));
+
+    for _ in 0..40 {
+        walled.step();
+        bounded.step();
+        for y in 0..10 {
+            for x in 0..20 {
+                assert_eq!(
+                    walled.get(GridPos(x, y)),
+                    x < 10 && bounded.get(GridPos(x, y))
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_zero_dimensions_are_raised_to_one() {
+    let mut game = GameOfLife::new((0, 3));
+    assert_eq!(game.size(), (1, 3));
+    game.resize((4, 0));
+    assert_eq!(game.size(), (4, 1));
+    assert_eq!(
+        game.count_neighbors(GridPos(3, 0)),
+        game.get(GridPos(2, 0)) as usize
+    );
+}
+
+#[test]
+fn test_count_neighbors_on_thin_boards() {
+    for size in [(1, 1), (1, 5), (5, 1), (2, 4), (4, 2)] {
+        for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+            let mut game = GameOfLife::new(size);
+            game.set_edge_mode(edges);
+            game.fill(1.0, 0);
+            for i in 0..game.cell_count() {
+                let GridPos(x, y) = game.coords(i);
+                // With every cell live, the count is the number of offsets
+                // that stay on the board.
+                let expected = match edges {
+                    EdgeMode::Toroidal | EdgeMode::Mirror => 8,
+                    EdgeMode::Bounded => NEIGHBORS
+                        .iter()
+                        .filter(|&&(dx, dy)| {
+                            x.checked_add_signed(dx).is_some_and(|i| i < size.0)
+                                && y.checked_add_signed(dy).is_some_and(|j| j < size.1)
+                        })
+                        .count(),
+                };
+                assert_eq!(
+                    game.count_neighbors(GridPos(x, y)),
+                    expected,
+                    "{size:?} {edges:?} ({x}, {y})"
+                );
+            }
+
+            // A single live cell, counted by hand: how many of the offsets
+            // around each cell land on it.
+            game.fill(0.0, 0);
+            game.set(GridPos(0, 0), true);
+            for i in 0..game.cell_count() {
+                let GridPos(x, y) = game.coords(i);
+                let mut expected = 0;
+                for dy in -1..=1isize {
+                    for dx in -1..=1isize {
+                        let (i, j) = (x as isize + dx, y as isize + dy);
+                        let (i, j) = match edges {
+                            EdgeMode::Bounded => (i, j),
+                            EdgeMode::Toroidal => {
+                                (i.rem_euclid(size.0 as isize), j.rem_euclid(size.1 as isize))
+                            }
+                            EdgeMode::Mirror => {
+                                (reflect(i, size.0) as isize, reflect(j, size.1) as isize)
+                            }
+                        };
+                        if (dx, dy) != (0, 0) && (i, j) == (0, 0) {
+                            expected += 1;
+                        }
+                    }
+                }
+                assert_eq!(
+                    game.count_neighbors(GridPos(x, y)),
+                    expected,
+                    "{size:?} {edges:?} ({x}, {y})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tick_returns_population() {
+    let mut game = GameOfLife::new((12, 9));
+    let mut reference = GameOfLife::new((12, 9));
+    reference.grid = game.grid.clone();
+    for _ in 0..10 {
+        reference.step();
+        assert_eq!(game.tick(), reference.population());
+        assert_eq!(game.surface(), reference.surface());
+    }
+}
+
+#[test]
+fn test_neighbors_respect_edge_mode() {
+    let mut game = GameOfLife::new((4, 3));
+    let mut corner = game
+        .neighbors(GridPos(0, 0))
+        .map(<(usize, usize)>::from)
+        .collect::<Vec<_>>();
+    corner.sort_unstable();
+    assert_eq!(corner, vec![(0, 1), (1, 0), (1, 1)]);
+    assert_eq!(game.neighbors(GridPos(1, 1)).count(), 8);
+
+    game.set_edge_mode(EdgeMode::Toroidal);
+    let mut corner = game
+        .neighbors(GridPos(0, 0))
+        .map(<(usize, usize)>::from)
+        .collect::<Vec<_>>();
+    corner.sort_unstable();
+    assert_eq!(
+        corner,
+        vec![
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (3, 0),
+            (3, 1),
+            (3, 2)
+        ]
+    );
+}
+
+#[test]
+fn test_property_empty_board_stays_empty() {
+    crate::testing::check(|rng| {
+        let mut game = crate::testing::game(rng);
+        if game.rule().next(false, 0) {
+            // B0 rules fill empty space by definition.
+            return;
+        }
+        game.fill(0.0, 0);
+        game.step();
+        assert_eq!(game.population(), 0);
+    });
+}
+
+#[test]
+fn test_property_population_change_is_births_minus_deaths() {
+    crate::testing::check(|rng| {
+        let mut game = crate::testing::game(rng);
+        let before = game.surface().to_vec();
+        let population = game.population();
+        let after = game.tick();
+        let births = (0..before.len())
+            .filter(|&i| !before[i] && game.surface()[i])
+            .count();
+        let deaths = (0..before.len())
+            .filter(|&i| before[i] && !game.surface()[i])
+            .count();
+        assert_eq!(after + deaths, population + births);
+        assert_eq!(after, game.population());
+    });
+}
+
+#[test]
+fn test_property_insert_then_extract_round_trips() {
+    use rand::Rng;
+    crate::testing::check(|rng| {
+        let mut game = crate::testing::game(rng);
+        let (w, h) = game.size();
+        let (x, y) = (rng.gen_range(0..w), rng.gen_range(0..h));
+        let rect = Rect {
+            x,
+            y,
+            width: rng.gen_range(1..=w - x),
+            height: rng.gen_range(1..=h - y),
+        };
+        let pattern = crate::testing::game(rng).extract(Rect { x: 0, y: 0, ..rect });
+        game.insert_pattern(&pattern, GridPos(x, y));
+        assert_eq!(game.extract(rect), pattern);
+    });
+}
+
+#[test]
+fn test_property_translate_on_torus_is_invertible() {
+    use rand::Rng;
+    crate::testing::check(|rng| {
+        let mut game = crate::testing::game(rng);
+        game.set_edge_mode(EdgeMode::Toroidal);
+        let before = game.surface().to_vec();
+        let (dx, dy) = (rng.gen_range(-30..=30), rng.gen_range(-30..=30));
+        game.translate(dx, dy);
+        game.translate(-dx, -dy);
+        assert_eq!(game.surface(), before);
+    });
+}
+
+#[test]
+fn test_tile_pattern_clips_at_edges() {
+    let mut game = GameOfLife::new((12, 6));
+    game.tile_pattern("blinker", (5, 4), &[]).unwrap();
+    // Copies at x = 0, 5 and 10 in rows 0 and 4; the last one loses a cell.
+    assert_eq!(game.population(), 2 * (3 + 3 + 2));
+    assert!(game.get(GridPos(10, 4)) && game.get(GridPos(11, 4)) && !game.get(GridPos(4, 0)));
+    // Spacing tighter than the pattern still keeps the copies apart.
+    game.tile_pattern("block", (0, 0), &[]).unwrap();
+    assert_eq!(game.population(), game.cell_count());
+    assert!(game.tile_pattern("nope", (4, 4), &[]).is_err());
+}
+
+#[test]
+fn test_glider_guns_fire_at_each_other() {
+    let mut game = GameOfLife::new((100, 100));
+    game.init_with_glider_guns(4, &[]).unwrap();
+    assert_eq!(game.edge_mode(), EdgeMode::Toroidal);
+    assert_eq!(game.population(), 4 * 36);
+    // Gliders leave each gun heading for the middle, which starts empty.
+    let middle = Rect {
+        x: 40,
+        y: 40,
+        width: 20,
+        height: 20,
+    };
+    assert_eq!(game.extract(middle).cells().len(), 0);
+    for _ in 0..150 {
+        game.step();
+    }
+    assert!(!game.extract(middle).cells().is_empty());
+
+    assert!(game.init_with_glider_guns(9, &[]).is_err());
+}
+
+#[test]
+fn test_fill_gradient() {
+    let mut game = GameOfLife::new((50, 40));
+    game.fill_gradient(1.0, 0.0, Axis::X, 7);
+    assert!((0..40).all(|y| game.get(GridPos(0, y)) && !game.get(GridPos(49, y))));
+    let column = |game: &GameOfLife, x| (0..40).filter(|&y| game.get(GridPos(x, y))).count();
+    assert!(column(&game, 10) > column(&game, 40));
+
+    let mut rows = GameOfLife::new((40, 50));
+    rows.fill_gradient(0.0, 1.0, Axis::Y, 7);
+    assert!((0..40).all(|x| !rows.get(GridPos(x, 0)) && rows.get(GridPos(x, 49))));
+
+    // The same seed gives the same board.
+    let mut again = GameOfLife::new((50, 40));
+    again.fill_gradient(1.0, 0.0, Axis::X, 7);
+    assert_eq!(again.surface(), game.surface());
+}
+
+#[test]
+fn test_fill_maze() {
+    let mut game = GameOfLife::new((31, 21));
+    game.fill_maze(3);
+    assert!(game.population() > 31 * 21 / 4);
+    // Every corridor is joined to every other.
+    let start = (0..game.cell_count())
+        .find(|&i| !game.surface()[i])
+        .unwrap();
+    let mut seen = vec![false; game.cell_count()];
+    let mut stack = vec![start];
+    seen[start] = true;
+    while let Some(i) = stack.pop() {
+        let GridPos(x, y) = game.coords(i);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                continue;
+            };
+            if nx < 31
+                && ny < 21
+                && !game.get(GridPos(nx, ny))
+                && !seen[game.index(GridPos(nx, ny))]
+            {
+                seen[game.index(GridPos(nx, ny))] = true;
+                stack.push(game.index(GridPos(nx, ny)));
+            }
+        }
+    }
+    assert!((0..game.cell_count()).all(|i| game.surface()[i] || seen[i]));
+
+    // The same seed gives the same maze, over whatever was there.
+    let mut again = GameOfLife::new((31, 21));
+    again.fill_maze(3);
+    assert_eq!(again.surface(), game.surface());
+    again.fill_maze(4);
+    assert_ne!(again.surface(), game.surface());
+}
+
+#[test]
+fn test_seed_follows_the_last_fill() {
+    assert_eq!(GameOfLife::new_empty((8, 8)).seed(), None);
+    // A board filled from the system's entropy can be filled the same again.
+    let game = GameOfLife::new((40, 30));
+    let mut again = GameOfLife::new_empty((40, 30));
+    again.fill(0.7, game.seed().unwrap());
+    assert_eq!(again.surface(), game.surface());
+    again.fill_maze(9);
+    assert_eq!(again.seed(), Some(9));
+    again.set(GridPos(0, 0), true);
+    assert_eq!(again.seed(), Some(9));
+    assert_eq!(crate::metadata::Metadata::of(&again).seed, Some(9));
+}
+
+#[test]
+fn test_fill_rect_is_stable_for_a_seed() {
+    let mut game = GameOfLife::new_empty((20, 10));
+    game.set(GridPos(0, 0), true);
+    let rect = Rect {
+        x: 4,
+        y: 2,
+        width: 30,
+        height: 5,
+    };
+    game.fill_rect(rect, 0.3, 8);
+    let sparse = game.surface().to_vec();
+    // Only the part of the rect on the board is filled.
+    assert!(game.get(GridPos(0, 0)));
+    assert!((0..game.cell_count()).all(|i| {
+        let GridPos(x, y) = game.coords(i);
+        (x, y) == (0, 0) || !sparse[i] || (x >= 4 && (2..7).contains(&y))
+    }));
+    assert!(game.population() > 1);
+
+    // Denser from the same seed only adds cells, and going back undoes it.
+    game.fill_rect(rect, 0.6, 8);
+    let dense = game.surface().to_vec();
+    assert!(sparse
+        .iter()
+        .zip(&dense)
+        .all(|(&sparse, &dense)| !sparse || dense));
+    assert_ne!(sparse, dense);
+    game.fill_rect(rect, 0.3, 8);
+    assert_eq!(game.surface(), sparse);
+}
+
+#[test]
+fn test_sprinkle_only_adds() {
+    let mut game = GameOfLife::new_empty((30, 20));
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(2, 2));
+    let before = game.surface().to_vec();
+    game.sprinkle(0.1, 3);
+    let after = game.surface().to_vec();
+    assert!(before
+        .iter()
+        .zip(&after)
+        .all(|(&before, &after)| !before || after));
+    let added = game.population() - 5;
+    assert!((20..100).contains(&added), "{added}");
+    // The same seed sprinkles the same cells.
+    let mut again = GameOfLife::new_empty((30, 20));
+    again.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(2, 2));
+    again.sprinkle(0.1, 3);
+    assert_eq!(again.surface(), after);
+    game.sprinkle(0.0, 4);
+    assert_eq!(game.surface(), after);
+}
+
+#[test]
+fn test_fill_exact() {
+    let mut game = GameOfLife::new((30, 20));
+    game.fill_exact(77, 5).unwrap();
+    assert_eq!(game.population(), 77);
+    let mut again = GameOfLife::new((30, 20));
+    again.fill_exact(77, 5).unwrap();
+    assert_eq!(again.surface(), game.surface());
+    again.fill_exact(77, 6).unwrap();
+    assert_eq!(again.population(), 77);
+    assert_ne!(again.surface(), game.surface());
+    game.fill_exact(600, 5).unwrap();
+    assert_eq!(game.population(), 600);
+    assert!(game.fill_exact(601, 5).is_err());
+
+    // Forced cells are left be and leave less room.
+    let border = Rect {
+        x: 0,
+        y: 0,
+        width: 30,
+        height: 20,
+    };
+    let wall = Mask::new((30, 20)).rect_border(border, MaskCell::ForcedDead);
+    game.set_mask(Some(wall));
+    game.fill_exact(504, 5).unwrap();
+    assert_eq!(game.population(), 504);
+    assert!(game.fill_exact(505, 5).is_err());
+}
+
+#[test]
+fn test_only_stepping_moves_the_generation() {
+    let mut game = GameOfLife::new((12, 12));
+    for _ in 0..5 {
+        game.step();
+    }
+    game.tick();
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(2, 2));
+    game.tile_pattern("blinker", (4, 4), &[]).unwrap();
+    game.fill(0.4, 1);
+    game.translate(1, 1);
+    game.resize((10, 14));
+    game.set(GridPos(0, 0), true);
+    assert_eq!(game.generation(), 6);
+    game.reset_generation();
+    assert_eq!(game.generation(), 0);
+    game.step();
+    assert_eq!(game.generation(), 1);
+}
+
+#[test]
+fn test_render_rgba() {
+    let mut game = GameOfLife::new_empty((3, 2));
+    game.set(GridPos(1, 0), true);
+    game.set(GridPos(2, 1), true);
+    let (live, dead) = ([255, 255, 255, 255], [0, 0, 0, 255]);
+    let mut buf = vec![7; 24];
+    game.render_rgba(&mut buf, live, dead).unwrap();
+    let pixels: Vec<_> = buf.chunks(4).map(|pixel| pixel == live).collect();
+    assert_eq!(pixels, [false, true, false, false, false, true]);
+    assert!(buf.chunks(4).all(|pixel| pixel == live || pixel == dead));
+
+    let err = game.render_rgba(&mut [0; 20], live, dead).unwrap_err();
+    assert!(err.contains("24 bytes, got 20"), "{err}");
+}
+
+#[test]
+fn test_new_empty() {
+    let game = GameOfLife::new_empty((4, 0));
+    assert_eq!(game.size(), (4, 1));
+    assert_eq!(game.population(), 0);
+    assert_eq!(game.generation(), 0);
+}
+
+#[test]
+fn test_packed_storage_steps_alike() {
+    use crate::PackedWords;
+    // Wider than a word, on both kinds of edge and with a longer reach.
+    for (edges, rule) in [
+        (EdgeMode::Bounded, Rule::default()),
+        (EdgeMode::Toroidal, Rule::default()),
+        (
+            EdgeMode::Mirror,
+            Rule::parse("R2,C0,M0,S2..3,B3,NN").unwrap(),
+        ),
+        (
+            EdgeMode::Bounded,
+            Rule::parse("R2,C0,M0,S2..3,5,B3,NM").unwrap(),
+        ),
+    ] {
+        let mut dense = GameOfLife::new_empty((70, 9));
+        dense.fill(0.4, 11);
+        dense.set_edge_mode(edges);
+        dense.set_rule(rule);
+        let mut packed = dense.to_storage::<PackedWords>();
+        assert_eq!(packed.fingerprint(), dense.fingerprint());
+        for _ in 0..8 {
+            assert_eq!(packed.tick(), dense.tick());
+        }
+        assert_eq!(packed.fingerprint(), dense.fingerprint());
+        assert_eq!(packed.to_storage().surface(), dense.surface());
+
+        // A packed board can be built up cell by cell too.
+        let mut built = GameOfLife::<PackedWords>::empty((70, 9));
+        for i in 0..dense.cell_count() {
+            let pos = dense.coords(i);
+            built.set(pos, dense.get(pos));
+        }
+        assert_eq!(built.population(), dense.population());
+    }
+}
+
+#[test]
+fn test_surface_mut() {
+    let mut game = GameOfLife::new_empty((4, 3));
+    game.surface_mut().fill(true);
+    assert_eq!(game.population(), 12);
+    let i = game.index(GridPos(2, 1));
+    game.surface_mut()[i] = false;
+    assert!(!game.get(GridPos(2, 1)) && game.get(GridPos(3, 1)));
+    assert_eq!(game.surface_mut().len(), game.cell_count());
+}
+
+#[test]
+fn test_property_neighbor_counts_match_count_neighbors() {
+    crate::testing::check(|rng| {
+        let mut game = crate::testing::game(rng);
+        let mut counts = vec![7; 3];
+        for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+            game.set_edge_mode(edges);
+            game.neighbor_counts_into(&mut counts);
+            assert_eq!(counts.len(), game.cell_count());
+            for (i, &count) in counts.iter().enumerate() {
+                let GridPos(x, y) = game.coords(i);
+                assert_eq!(
+                    count as usize,
+                    game.count_neighbors(GridPos(x, y)),
+                    "{edges:?} ({x}, {y})"
+                );
+            }
+            assert_eq!(game.neighbor_counts(), counts);
+            assert_eq!(game.neighbor_mismatches(), []);
+        }
+    });
+}
+
+#[test]
+fn test_placement_clips_or_wraps_at_every_edge() {
+    let block = Pattern::named("block").unwrap();
+    let place = |edges, origin| {
+        let mut game = GameOfLife::new_empty((6, 5));
+        game.set_edge_mode(edges);
+        let placed = game.insert_pattern(&block, origin);
+        let mut live = (0..game.cell_count())
+            .map(|i| game.coords(i))
+            .filter(|&pos| game.get(pos))
+            .map(<(usize, usize)>::from)
+            .collect::<Vec<_>>();
+        live.sort_by_key(|&(x, y)| (y, x));
+        (placed.placed, placed.clipped, live)
+    };
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal] {
+        // Anywhere it fits, down to the corners, it goes in whole.
+        for origin in [GridPos(0, 0), GridPos(4, 0), GridPos(0, 3), GridPos(4, 3)] {
+            assert_eq!(place(edges, origin).1, 0, "{edges:?} at {origin:?}");
+        }
+    }
+    let bounded = |origin| place(EdgeMode::Bounded, origin);
+    let toroidal = |origin| place(EdgeMode::Toroidal, origin);
+    // Over the right edge.
+    assert_eq!(bounded(GridPos(5, 1)), (2, 2, vec![(5, 1), (5, 2)]));
+    assert_eq!(
+        toroidal(GridPos(5, 1)),
+        (4, 0, vec![(0, 1), (5, 1), (0, 2), (5, 2)])
+    );
+    // Over the bottom edge.
+    assert_eq!(bounded(GridPos(2, 4)), (2, 2, vec![(2, 4), (3, 4)]));
+    assert_eq!(
+        toroidal(GridPos(2, 4)),
+        (4, 0, vec![(2, 0), (3, 0), (2, 4), (3, 4)])
+    );
+    // Over the bottom-right corner.
+    assert_eq!(bounded(GridPos(5, 4)), (1, 3, vec![(5, 4)]));
+    assert_eq!(
+        toroidal(GridPos(5, 4)),
+        (4, 0, vec![(0, 0), (5, 0), (0, 4), (5, 4)])
+    );
+    // Past the board altogether.
+    assert_eq!(bounded(GridPos(9, 2)), (0, 4, vec![]));
+    assert_eq!(
+        toroidal(GridPos(9, 2)),
+        (4, 0, vec![(3, 2), (4, 2), (3, 3), (4, 3)])
+    );
+
+    // The box is cleared where the pattern lands, wrapped or not.
+    let glider = Pattern::named("glider").unwrap();
+    for (edges, population, clipped) in [(EdgeMode::Bounded, 29, 5), (EdgeMode::Toroidal, 26, 0)] {
+        let mut game = GameOfLife::new_empty((6, 5));
+        game.set_edge_mode(edges);
+        game.fill(1.0, 0);
+        assert_eq!(game.insert_pattern(&glider, GridPos(5, 4)).clipped, clipped);
+        assert_eq!(game.population(), population, "{edges:?}");
+    }
+
+    let placed = GameOfLife::new_empty((6, 5)).insert_pattern(&block, GridPos(5, 4));
+    let warning = "block clipped: 3 cells off-grid — try a larger terminal";
+    assert_eq!(placed.warning("block").as_deref(), Some(warning));
+    assert_eq!(
+        PlacementResult {
+            placed: 4,
+            clipped: 0
+        }
+        .warning("block"),
+        None
+    );
+}
+
+#[test]
+fn test_from_ascii_sizes_to_the_art() {
+    let mut game = GameOfLife::from_ascii("...\n###\n...");
+    assert_eq!((game.size(), game.population()), ((3, 3), 3));
+    game.step();
+    assert_eq!(
+        game.surface(),
+        GameOfLife::from_ascii(".O.\n.O.\n.O.").surface()
+    );
+    // Stamped into a bigger board, the art's dead cells clear what's under.
+    let mut big = GameOfLife::new_empty((5, 5));
+    big.fill(1.0, 0);
+    big.insert_pattern(&Pattern::from_ascii("...\n.#.\n..."), GridPos(1, 1));
+    assert_eq!(big.population(), 17);
+    assert_eq!(GameOfLife::from_ascii("").size(), (1, 1));
+}
+
+#[test]
+fn test_get_wrapped() {
+    let mut game = GameOfLife::new_empty((4, 3));
+    game.set_edge_mode(EdgeMode::Bounded);
+    game.set(GridPos(3, 0), true);
+    assert!(game.get_wrapped(3, 0) && game.get_wrapped(-1, 0) && game.get_wrapped(-1, 3));
+    assert!(game.get_wrapped(7, -6) && game.get_wrapped(isize::MIN + 3, 0));
+    assert!(!game.get_wrapped(0, 0) && !game.get_wrapped(4, 0));
+}
+
+#[test]
+fn test_step_until() {
+    let mut game = GameOfLife::new_empty((20, 20));
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(1, 1));
+    let corner = Rect {
+        x: 10,
+        y: 10,
+        width: 30,
+        height: 30,
+    };
+    assert!(!game.any_live_in(corner));
+    // A glider moves a cell down and across every four generations.
+    let reached = game.step_until(100, |game| game.any_live_in(corner));
+    assert_eq!(reached, Some(game.generation()));
+    assert!(game.any_live_in(corner) && (24..=32).contains(&game.generation()));
+    // Already true, it doesn't step at all.
+    assert_eq!(game.step_until(0, |game| game.population() == 5), reached);
+
+    let mut blinker = GameOfLife::from_ascii(".....\n.###.\n.....");
+    assert_eq!(blinker.step_until(10, |game| game.population() < 3), None);
+    assert_eq!(blinker.generation(), 10);
+    let mut dying = GameOfLife::from_ascii("##");
+    assert_eq!(dying.step_until(10, |game| game.population() == 0), Some(1));
+}
+
+#[test]
+fn test_checksum_changes_with_any_cell() {
+    let mut game = GameOfLife::new_empty((70, 3));
+    game.fill(0.5, 3);
+    let checksum = game.checksum();
+    assert_eq!(game.clone().checksum(), checksum);
+    let mut words = Vec::new();
+    game.pack_into(&mut words);
+    assert_eq!(words.len(), 4);
+    assert_eq!(words[0] & 1, game.get(GridPos(0, 0)) as u64);
+    assert_eq!(words[1] >> 6 & 1, game.get(GridPos(0, 1)) as u64);
+    for i in 0..game.cell_count() {
+        let GridPos(x, y) = game.coords(i);
+        let mut changed = game.clone();
+        changed.set(GridPos(x, y), !game.get(GridPos(x, y)));
+        assert_ne!(changed.checksum(), checksum, "flipping ({x}, {y})");
+    }
+    // The same cells on a board of another shape are another board.
+    let (tall, wide) = (GameOfLife::new_empty((2, 8)), GameOfLife::new_empty((8, 2)));
+    assert_ne!(tall.checksum(), wide.checksum());
+    let packed = game.to_storage::<crate::PackedWords>();
+    assert_eq!(packed.checksum(), checksum);
+}
+
+#[test]
+fn test_mirror_edges_keep_symmetry() {
+    assert_eq!(
+        [-3, -2, -1, 0, 4, 5, 9].map(|i| reflect(i, 5)),
+        [2, 1, 0, 0, 4, 4, 0]
+    );
+    // A board symmetric about its middle column.
+    let mut game = GameOfLife::new_empty((12, 10));
+    game.fill(0.4, 5);
+    for y in 0..10 {
+        for x in 0..6 {
+            game.set(GridPos(11 - x, y), game.get(GridPos(x, y)));
+        }
+    }
+    // Under mirrored edges the board runs as a quarter of one four times
+    // the size, made of it and its reflections, would on a torus.
+    let unfold = |game: &GameOfLife| {
+        let (w, h) = game.size();
+        let mut whole = GameOfLife::new_empty((2 * w, 2 * h));
+        whole.set_edge_mode(EdgeMode::Toroidal);
+        for i in 0..whole.cell_count() {
+            let GridPos(x, y) = whole.coords(i);
+            let mirrored = GridPos(reflect(x as isize, w), reflect(y as isize, h));
+            whole.set(GridPos(x, y), game.get(mirrored));
+        }
+        whole
+    };
+    let mut whole = unfold(&game);
+    let bounded = game.clone();
+    game.set_edge_mode(EdgeMode::Mirror);
+    let (mut both, mut bounded_matches) = (true, true);
+    let mut once_bounded = bounded.clone();
+    once_bounded.step();
+    for _ in 0..100 {
+        game.step();
+        whole.step();
+        let symmetric = (0..game.cell_count()).all(|i| {
+            let GridPos(x, y) = game.coords(i);
+            game.get(GridPos(x, y)) == game.get(GridPos(11 - x, y))
+        });
+        assert!(symmetric, "generation {}", game.generation());
+        both &= unfold(&game).surface() == whole.surface();
+        if game.generation() == 1 {
+            bounded_matches = game.surface() == once_bounded.surface();
+        }
+    }
+    assert!(both);
+    // Dead edges read as something else altogether.
+    assert!(!bounded_matches);
+}
+
+#[test]
+fn test_population_in() {
+    let mut game = GameOfLife::new_empty((10, 8));
+    game.fill(0.5, 4);
+    let rect = |x, y, width, height| Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+    assert_eq!(game.population_in(rect(0, 0, 10, 8)), game.population());
+    // The halves of the board add up to it.
+    let left = game.population_in(rect(0, 0, 4, 8));
+    assert_eq!(
+        left + game.population_in(rect(4, 0, 6, 8)),
+        game.population()
+    );
+    let by_hand = (2..5).flat_map(|y| (3..7).map(move |x| (x, y)));
+    let by_hand = by_hand.filter(|&(x, y)| game.get(GridPos(x, y))).count();
+    assert_eq!(game.population_in(rect(3, 2, 4, 3)), by_hand);
+    // Parts off the board count for nothing.
+    assert_eq!(
+        game.population_in(rect(8, 6, 100, 100)),
+        game.population_in(rect(8, 6, 2, 2))
+    );
+    assert_eq!(game.population_in(rect(10, 0, 5, 8)), 0);
+    assert_eq!(game.population_in(rect(0, 3, 10, 0)), 0);
+}
+
+#[test]
+fn test_observers_see_every_step() {
+    use crate::StepEvent;
+    use std::sync::{Arc, Mutex};
+
+    let mut game = GameOfLife::new_empty((12, 12));
+    game.set_edge_mode(EdgeMode::Toroidal);
+    game.insert_pattern(&Pattern::named("glider").unwrap(), GridPos(1, 1));
+    let mut events = Vec::new();
+    for _ in 0..50 {
+        game.tick_observed(&mut events);
+    }
+    assert_eq!(events.len(), 100);
+    for (i, pair) in events.chunks(2).enumerate() {
+        let generation = i as u64;
+        assert_eq!(pair[0], StepEvent::Start { generation });
+        let StepEvent::End {
+            generation: ended,
+            births,
+            deaths,
+            ..
+        } = pair[1]
+        else {
+            panic!("{:?} isn't the end of a step", pair[1]);
+        };
+        // A glider loses as many cells as it gains each step.
+        assert_eq!((ended, births), (generation + 1, deaths));
+        assert!(births > 0);
+    }
+
+    // An observer the board keeps sees its steps, but not a clone's.
+    let seen = Arc::new(Mutex::new(Vec::<StepEvent>::new()));
+    game.set_observer(Box::new(seen.clone()));
+    game.step();
+    game.clone().step();
+    game.tick_observed(&mut ());
+    // Stepping by hand is a step like any other.
+    game.step_with_fn(|_, _| false);
+    let seen_so_far = seen.lock().unwrap().clone();
+    let starts = seen_so_far
+        .iter()
+        .filter(|event| matches!(event, StepEvent::Start { .. }));
+    assert_eq!((seen_so_far.len(), starts.count()), (6, 3));
+    let StepEvent::End { births, deaths, .. } = seen_so_far[5] else {
+        panic!("{:?} isn't the end of a step", seen_so_far[5]);
+    };
+    assert_eq!((births, deaths), (0, 5));
+    assert!(game.take_observer().is_some());
+    game.step();
+    assert_eq!(seen.lock().unwrap().len(), 6);
+
+    // Births and deaths are the cells that changed each way.
+    let mut game = GameOfLife::new((20, 16));
+    game.fill(0.4, 3);
+    let before = game.surface().to_vec();
+    let mut events = Vec::new();
+    game.tick_observed(&mut events);
+    let changed = |was: bool| {
+        let now = before.iter().zip(game.surface());
+        now.filter(|&(&then, &now)| then == was && now != was)
+            .count()
+    };
+    let StepEvent::End { births, deaths, .. } = events[1] else {
+        panic!("{:?} isn't the end of a step", events[1]);
+    };
+    assert_eq!((births, deaths), (changed(false), changed(true)));
+    assert!(births > 0 && deaths > 0);
+}
+
+#[test]
+fn test_shift_discards_or_wraps_by_edge_mode() {
+    let art = "...\n.#.\n.##";
+    let mut bounded = GameOfLife::from_ascii(art);
+    bounded.shift(1, 1);
+    assert_eq!(
+        bounded.surface(),
+        GameOfLife::from_ascii("...\n...\n..#").surface()
+    );
+    bounded.shift(-2, -2);
+    assert_eq!(
+        bounded.surface(),
+        GameOfLife::from_ascii("#..\n...\n...").surface()
+    );
+
+    let mut toroidal = GameOfLife::from_ascii(art);
+    toroidal.set_edge_mode(EdgeMode::Toroidal);
+    toroidal.shift(1, 1);
+    assert_eq!(
+        toroidal.surface(),
+        GameOfLife::from_ascii("#.#\n...\n..#").surface()
+    );
+    toroidal.shift(-1, -1);
+    assert_eq!(toroidal.surface(), GameOfLife::from_ascii(art).surface());
+}
@@ -0,0 +1,156 @@
+use crate::{DenseBool, EdgeMode, GameOfLife, GridPos, GridStorage, Rect};
+
+/// Read-only access to a grid of cells, which is all renderers need: a whole
+/// board, part of one, or anything else that can say which cells are alive.
+pub trait GridView {
+    /// Width and height in cells.
+    fn size(&self) -> (usize, usize);
+
+    /// Whether the cell at `(x, y)` is alive; both must be inside
+    /// [`size`](Self::size).
+    fn get(&self, x: usize, y: usize) -> bool;
+
+    /// Whether the cell at `(x, y)` is held at a fixed state, as by a
+    /// [`Mask`](crate::Mask).
+    fn is_forced(&self, _x: usize, _y: usize) -> bool {
+        false
+    }
+
+    /// Whether the grid's edges wrap around to meet each other.
+    fn wraps(&self) -> bool {
+        false
+    }
+}
+
+impl<S: GridStorage> GridView for GameOfLife<S> {
+    fn size(&self) -> (usize, usize) {
+        GameOfLife::size(self)
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        GameOfLife::get(self, GridPos(x, y))
+    }
+
+    fn is_forced(&self, x: usize, y: usize) -> bool {
+        GameOfLife::is_forced(self, GridPos(x, y))
+    }
+
+    fn wraps(&self) -> bool {
+        self.edge_mode() == EdgeMode::Toroidal
+    }
+}
+
+/// A rectangle of another grid, seen as a grid of its own whose top-left is
+/// the rectangle's. Its edges are cut, so it never wraps.
+#[derive(Clone, Copy)]
+pub struct Viewport<'a> {
+    grid: &'a dyn GridView,
+    rect: Rect,
+}
+
+impl<'a> Viewport<'a> {
+    /// The part of `rect` that lies inside `grid`.
+    pub fn new(grid: &'a dyn GridView, rect: Rect) -> Self {
+        let (w, h) = grid.size();
+        let x = rect.x.min(w);
+        let y = rect.y.min(h);
+        let rect = Rect {
+            x,
+            y,
+            width: rect.width.min(w - x),
+            height: rect.height.min(h - y),
+        };
+        Self { grid, rect }
+    }
+}
+
+impl GridView for Viewport<'_> {
+    fn size(&self) -> (usize, usize) {
+        (self.rect.width, self.rect.height)
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.grid.get(self.rect.x + x, self.rect.y + y)
+    }
+
+    fn is_forced(&self, x: usize, y: usize) -> bool {
+        self.grid.is_forced(self.rect.x + x, self.rect.y + y)
+    }
+}
+
+/// The generation after a board's, each cell worked out from the board as
+/// it is read rather than all of them stepped into a grid of their own: a
+/// look at what the next step will do without taking it. See
+/// [`GameOfLife::preview_next`].
+pub struct NextView<'a, S: GridStorage = DenseBool> {
+    game: &'a GameOfLife<S>,
+}
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// The next generation, as a view that works a cell out, under the rule
+    /// and the mask, only when it is asked for one.
+    pub fn preview_next(&self) -> NextView<'_, S> {
+        NextView { game: self }
+    }
+}
+
+impl<S: GridStorage> GridView for NextView<'_, S> {
+    fn size(&self) -> (usize, usize) {
+        self.game.size()
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.game.inspect(GridPos(x, y)).next
+    }
+
+    fn is_forced(&self, x: usize, y: usize) -> bool {
+        self.game.is_forced(GridPos(x, y))
+    }
+
+    fn wraps(&self) -> bool {
+        GridView::wraps(self.game)
+    }
+}
+
+#[test]
+fn test_viewport_clips_and_offsets() {
+    let mut game = GameOfLife::new_empty((8, 6));
+    game.set(GridPos(5, 4), true);
+    let rect = Rect {
+        x: 4,
+        y: 3,
+        width: 10,
+        height: 2,
+    };
+    let viewport = Viewport::new(&game, rect);
+    assert_eq!(viewport.size(), (4, 2));
+    assert!(viewport.get(1, 1));
+    assert!(!viewport.get(0, 0));
+    assert!(!viewport.wraps());
+
+    let outside = Rect { x: 9, ..rect };
+    assert_eq!(Viewport::new(&game, outside).size(), (0, 2));
+}
+
+#[test]
+fn test_next_view_is_the_step_not_taken() {
+    use crate::{Mask, MaskCell};
+
+    let mut game = GameOfLife::new((12, 9));
+    game.set_edge_mode(EdgeMode::Toroidal);
+    let mut mask = Mask::new(game.size());
+    mask.set(2, 2, MaskCell::ForcedAlive);
+    game.set_mask(Some(mask));
+    let mut stepped = game.clone();
+    stepped.step();
+    let next = game.preview_next();
+    assert_eq!((next.size(), next.wraps()), ((12, 9), true));
+    for y in 0..9 {
+        for x in 0..12 {
+            assert_eq!(next.get(x, y), stepped.get(GridPos(x, y)), "({x}, {y})");
+        }
+    }
+    assert!(next.get(2, 2) && next.is_forced(2, 2));
+    // The board itself hasn't moved on.
+    assert_eq!(game.generation(), 0);
+}
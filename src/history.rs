@@ -0,0 +1,365 @@
+use std::collections::VecDeque;
+
+use crate::{
+    metadata::{Conflict, Metadata},
+    rle, EdgeMode, GameOfLife, Rule,
+};
+
+/// A copy of the board as it was at one generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub generation: u64,
+    pub size: (usize, usize),
+    pub rule: Rule,
+    pub edges: EdgeMode,
+    /// Row-major, as in [`GameOfLife::surface`].
+    pub cells: Vec<bool>,
+}
+
+impl Frame {
+    pub fn of(game: &GameOfLife) -> Self {
+        Self {
+            generation: game.generation(),
+            size: game.size(),
+            rule: game.rule(),
+            edges: game.edge_mode(),
+            cells: game.surface().to_vec(),
+        }
+    }
+
+    /// The generation, rule and edges.
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            generation: Some(self.generation),
+            rule: Some(self.rule),
+            edges: Some(self.edges),
+            ..Metadata::default()
+        }
+    }
+
+    /// An RLE of the board, its [`metadata`](Self::metadata) in comments.
+    pub fn to_rle(&self) -> String {
+        rle::encode_with(&self.metadata(), self.size, |x, y| {
+            self.cells[x + y * self.size.0]
+        })
+    }
+
+    /// The cells where `other` differs from this frame, row by row, with
+    /// their state here. Past the edge of the smaller frame, cells are taken
+    /// to be dead.
+    pub fn diff(&self, other: &Frame) -> Vec<(usize, usize, bool)> {
+        let cell = |frame: &Frame, x: usize, y: usize| {
+            let (w, h) = frame.size;
+            x < w && y < h && frame.cells[x + y * w]
+        };
+        let (w, h) = (self.size.0.max(other.size.0), self.size.1.max(other.size.1));
+        (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .filter(|&(x, y)| cell(self, x, y) != cell(other, x, y))
+            .map(|(x, y)| (x, y, cell(self, x, y)))
+            .collect()
+    }
+
+    /// The newest frame of a file written by [`History::to_multi_rle`], as
+    /// it writes them now or as it did before metadata had a layout, with
+    /// where its metadata disagreed with itself.
+    pub fn last_of_multi_rle(text: &str) -> Result<(Self, Vec<Conflict>), String> {
+        let chunk = text
+            .split("\n\n")
+            .filter(|chunk| !chunk.trim().is_empty())
+            .last()
+            .ok_or("no generations in history file")?;
+        let (pattern, metadata, conflicts) = rle::decode_with_metadata(chunk)?;
+        let generation = metadata.generation.ok_or("no generation in history file")?;
+        let (w, h) = pattern.size();
+        let mut cells = vec![false; w * h];
+        for &(x, y) in pattern.cells() {
+            cells[x + y * w] = true;
+        }
+        let frame = Self {
+            generation,
+            size: (w, h),
+            rule: metadata.rule.unwrap_or_default(),
+            edges: metadata.edges.unwrap_or_default(),
+            cells,
+        };
+        Ok((frame, conflicts))
+    }
+}
+
+/// A generation put aside to see how far the board has strayed from it
+/// since, packed as by [`GameOfLife::pack_into`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub generation: u64,
+    pub size: (usize, usize),
+    words: Vec<u64>,
+}
+
+/// The cells alive now but not at a [`Bookmark`], and those alive then but
+/// not now, each packed like the bookmark, to be reused from frame to frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Divergence {
+    width: usize,
+    appeared: Vec<u64>,
+    vanished: Vec<u64>,
+    /// The board now, packed.
+    now: Vec<u64>,
+}
+
+impl Bookmark {
+    pub fn of(game: &GameOfLife) -> Self {
+        let mut words = Vec::new();
+        game.pack_into(&mut words);
+        Self {
+            generation: game.generation(),
+            size: game.size(),
+            words,
+        }
+    }
+
+    /// Fills `into` with how `game` differs from the bookmark, a word at a
+    /// time, or leaves it alone and returns false if the board is no longer
+    /// the bookmark's size.
+    pub fn diverge(&self, game: &GameOfLife, into: &mut Divergence) -> bool {
+        if game.size() != self.size {
+            return false;
+        }
+        game.pack_into(&mut into.now);
+        into.width = self.size.0;
+        into.appeared.clear();
+        into.vanished.clear();
+        for (&now, &then) in into.now.iter().zip(&self.words) {
+            let changed = now ^ then;
+            into.appeared.push(changed & now);
+            into.vanished.push(changed & then);
+        }
+        true
+    }
+}
+
+impl Divergence {
+    /// Whether the cell is alive now but wasn't at the bookmark.
+    pub fn appeared(&self, x: usize, y: usize) -> bool {
+        bit(&self.appeared, x + y * self.width)
+    }
+
+    /// Whether the cell was alive at the bookmark but isn't now.
+    pub fn vanished(&self, x: usize, y: usize) -> bool {
+        bit(&self.vanished, x + y * self.width)
+    }
+
+    /// How many cells appeared and vanished.
+    pub fn counts(&self) -> (usize, usize) {
+        let ones = |words: &[u64]| words.iter().map(|word| word.count_ones() as usize).sum();
+        (ones(&self.appeared), ones(&self.vanished))
+    }
+}
+
+fn bit(words: &[u64], i: usize) -> bool {
+    words
+        .get(i / 64)
+        .is_some_and(|word| word >> (i % 64) & 1 == 1)
+}
+
+/// The most recent generations of a board, up to a fixed capacity; pushing
+/// past it forgets the oldest.
+#[derive(Debug, Clone)]
+pub struct History {
+    capacity: usize,
+    frames: VecDeque<Frame>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn push(&mut self, game: &GameOfLife) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Frame::of(game));
+    }
+
+    /// The frame `index` places after the oldest.
+    pub fn get(&self, index: usize) -> Option<&Frame> {
+        self.frames.get(index)
+    }
+
+    /// Forgets all but the oldest `len` frames, e.g. the future of a
+    /// generation being resumed from.
+    pub fn truncate(&mut self, len: usize) {
+        self.frames.truncate(len);
+    }
+
+    /// Takes `game` back `n` generations, or as far as the frames go, and
+    /// forgets the frames after the one restored. The newest frame counts as
+    /// the game's own state when it is of the same generation. Returns how
+    /// many generations it went back.
+    pub fn step_back_n(&mut self, game: &mut GameOfLife, n: usize) -> usize {
+        let current = match self.frames.back() {
+            Some(newest) if newest.generation == game.generation() => self.len() - 1,
+            _ => self.len(),
+        };
+        let back = n.min(current);
+        if back == 0 {
+            return 0;
+        }
+        let target = current - back;
+        game.restore(&self.frames[target]);
+        self.truncate(target + 1);
+        back
+    }
+
+    /// Frames from oldest to newest.
+    pub fn frames(&self) -> impl Iterator<Item = &Frame> {
+        self.frames.iter()
+    }
+
+    /// The newest `n` frames (or all of them, if there are fewer), oldest
+    /// first.
+    pub fn last(&self, n: usize) -> impl Iterator<Item = &Frame> {
+        self.frames.iter().skip(self.frames.len().saturating_sub(n))
+    }
+
+    /// Writes the newest `n` frames as one file of RLEs, oldest first, each
+    /// with its metadata and separated by blank lines.
+    pub fn to_multi_rle(&self, n: usize) -> String {
+        self.last(n)
+            .map(Frame::to_rle)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[test]
+fn test_frame_diff() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::new_empty((4, 3));
+    game.set(GridPos(1, 1), true);
+    let before = Frame::of(&game);
+    game.set(GridPos(1, 1), false);
+    game.set(GridPos(3, 2), true);
+    let after = Frame::of(&game);
+    assert_eq!(before.diff(&after), [(1, 1, true), (3, 2, false)]);
+    assert_eq!(after.diff(&before), [(1, 1, false), (3, 2, true)]);
+    assert!(after.diff(&after).is_empty());
+    let wider = Frame::of(&GameOfLife::new_empty((5, 3)));
+    assert_eq!(after.diff(&wider), [(3, 2, true)]);
+}
+
+#[test]
+fn test_divergence_from_a_bookmark() {
+    use crate::GridPos;
+
+    // Wider than a word, so rows straddle them.
+    let mut game = GameOfLife::new_empty((70, 3));
+    game.set(GridPos(1, 0), true);
+    game.set(GridPos(65, 1), true);
+    game.set(GridPos(2, 2), true);
+    let bookmark = Bookmark::of(&game);
+    let mut divergence = Divergence::default();
+    assert!(bookmark.diverge(&game, &mut divergence));
+    assert_eq!(divergence.counts(), (0, 0));
+
+    game.set(GridPos(65, 1), false);
+    game.set(GridPos(69, 2), true);
+    game.set(GridPos(0, 0), true);
+    assert!(bookmark.diverge(&game, &mut divergence));
+    assert_eq!(divergence.counts(), (2, 1));
+    assert!(divergence.appeared(69, 2) && divergence.appeared(0, 0));
+    assert!(divergence.vanished(65, 1) && !divergence.appeared(65, 1));
+    // Alive both times, or dead both times, is no change.
+    assert!(!divergence.appeared(1, 0) && !divergence.vanished(1, 0));
+    assert!(!divergence.appeared(2, 2) && !divergence.vanished(3, 2));
+
+    game.resize((10, 3));
+    assert!(!bookmark.diverge(&game, &mut divergence));
+    assert_eq!(divergence.counts(), (2, 1));
+}
+
+#[test]
+fn test_multi_rle_matches_history() {
+    let mut game = GameOfLife::new((8, 6));
+    let mut history = History::new(5);
+    for _ in 0..9 {
+        history.push(&game);
+        game.step();
+    }
+    assert_eq!(history.len(), 5);
+
+    let frames = history.last(3).collect::<Vec<_>>();
+    let text = history.to_multi_rle(3);
+    let chunks = text.split("\n\n").collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 3);
+    for (chunk, frame) in chunks.iter().zip(&frames) {
+        let comments = format!("#C version = 2\n#C generation = {}\n", frame.generation);
+        assert!(chunk.starts_with(&format!("{comments}#C edges = bounded\nx = 8")));
+        assert_eq!(format!("{}\n", chunk.trim_end()), frame.to_rle());
+    }
+    assert_eq!(frames.last().unwrap().generation, 8);
+    assert_eq!(frames[0].generation, 6);
+    let (last, conflicts) = Frame::last_of_multi_rle(&text).unwrap();
+    assert_eq!((&last, conflicts), (*frames.last().unwrap(), Vec::new()));
+    assert!(Frame::last_of_multi_rle("").is_err());
+}
+
+#[test]
+fn test_restore_and_truncate() {
+    let mut game = GameOfLife::new((7, 5));
+    let mut history = History::new(8);
+    for _ in 0..4 {
+        history.push(&game);
+        game.step();
+    }
+    let frame = history.get(1).unwrap().clone();
+    game.restore(&frame);
+    assert_eq!(Frame::of(&game), frame);
+    history.truncate(2);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.frames().last(), Some(&frame));
+    assert!(history.get(2).is_none());
+}
+
+#[test]
+fn test_step_back_n() {
+    let mut game = GameOfLife::new((9, 9));
+    let mut history = History::new(6);
+    history.push(&game);
+    for _ in 0..8 {
+        game.step();
+        history.push(&game);
+    }
+    // Generations 3 to 8 are kept; 8 is the game's.
+    let five = history.get(2).unwrap().clone();
+    assert_eq!(history.step_back_n(&mut game, 3), 3);
+    assert_eq!(Frame::of(&game), five);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.step_back_n(&mut game, 10), 2);
+    assert_eq!(game.generation(), 3);
+    assert_eq!(history.step_back_n(&mut game, 1), 0);
+    // Stepping on past what was recorded counts the newest frame as one back.
+    game.step();
+    assert_eq!(history.step_back_n(&mut game, 1), 1);
+    assert_eq!(game.generation(), 3);
+}
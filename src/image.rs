@@ -0,0 +1,652 @@
+use std::{fmt, fs, io, path::Path};
+
+use crate::{capture::adler32, GameOfLife};
+
+/// What every PNG file starts with.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The most pixels a PNG may have. Its header says how big it is, so larger
+/// ones are turned down before anything is decompressed for them.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// Why an image could not be loaded.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    /// The file is not a PNG or Netpbm image this loader understands.
+    Format(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "could not read image: {err}"),
+            ImageError::Format(reason) => write!(f, "unsupported image: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<io::Error> for ImageError {
+    fn from(err: io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+impl GameOfLife {
+    /// Seeds a board the size of the image at `path`, with pixels darker than
+    /// `threshold` (0 black to 255 white) live. The image may be a PNG of any
+    /// color type and bit depth, with transparent pixels taken as white, as
+    /// long as it isn't interlaced; or a PBM or PGM file, in either the plain
+    /// or binary encoding.
+    pub fn from_image(path: impl AsRef<Path>, threshold: u8) -> Result<Self, ImageError> {
+        let (size, brightness) = decode(&fs::read(path)?)?;
+        let mut game = GameOfLife::new_empty(size);
+        for (cell, &value) in game.surface_mut().iter_mut().zip(&brightness) {
+            *cell = value < threshold;
+        }
+        Ok(game)
+    }
+}
+
+/// The size and row-major brightness (0-255) of a PNG or Netpbm image.
+fn decode(data: &[u8]) -> Result<((usize, usize), Vec<u8>), ImageError> {
+    match data.starts_with(PNG_SIGNATURE) {
+        true => decode_png(data),
+        false => decode_netpbm(data),
+    }
+}
+
+/// The size and row-major brightness (0-255) of a Netpbm bitmap or graymap.
+fn decode_netpbm(data: &[u8]) -> Result<((usize, usize), Vec<u8>), ImageError> {
+    let format = |reason: &str| ImageError::Format(reason.to_string());
+    let mut pos = 0;
+    // Header fields are whitespace-separated, with `#` starting a comment.
+    let mut field = || -> Result<&[u8], ImageError> {
+        loop {
+            while pos < data.len() && data[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if data.get(pos) != Some(&b'#') {
+                break;
+            }
+            while pos < data.len() && data[pos] != b'\n' {
+                pos += 1;
+            }
+        }
+        let start = pos;
+        while pos < data.len() && !data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        (start < pos)
+            .then(|| &data[start..pos])
+            .ok_or_else(|| format("truncated header"))
+    };
+    let number = |field: &[u8]| {
+        std::str::from_utf8(field)
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .ok_or_else(|| format("bad number in header"))
+    };
+
+    let magic = field()?.to_vec();
+    let (w, h) = (number(field()?)?, number(field()?)?);
+    if w == 0 || h == 0 {
+        return Err(format("empty image"));
+    }
+    let max = match magic.as_slice() {
+        b"P1" | b"P4" => 1,
+        b"P2" | b"P5" => number(field()?)?,
+        _ => return Err(format("not a PBM or PGM file")),
+    };
+    if max == 0 || max > 255 {
+        return Err(format("only 8-bit graymaps are supported"));
+    }
+    let scale = |value: usize| (value.min(max) * 255 / max) as u8;
+
+    let pixels = match magic.as_slice() {
+        // In bitmaps 1 is black.
+        b"P1" => (0..w * h)
+            .map(|_| {
+                while pos < data.len() && !matches!(data[pos], b'0' | b'1') {
+                    pos += 1;
+                }
+                let bit = *data.get(pos).ok_or_else(|| format("truncated pixels"))?;
+                pos += 1;
+                Ok(if bit == b'1' { 0 } else { 255 })
+            })
+            .collect::<Result<Vec<_>, ImageError>>()?,
+        b"P2" => (0..w * h)
+            .map(|_| field().and_then(number).map(scale))
+            .collect::<Result<Vec<_>, ImageError>>()?,
+        binary => {
+            // A single whitespace byte separates the header from the pixels.
+            let body = data.get(pos + 1..).unwrap_or_default();
+            if binary == b"P5" {
+                body.get(..w * h)
+                    .ok_or_else(|| format("truncated pixels"))?
+                    .iter()
+                    .map(|&v| scale(v as usize))
+                    .collect()
+            } else {
+                let stride = w.div_ceil(8);
+                let rows = body
+                    .get(..stride * h)
+                    .ok_or_else(|| format("truncated pixels"))?;
+                (0..w * h)
+                    .map(|i| {
+                        let (x, y) = (i % w, i / w);
+                        let bit = rows[y * stride + x / 8] & (0x80 >> (x % 8));
+                        if bit != 0 {
+                            0
+                        } else {
+                            255
+                        }
+                    })
+                    .collect()
+            }
+        }
+    };
+    Ok(((w, h), pixels))
+}
+
+/// The size and row-major brightness (0-255) of a PNG, its colors weighted
+/// as luma and laid over white by their alpha.
+fn decode_png(data: &[u8]) -> Result<((usize, usize), Vec<u8>), ImageError> {
+    let format = |reason: &str| ImageError::Format(reason.to_string());
+    let (mut header, mut palette, mut transparency, mut zlib) =
+        (None, &[][..], &[][..], Vec::new());
+    let mut at = PNG_SIGNATURE.len();
+    loop {
+        let len = data
+            .get(at..at + 4)
+            .ok_or_else(|| format("truncated PNG"))?;
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        let chunk = data
+            .get(at + 4..(at + 8).saturating_add(len))
+            .ok_or_else(|| format("truncated PNG"))?;
+        let (kind, body) = chunk.split_at(4);
+        match kind {
+            b"IHDR" if body.len() == 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => zlib.extend_from_slice(body),
+            b"IEND" => break,
+            // Chunks named in lowercase are safe to pass over.
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => return Err(format("unknown critical PNG chunk")),
+        }
+        at += len + 12;
+    }
+    let header = header.ok_or_else(|| format("no PNG header"))?;
+    let number = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap()) as usize;
+    let (w, h, depth, color) = (number(0), number(4), header[8] as usize, header[9]);
+    if w == 0 || h == 0 {
+        return Err(format("empty image"));
+    }
+    if header[10..12] != [0, 0] {
+        return Err(format("unknown PNG compression or filter method"));
+    }
+    if header[12] != 0 {
+        return Err(format("interlaced PNGs aren't supported"));
+    }
+    let channels = match (color, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) => 1,
+        (4, 8 | 16) => 2,
+        (2, 8 | 16) => 3,
+        (6, 8 | 16) => 4,
+        _ => return Err(format("bad PNG bit depth or color type")),
+    };
+
+    // Each row is a filter byte and then the filtered samples, which are
+    // undone against the row before and the pixel to the left. Nothing past
+    // the rows is decompressed, however much more the stream holds.
+    let stride = w.checked_mul(channels * depth).map(|bits| bits.div_ceil(8));
+    let expected = stride
+        .and_then(|stride| (stride + 1).checked_mul(h))
+        .filter(|_| w.checked_mul(h).is_some_and(|pixels| pixels <= MAX_PIXELS));
+    let (Some(stride), Some(expected)) = (stride, expected) else {
+        return Err(format("PNG too large"));
+    };
+    let raw = inflate(&zlib, expected)?;
+    let step = (channels * depth).div_ceil(8);
+    if raw.len() < expected {
+        return Err(format("truncated pixels"));
+    }
+    let mut rows = vec![0; stride * h];
+    for y in 0..h {
+        let (filter, line) = (raw[y * (stride + 1)], &raw[y * (stride + 1) + 1..]);
+        let (above, row) = rows.split_at_mut(y * stride);
+        let above = above.get((y.max(1) - 1) * stride..).unwrap_or_default();
+        for i in 0..stride {
+            let left = |row: &[u8]| if i >= step { row[i - step] } else { 0 };
+            let (a, b, c) = (
+                left(row) as i16,
+                *above.get(i).unwrap_or(&0) as i16,
+                if above.is_empty() {
+                    0
+                } else {
+                    left(above) as i16
+                },
+            );
+            let predicted = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => (a + b) / 2,
+                4 => {
+                    let p = a + b - c;
+                    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                    if pa <= pb && pa <= pc {
+                        a
+                    } else if pb <= pc {
+                        b
+                    } else {
+                        c
+                    }
+                }
+                _ => return Err(format("bad PNG row filter")),
+            };
+            row[i] = line[i].wrapping_add(predicted as u8);
+        }
+    }
+
+    let max = (1 << depth) - 1;
+    let sample = |row: &[u8], n: usize| match depth {
+        16 => u16::from_be_bytes([row[2 * n], row[2 * n + 1]]) as usize,
+        8 => row[n] as usize,
+        _ => (row[n * depth / 8] >> (8 - depth - n * depth % 8)) as usize & max,
+    };
+    // A color key in tRNS is as many samples as a pixel has colors.
+    let key: Vec<usize> = match color {
+        0 | 2 => transparency
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let luma = |r: usize, g: usize, b: usize| (299 * r + 587 * g + 114 * b) / 1000;
+    let mut brightness = Vec::with_capacity(w * h);
+    for row in rows.chunks_exact(stride) {
+        for x in 0..w {
+            let raw = |n: usize| sample(row, x * channels + n);
+            let scaled = |n: usize| raw(n) * 255 / max;
+            let (value, alpha) = match color {
+                3 => {
+                    let i = raw(0);
+                    let rgb = palette
+                        .get(3 * i..3 * i + 3)
+                        .ok_or_else(|| format("palette index out of range"))?;
+                    let alpha = transparency.get(i).map_or(255, |&alpha| alpha as usize);
+                    (
+                        luma(rgb[0] as usize, rgb[1] as usize, rgb[2] as usize),
+                        alpha,
+                    )
+                }
+                0 | 2 => {
+                    let samples: Vec<usize> = (0..channels).map(raw).collect();
+                    let alpha = if samples == key { 0 } else { 255 };
+                    match color {
+                        0 => (scaled(0), alpha),
+                        _ => (luma(scaled(0), scaled(1), scaled(2)), alpha),
+                    }
+                }
+                4 => (scaled(0), scaled(1)),
+                _ => (luma(scaled(0), scaled(1), scaled(2)), scaled(3)),
+            };
+            brightness.push(((value * alpha + 255 * (255 - alpha)) / 255) as u8);
+        }
+    }
+    Ok(((w, h), brightness))
+}
+
+/// Lengths of the deflate length codes from 257, before their extra bits.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Distances of the deflate distance codes, before their extra bits.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order a dynamic block lists the lengths of its code length code in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// A deflate stream read a bit at a time, lowest bit of each byte first.
+struct Bits<'a> {
+    data: &'a [u8],
+    at: usize,
+}
+
+impl Bits<'_> {
+    fn take(&mut self, n: u8) -> Result<usize, ImageError> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = self.data.get(self.at / 8).ok_or_else(truncated_deflate)?;
+            value |= (*byte as usize >> (self.at % 8) & 1) << i;
+            self.at += 1;
+        }
+        Ok(value)
+    }
+
+    /// Skips to the start of the next byte.
+    fn align(&mut self) {
+        self.at = self.at.div_ceil(8) * 8;
+    }
+}
+
+fn truncated_deflate() -> ImageError {
+    ImageError::Format("truncated deflate stream".to_string())
+}
+
+/// A canonical Huffman code, as how many codes there are of each length and
+/// the symbols they stand for in code order.
+struct Huffman {
+    counts: [usize; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// The code giving symbol `i` a code `lengths[i]` bits long, or none if
+    /// that is zero.
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate().filter(|&(_, &len)| len != 0) {
+            symbols[offsets[len as usize]] = symbol as u16;
+            offsets[len as usize] += 1;
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, ImageError> {
+        // The first code of each length, and where its symbols start.
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for &count in &self.counts[1..] {
+            code |= bits.take(1)?;
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::Format("bad Huffman code".to_string()))
+    }
+}
+
+/// The data in a zlib stream, checked against its Adler-32, or its first
+/// `limit` bytes if there is more; those can't be checked.
+fn inflate(zlib: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    let format = |reason: &str| ImageError::Format(reason.to_string());
+    match zlib {
+        [method, flags, ..]
+            if method & 0x0f == 8
+                && (u16::from(*method) << 8 | u16::from(*flags)) % 31 == 0
+                && flags & 0x20 == 0 => {}
+        _ => return Err(format("bad zlib header")),
+    }
+    let mut bits = Bits {
+        data: &zlib[2..],
+        at: 0,
+    };
+    let mut out = Vec::new();
+    'blocks: loop {
+        let last = bits.take(1)? == 1;
+        let (literals, distances) = match bits.take(2)? {
+            0 => {
+                bits.align();
+                let (len, inverse) = (bits.take(16)?, bits.take(16)?);
+                if len ^ inverse != 0xffff {
+                    return Err(format("bad stored deflate block"));
+                }
+                let start = bits.at / 8;
+                let stored = bits
+                    .data
+                    .get(start..start + len)
+                    .ok_or_else(truncated_deflate)?;
+                out.extend_from_slice(&stored[..stored.len().min(limit - out.len())]);
+                bits.at += 8 * len;
+                if out.len() == limit {
+                    return Ok(out);
+                }
+                if last {
+                    break;
+                }
+                continue;
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+            }
+            2 => dynamic_codes(&mut bits)?,
+            _ => return Err(format("bad deflate block type")),
+        };
+        loop {
+            let symbol = literals.decode(&mut bits)? as usize;
+            if out.len() == limit {
+                break 'blocks;
+            }
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let i = symbol - 257;
+                    let base = *LENGTH_BASE
+                        .get(i)
+                        .ok_or_else(|| format("bad deflate length"))?;
+                    let len = base as usize + bits.take(LENGTH_EXTRA[i])?;
+                    let d = distances.decode(&mut bits)? as usize;
+                    let base = *DISTANCE_BASE
+                        .get(d)
+                        .ok_or_else(|| format("bad deflate distance"))?;
+                    let distance = base as usize + bits.take(DISTANCE_EXTRA[d])?;
+                    let start = out
+                        .len()
+                        .checked_sub(distance)
+                        .ok_or_else(|| format("deflate distance past the start"))?;
+                    // The copy may run into bytes it is itself writing.
+                    for k in start..start + len.min(limit - out.len()) {
+                        out.push(out[k]);
+                    }
+                }
+            }
+        }
+        if last {
+            break;
+        }
+    }
+    if out.len() == limit {
+        return Ok(out);
+    }
+    bits.align();
+    let end = bits.at / 8;
+    if bits.data.get(end..end + 4) != Some(&adler32(&out).to_be_bytes()[..]) {
+        return Err(format("bad zlib checksum"));
+    }
+    Ok(out)
+}
+
+/// The literal and distance codes a dynamic deflate block starts with.
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), ImageError> {
+    let format = |reason: &str| ImageError::Format(reason.to_string());
+    let (literals, distances) = (bits.take(5)? + 257, bits.take(5)? + 1);
+    let mut code_lengths = [0; 19];
+    for &i in &CODE_LENGTH_ORDER[..bits.take(4)? + 4] {
+        code_lengths[i] = bits.take(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths);
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (len, repeat) = match code.decode(bits)? {
+            len @ 0..=15 => (len as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| format("bad deflate lengths"))?;
+                (previous, 3 + bits.take(2)?)
+            }
+            17 => (0, 3 + bits.take(3)?),
+            _ => (0, 11 + bits.take(7)?),
+        };
+        if lengths.len() + repeat > literals + distances {
+            return Err(format("bad deflate lengths"));
+        }
+        lengths.extend(std::iter::repeat_n(len, repeat));
+    }
+    if lengths[256] == 0 {
+        return Err(format("deflate block without an end code"));
+    }
+    let (literals, distances) = lengths.split_at(literals);
+    Ok((Huffman::new(literals), Huffman::new(distances)))
+}
+
+#[test]
+fn test_decode_netpbm() {
+    let plain = b"P2\n# a comment\n3 2\n4\n0 4 2\n1 3 4\n";
+    assert_eq!(
+        decode(plain).unwrap(),
+        ((3, 2), vec![0, 255, 127, 63, 191, 255])
+    );
+    let bitmap = b"P4 10 1\n\xC0\x40";
+    let (size, pixels) = decode(bitmap).unwrap();
+    assert_eq!(size, (10, 1));
+    assert_eq!(pixels, [0, 0, 255, 255, 255, 255, 255, 255, 255, 0]);
+    assert!(matches!(
+        decode(b"P6 1 1 255\n\0\0\0"),
+        Err(ImageError::Format(_))
+    ));
+}
+
+#[test]
+fn test_decode_png() {
+    use crate::capture::encode_png;
+
+    // Stored blocks, as PNG export writes them. Black at half alpha shows
+    // as gray over white.
+    let pixels = [0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 128];
+    assert_eq!(
+        decode(&encode_png((3, 1), &pixels)).unwrap(),
+        ((3, 1), vec![0, 255, 127])
+    );
+
+    // Two bits a pixel, from a palette of black, white, red and a black
+    // that is transparent; in fixed Huffman codes.
+    let paletted = b"\x89\x50\x4e\x47\x0d\x0a\x1a\x0a\x00\x00\x00\x0d\x49\x48\x44\x52\x00\x00\x00\
+                     \x04\x00\x00\x00\x02\x02\x03\x00\x00\x00\x02\xc6\x95\xf0\x00\x00\x00\x0c\x50\
+                     \x4c\x54\x45\x00\x00\x00\xff\xff\xff\xff\x00\x00\x00\x00\x00\x2c\x1f\xdc\xc7\
+                     \x00\x00\x00\x04\x74\x52\x4e\x53\xff\xff\xff\x00\x40\x2a\xa9\xf4\x00\x00\x00\
+                     \x0c\x49\x44\x41\x54\x78\xda\x63\x90\x66\x78\x02\x00\x01\x39\x01\x00\x7b\x99\
+                     \x42\x37\x00\x00\x00\x00\x49\x45\x4e\x44\xae\x42\x60\x82";
+    let (size, pixels) = decode(paletted).unwrap();
+    assert_eq!(
+        (size, pixels),
+        ((4, 2), vec![0, 255, 76, 255, 255, 76, 255, 0])
+    );
+    let mut interlaced = paletted.to_vec();
+    interlaced[28] = 1;
+    assert!(matches!(decode(&interlaced), Err(ImageError::Format(_))));
+    assert!(matches!(
+        decode(&paletted[..60]),
+        Err(ImageError::Format(_))
+    ));
+
+    // 24 by 12 in eight-bit RGB, the rows in turn unfiltered and filtered
+    // by the left, above, average and Paeth predictors.
+    let filtered = b"\x89\x50\x4e\x47\x0d\x0a\x1a\x0a\x00\x00\x00\x0d\x49\x48\x44\x52\x00\x00\x00\
+                     \x18\x00\x00\x00\x0c\x08\x02\x00\x00\x00\xf7\x52\xea\x22\x00\x00\x00\x40\x49\
+                     \x44\x41\x54\x78\xda\x63\xe0\x12\x91\xfb\xf5\xe1\x19\x1c\x91\xcd\x65\x04\x72\
+                     \x3e\xdc\x39\xc1\x00\x03\x02\x2a\x16\xe4\x71\x99\x18\xa8\x04\x98\x59\xb9\xf8\
+                     \x2b\xf2\x52\xe0\xfc\x8e\x49\x73\xc8\xe3\xb2\x40\x5c\x88\x6c\x36\x99\xdc\xd1\
+                     \xc0\x1e\xc9\x81\x0d\x00\x0f\x46\xef\x62\xe6\x1f\x5f\x5a\x00\x00\x00\x00\x49\
+                     \x45\x4e\x44\xae\x42\x60\x82";
+    let dark = |x: usize, y: usize| (x * y + x).is_multiple_of(5);
+    let expected = (0..12).flat_map(|y| (0..24).map(move |x| if dark(x, y) { 18 } else { 241 }));
+    assert_eq!(decode(filtered).unwrap(), ((24, 12), expected.collect()));
+}
+
+#[test]
+fn test_inflate_dynamic_huffman_blocks() {
+    // The cells of the Gosper glider gun's RLE, without its header, which
+    // zlib compresses best with codes of its own.
+    let zlib = b"\x78\xda\x3d\x4c\xc9\x0d\x00\x20\x0c\x9a\xc5\xc4\x01\x2c\xd6\x63\x1e\x16\x60\xff\
+                 \x9f\x55\xa3\x0f\xc2\x15\x80\x53\x19\xa0\x82\x0c\x84\x7a\xe0\x88\x6c\x46\x55\xca\
+                 \x7f\x00\x4d\xaa\xed\xf0\x99\xa8\x43\xfb\x5d\x97\x53\x0e\xfe\xe5\x3d\x4c\x0b\xbb\
+                 \xbe\x1d\xa2";
+    assert_eq!((zlib[2] >> 1) & 3, 2, "not a dynamic block");
+    let cells = String::from_utf8(inflate(zlib, usize::MAX).unwrap()).unwrap();
+    let rle = format!("x = 36, y = 9\n{cells}");
+    let (gun, _) = crate::rle::decode(&rle).unwrap();
+    assert_eq!(gun, crate::Pattern::named("gosper-glider-gun").unwrap());
+    let mut corrupt = zlib.to_vec();
+    *corrupt.last_mut().unwrap() ^= 1;
+    assert!(inflate(&corrupt, usize::MAX).is_err());
+    assert!(inflate(&zlib[..30], usize::MAX).is_err());
+    // However much the stream holds, no more than the limit comes out.
+    assert_eq!(inflate(zlib, 10).unwrap(), cells.as_bytes()[..10]);
+}
+
+#[test]
+fn test_decode_png_turns_down_oversized_and_truncated_streams() {
+    // Only the chunks' layout is read, not their checksums.
+    let chunk = |kind: &[u8], body: &[u8]| {
+        [&(body.len() as u32).to_be_bytes()[..], kind, body, &[0; 4]].concat()
+    };
+    let png = |(w, h): (u32, u32), zlib: &[u8]| {
+        let header = [&w.to_be_bytes()[..], &h.to_be_bytes(), &[8, 0, 0, 0, 0]].concat();
+        [
+            PNG_SIGNATURE,
+            &chunk(b"IHDR", &header),
+            &chunk(b"IDAT", zlib),
+            &chunk(b"IEND", &[]),
+        ]
+        .concat()
+    };
+    // Two rows of two gray pixels in a stored block.
+    let rows = [0, 10, 20, 0, 30, 40];
+    let zlib = [
+        &[0x78, 0x01, 0x01, 6, 0, !6, 0xff][..],
+        &rows,
+        &adler32(&rows).to_be_bytes(),
+    ]
+    .concat();
+    assert_eq!(
+        decode(&png((2, 2), &zlib)).unwrap(),
+        ((2, 2), vec![10, 20, 30, 40])
+    );
+    // A shorter image takes only the rows it needs.
+    assert_eq!(decode(&png((2, 1), &zlib)).unwrap(), ((2, 1), vec![10, 20]));
+    assert!(matches!(
+        decode(&png((2, 2), &zlib[..zlib.len() - 5])),
+        Err(ImageError::Format(_))
+    ));
+
+    // The header is turned down before the stream is even looked at.
+    for size in [(u32::MAX, u32::MAX), (1 << 14, 1 << 14)] {
+        let Err(ImageError::Format(reason)) = decode(&png(size, b"")) else {
+            panic!("{size:?} decoded");
+        };
+        assert_eq!(reason, "PNG too large");
+    }
+}
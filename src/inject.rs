@@ -0,0 +1,180 @@
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{GameOfLife, GridPos};
+
+/// A side of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Edge {
+    #[default]
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl FromStr for Edge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Edge::Top),
+            "bottom" => Ok(Edge::Bottom),
+            "left" => Ok(Edge::Left),
+            "right" => Ok(Edge::Right),
+            _ => Err(format!(
+                "unknown edge {s:?}, expected top, bottom, left or right"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+            Edge::Left => "left",
+            Edge::Right => "right",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where along an edge injected cells land.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spread {
+    /// Anywhere, equally likely.
+    #[default]
+    Uniform,
+    /// Mostly near the middle, thinning out linearly to the corners.
+    Middle,
+}
+
+impl FromStr for Spread {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Spread::Uniform),
+            "middle" => Ok(Spread::Middle),
+            _ => Err(format!("unknown spread {s:?}, expected uniform or middle")),
+        }
+    }
+}
+
+/// Brings random cells along one edge of the board to life after every
+/// step, like rain, so the board never settles. Injection happens at the
+/// edge even when the board wraps.
+///
+/// Injected cells aren't births: no step made them. They are counted in
+/// [`injected`](Self::injected) instead, though they show up in the
+/// population like any other live cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Injector {
+    pub edge: Edge,
+    /// Cells injected per generation, on average; fractions build up from one
+    /// generation to the next.
+    pub rate: f64,
+    pub spread: Spread,
+    rng: StdRng,
+    /// The fraction of a cell owed from earlier generations.
+    owed: f64,
+    /// Cells brought to life so far. Picks that land on live or forced cells
+    /// don't count.
+    pub injected: u64,
+}
+
+impl Injector {
+    /// An injector picking cells at random from `seed`.
+    pub fn new(edge: Edge, rate: f64, spread: Spread, seed: u64) -> Self {
+        Self {
+            edge,
+            rate,
+            spread,
+            rng: StdRng::seed_from_u64(seed),
+            owed: 0.0,
+            injected: 0,
+        }
+    }
+
+    /// Picks this generation's share of [`rate`](Self::rate) cells along the
+    /// edge and brings them to life, returning how many weren't already.
+    pub fn inject(&mut self, game: &mut GameOfLife) -> usize {
+        self.owed += self.rate.max(0.0);
+        let picks = self.owed.floor();
+        self.owed -= picks;
+        let (w, h) = game.size();
+        let length = match self.edge {
+            Edge::Top | Edge::Bottom => w,
+            Edge::Left | Edge::Right => h,
+        };
+        let mut injected = 0;
+        for _ in 0..picks as usize {
+            let along = self.along(length);
+            let (x, y) = match self.edge {
+                Edge::Top => (along, 0),
+                Edge::Bottom => (along, h - 1),
+                Edge::Left => (0, along),
+                Edge::Right => (w - 1, along),
+            };
+            if !game.get(GridPos(x, y)) && !game.is_forced(GridPos(x, y)) {
+                game.set(GridPos(x, y), true);
+                injected += 1;
+            }
+        }
+        self.injected += injected as u64;
+        injected
+    }
+
+    /// A position along an edge `length` cells long.
+    fn along(&mut self, length: usize) -> usize {
+        match self.spread {
+            Spread::Uniform => self.rng.gen_range(0..length),
+            // The mean of two uniform picks is triangular about the middle.
+            Spread::Middle => (self.rng.gen_range(0..length) + self.rng.gen_range(0..length)) / 2,
+        }
+    }
+}
+
+#[test]
+fn test_injection_rate_and_spread() {
+    // Half a cell per generation is one every other generation.
+    let mut game = GameOfLife::new_empty((200, 5));
+    let mut injector = Injector::new(Edge::Bottom, 0.5, Spread::Uniform, 3);
+    let counts = (0..4)
+        .map(|_| injector.inject(&mut game))
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [0, 1, 0, 1]);
+    assert_eq!((injector.injected, game.population()), (2, 2));
+    assert!((0..200).all(|x| (0..4).all(|y| !game.get(GridPos(x, y)))));
+    // Landing on a live cell does nothing and isn't counted.
+    game.fill(1.0, 0);
+    injector.rate = 3.0;
+    assert_eq!(injector.inject(&mut game), 0);
+    assert_eq!(injector.injected, 2);
+
+    // Picks spread evenly along the edge, or bunch up in the middle.
+    let hits = |spread| {
+        let mut injector = Injector::new(Edge::Left, 1.0, spread, 9);
+        let mut hits = [0; 10];
+        for _ in 0..5000 {
+            let mut game = GameOfLife::new_empty((3, 10));
+            injector.inject(&mut game);
+            let y = (0..10).find(|&y| game.get(GridPos(0, y))).unwrap();
+            hits[y] += 1;
+        }
+        hits
+    };
+    let uniform = hits(Spread::Uniform);
+    assert!(
+        uniform.iter().all(|&n| (400..600).contains(&n)),
+        "{uniform:?}"
+    );
+    let middle = hits(Spread::Middle);
+    assert!(
+        middle[4] > 3 * middle[0] && middle[5] > 3 * middle[9],
+        "{middle:?}"
+    );
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+
+use crate::{
+    game::{reflect, Stepped},
+    EdgeMode, GameOfLife, GridPos, GridStorage, Rule, MAX_RANGE,
+};
+
+/// A square of integer weights centered on a cell, odd on a side and reaching
+/// at most [`MAX_RANGE`] cells out, summed over the live cells under it to
+/// decide the cell's next state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Kernel {
+    side: usize,
+    /// Row-major, top row first: the weight of the cell `(dx, dy)` away is at
+    /// `(dy + reach) * side + dx + reach`.
+    weights: Vec<i32>,
+}
+
+/// A kernel split into a weight for each column and one for each row, so it
+/// is summed along rows and then down columns rather than cell by cell.
+///
+/// Every weight but the center's is `row[dx] * column[dy] / scale`; the
+/// center's is off from that by `center / scale`, which is how kernels that
+/// leave the cell itself out, Moore's among them, still split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Separable {
+    row: Vec<i64>,
+    column: Vec<i64>,
+    scale: i64,
+    center: i64,
+}
+
+impl Kernel {
+    /// A kernel from its rows, top first, which must make an odd square no
+    /// more than `2 * MAX_RANGE + 1` on a side.
+    pub fn from_rows(rows: &[Vec<i32>]) -> Result<Self, String> {
+        let side = rows.len();
+        if side.is_multiple_of(2) {
+            return Err(format!("a kernel must be odd on a side, got {side} rows"));
+        }
+        if side > 2 * MAX_RANGE + 1 {
+            let most = 2 * MAX_RANGE + 1;
+            return Err(format!(
+                "a kernel can be at most {most} on a side, got {side} rows"
+            ));
+        }
+        if let Some((n, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != side) {
+            return Err(format!(
+                "a kernel must be square, but row {} of {side} has {} weights",
+                n + 1,
+                row.len()
+            ));
+        }
+        Ok(Self {
+            side,
+            weights: rows.concat(),
+        })
+    }
+
+    /// A kernel row of weights separated by spaces or commas, such as
+    /// `1 2 1`.
+    pub fn parse_row(s: &str) -> Result<Vec<i32>, String> {
+        s.split([' ', ',', '\t'])
+            .filter(|weight| !weight.is_empty())
+            .map(|weight| {
+                weight
+                    .parse()
+                    .map_err(|_| format!("bad kernel weight {weight:?}"))
+            })
+            .collect()
+    }
+
+    /// The kernel a rule counts neighbors with: a weight of one on each of
+    /// its neighbors and nothing elsewhere.
+    pub fn of_rule(rule: &Rule) -> Self {
+        let r = rule.range() as isize;
+        let side = 2 * rule.range() + 1;
+        let mut weights = vec![0; side * side];
+        for (dx, dy) in rule.neighbor_offsets() {
+            weights[((dy + r) * side as isize + dx + r) as usize] = 1;
+        }
+        Self { side, weights }
+    }
+
+    pub fn side(&self) -> usize {
+        self.side
+    }
+
+    /// How many cells out from the center the kernel reaches.
+    pub fn reach(&self) -> usize {
+        self.side / 2
+    }
+
+    /// The weight of the cell `(dx, dy)` away from the center.
+    pub fn weight(&self, dx: isize, dy: isize) -> i32 {
+        let r = self.reach() as isize;
+        self.weights[((dy + r) * self.side as isize + dx + r) as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[i32]> {
+        self.weights.chunks(self.side)
+    }
+
+    /// The weights as rows and columns, if every weight off the center is the
+    /// product of the two.
+    fn separable(&self) -> Option<Separable> {
+        let (side, c) = (self.side, self.reach());
+        let at = |i: usize, j: usize| self.weights[i * side + j] as i64;
+        // A weight off the center's row and column, to scale the rest by.
+        let (i0, j0) = (0..side)
+            .flat_map(|i| (0..side).map(move |j| (i, j)))
+            .find(|&(i, j)| i != c && j != c && at(i, j) != 0)?;
+        let scale = at(i0, j0);
+        let split = (0..side)
+            .flat_map(|i| (0..side).map(move |j| (i, j)))
+            .filter(|&(i, j)| (i, j) != (c, c))
+            .all(|(i, j)| at(i, j) * scale == at(i, j0) * at(i0, j));
+        split.then(|| Separable {
+            row: (0..side).map(|j| at(i0, j)).collect(),
+            column: (0..side).map(|i| at(i, j0)).collect(),
+            scale,
+            center: at(c, j0) * at(i0, c) - at(c, c) * scale,
+        })
+    }
+}
+
+impl fmt::Display for Kernel {
+    /// The rows one to a line, as [`Kernel::parse_row`] reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, row) in self.rows().enumerate() {
+            if n > 0 {
+                writeln!(f)?;
+            }
+            let row = row.iter().map(i32::to_string).collect::<Vec<_>>();
+            write!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A rule over a [`Kernel`]'s weighted sum of live cells, rather than a count
+/// of live neighbors: which sums bring a dead cell to life, and which keep a
+/// live one alive, as inclusive ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KernelRule {
+    kernel: Kernel,
+    birth: Vec<(i64, i64)>,
+    survival: Vec<(i64, i64)>,
+}
+
+impl KernelRule {
+    pub fn new(kernel: Kernel, birth: Vec<(i64, i64)>, survival: Vec<(i64, i64)>) -> Self {
+        Self {
+            kernel,
+            birth,
+            survival,
+        }
+    }
+
+    pub fn kernel(&self) -> &Kernel {
+        &self.kernel
+    }
+
+    /// The next state of a cell with the given state and weighted sum.
+    pub fn next(&self, cell: bool, sum: i64) -> bool {
+        let ranges = if cell { &self.survival } else { &self.birth };
+        ranges.iter().any(|&(from, to)| (from..=to).contains(&sum))
+    }
+
+    /// Sums separated by commas, each a number, which may be negative, or an
+    /// inclusive `a..b` range, such as `-2..3, 5`.
+    pub fn parse_sums(s: &str) -> Result<Vec<(i64, i64)>, String> {
+        let number = |n: &str| {
+            n.trim()
+                .parse::<i64>()
+                .map_err(|_| format!("bad kernel sum {:?}", n.trim()))
+        };
+        let mut ranges = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (from, to) = match part.split_once("..") {
+                Some((from, to)) => (number(from)?, number(to)?),
+                None => (number(part)?, number(part)?),
+            };
+            if from > to {
+                return Err(format!("kernel sum range {part:?} runs backwards"));
+            }
+            ranges.push((from, to));
+        }
+        Ok(ranges)
+    }
+
+    /// The ranges as [`parse_sums`](Self::parse_sums) reads them back, birth
+    /// first.
+    pub fn sums(&self) -> (String, String) {
+        let write = |ranges: &[(i64, i64)]| {
+            let ranges = ranges.iter().map(|&(from, to)| match from == to {
+                true => from.to_string(),
+                false => format!("{from}..{to}"),
+            });
+            ranges.collect::<Vec<_>>().join(", ")
+        };
+        (write(&self.birth), write(&self.survival))
+    }
+}
+
+impl From<Rule> for KernelRule {
+    /// The rule over its own neighborhood as a kernel, which steps exactly as
+    /// the rule does.
+    fn from(rule: Rule) -> Self {
+        let counts = |survival| {
+            (0..=rule.neighbor_count())
+                .filter(|&n| rule.next(survival, n))
+                .map(|n| (n as i64, n as i64))
+                .collect()
+        };
+        Self::new(Kernel::of_rule(&rule), counts(false), counts(true))
+    }
+}
+
+/// Where `i` lands along an axis `n` long under `edges`, if anywhere.
+fn land(i: isize, n: usize, edges: EdgeMode) -> Option<usize> {
+    match edges {
+        EdgeMode::Bounded => usize::try_from(i).ok().filter(|&i| i < n),
+        EdgeMode::Toroidal => Some(i.rem_euclid(n as isize) as usize),
+        EdgeMode::Mirror => Some(reflect(i, n)),
+    }
+}
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// The kernel rule the board steps by in place of its [`Rule`], if any.
+    pub fn kernel_rule(&self) -> Option<&KernelRule> {
+        self.kernel.as_ref()
+    }
+
+    /// Has [`step`](Self::step) and [`tick`](Self::tick) follow `rule` over
+    /// the board's edges, or its [`Rule`] again if `None`. Setting a
+    /// [`Rule`] takes this away.
+    pub fn set_kernel_rule(&mut self, rule: Option<KernelRule>) {
+        self.kernel = rule;
+    }
+
+    /// The weighted sum of live cells under `kernel` centered on `pos`,
+    /// with the cells beyond the edges found as
+    /// [`neighbors`](Self::neighbors) finds them.
+    pub fn weighted_sum(&self, kernel: &Kernel, pos: GridPos) -> i64 {
+        let (GridPos(x, y), (w, h)) = (pos, self.size());
+        let r = kernel.reach() as isize;
+        let mut sum = 0;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let weight = kernel.weight(dx, dy);
+                let at = land(x as isize + dx, w, self.edge_mode()).zip(land(
+                    y as isize + dy,
+                    h,
+                    self.edge_mode(),
+                ));
+                if let Some((i, j)) = at.filter(|_| weight != 0) {
+                    sum += weight as i64 * self.get(GridPos(i, j)) as i64;
+                }
+            }
+        }
+        sum
+    }
+
+    /// Every cell's [`weighted_sum`](Self::weighted_sum), row-major, into
+    /// `sums`. A kernel that splits into rows and columns is summed along
+    /// each row and then down each column of those sums; any other is summed
+    /// weight by weight.
+    pub fn weighted_sums_into(&self, kernel: &Kernel, sums: &mut Vec<i64>) {
+        let (w, h) = self.size();
+        let edges = self.edge_mode();
+        let r = kernel.reach() as isize;
+        let mut cells = vec![false; w * h];
+        for (y, row) in cells.chunks_mut(w).enumerate() {
+            self.storage().read_row(y, row);
+        }
+        sums.clear();
+        sums.resize(w * h, 0);
+        let Some(split) = kernel.separable() else {
+            for (dy, row) in (-r..=r).zip(kernel.rows()) {
+                for (dx, &weight) in (-r..=r).zip(row).filter(|(_, &weight)| weight != 0) {
+                    for y in 0..h {
+                        let Some(j) = land(y as isize + dy, h, edges) else {
+                            continue;
+                        };
+                        for x in 0..w {
+                            if let Some(i) = land(x as isize + dx, w, edges) {
+                                sums[y * w + x] += weight as i64 * cells[j * w + i] as i64;
+                            }
+                        }
+                    }
+                }
+            }
+            return;
+        };
+        // Along the rows, then down the columns of what that gave.
+        let mut across = vec![0; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                across[y * w + x] = (-r..=r)
+                    .zip(&split.row)
+                    .filter_map(|(dx, &weight)| {
+                        land(x as isize + dx, w, edges).map(|i| weight * cells[y * w + i] as i64)
+                    })
+                    .sum();
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let down: i64 = (-r..=r)
+                    .zip(&split.column)
+                    .filter_map(|(dy, &weight)| {
+                        land(y as isize + dy, h, edges).map(|j| weight * across[j * w + x])
+                    })
+                    .sum();
+                let own = split.center * cells[y * w + x] as i64;
+                sums[y * w + x] = (down - own) / split.scale;
+            }
+        }
+    }
+
+    /// Steps by the board's kernel rule, which it must have.
+    pub(crate) fn advance_weighted(&mut self) -> Stepped {
+        let rule = self.kernel.take().expect("a kernel rule to step by");
+        let mut sums = Vec::new();
+        self.weighted_sums_into(&rule.kernel, &mut sums);
+        let stepped = self.advance_by(|i, cell| rule.next(cell, sums[i]));
+        self.kernel = Some(rule);
+        stepped
+    }
+}
+
+#[test]
+fn test_kernels_must_be_odd_squares() {
+    let rows = |text: &str| {
+        text.split(';')
+            .map(|row| Kernel::parse_row(row).unwrap())
+            .collect()
+    };
+    let even: Vec<_> = rows("1 1; 1 1");
+    let err = Kernel::from_rows(&even).unwrap_err();
+    assert_eq!(err, "a kernel must be odd on a side, got 2 rows");
+    assert!(Kernel::from_rows(&rows("1 1 1 1; 1 0 1 1; 1 1 1 1; 1 1 1 1")).is_err());
+    let err = Kernel::from_rows(&rows("1 1 1; 1 0; 1 1 1")).unwrap_err();
+    assert!(err.contains("row 2 of 3 has 2"), "{err}");
+    assert!(Kernel::from_rows(&vec![vec![1; 9]; 9]).is_err());
+    assert!(Kernel::parse_row("1 x 1").is_err());
+
+    let moore = Kernel::from_rows(&rows("1, 1, 1; 1 0 1; 1 1 1")).unwrap();
+    assert_eq!(moore, Kernel::of_rule(&Rule::CONWAY));
+    assert_eq!(moore.to_string(), "1 1 1\n1 0 1\n1 1 1");
+    assert_eq!(
+        KernelRule::parse_sums("-2..3, 5"),
+        Ok(vec![(-2, 3), (5, 5)])
+    );
+    assert!(KernelRule::parse_sums("3..2").is_err());
+}
+
+#[test]
+fn test_moore_kernel_steps_as_conway() {
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+        let mut life = GameOfLife::new_empty((40, 30));
+        life.set_edge_mode(edges);
+        life.fill(0.35, 21);
+        let mut kernel = life.clone();
+        kernel.set_kernel_rule(Some(KernelRule::from(Rule::CONWAY)));
+        // The square around a cell splits into rows and columns, less the
+        // cell itself.
+        assert!(Kernel::of_rule(&Rule::CONWAY).separable().is_some());
+        for _ in 0..60 {
+            assert_eq!(kernel.tick(), life.tick());
+            assert_eq!(
+                kernel.checksum(),
+                life.checksum(),
+                "{edges} at {}",
+                life.generation()
+            );
+        }
+    }
+    // A diamond doesn't split, and is summed weight by weight alike.
+    let diamond = Rule::parse("R2,C0,M0,S2..4,B3..4,NN").unwrap();
+    assert!(Kernel::of_rule(&diamond).separable().is_none());
+    let mut life = GameOfLife::new_empty((24, 24));
+    life.set_rule(diamond);
+    life.fill(0.3, 5);
+    let mut kernel = life.clone();
+    kernel.set_kernel_rule(Some(diamond.into()));
+    for _ in 0..30 {
+        life.step();
+        kernel.step();
+        assert_eq!(kernel.checksum(), life.checksum());
+    }
+}
+
+#[test]
+fn test_weighted_kernels() {
+    // Only the cell to the right counts, once: everything moves one cell
+    // left a step, and comes back round a torus its width later.
+    let right = Kernel::from_rows(&[vec![0, 0, 0], vec![0, 0, 1], vec![0, 0, 0]]).unwrap();
+    let mut game = GameOfLife::new_empty((13, 7));
+    game.set_edge_mode(EdgeMode::Toroidal);
+    game.fill(0.5, 3);
+    let start = game.clone();
+    game.set_kernel_rule(Some(KernelRule::new(right, vec![(1, 1)], vec![(1, 1)])));
+    game.step();
+    let mut shifted = start.clone();
+    shifted.translate(-1, 0);
+    assert_eq!(game.checksum(), shifted.checksum());
+    for _ in 1..13 {
+        game.step();
+    }
+    assert_eq!(game.checksum(), start.checksum());
+
+    // A blurred kernel, with the cell itself weighed heaviest, splits; its
+    // sums are those taken weight by weight at every edge.
+    let blur = Kernel::from_rows(&[vec![1, 2, 1], vec![2, 4, 2], vec![1, 2, 1]]).unwrap();
+    let skewed = Kernel::from_rows(&[vec![2, -1, 2], vec![0, 3, 1], vec![1, 2, 1]]).unwrap();
+    assert!(blur.separable().is_some() && skewed.separable().is_none());
+    let mut game = GameOfLife::new_empty((17, 11));
+    game.fill(0.4, 8);
+    let mut sums = Vec::new();
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+        game.set_edge_mode(edges);
+        for kernel in [&blur, &skewed] {
+            game.weighted_sums_into(kernel, &mut sums);
+            for (i, &sum) in sums.iter().enumerate() {
+                let GridPos(x, y) = game.coords(i);
+                assert_eq!(
+                    sum,
+                    game.weighted_sum(kernel, GridPos(x, y)),
+                    "{edges} at {x},{y}"
+                );
+            }
+        }
+    }
+    // A lone live cell under the blur is born around by weight: four at the
+    // sides, which weigh 2, and nothing at the corners, which weigh 1.
+    let mut game = GameOfLife::from_ascii(".....\n.....\n..#..\n.....\n.....");
+    game.set_kernel_rule(Some(KernelRule::new(blur, vec![(2, 3)], vec![])));
+    game.step();
+    assert_eq!(game.population(), 4);
+    let plus = [GridPos(2, 1), GridPos(1, 2), GridPos(3, 2), GridPos(2, 3)];
+    assert!(plus.into_iter().all(|cell| game.get(cell)));
+}
@@ -0,0 +1,80 @@
+mod activity;
+mod annotation;
+mod census;
+mod clock;
+mod coords;
+mod cycle;
+mod diagnostics;
+mod edit;
+mod fixed;
+mod game;
+mod grid;
+mod history;
+#[cfg(feature = "image")]
+mod image;
+mod inject;
+mod kernel;
+mod mask;
+mod neighbors;
+mod observer;
+mod palette;
+mod pattern;
+mod rule;
+mod ruletable;
+mod storage;
+#[cfg(feature = "tags")]
+mod tags;
+#[cfg(test)]
+mod testing;
+mod trace;
+
+pub mod app;
+pub mod archive;
+pub mod autosave;
+pub mod capture;
+pub mod challenge;
+pub mod chart;
+pub mod doctor;
+pub mod feedback;
+pub mod frontend;
+pub mod macrocell;
+pub mod metadata;
+pub mod perturb;
+pub mod publish;
+pub mod query;
+pub mod raster;
+pub mod rle;
+pub mod scene;
+pub mod serve;
+pub mod stats;
+pub mod territory;
+pub mod tune;
+pub mod verify;
+
+pub use annotation::Annotation;
+pub use census::{Census, Spaceship};
+pub use clock::{utc_stamp, RunClock};
+pub use coords::{GridPos, ScreenPos, WorldPos};
+pub use cycle::{
+    exit_code, run_until_settled, run_until_settled_observing, CycleDetector, Settled,
+};
+pub use diagnostics::{Diagnostics, FrameStats, FrameTimes, StepTally};
+pub use edit::Edit;
+pub use fixed::ConstGame;
+pub use game::{
+    Axis, CellInfo, EdgeMode, GameOfLife, MergeMode, PlacementResult, Rect, INIT_DENSITY, NEIGHBORS,
+};
+pub use grid::{GridView, NextView, Viewport};
+pub use history::{Bookmark, Divergence, Frame, History};
+#[cfg(feature = "image")]
+pub use image::ImageError;
+pub use inject::{Edge, Injector, Spread};
+pub use kernel::{Kernel, KernelRule};
+pub use mask::{Mask, MaskCell};
+pub use neighbors::NeighborCounts;
+pub use observer::{SimObserver, StepEvent};
+pub use palette::{luminance, parse_background_reply, Palette, Theme};
+pub use pattern::Pattern;
+pub use rule::{Neighborhood, Rule, MAX_RANGE};
+pub use storage::{ChunkedTiles, ColumnMajor, DenseBool, GridStorage, PackedWords, TILE};
+pub use trace::{CellChange, StepTrace};
@@ -0,0 +1,243 @@
+use crate::{Pattern, Rule};
+
+/// Largest pattern, in cells of its bounding box, that [`decode`] will expand.
+pub const MAX_CELLS: usize = 1 << 26;
+
+/// Level of the 8×8 leaf blocks.
+const LEAF_LEVEL: u32 = 3;
+
+/// Deepest level whose side still fits comfortably in a `usize`.
+const MAX_LEVEL: u32 = 60;
+
+/// A node of the quadtree, as read: a leaf's rows as bitmasks with bit `x`
+/// for column `x`, or the ids of a node's four quadrants.
+enum Node {
+    Leaf([u8; 8]),
+    Branch([usize; 4]),
+}
+
+/// What is known about a node without expanding it.
+struct Info {
+    level: u32,
+    /// The live cells' bounding box, inclusive, relative to the node's
+    /// top-left corner; `None` if it is empty.
+    bounds: Option<(usize, usize, usize, usize)>,
+}
+
+/// Reads a two-state Golly macrocell (`.mc`) file into a pattern and its rule,
+/// which is Conway's when no `#R` line names one. The pattern is trimmed to
+/// its live cells. Patterns bigger than [`MAX_CELLS`] are refused with their
+/// dimensions rather than expanded.
+pub fn decode(text: &str) -> Result<(Pattern, Rule), String> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    match lines.next() {
+        Some(header) if header.starts_with("[M2]") => {}
+        _ => return Err("missing macrocell header [M2]".into()),
+    }
+    let mut rule = Rule::default();
+    // Id 0 is the empty node of any level; real ones are numbered from 1.
+    let mut nodes = vec![(
+        Node::Branch([0; 4]),
+        Info {
+            level: 0,
+            bounds: None,
+        },
+    )];
+    for line in lines {
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some(value) = comment.strip_prefix('R') {
+                rule = Rule::parse(value.trim())?;
+            }
+            continue;
+        }
+        let id = nodes.len();
+        let node = if line.starts_with(['.', '*', '$']) {
+            leaf(line).ok_or_else(|| format!("bad macrocell leaf {line:?} (node {id})"))?
+        } else {
+            branch(line, &nodes).map_err(|err| format!("{err} (node {id})"))?
+        };
+        nodes.push(node);
+    }
+    if nodes.len() == 1 {
+        return Ok((Pattern::default(), rule));
+    }
+    let root = nodes.len() - 1;
+    let Some((x0, y0, x1, y1)) = nodes[root].1.bounds else {
+        return Ok((Pattern::default(), rule));
+    };
+    let (w, h) = (x1 - x0 + 1, y1 - y0 + 1);
+    if w.saturating_mul(h) > MAX_CELLS {
+        return Err(format!(
+            "macrocell pattern is {w}x{h}, larger than the limit of {MAX_CELLS} cells"
+        ));
+    }
+    let mut cells = Vec::new();
+    expand(&nodes, root, (0, 0), &mut cells);
+    let cells = cells.into_iter().map(|(x, y)| (x - x0, y - y0)).collect();
+    Ok((Pattern::with_size((w, h), cells), rule))
+}
+
+/// An 8×8 leaf: rows ended by `$`, with `.` for dead cells and `*` for live
+/// ones. Trailing dead cells and rows may be left out.
+fn leaf(line: &str) -> Option<(Node, Info)> {
+    let mut rows = [0u8; 8];
+    let (mut x, mut y) = (0, 0);
+    for c in line.chars() {
+        match c {
+            '$' => (x, y) = (0, y + 1),
+            '.' | '*' if x < 8 && y < 8 => {
+                if c == '*' {
+                    rows[y] |= 1 << x;
+                }
+                x += 1;
+            }
+            _ => return None,
+        }
+    }
+    let live = |y: usize| rows[y] != 0;
+    let bounds = (0..8).find(|&y| live(y)).map(|top| {
+        let bottom = (0..8).rev().find(|&y| live(y)).unwrap_or(top);
+        let columns = rows.iter().fold(0, |columns, row| columns | row);
+        let (left, right) = (columns.trailing_zeros(), 7 - columns.leading_zeros());
+        (left as usize, top, right as usize, bottom)
+    });
+    Some((
+        Node::Leaf(rows),
+        Info {
+            level: LEAF_LEVEL,
+            bounds,
+        },
+    ))
+}
+
+/// `LEVEL NW NE SW SE`: a node of side 2^LEVEL made of four earlier nodes
+/// one level down, by id.
+fn branch(line: &str, nodes: &[(Node, Info)]) -> Result<(Node, Info), String> {
+    let numbers = line
+        .split_whitespace()
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("bad macrocell node {line:?}"))?;
+    let &[level, nw, ne, sw, se] = numbers.as_slice() else {
+        return Err(format!(
+            "bad macrocell node {line:?}, expected LEVEL NW NE SW SE"
+        ));
+    };
+    let level = level as u32;
+    if level <= LEAF_LEVEL {
+        return Err(format!(
+            "unsupported macrocell node level {level}; only two-state files are"
+        ));
+    }
+    if level > MAX_LEVEL {
+        return Err(format!("macrocell node level {level} is too deep"));
+    }
+    let children = [nw, ne, sw, se];
+    let half = 1 << (level - 1);
+    let mut bounds = None::<(usize, usize, usize, usize)>;
+    for (i, &child) in children.iter().enumerate() {
+        let info = match nodes.get(child) {
+            Some((_, info)) => info,
+            None => return Err(format!("macrocell node refers to later node {child}")),
+        };
+        if child != 0 && info.level != level - 1 {
+            return Err(format!(
+                "macrocell node of level {level} has a child of level {}",
+                info.level
+            ));
+        }
+        let Some((x0, y0, x1, y1)) = info.bounds else {
+            continue;
+        };
+        let (dx, dy) = (i % 2 * half, i / 2 * half);
+        let child = (x0 + dx, y0 + dy, x1 + dx, y1 + dy);
+        bounds = Some(match bounds {
+            Some((a, b, c, d)) => (
+                a.min(child.0),
+                b.min(child.1),
+                c.max(child.2),
+                d.max(child.3),
+            ),
+            None => child,
+        });
+    }
+    Ok((Node::Branch(children), Info { level, bounds }))
+}
+
+/// Collects the live cells of node `id`, whose top-left is at `origin`.
+fn expand(
+    nodes: &[(Node, Info)],
+    id: usize,
+    origin: (usize, usize),
+    cells: &mut Vec<(usize, usize)>,
+) {
+    let (node, info) = &nodes[id];
+    if info.bounds.is_none() {
+        return;
+    }
+    match node {
+        Node::Leaf(rows) => {
+            for (y, row) in rows.iter().enumerate() {
+                for x in (0..8).filter(|x| row & 1 << x != 0) {
+                    cells.push((origin.0 + x, origin.1 + y));
+                }
+            }
+        }
+        Node::Branch(children) => {
+            let half = 1 << (info.level - 1);
+            for (i, &child) in children.iter().enumerate() {
+                let at = (origin.0 + i % 2 * half, origin.1 + i / 2 * half);
+                expand(nodes, child, at, cells);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_macrocell_decode() {
+    let (glider, rule) = decode("[M2] (golly 4.2)\n#R B3/S23\n.*$..*$***$\n").unwrap();
+    assert_eq!(rule, Rule::CONWAY);
+    assert_eq!(
+        glider,
+        Pattern::new(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+
+    // A block in the north-west leaf and a cell near the end of the
+    // south-east one.
+    let text = "[M2]\n#R B36/S23\n#G 12\n**$**$\n$$$$$$$.*$\n4 1 0 0 2\n";
+    let (pattern, rule) = decode(text).unwrap();
+    assert_eq!(rule.to_string(), "B36/S23");
+    assert_eq!(pattern.size(), (10, 16));
+    assert_eq!(pattern.cells(), [(0, 0), (1, 0), (0, 1), (1, 1), (9, 15)]);
+
+    // Trimmed to the live cells, wherever they are in the tree.
+    let (shifted, _) = decode("[M2]\n$$$...**$...**$\n4 0 0 0 1\n5 0 0 2 0\n").unwrap();
+    assert_eq!(shifted, Pattern::new(vec![(0, 0), (1, 0), (0, 1), (1, 1)]));
+    assert_eq!(decode("[M2]\n4 0 0 0 0\n").unwrap().0, Pattern::default());
+}
+
+#[test]
+fn test_macrocell_errors() {
+    for bad in [
+        "",
+        "x = 3, y = 3\nbo$2bo$3o!",
+        "[M2]\n.*x$\n",
+        "[M2]\n*********$\n",
+        "[M2]\n4 2 0 0 0\n",
+        "[M2]\n*$\n5 1 0 0 0\n",
+        "[M2]\n*$\n4 1 0 0\n",
+        "[M2]\n1 0 0 0 1\n",
+        "[M2]\n#R B3/Q23\n*$\n",
+    ] {
+        assert!(decode(bad).is_err(), "{bad:?}");
+    }
+
+    // Cells in opposite corners of a level-16 tree span nearly 2^16 across.
+    let mut text = String::from("[M2]\n*$\n");
+    for level in 4..=16 {
+        let child = level - 3;
+        text += &format!("{level} {child} 0 0 {child}\n");
+    }
+    let err = decode(&text).unwrap_err();
+    assert!(err.contains("65529x65529"), "{err}");
+}
@@ -1,351 +1,1592 @@
 use std::{
-    error::Error,
-    time::{Duration, Instant},
+    io::IsTerminal,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use braille::BRAILLE;
-use rand::Rng;
+use gol::{
+    app::{
+        failure_code, oversize, run_app, terminal_failure, AppConfig, Budget, Launch, Mitigation,
+        Source, Usage, CELL_BUDGET, NO_TERMINAL,
+    },
+    archive::{FrameReader, FrameWriter},
+    autosave::{state_dir, Autosave},
+    capture::{Capture, Capturer},
+    challenge::{Challenge, ChallengeConfig, HighScores},
+    doctor::{probe, Findings},
+    exit_code,
+    feedback::Feedback,
+    frontend::{
+        grown, query_background, run_challenge, run_compare, run_confirm, run_menu, run_observe,
+        run_replay, run_territory, startup_dimensions, supports_sync, BlockRenderer,
+        BrailleRenderer, Choice, Glyphs, KeyPreset, NullRenderer, Options, Renderer, Scale,
+        ScreensaverConfig, SlowMotion, Smooth, SweepConfig, DELAY, FALLBACK_TERMINAL, SMOOTH_FRAME,
+    },
+    perturb::{Distance, Perturbation},
+    query::{Query, Run, ASSERTION_FAILED},
+    run_until_settled_observing,
+    scene::{Scene, Transform},
+    serve::Feed,
+    stats::{run_soups, SoupConfig, SoupStats},
+    territory::{Territory, TerritoryConfig},
+    tune::{measure, pick, Tuning, BUDGET, ENGINES},
+    verify::verify_engines,
+    Axis, CellChange, CycleDetector, Edge, EdgeMode, Frame, GameOfLife, GridPos, Injector, Mask,
+    MaskCell, Palette, Pattern, Rect, Rule, Settled, Spread, StepTally, StepTrace, Theme,
+};
 use termwiz::{
-    caps::Capabilities,
-    input::{MouseButtons, MouseEvent},
-    surface::{Change, CursorVisibility},
+    caps::{Capabilities, ColorLevel},
+    input::KeyCode,
     terminal::{buffered::BufferedTerminal, new_terminal, Terminal},
 };
 
-pub trait Frontend {
-    fn run(&mut self, game: &mut GameOfLife) -> Result<(), Box<dyn Error>>;
+/// Pixels darker than this are live when seeding from an image.
+#[cfg(feature = "image")]
+const IMAGE_THRESHOLD: u8 = 128;
+
+/// How much of the board the default random soup fills.
+const SOUP_DENSITY: f64 = 0.7;
+
+/// How many guns `--demo guns` sets up.
+const DEMO_GUNS: usize = 4;
+
+/// Where the `verify` subcommand writes what it found when an engine goes
+/// wrong, unless told otherwise.
+const DEBUG_DIR: &str = "divergence";
+
+/// Longest cycle the `headless` subcommand notices the board settling into.
+const HEADLESS_MAX_PERIOD: usize = 32;
+
+/// How long to wait for the terminal to say what its background color is
+/// before taking it to be dark.
+const BACKGROUND_QUERY: Duration = Duration::from_millis(100);
+
+/// How much `--sweep` raises the density between soups by default.
+const SWEEP_STEP: f64 = 0.05;
+
+/// The environment variable giving a default delay between generations, in
+/// milliseconds, for when `--delay` isn't given.
+const DELAY_VAR: &str = "GOL_DELAY_MS";
+
+/// The environment variable naming the [`Glyphs`] the braille renderer
+/// packs cells into, for when `--glyphs` isn't given.
+const GLYPHS_VAR: &str = "GOL_GLYPHS";
+
+/// The cells per generation `--inject` adds when `--inject-rate` isn't given.
+const INJECT_RATE: f64 = 1.0;
+
+/// Command line options.
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    /// World width in cells; defaults to what fits on screen.
+    pub width: Option<usize>,
+    /// World height in cells; defaults to what fits on screen.
+    pub height: Option<usize>,
+    /// Play a two-player territory game instead of watching.
+    pub territory: Option<TerritoryConfig>,
+    /// Play a population-cap challenge instead of watching.
+    pub challenge: Option<ChallengeConfig>,
+    /// Play back a frame archive recorded by `headless --record` instead.
+    pub replay: Option<std::path::PathBuf>,
+    /// Watch a game served with `--serve` over this socket instead.
+    pub observe: Option<std::path::PathBuf>,
+    /// Key that passes the turn in a territory game.
+    pub turn_key: KeyCode,
+    /// Image to seed the board from, cropped to the board.
+    #[cfg(feature = "image")]
+    pub image: Option<std::path::PathBuf>,
+    /// Split the board into this many teams, side by side, whose colonies
+    /// compete as cells are born to them.
+    #[cfg(feature = "tags")]
+    pub teams: Option<u8>,
+    /// Rule the world follows.
+    pub rule: Rule,
+    /// Run a copy of the board under this rule side by side with it.
+    pub compare: Option<Rule>,
+    /// Run a copy of the board with this many random cells flipped side by
+    /// side with it.
+    pub perturb: Option<usize>,
+    /// What lies beyond the edges of the world.
+    pub edges: EdgeMode,
+    /// Shape cells are kept inside of.
+    pub mask: Option<MaskShape>,
+    /// A library pattern to fill the board with, and the spacing of its copies.
+    pub tile: Option<(String, (usize, usize))>,
+    /// Turns and flips, in order, for the patterns stamped from the menu,
+    /// by `--tile` and by the demo.
+    pub transforms: Vec<Transform>,
+    /// Fill densities at either end of an axis to seed the board with.
+    pub gradient: Option<(f64, f64, Axis)>,
+    /// A ready-made scene to start from instead of a random soup.
+    pub demo: Option<Demo>,
+    /// A generated board to start from instead of a random soup.
+    pub start: Option<Start>,
+    /// Seed for random fills; a random one when not given.
+    pub seed: Option<u64>,
+    /// Draw the seed from the system's entropy, saying which it was.
+    pub random_seed: bool,
+    /// Sweep soup densities in steps of this size.
+    pub sweep: Option<f64>,
+    /// Generations to step before the board is first shown.
+    pub warmup: u64,
+    /// Delay between generations to start with, overriding [`DELAY_VAR`].
+    pub delay: Option<Duration>,
+    /// Inject cells at this edge, at this rate per generation, spread along it
+    /// like so.
+    pub inject: Option<(Edge, f64, Spread)>,
+    /// A scene file to build the world from, instead of a random soup.
+    pub scene: Option<std::path::PathBuf>,
+    /// Where to write the scene back out to, as read.
+    pub export_scene: Option<std::path::PathBuf>,
+    /// Draw a frame around the world.
+    pub border: bool,
+    /// Width over height of a terminal character, to draw cells square by;
+    /// the renderer's own default when not given.
+    pub aspect: Option<f64>,
+    /// What the braille renderer packs cells into, overriding [`GLYPHS_VAR`]
+    /// and the guess from `$TERM`.
+    pub glyphs: Option<Glyphs>,
+    /// Animate from one generation to the next, as smoothly as the terminal's
+    /// colors allow.
+    pub smooth: bool,
+    /// Dissolve each generation into the next over this many frames, a share
+    /// of the changing cells at a time, stepping as often as that takes
+    /// unless a delay is given.
+    pub dissolve: Option<u32>,
+    /// Skip the startup menu shown when no other options are given.
+    pub no_menu: bool,
+    /// How the world is drawn.
+    pub renderer: RendererKind,
+    /// Don't send frames as synchronized updates, even to terminals that
+    /// seem to support them.
+    pub no_sync: bool,
+    /// Leave the terminal's title alone rather than showing the generation
+    /// and population in it.
+    pub no_title: bool,
+    /// Keep to the default delay rather than the one tuning found this
+    /// machine keeps up with, tuning first if it hasn't been.
+    pub no_tune: bool,
+    /// The most cells a board fitted to the screen has before it is drawn
+    /// coarser or stepped slower; 0 for no limit.
+    pub cell_budget: usize,
+    /// Draw for a dark or light background instead of asking the terminal
+    /// which it has.
+    pub theme: Option<Theme>,
+    /// Print a table of what the run came to on quitting, rather than a line.
+    pub stats: bool,
+    /// Chart the population over the run to this SVG file on quitting.
+    pub chart: Option<std::path::PathBuf>,
+    /// Map where cells were alive over the run to this PNG file on quitting.
+    pub activity: Option<std::path::PathBuf>,
+    /// Save the board now and then, in the state directory unless told
+    /// otherwise, and offer to recover it after a crash.
+    pub autosave: Option<Autosave>,
+    pub frontend: Options,
 }
 
-pub struct BlockRenderer<T: Terminal> {
-    screen: BufferedTerminal<T>,
+/// A renderer that can be picked on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RendererKind {
+    /// A block character per cell.
+    #[default]
+    Block,
+    /// Braille dots, several cells per character.
+    Braille,
+    /// Nothing drawn, for timing everything else.
+    Null,
 }
 
-impl<T: Terminal> BlockRenderer<T> {
-    pub fn new(screen: BufferedTerminal<T>) -> Result<Self, Box<dyn Error>> {
-        Ok(Self { screen })
+impl RendererKind {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "block" => Ok(RendererKind::Block),
+            "braille" => Ok(RendererKind::Braille),
+            "null" => Ok(RendererKind::Null),
+            _ => Err(format!(
+                "--renderer must be block, braille or null, got {value:?}"
+            )),
+        }
     }
+}
 
-    pub fn size(&self) -> (usize, usize) {
-        let (w, h) = self.screen.dimensions();
-        (w, h)
+/// A demo that can be picked on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Demo {
+    /// Glider guns firing at each other across a wrapping board.
+    Guns,
+}
+
+impl Demo {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "guns" => Ok(Demo::Guns),
+            _ => Err(format!("--demo must be guns, got {value:?}")),
+        }
     }
+}
 
-    pub fn screen(&mut self) -> &mut BufferedTerminal<T> {
-        &mut self.screen
+/// A generated start that can be picked on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Start {
+    /// Corridors between live walls, from the seed.
+    Maze,
+}
+
+impl Start {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "maze" => Ok(Start::Maze),
+            _ => Err(format!("--start must be maze, got {value:?}")),
+        }
     }
+}
+
+/// A mask that can be picked on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskShape {
+    /// The largest circle centered on the board.
+    Circle,
+    /// A wall of live cells around the board.
+    Border,
+    /// Text centered on the board; cells only live inside the letters.
+    Text(String),
+}
 
-    pub fn render(&mut self, game: &GameOfLife) {
-        if self.screen.dimensions() != game.size {
-            self.screen.resize(game.size.0, game.size.1);
+impl MaskShape {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "circle" => Ok(MaskShape::Circle),
+            "border" => Ok(MaskShape::Border),
+            _ => match value.strip_prefix("text:") {
+                Some(text) if !text.is_empty() => Ok(MaskShape::Text(text.to_string())),
+                _ => Err(format!(
+                    "--mask must be circle, border or text:TEXT, got {value:?}"
+                )),
+            },
         }
-        for y in 0..game.size.1 {
-            for x in 0..game.size.0 {
-                let char = if game.get(x, y) { '█' } else { ' ' };
-                self.screen.add_change(Change::CursorPosition {
-                    x: termwiz::surface::Position::Absolute(x as usize),
-                    y: termwiz::surface::Position::Absolute(y as usize),
-                });
-                self.screen.add_change(&char.to_string());
+    }
+
+    fn build(&self, size: (usize, usize)) -> Mask {
+        let (w, h) = size;
+        match self {
+            MaskShape::Circle => {
+                Mask::new(size).outside_circle((w / 2, h / 2), w.min(h) / 2, MaskCell::ForcedDead)
+            }
+            MaskShape::Border => Mask::new(size).rect_border(
+                Rect {
+                    x: 0,
+                    y: 0,
+                    width: w,
+                    height: h,
+                },
+                MaskCell::ForcedAlive,
+            ),
+            MaskShape::Text(text) => {
+                let (tw, th) = Mask::text_size(text);
+                let origin = (w.saturating_sub(tw) / 2, h.saturating_sub(th) / 2);
+                let letters = Mask::new(size).text(text, origin, MaskCell::ForcedDead);
+                // Invert so the letters are the free cells.
+                let mut mask = Mask::new(size);
+                for y in 0..h {
+                    for x in 0..w {
+                        if letters.get(x, y) == MaskCell::Free {
+                            mask.set(x, y, MaskCell::ForcedDead);
+                        }
+                    }
+                }
+                mask
             }
         }
-        self.screen.flush().ok();
     }
 }
 
-impl<T: Terminal> Frontend for BlockRenderer<T> {
-    fn run(&mut self, game: &mut GameOfLife) -> Result<(), Box<dyn Error>> {
-        let term = self.screen().terminal();
-        term.enter_alternate_screen()?;
-        term.set_raw_mode()?;
-        self.screen
-            .add_change(Change::CursorVisibility(CursorVisibility::Hidden));
-        let mut start = Instant::now();
-        loop {
-            let delay = if start.elapsed() >= DELAY {
-                start = Instant::now();
-                DELAY
-            } else {
-                DELAY.saturating_sub(start.elapsed())
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            width: None,
+            height: None,
+            territory: None,
+            challenge: None,
+            replay: None,
+            observe: None,
+            turn_key: KeyCode::Tab,
+            #[cfg(feature = "image")]
+            image: None,
+            #[cfg(feature = "tags")]
+            teams: None,
+            rule: Rule::default(),
+            compare: None,
+            perturb: None,
+            edges: EdgeMode::default(),
+            mask: None,
+            tile: None,
+            transforms: Vec::new(),
+            gradient: None,
+            demo: None,
+            start: None,
+            seed: None,
+            random_seed: false,
+            sweep: None,
+            warmup: 0,
+            delay: None,
+            inject: None,
+            scene: None,
+            export_scene: None,
+            border: false,
+            aspect: None,
+            glyphs: None,
+            smooth: false,
+            dissolve: None,
+            no_menu: false,
+            renderer: RendererKind::Block,
+            no_sync: false,
+            no_title: false,
+            no_tune: false,
+            cell_budget: CELL_BUDGET,
+            theme: None,
+            stats: false,
+            chart: None,
+            activity: None,
+            autosave: state_dir().map(Autosave::new),
+            frontend: Options {
+                feedback: Feedback::Bell,
+                ..Options::default()
+            },
+        }
+    }
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let inject = (Edge::default(), INJECT_RATE, Spread::default());
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{name} requires a value"))
             };
-            match self.screen.terminal().poll_input(Some(delay)) {
-                Ok(res) => match res {
-                    Some(evt) => match evt {
-                        termwiz::input::InputEvent::Key(k) => {
-                            if k.key == termwiz::input::KeyCode::Char('q') {
-                                break;
-                            }
-                        }
-                        termwiz::input::InputEvent::Resized { cols, rows } => {
-                            *game = GameOfLife::new((cols as usize * 2, rows as usize * 3));
-                            continue;
-                        }
-                        termwiz::input::InputEvent::Wake => {
-                            continue;
-                        }
-                        termwiz::input::InputEvent::Mouse(MouseEvent {
-                            x,
-                            y,
-                            mouse_buttons,
-                            ..
-                        }) => {
-                            if mouse_buttons.contains(MouseButtons::LEFT) {
-                                game.set(x as usize, y as usize, true);
-                            }
+            match arg.as_str() {
+                "--width" => parsed.width = Some(parse_dimension("--width", &value("--width")?)?),
+                "--height" => {
+                    parsed.height = Some(parse_dimension("--height", &value("--height")?)?)
+                }
+                "--territory" => {
+                    parsed
+                        .territory
+                        .get_or_insert_with(TerritoryConfig::default);
+                }
+                "--budget" => {
+                    let budget = parse_dimension("--budget", &value("--budget")?)?;
+                    parsed
+                        .territory
+                        .get_or_insert_with(TerritoryConfig::default)
+                        .budget = budget;
+                }
+                "--territory-generations" => {
+                    let name = "--territory-generations";
+                    let generations = parse_dimension(name, &value(name)?)? as u64;
+                    parsed
+                        .territory
+                        .get_or_insert_with(TerritoryConfig::default)
+                        .generations = generations;
+                }
+                "--replay" => parsed.replay = Some(value("--replay")?.into()),
+                "--serve" => parsed.frontend.serve = Some(value("--serve")?.into()),
+                "--observe" => parsed.observe = Some(value("--observe")?.into()),
+                "--challenge" => {
+                    let scoring = value("--challenge")?.parse()?;
+                    parsed
+                        .challenge
+                        .get_or_insert_with(ChallengeConfig::default)
+                        .scoring = scoring;
+                }
+                "--challenge-budget" => {
+                    let name = "--challenge-budget";
+                    let budget = parse_dimension(name, &value(name)?)?;
+                    parsed
+                        .challenge
+                        .get_or_insert_with(ChallengeConfig::default)
+                        .budget = budget;
+                }
+                "--challenge-generations" => {
+                    let name = "--challenge-generations";
+                    let generations = parse_dimension(name, &value(name)?)? as u64;
+                    parsed
+                        .challenge
+                        .get_or_insert_with(ChallengeConfig::default)
+                        .generations = generations;
+                }
+                #[cfg(feature = "image")]
+                "--image" => parsed.image = Some(value("--image")?.into()),
+                #[cfg(feature = "tags")]
+                "--teams" => {
+                    let teams = value("--teams")?;
+                    match teams.parse::<u8>() {
+                        Ok(teams) if teams > 0 => parsed.teams = Some(teams),
+                        _ => return Err(format!("--teams must be from 1 to 255, got {teams:?}")),
+                    }
+                }
+                "--rule" => parsed.rule = parse_rule(&value("--rule")?)?,
+                "--rule-file" => {
+                    let (rule, edges) = load_rule_file(&value("--rule-file")?)?;
+                    parsed.rule = rule;
+                    parsed.edges = edges.unwrap_or(parsed.edges);
+                }
+                "--compare" => parsed.compare = Some(value("--compare")?.parse()?),
+                "--perturb" => {
+                    parsed.perturb = Some(parse_dimension("--perturb", &value("--perturb")?)?)
+                }
+                "--edges" => parsed.edges = value("--edges")?.parse()?,
+                "--mask" => parsed.mask = Some(MaskShape::parse(&value("--mask")?)?),
+                "--tile" => parsed.tile = Some(parse_tile(&value("--tile")?)?),
+                "--rotate" => parsed
+                    .transforms
+                    .push(Transform::rotation(&value("--rotate")?)?),
+                "--flip" => parsed
+                    .transforms
+                    .push(Transform::Flip(value("--flip")?.parse()?)),
+                "--gradient" => parsed.gradient = Some(parse_gradient(&value("--gradient")?)?),
+                "--demo" => parsed.demo = Some(Demo::parse(&value("--demo")?)?),
+                "--start" => parsed.start = Some(Start::parse(&value("--start")?)?),
+                "--renderer" => parsed.renderer = RendererKind::parse(&value("--renderer")?)?,
+                "--sweep" => {
+                    parsed.sweep.get_or_insert(SWEEP_STEP);
+                }
+                "--sweep-step" => {
+                    let step = value("--sweep-step")?;
+                    match step.parse::<f64>() {
+                        Ok(step) if step > 0.0 && step <= 1.0 => parsed.sweep = Some(step),
+                        _ => return Err(format!("--sweep-step must be in (0, 1], got {step:?}")),
+                    }
+                }
+                "--screensaver" => {
+                    parsed
+                        .frontend
+                        .screensaver
+                        .get_or_insert_with(ScreensaverConfig::default);
+                }
+                "--candidates" => {
+                    let candidates = parse_dimension("--candidates", &value("--candidates")?)?;
+                    let saver = parsed
+                        .frontend
+                        .screensaver
+                        .get_or_insert_with(Default::default);
+                    saver.candidates = candidates;
+                }
+                "--burn-in" => {
+                    let burn_in = parse_dimension("--burn-in", &value("--burn-in")?)? as u64;
+                    let saver = parsed
+                        .frontend
+                        .screensaver
+                        .get_or_insert_with(Default::default);
+                    saver.burn_in = burn_in;
+                }
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("--seed must be an integer, got {seed:?}"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--random-seed" => parsed.random_seed = true,
+                "--scene" => parsed.scene = Some(value("--scene")?.into()),
+                "--export-scene" => parsed.export_scene = Some(value("--export-scene")?.into()),
+                "--dump-history" => {
+                    parsed.frontend.history_path = value("--dump-history")?.into();
+                }
+                "--fade-out" => parsed.frontend.fade_out = true,
+                "--border" => parsed.border = true,
+                "--aspect" => parsed.aspect = Some(parse_aspect(&value("--aspect")?)?),
+                // Only the braille renderer packs cells into glyphs.
+                "--glyphs" => {
+                    parsed.glyphs = Some(value("--glyphs")?.parse()?);
+                    parsed.renderer = RendererKind::Braille;
+                }
+                "--min-size" => {
+                    parsed.frontend.min_size = parse_size("--min-size", &value("--min-size")?)?;
+                }
+                "--seam" => parsed.frontend.seam = true,
+                "--high-contrast" => parsed.frontend.access.high_contrast = true,
+                "--reduced-motion" => parsed.frontend.access.reduced_motion = true,
+                "--margin" => {
+                    parsed.frontend.margin = parse_dimension("--margin", &value("--margin")?)?;
+                }
+                "--clock" => parsed.frontend.clock = true,
+                "--feedback" => parsed.frontend.feedback = value("--feedback")?.parse()?,
+                "--keys" => {
+                    let keys: KeyPreset = value("--keys")?.parse()?;
+                    parsed.frontend.keys = keys;
+                    parsed.frontend.bindings = keys.bindings();
+                }
+                "--smooth" => parsed.smooth = true,
+                "--dissolve" => {
+                    let frames = parse_dimension("--dissolve", &value("--dissolve")?)?;
+                    let frames =
+                        u32::try_from(frames).map_err(|_| "--dissolve: too many frames")?;
+                    parsed.dissolve = Some(frames);
+                }
+                "--no-menu" => parsed.no_menu = true,
+                "--run-for" => {
+                    let limit = parse_duration("--run-for", &value("--run-for")?)?;
+                    parsed.frontend.run_for = Some(limit);
+                }
+                "--generations" => {
+                    let generations = parse_dimension("--generations", &value("--generations")?)?;
+                    parsed.frontend.generations = Some(generations as u64);
+                }
+                "--warmup" => {
+                    parsed.warmup = parse_dimension("--warmup", &value("--warmup")?)? as u64
+                }
+                "--max-gen" => {
+                    let limit = parse_dimension("--max-gen", &value("--max-gen")?)?;
+                    parsed.frontend.max_generation = Some(limit as u64);
+                }
+                "--slow-motion" => {
+                    parsed
+                        .frontend
+                        .slow_motion
+                        .get_or_insert_with(SlowMotion::default);
+                }
+                "--slow-motion-threshold" => {
+                    let name = "--slow-motion-threshold";
+                    let threshold = parse_dimension(name, &value(name)?)?;
+                    let slow = parsed
+                        .frontend
+                        .slow_motion
+                        .get_or_insert_with(SlowMotion::default);
+                    slow.threshold = threshold;
+                }
+                "--slow-motion-factor" => {
+                    let factor = value("--slow-motion-factor")?;
+                    let slow = parsed
+                        .frontend
+                        .slow_motion
+                        .get_or_insert_with(SlowMotion::default);
+                    match factor.parse::<f64>() {
+                        Ok(factor) if factor >= 1.0 => slow.factor = factor,
+                        _ => {
+                            return Err(format!(
+                                "--slow-motion-factor must be at least 1, got {factor:?}"
+                            ))
                         }
-                        _ => {}
-                    },
-                    None => {}
-                },
-                Err(_) => {
-                    break;
+                    }
                 }
+                "--slow-motion-duration" => {
+                    let name = "--slow-motion-duration";
+                    let duration = parse_duration(name, &value(name)?)?;
+                    let slow = parsed
+                        .frontend
+                        .slow_motion
+                        .get_or_insert_with(SlowMotion::default);
+                    slow.duration = duration;
+                }
+                "--no-autosave" => parsed.autosave = None,
+                "--no-sync" => parsed.no_sync = true,
+                "--no-title" => parsed.no_title = true,
+                "--no-tune" => parsed.no_tune = true,
+                "--cell-budget" => {
+                    let cells = value("--cell-budget")?;
+                    parsed.cell_budget = cells.parse().map_err(|_| {
+                        format!("--cell-budget must be a whole number of cells, got {cells:?}")
+                    })?;
+                }
+                "--stats" => parsed.stats = true,
+                "--chart" => parsed.chart = Some(value("--chart")?.into()),
+                "--activity" => parsed.activity = Some(value("--activity")?.into()),
+                "--autosave-every" => {
+                    let name = "--autosave-every";
+                    let generations = parse_dimension(name, &value(name)?)? as u64;
+                    parsed.autosave = Some(Autosave {
+                        generations,
+                        ..autosave(parsed.autosave.take())?
+                    });
+                }
+                "--autosave-interval" => {
+                    let name = "--autosave-interval";
+                    let interval = parse_duration(name, &value(name)?)?;
+                    parsed.autosave = Some(Autosave {
+                        interval,
+                        ..autosave(parsed.autosave.take())?
+                    });
+                }
+                "--autosave-dir" => {
+                    let dir = value("--autosave-dir")?.into();
+                    parsed.autosave = Some(Autosave {
+                        dir,
+                        ..parsed
+                            .autosave
+                            .take()
+                            .unwrap_or_else(|| Autosave::new(".".into()))
+                    });
+                }
+                "--capture-every" => {
+                    let every = parse_dimension("--capture-every", &value("--capture-every")?)?;
+                    let capture = parsed.frontend.capture.get_or_insert_with(default_capture);
+                    capture.every = every as u64;
+                }
+                "--capture-dir" => {
+                    let dir = value("--capture-dir")?.into();
+                    parsed
+                        .frontend
+                        .capture
+                        .get_or_insert_with(default_capture)
+                        .dir = dir;
+                }
+                "--background-tabs" => parsed.frontend.background_tabs = true,
+                "--delay" => parsed.delay = Some(parse_delay("--delay", &value("--delay")?)?),
+                "--inject" => {
+                    let edge = value("--inject")?
+                        .parse()
+                        .map_err(|err| format!("--inject: {err}"))?;
+                    parsed.inject.get_or_insert(inject).0 = edge;
+                }
+                "--inject-rate" => {
+                    let rate = value("--inject-rate")?;
+                    let inject = parsed.inject.get_or_insert(inject);
+                    match rate.parse::<f64>() {
+                        Ok(rate) if rate >= 0.0 => inject.1 = rate,
+                        _ => return Err(format!("--inject-rate must be at least 0, got {rate:?}")),
+                    }
+                }
+                "--inject-spread" => {
+                    let spread = value("--inject-spread")?
+                        .parse()
+                        .map_err(|err| format!("--inject-spread: {err}"))?;
+                    parsed.inject.get_or_insert(inject).2 = spread;
+                }
+                "--trail" => {
+                    parsed.frontend.trail = parse_dimension("--trail", &value("--trail")?)? as u32
+                }
+                "--palette" => parsed.frontend.palette = load_palette(&value("--palette")?)?,
+                "--theme" => parsed.theme = Some(value("--theme")?.parse()?),
+                "--turn-key" => parsed.turn_key = parse_key(&value("--turn-key")?)?,
+                _ => return Err(format!("unknown argument: {arg}")),
             }
-            if start.elapsed() <= DELAY {
-                continue;
-            }
-            game.step();
-            self.render(&game);
-            self.screen.flush()?;
         }
+        if parsed.random_seed && parsed.seed.is_some() {
+            return Err("--seed and --random-seed both choose the seed; pick one".into());
+        }
+        Ok(parsed)
+    }
 
-        self.screen.terminal().exit_alternate_screen()?;
-        self.screen
-            .add_change(Change::CursorVisibility(CursorVisibility::Visible));
-        Ok(())
+    /// The budget a board fitted to the screen is kept to. A delay counts as
+    /// given once tuning is off, as it is when one is asked for.
+    fn budget(&self) -> Budget {
+        Budget {
+            cells: self.cell_budget,
+            size: (self.width, self.height),
+            delay: self.no_tune.then_some(self.frontend.delay),
+        }
     }
 }
 
-pub struct BrailleRenderer<T: Terminal> {
-    screen: BufferedTerminal<T>,
+/// The autosave settings `--autosave-every` and `--autosave-interval` adjust,
+/// which need somewhere to save to.
+fn autosave(autosave: Option<Autosave>) -> Result<Autosave, String> {
+    autosave
+        .or_else(|| state_dir().map(Autosave::new))
+        .ok_or_else(|| "no state directory to autosave to; give --autosave-dir".to_string())
+}
+
+/// Reads a subcommand's `args` with `each`, which is handed each argument
+/// and a way to take the value after it. Whatever it turns down is a
+/// [`Usage`] error.
+fn each_arg(
+    args: impl IntoIterator<Item = String>,
+    mut each: impl FnMut(
+        &str,
+        &mut dyn FnMut(&str) -> Result<String, String>,
+    ) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Usage> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        each(&arg, &mut value).map_err(|err| Usage(err.to_string()))?;
+    }
+    Ok(())
 }
 
-impl<T: Terminal> BrailleRenderer<T> {
-    pub fn new(screen: BufferedTerminal<T>) -> Result<Self, Box<dyn Error>> {
-        Ok(Self { screen })
+fn parse_dimension(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("{name} must be a positive integer, got {value:?}")),
+        Ok(n) => Ok(n),
     }
+}
+
+/// Where frames are captured to, and how often, unless told otherwise.
+fn default_capture() -> Capture {
+    Capture::new("frames".into())
+}
 
-    pub fn size(&self) -> (usize, usize) {
-        let (w, h) = self.screen.dimensions();
-        (w * 2, h * 3)
+/// A whole, positive number of milliseconds.
+fn parse_delay(name: &str, value: &str) -> Result<Duration, String> {
+    match value.parse::<u64>() {
+        Ok(ms) if ms > 0 => Ok(Duration::from_millis(ms)),
+        _ => Err(format!(
+            "{name} must be a positive number of milliseconds, got {value:?}"
+        )),
     }
+}
+
+/// The delay [`DELAY_VAR`] asks for, if it is set. A value that isn't a delay
+/// is warned about and ignored.
+fn env_delay() -> Option<Duration> {
+    let value = std::env::var(DELAY_VAR).ok()?;
+    parse_delay(DELAY_VAR, &value)
+        .map_err(|err| eprintln!("ignoring {err}, using the default delay"))
+        .ok()
+}
 
-    pub fn screen(&mut self) -> &mut BufferedTerminal<T> {
-        &mut self.screen
+/// The glyphs [`GLYPHS_VAR`] asks for, if it is set. A value that isn't a
+/// set of glyphs is warned about and ignored.
+fn env_glyphs() -> Option<Glyphs> {
+    let value = std::env::var(GLYPHS_VAR).ok()?;
+    value
+        .parse()
+        .map_err(|err| eprintln!("ignoring {GLYPHS_VAR}: {err}"))
+        .ok()
+}
+
+/// A positive time such as `30s`, `2m` or `500ms`; plain numbers are seconds.
+fn parse_duration(name: &str, value: &str) -> Result<Duration, String> {
+    let (number, unit) = value
+        .find(|c: char| c.is_ascii_alphabetic())
+        .map_or((value, "s"), |at| value.split_at(at));
+    let scale = match unit {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => f64::NAN,
+    };
+    match number.parse::<f64>() {
+        Ok(n) if n > 0.0 && (n * scale).is_finite() => Ok(Duration::from_secs_f64(n * scale)),
+        _ => Err(format!(
+            "{name} must be a time such as 30s, 2m or 500ms, got {value:?}"
+        )),
     }
+}
 
-    pub fn render(&mut self, game: &GameOfLife) {
-        if self.screen.dimensions() != { (game.size.0 / 2, game.size.1 / 3) } {
-            self.screen.resize(game.size.0, game.size.1);
-        }
-        // 2x3 groups of cells to be represented by braille chars
-        let mut groups = vec![
-            vec![[false, false, false, false, false, false]; game.size.0 / 2];
-            game.size.1 / 3
-        ];
+/// A character's width over its height, e.g. 0.5.
+fn parse_aspect(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(aspect) if aspect.is_finite() && aspect > 0.0 => Ok(aspect),
+        _ => Err(format!("--aspect must be a positive number, got {value:?}")),
+    }
+}
+
+/// `WxH`, both positive.
+fn parse_size(name: &str, value: &str) -> Result<(usize, usize), String> {
+    let (w, h) = value
+        .split_once('x')
+        .ok_or_else(|| format!("{name} must be WxH, got {value:?}"))?;
+    Ok((parse_dimension(name, w)?, parse_dimension(name, h)?))
+}
+
+/// `FROM:TO` or `FROM:TO:AXIS`, densities between 0 and 1 along `x` (the
+/// default) or `y`.
+fn parse_gradient(value: &str) -> Result<(f64, f64, Axis), String> {
+    let invalid = || format!("--gradient must be FROM:TO[:x|y], e.g. 0.8:0.1, got {value:?}");
+    let mut parts = value.split(':');
+    let mut density = || match parts.next().map(str::parse::<f64>) {
+        Some(Ok(density)) if (0.0..=1.0).contains(&density) => Ok(density),
+        _ => Err(invalid()),
+    };
+    let (from, to) = (density()?, density()?);
+    let axis = match parts.next() {
+        Some(axis) => axis.parse().map_err(|_| invalid())?,
+        None => Axis::default(),
+    };
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((from, to, axis))
+}
+
+/// `NAME:WxH`, a library pattern and the spacing to tile it at.
+fn parse_tile(value: &str) -> Result<(String, (usize, usize)), String> {
+    let invalid = || format!("--tile must be NAME:WxH, e.g. blinker:10x10, got {value:?}");
+    let (name, spacing) = value.split_once(':').ok_or_else(invalid)?;
+    let spacing = parse_size("--tile", spacing).map_err(|_| invalid())?;
+    if Pattern::named(name).is_none() {
+        let names = Pattern::names().collect::<Vec<_>>().join(", ");
+        return Err(format!("unknown pattern {name:?}, expected one of {names}"));
+    }
+    Ok((name.to_string(), spacing))
+}
+
+/// A built-in palette by name, or else the theme file at that path.
+fn load_palette(value: &str) -> Result<Palette, String> {
+    if let Some(palette) = Palette::builtin(value) {
+        return Ok(palette);
+    }
+    let text = std::fs::read_to_string(value).map_err(|err| format!("{value}: {err}"))?;
+    Palette::parse(&text).map_err(|err| format!("{value}: {err}"))
+}
+
+/// The rule `--rule` gives: the Golly rule table in the file it names if it
+/// ends in `.rule`, else the rule written out.
+fn parse_rule(value: &str) -> Result<Rule, String> {
+    if !value.ends_with(".rule") {
+        return value.parse();
+    }
+    let text = std::fs::read_to_string(value).map_err(|err| format!("{value}: {err}"))?;
+    Rule::parse_golly(&text).map_err(|err| format!("{value}: {err}"))
+}
+
+/// Reads a rule, and maybe an edge mode, from the rule file at `path`.
+fn load_rule_file(path: &str) -> Result<(Rule, Option<EdgeMode>), String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    Rule::parse_file(&text).map_err(|err| format!("{path}: {err}"))
+}
+
+/// Reads the scene file at `path`, building its board at `size` unless it
+/// gives its own, with warnings about patterns that didn't fit.
+fn load_scene(
+    path: &Path,
+    size: (usize, usize),
+) -> Result<(Scene, GameOfLife, Vec<String>), String> {
+    let error = |err: String| format!("{}: {err}", path.display());
+    let text = std::fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+    let scene = Scene::parse(&text).map_err(error)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let (game, warnings) = scene.build(size, dir).map_err(error)?;
+    Ok((scene, game, warnings))
+}
 
-        for y in 0..game.size.1 {
-            for x in 0..game.size.0 {
-                groups[y.saturating_div(3)][x.saturating_div(2)][(y % 3) + (x % 2)] =
-                    game.get(x, y);
+/// The newest generation in the history file at `path`, with a warning for
+/// each place its metadata disagreed with itself.
+fn load_session(path: &Path) -> Result<(Frame, Vec<String>), String> {
+    let error = |err: String| format!("{}: {err}", path.display());
+    let text = std::fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+    let (frame, conflicts) = Frame::last_of_multi_rle(&text).map_err(error)?;
+    let warnings = conflicts.iter().map(|conflict| error(conflict.to_string()));
+    Ok((frame, warnings.collect()))
+}
+
+fn parse_key(value: &str) -> Result<KeyCode, String> {
+    let mut chars = value.chars();
+    match (
+        value.to_ascii_lowercase().as_str(),
+        chars.next(),
+        chars.next(),
+    ) {
+        ("tab", ..) => Ok(KeyCode::Tab),
+        ("enter", ..) => Ok(KeyCode::Enter),
+        ("space", ..) => Ok(KeyCode::Char(' ')),
+        (_, Some(c), None) => Ok(KeyCode::Char(c)),
+        _ => Err(format!("unknown key {value:?}")),
+    }
+}
+
+/// The hidden `verify` subcommand: checks every engine against the reference
+/// stepper for a longer run than the tests do.
+fn verify(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut width, mut height, mut seed, mut generations) = (64, 64, 0, 1000);
+    let mut rule = Rule::default();
+    let mut edges = EdgeMode::default();
+    let mut debug_dir = Path::new(DEBUG_DIR).to_path_buf();
+    each_arg(args, |arg, value| {
+        match arg {
+            "--width" => width = parse_dimension("--width", &value("--width")?)?,
+            "--height" => height = parse_dimension("--height", &value("--height")?)?,
+            "--seed" => seed = value("--seed")?.parse()?,
+            "--generations" => {
+                generations = parse_dimension("--generations", &value("--generations")?)? as u64
             }
+            "--rule" => rule = parse_rule(&value("--rule")?)?,
+            "--edges" => edges = value("--edges")?.parse()?,
+            "--debug-dir" => debug_dir = value("--debug-dir")?.into(),
+            _ => return Err(format!("unknown argument: {arg}").into()),
         }
-
-        let chars = groups
-            .into_iter()
-            .map(|row| {
-                row.into_iter()
-                    .map(|cell| {
-                        BRAILLE[cell[0] as usize][cell[3] as usize][cell[1] as usize]
-                            [cell[4] as usize][cell[2] as usize][cell[5] as usize][0][0]
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        self.screen.add_change(Change::CursorPosition {
-            x: termwiz::surface::Position::Absolute(0),
-            y: termwiz::surface::Position::Absolute(0),
-        });
-        let mut buf = String::new();
-        for y in 0..game.size.1 / 3 {
-            buf.clear();
-            for x in 0..game.size.0 / 2 {
-                let char = chars[y][x];
-                buf.push(char);
+        Ok(())
+    })?;
+    if let Err(divergence) = verify_engines(width, height, seed, rule, edges, generations) {
+        eprint!("{divergence}");
+        match divergence.write_report(&debug_dir) {
+            Ok(paths) => {
+                let paths = paths.map(|path| path.display().to_string());
+                eprintln!(
+                    "both boards and every differing cell are in {}",
+                    paths.join(", ")
+                );
             }
-            self.screen.add_change(&buf);
-        }
-        self.screen.flush().ok();
-    }
-}
-
-impl<T: Terminal> Frontend for BrailleRenderer<T> {
-    fn run(&mut self, game: &mut GameOfLife) -> Result<(), Box<dyn Error>> {
-        let term = self.screen().terminal();
-        term.enter_alternate_screen()?;
-        term.set_raw_mode()?;
-        self.screen
-            .add_change(Change::CursorVisibility(CursorVisibility::Hidden));
-        let mut start = Instant::now();
-        loop {
-            let delay = if start.elapsed() >= DELAY {
-                start = Instant::now();
-                DELAY
-            } else {
-                DELAY.saturating_sub(start.elapsed())
-            };
-            match self.screen.terminal().poll_input(Some(delay)) {
-                Ok(res) => match res {
-                    Some(evt) => match evt {
-                        termwiz::input::InputEvent::Key(k) => {
-                            if k.key == termwiz::input::KeyCode::Char('q') {
-                                break;
-                            }
-                        }
-                        termwiz::input::InputEvent::Resized { cols, rows } => {
-                            *game = GameOfLife::new((cols as usize * 2, rows as usize * 3));
-                            continue;
-                        }
-                        termwiz::input::InputEvent::Wake => {
-                            continue;
-                        }
-                        termwiz::input::InputEvent::Mouse(MouseEvent {
-                            x,
-                            y,
-                            mouse_buttons,
-                            ..
-                        }) => {
-                            if mouse_buttons.contains(MouseButtons::LEFT) {
-                                let col = x as usize * 2;
-                                let row = y as usize * 3;
-                                for y in 0..3 {
-                                    for x in 0..2 {
-                                        game.set(col + x, row + y, true);
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    },
-                    None => {}
-                },
-                Err(_) => {
-                    break;
+            Err(err) => eprintln!(
+                "could not write the report to {}: {err}",
+                debug_dir.display()
+            ),
+        }
+        std::process::exit(1);
+    }
+    println!(
+        "all engines agree for {generations} generations of {width}x{height} {rule} {edges:?}"
+    );
+    Ok(())
+}
+
+/// The `headless` subcommand: runs a random soup without a terminal until it
+/// settles or reaches the generation limit, prints how it ended and exits with
+/// the matching [`exit_code`]. With `--debug-diff` it prints each cell every
+/// step changes as CSV on the way, the summary after it commented out, and
+/// with `--record PATH` it keeps every generation in a frame archive there
+/// for `--replay`. Each `--assert QUERY` is then asked of the board it ended
+/// on, as [`Query`] reads it, and any that fails exits with
+/// [`ASSERTION_FAILED`] instead. With `--experiment perturb --flips K` it
+/// runs the soup beside a copy with K cells flipped instead, printing how
+/// many cells differ each generation as CSV.
+fn headless(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut width, mut height, mut density, mut generations) = (64, 64, 0.5, 1000);
+    let mut seed = None;
+    let mut rule = Rule::default();
+    let mut edges = EdgeMode::default();
+    let mut capture = None;
+    let mut trace = None;
+    let mut record = None;
+    let mut asserts = Vec::new();
+    let (mut perturb, mut flips) = (false, 1);
+    each_arg(args, |arg, value| {
+        match arg {
+            "--debug-diff" => trace = Some(StepTrace::new()),
+            "--record" => record = Some(std::path::PathBuf::from(value("--record")?)),
+            "--width" => width = parse_dimension("--width", &value("--width")?)?,
+            "--height" => height = parse_dimension("--height", &value("--height")?)?,
+            "--seed" => seed = Some(value("--seed")?.parse()?),
+            "--density" => {
+                let value = value("--density")?;
+                density = match value.parse::<f64>() {
+                    Ok(density) if (0.0..=1.0).contains(&density) => density,
+                    _ => return Err(format!("--density must be in [0, 1], got {value:?}").into()),
                 }
             }
-            if start.elapsed() <= DELAY {
-                continue;
+            "--generations" => {
+                generations = parse_dimension("--generations", &value("--generations")?)? as u64
+            }
+            // The soup starts at generation 0, so this is the same limit.
+            "--max-gen" => generations = parse_dimension("--max-gen", &value("--max-gen")?)? as u64,
+            "--rule" => rule = parse_rule(&value("--rule")?)?,
+            "--edges" => edges = value("--edges")?.parse()?,
+            "--experiment" => match value("--experiment")?.as_str() {
+                "perturb" => perturb = true,
+                other => return Err(format!("unknown experiment {other:?}; try perturb").into()),
+            },
+            "--flips" => flips = parse_dimension("--flips", &value("--flips")?)?,
+            // Read now, so a typo shows before a long run rather than after.
+            "--assert" => {
+                let source = value("--assert")?;
+                let query =
+                    Query::parse(&source).map_err(|err| format!("--assert {source:?}: {err}"))?;
+                asserts.push(query);
+            }
+            "--capture-every" => {
+                let every = parse_dimension("--capture-every", &value("--capture-every")?)?;
+                capture.get_or_insert_with(default_capture).every = every as u64;
+            }
+            "--capture-dir" => {
+                capture.get_or_insert_with(default_capture).dir = value("--capture-dir")?.into();
             }
-            game.step();
-            self.render(&game);
-            self.screen.flush()?;
+            _ => return Err(format!("unknown argument: {arg}").into()),
         }
-
-        self.screen.terminal().exit_alternate_screen()?;
-        self.screen
-            .add_change(Change::CursorVisibility(CursorVisibility::Visible));
         Ok(())
+    })?;
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut capturer = capture.map(Capturer::new).transpose()?;
+    let mut game = GameOfLife::new_empty((width, height));
+    game.set_rule(rule);
+    game.set_edge_mode(edges);
+    game.fill(density, seed);
+    if perturb {
+        // The flips are picked from the same seed as the soup.
+        println!("{}", Distance::CSV_HEADER);
+        for distance in Perturbation::new(&game, flips, seed).series(generations) {
+            println!("{distance}");
+        }
+        return Ok(());
+    }
+    let mut recorder = match &record {
+        Some(path) => {
+            let file =
+                std::fs::File::create(path).map_err(|err| format!("{}: {err}", path.display()))?;
+            Some(FrameWriter::new(
+                std::io::BufWriter::new(file),
+                game.size(),
+            )?)
+        }
+        None => None,
+    };
+    let mut detector = CycleDetector::new(HEADLESS_MAX_PERIOD);
+    if trace.is_some() {
+        println!("{}", CellChange::CSV_HEADER);
+    }
+    // Births and deaths are only counted for the assertions that ask, as the
+    // board steps.
+    let tally = Arc::new(Mutex::new(StepTally::default()));
+    if !asserts.is_empty() {
+        game.set_observer(Box::new(tally.clone()));
+    }
+    let outcome = run_until_settled_observing(&mut game, &mut detector, generations, |game| {
+        for change in trace
+            .as_mut()
+            .map(|trace| trace.observe(game))
+            .unwrap_or_default()
+        {
+            println!("{change}");
+        }
+        if let Some(failed) = capturer
+            .as_mut()
+            .and_then(|capturer| capturer.observe(game))
+        {
+            eprintln!("{failed}");
+        }
+        // A recording that can't go on stops, rather than leaving a gap.
+        if let Some(Err(err)) = recorder.as_mut().map(|recorder| recorder.push(game)) {
+            eprintln!(
+                "stopped recording at generation {}: {err}",
+                game.generation()
+            );
+            recorder = None;
+        }
+    });
+    if let (Some(recorder), Some(path)) = (recorder, &record) {
+        recorder
+            .finish()
+            .map_err(|err| format!("{}: {err}", path.display()))?;
     }
+    let ended = match outcome {
+        Some(Settled::Died) => "died out".to_string(),
+        Some(Settled::Cycle(1)) => "settled into still lifes".to_string(),
+        Some(Settled::Cycle(period)) => format!("settled into period {period}"),
+        None => "still changing".to_string(),
+    };
+    println!(
+        "{}{ended} at generation {}, population {}, seed {seed}",
+        if trace.is_some() { "# " } else { "" },
+        game.generation(),
+        game.population()
+    );
+    if let Some(capturer) = capturer {
+        let dir = capturer.config().dir.clone();
+        println!("{}", capturer.finish().line(&dir));
+    }
+    let StepTally { births, deaths, .. } = *tally.lock().unwrap();
+    let run = Run {
+        game: &game,
+        births: births as u64,
+        deaths: deaths as u64,
+        outcome,
+    };
+    let mut failed = false;
+    for query in &asserts {
+        let holds = query.holds(&run);
+        match &holds {
+            Ok(true) => {}
+            Ok(false) => eprintln!("assertion failed: {}", query.source()),
+            Err(err) => eprintln!("assertion failed: {}: {err}", query.source()),
+        }
+        failed |= holds != Ok(true);
+    }
+    if failed {
+        std::process::exit(ASSERTION_FAILED.into());
+    }
+    std::process::exit(exit_code(outcome).into())
 }
 
-pub struct GameOfLife {
-    size: (usize, usize),
-    grid: Vec<bool>,
+/// The `stats` subcommand: runs many random soups under one rule, spread over
+/// every core, and prints how they tended to end.
+fn stats(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = SoupConfig {
+        rule: Rule::default(),
+        edges: EdgeMode::default(),
+        size: (64, 64),
+        density: 0.35,
+        generations: 1000,
+        seed: 0,
+        soups: 100,
+    };
+    let (mut seed, mut json) = (None, false);
+    let mut threads = std::thread::available_parallelism().map_or(1, usize::from);
+    each_arg(args, |arg, value| {
+        match arg {
+            "--rule" => config.rule = parse_rule(&value("--rule")?)?,
+            "--edges" => config.edges = value("--edges")?.parse()?,
+            "--size" => config.size = parse_size("--size", &value("--size")?)?,
+            "--soups" => config.soups = parse_dimension("--soups", &value("--soups")?)?,
+            "--density" => {
+                let value = value("--density")?;
+                config.density = match value.parse::<f64>() {
+                    Ok(density) if (0.0..=1.0).contains(&density) => density,
+                    _ => return Err(format!("--density must be in [0, 1], got {value:?}").into()),
+                }
+            }
+            "--generations" | "--max-gen" => {
+                config.generations = parse_dimension(arg, &value(arg)?)? as u64
+            }
+            "--seed" => seed = Some(value("--seed")?.parse()?),
+            "--threads" => threads = parse_dimension("--threads", &value("--threads")?)?,
+            "--json" => json = true,
+            _ => return Err(format!("unknown argument: {arg}").into()),
+        }
+        Ok(())
+    })?;
+    config.seed = seed.unwrap_or_else(rand::random);
+    let soups = config.soups;
+    let outcomes = run_soups(&config, threads, &|done| {
+        eprint!("\r{done}/{soups} soups");
+    });
+    eprintln!();
+    let stats = SoupStats::of(&outcomes);
+    if json {
+        println!("{}", stats.json());
+    } else {
+        let (w, h) = config.size;
+        println!(
+            "{} {w}x{h} at density {}, seeds {}..",
+            config.rule, config.density, config.seed
+        );
+        println!("{}", stats.table());
+    }
+    Ok(())
 }
 
-impl GameOfLife {
-    pub fn new(size: (usize, usize)) -> Self {
-        let mut new = Self {
-            size,
-            grid: vec![false; size.0 * size.1],
-        };
-        new.init();
-        new
+/// The `doctor` subcommand: finds out what the terminal can do, asking it
+/// and then the user, and prints it with the flags that suit it. Without a
+/// terminal on both stdin and stdout, or with `--no-ask`, the user isn't
+/// asked; without one at all only the environment is gone by.
+fn doctor(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut ask = true;
+    each_arg(args, |arg, _| {
+        match arg {
+            "--no-ask" => ask = false,
+            _ => return Err(format!("unknown argument: {arg}").into()),
+        }
+        Ok(())
+    })?;
+    let term = std::env::var("TERM").ok();
+    let caps = Capabilities::new_from_env().ok();
+    let terminal = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let screen = match &caps {
+        Some(caps) if terminal && term.as_deref() != Some("dumb") => new_terminal(caps.clone())
+            .and_then(BufferedTerminal::new)
+            .ok(),
+        _ => None,
+    };
+    let findings = match (screen, &caps) {
+        (Some(mut screen), Some(caps)) => probe(&mut screen, term, caps, ask),
+        _ => Findings::from_env(term, caps.as_ref()),
+    };
+    for line in findings.report() {
+        println!("{line}");
+    }
+    let suggestions = findings.suggestions();
+    let flags: Vec<_> = suggestions.iter().map(|(flag, _)| flag.as_str()).collect();
+    println!("\nsuggested: gol {}", flags.join(" "));
+    for (flag, why) in &suggestions {
+        println!("  {flag}: {why}");
     }
+    Ok(())
+}
 
-    pub fn init(&mut self) {
-        let mut rng = rand::thread_rng();
-        for y in 0..self.size.1 {
-            for x in 0..self.size.0 {
-                self.set(x, y, rng.gen_bool(0.7));
-            }
+/// The `tune` subcommand: times each engine on a soup the size of a typical
+/// terminal's board, or `--size`, within [`BUDGET`], and keeps what suits
+/// this machine for the game to start with.
+fn tune(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut size = FALLBACK_TERMINAL;
+    each_arg(args, |arg, value| {
+        match arg {
+            "--size" => size = parse_size("--size", &value("--size")?)?,
+            _ => return Err(format!("unknown argument: {arg}").into()),
         }
+        Ok(())
+    })?;
+    let timings = measure(size, BUDGET, ENGINES);
+    for timing in &timings {
+        println!(
+            "{:<10}{:>10.3} ms a step",
+            timing.engine,
+            timing.per_step.as_secs_f64() * 1e3
+        );
     }
+    let tuning = pick(size, &timings, DELAY).ok_or("no engines to time")?;
+    println!(
+        "\nfastest at {}x{}: {}, keeping up with a delay of {} ms",
+        size.0,
+        size.1,
+        tuning.engine,
+        tuning.delay.as_millis()
+    );
+    let dir = state_dir().ok_or("no home directory to keep the tuning in")?;
+    tuning
+        .save(&dir)
+        .map_err(|err| format!("{}: {err}", Tuning::path(&dir).display()))?;
+    println!("kept in {}", Tuning::path(&dir).display());
+    Ok(())
+}
 
-    pub fn size(&self) -> (usize, usize) {
-        self.size
+/// What suits a board of `size` here: the tuning kept from before if it was
+/// for a board about as big, or else what tuning finds now, within
+/// [`BUDGET`], which is kept for next time. `None` without a home directory.
+fn tuned(size: (usize, usize)) -> Option<Tuning> {
+    let dir = state_dir()?;
+    if let Some(tuning) = Tuning::load(&dir).filter(|tuning| tuning.suits(size)) {
+        return Some(tuning);
     }
+    let tuning = pick(size, &measure(size, BUDGET, ENGINES), DELAY)?;
+    // Not kept, it is only found again next time.
+    let _ = tuning.save(&dir);
+    Some(tuning)
+}
 
-    pub fn surface(&self) -> &[bool] {
-        &self.grid
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {err}");
+        std::process::exit(failure_code(&*err).into());
     }
+}
 
-    pub fn set(&mut self, x: usize, y: usize, value: bool) {
-        self.grid[x + y * self.size.0] = value;
+/// The game or the subcommand the command line asks for.
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1).peekable();
+    let first = args.peek().cloned();
+    let launch = Launch::new(
+        first.as_deref(),
+        std::io::stdin().is_terminal(),
+        std::io::stdout().is_terminal(),
+    );
+    if launch.consumes(first.as_deref()) {
+        args.next();
     }
-
-    pub fn get(&self, x: usize, y: usize) -> bool {
-        self.grid[x + y * self.size.0]
+    match launch {
+        Launch::Verify => return verify(args),
+        Launch::Headless => return headless(args),
+        Launch::Stats => return stats(args),
+        Launch::Doctor => return doctor(args),
+        Launch::Tune => return tune(args),
+        Launch::Play => {}
+        Launch::NoTerminal { .. } => {
+            eprintln!("{}", launch.message().unwrap_or_default());
+            std::process::exit(NO_TERMINAL.into());
+        }
     }
-
-    pub fn count_neighbors(&self, x: usize, y: usize) -> usize {
-        let mut count = 0;
-        for i in x.saturating_sub(1)..=(x + 1).min(self.size.0 - 1) {
-            for j in y.saturating_sub(1)..=(y + 1).min(self.size.1 - 1) {
-                if i == x && j == y {
-                    continue;
-                }
-                if self.get(i, j) {
-                    count += 1;
+    let mut args = Args::parse(args).map_err(Usage)?;
+    // Any option at all means the user knows what they want.
+    let menu = args == Args::default();
+    // A delay asked for is never second-guessed by tuning.
+    if let Some(delay) = args.delay.or_else(env_delay) {
+        args.frontend.delay = delay;
+        args.no_tune = true;
+    } else if let Some(frames) = args.dissolve {
+        args.frontend.delay = SMOOTH_FRAME * frames;
+        args.no_tune = true;
+    }
+    // Past here there is a terminal, though it may not be one we can use.
+    let term = std::env::var("TERM").ok();
+    let unusable = |err: &dyn std::fmt::Display| -> ! {
+        eprintln!("{}", terminal_failure(err, term.as_deref()));
+        std::process::exit(NO_TERMINAL.into());
+    };
+    if term.as_deref() == Some("dumb") {
+        unusable(&"it can't move the cursor");
+    }
+    let caps = Capabilities::new_from_env().unwrap_or_else(|err| unusable(&err));
+    args.frontend.sync = !args.no_sync && supports_sync(&caps);
+    args.frontend.title = !args.no_title;
+    // Fading needs the greyscale ramp of at least 256 colors.
+    let smooth = match caps.color_level() {
+        ColorLevel::Sixteen => Smooth::Midpoint,
+        _ => Smooth::Fade,
+    };
+    let terminal = new_terminal(caps).unwrap_or_else(|err| unusable(&err));
+    let mut screen = BufferedTerminal::new(terminal).unwrap_or_else(|err| unusable(&err));
+    args.frontend.theme = match args.theme {
+        Some(theme) => theme,
+        None => query_background(&mut screen, BACKGROUND_QUERY)
+            .map_or(Theme::Dark, Theme::for_background),
+    };
+    // Braille dots are too fine to stand out, so high contrast draws blocks.
+    if args.frontend.access.high_contrast && args.renderer == RendererKind::Braille {
+        args.renderer = RendererKind::Block;
+    }
+    match args.renderer {
+        RendererKind::Block => play(block_renderer(screen, &args)?, args, menu, smooth),
+        RendererKind::Braille => {
+            let glyphs = args
+                .glyphs
+                .or_else(env_glyphs)
+                .unwrap_or(Glyphs::guess(term.as_deref()));
+            let mut render = BrailleRenderer::new(screen)?
+                .with_border(args.border)
+                .with_glyphs(glyphs);
+            if let Some(aspect) = args.aspect {
+                render = render.with_aspect(aspect);
+            }
+            // A screen too big to fill with dots gets a cell per character.
+            let (dimensions, scale) = (render.dimensions(), render.scale());
+            let coarser = Some(block_scale(&render, args.aspect));
+            let too_big = oversize(dimensions, scale, args.border, coarser, &args.budget());
+            if let Some(too_big) = too_big {
+                if let Mitigation::Coarser { .. } = too_big.mitigation {
+                    args.frontend.notices.push(too_big.to_string());
+                    let render = block_renderer(render.into_screen(), &args)?;
+                    return play(render, args, menu, smooth);
                 }
             }
+            play(render, args, menu, smooth)
         }
-        count
-    }
-
-    pub fn step(&mut self) {
-        let mut next = self.grid.clone();
-        for y in 0..self.size.1 {
-            for x in 0..self.size.0 {
-                let neighbors = self.count_neighbors(x, y);
-                let cell = self.get(x, y);
-                let next_cell = match (cell, neighbors) {
-                    (true, 2) | (true, 3) => true,
-                    (true, _) => false,
-                    (false, 3) => true,
-                    (false, _) => false,
-                };
-                next[x + y * self.size.0] = next_cell;
+        RendererKind::Null => {
+            let mut render = NullRenderer::new(screen)?.with_border(args.border);
+            if let Some(aspect) = args.aspect {
+                render = render.with_aspect(aspect);
             }
+            play(render, args, menu, smooth)
         }
-        self.grid = next;
     }
 }
 
-const DELAY: Duration = Duration::from_millis(50);
+/// A [`BlockRenderer`] on `screen`, framed and shaped as `args` ask.
+fn block_renderer<T: Terminal>(
+    screen: BufferedTerminal<T>,
+    args: &Args,
+) -> Result<BlockRenderer<T>, Box<dyn std::error::Error>> {
+    let mut render = BlockRenderer::new(screen)?.with_border(args.border);
+    if let Some(aspect) = args.aspect {
+        render = render.with_aspect(aspect);
+    }
+    Ok(render)
+}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let caps = Capabilities::new_from_env()?;
-    let screen = BufferedTerminal::new(new_terminal(caps)?)?;
-    // let mut render = BrailleRenderer::new(screen)?;
-    let mut render = BlockRenderer::new(screen)?;
-    let mut game = GameOfLife::new(render.size());
+/// How a [`BlockRenderer`] on the same terminal as `_render` would map
+/// cells, for characters `aspect` times as wide as they are tall if given.
+fn block_scale<T: Terminal>(_render: &BrailleRenderer<T>, aspect: Option<f64>) -> Scale {
+    Scale::block(aspect.unwrap_or(BlockRenderer::<T>::ASPECT))
+}
 
-    render.run(&mut game)?;
+/// Sets up the world `args` describe and runs it on `render`.
+fn play<R: Renderer>(
+    mut render: R,
+    mut args: Args,
+    menu: bool,
+    smooth: Smooth,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dimensions = match startup_dimensions(render.screen())? {
+        Some(dimensions) => dimensions,
+        // The session shows a warning until the terminal grows; the world
+        // is sized for a typical one meanwhile.
+        None => {
+            let (cols, rows) = render.screen().dimensions();
+            let (fw, fh) = FALLBACK_TERMINAL;
+            eprintln!("terminal reports {cols}x{rows} characters, sizing the world for {fw}x{fh}");
+            FALLBACK_TERMINAL
+        }
+    };
+    let (w, h) = render.cells_for(dimensions);
+    // A comparison shows two worlds, each in half of the screen.
+    let side_by_side = args.compare.is_some() || args.perturb.is_some();
+    let w = if side_by_side { w / 2 } else { w };
+    let size = (args.width.unwrap_or(w), args.height.unwrap_or(h));
+    if let Some(tuning) = Some(size).filter(|_| !args.no_tune).and_then(tuned) {
+        args.frontend.delay = tuning.delay;
+    }
+    let budget = args.budget();
+    if let Some(too_big) = oversize(dimensions, render.scale(), render.border(), None, &budget) {
+        if let Mitigation::Slower { delay } = too_big.mitigation {
+            args.frontend.delay = args.frontend.delay.max(delay);
+        }
+        args.frontend.notices.push(too_big.to_string());
+    }
+    let seed = args.seed.unwrap_or_else(rand::random);
+    if args.random_seed {
+        // Before the screen is taken, so it can be read back and run again.
+        eprintln!("seed {seed}");
+    }
+    let recovered = match args.autosave.as_ref().and_then(Autosave::recoverable) {
+        Some(frame) => {
+            let lines = [
+                format!(
+                    "The last run ended at generation {} without quitting.",
+                    frame.generation
+                ),
+                String::new(),
+                "y  recover its board".to_string(),
+                "any other key starts afresh".to_string(),
+            ];
+            run_confirm(&mut render, &lines)?.then_some(frame)
+        }
+        None => None,
+    };
+    let menu = menu && recovered.is_none();
+    let choice = match menu {
+        true => Some(run_menu(&mut render, args.frontend.history_path.exists())?),
+        false => None,
+    };
 
-    Ok(())
-}
+    if let Some(config) = args.territory {
+        let mut territory = Territory::new(size, config);
+        run_territory(&mut render, &mut territory, args.turn_key)?;
+        return Ok(());
+    }
+    if let Some(path) = &args.replay {
+        let file = std::fs::File::open(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let mut reader = FrameReader::new(std::io::BufReader::new(file))
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        run_replay(&mut render, &mut reader)?;
+        return Ok(());
+    }
+    if let Some(path) = &args.observe {
+        let mut feed = Feed::connect(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        run_observe(&mut render, &mut feed)?;
+        return Ok(());
+    }
+    if let Some(config) = args.challenge {
+        let mut challenge = Challenge::new(size, config);
+        let scores = state_dir().map(HighScores::new);
+        run_challenge(&mut render, &mut challenge, scores.as_ref())?;
+        return Ok(());
+    }
+    if args.compare.is_some() && args.perturb.is_some() {
+        return Err("--compare and --perturb each take the other half of the screen".into());
+    }
+    // The board is that much bigger than what is shown of it.
+    let margin = args.frontend.margin;
+    if margin > 0 && side_by_side {
+        let message = "--compare and --perturb show both worlds whole, so can't keep a --margin";
+        return Err(message.into());
+    }
+    let size = grown(size, margin);
 
-#[test]
-fn test_count_neighbors() {
-    let mut game = GameOfLife::new((3, 3));
-    game.set(0, 0, true);
-    game.set(1, 0, true);
-    game.set(2, 0, true);
+    let mut game = match &args.scene {
+        Some(path) => {
+            let (scene, game, warnings) = load_scene(path, size)?;
+            if let Some(out) = &args.export_scene {
+                std::fs::write(out, scene.to_string())?;
+            }
+            args.frontend.notices.extend(warnings);
+            args.frontend.annotations = scene.metadata.annotations;
+            game
+        }
+        None if args.export_scene.is_some() => return Err("--export-scene needs --scene".into()),
+        None => {
+            let mut game = GameOfLife::new_empty(size);
+            game.fill(SOUP_DENSITY, seed);
+            game
+        }
+    };
+    #[cfg(feature = "image")]
+    if let Some(path) = &args.image {
+        game = GameOfLife::from_image(path, IMAGE_THRESHOLD)?;
+        game.resize(size);
+    }
+    if let Some((name, spacing)) = &args.tile {
+        game.tile_pattern(name, *spacing, &args.transforms)?;
+    }
+    if let Some((from, to, axis)) = args.gradient {
+        game.fill_gradient(from, to, axis, seed);
+    }
+    if let Some(Start::Maze) = args.start {
+        game.fill_maze(seed);
+    }
+    // A scene brings its own rule and edges.
+    if args.scene.is_none() {
+        game.set_rule(args.rule);
+        game.set_edge_mode(args.edges);
+    }
+    match choice {
+        Some(Choice::Quit) => return Ok(()),
+        Some(Choice::Soup(density)) => game.fill(density, seed),
+        Some(Choice::Pattern(name)) => {
+            let pattern = Pattern::named(name)
+                .unwrap_or_default()
+                .transformed(&args.transforms);
+            let (pw, ph) = pattern.size();
+            game = GameOfLife::new_empty(size);
+            let origin = GridPos(size.0.saturating_sub(pw) / 2, size.1.saturating_sub(ph) / 2);
+            let placed = game.insert_pattern(&pattern, origin);
+            args.frontend.notices.extend(placed.warning(name));
+        }
+        Some(Choice::Resume) => {
+            let (frame, warnings) = load_session(&args.frontend.history_path)?;
+            game.restore(&frame);
+            args.frontend.notices.extend(warnings);
+        }
+        None => {}
+    }
+    if let Some(frame) = &recovered {
+        game.restore(frame);
+    }
+    if let Some(Demo::Guns) = args.demo {
+        game.init_with_glider_guns(DEMO_GUNS, &args.transforms)?;
+    }
+    #[cfg(feature = "tags")]
+    if let Some(teams) = args.teams {
+        game.tag_by_columns(teams);
+    }
+    if let Some(shape) = &args.mask {
+        game.set_mask(Some(shape.build(game.size())));
+    }
+    // Past the chaos a soup starts in, before anyone sees it. A recovered
+    // board is already where it was left.
+    let warmup = if recovered.is_some() { 0 } else { args.warmup };
+    for _ in 0..warmup {
+        game.step();
+    }
+    if let Some(rule) = args.compare {
+        let mut other = game.clone();
+        other.set_rule(rule);
+        run_compare(&mut render, [&mut game, &mut other])?;
+        return Ok(());
+    }
+    if let Some(flips) = args.perturb {
+        let mut other = Perturbation::new(&game, flips, seed).perturbed;
+        run_compare(&mut render, [&mut game, &mut other])?;
+        return Ok(());
+    }
+    let mut options = args.frontend;
+    options.sweep = args.sweep.map(|step| SweepConfig { step, seed });
+    if let Some(screensaver) = &mut options.screensaver {
+        if options.sweep.is_some() {
+            return Err("--sweep and --screensaver both reseed the board; pick one".into());
+        }
+        screensaver.seed = seed;
+    }
+    options.injector = args
+        .inject
+        .map(|(edge, rate, spread)| Injector::new(edge, rate, spread, seed));
+    options.smooth = match args.dissolve {
+        Some(_) => Some(Smooth::Dissolve),
+        None => args.smooth.then_some(smooth),
+    };
+    options.autosave = args.autosave;
+    options.activity = args.activity.is_some();
+    let capture_dir = options.capture.as_ref().map(|capture| capture.dir.clone());
+    let summary = run_app(
+        &mut render,
+        AppConfig {
+            options,
+            ..AppConfig::new(Source::Game(Box::new(game)))
+        },
+    )?;
+    // Printed once the terminal is back to how it was.
+    drop(render);
+    match args.stats {
+        true => println!("{}", summary.stats(seed)),
+        false => println!("{}", summary.report(seed)),
+    }
+    if let (Some(report), Some(dir)) = (summary.captures, capture_dir) {
+        println!("{}", report.line(&dir));
+    }
+    if let Some(path) = &args.chart {
+        summary
+            .save_population_svg(path)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+    if let Some(path) = &args.activity {
+        summary
+            .save_activity_png(path)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
 
-    game.set(0, 1, true);
-    game.set(1, 1, true);
-    game.set(2, 1, true);
-    let neighbors = GameOfLife::count_neighbors(&game, 1, 1);
-    assert_eq!(neighbors, 5);
+    Ok(())
 }
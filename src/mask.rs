@@ -0,0 +1,191 @@
+use crate::{Pattern, Rect};
+
+/// What a [`Mask`] allows at a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskCell {
+    /// The cell follows the rules as usual.
+    #[default]
+    Free,
+    /// The cell is always dead, so it never counts as a neighbor.
+    ForcedDead,
+    /// The cell is always alive, acting as a wall that counts as a neighbor.
+    ForcedAlive,
+}
+
+impl MaskCell {
+    /// The state the cell is held at, if any.
+    pub fn forced(self) -> Option<bool> {
+        match self {
+            MaskCell::Free => None,
+            MaskCell::ForcedDead => Some(false),
+            MaskCell::ForcedAlive => Some(true),
+        }
+    }
+}
+
+/// A stencil the same size as the board restricting where cells can live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    size: (usize, usize),
+    cells: Vec<MaskCell>,
+}
+
+impl Mask {
+    /// A mask leaving every cell free.
+    pub fn new(size: (usize, usize)) -> Self {
+        Self {
+            size,
+            cells: vec![MaskCell::Free; size.0 * size.1],
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> MaskCell {
+        self.cells[x + y * self.size.0]
+    }
+
+    /// Sets a cell, ignoring coordinates outside the mask.
+    pub fn set(&mut self, x: usize, y: usize, cell: MaskCell) {
+        if x < self.size.0 && y < self.size.1 {
+            self.cells[x + y * self.size.0] = cell;
+        }
+    }
+
+    /// Marks the live cells of `pattern`, placed with its top-left corner at
+    /// `origin`, as `cell`.
+    pub fn pattern(mut self, pattern: &Pattern, origin: (usize, usize), cell: MaskCell) -> Self {
+        for &(x, y) in pattern.cells() {
+            self.set(origin.0 + x, origin.1 + y, cell);
+        }
+        self
+    }
+
+    /// Marks everything outside the circle around `center` as `cell`.
+    pub fn outside_circle(mut self, center: (usize, usize), radius: usize, cell: MaskCell) -> Self {
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let dx = x.abs_diff(center.0);
+                let dy = y.abs_diff(center.1);
+                if dx * dx + dy * dy > radius * radius {
+                    self.set(x, y, cell);
+                }
+            }
+        }
+        self
+    }
+
+    /// Marks the one-cell outline of `rect` as `cell`.
+    pub fn rect_border(mut self, rect: Rect, cell: MaskCell) -> Self {
+        if rect.width == 0 || rect.height == 0 {
+            return self;
+        }
+        let (right, bottom) = (rect.x + rect.width - 1, rect.y + rect.height - 1);
+        for x in rect.x..=right {
+            self.set(x, rect.y, cell);
+            self.set(x, bottom, cell);
+        }
+        for y in rect.y..=bottom {
+            self.set(rect.x, y, cell);
+            self.set(right, y, cell);
+        }
+        self
+    }
+
+    /// Marks `text` written in a 5×7 font, with its top-left corner at
+    /// `origin`, as `cell`. Letters are case-insensitive and characters the
+    /// font doesn't have are left blank. Glyphs are 6 cells apart.
+    pub fn text(mut self, text: &str, origin: (usize, usize), cell: MaskCell) -> Self {
+        for (i, c) in text.chars().enumerate() {
+            let rows = glyph(c.to_ascii_uppercase());
+            for (y, row) in rows.iter().enumerate() {
+                for x in 0..5 {
+                    if row & (0b10000 >> x) != 0 {
+                        self.set(origin.0 + i * 6 + x, origin.1 + y, cell);
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// Changes the mask size, keeping the overlap; new cells are free.
+    pub fn resize(&mut self, size: (usize, usize)) {
+        let mut resized = Mask::new(size);
+        for y in 0..self.size.1.min(size.1) {
+            for x in 0..self.size.0.min(size.0) {
+                resized.set(x, y, self.get(x, y));
+            }
+        }
+        *self = resized;
+    }
+
+    /// Size in cells of `text` as drawn by [`text`](Self::text).
+    pub fn text_size(text: &str) -> (usize, usize) {
+        let n = text.chars().count();
+        ((n * 6).saturating_sub(1), 7)
+    }
+}
+
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        _ => [0; 7],
+    }
+}
+
+#[test]
+fn test_mask_text_glyph() {
+    let mask = Mask::new((12, 7)).text("hi", (0, 0), MaskCell::ForcedDead);
+    let row = |y: usize| {
+        (0..12)
+            .map(|x| match mask.get(x, y) {
+                MaskCell::Free => '.',
+                _ => '#',
+            })
+            .collect::<String>()
+    };
+    assert_eq!(row(0), "#...#..###..");
+    assert_eq!(row(3), "#####...#...");
+    assert_eq!(Mask::text_size("hi"), (11, 7));
+}
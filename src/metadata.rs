@@ -0,0 +1,339 @@
+use std::fmt;
+
+use crate::{Annotation, EdgeMode, GameOfLife, Rule};
+
+/// The layout of metadata written now. Layout 1, from before layouts were
+/// numbered, had only the generation, as a `generation N` comment.
+pub const VERSION: u32 = 2;
+
+/// What a saved board says about itself besides its cells, read and written
+/// one way wherever it is kept: as `#C key = value` comments in RLE files,
+/// the history file among them, and as `key = value` lines in scene files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// The layout the fields were written in, brought up to [`VERSION`] on
+    /// reading unless it is from a later one.
+    pub version: u32,
+    pub generation: Option<u64>,
+    pub rule: Option<Rule>,
+    pub edges: Option<EdgeMode>,
+    /// The seed the board was filled from.
+    pub seed: Option<u64>,
+    /// Notes pinned to cells, one `annotation = X,Y COLOR TEXT` line each,
+    /// in the order they were added.
+    pub annotations: Vec<Annotation>,
+    /// Fields this version doesn't know, from a later one, kept in order so
+    /// that saving again writes them back as they were.
+    pub unknown: Vec<(String, String)>,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            generation: None,
+            rule: None,
+            edges: None,
+            seed: None,
+            annotations: Vec::new(),
+            unknown: Vec::new(),
+        }
+    }
+}
+
+/// Where two accounts of a field disagreed, and which was gone by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub field: &'static str,
+    /// The value that won, from the header.
+    pub header: String,
+    /// The value passed over, from a comment.
+    pub comment: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the header gives {0} {1} but a comment gives {0} {2}; going by the header",
+            self.field, self.header, self.comment
+        )
+    }
+}
+
+impl Metadata {
+    /// The generation, rule, edges and seed of `game`.
+    pub fn of(game: &GameOfLife) -> Self {
+        Self {
+            generation: Some(game.generation()),
+            rule: Some(game.rule()),
+            edges: Some(game.edge_mode()),
+            seed: game.seed(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads the fields among `lines`, which may have other text between
+    /// them, then [migrates](Self::migrate) them.
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut metadata = Self::unversioned();
+        for line in lines {
+            metadata.read_line(line)?;
+        }
+        metadata.migrate();
+        Ok(metadata)
+    }
+
+    /// Nothing yet, in layout 1 until a `version` field says otherwise: what
+    /// [`read_line`](Self::read_line) starts from.
+    pub fn unversioned() -> Self {
+        Self {
+            version: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Reads `line` if it is a field, returning whether it was. A field is
+    /// `key = value` with a key of one word; any other line is prose and is
+    /// left alone. A field given twice must agree with itself, but for
+    /// `annotation`, which adds another note each time.
+    pub fn read_line(&mut self, line: &str) -> Result<bool, String> {
+        let line = line.trim();
+        let field = match line.split_once('=') {
+            Some((key, value)) => Some((key.trim(), value.trim())),
+            // Layout 1 wrote the generation without an `=`.
+            None if self.version == 1 => line
+                .strip_prefix("generation ")
+                .filter(|n| n.trim().parse::<u64>().is_ok())
+                .map(|n| ("generation", n.trim())),
+            None => None,
+        };
+        let Some((key, value)) = field else {
+            return Ok(false);
+        };
+        let word = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if key.is_empty() || !key.chars().all(word) {
+            return Ok(false);
+        }
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("bad {key} {value:?}"))
+        };
+        match key {
+            "version" => {
+                let version = number()?;
+                let version = u32::try_from(version)
+                    .ok()
+                    .filter(|&version| version > 0)
+                    .ok_or_else(|| format!("bad version {value:?}"))?;
+                // Until one is read, it is layout 1 by default.
+                if self.version != 1 && self.version != version {
+                    return Err(twice(key, &self.version, &version));
+                }
+                self.version = version;
+            }
+            "generation" => set_once(&mut self.generation, key, number()?)?,
+            "rule" => set_once(&mut self.rule, key, value.parse()?)?,
+            "edges" => set_once(&mut self.edges, key, value.parse()?)?,
+            "seed" => set_once(&mut self.seed, key, number()?)?,
+            "annotation" => self.annotations.push(value.parse()?),
+            _ => match self.unknown.iter().find(|(known, _)| known == key) {
+                Some((_, was)) if was != value => return Err(twice(key, was, &value)),
+                Some(_) => {}
+                None => self.unknown.push((key.to_string(), value.to_string())),
+            },
+        }
+        Ok(true)
+    }
+
+    /// Brings fields read in an older layout up to this one. Layout 1's
+    /// generation means what it does now, and its other spelling was read as
+    /// it came, so all that is left is the number.
+    pub fn migrate(&mut self) {
+        self.version = self.version.max(VERSION);
+    }
+
+    /// The fields, one `key = value` line each, the version first and the
+    /// fields of a later version last.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("version = {}", self.version.max(VERSION))];
+        lines.extend(
+            self.generation
+                .map(|generation| format!("generation = {generation}")),
+        );
+        lines.extend(self.rule.map(|rule| format!("rule = {rule}")));
+        lines.extend(self.edges.map(|edges| format!("edges = {edges}")));
+        lines.extend(self.seed.map(|seed| format!("seed = {seed}")));
+        lines.extend(
+            self.annotations
+                .iter()
+                .map(|note| format!("annotation = {note}")),
+        );
+        lines.extend(
+            self.unknown
+                .iter()
+                .map(|(key, value)| format!("{key} = {value}")),
+        );
+        lines
+    }
+
+    /// Settles the fields that `header`, read from a file's header, gives as
+    /// well. The header wins, being what other programs read and what they
+    /// change when they edit the file; each field where this disagreed is
+    /// returned.
+    pub fn reconcile(&mut self, header: &Metadata) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        resolve(
+            &mut self.generation,
+            header.generation,
+            "generation",
+            &mut conflicts,
+        );
+        resolve(&mut self.rule, header.rule, "rule", &mut conflicts);
+        resolve(&mut self.edges, header.edges, "edges", &mut conflicts);
+        resolve(&mut self.seed, header.seed, "seed", &mut conflicts);
+        conflicts
+    }
+}
+
+fn twice(key: &str, was: &impl fmt::Display, value: &impl fmt::Display) -> String {
+    format!("{key} is given twice, as {was} and as {value}")
+}
+
+fn set_once<T>(field: &mut Option<T>, key: &str, value: T) -> Result<(), String>
+where
+    T: PartialEq + fmt::Display,
+{
+    match field {
+        Some(was) if *was != value => Err(twice(key, was, &value)),
+        _ => {
+            *field = Some(value);
+            Ok(())
+        }
+    }
+}
+
+/// Takes `header`'s value for a field over `comment`'s, noting a conflict
+/// if both are there and differ.
+fn resolve<T>(
+    comment: &mut Option<T>,
+    header: Option<T>,
+    field: &'static str,
+    into: &mut Vec<Conflict>,
+) where
+    T: PartialEq + fmt::Display,
+{
+    if let Some(header) = header {
+        if let Some(comment) = comment.as_ref().filter(|comment| **comment != header) {
+            into.push(Conflict {
+                field,
+                header: header.to_string(),
+                comment: comment.to_string(),
+            });
+        }
+        *comment = Some(header);
+    }
+}
+
+#[test]
+fn test_metadata_round_trips_and_migrates() {
+    let metadata = Metadata {
+        generation: Some(40),
+        rule: Some("B36/S23".parse().unwrap()),
+        edges: Some(EdgeMode::Mirror),
+        seed: Some(7),
+        ..Metadata::default()
+    };
+    let lines = metadata.lines();
+    assert_eq!(
+        lines,
+        [
+            "version = 2",
+            "generation = 40",
+            "rule = B36/S23",
+            "edges = mirror",
+            "seed = 7"
+        ]
+    );
+    // Prose between fields is passed over.
+    let text = ["A note", "see https://example.com/?a=b"];
+    let parsed = Metadata::parse(text.into_iter().chain(lines.iter().map(String::as_str)));
+    assert_eq!(parsed, Ok(metadata));
+
+    // Notes may come any number of times, and keep their order.
+    let notes = [
+        "annotation = 3,1 red eater",
+        "annotation = 0,0 yellow gun #1",
+    ];
+    let noted = Metadata::parse(notes).unwrap();
+    assert_eq!(noted.annotations.len(), 2);
+    assert_eq!(noted.lines()[1..], notes);
+    assert!(Metadata::parse(["annotation = 3,1 mauve eater"]).is_err());
+
+    // Missing fields are missing, whatever the layout.
+    assert_eq!(Metadata::parse(["version = 2"]), Ok(Metadata::default()));
+    assert_eq!(Metadata::parse([]), Ok(Metadata::default()));
+
+    // Layout 1 wrote only `generation N`, which a layout 2 file doesn't.
+    let old = Metadata::parse([" started 2024-02-29 13:05:09 UTC, 1.0 s", " generation 12"]);
+    assert_eq!(
+        old,
+        Ok(Metadata {
+            generation: Some(12),
+            ..Metadata::default()
+        })
+    );
+    let new = Metadata::parse(["version = 2", "generation 12"]).unwrap();
+    assert_eq!(new.generation, None);
+
+    // A later version's fields are kept, in order, to be written back.
+    let later = Metadata::parse([
+        "version = 3",
+        "topology = klein",
+        "rule = B3/S23",
+        "tag = x",
+    ]);
+    let later = later.unwrap();
+    assert_eq!(later.version, 3);
+    assert_eq!(
+        later.unknown,
+        [
+            ("topology".into(), "klein".into()),
+            ("tag".into(), "x".into())
+        ]
+    );
+    assert_eq!(
+        later.lines(),
+        [
+            "version = 3",
+            "rule = B3/S23",
+            "topology = klein",
+            "tag = x"
+        ]
+    );
+
+    // A field can't say two things, nor a known one something unreadable.
+    let err = Metadata::parse(["rule = B3/S23", "rule = B36/S23"]).unwrap_err();
+    assert_eq!(err, "rule is given twice, as B3/S23 and as B36/S23");
+    assert!(Metadata::parse(["rule = B3/S23", "rule = b3/s23"]).is_ok());
+    assert!(Metadata::parse(["generation = soon"]).is_err());
+    assert!(Metadata::parse(["version = 0"]).is_err());
+
+    // The header wins where both say something.
+    let mut comments = Metadata::parse(["rule = B3/S23", "seed = 4"]).unwrap();
+    let header = Metadata {
+        rule: Some("B36/S23".parse().unwrap()),
+        ..Metadata::default()
+    };
+    let conflicts = comments.reconcile(&header);
+    assert_eq!(comments.rule, header.rule);
+    assert_eq!(comments.seed, Some(4));
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(|conflict| conflict.to_string())
+            .collect::<Vec<_>>(),
+        ["the header gives rule B36/S23 but a comment gives rule B3/S23; going by the header"]
+    );
+}
@@ -0,0 +1,86 @@
+use crate::{EdgeMode, GameOfLife, GridPos};
+
+/// Every cell's live-neighbor count, kept up to date through edits by
+/// recounting only around the cells that changed, for overlays that show
+/// counts while the board is edited.
+///
+/// Edits must go through [`set`](Self::set) or [`toggle`](Self::toggle) to be
+/// noticed. Anything else that changes the board's size, edges or generation
+/// causes a full recount on the next [`counts`](Self::counts).
+#[derive(Debug, Clone, Default)]
+pub struct NeighborCounts {
+    counts: Vec<u8>,
+    /// What the counts were taken from: the board's size, edges and
+    /// generation.
+    taken: Option<((usize, usize), EdgeMode, u64)>,
+    /// Cells edited since the counts were last brought up to date.
+    dirty: Vec<(usize, usize)>,
+}
+
+impl NeighborCounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cell at `(x, y)`, as [`GameOfLife::set`] does, and marks its
+    /// neighbors for recounting.
+    pub fn set(&mut self, game: &mut GameOfLife, x: usize, y: usize, value: bool) {
+        if game.get(GridPos(x, y)) != value {
+            game.set(GridPos(x, y), value);
+            self.dirty.push((x, y));
+        }
+    }
+
+    /// Flips the cell at `(x, y)` between alive and dead.
+    pub fn toggle(&mut self, game: &mut GameOfLife, x: usize, y: usize) {
+        self.set(game, x, y, !game.get(GridPos(x, y)));
+    }
+
+    /// The live-neighbor count of every cell, row-major as in
+    /// [`GameOfLife::surface`]. Only the cells around edits are recounted,
+    /// unless the board has moved on some other way.
+    pub fn counts(&mut self, game: &GameOfLife) -> &[u8] {
+        let taken = (game.size(), game.edge_mode(), game.generation());
+        if self.taken != Some(taken) {
+            game.neighbor_counts_into(&mut self.counts);
+            self.taken = Some(taken);
+            self.dirty.clear();
+        }
+        for (x, y) in std::mem::take(&mut self.dirty) {
+            for pos in game.neighbors(GridPos(x, y)) {
+                self.counts[game.index(pos)] = game.count_neighbors(pos) as u8;
+            }
+        }
+        &self.counts
+    }
+}
+
+#[test]
+fn test_incremental_counts_match_full_recount() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+        let mut game = GameOfLife::new((17, 11));
+        game.set_edge_mode(edges);
+        game.fill(0.3, 1);
+        let mut cache = NeighborCounts::new();
+        cache.counts(&game);
+        let mut rng = StdRng::seed_from_u64(2);
+        for batch in 0..10 {
+            for _ in 0..25 {
+                let (x, y) = (rng.gen_range(0..17), rng.gen_range(0..11));
+                if rng.gen() {
+                    cache.toggle(&mut game, x, y);
+                } else {
+                    cache.set(&mut game, x, y, rng.gen());
+                }
+            }
+            let full = NeighborCounts::new().counts(&game).to_vec();
+            assert_eq!(cache.counts(&game), full, "{edges:?} batch {batch}");
+        }
+        // Stepping changes everything, so it is recounted in full.
+        game.step();
+        let full = NeighborCounts::new().counts(&game).to_vec();
+        assert_eq!(cache.counts(&game), full);
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Told about each step a board takes, for timing the engine from outside:
+/// see [`GameOfLife::set_observer`](crate::GameOfLife::set_observer) and
+/// [`tick_observed`](crate::GameOfLife::tick_observed). A board with no
+/// observer doesn't time or count anything.
+pub trait SimObserver {
+    /// A step from `generation` is starting.
+    fn on_step_start(&mut self, _generation: u64) {}
+
+    /// The step to `generation` took `duration`, bringing `births` cells to
+    /// life and killing `deaths`.
+    fn on_step_end(
+        &mut self,
+        _generation: u64,
+        _duration: Duration,
+        _births: usize,
+        _deaths: usize,
+    ) {
+    }
+}
+
+/// Ignores every step.
+impl SimObserver for () {}
+
+/// Passes each step on to the observer shared, so that whoever handed the
+/// board a clone can still read it.
+impl<T: SimObserver> SimObserver for Arc<Mutex<T>> {
+    fn on_step_start(&mut self, generation: u64) {
+        if let Ok(mut observer) = self.lock() {
+            observer.on_step_start(generation);
+        }
+    }
+
+    fn on_step_end(&mut self, generation: u64, duration: Duration, births: usize, deaths: usize) {
+        if let Ok(mut observer) = self.lock() {
+            observer.on_step_end(generation, duration, births, deaths);
+        }
+    }
+}
+
+/// What a [`SimObserver`] is told, one call each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    Start {
+        generation: u64,
+    },
+    End {
+        generation: u64,
+        duration: Duration,
+        births: usize,
+        deaths: usize,
+    },
+}
+
+/// Keeps the events instead, so that tests can see what was reported.
+impl SimObserver for Vec<StepEvent> {
+    fn on_step_start(&mut self, generation: u64) {
+        self.push(StepEvent::Start { generation });
+    }
+
+    fn on_step_end(&mut self, generation: u64, duration: Duration, births: usize, deaths: usize) {
+        self.push(StepEvent::End {
+            generation,
+            duration,
+            births,
+            deaths,
+        });
+    }
+}
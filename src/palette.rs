@@ -0,0 +1,333 @@
+use std::{fmt, str::FromStr};
+
+use termwiz::color::{AnsiColor, ColorAttribute, SrgbaTuple};
+
+/// The colors cells are drawn in: one per state, and optionally one per age
+/// bucket for two-state rules, where a live cell's color then depends on how
+/// many generations it has been alive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Indexed by state; state 0 is dead.
+    states: Vec<ColorAttribute>,
+    /// `(first age, color)`, sorted by age.
+    ages: Vec<(u32, ColorAttribute)>,
+}
+
+impl Palette {
+    /// The built-in palette for a rule family, by name: `life`, `life-age` or
+    /// `brians-brain`.
+    pub fn builtin(family: &str) -> Option<Self> {
+        let ansi = |color: AnsiColor| ColorAttribute::PaletteIndex(color as u8);
+        let palette = match family {
+            "life" => Self::default(),
+            "life-age" => Self {
+                ages: vec![
+                    (0, ansi(AnsiColor::Lime)),
+                    (4, ansi(AnsiColor::Yellow)),
+                    (16, ansi(AnsiColor::Red)),
+                ],
+                ..Self::default()
+            },
+            "brians-brain" => Self {
+                states: vec![
+                    ansi(AnsiColor::Black),
+                    ansi(AnsiColor::White),
+                    ansi(AnsiColor::Blue),
+                ],
+                ages: Vec::new(),
+            },
+            _ => return None,
+        };
+        Some(palette)
+    }
+
+    /// Reads the `[palette]` section of a theme file, or the whole file if it
+    /// has no sections. Each line is `STATE = COLOR` or `age AGE = COLOR`,
+    /// where a color is a name, a `#rrggbb` hex code or a 256-color index.
+    /// Comments are lines starting with `#`, or anything after ` # `. States
+    /// left out take the default palette's.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let sectioned = text.lines().any(|line| line.trim().starts_with('['));
+        let mut in_palette = !sectioned;
+        let mut states = Self::default().states;
+        let mut ages = Vec::new();
+        for line in text.lines() {
+            let line = line.split(" # ").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                in_palette = section.trim_end_matches(']').trim() == "palette";
+                continue;
+            }
+            if !in_palette {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected KEY = COLOR, got {line:?}"))?;
+            let color = parse_color(value.trim())?;
+            let key = key.trim();
+            if let Some(age) = key.strip_prefix("age") {
+                let age = age
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("bad age {:?}", age.trim()))?;
+                ages.retain(|&(first, _)| first != age);
+                ages.push((age, color));
+            } else {
+                let state = key
+                    .parse::<usize>()
+                    .map_err(|_| format!("bad state {key:?}"))?;
+                if states.len() <= state {
+                    states.resize(state + 1, ColorAttribute::Default);
+                }
+                states[state] = color;
+            }
+        }
+        ages.sort_by_key(|&(age, _)| age);
+        Ok(Self { states, ages })
+    }
+
+    /// The color of a cell in `state`. States past the last one given take
+    /// the last color.
+    pub fn state(&self, state: usize) -> ColorAttribute {
+        let last = self.states.len().saturating_sub(1);
+        self.states
+            .get(state.min(last))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether live cells are colored by age rather than by state.
+    pub fn has_ages(&self) -> bool {
+        !self.ages.is_empty()
+    }
+
+    /// The color of a live cell that has been alive for `age` generations:
+    /// that of the last bucket starting at or before it, or of state 1 when
+    /// no bucket does.
+    pub fn age(&self, age: u32) -> ColorAttribute {
+        self.ages
+            .iter()
+            .rev()
+            .find(|&&(first, _)| first <= age)
+            .map_or_else(|| self.state(1), |&(_, color)| color)
+    }
+}
+
+impl Default for Palette {
+    /// Plain Life in the terminal's own colors.
+    fn default() -> Self {
+        Self {
+            states: vec![ColorAttribute::Default, ColorAttribute::Default],
+            ages: Vec::new(),
+        }
+    }
+}
+
+/// Whether what is drawn is meant to show up against a dark terminal
+/// background or a light one: greys fade towards the background whichever
+/// it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// The name [`FromStr`] reads: `dark` or `light`.
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        })
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(format!("unknown theme {s:?}, expected dark or light")),
+        }
+    }
+}
+
+impl Theme {
+    /// The theme for a background of `rgb`, each channel from 0 to 1: dark if
+    /// white stands out more against it than black does.
+    pub fn for_background(rgb: (f64, f64, f64)) -> Self {
+        // Where the contrast ratios with black and white are equal.
+        if luminance(rgb) < (1.05f64 * 0.05).sqrt() - 0.05 {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+}
+
+/// The relative luminance of an sRGB color, each channel from 0 to 1, from
+/// 0 for black to 1 for white.
+pub fn luminance((r, g, b): (f64, f64, f64)) -> f64 {
+    let linear = |c: f64| match c <= 0.04045 {
+        true => c / 12.92,
+        false => ((c + 0.055) / 1.055).powf(2.4),
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// The background color in a terminal's reply to an OSC 11 query,
+/// `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` ended by `ESC \` or BEL, where each
+/// channel has from one to four hex digits. `None` until the whole reply is
+/// there, or if it isn't one.
+pub fn parse_background_reply(reply: &str) -> Option<(f64, f64, f64)> {
+    let start = reply.find("\x1b]11;rgb:")?;
+    let rest = &reply[start + "\x1b]11;rgb:".len()..];
+    let (channels, end) = rest.split_at(rest.find(['\x07', '\x1b'])?);
+    if !end.starts_with('\x07') && !end.starts_with("\x1b\\") {
+        return None;
+    }
+    let mut channels = channels.split('/').map(|digits| {
+        if !(1..=4).contains(&digits.len()) {
+            return None;
+        }
+        let max = 16u32.pow(digits.len() as u32) - 1;
+        Some(u32::from_str_radix(digits, 16).ok()? as f64 / max as f64)
+    });
+    let rgb = (channels.next()??, channels.next()??, channels.next()??);
+    channels.next().is_none().then_some(rgb)
+}
+
+pub(crate) fn parse_color(s: &str) -> Result<ColorAttribute, String> {
+    let ansi = |color: AnsiColor| ColorAttribute::PaletteIndex(color as u8);
+    let color = match s.to_ascii_lowercase().as_str() {
+        "default" => ColorAttribute::Default,
+        "black" => ansi(AnsiColor::Black),
+        "red" => ansi(AnsiColor::Red),
+        "green" => ansi(AnsiColor::Lime),
+        "yellow" => ansi(AnsiColor::Yellow),
+        "blue" => ansi(AnsiColor::Blue),
+        "magenta" => ansi(AnsiColor::Fuchsia),
+        "cyan" => ansi(AnsiColor::Aqua),
+        "white" => ansi(AnsiColor::White),
+        "grey" | "gray" => ansi(AnsiColor::Grey),
+        hex if hex.starts_with('#') => {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .map(|c| c as f32 / 255.0)
+            };
+            match (hex.len(), channel(1), channel(3), channel(5)) {
+                (7, Some(r), Some(g), Some(b)) => {
+                    ColorAttribute::TrueColorWithDefaultFallback(SrgbaTuple(r, g, b, 1.0))
+                }
+                _ => return Err(format!("bad hex color {s:?}, expected #rrggbb")),
+            }
+        }
+        index => index
+            .parse::<u8>()
+            .map(ColorAttribute::PaletteIndex)
+            .map_err(|_| format!("unknown color {s:?}"))?,
+    };
+    Ok(color)
+}
+
+#[test]
+fn test_palette_parse() {
+    let theme = "[view]\nzoom = 2\n\n[palette]\n# Brian's Brain\n0 = black\n1 = #FF8000\n";
+    let palette = Palette::parse(&format!("{theme}2 = blue # dying\n")).unwrap();
+    assert_eq!(
+        palette.state(0),
+        ColorAttribute::PaletteIndex(AnsiColor::Black as u8)
+    );
+    assert_eq!(
+        palette.state(1),
+        ColorAttribute::TrueColorWithDefaultFallback(SrgbaTuple(1.0, 128.0 / 255.0, 0.0, 1.0))
+    );
+    assert_eq!(
+        palette.state(2),
+        ColorAttribute::PaletteIndex(AnsiColor::Blue as u8)
+    );
+    assert_eq!(
+        Palette::parse("1 = 208").unwrap().state(1),
+        ColorAttribute::PaletteIndex(208)
+    );
+    for bad in [
+        "1 = #12345",
+        "1 = #gg0000",
+        "1 = mauve",
+        "x = red",
+        "age y = red",
+        "1 red",
+    ] {
+        assert!(Palette::parse(bad).is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn test_palette_resolution_clamps() {
+    let brain = Palette::builtin("brians-brain").unwrap();
+    assert_eq!(brain.state(7), brain.state(2));
+    assert_eq!(brain.state(usize::MAX), brain.state(2));
+    assert_eq!(Palette::default().state(3), ColorAttribute::Default);
+
+    let aged = Palette::parse("1 = white\nage 10 = red\nage 3 = yellow").unwrap();
+    assert!(aged.has_ages());
+    assert_eq!(aged.age(0), aged.state(1));
+    assert_eq!(
+        aged.age(3),
+        ColorAttribute::PaletteIndex(AnsiColor::Yellow as u8)
+    );
+    assert_eq!(aged.age(9), aged.age(3));
+    assert_eq!(
+        aged.age(u32::MAX),
+        ColorAttribute::PaletteIndex(AnsiColor::Red as u8)
+    );
+    assert!(Palette::builtin("wireworld").is_none());
+}
+
+#[test]
+fn test_background_replies() {
+    let white = Some((1.0, 1.0, 1.0));
+    assert_eq!(
+        parse_background_reply("\x1b]11;rgb:ffff/ffff/ffff\x1b\\"),
+        white
+    );
+    assert_eq!(parse_background_reply("\x1b]11;rgb:ff/ff/ff\x07"), white);
+    // Keys typed before the reply came back don't hide it.
+    assert_eq!(
+        parse_background_reply("jk\x1b]11;rgb:0/0/0\x07"),
+        Some((0.0, 0.0, 0.0))
+    );
+    let grey = parse_background_reply("\x1b]11;rgb:8080/8080/8080\x1b\\").unwrap();
+    assert!((grey.0 - 0x8080 as f64 / 0xffff as f64).abs() < 1e-9);
+    for bad in [
+        "\x1b]11;rgb:ffff/ffff/ff",
+        "\x1b]11;rgb:ffff/ffff\x07",
+        "\x1b]11;rgb:ffff/ffff/ffff/ffff\x07",
+        "\x1b]11;rgb:fffff/0/0\x07",
+        "\x1b]11;rgb:gg/0/0\x07",
+        "\x1b]10;rgb:0/0/0\x07",
+    ] {
+        assert_eq!(parse_background_reply(bad), None, "{bad:?}");
+    }
+}
+
+#[test]
+fn test_theme_follows_luminance() {
+    assert_eq!(luminance((0.0, 0.0, 0.0)), 0.0);
+    assert!((luminance((1.0, 1.0, 1.0)) - 1.0).abs() < 1e-9);
+    // Green counts for far more than blue.
+    assert!(luminance((0.0, 1.0, 0.0)) > 5.0 * luminance((0.0, 0.0, 1.0)));
+    assert_eq!(Theme::for_background((0.0, 0.0, 0.0)), Theme::Dark);
+    assert_eq!(Theme::for_background((0.16, 0.16, 0.2)), Theme::Dark);
+    assert_eq!(Theme::for_background((1.0, 1.0, 0.94)), Theme::Light);
+    assert_eq!(Theme::for_background((0.6, 0.6, 0.6)), Theme::Light);
+    assert_eq!("light".parse(), Ok(Theme::Light));
+    assert!("sepia".parse::<Theme>().is_err());
+}
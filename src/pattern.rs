@@ -0,0 +1,284 @@
+use braille::BRAILLE;
+
+use crate::{scene::Transform, Axis};
+
+/// Patterns that can be looked up by name, drawn with `#` for live cells.
+const LIBRARY: &[(&str, &[&str])] = &[
+    ("block", &["##", "##"]),
+    ("blinker", &["###"]),
+    ("toad", &[".###", "###."]),
+    ("beacon", &["##..", "##..", "..##", "..##"]),
+    ("beehive", &[".##.", "#..#", ".##."]),
+    ("glider", &[".#.", "..#", "###"]),
+    ("lwss", &[".#..#", "#....", "#...#", "####."]),
+    ("r-pentomino", &[".##", "##.", ".#."]),
+    (
+        "gosper-glider-gun",
+        &[
+            "........................#...........",
+            "......................#.#...........",
+            "............##......##............##",
+            "...........#...#....##............##",
+            "##........#.....#...##..............",
+            "##........#...#.##....#.#...........",
+            "..........#.....#.......#...........",
+            "...........#...#....................",
+            "............##......................",
+        ],
+    ),
+];
+
+/// A shape made of live cells, positioned relative to its top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pattern {
+    size: (usize, usize),
+    cells: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+    /// A pattern of the given live cells, sized to their bounding box from the
+    /// origin.
+    pub fn new(cells: Vec<(usize, usize)>) -> Self {
+        let size = cells
+            .iter()
+            .fold((0, 0), |(w, h), &(x, y)| (w.max(x + 1), h.max(y + 1)));
+        Self::with_size(size, cells)
+    }
+
+    /// A pattern of the given live cells in a box of `size`, which may have
+    /// dead margins. Cells outside the box are dropped.
+    pub fn with_size(size: (usize, usize), mut cells: Vec<(usize, usize)>) -> Self {
+        cells.retain(|&(x, y)| x < size.0 && y < size.1);
+        // Row-major and without repeats, so equal patterns compare equal.
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        Self { size, cells }
+    }
+
+    /// A pattern drawn in `art`, one line per row, with `#` or `O` for a live
+    /// cell and anything else for a dead one. The box is as wide as the
+    /// longest line and as tall as the lines, dead margins and all.
+    pub fn from_ascii(art: &str) -> Self {
+        let rows = art.lines().collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '#' || c == 'O')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect();
+        Self::with_size((width, rows.len()), cells)
+    }
+
+    /// A pattern from the built-in library, or `None` if there is no pattern
+    /// of that name.
+    pub fn named(name: &str) -> Option<Self> {
+        let &(_, rows) = LIBRARY.iter().find(|&&(known, _)| known == name)?;
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.char_indices()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect();
+        Some(Self::new(cells))
+    }
+
+    /// The names of the library patterns.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        LIBRARY.iter().map(|&(name, _)| name)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// The live cells, in row-major order.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    /// The pattern turned clockwise by `quarter_turns` quarters of a turn,
+    /// its box turning with it.
+    pub fn rotated(&self, quarter_turns: u32) -> Self {
+        let mut pattern = self.clone();
+        for _ in 0..quarter_turns % 4 {
+            let (_, h) = pattern.size;
+            let cells = pattern.cells.iter().map(|&(x, y)| (h - 1 - y, x)).collect();
+            pattern = Self::with_size((h, pattern.size.0), cells);
+        }
+        pattern
+    }
+
+    /// The pattern mirrored along `axis`: left and right swap for
+    /// [`Axis::X`], top and bottom for [`Axis::Y`].
+    pub fn flipped(&self, axis: Axis) -> Self {
+        let (w, h) = self.size;
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(x, y)| match axis {
+                Axis::X => (w - 1 - x, y),
+                Axis::Y => (x, h - 1 - y),
+            })
+            .collect();
+        Self::with_size(self.size, cells)
+    }
+
+    /// The pattern put through `transforms` in order.
+    pub fn transformed(&self, transforms: &[Transform]) -> Self {
+        transforms
+            .iter()
+            .fold(self.clone(), |pattern, transform| match *transform {
+                Transform::Rotate(turns) => pattern.rotated(turns),
+                Transform::Flip(axis) => pattern.flipped(axis),
+            })
+    }
+
+    /// Where the cell at `cell` of a box of `size` ends up when the box is
+    /// put through `transforms`, as [`transformed`](Self::transformed) moves
+    /// a pattern's cells.
+    pub fn transform_cell(
+        size: (usize, usize),
+        cell: (usize, usize),
+        transforms: &[Transform],
+    ) -> (usize, usize) {
+        let ((x, y), _) =
+            transforms.iter().fold(
+                (cell, size),
+                |((x, y), (w, h)), transform| match *transform {
+                    Transform::Rotate(turns) => (0..turns % 4)
+                        .fold(((x, y), (w, h)), |((x, y), (w, h)), _| {
+                            ((h - 1 - y, x), (h, w))
+                        }),
+                    Transform::Flip(Axis::X) => ((w - 1 - x, y), (w, h)),
+                    Transform::Flip(Axis::Y) => ((x, h - 1 - y), (w, h)),
+                },
+            );
+        (x, y)
+    }
+
+    /// A picture of the pattern in braille, `size.0` characters wide and
+    /// `size.1` tall, with the pattern's top-left at the top-left. A pattern
+    /// with more cells than the 2×4 dots per character give is shrunk by a
+    /// whole factor, each dot showing if any cell of its block is alive.
+    pub fn thumbnail(&self, size: (usize, usize)) -> Vec<String> {
+        let (cols, rows) = size;
+        let dots = (cols * 2, rows * 4);
+        let shrink = |cells: usize, dots: usize| cells.div_ceil(dots.max(1)).max(1);
+        let factor = shrink(self.size.0, dots.0).max(shrink(self.size.1, dots.1));
+        // Each char's dots, row by row, as the BRAILLE table is indexed.
+        let mut chars = vec![vec![[0; 8]; cols]; rows];
+        for &(x, y) in &self.cells {
+            let (x, y) = (x / factor, y / factor);
+            if x < dots.0 && y < dots.1 {
+                chars[y / 4][x / 2][y % 4 * 2 + x % 2] = 1;
+            }
+        }
+        chars
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&[a, b, c, d, e, f, g, h]| BRAILLE[a][b][c][d][e][f][g][h])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_named_patterns() {
+    let glider = Pattern::named("glider").unwrap();
+    assert_eq!(
+        glider,
+        Pattern::new(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+    assert_eq!(Pattern::named("blinker").unwrap().size(), (3, 1));
+    assert!(Pattern::named("gosper").is_none());
+    assert!(Pattern::names().all(|name| Pattern::named(name).is_some()));
+}
+
+#[test]
+fn test_thumbnails() {
+    let glider = Pattern::named("glider").unwrap();
+    assert_eq!(glider.thumbnail((3, 2)), ["⠬⠆⠀", "⠀⠀⠀"]);
+
+    // The gun is shrunk by half to fit eight dots down.
+    let gun = Pattern::named("gosper-glider-gun").unwrap();
+    let thumbnail = gun.thumbnail((10, 2));
+    assert_eq!(thumbnail.len(), 2);
+    assert!(thumbnail.iter().all(|line| line.chars().count() == 10));
+    assert!(thumbnail.concat().chars().any(|c| c != '⠀'));
+    assert_eq!(Pattern::default().thumbnail((2, 1)), ["⠀⠀"]);
+}
+
+#[test]
+fn test_rotate_and_flip() {
+    let glider = Pattern::named("glider").unwrap();
+    // A quarter turn clockwise sends the glider from heading down-right to
+    // heading down-left.
+    let turned = Pattern::new(vec![(0, 0), (0, 1), (2, 1), (0, 2), (1, 2)]);
+    assert_eq!(glider.rotated(1), turned);
+    assert_eq!(glider.rotated(4), glider);
+    assert_eq!(glider.rotated(1).rotated(3), glider);
+    assert_eq!(glider.flipped(Axis::X).flipped(Axis::X), glider);
+    assert_eq!(glider.rotated(2), glider.flipped(Axis::X).flipped(Axis::Y));
+
+    let gun = Pattern::named("gosper-glider-gun").unwrap();
+    assert_eq!(gun.rotated(1).size(), (9, 36));
+    assert_eq!(gun.rotated(3).cells().len(), gun.cells().len());
+}
+
+#[test]
+fn test_transform_cell_follows_the_pattern() {
+    // A lone cell in a box taller than it is wide goes where the pattern
+    // takes it, for every turn and flip in turn.
+    let (size, cell) = ((3, 5), (2, 1));
+    let lone = Pattern::with_size(size, vec![cell]);
+    let transforms = [
+        Transform::Rotate(1),
+        Transform::Flip(Axis::X),
+        Transform::Rotate(3),
+        Transform::Rotate(2),
+        Transform::Flip(Axis::Y),
+        Transform::Rotate(5),
+    ];
+    for n in 0..=transforms.len() {
+        let moved = Pattern::transform_cell(size, cell, &transforms[..n]);
+        assert_eq!(
+            lone.transformed(&transforms[..n]).cells(),
+            [moved],
+            "{n} transforms"
+        );
+    }
+    // A quarter turn clockwise takes the top-left corner to the top-right.
+    assert_eq!(
+        Pattern::transform_cell((3, 5), (0, 0), &[Transform::Rotate(1)]),
+        (4, 0)
+    );
+    assert_eq!(
+        Pattern::transform_cell((3, 5), (0, 0), &[Transform::Rotate(4)]),
+        (0, 0)
+    );
+}
+
+#[test]
+fn test_ascii_art() {
+    let glider = Pattern::from_ascii(".#.\n..#\n###");
+    assert_eq!(glider, Pattern::named("glider").unwrap());
+    // `O` is live too, anything else is dead, and short lines are padded.
+    let art = Pattern::from_ascii("O.\r\n_x_#\n\n");
+    assert_eq!(art.size(), (4, 3));
+    assert_eq!(art.cells(), [(0, 0), (3, 1)]);
+    assert_eq!(Pattern::from_ascii("").size(), (0, 0));
+}
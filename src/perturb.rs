@@ -0,0 +1,144 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, seq::index, SeedableRng};
+
+use crate::GameOfLife;
+
+/// A board and a copy of it with a few cells flipped, stepped in lockstep to
+/// see how far apart the two grow.
+#[derive(Clone)]
+pub struct Perturbation {
+    pub original: GameOfLife,
+    pub perturbed: GameOfLife,
+    /// Both boards packed, kept to reuse from step to step.
+    words: (Vec<u64>, Vec<u64>),
+}
+
+/// How far apart the boards of a [`Perturbation`] were at a generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distance {
+    pub generation: u64,
+    /// Cells alive on one board and dead on the other.
+    pub cells: usize,
+    /// Those cells as a share of the board.
+    pub fraction: f64,
+}
+
+impl Distance {
+    pub const CSV_HEADER: &'static str = "generation,distance,fraction";
+}
+
+impl fmt::Display for Distance {
+    /// A CSV row under [`CSV_HEADER`](Self::CSV_HEADER).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{:.6}", self.generation, self.cells, self.fraction)
+    }
+}
+
+impl Perturbation {
+    /// `game` and a copy of it with `flips` cells flipped, picked from
+    /// `seed`: no cell twice, and every cell if there are fewer.
+    pub fn new(game: &GameOfLife, flips: usize, seed: u64) -> Self {
+        let mut perturbed = game.clone();
+        let cells = game.cell_count();
+        let mut rng = StdRng::seed_from_u64(seed);
+        for i in index::sample(&mut rng, cells, flips.min(cells)) {
+            let pos = game.coords(i);
+            perturbed.set(pos, !game.get(pos));
+        }
+        Self {
+            original: game.clone(),
+            perturbed,
+            words: Default::default(),
+        }
+    }
+
+    /// Steps both boards one generation.
+    pub fn step(&mut self) {
+        self.original.step();
+        self.perturbed.step();
+    }
+
+    /// How far apart the boards are now, as the Hamming distance between
+    /// them, counted a packed word at a time.
+    pub fn distance(&mut self) -> Distance {
+        let (original, perturbed) = &mut self.words;
+        self.original.pack_into(original);
+        self.perturbed.pack_into(perturbed);
+        let cells = original
+            .iter()
+            .zip(perturbed.iter())
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum();
+        Distance {
+            generation: self.original.generation(),
+            cells,
+            fraction: cells as f64 / self.original.cell_count() as f64,
+        }
+    }
+
+    /// The distance now and after each of the next `generations` steps.
+    pub fn series(&mut self, generations: u64) -> Vec<Distance> {
+        let mut series = vec![self.distance()];
+        for _ in 0..generations {
+            self.step();
+            series.push(self.distance());
+        }
+        series
+    }
+}
+
+#[test]
+fn test_flips_are_seeded_and_distinct() {
+    let game = GameOfLife::new_empty((8, 8));
+    let mut flipped = Perturbation::new(&game, 5, 7);
+    assert_eq!(flipped.distance().cells, 5);
+    assert_eq!(flipped.perturbed.population(), 5);
+    assert_eq!(
+        Perturbation::new(&game, 5, 7).perturbed.surface(),
+        flipped.perturbed.surface()
+    );
+    assert_ne!(
+        Perturbation::new(&game, 5, 8).perturbed.surface(),
+        flipped.perturbed.surface()
+    );
+    // Flips turn live cells off as well, and can't number more than the cells.
+    let full = Perturbation::new(&flipped.perturbed, 100, 1);
+    assert_eq!(full.perturbed.population(), 64 - 5);
+    assert_eq!(
+        Perturbation::new(&game, 0, 1).perturbed.surface(),
+        game.surface()
+    );
+}
+
+#[test]
+fn test_divergence_series() {
+    use crate::GridPos;
+
+    // A block is a still life; knocking a cell out of it grows a difference
+    // that settles, while flipping a far-off cell dies out at once.
+    let game = GameOfLife::from_ascii("......\n.##...\n.##...\n......\n......\n......");
+    let mut perturbation = Perturbation::new(&game, 0, 0);
+    perturbation.perturbed.set(GridPos(1, 1), false);
+    let cells: Vec<_> = perturbation.series(3).iter().map(|d| d.cells).collect();
+    assert_eq!(cells, [1, 0, 0, 0]);
+    let mut lone = Perturbation::new(&game, 0, 0);
+    lone.perturbed.set(GridPos(5, 5), true);
+    let series = lone.series(2);
+    let points: Vec<_> = series.iter().map(|d| (d.generation, d.cells)).collect();
+    assert_eq!(points, [(0, 1), (1, 0), (2, 0)]);
+    assert_eq!(series[0].to_string(), "0,1,0.027778");
+
+    // A seeded soup diverges the same way every time.
+    let mut soup = GameOfLife::new_empty((32, 32));
+    soup.fill(0.4, 11);
+    let first = Perturbation::new(&soup, 3, 5).series(40);
+    assert_eq!(first, Perturbation::new(&soup, 3, 5).series(40));
+    assert_eq!(first.len(), 41);
+    assert_eq!(first[0].cells, 3);
+    assert!(first.iter().all(|d| d.cells <= 32 * 32));
+    assert!(
+        first.iter().any(|d| d.cells > 3),
+        "a soup spreads a difference"
+    );
+}
@@ -0,0 +1,215 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{game::checksum_words, GameOfLife, GridStorage, GridView};
+
+/// One finished generation, as handed to readers on other threads. It never
+/// changes once published, so a reader can take its time over it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    generation: u64,
+    size: (usize, usize),
+    /// The cells as [`GameOfLife::pack_into`] packs them.
+    words: Vec<u64>,
+    /// The [`GameOfLife::checksum`] of the board these were taken from.
+    checksum: u64,
+}
+
+impl Snapshot {
+    fn of<S: GridStorage>(game: &GameOfLife<S>) -> Self {
+        let mut snapshot = Self {
+            generation: 0,
+            size: (0, 0),
+            words: Vec::new(),
+            checksum: 0,
+        };
+        snapshot.take(game);
+        snapshot
+    }
+
+    /// Makes this a snapshot of `game`, keeping its allocation.
+    fn take<S: GridStorage>(&mut self, game: &GameOfLife<S>) {
+        game.pack_into(&mut self.words);
+        self.generation = game.generation();
+        self.size = game.size();
+        self.checksum = checksum_words(self.size, &self.words);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The checksum the board had when it was published.
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    /// Whether the cells still hash to [`checksum`](Self::checksum), as they
+    /// must unless something wrote to them after publishing.
+    pub fn is_intact(&self) -> bool {
+        checksum_words(self.size, &self.words) == self.checksum
+    }
+
+    pub fn population(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+impl GridView for Snapshot {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let i = x + y * self.size.0;
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+}
+
+/// Hands each finished generation of a run to any number of [`Reader`]s,
+/// such as a stats thread, without them ever seeing one half stepped or
+/// holding up the run for longer than it takes to swap a pointer.
+///
+/// The generation published last is shared behind an [`Arc`]; readers clone
+/// that and let go of the lock at once. Publishing fills the buffer put
+/// aside by the publish before, if no reader still holds it, and only then
+/// swaps it in, so a run whose readers keep up allocates nothing once going.
+#[derive(Debug)]
+pub struct Publisher {
+    latest: Arc<RwLock<Arc<Snapshot>>>,
+    /// The snapshot replaced last, to be filled again.
+    spare: Option<Arc<Snapshot>>,
+}
+
+/// Where a [`Publisher`]'s latest generation is read from, on any thread.
+#[derive(Debug, Clone)]
+pub struct Reader {
+    latest: Arc<RwLock<Arc<Snapshot>>>,
+}
+
+impl Publisher {
+    /// Publishes `game` as it is now to start with.
+    pub fn new<S: GridStorage>(game: &GameOfLife<S>) -> Self {
+        let latest = Arc::new(RwLock::new(Arc::new(Snapshot::of(game))));
+        Self {
+            latest,
+            spare: None,
+        }
+    }
+
+    pub fn reader(&self) -> Reader {
+        Reader {
+            latest: Arc::clone(&self.latest),
+        }
+    }
+
+    /// Publishes `game` as it is now, in place of the last generation.
+    /// Readers holding that one keep it until they let it go.
+    pub fn publish<S: GridStorage>(&mut self, game: &GameOfLife<S>) {
+        let mut next = match self.spare.take() {
+            Some(mut spare) => match Arc::get_mut(&mut spare) {
+                Some(snapshot) => {
+                    snapshot.take(game);
+                    spare
+                }
+                None => Arc::new(Snapshot::of(game)),
+            },
+            None => Arc::new(Snapshot::of(game)),
+        };
+        let mut latest = self.latest.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::swap(&mut *latest, &mut next);
+        drop(latest);
+        self.spare = Some(next);
+    }
+
+    pub fn latest(&self) -> Arc<Snapshot> {
+        self.reader().latest()
+    }
+}
+
+impl Reader {
+    /// The generation published last.
+    pub fn latest(&self) -> Arc<Snapshot> {
+        Arc::clone(&self.latest.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+#[test]
+fn test_publish_reuses_a_buffer_no_one_holds() {
+    let mut game = GameOfLife::from_ascii(".....\n.....\n.###.\n.....\n.....");
+    let mut publisher = Publisher::new(&game);
+    let first = publisher.latest();
+    assert_eq!((first.generation(), first.population()), (0, 3));
+    assert!(first.get(1, 2) && !first.get(2, 1));
+
+    game.step();
+    publisher.publish(&game);
+    // What was held stays as it was.
+    assert_eq!(first.generation(), 0);
+    assert!(first.get(1, 2));
+    let second = publisher.latest();
+    assert_eq!(second.checksum(), game.checksum());
+    assert!(second.get(2, 1) && !second.get(1, 2));
+
+    // Once let go, the first buffer is filled again rather than a new one.
+    let buffer = first.words.as_ptr();
+    drop(first);
+    game.step();
+    publisher.publish(&game);
+    assert_eq!(publisher.latest().words.as_ptr(), buffer);
+    assert_eq!(publisher.latest().generation(), 2);
+    // One still held isn't.
+    let held = publisher.latest();
+    drop(second);
+    game.step();
+    publisher.publish(&game);
+    game.step();
+    publisher.publish(&game);
+    assert_eq!(held.generation(), 2);
+    assert!(held.is_intact());
+}
+
+#[test]
+fn test_readers_see_only_whole_generations() {
+    let mut game = GameOfLife::new_empty((48, 32));
+    game.fill(0.4, 11);
+    let mut publisher = Publisher::new(&game);
+    let reader = publisher.reader();
+    let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let watcher = {
+        let done = Arc::clone(&done);
+        std::thread::spawn(move || {
+            let mut seen: Vec<(u64, u64)> = Vec::new();
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                let snapshot = reader.latest();
+                assert!(
+                    snapshot.is_intact(),
+                    "generation {} torn",
+                    snapshot.generation()
+                );
+                match seen.last() {
+                    Some(&(last, _)) if last == snapshot.generation() => continue,
+                    Some(&(last, _)) => assert!(snapshot.generation() > last),
+                    None => {}
+                }
+                seen.push((snapshot.generation(), snapshot.checksum()));
+            }
+            seen
+        })
+    };
+    let mut checksums = vec![game.checksum()];
+    for _ in 0..300 {
+        game.step();
+        publisher.publish(&game);
+        checksums.push(game.checksum());
+    }
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    let seen = watcher.join().unwrap();
+    assert!(!seen.is_empty());
+    // Every generation read is the board it was taken from.
+    for (generation, checksum) in seen {
+        assert_eq!(checksum, checksums[generation as usize]);
+    }
+}
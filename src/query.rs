@@ -0,0 +1,535 @@
+use std::fmt;
+
+use crate::{GameOfLife, GridPos, Settled};
+
+/// The exit code of a headless run whose assertions didn't all hold.
+pub const ASSERTION_FAILED: u8 = 4;
+
+/// What a [`Query`] asks about: a board at the end of a run, and how the run
+/// went.
+#[derive(Clone, Copy)]
+pub struct Run<'a> {
+    pub game: &'a GameOfLife,
+    /// Cells born over the whole run.
+    pub births: u64,
+    /// Cells that died over the whole run.
+    pub deaths: u64,
+    /// How the run ended; `None` if it was still changing.
+    pub outcome: Option<Settled>,
+}
+
+/// Where in a query something went wrong, counting characters from 0, and
+/// what.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub at: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at column {}: {}", self.at + 1, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+fn error<T>(at: usize, message: impl Into<String>) -> Result<T, QueryError> {
+    Err(QueryError {
+        at,
+        message: message.into(),
+    })
+}
+
+/// A question about a [`Run`], such as `pop > 0 && period <= 2`, read by
+/// [`Query::parse`] and answered by [`Query::eval`].
+///
+/// Values are integers, and comparisons and `!`, `&&` and `||` give 1 for
+/// true and 0 for false; any other value than 0 counts as true. From
+/// loosest to tightest: `||`, `&&`, the comparisons `==` `!=` `<` `<=` `>`
+/// `>=` (which don't chain), `+` and `-`, `*` `/` and `%`, then `!` and
+/// unary `-`. Parentheses group.
+///
+/// The facts are `pop`, `births`, `deaths`, `gen`, `period` (0 if the board
+/// died out, an error if it never settled), `bbox_w` and `bbox_h` (the live
+/// cells' bounding box, 0 on an empty board), and `alive(x, y)`, 1 or 0 and
+/// an error off the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Number(i64),
+    /// A fact by name, and where it was named.
+    Fact(Fact, usize),
+    Alive(Box<Expr>, Box<Expr>, usize),
+    Not(Box<Expr>),
+    Negate(Box<Expr>, usize),
+    /// An operator, where it is, and what it joins.
+    Binary(Op, usize, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fact {
+    Population,
+    Births,
+    Deaths,
+    Generation,
+    Period,
+    BoxWidth,
+    BoxHeight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(i64),
+    Name(String),
+    Op(Op),
+    Not,
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let at = i;
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            _ if c.is_ascii_digit() => {
+                let len = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+                let digits: String = chars[i..i + len].iter().collect();
+                let n = digits
+                    .parse()
+                    .or_else(|_| error(at, format!("{digits} is too big")))?;
+                (Token::Number(n), len)
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .count();
+                (Token::Name(chars[i..i + len].iter().collect()), len)
+            }
+            ('|', Some('|')) => (Token::Op(Op::Or), 2),
+            ('&', Some('&')) => (Token::Op(Op::And), 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('+', _) => (Token::Op(Op::Add), 1),
+            ('-', _) => (Token::Op(Op::Sub), 1),
+            ('*', _) => (Token::Op(Op::Mul), 1),
+            ('/', _) => (Token::Op(Op::Div), 1),
+            ('%', _) => (Token::Op(Op::Rem), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            (',', _) => (Token::Comma, 1),
+            ('=', _) => return error(at, "expected == to compare"),
+            _ => return error(at, format!("unexpected {c:?}")),
+        };
+        tokens.push((token, at));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Reads tokens into an [`Expr`] by recursive descent, one function a level
+/// of precedence.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    /// Where the source ends, for errors about running out.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    /// Where the next token is, or the end.
+    fn at(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |&(_, at)| at)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let matched = self.peek() == Some(token);
+        self.next += matched as usize;
+        matched
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), QueryError> {
+        match self.eat(&token) {
+            true => Ok(()),
+            false => error(self.at(), format!("expected {what}")),
+        }
+    }
+
+    /// A left-associative run of `ops` between operands read by `operand`.
+    fn chain(
+        &mut self,
+        ops: &[Op],
+        operand: fn(&mut Self) -> Result<Expr, QueryError>,
+    ) -> Result<Expr, QueryError> {
+        let mut left = operand(self)?;
+        while let Some(&Token::Op(op)) = self.peek().filter(|token| match token {
+            Token::Op(op) => ops.contains(op),
+            _ => false,
+        }) {
+            let at = self.at();
+            self.next += 1;
+            left = Expr::Binary(op, at, Box::new(left), Box::new(operand(self)?));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        self.chain(&[Op::Or], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        self.chain(&[Op::And], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, QueryError> {
+        let left = self.sum()?;
+        let Some(&Token::Op(op)) = self.peek() else {
+            return Ok(left);
+        };
+        if !matches!(op, Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge) {
+            return Ok(left);
+        }
+        let at = self.at();
+        self.next += 1;
+        let right = self.sum()?;
+        if let Some(Token::Op(Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge)) = self.peek() {
+            return error(self.at(), "comparisons don't chain; join them with &&");
+        }
+        Ok(Expr::Binary(op, at, Box::new(left), Box::new(right)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, QueryError> {
+        self.chain(&[Op::Add, Op::Sub], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, QueryError> {
+        self.chain(&[Op::Mul, Op::Div, Op::Rem], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        let at = self.at();
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::Op(Op::Sub)) {
+            return Ok(Expr::Negate(Box::new(self.unary()?), at));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, QueryError> {
+        let at = self.at();
+        let Some((token, _)) = self.tokens.get(self.next).cloned() else {
+            return error(at, "expected a number, a fact or (");
+        };
+        self.next += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Open => {
+                let inner = self.or()?;
+                self.expect(Token::Close, ")")?;
+                Ok(inner)
+            }
+            Token::Name(name) if name == "alive" => {
+                self.expect(Token::Open, "( after alive")?;
+                let x = self.or()?;
+                self.expect(Token::Comma, ", between alive's x and y")?;
+                let y = self.or()?;
+                self.expect(Token::Close, ") after alive's y")?;
+                Ok(Expr::Alive(Box::new(x), Box::new(y), at))
+            }
+            Token::Name(name) => {
+                let fact = match name.as_str() {
+                    "pop" => Fact::Population,
+                    "births" => Fact::Births,
+                    "deaths" => Fact::Deaths,
+                    "gen" => Fact::Generation,
+                    "period" => Fact::Period,
+                    "bbox_w" => Fact::BoxWidth,
+                    "bbox_h" => Fact::BoxHeight,
+                    _ => return error(at, format!("unknown fact {name:?}")),
+                };
+                Ok(Expr::Fact(fact, at))
+            }
+            _ => error(at, "expected a number, a fact or ("),
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end: source.chars().count(),
+        };
+        let expr = parser.or()?;
+        if parser.next < parser.tokens.len() {
+            return error(parser.at(), "expected an operator or the end");
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The query's value for `run`.
+    pub fn eval(&self, run: &Run) -> Result<i64, QueryError> {
+        eval(&self.expr, run)
+    }
+
+    /// Whether the query holds for `run`: its value is anything but 0.
+    pub fn holds(&self, run: &Run) -> Result<bool, QueryError> {
+        self.eval(run).map(|value| value != 0)
+    }
+}
+
+fn eval(expr: &Expr, run: &Run) -> Result<i64, QueryError> {
+    let value = match expr {
+        Expr::Number(n) => *n,
+        Expr::Fact(fact, at) => fact_of(*fact, *at, run)?,
+        Expr::Alive(x, y, at) => {
+            let (x, y) = (eval(x, run)?, eval(y, run)?);
+            let (w, h) = run.game.size();
+            let on = |n: i64, len: usize| usize::try_from(n).ok().filter(|&n| n < len);
+            match (on(x, w), on(y, h)) {
+                (Some(x), Some(y)) => run.game.get(GridPos(x, y)) as i64,
+                _ => return error(*at, format!("alive({x}, {y}) is off the {w}x{h} board")),
+            }
+        }
+        Expr::Not(inner) => (eval(inner, run)? == 0) as i64,
+        Expr::Negate(inner, at) => match eval(inner, run)?.checked_neg() {
+            Some(n) => n,
+            None => return error(*at, "overflow"),
+        },
+        // Both sides are always checked, so an error on either is reported.
+        Expr::Binary(op, at, left, right) => {
+            let (a, b) = (eval(left, run)?, eval(right, run)?);
+            let checked = match op {
+                Op::Or => Some((a != 0 || b != 0) as i64),
+                Op::And => Some((a != 0 && b != 0) as i64),
+                Op::Eq => Some((a == b) as i64),
+                Op::Ne => Some((a != b) as i64),
+                Op::Lt => Some((a < b) as i64),
+                Op::Le => Some((a <= b) as i64),
+                Op::Gt => Some((a > b) as i64),
+                Op::Ge => Some((a >= b) as i64),
+                Op::Add => a.checked_add(b),
+                Op::Sub => a.checked_sub(b),
+                Op::Mul => a.checked_mul(b),
+                Op::Div | Op::Rem if b == 0 => return error(*at, "division by zero"),
+                Op::Div => a.checked_div(b),
+                Op::Rem => a.checked_rem(b),
+            };
+            match checked {
+                Some(n) => n,
+                None => return error(*at, "overflow"),
+            }
+        }
+    };
+    Ok(value)
+}
+
+fn fact_of(fact: Fact, at: usize, run: &Run) -> Result<i64, QueryError> {
+    let game = run.game;
+    // The live cells' extent along one axis, as (least, most).
+    let span = |along_x: bool| {
+        (0..game.cell_count())
+            .map(|i| game.coords(i))
+            .filter(|&pos| game.get(pos))
+            .map(|GridPos(x, y)| if along_x { x } else { y })
+            .fold(None, |span: Option<(usize, usize)>, n| match span {
+                Some((least, most)) => Some((least.min(n), most.max(n))),
+                None => Some((n, n)),
+            })
+            .map_or(0, |(least, most)| most - least + 1)
+    };
+    let value = match fact {
+        Fact::Population => game.population() as u64,
+        Fact::Births => run.births,
+        Fact::Deaths => run.deaths,
+        Fact::Generation => game.generation(),
+        Fact::Period => match run.outcome {
+            Some(Settled::Cycle(period)) => period as u64,
+            Some(Settled::Died) => 0,
+            None => return error(at, "period is unknown; the board never settled"),
+        },
+        Fact::BoxWidth => span(true) as u64,
+        Fact::BoxHeight => span(false) as u64,
+    };
+    Ok(value as i64)
+}
+
+#[test]
+fn test_queries_about_a_run() {
+    // A vertical blinker in a 5x5 board.
+    let game = GameOfLife::from_ascii(".....\n..#..\n..#..\n..#..\n.....");
+    let run = crate::testing::blinker_run(&game);
+    let value = |source: &str| Query::parse(source).unwrap().eval(&run);
+    assert_eq!(value("pop"), Ok(3));
+    assert_eq!(value("births + deaths"), Ok(10));
+    assert_eq!(value("gen"), Ok(0));
+    assert_eq!(value("period"), Ok(2));
+    assert_eq!(value("bbox_w * 10 + bbox_h"), Ok(13));
+    assert_eq!(value("alive(2, 1) + alive(1,2)"), Ok(1));
+    let holding = [
+        "pop > 0",
+        "period <= 2",
+        "alive(2,2)",
+        "!alive(0, 0)",
+        "pop == 3 && bbox_h == 3",
+    ];
+    for holds in holding {
+        assert_eq!(
+            Query::parse(holds).unwrap().holds(&run),
+            Ok(true),
+            "{holds}"
+        );
+    }
+    assert_eq!(
+        Query::parse("pop != 3 || deaths < 6").unwrap().holds(&run),
+        Ok(false)
+    );
+
+    let empty = GameOfLife::new_empty((4, 4));
+    let died = Run {
+        game: &empty,
+        births: 0,
+        deaths: 3,
+        outcome: Some(Settled::Died),
+    };
+    let value = |source: &str| Query::parse(source).unwrap().eval(&died);
+    assert_eq!(
+        (value("period"), value("bbox_w"), value("bbox_h")),
+        (Ok(0), Ok(0), Ok(0))
+    );
+    let changing = Run {
+        outcome: None,
+        ..died
+    };
+    let err = Query::parse("pop > 0 || period == 1")
+        .unwrap()
+        .eval(&changing)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at column 12: period is unknown; the board never settled"
+    );
+}
+
+#[test]
+fn test_query_precedence() {
+    let game = GameOfLife::new_empty((1, 1));
+    let run = crate::testing::blinker_run(&game);
+    let value = |source: &str| Query::parse(source).unwrap().eval(&run).unwrap();
+    assert_eq!(value("1 + 2 * 3"), 7);
+    assert_eq!(value("(1 + 2) * 3"), 9);
+    assert_eq!(value("10 - 4 - 3"), 3);
+    assert_eq!(value("17 / 3 % 4"), 1);
+    assert_eq!(value("-2 * -3"), 6);
+    assert_eq!(value("- (2 + 3)"), -5);
+    assert_eq!(value("1 + 1 == 2"), 1);
+    assert_eq!(value("2 < 1 + 2"), 1);
+    // && binds tighter than ||, and ! tighter than either.
+    assert_eq!(value("1 || 0 && 0"), 1);
+    assert_eq!(value("(1 || 0) && 0"), 0);
+    assert_eq!(value("!0 && !1"), 0);
+    assert_eq!(value("!(1 == 2)"), 1);
+    assert_eq!(value("!2 + 1"), 1);
+}
+
+#[test]
+fn test_query_errors_say_where() {
+    let err = |source: &str| Query::parse(source).unwrap_err().to_string();
+    assert_eq!(err("pop >"), "at column 6: expected a number, a fact or (");
+    assert_eq!(err("pop = 3"), "at column 5: expected == to compare");
+    assert_eq!(err("popu > 3"), "at column 1: unknown fact \"popu\"");
+    assert_eq!(err("(pop > 3"), "at column 9: expected )");
+    assert_eq!(err("pop 3"), "at column 5: expected an operator or the end");
+    assert_eq!(
+        err("1 < 2 < 3"),
+        "at column 7: comparisons don't chain; join them with &&"
+    );
+    assert_eq!(
+        err("alive(1)"),
+        "at column 8: expected , between alive's x and y"
+    );
+    assert_eq!(err("alive 1, 2"), "at column 7: expected ( after alive");
+    assert_eq!(err("pop # 2"), "at column 5: unexpected '#'");
+    assert_eq!(
+        err("99999999999999999999 > 0"),
+        "at column 1: 99999999999999999999 is too big"
+    );
+    assert_eq!(err(""), "at column 1: expected a number, a fact or (");
+
+    let game = GameOfLife::new_empty((8, 6));
+    let run = crate::testing::blinker_run(&game);
+    let err = |source: &str| {
+        Query::parse(source)
+            .unwrap()
+            .eval(&run)
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        err("alive(8, 0)"),
+        "at column 1: alive(8, 0) is off the 8x6 board"
+    );
+    assert_eq!(
+        err("1 + alive(0, -1)"),
+        "at column 5: alive(0, -1) is off the 8x6 board"
+    );
+    assert_eq!(
+        err("alive(7, 5) + alive(3, 6)"),
+        "at column 15: alive(3, 6) is off the 8x6 board"
+    );
+    assert_eq!(err("pop / (gen - 0)"), "at column 5: division by zero");
+    assert_eq!(err("9223372036854775807 + 1"), "at column 21: overflow");
+}
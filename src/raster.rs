@@ -0,0 +1,432 @@
+use std::ops::{ControlFlow, Range};
+
+use crate::{GridView, Theme};
+
+/// A trail of fading color left where cells died, as in the terminal's
+/// trails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trail<'a> {
+    /// Generations since each cell was last alive, row-major.
+    pub dead_for: &'a [u32],
+    /// How many generations a trail lasts; at least 1.
+    pub length: u32,
+    /// The color of the freshest trail, which fades to the dead color.
+    pub color: [u8; 4],
+}
+
+/// Live cells colored by how long they have been alive, going from the
+/// live color to `old` over `span` generations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ages<'a> {
+    /// Generations each cell has been alive, row-major.
+    pub ages: &'a [u32],
+    pub old: [u8; 4],
+    /// The age that is drawn in `old`, and every age past it; at least 1.
+    pub span: u32,
+}
+
+/// The shape each live cell is drawn in, within its square of
+/// [`scale`](ImageOptions::scale) pixels. What the shape leaves of the
+/// square is the dead color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellStyle {
+    /// The whole square.
+    #[default]
+    Square,
+    /// The dot inscribed in the square: the pixels whose centers are within
+    /// half a cell of its center.
+    Circle,
+    /// The square less this many pixels along its right and bottom edges,
+    /// leaving a grid of lines between cells. At least one pixel is drawn.
+    SquareWithGap(usize),
+}
+
+impl CellStyle {
+    /// Whether the pixel at `(x, y)` within a cell `scale` pixels across
+    /// is part of the shape.
+    fn covers(self, scale: usize, (x, y): (usize, usize)) -> bool {
+        match self {
+            Self::Square => true,
+            Self::Circle => {
+                let middle = scale as f64 / 2.0;
+                let (dx, dy) = (x as f64 + 0.5 - middle, y as f64 + 0.5 - middle);
+                dx * dx + dy * dy <= middle * middle
+            }
+            Self::SquareWithGap(gap) => {
+                let drawn = scale.saturating_sub(gap).max(1);
+                x < drawn && y < drawn
+            }
+        }
+    }
+}
+
+/// How [`render_image`] draws a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions<'a> {
+    /// Pixels across and down each cell; at least 1.
+    pub scale: usize,
+    pub live: [u8; 4],
+    pub dead: [u8; 4],
+    pub trail: Option<Trail<'a>>,
+    pub ages: Option<Ages<'a>>,
+    /// The shape of live cells, and of trails.
+    pub style: CellStyle,
+}
+
+impl ImageOptions<'_> {
+    /// Full-strength cells against the background of `theme`, one pixel
+    /// each: white on black for a dark theme and black on white for a light
+    /// one.
+    pub fn for_theme(theme: Theme) -> Self {
+        let (black, white) = ([0, 0, 0, 255], [255, 255, 255, 255]);
+        let (live, dead) = match theme {
+            Theme::Dark => (white, black),
+            Theme::Light => (black, white),
+        };
+        Self {
+            scale: 1,
+            live,
+            dead,
+            trail: None,
+            ages: None,
+            style: CellStyle::Square,
+        }
+    }
+
+    /// The width and height in pixels of an image of `grid`.
+    pub fn image_size(&self, grid: &dyn GridView) -> (usize, usize) {
+        let (w, h) = grid.size();
+        let scale = self.scale.max(1);
+        (w * scale, h * scale)
+    }
+
+    /// The color of the cell at index `i`, alive or not.
+    fn color(&self, i: usize, alive: bool) -> [u8; 4] {
+        if alive {
+            return match self.ages {
+                Some(Ages { ages, old, span }) => {
+                    let age = ages.get(i).copied().unwrap_or(0).min(span.max(1));
+                    blend(self.live, old, age as f64 / span.max(1) as f64)
+                }
+                None => self.live,
+            };
+        }
+        let Some(Trail {
+            dead_for,
+            length,
+            color,
+        }) = self.trail
+        else {
+            return self.dead;
+        };
+        let length = length.max(1);
+        match dead_for.get(i) {
+            Some(&dead) if dead <= length => {
+                blend(self.dead, color, (length + 1 - dead) as f64 / length as f64)
+            }
+            _ => self.dead,
+        }
+    }
+}
+
+/// `from` moved `t` of the way to `to`, channel by channel.
+fn blend(from: [u8; 4], to: [u8; 4], t: f64) -> [u8; 4] {
+    let t = t.clamp(0.0, 1.0);
+    std::array::from_fn(|c| (from[c] as f64 + (to[c] as f64 - from[c] as f64) * t).round() as u8)
+}
+
+/// Draws `grid` into `out` as an image of tightly packed RGBA rows, top row
+/// first, each cell a square of [`scale`](ImageOptions::scale) pixels with
+/// its [`style`](ImageOptions::style) drawn in it. `out` is cleared first
+/// and keeps its allocation, so drawing frame after frame into one buffer
+/// allocates only when the image grows.
+pub fn render_image(grid: &dyn GridView, opts: &ImageOptions, out: &mut Vec<u8>) {
+    let (w, h) = grid.size();
+    let scale = opts.scale.max(1);
+    out.clear();
+    out.reserve(w * h * scale * scale * 4);
+    Rows::new(grid, opts).draw(0..h, out);
+}
+
+/// A strip of whole pixel rows of an image, as [`render_bands`] hands them
+/// over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Band<'a> {
+    /// The pixel row the band starts at.
+    pub top: usize,
+    /// The band's pixels, packed as by [`render_image`].
+    pub rgba: &'a [u8],
+}
+
+/// Draws `grid` as [`render_image`] does, but `cells` rows of cells at a
+/// time, handing each band to `sink` top to bottom, so that only a band is
+/// ever held rather than the whole image. Every band is drawn into the same
+/// buffer. Drawing stops at the first band `sink` breaks on, and its break
+/// is returned.
+pub fn render_bands<B>(
+    grid: &dyn GridView,
+    opts: &ImageOptions,
+    cells: usize,
+    mut sink: impl FnMut(Band) -> ControlFlow<B>,
+) -> ControlFlow<B> {
+    let (w, h) = grid.size();
+    let (scale, cells) = (opts.scale.max(1), cells.max(1));
+    let rows = Rows::new(grid, opts);
+    let mut out = Vec::with_capacity(w * cells.min(h) * scale * scale * 4);
+    for first in (0..h).step_by(cells) {
+        out.clear();
+        rows.draw(first..(first + cells).min(h), &mut out);
+        sink(Band {
+            top: first * scale,
+            rgba: &out,
+        })?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// What drawing rows of cells needs, worked out once an image.
+struct Rows<'a> {
+    grid: &'a dyn GridView,
+    opts: &'a ImageOptions<'a>,
+    scale: usize,
+    /// Which pixels of a cell's square its shape covers, row-major.
+    shape: Vec<bool>,
+}
+
+impl<'a> Rows<'a> {
+    fn new(grid: &'a dyn GridView, opts: &'a ImageOptions<'a>) -> Self {
+        let scale = opts.scale.max(1);
+        let shape = (0..scale * scale)
+            .map(|i| opts.style.covers(scale, (i % scale, i / scale)))
+            .collect();
+        Self {
+            grid,
+            opts,
+            scale,
+            shape,
+        }
+    }
+
+    /// Appends the pixels of the cell rows `ys` to `out`.
+    fn draw(&self, ys: Range<usize>, out: &mut Vec<u8>) {
+        let (w, scale, opts) = (self.grid.size().0, self.scale, self.opts);
+        let mut colors = Vec::with_capacity(w);
+        for y in ys {
+            colors.clear();
+            colors.extend((0..w).map(|x| opts.color(x + y * w, self.grid.get(x, y))));
+            for py in 0..scale {
+                for color in &colors {
+                    for &covered in &self.shape[py * scale..(py + 1) * scale] {
+                        out.extend_from_slice(if covered { color } else { &opts.dead });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_render_image_by_theme_and_scale() {
+    const W: [u8; 4] = [255, 255, 255, 255];
+    const B: [u8; 4] = [0, 0, 0, 255];
+    let game = crate::testing::checker();
+    let mut out = Vec::new();
+    render_image(&game, &ImageOptions::for_theme(Theme::Dark), &mut out);
+    assert_eq!(out, [W, B, B, W].concat());
+    render_image(&game, &ImageOptions::for_theme(Theme::Light), &mut out);
+    assert_eq!(out, [B, W, W, B].concat());
+
+    // Each cell a square of pixels, the rows packed with no padding.
+    let big = ImageOptions {
+        scale: 2,
+        ..ImageOptions::for_theme(Theme::Dark)
+    };
+    assert_eq!(big.image_size(&game), (4, 4));
+    render_image(&game, &big, &mut out);
+    let top = [W, W, B, B].concat();
+    let bottom = [B, B, W, W].concat();
+    assert_eq!(out, [&top[..], &top, &bottom, &bottom].concat());
+
+    // Drawing again reuses the buffer.
+    let (capacity, at) = (out.capacity(), out.as_ptr());
+    render_image(&game, &big, &mut out);
+    assert_eq!((out.capacity(), out.as_ptr()), (capacity, at));
+}
+
+#[test]
+fn test_render_image_trails_and_ages() {
+    let game = crate::testing::checker();
+    let (live, dead) = ([200, 100, 0, 255], [0, 0, 0, 255]);
+    let plain = ImageOptions {
+        live,
+        dead,
+        ..ImageOptions::for_theme(Theme::Dark)
+    };
+    let mut out = Vec::new();
+
+    // A trail fades from its color to the dead one over its length.
+    let dead_for = [0, 1, 2, 0];
+    let trail = Trail {
+        dead_for: &dead_for,
+        length: 2,
+        color: [100, 100, 100, 255],
+    };
+    render_image(
+        &game,
+        &ImageOptions {
+            trail: Some(trail),
+            ..plain
+        },
+        &mut out,
+    );
+    assert_eq!(
+        out,
+        [live, [100, 100, 100, 255], [50, 50, 50, 255], live].concat()
+    );
+    let gone = Trail { length: 1, ..trail };
+    render_image(
+        &game,
+        &ImageOptions {
+            trail: Some(gone),
+            ..plain
+        },
+        &mut out,
+    );
+    assert_eq!(out, [live, [100, 100, 100, 255], dead, live].concat());
+
+    // Ages go from the live color to the old one, and stay there.
+    let ages = [1, 0, 0, 9];
+    let aged = Ages {
+        ages: &ages,
+        old: [0, 200, 100, 255],
+        span: 2,
+    };
+    render_image(
+        &game,
+        &ImageOptions {
+            ages: Some(aged),
+            ..plain
+        },
+        &mut out,
+    );
+    assert_eq!(
+        out,
+        [[100, 150, 50, 255], dead, dead, [0, 200, 100, 255]].concat()
+    );
+
+    // Both at once, each on its own cells.
+    let both = ImageOptions {
+        trail: Some(trail),
+        ages: Some(aged),
+        ..plain
+    };
+    render_image(&game, &both, &mut out);
+    let expected = [
+        [100, 150, 50, 255],
+        [100, 100, 100, 255],
+        [50, 50, 50, 255],
+        aged.old,
+    ];
+    assert_eq!(out, expected.concat());
+}
+
+#[test]
+fn test_cell_styles_stay_within_their_cells() {
+    use crate::GridPos;
+
+    const W: [u8; 4] = [255, 255, 255, 255];
+    const B: [u8; 4] = [0, 0, 0, 255];
+    let mut game = crate::GameOfLife::new_empty((3, 1));
+    game.set(GridPos(1, 0), true);
+    let pixel = |out: &[u8], x: usize, y: usize| -> [u8; 4] {
+        out[(x + y * 24) * 4..][..4].try_into().unwrap()
+    };
+    let mut out = Vec::new();
+    let round = ImageOptions {
+        scale: 8,
+        style: CellStyle::Circle,
+        ..ImageOptions::for_theme(Theme::Dark)
+    };
+    render_image(&game, &round, &mut out);
+    assert_eq!(out.len(), 24 * 8 * 4);
+    for y in 0..8 {
+        for x in 0..24 {
+            // Only the live cell's dot is drawn, never past its square.
+            let (dx, dy) = (x as f64 - 11.5, y as f64 - 3.5);
+            let inside = (8..16).contains(&x) && dx * dx + dy * dy <= 16.0;
+            assert_eq!(pixel(&out, x, y), if inside { W } else { B }, "at {x}, {y}");
+        }
+    }
+    // The middle is filled and the corners are not.
+    assert_eq!(pixel(&out, 11, 3), W);
+    assert_eq!([pixel(&out, 8, 0), pixel(&out, 15, 7)], [B, B]);
+
+    let gapped = ImageOptions {
+        style: CellStyle::SquareWithGap(2),
+        ..round
+    };
+    render_image(&game, &gapped, &mut out);
+    assert_eq!(pixel(&out, 8, 0), W);
+    assert_eq!(
+        [pixel(&out, 13, 5), pixel(&out, 14, 5), pixel(&out, 13, 6)],
+        [W, B, B]
+    );
+    // No gap is so wide as to leave nothing.
+    let all_gap = ImageOptions {
+        style: CellStyle::SquareWithGap(9),
+        ..round
+    };
+    render_image(&game, &all_gap, &mut out);
+    assert_eq!(
+        [pixel(&out, 8, 0), pixel(&out, 9, 0), pixel(&out, 8, 1)],
+        [W, B, B]
+    );
+}
+
+#[test]
+fn test_bands_cover_the_image_in_order() {
+    let mut game = crate::GameOfLife::new_empty((5, 7));
+    game.fill(0.5, 3);
+    let opts = ImageOptions {
+        scale: 3,
+        ..ImageOptions::for_theme(Theme::Dark)
+    };
+    let mut whole = Vec::new();
+    render_image(&game, &opts, &mut whole);
+
+    // A fake encoder that keeps what it is handed, and where the band was.
+    let (mut tops, mut joined, mut buffers) = (Vec::new(), Vec::new(), Vec::new());
+    let flow = render_bands::<()>(&game, &opts, 2, |band| {
+        tops.push(band.top);
+        joined.extend_from_slice(band.rgba);
+        buffers.push((band.rgba.as_ptr(), band.rgba.len()));
+        ControlFlow::Continue(())
+    });
+    assert_eq!(flow, ControlFlow::Continue(()));
+    assert_eq!(tops, [0, 6, 12, 18]);
+    assert_eq!(joined, whole);
+    // Every band is at most two rows of cells, drawn in the one buffer.
+    let band = 5 * 3 * 2 * 3 * 4;
+    let lengths: Vec<_> = buffers.iter().map(|&(_, len)| len).collect();
+    assert_eq!(lengths, [band, band, band, band / 2]);
+    assert!(buffers.iter().all(|&(at, _)| at == buffers[0].0));
+
+    // A sink that breaks stops the drawing there.
+    let mut seen = 0;
+    let flow = render_bands(&game, &opts, 3, |band| {
+        seen += 1;
+        match band.top {
+            0 => ControlFlow::Continue(()),
+            top => ControlFlow::Break(top),
+        }
+    });
+    assert_eq!((flow, seen), (ControlFlow::Break(9), 2));
+    // Bands taller than the board are the board.
+    let mut bands = 0;
+    let _ = render_bands::<()>(&game, &opts, 100, |band| {
+        bands += 1;
+        assert_eq!(band.rgba, whole);
+        ControlFlow::Continue(())
+    });
+    assert_eq!(bands, 1);
+}
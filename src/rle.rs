@@ -0,0 +1,302 @@
+use crate::{
+    metadata::{Conflict, Metadata},
+    GameOfLife, GridPos, Pattern, Rule,
+};
+
+/// Longest line written, as the format recommends.
+const LINE_WIDTH: usize = 70;
+
+/// Encodes a board of `size` cells, live where `alive` says so, as RLE (the
+/// run length encoded format most Life software reads). The output has the
+/// header line but no comments; trailing dead cells and rows are left out.
+pub fn encode(size: (usize, usize), rule: Rule, alive: impl Fn(usize, usize) -> bool) -> String {
+    let (w, h) = size;
+    let mut tokens = Vec::new();
+    let mut push = |count: usize, tag: char| match count {
+        0 => {}
+        1 => tokens.push(tag.to_string()),
+        n => tokens.push(format!("{n}{tag}")),
+    };
+    let mut rows = 0;
+    for y in 0..h {
+        let mut run = (0, false);
+        let mut row = Vec::new();
+        for x in 0..w {
+            let cell = alive(x, y);
+            if cell != run.1 && run.0 > 0 {
+                row.push(run);
+                run.0 = 0;
+            }
+            run = (run.0 + 1, cell);
+        }
+        if run.1 {
+            row.push(run);
+        }
+        if row.is_empty() {
+            continue;
+        }
+        // Empty rows in between fold into the end-of-line count.
+        push(y - rows, '$');
+        for (count, live) in row {
+            push(count, if live { 'o' } else { 'b' });
+        }
+        rows = y;
+    }
+    push(1, '!');
+
+    let mut out = format!("x = {w}, y = {h}, rule = {rule}\n");
+    let mut line = 0;
+    for token in tokens {
+        if line + token.len() > LINE_WIDTH {
+            out.push('\n');
+            line = 0;
+        }
+        line += token.len();
+        out.push_str(&token);
+    }
+    out.push('\n');
+    out
+}
+
+/// Encodes a board as [`encode`] does, preceded by `metadata` as `#C`
+/// comments. Its rule goes in the header, as the only place other programs
+/// look for one, rather than in a comment as well.
+pub fn encode_with(
+    metadata: &Metadata,
+    size: (usize, usize),
+    alive: impl Fn(usize, usize) -> bool,
+) -> String {
+    let comments = Metadata {
+        rule: None,
+        ..metadata.clone()
+    };
+    let mut out = String::new();
+    for line in comments.lines() {
+        out.push_str(&format!("#C {line}\n"));
+    }
+    out + &encode(size, metadata.rule.unwrap_or_default(), alive)
+}
+
+/// Reads an RLE pattern and its rule, which is Conway's when the header
+/// doesn't name one. `#` comment lines are skipped, and any state other than
+/// `b` (or `.`) counts as live, except that the letters of the extended
+/// format's numbered states are read as such: `A` is live, but `B` to `X`
+/// and the two-letter states from `pA` on are more than a two-state rule
+/// has, and are refused.
+pub fn decode(text: &str) -> Result<(Pattern, Rule), String> {
+    let (pattern, rule) = read(text)?;
+    Ok((pattern, rule.unwrap_or_default()))
+}
+
+/// Reads an RLE pattern as [`decode`] does, along with the metadata in its
+/// `#C` comments. The header's rule is gone by where a comment disagrees,
+/// and the disagreements are returned; the metadata's rule is the header's,
+/// else the comments', else Conway's.
+pub fn decode_with_metadata(text: &str) -> Result<(Pattern, Metadata, Vec<Conflict>), String> {
+    let comments = text.lines().filter_map(|line| {
+        let line = line.trim();
+        line.strip_prefix("#C").or_else(|| line.strip_prefix("#c"))
+    });
+    let mut metadata = Metadata::parse(comments)?;
+    let (pattern, rule) = read(text)?;
+    let conflicts = metadata.reconcile(&Metadata {
+        rule,
+        ..Metadata::default()
+    });
+    metadata.rule = Some(metadata.rule.unwrap_or_default());
+    Ok((pattern, metadata, conflicts))
+}
+
+/// [`decode`], with the rule left out when the header doesn't name one.
+fn read(text: &str) -> Result<(Pattern, Option<Rule>), String> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header = lines.next().ok_or("missing RLE header")?;
+    let (mut width, mut height, mut named) = (None, None, None);
+    // The rule comes last and may have commas of its own.
+    let (header, rulestring) = match header.find("rule") {
+        Some(at) => (
+            header[..at].trim_end().trim_end_matches(','),
+            Some(&header[at..]),
+        ),
+        None => (header, None),
+    };
+    for field in header.split(',').chain(rulestring) {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("bad RLE header field {field:?}"))?;
+        let value = value.trim();
+        let dimension = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("bad RLE dimension {value:?}"))
+        };
+        match key.trim() {
+            "x" => width = Some(dimension()?),
+            "y" => height = Some(dimension()?),
+            "rule" => named = Some(Rule::parse(value)?),
+            _ => {}
+        }
+    }
+    let size = match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        _ => return Err("RLE header needs x and y".into()),
+    };
+
+    let rule = named.unwrap_or_default();
+    let mut cells = Vec::new();
+    let (mut x, mut y, mut count) = (0, 0, None::<usize>);
+    'body: for line in lines {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if let Some(digit) = c.to_digit(10) {
+                count = Some(count.unwrap_or(0) * 10 + digit as usize);
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+            let n = count.take().unwrap_or(1);
+            match c {
+                '!' => break 'body,
+                '$' => (x, y) = (0, y + n),
+                'b' | '.' => x += n,
+                'B'..='X' => return Err(beyond_two_states(rule, &c.to_string())),
+                'p'..='y' if chars.peek().is_some_and(|c| matches!(c, 'A'..='X')) => {
+                    let state = format!("{c}{}", chars.next().unwrap_or_default());
+                    return Err(beyond_two_states(rule, &state));
+                }
+                _ => {
+                    cells.extend((x..x + n).map(|x| (x, y)));
+                    x += n;
+                }
+            }
+        }
+    }
+    if let Some(&(x, y)) = cells.iter().find(|&&(x, y)| x >= size.0 || y >= size.1) {
+        return Err(format!(
+            "RLE cell ({x}, {y}) is outside its {}x{} box",
+            size.0, size.1
+        ));
+    }
+    Ok((Pattern::with_size(size, cells), named))
+}
+
+/// The error for a cell in `state`, a number above 1 in the extended
+/// format's letters.
+fn beyond_two_states(rule: Rule, state: &str) -> String {
+    format!("RLE state {state:?} is beyond the two states of rule {rule}")
+}
+
+impl GameOfLife {
+    pub fn to_rle(&self) -> String {
+        encode(self.size(), self.rule(), |x, y| self.get(GridPos(x, y)))
+    }
+}
+
+#[test]
+fn test_rle_encode() {
+    let mut game = GameOfLife::new_empty((6, 5));
+    for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (5, 4)] {
+        game.set(GridPos(x, y), true);
+    }
+    assert_eq!(
+        game.to_rle(),
+        "x = 6, y = 5, rule = B3/S23\nbo$2bo$3o2$5bo!\n"
+    );
+    let blank = encode((3, 3), Rule::parse("B36/S23").unwrap(), |_, _| false);
+    assert_eq!(blank, "x = 3, y = 3, rule = B36/S23\n!\n");
+}
+
+#[test]
+fn test_rle_decode() {
+    let (glider, rule) = decode("#N Glider\nx = 3, y = 3\nbo$2bo$3o!").unwrap();
+    assert_eq!(rule, Rule::CONWAY);
+    assert_eq!(
+        glider,
+        Pattern::new(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    );
+    let (_, ranged) = decode("x = 1, y = 1, rule = R2,C0,M0,S2..3,B3,NM\no!").unwrap();
+    assert_eq!(ranged.to_string(), "R2,C0,M0,S2..3,B3,NM");
+    let (wrapped, rule) = decode("x = 4, y = 3, rule = B36/S23\n2o\n2$3bo!").unwrap();
+    assert_eq!(rule.to_string(), "B36/S23");
+    assert_eq!(
+        wrapped,
+        Pattern::with_size((4, 3), vec![(0, 0), (1, 0), (3, 2)])
+    );
+    assert!(decode("x = 2, y = 1\n3o!").is_err());
+    assert!(decode("bo!").is_err());
+}
+
+#[test]
+fn test_rle_decode_numbered_states() {
+    // `.` and `A` are the two states a rule of this crate has.
+    let (blinker, _) = decode("x = 3, y = 2\n.A.$3A!").unwrap();
+    assert_eq!(
+        blinker,
+        Pattern::with_size((3, 2), vec![(1, 0), (0, 1), (1, 1), (2, 1)])
+    );
+    let (classic, _) = decode("x = 2, y = 1\nbp!").unwrap();
+    assert_eq!(classic, Pattern::with_size((2, 1), vec![(1, 0)]));
+    assert_eq!(
+        decode("x = 3, y = 1, rule = B36/S23\nA2B!"),
+        Err("RLE state \"B\" is beyond the two states of rule B36/S23".to_string())
+    );
+    assert_eq!(
+        decode("x = 2, y = 1\nApC!"),
+        Err("RLE state \"pC\" is beyond the two states of rule B3/S23".to_string())
+    );
+}
+
+#[test]
+fn test_property_rle_round_trips() {
+    crate::testing::check(|rng| {
+        let game = crate::testing::game(rng);
+        let (w, h) = game.size();
+        let (pattern, rule) = decode(&game.to_rle()).unwrap();
+        assert_eq!(rule, game.rule());
+        let whole = crate::Rect {
+            x: 0,
+            y: 0,
+            width: w,
+            height: h,
+        };
+        assert_eq!(pattern, game.extract(whole));
+    });
+}
+
+#[test]
+fn test_rle_metadata_is_checked_against_the_header() {
+    let text = "#N Pond\n#C version = 2\n#C rule = B3/S23\n#C edges = toroidal\n\
+                x = 2, y = 1, rule = B36/S23\n2o!";
+    let (pattern, metadata, conflicts) = decode_with_metadata(text).unwrap();
+    assert_eq!(pattern.cells().len(), 2);
+    assert_eq!(metadata.rule, Some("B36/S23".parse().unwrap()));
+    assert_eq!(metadata.edges, Some(crate::EdgeMode::Toroidal));
+    assert_eq!((conflicts.len(), conflicts[0].field), (1, "rule"));
+    // Without a rule in the header, the comment's is gone by; without
+    // either, Conway's.
+    let text = "#C rule = B2/S\nx = 1, y = 1\no!";
+    let (_, metadata, conflicts) = decode_with_metadata(text).unwrap();
+    assert_eq!(
+        (metadata.rule.unwrap().to_string(), conflicts),
+        ("B2/S".into(), Vec::new())
+    );
+    let (_, metadata, _) = decode_with_metadata("x = 1, y = 1\no!").unwrap();
+    assert_eq!(metadata.rule, Some(Rule::CONWAY));
+    assert!(decode_with_metadata("#C rule = B2/S\n#C rule = B3/S\nx = 1, y = 1\no!").is_err());
+
+    // A later version's fields survive being read and saved again.
+    let text = "#C version = 3\n#C generation = 9\n#C topology = klein\nx = 1, y = 1\no!";
+    let (pattern, metadata, _) = decode_with_metadata(text).unwrap();
+    let saved = encode_with(&metadata, pattern.size(), |x, y| {
+        pattern.cells().contains(&(x, y))
+    });
+    assert_eq!(
+        saved,
+        "#C version = 3\n#C generation = 9\n#C topology = klein\nx = 1, y = 1, rule = B3/S23\no!\n"
+    );
+    assert_eq!(decode_with_metadata(&saved).unwrap().1, metadata);
+}
@@ -0,0 +1,436 @@
+use std::{fmt, str::FromStr};
+
+use crate::EdgeMode;
+
+/// Largest neighborhood range a [`Rule`] can have.
+pub const MAX_RANGE: usize = 3;
+
+/// Which cells within a rule's range of a cell are its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Neighborhood {
+    /// The square around the cell.
+    #[default]
+    Moore,
+    /// The diamond around the cell: those at most the range away counting
+    /// steps across and down.
+    VonNeumann,
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    /// Any case is accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "moore" => Ok(Neighborhood::Moore),
+            "vonneumann" | "von-neumann" => Ok(Neighborhood::VonNeumann),
+            _ => Err(format!(
+                "unknown neighborhood {s:?}, expected moore or vonneumann"
+            )),
+        }
+    }
+}
+
+/// An outer-totalistic rule: which live-neighbor counts bring a dead cell to
+/// life, and which keep a live cell alive, and which cells are neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    /// Bit `n` is set when a dead cell with `n` live neighbors is born.
+    birth: u64,
+    /// Bit `n` is set when a live cell with `n` live neighbors survives.
+    survival: u64,
+    neighborhood: Neighborhood,
+    range: u8,
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+        neighborhood: Neighborhood::Moore,
+        range: 1,
+    };
+
+    /// A rule from the neighbor counts for birth and survival among the eight
+    /// cells around a cell. Counts above eight can never happen and are
+    /// ignored.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        let mask = |counts: &[usize]| {
+            counts
+                .iter()
+                .filter(|&&n| n <= 8)
+                .fold(0, |mask, &n| mask | 1 << n)
+        };
+        Self {
+            birth: mask(birth),
+            survival: mask(survival),
+            ..Self::CONWAY
+        }
+    }
+
+    /// A rule counting the neighbors in `neighborhood` out to `range` cells
+    /// away, from 1 to [`MAX_RANGE`]. Every count must be one the
+    /// neighborhood can reach.
+    pub fn with_neighborhood(
+        birth: &[usize],
+        survival: &[usize],
+        neighborhood: Neighborhood,
+        range: usize,
+    ) -> Result<Self, String> {
+        if !(1..=MAX_RANGE).contains(&range) {
+            return Err(format!("rule range must be 1 to {MAX_RANGE}, got {range}"));
+        }
+        let rule = Self {
+            neighborhood,
+            range: range as u8,
+            ..Self::CONWAY
+        };
+        let most = rule.neighbor_count();
+        let mask = |counts: &[usize], name: &str| {
+            counts.iter().try_fold(0, |mask, &n| match n <= most {
+                true => Ok(mask | 1 << n),
+                false => Err(format!(
+                    "{name} count {n} is more than the {most} neighbors of a range {range} \
+                     {neighborhood:?} neighborhood"
+                )),
+            })
+        };
+        Ok(Self {
+            birth: mask(birth, "birth")?,
+            survival: mask(survival, "survival")?,
+            ..rule
+        })
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn range(&self) -> usize {
+        self.range as usize
+    }
+
+    /// The offsets of a cell's neighbors, row by row.
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        self.neighbor_offsets().collect()
+    }
+
+    /// The [`offsets`](Self::offsets), one at a time, for going through them
+    /// without allocating.
+    pub fn neighbor_offsets(&self) -> impl Iterator<Item = (isize, isize)> {
+        let (r, neighborhood) = (self.range as isize, self.neighborhood);
+        (-r..=r)
+            .flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| (dx, dy) != (0, 0))
+            .filter(move |&(dx, dy)| match neighborhood {
+                Neighborhood::Moore => true,
+                Neighborhood::VonNeumann => dx.abs() + dy.abs() <= r,
+            })
+    }
+
+    /// How many neighbors each cell has.
+    pub fn neighbor_count(&self) -> usize {
+        let r = self.range as usize;
+        match self.neighborhood {
+            Neighborhood::Moore => (2 * r + 1).pow(2) - 1,
+            Neighborhood::VonNeumann => 2 * r * (r + 1),
+        }
+    }
+
+    /// Parses the `B3/S23` notation, in either order and any case, or the
+    /// `R2,C0,M0,S2..3,B3,NM` notation for larger neighborhoods, where `NM`
+    /// is Moore and `NN` von Neumann.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.starts_with(['R', 'r']) && s.contains(',') {
+            return parse_ranged(s);
+        }
+        let invalid = || format!("invalid rule {s:?}, expected e.g. B3/S23");
+        let mut birth = None;
+        let mut survival = None;
+        for part in s.split('/') {
+            let (slot, digits) = match part.chars().next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => (&mut birth, &part[1..]),
+                Some('S') => (&mut survival, &part[1..]),
+                _ => return Err(invalid()),
+            };
+            if slot.is_some() {
+                return Err(invalid());
+            }
+            let counts = digits
+                .chars()
+                .map(|c| c.to_digit(9).map(|n| n as usize))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?;
+            *slot = Some(counts);
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rule::new(&birth, &survival)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Flips whether `count` live neighbors bring a dead cell to life, or
+    /// keep a live one alive if `survival`. Counts the neighborhood can't
+    /// reach are ignored.
+    pub fn toggle(&mut self, survival: bool, count: usize) {
+        if count > self.neighbor_count() {
+            return;
+        }
+        let mask = match survival {
+            true => &mut self.survival,
+            false => &mut self.birth,
+        };
+        *mask ^= 1 << count;
+    }
+
+    /// The next state of a cell with the given state and live-neighbor count.
+    pub fn next(&self, cell: bool, neighbors: usize) -> bool {
+        let mask = if cell { self.survival } else { self.birth };
+        neighbors < 64 && mask & 1 << neighbors != 0
+    }
+
+    /// Reads a rule file of `key = value` lines: `birth` and `survival` as
+    /// lists of counts and `a..b` ranges, such as `2..3, 5`, and optionally
+    /// `neighborhood`, `range` and `edges`. Lines starting with `#` are
+    /// comments. Returns the rule and the edge mode, if the file sets one.
+    pub fn parse_file(text: &str) -> Result<(Self, Option<EdgeMode>), String> {
+        let (mut birth, mut survival) = (None, None);
+        let (mut neighborhood, mut range, mut edges) = (Neighborhood::default(), 1, None);
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected KEY = VALUE, got {line:?}"))?;
+            let value = value.trim();
+            match key.trim() {
+                "birth" => birth = Some(parse_counts(value)?),
+                "survival" => survival = Some(parse_counts(value)?),
+                "neighborhood" => neighborhood = value.parse()?,
+                "range" => {
+                    range = value
+                        .parse()
+                        .map_err(|_| format!("bad rule range {value:?}"))?
+                }
+                "edges" => edges = Some(value.parse()?),
+                key => return Err(format!("unknown rule file key {key:?}")),
+            }
+        }
+        let birth = birth.ok_or("rule file needs a birth line")?;
+        let survival = survival.ok_or("rule file needs a survival line")?;
+        let rule = Rule::with_neighborhood(&birth, &survival, neighborhood, range)?;
+        Ok((rule, edges))
+    }
+}
+
+/// Counts separated by commas, each a number or an inclusive `a..b` range.
+fn parse_counts(s: &str) -> Result<Vec<usize>, String> {
+    let number = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("bad neighbor count {:?}", n.trim()))
+    };
+    let mut counts = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        match part.split_once("..") {
+            Some((from, to)) => counts.extend(number(from)?..=number(to)?),
+            None => counts.push(number(part)?),
+        }
+    }
+    Ok(counts)
+}
+
+/// Parses `R2,C0,M0,S2..3,B3,NM`, where the counts after `S` and `B` run on
+/// until the next letter.
+fn parse_ranged(s: &str) -> Result<Rule, String> {
+    let invalid = |why: &str| format!("invalid rule {s:?}: {why}");
+    let (mut range, mut neighborhood) = (None, Neighborhood::Moore);
+    let (mut birth, mut survival) = (Vec::new(), Vec::new());
+    let mut counts: Option<&mut Vec<usize>> = None;
+    for field in s.split(',').map(str::trim) {
+        let upper = field.to_ascii_uppercase();
+        let (tag, rest) = upper.split_at(upper.chars().next().map_or(0, char::len_utf8));
+        match tag {
+            "R" => range = Some(rest.parse::<usize>().map_err(|_| invalid("bad range"))?),
+            "C" if rest == "0" || rest == "2" => {}
+            "M" if rest == "0" => {}
+            "M" => return Err(invalid("only M0 is supported")),
+            "N" => {
+                neighborhood = match rest {
+                    "M" => Neighborhood::Moore,
+                    "N" => Neighborhood::VonNeumann,
+                    _ => return Err(invalid("expected NM or NN")),
+                }
+            }
+            "S" | "B" => {
+                let list = if tag == "S" {
+                    &mut survival
+                } else {
+                    &mut birth
+                };
+                list.extend(parse_counts(rest)?);
+                counts = Some(list);
+                continue;
+            }
+            _ if tag.starts_with(|c: char| c.is_ascii_digit()) => match &mut counts {
+                Some(list) => list.extend(parse_counts(field)?),
+                None => return Err(invalid("counts outside S or B")),
+            },
+            _ => return Err(invalid("unknown field")),
+        }
+        counts = None;
+    }
+    let range = range.ok_or_else(|| invalid("missing range"))?;
+    Rule::with_neighborhood(&birth, &survival, neighborhood, range)
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.range == 1 && self.neighborhood == Neighborhood::Moore {
+            let counts = |mask: u64| {
+                (0..=8)
+                    .filter(|n| mask & 1 << n != 0)
+                    .map(|n| char::from(b'0' + n as u8))
+                    .collect::<String>()
+            };
+            return write!(f, "B{}/S{}", counts(self.birth), counts(self.survival));
+        }
+        // Runs of counts are written as ranges.
+        let counts = |tag: char, mask: u64| {
+            let mut runs = Vec::new();
+            let mut n = 0;
+            while n < 64 {
+                if mask & 1 << n == 0 {
+                    n += 1;
+                    continue;
+                }
+                let mut end = n;
+                while end < 63 && mask & 1 << (end + 1) != 0 {
+                    end += 1;
+                }
+                runs.push(match end == n {
+                    true => n.to_string(),
+                    false => format!("{n}..{end}"),
+                });
+                n = end + 1;
+            }
+            format!("{tag}{}", runs.join(","))
+        };
+        let n = match self.neighborhood {
+            Neighborhood::Moore => 'M',
+            Neighborhood::VonNeumann => 'N',
+        };
+        let (s, b) = (counts('S', self.survival), counts('B', self.birth));
+        write!(f, "R{},C0,M0,{s},{b},N{n}", self.range)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// As [`Rule::parse`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::parse(s)
+    }
+}
+
+#[test]
+fn test_rule_parse_and_display() {
+    assert_eq!(Rule::parse("B3/S23"), Ok(Rule::CONWAY));
+    assert_eq!(Rule::parse("s23/b3"), Ok(Rule::CONWAY));
+    let highlife = Rule::parse("B36/S23").unwrap();
+    assert!(highlife.next(false, 6) && !Rule::CONWAY.next(false, 6));
+    assert_eq!(highlife.to_string(), "B36/S23");
+    assert_eq!(Rule::parse("B2/S").unwrap().to_string(), "B2/S");
+    for bad in ["", "B3", "B3/S2/S3", "B9/S23", "X3/S23"] {
+        assert!(Rule::parse(bad).is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn test_toggle_counts() {
+    let mut rule = Rule::CONWAY;
+    rule.toggle(false, 6);
+    assert_eq!(rule.to_string(), "B36/S23");
+    rule.toggle(true, 2);
+    rule.toggle(true, 9);
+    assert_eq!(rule.to_string(), "B36/S3");
+    rule.toggle(false, 6);
+    rule.toggle(true, 2);
+    assert_eq!(rule, Rule::CONWAY);
+}
+
+#[test]
+fn test_ranged_rules() {
+    let rule = Rule::parse("R2,C0,M0,S2..3,5,B3,NM").unwrap();
+    assert_eq!(
+        (rule.range(), rule.neighborhood()),
+        (2, Neighborhood::Moore)
+    );
+    assert_eq!(rule.neighbor_count(), 24);
+    assert_eq!(rule.offsets().len(), 24);
+    assert!(rule.next(true, 5) && !rule.next(true, 4) && rule.next(false, 3));
+    assert_eq!(rule.to_string(), "R2,C0,M0,S2..3,5,B3,NM");
+    assert_eq!(Rule::parse(&rule.to_string()), Ok(rule));
+
+    let diamond = Rule::with_neighborhood(&[], &[12], Neighborhood::VonNeumann, 2).unwrap();
+    assert_eq!(diamond.offsets().len(), diamond.neighbor_count());
+    assert_eq!(diamond.to_string(), "R2,C0,M0,S12,B,NN");
+    assert_eq!(Rule::parse(&diamond.to_string()), Ok(diamond));
+    assert!(Rule::with_neighborhood(&[13], &[], Neighborhood::VonNeumann, 2).is_err());
+    for bad in [
+        "R4,C0,M0,S2,B3,NM",
+        "R2,C0,M1,S2,B3,NM",
+        "R2,9,S2,B3",
+        "R2,C0,S2,B3,NX",
+    ] {
+        assert!(Rule::parse(bad).is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn test_rule_file() {
+    let text = "# Star Wars-ish\nbirth = 2\nsurvival = 3..5, 7\nrange = 2\nedges = toroidal\n";
+    let (rule, edges) = Rule::parse_file(text).unwrap();
+    let expected = Rule::with_neighborhood(&[2], &[3, 4, 5, 7], Neighborhood::Moore, 2).unwrap();
+    assert_eq!((rule, edges), (expected, Some(EdgeMode::Toroidal)));
+    let (conway, edges) = Rule::parse_file("survival = 2, 3\nbirth = 3").unwrap();
+    assert_eq!((conway, edges), (Rule::CONWAY, None));
+    for bad in [
+        "birth = 3",
+        "birth = 3\nsurvival = 9",
+        "birth = 3\nsurvival = 2\nneighborhood = hex",
+        "birth = 3\nsurvival = 2\nrange = 0",
+        "birth = 3\nsurvival = 2\ncolor = red",
+        "birth 3",
+    ] {
+        assert!(Rule::parse_file(bad).is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!("B3/S23".parse(), Ok(Rule::CONWAY));
+    assert_eq!("Toroidal".parse(), Ok(EdgeMode::Toroidal));
+    assert_eq!("BOUNDED".parse(), Ok(EdgeMode::Bounded));
+    assert_eq!("mirror".parse(), Ok(EdgeMode::Mirror));
+    assert_eq!("VonNeumann".parse(), Ok(Neighborhood::VonNeumann));
+    assert_eq!("Moore".parse(), Ok(Neighborhood::Moore));
+
+    let err = "hexagonal".parse::<Neighborhood>().unwrap_err();
+    assert!(err.contains("\"hexagonal\""), "{err}");
+    let err = "sphere".parse::<EdgeMode>().unwrap_err();
+    assert!(
+        err.contains("expected bounded, toroidal or mirror"),
+        "{err}"
+    );
+    assert!("B9/S23".parse::<Rule>().is_err());
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use crate::{Neighborhood, Rule};
+
+/// Sections of a `.rule` file that only say how to draw the rule, and so
+/// are passed over.
+const COSMETIC: [&str; 3] = ["@COLORS", "@ICONS", "@NAMES"];
+
+/// One entry of a transition: a state, or a variable standing for any of
+/// several.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    State(u8),
+    Var(String),
+}
+
+/// What the `@TABLE` section of a `.rule` file says, as far as it has been
+/// read.
+#[derive(Debug, Default)]
+struct Table {
+    states: Option<u8>,
+    neighborhood: Option<Neighborhood>,
+    permute: bool,
+    vars: HashMap<String, Vec<u8>>,
+    /// The cell, its neighbors and what the cell becomes, in order.
+    transitions: Vec<Vec<Entry>>,
+}
+
+impl Rule {
+    /// Reads a Golly `.rule` file whose `@TABLE` a rule can say as well: two
+    /// states, the Moore or von Neumann neighborhood, and `permute`
+    /// symmetry, under which what a cell becomes depends only on how many
+    /// of its neighbors are alive. As in Golly, the first transition that
+    /// matches wins, a variable repeated in a transition stands for the same
+    /// state throughout, and a cell no transition matches stays as it is.
+    /// The `@COLORS`, `@ICONS` and `@NAMES` sections are passed over; any
+    /// other section, and any other directive or symmetry, is refused with
+    /// the line it is on.
+    pub fn parse_golly(text: &str) -> Result<Self, String> {
+        let mut section = None;
+        let mut table = Table::default();
+        for (n, line) in text.lines().enumerate() {
+            let at = |err: String| format!("line {}: {err}", n + 1);
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('@') {
+                let name = line.split_whitespace().next().unwrap_or(line);
+                section = match name {
+                    "@RULE" | "@TABLE" => Some(name),
+                    _ if COSMETIC.contains(&name) => Some(name),
+                    _ => return Err(at(format!("{name} isn't supported, only @TABLE"))),
+                };
+                continue;
+            }
+            match section {
+                None => return Err(at(format!("expected @RULE, got {line:?}"))),
+                Some("@TABLE") => table.read(line).map_err(at)?,
+                // The rule's name and description, or how to draw it.
+                Some(_) => {}
+            }
+        }
+        if table.transitions.is_empty() {
+            return Err("no transitions in an @TABLE section".to_string());
+        }
+        table.rule()
+    }
+}
+
+impl Table {
+    /// Reads a line of the table.
+    fn read(&mut self, line: &str) -> Result<(), String> {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "n_states" => match value.parse::<u8>() {
+                    Ok(2) => self.states = Some(2),
+                    Ok(states) if states > 2 => {
+                        return Err(format!("n_states:{value} isn't supported, only 2"))
+                    }
+                    _ => return Err(format!("bad n_states:{value}")),
+                },
+                "neighborhood" => {
+                    self.neighborhood = Some(match value {
+                        "Moore" => Neighborhood::Moore,
+                        "vonNeumann" => Neighborhood::VonNeumann,
+                        _ => {
+                            return Err(format!(
+                                "neighborhood:{value} isn't supported, only Moore and vonNeumann"
+                            ))
+                        }
+                    })
+                }
+                "symmetries" if value == "permute" => self.permute = true,
+                "symmetries" => {
+                    return Err(format!("symmetries:{value} isn't supported, only permute"))
+                }
+                key => return Err(format!("unknown directive {key:?}")),
+            }
+            return Ok(());
+        }
+        if let Some(var) = line.strip_prefix("var ") {
+            let (name, set) = var.split_once('=').ok_or("expected var NAME = {STATES}")?;
+            let name = name.trim();
+            if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                return Err(format!("bad variable name {name:?}"));
+            }
+            let set = set.trim();
+            let set = set
+                .strip_prefix('{')
+                .and_then(|set| set.strip_suffix('}'))
+                .ok_or_else(|| format!("expected a set such as {{0,1}}, got {set:?}"))?;
+            let mut states = Vec::new();
+            for entry in set.split(',') {
+                match self.entry(entry.trim())? {
+                    Entry::State(state) => states.push(state),
+                    Entry::Var(var) => states.extend(&self.vars[&var]),
+                }
+            }
+            self.vars.insert(name.to_string(), states);
+            return Ok(());
+        }
+
+        let neighborhood = match (self.states, self.neighborhood, self.permute) {
+            (Some(_), Some(neighborhood), true) => neighborhood,
+            _ => return Err("n_states, neighborhood and symmetries must come first".to_string()),
+        };
+        let entries: Result<Vec<Entry>, String> = match line.contains(',') {
+            true => line
+                .split(',')
+                .map(|entry| self.entry(entry.trim()))
+                .collect(),
+            // States of one digit can be run together.
+            false => line.chars().map(|c| self.entry(&c.to_string())).collect(),
+        };
+        let entries = entries?;
+        let wanted = Rule::with_neighborhood(&[], &[], neighborhood, 1)?.neighbor_count() + 2;
+        if entries.len() != wanted {
+            return Err(format!(
+                "expected {wanted} entries for the {neighborhood:?} neighborhood, got {}",
+                entries.len()
+            ));
+        }
+        if let Some(Entry::Var(var)) = entries.last() {
+            if !entries[..wanted - 1].contains(&Entry::Var(var.clone())) {
+                return Err(format!(
+                    "the new state {var} isn't bound by the cell or a neighbor"
+                ));
+            }
+        }
+        self.transitions.push(entries);
+        Ok(())
+    }
+
+    /// A state below `n_states`, or a variable defined already.
+    fn entry(&self, entry: &str) -> Result<Entry, String> {
+        match entry.parse::<u8>() {
+            Ok(state) if state < self.states.unwrap_or(2) => Ok(Entry::State(state)),
+            Ok(state) => Err(format!("state {state} is more than a two-state table has")),
+            Err(_) if self.vars.contains_key(entry) => Ok(Entry::Var(entry.to_string())),
+            Err(_) => Err(format!("unknown variable {entry:?}")),
+        }
+    }
+
+    /// The rule the transitions make, from the state a cell becomes with
+    /// each count of live neighbors.
+    fn rule(&self) -> Result<Rule, String> {
+        let neighborhood = self.neighborhood.unwrap_or_default();
+        let neighbors = Rule::with_neighborhood(&[], &[], neighborhood, 1)?.neighbor_count();
+        let next = |cell: u8, alive: usize| {
+            let matched = self.transitions.iter().find_map(|entries| {
+                self.assignments(entries).into_iter().find_map(|states| {
+                    let live = states[1..=neighbors]
+                        .iter()
+                        .filter(|&&state| state == 1)
+                        .count();
+                    (states[0] == cell && live == alive).then_some(states[neighbors + 1])
+                })
+            });
+            matched.unwrap_or(cell) == 1
+        };
+        let birth: Vec<_> = (0..=neighbors).filter(|&alive| next(0, alive)).collect();
+        let survival: Vec<_> = (0..=neighbors).filter(|&alive| next(1, alive)).collect();
+        Rule::with_neighborhood(&birth, &survival, neighborhood, 1)
+    }
+
+    /// Every way of giving the variables in `entries` states, each the same
+    /// everywhere it appears, as the states the entries then are.
+    fn assignments(&self, entries: &[Entry]) -> Vec<Vec<u8>> {
+        let mut names: Vec<&str> = Vec::new();
+        for entry in entries {
+            if let Entry::Var(var) = entry {
+                if !names.contains(&var.as_str()) {
+                    names.push(var);
+                }
+            }
+        }
+        let mut bindings: Vec<Vec<u8>> = vec![Vec::new()];
+        for name in &names {
+            bindings = bindings
+                .into_iter()
+                .flat_map(|bound| {
+                    self.vars[*name].iter().map(move |&state| {
+                        let mut bound = bound.clone();
+                        bound.push(state);
+                        bound
+                    })
+                })
+                .collect();
+        }
+        let state = |bound: &[u8], entry: &Entry| match entry {
+            Entry::State(state) => *state,
+            Entry::Var(var) => bound[names.iter().position(|name| name == var).unwrap()],
+        };
+        bindings
+            .iter()
+            .map(|bound| entries.iter().map(|entry| state(bound, entry)).collect())
+            .collect()
+    }
+}
+
+#[test]
+fn test_permute_tables_become_rules() {
+    let highlife = "\
+@RULE HighLife
+A hand-written table for B36/S23.
+
+@TABLE
+n_states:2
+neighborhood:Moore
+symmetries:permute
+var a={0,1}
+var b={0,1}
+var c={0,1}
+var d={0,1}
+var e={0,1}
+var f={0,1}
+var g={0,1}
+var h={a}
+
+# Born with three or six neighbors.
+0,1,1,1,0,0,0,0,0,1
+0111111001
+# Surviving with two or three, in any order.
+1,0,1,0,1,0,0,0,0,1
+1,1,1,1,0,0,0,0,0,1
+# Anything else dies.
+1,a,b,c,d,e,f,g,h,0
+
+@COLORS
+1 255 255 0
+";
+    assert_eq!(Rule::parse_golly(highlife), Rule::parse("B36/S23"));
+    // The first transition to match wins, bound variables match alike
+    // states, and what nothing matches stays as it was.
+    let bound = "@RULE Bound\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:permute\n\
+                 var a={0,1}\n1,a,a,a,a,a\n0,1,1,0,0,1\n0,1,1,0,0,0\n1,1,0,0,0,0\n";
+    let rule = Rule::with_neighborhood(&[2], &[2, 3, 4], Neighborhood::VonNeumann, 1);
+    assert_eq!(Rule::parse_golly(bound), rule);
+}
+
+#[test]
+fn test_unsupported_tables_name_the_line() {
+    let table = |directives: &str| {
+        let text = format!("@RULE Test\n@TABLE\n{directives}\n0,1,1,1,0,0,0,0,0,1\n");
+        Rule::parse_golly(&text).unwrap_err()
+    };
+    let permute = "n_states:2\nneighborhood:Moore\nsymmetries:permute";
+    assert_eq!(
+        table("n_states:2\nneighborhood:Moore\nsymmetries:rotate4"),
+        "line 5: symmetries:rotate4 isn't supported, only permute"
+    );
+    assert_eq!(
+        table("n_states:2\nneighborhood:hexagonal\nsymmetries:permute"),
+        "line 4: neighborhood:hexagonal isn't supported, only Moore and vonNeumann"
+    );
+    assert_eq!(
+        table("n_states:3"),
+        "line 3: n_states:3 isn't supported, only 2"
+    );
+    assert_eq!(
+        table("neighborhood:Moore\nsymmetries:permute"),
+        "line 5: n_states, neighborhood and symmetries must come first"
+    );
+    assert_eq!(
+        table(&format!("{permute}\n0,1,1\n")),
+        "line 6: expected 10 entries for the Moore neighborhood, got 3"
+    );
+    let unknown = table(&format!("{permute}\n0,1,1,x,0,0,0,0,0,1"));
+    assert_eq!(unknown, "line 6: unknown variable \"x\"");
+    assert_eq!(
+        table(&format!("{permute}\nvar a={{0,1}}\n0,1,1,1,0,0,0,0,0,a")),
+        "line 7: the new state a isn't bound by the cell or a neighbor"
+    );
+    assert_eq!(
+        table(&format!("{permute}\n@TREE")),
+        "line 6: @TREE isn't supported, only @TABLE"
+    );
+    let err = Rule::parse_golly("n_states:2").unwrap_err();
+    assert_eq!(err, "line 1: expected @RULE, got \"n_states:2\"");
+    assert!(Rule::parse_golly("@RULE Empty\n@TABLE\n").is_err());
+}
@@ -0,0 +1,367 @@
+use std::{fmt, path::Path};
+
+use crate::{
+    macrocell,
+    metadata::{Metadata, VERSION},
+    rle, Axis, EdgeMode, GameOfLife, GridPos, Kernel, KernelRule, Pattern, Rule,
+};
+
+/// One change to a pattern's orientation before it is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Turned clockwise by this many quarter turns, 0 to 3.
+    Rotate(u32),
+    /// Mirrored along the axis, as by [`Pattern::flipped`].
+    Flip(Axis),
+}
+
+impl Transform {
+    /// A clockwise turn of `degrees`, which must be 0, 90, 180 or 270.
+    pub fn rotation(degrees: &str) -> Result<Self, String> {
+        match degrees {
+            "0" => Ok(Transform::Rotate(0)),
+            "90" => Ok(Transform::Rotate(1)),
+            "180" => Ok(Transform::Rotate(2)),
+            "270" => Ok(Transform::Rotate(3)),
+            _ => Err(format!(
+                "rotation must be 0, 90, 180 or 270, got {degrees:?}"
+            )),
+        }
+    }
+}
+
+/// A pattern put on the board by a scene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// A library pattern's name, or the path of an RLE or macrocell (`.mc`)
+    /// file relative to the scene file.
+    pub source: String,
+    /// Where the transformed pattern's top-left corner goes.
+    pub origin: GridPos,
+    /// Applied in order.
+    pub transforms: Vec<Transform>,
+}
+
+/// A board set up from a scene file: its size, rule and edges, and the
+/// patterns placed on it, for experiments that need to start the same way
+/// every time.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scene {
+    /// The board's size; the caller's default when not given.
+    pub size: Option<(usize, usize)>,
+    pub rule: Rule,
+    /// Followed in place of `rule` when given.
+    pub kernel: Option<KernelRule>,
+    pub edges: EdgeMode,
+    /// Placed in order, so later patterns overwrite earlier ones where their
+    /// boxes overlap.
+    pub placements: Vec<Placement>,
+    /// The rest of what the file says about itself, such as its version; the
+    /// rule and edges are taken out of it into their own fields.
+    pub metadata: Metadata,
+}
+
+impl Scene {
+    /// Reads a scene file of `key = value` lines: `size` as `WxH`, `rule`,
+    /// `edges`, and any number of `place` lines such as
+    /// `place = glider at 10,4 rotate 90 flip x`, where rotations are
+    /// clockwise in degrees and the pattern's source can't contain spaces.
+    /// A [`KernelRule`] is given by a `kernel` line for each row of weights,
+    /// top first, such as `kernel = 1 2 1`, with `birth` and `survival` as
+    /// ranges of the weighted sum, such as `-1..3, 5`. Lines starting with
+    /// `#` are comments, and other keys are [`Metadata`], whose fields from a
+    /// later version are kept but which are refused in a file of this version
+    /// or older. Errors name the line at fault.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut scene = Scene::default();
+        let mut metadata = Metadata::unversioned();
+        // Where the first key metadata doesn't know is.
+        let mut unknown = None;
+        let mut weights = Vec::new();
+        let (mut birth, mut survival) = (None, None);
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let at = |err: String| format!("line {}: {err}", n + 1);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| at(format!("expected KEY = VALUE, got {line:?}")))?;
+            let value = value.trim();
+            match key.trim() {
+                "size" => scene.size = Some(parse_size(value).map_err(at)?),
+                "place" => scene.placements.push(parse_placement(value).map_err(at)?),
+                "kernel" => weights.push(Kernel::parse_row(value).map_err(at)?),
+                "birth" => birth = Some(KernelRule::parse_sums(value).map_err(at)?),
+                "survival" => survival = Some(KernelRule::parse_sums(value).map_err(at)?),
+                key => {
+                    let known = metadata.unknown.len();
+                    if !metadata.read_line(line).map_err(at)? {
+                        return Err(at(format!("unknown scene key {key:?}")));
+                    }
+                    if metadata.unknown.len() > known {
+                        unknown = unknown.or(Some(at(format!("unknown scene key {key:?}"))));
+                    }
+                }
+            }
+        }
+        if let Some(err) = unknown.filter(|_| metadata.version <= VERSION) {
+            return Err(err);
+        }
+        scene.kernel = match (weights.is_empty(), birth, survival) {
+            (true, None, None) => None,
+            (false, Some(birth), Some(survival)) => Some(KernelRule::new(
+                Kernel::from_rows(&weights)?,
+                birth,
+                survival,
+            )),
+            _ => return Err("a kernel needs kernel, birth and survival lines".to_string()),
+        };
+        metadata.migrate();
+        scene.rule = metadata.rule.take().unwrap_or_default();
+        scene.edges = metadata.edges.take().unwrap_or_default();
+        scene.metadata = metadata;
+        Ok(scene)
+    }
+
+    /// Builds the board, `size` cells unless the scene gives its own, with
+    /// pattern files read relative to `dir`, along with a warning for each
+    /// pattern that didn't fit. Errors name the placement at fault, counting
+    /// from 1.
+    pub fn build(
+        &self,
+        size: (usize, usize),
+        dir: &Path,
+    ) -> Result<(GameOfLife, Vec<String>), String> {
+        let mut game = GameOfLife::new_empty(self.size.unwrap_or(size));
+        game.set_rule(self.rule);
+        game.set_kernel_rule(self.kernel.clone());
+        game.set_edge_mode(self.edges);
+        let (w, h) = game.size();
+        let mut warnings = Vec::new();
+        for (i, placement) in self.placements.iter().enumerate() {
+            let at = |err: String| format!("placement {} ({}): {err}", i + 1, placement.source);
+            let pattern = placement.pattern(dir).map_err(at)?;
+            let GridPos(x, y) = placement.origin;
+            if x >= w || y >= h {
+                return Err(at(format!("{x},{y} is outside the {w}x{h} board")));
+            }
+            let placed = game.insert_pattern(&pattern, placement.origin);
+            warnings.extend(placed.warning(&placement.source));
+        }
+        Ok((game, warnings))
+    }
+}
+
+impl Placement {
+    /// The pattern, loaded and transformed.
+    pub fn pattern(&self, dir: &Path) -> Result<Pattern, String> {
+        let pattern = match Pattern::named(&self.source) {
+            Some(pattern) => pattern,
+            None => {
+                let path = dir.join(&self.source);
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("{}: {err}", path.display()))?;
+                let decode = match path.extension() {
+                    Some(ext) if ext == "mc" => macrocell::decode,
+                    _ => rle::decode,
+                };
+                decode(&text)?.0
+            }
+        };
+        Ok(pattern.transformed(&self.transforms))
+    }
+}
+
+/// `WxH`, both positive.
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("size must be WxH, e.g. 80x40, got {value:?}");
+    let (w, h) = value.split_once('x').ok_or_else(invalid)?;
+    match (w.parse(), h.parse()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(invalid()),
+    }
+}
+
+/// `SOURCE at X,Y` followed by any of `rotate DEGREES` and `flip x|y`.
+fn parse_placement(value: &str) -> Result<Placement, String> {
+    let invalid = |why: &str| format!("{why} in {value:?}, expected e.g. glider at 10,4 rotate 90");
+    let mut words = value.split_whitespace();
+    let source = words
+        .next()
+        .ok_or_else(|| invalid("no pattern"))?
+        .to_string();
+    if words.next() != Some("at") {
+        return Err(invalid("no position"));
+    }
+    let origin = words
+        .next()
+        .and_then(|xy| xy.split_once(','))
+        .and_then(|(x, y)| Some(GridPos(x.parse().ok()?, y.parse().ok()?)))
+        .ok_or_else(|| invalid("bad position"))?;
+    let mut transforms = Vec::new();
+    while let Some(word) = words.next() {
+        let transform = match (word, words.next()) {
+            ("rotate", degrees) => Transform::rotation(degrees.unwrap_or_default())
+                .map_err(|_| invalid("rotation must be 0, 90, 180 or 270"))?,
+            ("flip", Some(axis)) => Transform::Flip(axis.parse()?),
+            _ => return Err(invalid(&format!("unknown transform {word:?}"))),
+        };
+        transforms.push(transform);
+    }
+    Ok(Placement {
+        source,
+        origin,
+        transforms,
+    })
+}
+
+impl fmt::Display for Scene {
+    /// The scene file [`Scene::parse`] reads back as the same scene.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metadata = Metadata {
+            rule: Some(self.rule),
+            edges: Some(self.edges),
+            ..self.metadata.clone()
+        };
+        for line in metadata.lines() {
+            writeln!(f, "{line}")?;
+        }
+        if let Some((w, h)) = self.size {
+            writeln!(f, "size = {w}x{h}")?;
+        }
+        if let Some(kernel) = &self.kernel {
+            for row in kernel.kernel().to_string().lines() {
+                writeln!(f, "kernel = {row}")?;
+            }
+            let (birth, survival) = kernel.sums();
+            writeln!(f, "birth = {birth}\nsurvival = {survival}")?;
+        }
+        for placement in &self.placements {
+            let GridPos(x, y) = placement.origin;
+            write!(f, "place = {} at {x},{y}", placement.source)?;
+            for transform in &placement.transforms {
+                match transform {
+                    Transform::Rotate(turns) => write!(f, " rotate {}", turns * 90)?,
+                    Transform::Flip(Axis::X) => write!(f, " flip x")?,
+                    Transform::Flip(Axis::Y) => write!(f, " flip y")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_scene_round_trips() {
+    let text = "\
+# Two guns aimed at each other.
+size = 120x60
+rule = B3/S23
+edges = toroidal
+place = gosper-glider-gun at 2,2
+place = gosper-glider-gun at 80,40 rotate 180 flip y
+annotation = 2,1 yellow gun #1
+";
+    let scene = Scene::parse(text).unwrap();
+    assert_eq!(scene.size, Some((120, 60)));
+    assert_eq!(scene.edges, EdgeMode::Toroidal);
+    assert_eq!(
+        scene.placements[1].transforms,
+        [Transform::Rotate(2), Transform::Flip(Axis::Y)]
+    );
+    // Notes ride along in the metadata, and put nothing on the board.
+    assert_eq!(
+        scene.metadata.annotations[0].to_string(),
+        "2,1 yellow gun #1"
+    );
+    assert_eq!(Scene::parse(&scene.to_string()), Ok(scene.clone()));
+
+    let (game, warnings) = scene.build((10, 10), Path::new(".")).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(game.size(), (120, 60));
+    assert_eq!(game.edge_mode(), EdgeMode::Toroidal);
+    assert_eq!(game.population(), 72);
+    let gun = Pattern::named("gosper-glider-gun").unwrap();
+    let flipped = gun.rotated(2).flipped(Axis::Y);
+    for &(x, y) in flipped.cells() {
+        assert!(game.get(GridPos(80 + x, 40 + y)));
+    }
+}
+
+#[test]
+fn test_scene_errors_name_the_entry() {
+    let err = Scene::parse("size = 10x10\nplace = glider 4,4\n").unwrap_err();
+    assert!(err.starts_with("line 2:"), "{err}");
+    let err = Scene::parse("place = glider at 1,1 rotate 45").unwrap_err();
+    assert!(err.contains("rotation"), "{err}");
+    assert!(Scene::parse("rule = B9/S")
+        .unwrap_err()
+        .starts_with("line 1:"));
+    assert!(Scene::parse("speed = 3").is_err());
+
+    let scene = Scene::parse("size = 8x8\nplace = block at 0,0\nplace = glider at 9,0").unwrap();
+    let err = scene.build((8, 8), Path::new(".")).err().unwrap();
+    assert!(err.starts_with("placement 2 (glider):"), "{err}");
+    // Patterns running off the board aren't errors, but are warned about.
+    let scene = Scene::parse("size = 8x8\nplace = block at 7,7").unwrap();
+    let (game, warnings) = scene.build((8, 8), Path::new(".")).unwrap();
+    assert_eq!(game.population(), 1);
+    assert_eq!(
+        warnings,
+        ["block clipped: 3 cells off-grid — try a larger terminal"]
+    );
+    let scene = Scene::parse("size = 8x8\nedges = toroidal\nplace = block at 7,7").unwrap();
+    let (game, warnings) = scene.build((8, 8), Path::new(".")).unwrap();
+    assert_eq!((game.population(), warnings.len()), (4, 0));
+    let scene = Scene::parse("place = no-such-pattern.rle at 0,0").unwrap();
+    let err = scene
+        .build((8, 8), Path::new("/nonexistent"))
+        .err()
+        .unwrap();
+    assert!(
+        err.starts_with("placement 1 (no-such-pattern.rle):"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_scene_keeps_a_later_versions_fields() {
+    let text = "version = 3\nsize = 8x8\ntopology = klein\nplace = block at 1,1\n";
+    let scene = Scene::parse(text).unwrap();
+    assert_eq!(
+        scene.metadata.unknown,
+        [("topology".to_string(), "klein".to_string())]
+    );
+    let saved = scene.to_string();
+    assert!(saved.starts_with("version = 3\nrule = B3/S23\nedges = bounded\ntopology = klein\n"));
+    assert_eq!(Scene::parse(&saved), Ok(scene));
+    // This version's files can't have keys it doesn't know, wherever the
+    // version is given.
+    let err = Scene::parse("speed = 3\nversion = 2").unwrap_err();
+    assert_eq!(err, "line 1: unknown scene key \"speed\"");
+    let err = Scene::parse("rule = B3/S23\nrule = B36/S23").unwrap_err();
+    assert!(err.starts_with("line 2: rule is given twice"), "{err}");
+}
+
+#[test]
+fn test_scene_kernel_rules() {
+    let text = "size = 9x9\nkernel = 1 2 1\nkernel = 2 0 2\nkernel = 1 2 1\nbirth = 5..6\n\
+                survival = 4..8, 10\nplace = blinker at 3,3\n";
+    let scene = Scene::parse(text).unwrap();
+    let kernel = scene.kernel.as_ref().unwrap();
+    assert_eq!(kernel.kernel().weight(1, 0), 2);
+    assert_eq!(kernel.sums(), ("5..6".to_string(), "4..8, 10".to_string()));
+    assert_eq!(Scene::parse(&scene.to_string()), Ok(scene.clone()));
+    let (game, _) = scene.build((9, 9), Path::new(".")).unwrap();
+    assert_eq!(game.kernel_rule(), scene.kernel.as_ref());
+
+    let err = Scene::parse("kernel = 1 1\nkernel = 1 1\nbirth = 1\nsurvival = 1").unwrap_err();
+    assert!(err.contains("odd on a side"), "{err}");
+    assert!(Scene::parse("kernel = 1\nbirth = 1").is_err());
+    assert!(Scene::parse("birth = 1..")
+        .unwrap_err()
+        .starts_with("line 1:"));
+}
@@ -0,0 +1,502 @@
+use std::{
+    cell::OnceCell,
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+use crate::{Edit, GameOfLife, GridPos};
+
+/// What a stream to an observer starts with, before its version.
+const MAGIC: &[u8; 8] = b"GOLWATCH";
+
+/// The version written now.
+const VERSION: u32 = 1;
+
+/// Bytes before the first message: the magic and the version.
+const HEADER: usize = 8 + 4;
+
+/// The tag of a message carrying the whole board: its generation, its width
+/// and height, and its cells packed as [`GameOfLife::pack_into`] packs them.
+const KEYFRAME: u8 = b'K';
+
+/// The tag of a message carrying the cells that changed since the message
+/// before, an [`Edit::Cells`] that flips them: its generation, how many
+/// cells, and their indices, row-major.
+const DIFF: u8 = b'D';
+
+/// Bytes an observer can have waiting before it counts as falling behind,
+/// unless told otherwise. It then gets a keyframe in place of everything
+/// it hasn't read, rather than the backlog growing without bound.
+pub const BACKLOG: usize = 1 << 20;
+
+fn invalid(why: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, why.into())
+}
+
+/// Streams a board to any number of observers as it changes: a keyframe of
+/// the whole board to each as it joins, then the cells each generation
+/// changed. Observers are only ever written to without waiting, and one
+/// that can't keep up skips to a keyframe.
+pub struct Server {
+    /// The socket observers connect to, and where it is, to remove it.
+    listener: Option<(UnixListener, PathBuf)>,
+    observers: Vec<Watcher>,
+    /// The board as last sent, and its generation.
+    sent: Option<(u64, GameOfLife)>,
+    words: Vec<u64>,
+    backlog: usize,
+    resyncs: u64,
+}
+
+/// An observer and the messages it hasn't read yet.
+#[derive(Debug)]
+struct Watcher {
+    stream: UnixStream,
+    queue: VecDeque<Vec<u8>>,
+    /// How much of the front message is written.
+    written: usize,
+    /// Needs the whole board, having just joined or fallen behind.
+    keyframe: bool,
+}
+
+impl Watcher {
+    /// Bytes queued and not yet written.
+    fn waiting(&self) -> usize {
+        self.queue.iter().map(Vec::len).sum::<usize>() - self.written
+    }
+
+    /// Forgets what is queued but the part of a message already begun, which
+    /// has to be finished for the stream to make sense.
+    fn skip(&mut self) {
+        let begun = (self.written > 0).then(|| self.queue.pop_front()).flatten();
+        self.queue.clear();
+        self.queue.extend(begun);
+    }
+
+    /// Writes what it can without waiting; an error once it has gone.
+    fn flush(&mut self) -> io::Result<()> {
+        while let Some(message) = self.queue.front() {
+            match self.stream.write(&message[self.written..]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => self.written += n,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+            if self.written == message.len() {
+                self.queue.pop_front();
+                self.written = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Server {
+    /// A server with no socket, streaming only to the observers
+    /// [`add`](Self::add)ed to it.
+    pub fn new() -> Self {
+        Self {
+            listener: None,
+            observers: Vec::new(),
+            sent: None,
+            words: Vec::new(),
+            backlog: BACKLOG,
+            resyncs: 0,
+        }
+    }
+
+    /// Listens for observers on a Unix socket at `path`, taking the place of
+    /// one left behind by a server that is no longer running.
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if path.exists() && UnixStream::connect(path).is_err() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        let mut server = Self::new();
+        server.listener = Some((listener, path.to_path_buf()));
+        Ok(server)
+    }
+
+    /// Lets each observer fall `bytes` behind before it skips to a keyframe.
+    pub fn with_backlog(mut self, bytes: usize) -> Self {
+        self.backlog = bytes;
+        self
+    }
+
+    /// Streams to `stream` from the next [`publish`](Self::publish) on.
+    pub fn add(&mut self, stream: UnixStream) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        let mut header = MAGIC.to_vec();
+        header.extend(VERSION.to_le_bytes());
+        let queue = VecDeque::from([header]);
+        self.observers.push(Watcher {
+            stream,
+            queue,
+            written: 0,
+            keyframe: true,
+        });
+        Ok(())
+    }
+
+    /// How many observers are connected.
+    pub fn observers(&self) -> usize {
+        self.observers.len()
+    }
+
+    /// How many times an observer fell behind and skipped to a keyframe.
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// Takes in observers that have connected, sends each what changed on
+    /// `game` since the last call, and writes all it can without waiting.
+    /// Observers that have gone are dropped.
+    pub fn publish(&mut self, game: &GameOfLife) {
+        let mut joined = Vec::new();
+        while let Some((listener, _)) = &self.listener {
+            match listener.accept() {
+                Ok((stream, _)) => joined.push(stream),
+                Err(_) => break,
+            }
+        }
+        for stream in joined {
+            let _ = self.add(stream);
+        }
+        if self.observers.is_empty() {
+            // Whoever joins next starts from a keyframe anyway.
+            self.sent = None;
+            return;
+        }
+        let (size, generation) = (game.size(), game.generation());
+        game.pack_into(&mut self.words);
+        let keyframe = OnceCell::new();
+        let keyframe = || keyframe.get_or_init(|| encode_keyframe(size, generation, &self.words));
+        // What everyone not waiting on a keyframe is sent: `None` for the
+        // board, or nothing if it hasn't changed.
+        let update = match &self.sent {
+            Some((_, sent)) if sent.size() != size => None,
+            Some((sent_generation, sent)) => {
+                let edit = Edit::between(sent, game);
+                match edit.is_empty() && *sent_generation == generation {
+                    true => Some(None),
+                    // A diff bigger than the board may as well be the board.
+                    false => encode_diff(generation, &edit, size.0)
+                        .filter(|diff| diff.len() < 17 + self.words.len() * 8)
+                        .map(Some),
+                }
+            }
+            None => None,
+        };
+        for watcher in &mut self.observers {
+            let message = match &update {
+                _ if watcher.keyframe => keyframe(),
+                Some(Some(diff)) => diff,
+                Some(None) => continue,
+                None => keyframe(),
+            };
+            if watcher.waiting() + message.len() > self.backlog.max(keyframe().len()) {
+                watcher.skip();
+                watcher.queue.push_back(keyframe().clone());
+                self.resyncs += 1;
+            } else {
+                watcher.queue.push_back(message.clone());
+            }
+            watcher.keyframe = false;
+        }
+        self.observers.retain_mut(|watcher| watcher.flush().is_ok());
+        match &mut self.sent {
+            Some((sent_generation, sent)) if sent.size() == size => {
+                *sent_generation = generation;
+                sent.surface_mut().copy_from_slice(game.surface());
+            }
+            _ => {
+                let mut sent = GameOfLife::new_empty(size);
+                sent.surface_mut().copy_from_slice(game.surface());
+                self.sent = Some((generation, sent));
+            }
+        }
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some((_, path)) = &self.listener {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn encode_keyframe(size: (usize, usize), generation: u64, words: &[u64]) -> Vec<u8> {
+    let mut message = vec![KEYFRAME];
+    message.extend(generation.to_le_bytes());
+    message.extend((size.0 as u32).to_le_bytes());
+    message.extend((size.1 as u32).to_le_bytes());
+    message.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+    message
+}
+
+/// A diff of the cells `edit` lists, on a board `width` cells wide, or
+/// `None` for edits that aren't a list of cells.
+fn encode_diff(generation: u64, edit: &Edit, width: usize) -> Option<Vec<u8>> {
+    let Edit::Cells(cells) = edit else {
+        return None;
+    };
+    let mut message = vec![DIFF];
+    message.extend(generation.to_le_bytes());
+    message.extend((cells.len() as u32).to_le_bytes());
+    for &(GridPos(x, y), _) in cells {
+        message.extend(((y * width + x) as u32).to_le_bytes());
+    }
+    Some(message)
+}
+
+/// The board a [`Server`] streams, as seen from an observer. Reads from an
+/// input that doesn't wait, such as a non-blocking socket, applying each
+/// message once it has all arrived.
+pub struct Feed<R: Read> {
+    input: R,
+    /// Bytes read that don't make up a whole message yet.
+    buffer: Vec<u8>,
+    header: bool,
+    closed: bool,
+    generation: Option<u64>,
+    /// The board once a keyframe has arrived, each diff after it applied as
+    /// an [`Edit`].
+    board: Option<GameOfLife>,
+}
+
+impl Feed<UnixStream> {
+    /// Observes the server listening at `path`.
+    pub fn connect(path: &Path) -> io::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        stream.set_nonblocking(true)?;
+        Ok(Self::new(stream))
+    }
+}
+
+impl<R: Read> Feed<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            buffer: Vec::new(),
+            header: false,
+            closed: false,
+            generation: None,
+            board: None,
+        }
+    }
+
+    /// The board's size, once a keyframe has arrived.
+    pub fn size(&self) -> (usize, usize) {
+        self.board.as_ref().map_or((0, 0), GameOfLife::size)
+    }
+
+    /// The generation on the board, once a keyframe has arrived.
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Row-major, as in [`GameOfLife::surface`].
+    pub fn cells(&self) -> &[bool] {
+        self.board.as_ref().map_or(&[], GameOfLife::surface)
+    }
+
+    /// Whether the server has hung up.
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The board as of the last message, once a keyframe has arrived.
+    pub fn board(&self) -> Option<GameOfLife> {
+        self.board.clone()
+    }
+
+    /// Reads what has arrived, without waiting for more, and applies it.
+    /// Returns whether the board changed.
+    pub fn poll(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 1 << 16];
+        while !self.closed {
+            match self.input.read(&mut chunk) {
+                Ok(0) => self.closed = true,
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        self.apply()
+    }
+
+    /// Applies each whole message in the buffer, keeping the rest.
+    fn apply(&mut self) -> io::Result<bool> {
+        let mut at = 0;
+        if !self.header {
+            if self.buffer.len() < HEADER {
+                return Ok(false);
+            }
+            if &self.buffer[..8] != MAGIC {
+                return Err(invalid("not a game of life stream"));
+            }
+            let version = u32::from_le_bytes(self.buffer[8..12].try_into().unwrap());
+            if version != VERSION {
+                return Err(invalid(format!("unsupported stream version {version}")));
+            }
+            (self.header, at) = (true, HEADER);
+        }
+        let mut changed = false;
+        while let Some(length) = message_length(&self.buffer[at..])? {
+            let (message, at_end) = (&self.buffer[at..at + length], at + length);
+            let generation = u64::from_le_bytes(message[1..9].try_into().unwrap());
+            let word = |i: usize| u32::from_le_bytes(message[i..i + 4].try_into().unwrap());
+            if message[0] == KEYFRAME {
+                let mut board = GameOfLife::new_empty((word(9) as usize, word(13) as usize));
+                let bits = message[17..].chunks_exact(8).flat_map(|word| {
+                    let word = u64::from_le_bytes(word.try_into().unwrap());
+                    (0..64).map(move |bit| word >> bit & 1 != 0)
+                });
+                board
+                    .surface_mut()
+                    .iter_mut()
+                    .zip(bits)
+                    .for_each(|(cell, bit)| *cell = bit);
+                self.board = Some(board);
+            } else {
+                let board = self
+                    .board
+                    .as_mut()
+                    .ok_or_else(|| invalid("a diff before any keyframe"))?;
+                let cells = (13..message.len())
+                    .step_by(4)
+                    .map(|i| word(i) as usize)
+                    .map(|i| match i < board.cell_count() {
+                        true => Ok((board.coords(i), !board.get(board.coords(i)))),
+                        false => Err(invalid("a cell off the board")),
+                    })
+                    .collect::<io::Result<_>>()?;
+                board.apply_edit(&Edit::Cells(cells));
+            }
+            (self.generation, changed, at) = (Some(generation), true, at_end);
+        }
+        self.buffer.drain(..at);
+        Ok(changed)
+    }
+}
+
+/// How long the message starting `bytes` is, if it has arrived whole.
+fn message_length(bytes: &[u8]) -> io::Result<Option<usize>> {
+    let word = |i: usize| {
+        bytes
+            .get(i..i + 4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+    };
+    let length = match bytes.first() {
+        None => return Ok(None),
+        Some(&KEYFRAME) => match (word(9), word(13)) {
+            (Some(w), Some(h)) => 17 + (w as usize * h as usize).div_ceil(64) * 8,
+            _ => return Ok(None),
+        },
+        Some(&DIFF) => match word(9) {
+            Some(count) => 13 + count as usize * 4,
+            None => return Ok(None),
+        },
+        Some(tag) => return Err(invalid(format!("unknown message {tag:#04x}"))),
+    };
+    Ok((bytes.len() >= length).then_some(length))
+}
+
+#[test]
+fn test_observers_converge_on_the_board() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::new_empty((40, 30));
+    game.fill(0.35, 3);
+    let mut server = Server::new();
+    let (ours, theirs) = UnixStream::pair().unwrap();
+    server.add(ours).unwrap();
+    theirs.set_nonblocking(true).unwrap();
+    let mut early = Feed::new(theirs);
+    // Nothing to see until the server has sent something.
+    assert!(!early.poll().unwrap() && early.board().is_none());
+    server.publish(&game);
+    assert!(early.poll().unwrap());
+    assert_eq!(early.board().unwrap().fingerprint(), game.fingerprint());
+    for _ in 0..10 {
+        game.step();
+        server.publish(&game);
+    }
+    // Joining mid-run starts from a keyframe and follows from there, edits
+    // between generations included.
+    let (ours, theirs) = UnixStream::pair().unwrap();
+    server.add(ours).unwrap();
+    theirs.set_nonblocking(true).unwrap();
+    let mut late = Feed::new(theirs);
+    for generation in 0..10 {
+        game.step();
+        if generation == 4 {
+            game.set(GridPos(0, 0), !game.get(GridPos(0, 0)));
+        }
+        server.publish(&game);
+        // Publishing an unchanged board sends nothing more.
+        server.publish(&game);
+    }
+    for feed in [&mut early, &mut late] {
+        feed.poll().unwrap();
+        assert_eq!(feed.generation(), Some(game.generation()));
+        assert_eq!(feed.board().unwrap().fingerprint(), game.fingerprint());
+        assert!(!feed.poll().unwrap());
+    }
+    assert_eq!((server.observers(), server.resyncs()), (2, 0));
+
+    // An observer that hangs up is dropped, and its feed sees the server go.
+    drop(late);
+    game.step();
+    server.publish(&game);
+    assert_eq!(server.observers(), 1);
+    drop(server);
+    early.poll().unwrap();
+    assert!(early.closed());
+    assert_eq!(early.board().unwrap().fingerprint(), game.fingerprint());
+}
+
+#[test]
+fn test_a_slow_observer_skips_to_a_keyframe() {
+    let mut game = GameOfLife::new_empty((128, 128));
+    game.fill(0.4, 9);
+    let mut server = Server::new().with_backlog(16 << 10);
+    let (ours, theirs) = UnixStream::pair().unwrap();
+    server.add(ours).unwrap();
+    theirs.set_nonblocking(true).unwrap();
+    let mut feed = Feed::new(theirs);
+    // Far more than the socket holds goes unread...
+    for _ in 0..150 {
+        game.step();
+        server.publish(&game);
+    }
+    assert!(server.resyncs() > 0);
+    // ...and what is queued stays near the backlog rather than growing.
+    assert!(server.observers[0].waiting() <= 16 << 10);
+    // Once it reads again it catches up with the board as it is.
+    for _ in 0..1000 {
+        feed.poll().unwrap();
+        server.publish(&game);
+        if feed.generation() == Some(game.generation()) {
+            break;
+        }
+    }
+    assert_eq!(feed.board().unwrap().fingerprint(), game.fingerprint());
+
+    let mut junk = Feed::new(&b"GOLFRAME\x01\0\0\0"[..]);
+    assert_eq!(junk.poll().unwrap_err().kind(), ErrorKind::InvalidData);
+    let mut early = Feed::new(&b"GOLWATCH\x01\0\0\0D\0\0\0\0\0\0\0\0\0\0\0\0"[..]);
+    assert!(early.poll().is_err());
+}
@@ -0,0 +1,499 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::Mutex,
+    time::Instant,
+};
+
+use crate::{CycleDetector, EdgeMode, GameOfLife, Rule, Settled};
+
+/// The longest oscillator period a soup is recognized as settling into.
+const MAX_PERIOD: usize = 32;
+
+/// The soups [`run_soups`] runs: one per seed, all alike otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoupConfig {
+    pub rule: Rule,
+    pub edges: EdgeMode,
+    pub size: (usize, usize),
+    /// How likely each cell is to start alive.
+    pub density: f64,
+    /// A soup still changing after this many generations is given up on.
+    pub generations: u64,
+    /// The first soup's seed; the rest count up from it.
+    pub seed: u64,
+    pub soups: usize,
+}
+
+/// How one soup ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoupOutcome {
+    pub seed: u64,
+    /// `None` if it was still changing at the generation cap.
+    pub settled: Option<Settled>,
+    /// The generation it settled at: the first of the cycle it fell into, or
+    /// the first with nothing alive. The cap if it never did.
+    pub lifespan: u64,
+    /// Live cells at the end.
+    pub population: usize,
+}
+
+/// Runs the soup `config` seeds with `seed`.
+pub fn run_soup(config: &SoupConfig, seed: u64) -> SoupOutcome {
+    let mut game = GameOfLife::new_empty(config.size);
+    game.set_rule(config.rule);
+    game.set_edge_mode(config.edges);
+    game.fill(config.density, seed);
+    let mut detector = CycleDetector::new(MAX_PERIOD);
+    let settled = crate::run_until_settled(&mut game, &mut detector, config.generations);
+    let lifespan = match settled {
+        Some(Settled::Cycle(period)) => game.generation() - period as u64,
+        _ => game.generation(),
+    };
+    SoupOutcome {
+        seed,
+        settled,
+        lifespan,
+        population: game.population(),
+    }
+}
+
+/// Runs every soup in `config` across `threads` threads, calling `progress`
+/// with how many are done as each finishes. The outcomes are in seed order
+/// however the soups were shared out.
+pub fn run_soups(
+    config: &SoupConfig,
+    threads: usize,
+    progress: &(dyn Fn(usize) + Sync),
+) -> Vec<SoupOutcome> {
+    let (next, done) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let outcomes = Mutex::new(Vec::with_capacity(config.soups));
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, config.soups.max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= config.soups {
+                    break;
+                }
+                let outcome = run_soup(config, config.seed.wrapping_add(i as u64));
+                outcomes.lock().unwrap().push(outcome);
+                progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+            });
+        }
+    });
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|outcome| outcome.seed.wrapping_sub(config.seed));
+    outcomes
+}
+
+/// Runs the soup `config` seeds with `seed` through a burn-in of
+/// `config.generations`, returning the board it left and how lively it was:
+/// the variance of its population over the burn-in. A soup that settled
+/// along the way scores nothing, however much it changed before it did.
+pub fn burn_in(config: &SoupConfig, seed: u64) -> (GameOfLife, f64) {
+    let mut game = GameOfLife::new_empty(config.size);
+    game.set_rule(config.rule);
+    game.set_edge_mode(config.edges);
+    game.fill(config.density, seed);
+    let mut detector = CycleDetector::new(MAX_PERIOD);
+    let mut populations = Vec::with_capacity(config.generations as usize + 1);
+    let settled =
+        crate::run_until_settled_observing(&mut game, &mut detector, config.generations, |game| {
+            populations.push(game.population() as f64)
+        });
+    if settled.is_some() {
+        return (game, 0.0);
+    }
+    let mean = populations.iter().sum::<f64>() / populations.len() as f64;
+    let variance = populations.iter().map(|p| (p - mean).powi(2)).sum::<f64>();
+    (game, variance / populations.len() as f64)
+}
+
+/// Burns in every soup in `config` across `threads` threads, returning the
+/// liveliest one's seed, board after the burn-in and score. Lower seeds win
+/// ties. `None` if `config` has no soups.
+pub fn liveliest(config: &SoupConfig, threads: usize) -> Option<(u64, GameOfLife, f64)> {
+    let seed = |i: usize| config.seed.wrapping_add(i as u64);
+    let next = AtomicUsize::new(0);
+    // The soup's index rather than its seed, so that seeds wrapping around
+    // still go in order.
+    let best = Mutex::new(None::<(usize, GameOfLife, f64)>);
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, config.soups.max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= config.soups {
+                    break;
+                }
+                let (game, score) = burn_in(config, seed(i));
+                let mut best = best.lock().unwrap();
+                let beaten = |&(j, _, best): &(usize, GameOfLife, f64)| {
+                    score > best || (score == best && i < j)
+                };
+                if best.as_ref().is_none_or(beaten) {
+                    *best = Some((i, game, score));
+                }
+            });
+        }
+    });
+    let best = best.into_inner().unwrap();
+    best.map(|(i, game, score)| (seed(i), game, score))
+}
+
+/// How interesting a soup was, from its population each generation up to
+/// the one it settled at, or the cap: how long it lasted, weighted by how
+/// widely its population swung, its standard deviation, over that time. A
+/// soup that settled at once scores nothing, and one that lasted without
+/// its population moving scores only its lifespan.
+pub fn interest(populations: &[usize], lifespan: u64) -> f64 {
+    if populations.is_empty() {
+        return 0.0;
+    }
+    let count = populations.len() as f64;
+    let mean = populations.iter().sum::<usize>() as f64 / count;
+    let variance = populations
+        .iter()
+        .map(|&p| (p as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    lifespan as f64 * (1.0 + variance.sqrt())
+}
+
+/// Runs the soup `config` seeds with `seed` until it settles or reaches the
+/// cap, returning its [`interest`].
+pub fn score_soup(config: &SoupConfig, seed: u64) -> f64 {
+    let mut game = GameOfLife::new_empty(config.size);
+    game.set_rule(config.rule);
+    game.set_edge_mode(config.edges);
+    game.fill(config.density, seed);
+    let mut detector = CycleDetector::new(MAX_PERIOD);
+    let mut populations = Vec::new();
+    let settled =
+        crate::run_until_settled_observing(&mut game, &mut detector, config.generations, |game| {
+            populations.push(game.population())
+        });
+    let lifespan = match settled {
+        Some(Settled::Cycle(period)) => game.generation() - period as u64,
+        _ => game.generation(),
+    };
+    // The generations it spent going round its cycle add nothing.
+    populations.truncate(lifespan as usize + 1);
+    interest(&populations, lifespan)
+}
+
+/// Scores the soups in `config` across `threads` threads until all are
+/// done, `deadline` passes or `cancel` is set, and returns the seed and
+/// [`interest`] of the best so far, lower seeds winning ties. `progress` is
+/// called with how many are done as each finishes. A soup already running
+/// is finished however late it makes the search. `None` if no soup was.
+pub fn search(
+    config: &SoupConfig,
+    threads: usize,
+    deadline: Instant,
+    cancel: &AtomicBool,
+    progress: &(dyn Fn(usize) + Sync),
+) -> Option<(u64, f64)> {
+    let (next, done) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let best = Mutex::new(None::<(usize, f64)>);
+    let stop = || cancel.load(Ordering::Relaxed) || Instant::now() >= deadline;
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, config.soups.max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= config.soups || stop() {
+                    break;
+                }
+                let score = score_soup(config, config.seed.wrapping_add(i as u64));
+                let mut best = best.lock().unwrap();
+                let beaten = |&(j, best): &(usize, f64)| score > best || (score == best && i < j);
+                if best.as_ref().is_none_or(beaten) {
+                    *best = Some((i, score));
+                }
+                drop(best);
+                progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+            });
+        }
+    });
+    let best = best.into_inner().unwrap();
+    best.map(|(i, score)| (config.seed.wrapping_add(i as u64), score))
+}
+
+/// The `p`th percentile, from 0 to 100, of `sorted`, interpolating between
+/// the two nearest values. `None` if there are none.
+pub fn percentile(sorted: &[u64], p: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p.clamp(0.0, 100.0) / 100.0 * last as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    let (a, b) = (sorted[below] as f64, sorted[above] as f64);
+    Some(a + (b - a) * (rank - below as f64))
+}
+
+/// The spread of some counts over a set of soups.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spread {
+    pub mean: f64,
+    pub min: u64,
+    pub p10: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: u64,
+}
+
+impl Spread {
+    /// The spread of `values`, or `None` if there are none.
+    pub fn of(mut values: Vec<u64>) -> Option<Self> {
+        values.sort_unstable();
+        let mean = values.iter().sum::<u64>() as f64 / values.len().max(1) as f64;
+        Some(Self {
+            mean,
+            min: *values.first()?,
+            p10: percentile(&values, 10.0)?,
+            median: percentile(&values, 50.0)?,
+            p90: percentile(&values, 90.0)?,
+            max: *values.last()?,
+        })
+    }
+
+    fn columns(&self) -> [String; 6] {
+        [
+            format!("{:.1}", self.mean),
+            self.min.to_string(),
+            format!("{:.1}", self.p10),
+            format!("{:.1}", self.median),
+            format!("{:.1}", self.p90),
+            self.max.to_string(),
+        ]
+    }
+
+    fn json(&self) -> String {
+        format!(
+            concat!(
+                r#"{{"mean": {:.3}, "min": {}, "p10": {:.3}, "#,
+                r#""median": {:.3}, "p90": {:.3}, "max": {}}}"#
+            ),
+            self.mean, self.min, self.p10, self.median, self.p90, self.max
+        )
+    }
+}
+
+/// What a set of soups under one rule came to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoupStats {
+    pub soups: usize,
+    /// The share of soups in which every cell died.
+    pub died: f64,
+    /// The share still changing at the generation cap.
+    pub active: f64,
+    /// How long the soups that settled took to, dying out included.
+    pub lifespan: Option<Spread>,
+    /// How many cells every soup ended with.
+    pub population: Option<Spread>,
+}
+
+impl SoupStats {
+    pub fn of(outcomes: &[SoupOutcome]) -> Self {
+        let share = |count: usize| count as f64 / outcomes.len().max(1) as f64;
+        let settled = outcomes.iter().filter(|outcome| outcome.settled.is_some());
+        Self {
+            soups: outcomes.len(),
+            died: share(
+                outcomes
+                    .iter()
+                    .filter(|o| o.settled == Some(Settled::Died))
+                    .count(),
+            ),
+            active: share(outcomes.iter().filter(|o| o.settled.is_none()).count()),
+            lifespan: Spread::of(settled.map(|outcome| outcome.lifespan).collect()),
+            population: Spread::of(outcomes.iter().map(|o| o.population as u64).collect()),
+        }
+    }
+
+    /// A table of the statistics, for people.
+    pub fn table(&self) -> String {
+        let mut lines = vec![
+            format!("soups         {}", self.soups),
+            format!("died out      {:.1}%", self.died * 100.0),
+            format!("still active  {:.1}%", self.active * 100.0),
+            String::new(),
+        ];
+        let header = ["", "mean", "min", "p10", "median", "p90", "max"].map(String::from);
+        let mut rows = vec![header];
+        for (name, spread) in [("lifespan", self.lifespan), ("population", self.population)] {
+            let [a, b, c, d, e, f] = match spread {
+                Some(spread) => spread.columns(),
+                None => std::array::from_fn(|_| "-".to_string()),
+            };
+            rows.push([name.to_string(), a, b, c, d, e, f]);
+        }
+        for row in rows {
+            let (name, columns) = row.split_first().unwrap();
+            let columns = columns.iter().map(|column| format!("{column:>9}"));
+            let columns = columns.collect::<String>();
+            lines.push(format!("{name:<12}{columns}").trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+
+    /// The statistics as a JSON object, for scripts.
+    pub fn json(&self) -> String {
+        let spread = |spread: Option<Spread>| spread.map_or("null".to_string(), |s| s.json());
+        format!(
+            r#"{{"soups": {}, "died": {:.4}, "active": {:.4}, "lifespan": {}, "population": {}}}"#,
+            self.soups,
+            self.died,
+            self.active,
+            spread(self.lifespan),
+            spread(self.population)
+        )
+    }
+}
+
+#[test]
+fn test_percentiles_interpolate() {
+    assert_eq!(percentile(&[], 50.0), None);
+    assert_eq!(percentile(&[7], 90.0), Some(7.0));
+    let values = [1, 2, 3, 4, 10];
+    assert_eq!(percentile(&values, 0.0), Some(1.0));
+    assert_eq!(percentile(&values, 50.0), Some(3.0));
+    assert_eq!(percentile(&values, 100.0), Some(10.0));
+    // A tenth of the way along four gaps is 0.4 of the way from 1 to 2.
+    let near = |p, expected: f64| (percentile(&values, p).unwrap() - expected).abs() < 1e-9;
+    assert!(near(10.0, 1.4) && near(90.0, 7.6));
+    assert_eq!(percentile(&[1, 2], 50.0), Some(1.5));
+}
+
+#[test]
+fn test_soup_stats_are_stable() {
+    let config = SoupConfig {
+        rule: Rule::default(),
+        edges: EdgeMode::Toroidal,
+        size: (16, 16),
+        density: 0.35,
+        generations: 300,
+        seed: 10,
+        soups: 12,
+    };
+    let ticks = AtomicUsize::new(0);
+    let outcomes = run_soups(&config, 4, &|_| {
+        ticks.fetch_add(1, Ordering::Relaxed);
+    });
+    assert_eq!(ticks.into_inner(), 12);
+    // However many threads share them out, the soups come out the same and
+    // in seed order.
+    assert_eq!(outcomes, run_soups(&config, 1, &|_| {}));
+    let seeds = outcomes.iter().map(|o| o.seed).collect::<Vec<_>>();
+    assert_eq!(seeds, (10..22).collect::<Vec<_>>());
+    assert_eq!(outcomes[3], run_soup(&config, 13));
+
+    let stats = SoupStats::of(&outcomes);
+    let count = |f: fn(&SoupOutcome) -> bool| outcomes.iter().filter(|o| f(o)).count();
+    assert_eq!(
+        stats.died,
+        count(|o| o.settled == Some(Settled::Died)) as f64 / 12.0
+    );
+    assert_eq!(stats.active, count(|o| o.settled.is_none()) as f64 / 12.0);
+    let population = stats.population.unwrap();
+    let max = outcomes.iter().map(|o| o.population as u64).max().unwrap();
+    assert_eq!(population.max, max);
+    assert!(population.min as f64 <= population.median && population.median <= max as f64);
+    assert!(stats.table().starts_with("soups         12\n"));
+    assert!(stats.json().starts_with(r#"{"soups": 12, "died": "#));
+
+    // Nothing settles in one generation, so there is no lifespan to speak of.
+    let stats = SoupStats::of(&run_soups(
+        &SoupConfig {
+            generations: 1,
+            ..config
+        },
+        2,
+        &|_| {},
+    ));
+    assert_eq!((stats.active, stats.lifespan), (1.0, None));
+    assert!(stats.table().contains("lifespan            -        -"));
+    assert!(stats.json().contains(r#""lifespan": null"#));
+}
+
+#[test]
+fn test_liveliest_soup_wins() {
+    let config = SoupConfig {
+        rule: Rule::default(),
+        edges: EdgeMode::Toroidal,
+        size: (24, 24),
+        density: 0.35,
+        generations: 40,
+        seed: 5,
+        soups: 6,
+    };
+    let scores = (0..6)
+        .map(|i| burn_in(&config, 5 + i).1)
+        .collect::<Vec<_>>();
+    let (seed, game, score) = liveliest(&config, 3).unwrap();
+    // However the soups were shared out, the first of the best wins.
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    assert_eq!(score, best);
+    assert_eq!(
+        seed,
+        5 + scores.iter().position(|&s| s == best).unwrap() as u64
+    );
+    assert_eq!(game.fingerprint(), burn_in(&config, seed).0.fingerprint());
+    assert!(liveliest(
+        &SoupConfig {
+            soups: 0,
+            ..config.clone()
+        },
+        2
+    )
+    .is_none());
+
+    assert!(score > 0.0);
+    // A soup that has died by the end scores nothing.
+    let sparse = SoupConfig {
+        density: 0.0,
+        ..config
+    };
+    assert_eq!(burn_in(&sparse, 1).1, 0.0);
+}
+
+#[test]
+fn test_interest_favors_long_and_turbulent_soups() {
+    assert_eq!(interest(&[], 0), 0.0);
+    // Settling at once, however crowded, is as dull as dying.
+    assert_eq!(interest(&[40], 0), 0.0);
+    // A steady population counts only for how long it lasted.
+    assert_eq!(interest(&[5; 11], 10), 10.0);
+    // Swinging between 10 and 30 is a standard deviation of 10.
+    let swinging = [10, 30].repeat(5);
+    assert_eq!(interest(&swinging, 9), 99.0);
+    // Lasting longer beats swinging wider over a short life, but at a
+    // lifespan alike the wider swing wins.
+    let brief = [0, 100].repeat(2);
+    assert!(interest(&brief, 3) < interest(&[5, 6].repeat(200), 399));
+    assert!(interest(&[5, 6].repeat(2), 3) < interest(&brief, 3));
+
+    let config = SoupConfig {
+        rule: Rule::default(),
+        edges: EdgeMode::Toroidal,
+        size: (16, 16),
+        density: 0.35,
+        generations: 200,
+        seed: 3,
+        soups: 10,
+    };
+    let cancel = AtomicBool::new(false);
+    let far = Instant::now() + std::time::Duration::from_secs(600);
+    let ticks = AtomicUsize::new(0);
+    let found = search(&config, 3, far, &cancel, &|_| {
+        ticks.fetch_add(1, Ordering::Relaxed);
+    });
+    assert_eq!(ticks.into_inner(), 10);
+    let best = (3..13)
+        .map(|seed| score_soup(&config, seed))
+        .fold(0.0, f64::max);
+    let (seed, score) = found.unwrap();
+    assert_eq!((score, score_soup(&config, seed)), (best, best));
+    // However many threads share them out, the same soup wins.
+    assert_eq!(search(&config, 1, far, &cancel, &|_| {}), found);
+    // Nothing is tried once the search is cancelled or out of time.
+    assert_eq!(search(&config, 2, Instant::now(), &cancel, &|_| {}), None);
+    cancel.store(true, Ordering::Relaxed);
+    assert_eq!(search(&config, 2, far, &cancel, &|_| {}), None);
+}
@@ -0,0 +1,624 @@
+use crate::{game::reflect, EdgeMode, GameOfLife, Rule};
+
+/// Where a [`GameOfLife`](crate::GameOfLife) keeps its cells: the current
+/// generation, and room for the next one while a step is being worked out.
+pub trait GridStorage: Clone {
+    /// A grid of `size` dead cells. Both dimensions are at least one.
+    fn new(size: (usize, usize)) -> Self;
+
+    /// Width and height in cells.
+    fn size(&self) -> (usize, usize);
+
+    fn get(&self, x: usize, y: usize) -> bool;
+
+    fn set(&mut self, x: usize, y: usize, value: bool);
+
+    /// Copies row `y` into `row`, which is exactly as long as the grid is
+    /// wide. Stepping reads the board a row at a time through this, so it is
+    /// worth making faster than a [`get`](Self::get) per cell.
+    fn read_row(&self, y: usize, row: &mut [bool]) {
+        for (x, cell) in row.iter_mut().enumerate() {
+            *cell = self.get(x, y);
+        }
+    }
+
+    /// Writes row `y` of the next generation, which stays out of sight until
+    /// [`swap_buffers`](Self::swap_buffers). Every row is written before each
+    /// swap.
+    fn set_next_row(&mut self, y: usize, row: &[bool]);
+
+    /// Makes the generation written by [`set_next_row`](Self::set_next_row)
+    /// the current one.
+    fn swap_buffers(&mut self);
+
+    /// Steps the grid a generation under `rule` and `edges` by itself, for
+    /// storage that can do better than a row at a time, and returns the new
+    /// population. `None`, as by default, leaves the step to the rows.
+    fn step_rule(&mut self, rule: Rule, edges: EdgeMode) -> Option<usize> {
+        let _ = (rule, edges);
+        None
+    }
+}
+
+/// A `bool` per cell, row-major: the fastest to read and write one cell at a
+/// time, and the default.
+#[derive(Debug, Clone)]
+pub struct DenseBool {
+    size: (usize, usize),
+    cells: Vec<bool>,
+    next: Vec<bool>,
+}
+
+impl DenseBool {
+    /// Every cell, row-major.
+    pub fn as_slice(&self) -> &[bool] {
+        &self.cells
+    }
+
+    /// Every cell, row-major, to change in place.
+    pub fn as_mut_slice(&mut self) -> &mut [bool] {
+        &mut self.cells
+    }
+}
+
+impl GridStorage for DenseBool {
+    fn new(size: (usize, usize)) -> Self {
+        Self {
+            size,
+            cells: vec![false; size.0 * size.1],
+            next: vec![false; size.0 * size.1],
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.cells[x + y * self.size.0]
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        self.cells[x + y * self.size.0] = value;
+    }
+
+    fn read_row(&self, y: usize, row: &mut [bool]) {
+        let w = self.size.0;
+        row.copy_from_slice(&self.cells[y * w..(y + 1) * w]);
+    }
+
+    fn set_next_row(&mut self, y: usize, row: &[bool]) {
+        let w = self.size.0;
+        self.next[y * w..(y + 1) * w].copy_from_slice(row);
+    }
+
+    fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.cells, &mut self.next);
+    }
+}
+
+/// A bit per cell, each row starting on a fresh 64-bit word: an eighth of the
+/// memory of [`DenseBool`], for boards too big to keep a byte per cell.
+#[derive(Debug, Clone)]
+pub struct PackedWords {
+    size: (usize, usize),
+    /// Words per row.
+    stride: usize,
+    words: Vec<u64>,
+    next: Vec<u64>,
+}
+
+impl PackedWords {
+    /// The word holding the cell at `(x, y)` and the cell's bit in it.
+    fn locate(&self, x: usize, y: usize) -> (usize, u64) {
+        (y * self.stride + x / 64, 1 << (x % 64))
+    }
+}
+
+impl GridStorage for PackedWords {
+    fn new(size: (usize, usize)) -> Self {
+        let stride = size.0.div_ceil(64);
+        Self {
+            size,
+            stride,
+            words: vec![0; stride * size.1],
+            next: vec![0; stride * size.1],
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let (word, bit) = self.locate(x, y);
+        self.words[word] & bit != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        let (word, bit) = self.locate(x, y);
+        if value {
+            self.words[word] |= bit;
+        } else {
+            self.words[word] &= !bit;
+        }
+    }
+
+    fn read_row(&self, y: usize, row: &mut [bool]) {
+        let words = &self.words[y * self.stride..(y + 1) * self.stride];
+        for (cells, &word) in row.chunks_mut(64).zip(words) {
+            for (bit, cell) in cells.iter_mut().enumerate() {
+                *cell = word >> bit & 1 != 0;
+            }
+        }
+    }
+
+    fn set_next_row(&mut self, y: usize, row: &[bool]) {
+        let words = &mut self.next[y * self.stride..(y + 1) * self.stride];
+        for (word, cells) in words.iter_mut().zip(row.chunks(64)) {
+            *word = cells
+                .iter()
+                .enumerate()
+                .fold(0, |word, (bit, &cell)| word | (cell as u64) << bit);
+        }
+    }
+
+    fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.words, &mut self.next);
+    }
+}
+
+/// Cells across and down a tile of [`ChunkedTiles`].
+pub const TILE: usize = 64;
+
+/// A tile's rows, a bit per cell with the leftmost in the lowest bit.
+type Tile = [u64; TILE];
+
+fn population(tile: &Tile) -> usize {
+    tile.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// The board in [`TILE`]-square tiles of bits, each flagged when it last
+/// changed. A step works out only the flagged tiles and those touching
+/// them, since no rule reaches further than the next tile; the rest stayed
+/// as it was and stays so. A few gliders on a huge board are stepped for a
+/// few tiles' worth of work, and each tile is worked out from memory close
+/// together rather than from rows far apart.
+///
+/// Boards are stepped a row at a time, as other storage is, for rules that
+/// bring cells to life from nothing, when a torus has a last tile narrower
+/// than the rule's reach, and for transitions other than a [`Rule`]'s.
+/// Every tile is flagged after such a step.
+#[derive(Debug, Clone)]
+pub struct ChunkedTiles {
+    size: (usize, usize),
+    /// Tiles across and down; those on the right and bottom may overhang
+    /// the board, their spare bits dead.
+    tiles: (usize, usize),
+    cells: Vec<Tile>,
+    /// The next generation of a step taken a row at a time; empty until one
+    /// is.
+    next: Vec<Tile>,
+    /// Which tiles changed in the last step or were edited since.
+    active: Vec<bool>,
+    /// The rule and edges of the last step, if there was one since the
+    /// board was set up or stepped by rows. Another rule or edges starts
+    /// from every tile again.
+    stepped: Option<(Rule, EdgeMode)>,
+    population: usize,
+}
+
+impl ChunkedTiles {
+    /// The tile holding the cell at `(x, y)`, its row in the tile and the
+    /// cell's bit in the row.
+    fn locate(&self, x: usize, y: usize) -> (usize, usize, u64) {
+        (
+            y / TILE * self.tiles.0 + x / TILE,
+            y % TILE,
+            1 << (x % TILE),
+        )
+    }
+
+    /// How many tiles the next step will work out: those flagged and their
+    /// neighbors.
+    pub fn active_tiles(&self) -> usize {
+        self.active.iter().filter(|&&active| active).count()
+    }
+
+    /// Works out tile `tile` of the next generation. `spans` holds how far
+    /// across each row of the neighborhood reaches, by `dy + reach`, as in
+    /// [`GameOfLife::neighbor_counts_into`](crate::GameOfLife::neighbor_counts_into).
+    fn step_tile(
+        &self,
+        tile: usize,
+        rule: &Rule,
+        spans: &[Option<usize>],
+        edges: EdgeMode,
+    ) -> Tile {
+        let (w, h) = self.size;
+        let alive = |x: isize, y: isize| {
+            let (x, y) = match edges {
+                EdgeMode::Bounded if x < 0 || y < 0 || x >= w as isize || y >= h as isize => {
+                    return false;
+                }
+                EdgeMode::Bounded => (x as usize, y as usize),
+                EdgeMode::Toroidal => (
+                    x.rem_euclid(w as isize) as usize,
+                    y.rem_euclid(h as isize) as usize,
+                ),
+                EdgeMode::Mirror => (reflect(x, w), reflect(y, h)),
+            };
+            let (tile, row, bit) = self.locate(x, y);
+            self.cells[tile][row] & bit != 0
+        };
+        let (left, top) = (tile % self.tiles.0 * TILE, tile / self.tiles.0 * TILE);
+        // Running totals along each row of the tile and the margin around
+        // it, so that every run of a neighborhood row is one subtraction.
+        let reach = spans.len() / 2;
+        let side = TILE + 2 * reach;
+        let mut totals = vec![0u32; side * (side + 1)];
+        for j in 0..side {
+            let y = (top + j) as isize - reach as isize;
+            let row = &mut totals[j * (side + 1)..(j + 1) * (side + 1)];
+            for i in 0..side {
+                row[i + 1] = row[i] + alive((left + i) as isize - reach as isize, y) as u32;
+            }
+        }
+        let mut next = [0; TILE];
+        for (j, word) in next.iter_mut().enumerate().take(h - top) {
+            for i in 0..TILE.min(w - left) {
+                let cell = self.cells[tile][j] >> i & 1 != 0;
+                let mut neighbors = 0;
+                for (dy, &span) in spans.iter().enumerate() {
+                    let Some(k) = span else {
+                        continue;
+                    };
+                    let row = &totals[(j + dy) * (side + 1)..];
+                    neighbors += row[i + reach + k + 1] - row[i + reach - k];
+                }
+                // A cell isn't its own neighbor.
+                let neighbors = neighbors as usize - cell as usize;
+                *word |= (rule.next(cell, neighbors) as u64) << i;
+            }
+        }
+        next
+    }
+}
+
+impl GridStorage for ChunkedTiles {
+    fn new(size: (usize, usize)) -> Self {
+        let tiles = (size.0.div_ceil(TILE), size.1.div_ceil(TILE));
+        Self {
+            size,
+            tiles,
+            cells: vec![[0; TILE]; tiles.0 * tiles.1],
+            next: Vec::new(),
+            active: vec![false; tiles.0 * tiles.1],
+            stepped: None,
+            population: 0,
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let (tile, row, bit) = self.locate(x, y);
+        self.cells[tile][row] & bit != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        let (tile, row, bit) = self.locate(x, y);
+        let word = &mut self.cells[tile][row];
+        if (*word & bit != 0) == value {
+            return;
+        }
+        *word ^= bit;
+        self.active[tile] = true;
+        match value {
+            true => self.population += 1,
+            false => self.population -= 1,
+        }
+    }
+
+    fn read_row(&self, y: usize, row: &mut [bool]) {
+        let tiles = &self.cells[y / TILE * self.tiles.0..][..self.tiles.0];
+        for (cells, tile) in row.chunks_mut(TILE).zip(tiles) {
+            let word = tile[y % TILE];
+            for (bit, cell) in cells.iter_mut().enumerate() {
+                *cell = word >> bit & 1 != 0;
+            }
+        }
+    }
+
+    fn set_next_row(&mut self, y: usize, row: &[bool]) {
+        if self.next.len() != self.cells.len() {
+            self.next = vec![[0; TILE]; self.cells.len()];
+        }
+        let tiles = &mut self.next[y / TILE * self.tiles.0..][..self.tiles.0];
+        for (tile, cells) in tiles.iter_mut().zip(row.chunks(TILE)) {
+            tile[y % TILE] = cells
+                .iter()
+                .enumerate()
+                .fold(0, |word, (bit, &cell)| word | (cell as u64) << bit);
+        }
+    }
+
+    fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.population = self.cells.iter().map(population).sum();
+        // Still under one transition says nothing about the next.
+        self.active.fill(true);
+        self.stepped = None;
+    }
+
+    fn step_rule(&mut self, rule: Rule, edges: EdgeMode) -> Option<usize> {
+        let offsets = rule.offsets();
+        let reach = offsets
+            .iter()
+            .map(|&(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()));
+        let reach = reach.max().unwrap_or(0);
+        let mut spans = vec![None; 2 * reach + 1];
+        for &(dx, dy) in &offsets {
+            let span: &mut Option<usize> = &mut spans[(reach as isize + dy) as usize];
+            *span = Some(span.unwrap_or(0).max(dx.unsigned_abs()));
+        }
+        // A torus wraps from its last tile into its first, and across it
+        // into the second when it is narrower than the reach.
+        let narrow = |cells: usize, tiles: usize| {
+            tiles > 1 && !cells.is_multiple_of(TILE) && cells % TILE < reach
+        };
+        let (tw, th) = self.tiles;
+        if rule.next(false, 0)
+            || edges == EdgeMode::Toroidal && (narrow(self.size.0, tw) || narrow(self.size.1, th))
+        {
+            return None;
+        }
+        // Cells left alone since they were set can only have been stepped
+        // under another rule or edges if there were other steps.
+        if self.stepped.is_some_and(|stepped| stepped != (rule, edges)) {
+            self.active.fill(true);
+        }
+        self.stepped = Some((rule, edges));
+        // The flagged tiles and their neighbors, across a torus's edges.
+        let mut woken = vec![false; self.active.len()];
+        for (tile, _) in self.active.iter().enumerate().filter(|(_, &active)| active) {
+            let (tx, ty) = ((tile % tw) as isize, (tile / tw) as isize);
+            for (dx, dy) in (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))) {
+                let (x, y) = match edges {
+                    EdgeMode::Toroidal => (
+                        (tx + dx).rem_euclid(tw as isize),
+                        (ty + dy).rem_euclid(th as isize),
+                    ),
+                    _ => (tx + dx, ty + dy),
+                };
+                if (0..tw as isize).contains(&x) && (0..th as isize).contains(&y) {
+                    woken[y as usize * tw + x as usize] = true;
+                }
+            }
+        }
+        let stepped = (0..woken.len())
+            .filter(|&tile| woken[tile])
+            .map(|tile| (tile, self.step_tile(tile, &rule, &spans, edges)))
+            .collect::<Vec<_>>();
+        self.active.fill(false);
+        for (tile, next) in stepped {
+            if next != self.cells[tile] {
+                self.population += population(&next);
+                self.population -= population(&self.cells[tile]);
+                self.cells[tile] = next;
+                self.active[tile] = true;
+            }
+        }
+        Some(self.population)
+    }
+}
+
+/// A `bool` per cell, column-major: a column's cells sit together, so a
+/// board far taller than it is wide is stepped down its columns, in the
+/// order its memory runs, rather than across rows a few cells long. A
+/// column-major board is laid out just as its transpose would be row-major,
+/// and since no neighborhood tells across from down, it is stepped as that
+/// transpose.
+///
+/// The board's own [`index`](crate::GameOfLife::index) and
+/// [`coords`](crate::GameOfLife::coords) stay row-major, as they name cells
+/// of what is shown, whatever the storage.
+#[derive(Clone)]
+pub struct ColumnMajor {
+    size: (usize, usize),
+    /// The transpose, row-major.
+    transposed: GameOfLife,
+    /// The next generation when it is written a row at a time, column-major
+    /// too. Empty until it is first needed.
+    next: Vec<bool>,
+}
+
+impl ColumnMajor {
+    /// Every cell, column-major: the cell at `(x, y)` is at `y + x * height`.
+    pub fn as_slice(&self) -> &[bool] {
+        self.transposed.surface()
+    }
+}
+
+impl GridStorage for ColumnMajor {
+    fn new(size: (usize, usize)) -> Self {
+        Self {
+            size,
+            transposed: GameOfLife::new_empty((size.1, size.0)),
+            next: Vec::new(),
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.transposed.surface()[y + x * self.size.1]
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        self.transposed.surface_mut()[y + x * self.size.1] = value;
+    }
+
+    fn read_row(&self, y: usize, row: &mut [bool]) {
+        let columns = self.transposed.surface().chunks(self.size.1);
+        for (cell, column) in row.iter_mut().zip(columns) {
+            *cell = column[y];
+        }
+    }
+
+    fn set_next_row(&mut self, y: usize, row: &[bool]) {
+        let h = self.size.1;
+        self.next.resize(self.size.0 * h, false);
+        for (column, &cell) in self.next.chunks_mut(h).zip(row) {
+            column[y] = cell;
+        }
+    }
+
+    fn swap_buffers(&mut self) {
+        self.transposed
+            .surface_mut()
+            .swap_with_slice(&mut self.next);
+    }
+
+    fn step_rule(&mut self, rule: Rule, edges: EdgeMode) -> Option<usize> {
+        if self.transposed.rule() != rule {
+            self.transposed.set_rule(rule);
+        }
+        self.transposed.set_edge_mode(edges);
+        Some(self.transposed.tick())
+    }
+}
+
+#[test]
+fn test_storages_agree() {
+    // Wide enough for rows to span words, with a partial last word.
+    let size = (70, 3);
+    let mut dense = DenseBool::new(size);
+    let mut packed = PackedWords::new(size);
+    for (x, y) in [(0, 0), (63, 0), (64, 1), (69, 2), (5, 2)] {
+        dense.set(x, y, true);
+        packed.set(x, y, true);
+    }
+    packed.set(64, 1, false);
+    dense.set(64, 1, false);
+    let mut rows = ([false; 70], [false; 70]);
+    for y in 0..3 {
+        dense.read_row(y, &mut rows.0);
+        packed.read_row(y, &mut rows.1);
+        assert_eq!(rows.0, rows.1);
+        assert!((0..70).all(|x| dense.get(x, y) == packed.get(x, y)));
+    }
+    assert!(packed.get(63, 0) && !packed.get(64, 0) && !packed.get(64, 1));
+
+    // The next generation only shows once the buffers are swapped.
+    let mut row = [false; 70];
+    row[66] = true;
+    for y in 0..3 {
+        packed.set_next_row(y, &row);
+    }
+    assert!(!packed.get(66, 1));
+    packed.swap_buffers();
+    assert!((0..3).all(|y| packed.get(66, y) && !packed.get(0, y)));
+}
+
+#[test]
+fn test_chunked_tiles_step_like_dense() {
+    use crate::GridPos;
+
+    let rules = [
+        "B3/S23",
+        "B36/S23",
+        "B2/S",
+        "B0/S8",
+        "R3,C0,M0,S2..5,B4..6,NM",
+    ];
+    let edges = [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror];
+    // Tiles that overhang the board, a board of one tile and one under the
+    // reach of the widest rule past a tile, which a torus steps by rows.
+    for size in [(130, 70), (20, 9), (129, 66)] {
+        for (i, edges) in edges.into_iter().enumerate() {
+            for rule in rules {
+                let mut dense = GameOfLife::new_empty(size);
+                dense.fill(0.3, i as u64);
+                dense.set_rule(rule.parse().unwrap());
+                dense.set_edge_mode(edges);
+                let mut chunked = dense.to_storage::<ChunkedTiles>();
+                for generation in 0..8 {
+                    assert_eq!(chunked.tick(), dense.tick(), "{size:?} {edges:?} {rule}");
+                    // Edits wake the tiles they are in.
+                    if generation == 5 {
+                        for (x, y) in [(0, 0), (size.0 - 1, size.1 / 2), (64, 64)] {
+                            let (x, y) = (x.min(size.0 - 1), y.min(size.1 - 1));
+                            dense.set(GridPos(x, y), true);
+                            chunked.set(GridPos(x, y), true);
+                        }
+                    }
+                }
+                let back = chunked.to_storage::<DenseBool>();
+                assert_eq!(back.surface(), dense.surface(), "{size:?} {edges:?} {rule}");
+                assert_eq!(chunked.population(), dense.population());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_chunked_tiles_leave_quiet_tiles_alone() {
+    use crate::{GridPos, Pattern};
+
+    let mut game = GameOfLife::<ChunkedTiles>::empty((512, 512));
+    let glider = Pattern::named("glider").unwrap();
+    for origin in [GridPos(10, 10), GridPos(250, 150), GridPos(400, 400)] {
+        game.insert_pattern(&glider, origin);
+    }
+    // A block sits still and so drops out after a step.
+    game.insert_pattern(&Pattern::named("block").unwrap(), GridPos(350, 50));
+    assert_eq!(game.storage().active_tiles(), 4);
+    for _ in 0..40 {
+        assert_eq!(game.tick(), 19);
+        assert!(
+            game.storage().active_tiles() <= 6,
+            "{}",
+            game.storage().active_tiles()
+        );
+    }
+    // A new rule starts from every tile again, but they soon settle.
+    game.set_rule("B36/S23".parse().unwrap());
+    game.step();
+    assert_eq!(game.storage().active_tiles(), 3);
+}
+
+#[test]
+fn test_column_major_steps_like_dense() {
+    let rules = ["B3/S23", "B36/S23", "B0/S8", "R2,C0,M0,S3..6,B4..5,NN"];
+    let edges = [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror];
+    for size in [(40, 7), (7, 40), (1, 9)] {
+        for (i, edges) in edges.into_iter().enumerate() {
+            for rule in rules {
+                let mut dense = GameOfLife::new_empty(size);
+                dense.fill(0.4, i as u64);
+                dense.set_rule(rule.parse().unwrap());
+                dense.set_edge_mode(edges);
+                let mut columns = dense.to_storage::<ColumnMajor>();
+                for _ in 0..6 {
+                    assert_eq!(columns.tick(), dense.tick(), "{size:?} {edges:?} {rule}");
+                }
+                // Transitions other than the rule's are stepped by rows.
+                columns.step_with_fn(|cell, neighbors| !cell && neighbors == 1);
+                dense.step_with_fn(|cell, neighbors| !cell && neighbors == 1);
+                let back = columns.to_storage::<DenseBool>();
+                assert_eq!(back.surface(), dense.surface(), "{size:?} {edges:?} {rule}");
+            }
+        }
+    }
+    let mut columns = ColumnMajor::new((3, 2));
+    columns.set(2, 0, true);
+    assert_eq!(
+        columns.as_slice(),
+        [false, false, false, false, true, false]
+    );
+}
@@ -0,0 +1,137 @@
+use crate::{GameOfLife, GridPos, GridStorage};
+
+impl<S: GridStorage> GameOfLife<S> {
+    /// Starts keeping a tag, such as a team, for every live cell, all 0 to
+    /// begin with. Once tags are on, a cell born in a step takes the tag most
+    /// of its live neighbors have, the lowest on a tie, and a surviving cell
+    /// keeps its own. Cells brought to life any other way than by
+    /// [`step`](Self::step) or [`set_tagged`](Self::set_tagged) take the tag
+    /// their cell last had.
+    pub fn enable_tags(&mut self) {
+        if self.tags.is_none() {
+            self.tags = Some(vec![0; self.cell_count()]);
+        }
+    }
+
+    /// Stops keeping tags and forgets them.
+    pub fn disable_tags(&mut self) {
+        self.tags = None;
+    }
+
+    pub fn has_tags(&self) -> bool {
+        self.tags.is_some()
+    }
+
+    /// The tag of the cell at `pos`, or `None` if it is dead or tags are off.
+    pub fn tag(&self, pos: GridPos) -> Option<u8> {
+        let tags = self.tags.as_ref()?;
+        self.get(pos).then(|| tags[self.index(pos)])
+    }
+
+    /// Brings the cell at `pos` to life with `tag`, turning tags on if they
+    /// are off. Cells the mask forces are left alone.
+    pub fn set_tagged(&mut self, pos: GridPos, tag: u8) {
+        if self.is_forced(pos) {
+            return;
+        }
+        self.enable_tags();
+        self.set(pos, true);
+        let i = self.index(pos);
+        if let Some(tags) = &mut self.tags {
+            tags[i] = tag;
+        }
+    }
+
+    /// Tags every live cell by which of `teams` equal, side-by-side columns of
+    /// the board it is in, from 0 on the left, for colonies that start apart.
+    pub fn tag_by_columns(&mut self, teams: u8) {
+        self.enable_tags();
+        let (w, _) = self.size();
+        let teams = teams.max(1) as usize;
+        for i in 0..self.cell_count() {
+            let GridPos(x, _) = self.coords(i);
+            if let Some(tags) = &mut self.tags {
+                tags[i] = (x * teams / w) as u8;
+            }
+        }
+    }
+
+    /// The tags of the next generation, `next`, if tags are on.
+    pub(crate) fn next_tags(&self, next: &[bool]) -> Option<Vec<u8>> {
+        let tags = self.tags.as_ref()?;
+        let mut counts = Vec::new();
+        let next_tags = (0..self.cell_count())
+            .map(|i| {
+                let GridPos(x, y) = self.coords(i);
+                match (self.get(GridPos(x, y)), next[i]) {
+                    (_, false) => 0,
+                    (true, true) => tags[i],
+                    (false, true) => {
+                        counts.clear();
+                        let live = self.neighbors(GridPos(x, y)).filter(|&pos| self.get(pos));
+                        counts.extend(live.map(|pos| tags[self.index(pos)]));
+                        counts.sort_unstable();
+                        majority(&counts)
+                    }
+                }
+            })
+            .collect();
+        Some(next_tags)
+    }
+}
+
+/// The most common of `sorted` tags, the lowest of those tied, or 0 if there
+/// are none.
+fn majority(sorted: &[u8]) -> u8 {
+    let mut best = (0, 0);
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > best.1 {
+            best = (run[0], run.len());
+        }
+    }
+    best.0
+}
+
+#[test]
+fn test_births_take_majority_tag() {
+    let mut game = GameOfLife::new_empty((6, 6));
+    assert_eq!(game.tag(GridPos(1, 1)), None);
+    // An L of three cells gives birth to the fourth corner of a block; two of
+    // its parents are team 2.
+    game.set_tagged(GridPos(1, 1), 2);
+    game.set_tagged(GridPos(2, 1), 2);
+    game.set_tagged(GridPos(1, 2), 5);
+    game.step();
+    assert!(game.get(GridPos(2, 2)));
+    assert_eq!(game.tag(GridPos(2, 2)), Some(2));
+    assert_eq!(game.tag(GridPos(1, 2)), Some(5));
+    assert_eq!(game.tag(GridPos(3, 3)), None);
+
+    // Tags follow a blinker as it turns.
+    let mut game = GameOfLife::new_empty((5, 5));
+    for x in 1..4 {
+        game.set(GridPos(x, 2), true);
+    }
+    game.tag_by_columns(5);
+    assert_eq!(game.tag(GridPos(1, 2)), Some(1));
+    game.step();
+    // Each new end is born of the whole row, one cell from each of teams 1,
+    // 2 and 3, so the lowest wins the tie.
+    assert_eq!(
+        (
+            game.tag(GridPos(2, 1)),
+            game.tag(GridPos(2, 2)),
+            game.tag(GridPos(2, 3))
+        ),
+        (Some(1), Some(2), Some(1))
+    );
+    // Moving and resizing the board take the tags along.
+    game.translate(1, 0);
+    game.resize((4, 4));
+    assert_eq!(
+        (game.tag(GridPos(3, 1)), game.tag(GridPos(3, 2))),
+        (Some(1), Some(2))
+    );
+    game.disable_tags();
+    assert_eq!(game.tag(GridPos(2, 2)), None);
+}
@@ -0,0 +1,278 @@
+use rand::Rng;
+
+use crate::{GameOfLife, GridPos};
+
+/// One of the two sides in a territory game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn other(self) -> Self {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+/// Rules of a territory game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerritoryConfig {
+    /// Cells each player may place before the simulation starts.
+    pub budget: usize,
+    /// Generations simulated before the score is taken.
+    pub generations: u64,
+}
+
+impl Default for TerritoryConfig {
+    fn default() -> Self {
+        Self {
+            budget: 50,
+            generations: 500,
+        }
+    }
+}
+
+/// Where a territory game is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The player is placing cells; the simulation is stopped.
+    Placing(Player),
+    /// The simulation runs until the configured number of generations.
+    Running,
+    /// The game is over; `None` is a draw.
+    Over(Option<Player>),
+}
+
+/// Why a cell could not be placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    /// Cells can only be placed before the simulation starts.
+    NotPlacing,
+    OutOfBounds,
+    Occupied,
+    BudgetExhausted,
+}
+
+/// A two-player game of Immigration Life: a live cell belongs to one of the
+/// players, and a newborn cell belongs to whoever owns the majority of the
+/// three cells that brought it to life. Each player seeds a half of the board
+/// and may then place a limited budget of extra cells; after a fixed number of
+/// generations, the player with more live cells wins.
+pub struct Territory {
+    game: GameOfLife,
+    /// Owner of each live cell; meaningless for dead cells.
+    owners: Vec<Player>,
+    budgets: [usize; 2],
+    phase: Phase,
+    config: TerritoryConfig,
+    start: u64,
+}
+
+impl Territory {
+    /// Starts a game with each player's half of the board seeded at random.
+    pub fn new(size: (usize, usize), config: TerritoryConfig) -> Self {
+        let mut territory = Self::empty(size, config);
+        let mut rng = rand::thread_rng();
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                let owner = if x < size.0 / 2 {
+                    Player::One
+                } else {
+                    Player::Two
+                };
+                territory.put(x, y, rng.gen_bool(0.3).then_some(owner));
+            }
+        }
+        territory
+    }
+
+    /// Starts a game on a blank board.
+    pub fn empty(size: (usize, usize), config: TerritoryConfig) -> Self {
+        let game = GameOfLife::new_empty(size);
+        Self {
+            start: game.generation(),
+            owners: vec![Player::One; game.cell_count()],
+            game,
+            budgets: [config.budget; 2],
+            phase: Phase::Placing(Player::One),
+            config,
+        }
+    }
+
+    pub fn game(&self) -> &GameOfLife {
+        &self.game
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The owner of the cell at `pos`, or `None` if it is dead.
+    pub fn owner(&self, pos: GridPos) -> Option<Player> {
+        self.game
+            .get(pos)
+            .then(|| self.owners[self.game.index(pos)])
+    }
+
+    pub fn population(&self, player: Player) -> usize {
+        (0..self.game.cell_count())
+            .filter(|&i| self.game.surface()[i] && self.owners[i] == player)
+            .count()
+    }
+
+    /// Cells the player may still place.
+    pub fn budget(&self, player: Player) -> usize {
+        self.budgets[player.index()]
+    }
+
+    /// Generations left before the game ends.
+    pub fn remaining(&self) -> u64 {
+        let elapsed = self.game.generation() - self.start;
+        self.config.generations.saturating_sub(elapsed)
+    }
+
+    /// Places a cell for the player whose turn it is. The turn passes on
+    /// automatically once their budget is spent.
+    pub fn place(&mut self, pos: GridPos) -> Result<(), PlaceError> {
+        let Phase::Placing(player) = self.phase else {
+            return Err(PlaceError::NotPlacing);
+        };
+        let (GridPos(x, y), (w, h)) = (pos, self.game.size());
+        if x >= w || y >= h {
+            return Err(PlaceError::OutOfBounds);
+        }
+        if self.game.get(GridPos(x, y)) {
+            return Err(PlaceError::Occupied);
+        }
+        let budget = &mut self.budgets[player.index()];
+        if *budget == 0 {
+            return Err(PlaceError::BudgetExhausted);
+        }
+        *budget -= 1;
+        let spent = *budget == 0;
+        self.put(x, y, Some(player));
+        if spent {
+            self.end_turn();
+        }
+        Ok(())
+    }
+
+    /// Ends the current player's placement turn. Once both players have
+    /// placed, the simulation starts.
+    pub fn end_turn(&mut self) {
+        self.phase = match self.phase {
+            Phase::Placing(Player::One) if self.budget(Player::Two) > 0 => {
+                Phase::Placing(Player::Two)
+            }
+            Phase::Placing(_) => Phase::Running,
+            phase => phase,
+        };
+    }
+
+    /// Advances one generation while running, ending the game when the last
+    /// generation has been played.
+    pub fn tick(&mut self) {
+        if self.phase != Phase::Running {
+            return;
+        }
+        let (w, h) = self.game.size();
+        let mut owners = self.owners.clone();
+        for y in 0..h {
+            for x in 0..w {
+                if self.game.get(GridPos(x, y)) || self.game.count_neighbors(GridPos(x, y)) != 3 {
+                    continue;
+                }
+                let ones = self
+                    .game
+                    .neighbors(GridPos(x, y))
+                    .filter(|&pos| self.owner(pos) == Some(Player::One))
+                    .count();
+                owners[self.game.index(GridPos(x, y))] =
+                    if ones >= 2 { Player::One } else { Player::Two };
+            }
+        }
+        self.game.step();
+        self.owners = owners;
+
+        if self.remaining() == 0 {
+            let (one, two) = (self.population(Player::One), self.population(Player::Two));
+            self.phase = Phase::Over(match one.cmp(&two) {
+                std::cmp::Ordering::Greater => Some(Player::One),
+                std::cmp::Ordering::Less => Some(Player::Two),
+                std::cmp::Ordering::Equal => None,
+            });
+        }
+    }
+
+    fn put(&mut self, x: usize, y: usize, owner: Option<Player>) {
+        self.game.set(GridPos(x, y), owner.is_some());
+        if let Some(owner) = owner {
+            let i = self.game.index(GridPos(x, y));
+            self.owners[i] = owner;
+        }
+    }
+}
+
+#[test]
+fn test_territory_turns_and_budget() {
+    let config = TerritoryConfig {
+        budget: 2,
+        generations: 3,
+    };
+    let mut territory = Territory::empty((10, 10), config);
+    assert_eq!(territory.phase(), Phase::Placing(Player::One));
+    assert_eq!(
+        territory.place(GridPos(20, 0)),
+        Err(PlaceError::OutOfBounds)
+    );
+    territory.place(GridPos(1, 1)).unwrap();
+    assert_eq!(territory.place(GridPos(1, 1)), Err(PlaceError::Occupied));
+    territory.place(GridPos(2, 1)).unwrap();
+    // Spending the whole budget passes the turn.
+    assert_eq!(territory.phase(), Phase::Placing(Player::Two));
+    assert_eq!(territory.budget(Player::One), 0);
+    territory.place(GridPos(7, 7)).unwrap();
+    territory.end_turn();
+    assert_eq!(territory.phase(), Phase::Running);
+    assert_eq!(territory.place(GridPos(5, 5)), Err(PlaceError::NotPlacing));
+    assert_eq!(territory.owner(GridPos(7, 7)), Some(Player::Two));
+    assert_eq!(territory.owner(GridPos(0, 0)), None);
+}
+
+#[test]
+fn test_territory_births_follow_majority_and_score() {
+    let config = TerritoryConfig {
+        budget: 3,
+        generations: 2,
+    };
+    let mut territory = Territory::empty((9, 9), config);
+    // Player one places two cells of a horizontal blinker, player two the third.
+    territory.place(GridPos(3, 4)).unwrap();
+    territory.place(GridPos(4, 4)).unwrap();
+    territory.end_turn();
+    territory.place(GridPos(5, 4)).unwrap();
+    territory.end_turn();
+
+    territory.tick();
+    // The vertical phase's new cells each have two player-one parents.
+    assert_eq!(territory.owner(GridPos(4, 3)), Some(Player::One));
+    assert_eq!(territory.owner(GridPos(4, 5)), Some(Player::One));
+    assert_eq!(territory.owner(GridPos(4, 4)), Some(Player::One));
+    assert_eq!(territory.phase(), Phase::Running);
+
+    territory.tick();
+    assert_eq!(territory.population(Player::One), 3);
+    assert_eq!(territory.population(Player::Two), 0);
+    assert_eq!(territory.phase(), Phase::Over(Some(Player::One)));
+}
@@ -0,0 +1,112 @@
+// Generators for property tests: random boards and rules of bounded size,
+// drawn from a seeded RNG so a failing case can be replayed from its seed.
+
+use std::{fs, path::PathBuf};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{capture::adler32, query::Run, EdgeMode, GameOfLife, GridPos, Rule, Settled};
+
+/// How many random cases each property is checked on.
+pub const CASES: u64 = 64;
+
+/// Runs `property` on [`CASES`] seeded RNGs, naming the seed that failed.
+pub fn check(property: impl Fn(&mut StdRng)) {
+    for seed in 0..CASES {
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            property(&mut StdRng::seed_from_u64(seed))
+        }));
+        if let Err(panic) = outcome {
+            eprintln!("property failed for seed {seed}");
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+/// A board up to 24×24 with a random density, edge mode and rule.
+pub fn game(rng: &mut StdRng) -> GameOfLife {
+    let size = (rng.gen_range(1..=24), rng.gen_range(1..=24));
+    let mut game = GameOfLife::new_empty(size);
+    let density = rng.gen_range(0.0..=1.0);
+    for i in 0..game.cell_count() {
+        let pos = game.coords(i);
+        game.set(pos, rng.gen_bool(density));
+    }
+    game.set_edge_mode(edge_mode(rng));
+    game.set_rule(rule(rng));
+    game
+}
+
+pub fn edge_mode(rng: &mut StdRng) -> EdgeMode {
+    if rng.gen() {
+        EdgeMode::Toroidal
+    } else {
+        EdgeMode::Bounded
+    }
+}
+
+/// Any valid rule, built from its rulestring so the parser is exercised too.
+pub fn rule(rng: &mut StdRng) -> Rule {
+    let counts = |rng: &mut StdRng| {
+        (0..=8)
+            .filter(|_| rng.gen_bool(0.3))
+            .map(|n: u8| char::from(b'0' + n))
+            .collect::<String>()
+    };
+    let rulestring = format!("B{}/S{}", counts(rng), counts(rng));
+    Rule::parse(&rulestring).unwrap_or_else(|err| panic!("{rulestring}: {err}"))
+}
+
+/// An empty directory of its own under the system's temporary one, for a
+/// test that writes files.
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gol-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// The image data of a PNG of stored deflate blocks, as
+/// [`encode_png`](crate::capture::encode_png) and
+/// [`PngStream`](crate::capture::PngStream) write them, and the size of
+/// its biggest IDAT chunk.
+pub fn stored_rows(png: &[u8]) -> (Vec<u8>, usize) {
+    let (mut zlib, mut biggest, mut at) = (Vec::new(), 0, 8);
+    while at < png.len() {
+        let len = u32::from_be_bytes(png[at..at + 4].try_into().unwrap()) as usize;
+        if &png[at + 4..at + 8] == b"IDAT" {
+            zlib.extend_from_slice(&png[at + 8..at + 8 + len]);
+            biggest = biggest.max(len);
+        }
+        at += len + 12;
+    }
+    let (mut raw, mut at) = (Vec::new(), 2);
+    loop {
+        let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]) as usize;
+        raw.extend_from_slice(&zlib[at + 5..at + 5 + len]);
+        let last = zlib[at] == 1;
+        at += 5 + len;
+        if last {
+            break;
+        }
+    }
+    assert_eq!(zlib[at..], adler32(&raw).to_be_bytes());
+    (raw, biggest)
+}
+
+/// A 2×2 board alive on its diagonal.
+pub fn checker() -> GameOfLife {
+    let mut game = GameOfLife::new_empty((2, 2));
+    game.set(GridPos(0, 0), true);
+    game.set(GridPos(1, 1), true);
+    game
+}
+
+/// A run of `game` that ended in a blinker's two-generation cycle.
+pub fn blinker_run(game: &GameOfLife) -> Run<'_> {
+    Run {
+        game,
+        births: 4,
+        deaths: 6,
+        outcome: Some(Settled::Cycle(2)),
+    }
+}
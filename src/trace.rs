@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::{GameOfLife, GridPos};
+
+/// A cell that a step brought to life or killed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange {
+    /// The generation the step led to.
+    pub generation: u64,
+    pub x: usize,
+    pub y: usize,
+    /// Born, rather than died.
+    pub born: bool,
+    /// Its live neighbors before the step, which decided it.
+    pub neighbors: u8,
+}
+
+impl CellChange {
+    pub const CSV_HEADER: &'static str = "gen,x,y,change,neighbors";
+}
+
+/// One line of CSV: `12,4,7,born,3`.
+impl fmt::Display for CellChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let change = if self.born { "born" } else { "died" };
+        write!(
+            f,
+            "{},{},{},{change},{}",
+            self.generation, self.x, self.y, self.neighbors
+        )
+    }
+}
+
+/// Watches a board generation by generation and says which cells each step
+/// changed, with the neighbor counts they changed on, for checking a rule's
+/// transitions by eye or by grep.
+#[derive(Debug, Clone, Default)]
+pub struct StepTrace {
+    /// The cells and their neighbor counts as of the generation seen last.
+    before: Option<(u64, Vec<bool>, Vec<u8>)>,
+}
+
+impl StepTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks at `game`, returning the cells that changed since the last
+    /// look if that was at the generation before, in row-major order.
+    pub fn observe(&mut self, game: &GameOfLife) -> Vec<CellChange> {
+        let generation = game.generation();
+        let mut changes = Vec::new();
+        if let Some((seen, cells, counts)) = &self.before {
+            if seen + 1 == generation && cells.len() == game.cell_count() {
+                for (i, (&was, &is)) in cells.iter().zip(game.surface()).enumerate() {
+                    if was != is {
+                        let GridPos(x, y) = game.coords(i);
+                        let neighbors = counts[i];
+                        changes.push(CellChange {
+                            generation,
+                            x,
+                            y,
+                            born: is,
+                            neighbors,
+                        });
+                    }
+                }
+            }
+        }
+        let (mut cells, mut counts) = match self.before.take() {
+            Some((_, cells, counts)) => (cells, counts),
+            None => Default::default(),
+        };
+        cells.clear();
+        cells.extend_from_slice(game.surface());
+        game.neighbor_counts_into(&mut counts);
+        self.before = Some((generation, cells, counts));
+        changes
+    }
+}
+
+#[test]
+fn test_step_trace_lists_transitions() {
+    use crate::GridPos;
+
+    let mut game = GameOfLife::new_empty((5, 5));
+    game.insert_pattern(&crate::Pattern::named("blinker").unwrap(), GridPos(1, 2));
+    let mut trace = StepTrace::new();
+    assert!(trace.observe(&game).is_empty());
+    game.step();
+    let lines = trace
+        .observe(&game)
+        .iter()
+        .map(|change| change.to_string())
+        .collect::<Vec<_>>();
+    // The ends die with one neighbor; above and below the middle are born
+    // with three.
+    assert_eq!(
+        lines,
+        [
+            "1,2,1,born,3",
+            "1,1,2,died,1",
+            "1,3,2,died,1",
+            "1,2,3,born,3"
+        ]
+    );
+    // A look that skips a generation has nothing to compare with.
+    game.step();
+    game.step();
+    assert!(trace.observe(&game).is_empty());
+    game.step();
+    assert_eq!(trace.observe(&game).len(), 4);
+}
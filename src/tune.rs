@@ -0,0 +1,230 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    autosave::write_atomic, ChunkedTiles, ColumnMajor, DenseBool, GameOfLife, GridStorage,
+    PackedWords,
+};
+
+/// How long tuning may take altogether, shared between the engines.
+pub const BUDGET: Duration = Duration::from_secs(2);
+
+/// How many times longer than a step takes the delay between steps is at
+/// least, to leave the machine time for drawing and everything else.
+pub const HEADROOM: u32 = 3;
+
+/// The storage engines tuning times, by name.
+pub const ENGINES: &[(&str, Timer)] = &[
+    ("dense", time_steps::<DenseBool>),
+    ("packed", time_steps::<PackedWords>),
+    ("chunked", time_steps::<ChunkedTiles>),
+    ("columns", time_steps::<ColumnMajor>),
+];
+
+/// Steps a soup of the given size for about as long as it is given, at least
+/// once, returning the steps it took and how long they took.
+pub type Timer = fn((usize, usize), Duration) -> (u32, Duration);
+
+/// What tuning found suits this machine at a board size, as kept between runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tuning {
+    /// The board size it was tuned for.
+    pub size: (usize, usize),
+    /// The engine that stepped fastest.
+    pub engine: String,
+    /// The delay between generations the machine keeps up with.
+    pub delay: Duration,
+}
+
+/// How long an engine took a step, on average.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub engine: &'static str,
+    pub per_step: Duration,
+}
+
+fn time_steps<S: GridStorage>(size: (usize, usize), budget: Duration) -> (u32, Duration) {
+    let mut game = GameOfLife::<S>::empty(size);
+    game.fill(0.35, 1);
+    let started = Instant::now();
+    let mut steps = 0;
+    while steps == 0 || started.elapsed() < budget {
+        game.step();
+        steps += 1;
+    }
+    (steps, started.elapsed())
+}
+
+/// Times each of `engines` on a soup of `size`, each for its share of
+/// `budget`. Only an engine taking longer than its share for a single step
+/// runs over.
+pub fn measure(
+    size: (usize, usize),
+    budget: Duration,
+    engines: &[(&'static str, Timer)],
+) -> Vec<Timing> {
+    let share = budget / engines.len().max(1) as u32;
+    engines
+        .iter()
+        .map(|&(engine, timer)| {
+            let (steps, took) = timer(size, share);
+            Timing {
+                engine,
+                per_step: took / steps,
+            }
+        })
+        .collect()
+}
+
+/// The fastest of `timings`, the first if some tie, and a delay of
+/// [`HEADROOM`] times its step, but no shorter than `floor`. `None` if there
+/// are no timings.
+pub fn pick(size: (usize, usize), timings: &[Timing], floor: Duration) -> Option<Tuning> {
+    let fastest = timings
+        .iter()
+        .reduce(|best, timing| match timing.per_step < best.per_step {
+            true => timing,
+            false => best,
+        })?;
+    // Rounded up to whole milliseconds, as delays are given.
+    let delay = (fastest.per_step * HEADROOM).max(floor);
+    let delay = Duration::from_millis(delay.as_nanos().div_ceil(1_000_000) as u64);
+    Some(Tuning {
+        size,
+        engine: fastest.engine.to_string(),
+        delay,
+    })
+}
+
+impl Tuning {
+    /// Where tuning is kept in `dir`.
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join("tuning")
+    }
+
+    /// The tuning kept in `dir`, if any reads back.
+    pub fn load(dir: &Path) -> Option<Self> {
+        fs::read_to_string(Self::path(dir)).ok()?.parse().ok()
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        write_atomic(&Self::path(dir), self.to_string().as_bytes())
+    }
+
+    /// Whether this suits a board of `size` as well: one within twice or
+    /// half as many cells as it was tuned for.
+    pub fn suits(&self, size: (usize, usize)) -> bool {
+        let (tuned, cells) = (self.size.0 * self.size.1, size.0 * size.1);
+        cells <= 2 * tuned && tuned <= 2 * cells
+    }
+}
+
+impl fmt::Display for Tuning {
+    /// `key = value` lines, after a comment saying where they came from.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Tuned automatically. Run `gol tune` to tune again; --delay overrides it."
+        )?;
+        writeln!(f, "size = {}x{}", self.size.0, self.size.1)?;
+        writeln!(f, "engine = {}", self.engine)?;
+        writeln!(f, "delay = {}", self.delay.as_millis())
+    }
+}
+
+impl std::str::FromStr for Tuning {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut size, mut engine, mut delay) = (None, None, None);
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad = || format!("bad tuning line {line:?}");
+            let (key, value) = line.split_once('=').ok_or_else(bad)?;
+            let value = value.trim();
+            match key.trim() {
+                "size" => {
+                    let (w, h) = value.split_once('x').ok_or_else(bad)?;
+                    size = Some((w.parse().map_err(|_| bad())?, h.parse().map_err(|_| bad())?));
+                }
+                "engine" => engine = Some(value.to_string()),
+                "delay" => delay = Some(Duration::from_millis(value.parse().map_err(|_| bad())?)),
+                _ => return Err(bad()),
+            }
+        }
+        match (size, engine, delay) {
+            (Some(size), Some(engine), Some(delay)) => Ok(Self {
+                size,
+                engine,
+                delay,
+            }),
+            _ => Err("tuning needs a size, an engine and a delay".to_string()),
+        }
+    }
+}
+
+#[test]
+fn test_pick_takes_the_fastest_with_headroom() {
+    let ms = Duration::from_millis;
+    let timings = [
+        Timing {
+            engine: "dense",
+            per_step: ms(9),
+        },
+        Timing {
+            engine: "packed",
+            per_step: ms(4),
+        },
+        Timing {
+            engine: "chunked",
+            per_step: ms(4),
+        },
+    ];
+    let floor = ms(50);
+    // A quick machine runs at the usual delay.
+    let tuning = pick((200, 60), &timings, floor).unwrap();
+    assert_eq!((tuning.engine.as_str(), tuning.delay), ("packed", floor));
+    // A slow one gets time to spare between its steps.
+    let slow = timings.map(|timing| Timing {
+        per_step: timing.per_step * 10,
+        ..timing
+    });
+    assert_eq!(pick((200, 60), &slow, floor).unwrap().delay, ms(120));
+    let odd = [Timing {
+        engine: "dense",
+        per_step: Duration::from_micros(17_300),
+    }];
+    assert_eq!(pick((200, 60), &odd, Duration::ZERO).unwrap().delay, ms(52));
+    assert_eq!(pick((200, 60), &[], floor), None);
+
+    let text = tuning.to_string();
+    assert!(text.starts_with("# Tuned automatically."));
+    assert_eq!(text.parse(), Ok(tuning.clone()));
+    assert!("size = 3x3\nengine = dense".parse::<Tuning>().is_err());
+    assert!(tuning.suits((300, 60)) && !tuning.suits((400, 80)) && !tuning.suits((50, 60)));
+}
+
+#[test]
+fn test_measure_keeps_to_its_budget() {
+    let budget = Duration::from_millis(120);
+    let started = Instant::now();
+    let timings = measure((64, 48), budget, ENGINES);
+    // A step at this size is far shorter than a share, so the total is
+    // barely over.
+    assert!(started.elapsed() < budget * 2, "{:?}", started.elapsed());
+    let names: Vec<_> = timings.iter().map(|timing| timing.engine).collect();
+    assert_eq!(names, ["dense", "packed", "chunked", "columns"]);
+    assert!(timings
+        .iter()
+        .all(|timing| timing.per_step > Duration::ZERO));
+    // An engine slower than its share still gets its one step timed.
+    let slow: Timer = |_, _| (1, Duration::from_secs(1));
+    let timings = measure((8, 8), Duration::from_millis(1), &[("slow", slow)]);
+    assert_eq!(timings[0].per_step, Duration::from_secs(1));
+}
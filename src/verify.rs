@@ -0,0 +1,357 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    game::reflect, ChunkedTiles, ColumnMajor, EdgeMode, Frame, GameOfLife, GridPos, GridStorage,
+    Neighborhood, PackedWords, Rule,
+};
+
+/// A board an engine keeps stepping, in whatever storage it steps in, from
+/// the first generation of a check to the last.
+pub trait Stepping {
+    /// Advances the board by one generation.
+    fn advance(&mut self);
+
+    fn fingerprint(&self) -> u64;
+
+    /// The board as it is, for a report.
+    fn board(&self) -> GameOfLife;
+}
+
+/// A board in `S` and how it steps.
+struct Stepper<S: GridStorage> {
+    game: GameOfLife<S>,
+    step: fn(&mut GameOfLife<S>),
+}
+
+impl<S: GridStorage + 'static> Stepping for Stepper<S> {
+    fn advance(&mut self) {
+        (self.step)(&mut self.game);
+    }
+
+    fn fingerprint(&self) -> u64 {
+        self.game.fingerprint()
+    }
+
+    fn board(&self) -> GameOfLife {
+        self.game.to_storage()
+    }
+}
+
+/// Keeps `game` stepping with `step`.
+pub fn stepping<S: GridStorage + 'static>(
+    game: GameOfLife<S>,
+    step: fn(&mut GameOfLife<S>),
+) -> Box<dyn Stepping> {
+    Box::new(Stepper { game, step })
+}
+
+/// Takes the starting board into an engine's own storage, to be stepped
+/// there for the whole check.
+pub type Engine = fn(&GameOfLife) -> Box<dyn Stepping>;
+
+/// Every stepping path of [`GameOfLife`] checked against the reference, by
+/// name. New engines should be added here so the harness covers them.
+pub const ENGINES: &[(&str, Engine)] = &[
+    ("step", |game| stepping(game.clone(), GameOfLife::step)),
+    ("tick", |game| {
+        stepping(game.clone(), |game| {
+            game.tick();
+        })
+    }),
+    ("packed", |game| {
+        stepping(game.to_storage::<PackedWords>(), GameOfLife::step)
+    }),
+    ("chunked", |game| {
+        stepping(game.to_storage::<ChunkedTiles>(), GameOfLife::step)
+    }),
+    ("column-major", |game| {
+        stepping(game.to_storage::<ColumnMajor>(), GameOfLife::step)
+    }),
+];
+
+/// How many of the cells that differ a [`Divergence`] shows; its report
+/// lists them all.
+const SHOWN: usize = 8;
+
+/// Where an engine first disagreed with the reference stepper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub engine: &'static str,
+    /// The generation whose state differed.
+    pub generation: u64,
+    /// The cells that differ, with the reference's state.
+    pub cells: Vec<(usize, usize, bool)>,
+    /// The reference's board, and the engine's, at that generation.
+    pub expected: Frame,
+    pub actual: Frame,
+}
+
+impl Divergence {
+    /// Writes both boards at the divergence as RLE, and every cell that
+    /// differs, into `dir`, which is made if need be. Returns the files, in
+    /// that order, named after the engine and generation.
+    pub fn write_report(&self, dir: &Path) -> io::Result<[PathBuf; 3]> {
+        fs::create_dir_all(dir)?;
+        let name = |what| dir.join(format!("{}-{}-{what}", self.engine, self.generation));
+        let paths = [name("expected.rle"), name("actual.rle"), name("diff.txt")];
+        fs::write(&paths[0], self.expected.to_rle())?;
+        fs::write(&paths[1], self.actual.to_rle())?;
+        let mut listing = self.heading();
+        for &cell in &self.cells {
+            listing += &format!("{}\n", describe(cell));
+        }
+        fs::write(&paths[2], listing)?;
+        Ok(paths)
+    }
+
+    fn heading(&self) -> String {
+        format!(
+            "engine {:?} diverged at generation {} in {} cells:\n",
+            self.engine,
+            self.generation,
+            self.cells.len()
+        )
+    }
+}
+
+/// A cell that differs, for a person: `(1, 2): expected live, got dead`.
+fn describe((x, y, expected): (usize, usize, bool)) -> String {
+    let (want, got) = if expected {
+        ("live", "dead")
+    } else {
+        ("dead", "live")
+    };
+    format!("({x}, {y}): expected {want}, got {got}")
+}
+
+impl fmt::Display for Divergence {
+    /// The first few cells that differ.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.heading())?;
+        for &cell in self.cells.iter().take(SHOWN) {
+            writeln!(f, "  {}", describe(cell))?;
+        }
+        if self.cells.len() > SHOWN {
+            writeln!(f, "  and {} more", self.cells.len() - SHOWN)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Runs a random `width`×`height` soup from `seed` for `generations` under
+/// `rule` and `edges` on the naive reference stepper and every one of the
+/// [`ENGINES`] in lockstep, each on one board in its own storage, comparing
+/// fingerprints each generation and reporting the first disagreement.
+pub fn verify_engines(
+    width: usize,
+    height: usize,
+    seed: u64,
+    rule: Rule,
+    edges: EdgeMode,
+    generations: u64,
+) -> Result<(), Box<Divergence>> {
+    verify_engines_of(ENGINES, (width, height), seed, rule, edges, generations)
+}
+
+/// Runs like [`verify_engines`], on `engines` rather than [`ENGINES`].
+pub fn verify_engines_of(
+    engines: &[(&'static str, Engine)],
+    (width, height): (usize, usize),
+    seed: u64,
+    rule: Rule,
+    edges: EdgeMode,
+    generations: u64,
+) -> Result<(), Box<Divergence>> {
+    let mut reference = GameOfLife::new_empty((width, height));
+    let mut rng = StdRng::seed_from_u64(seed);
+    for i in 0..reference.cell_count() {
+        let pos = reference.coords(i);
+        reference.set(pos, rng.gen_bool(0.5));
+    }
+    reference.set_rule(rule);
+    reference.set_edge_mode(edges);
+
+    let mut engines = engines
+        .iter()
+        .map(|&(name, start)| (name, start(&reference)))
+        .collect::<Vec<_>>();
+
+    for generation in 1..=generations {
+        let next = reference_step(&reference);
+        for (i, &cell) in next.iter().enumerate() {
+            let pos = reference.coords(i);
+            reference.set(pos, cell);
+        }
+        let expected = reference.fingerprint();
+        for (name, engine) in &mut engines {
+            engine.advance();
+            if engine.fingerprint() == expected {
+                continue;
+            }
+            let (expected, actual) = (Frame::of(&reference), Frame::of(&engine.board()));
+            return Err(Box::new(Divergence {
+                engine: name,
+                generation,
+                cells: expected.diff(&actual),
+                expected,
+                actual,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// The next generation of `game` computed as plainly as possible, sharing no
+/// neighbor logic with the engines.
+fn reference_step(game: &GameOfLife) -> Vec<bool> {
+    let (w, h) = game.size();
+    let (w, h) = (w as isize, h as isize);
+    let rule = game.rule();
+    let r = rule.range() as isize;
+    let mut next = Vec::with_capacity(game.cell_count());
+    for y in 0..h {
+        for x in 0..w {
+            let mut neighbors = 0;
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let outside = match rule.neighborhood() {
+                        Neighborhood::Moore => false,
+                        Neighborhood::VonNeumann => dx.abs() + dy.abs() > r,
+                    };
+                    if dx == 0 && dy == 0 || outside {
+                        continue;
+                    }
+                    let (i, j) = match game.edge_mode() {
+                        EdgeMode::Bounded => (x + dx, y + dy),
+                        EdgeMode::Toroidal => ((x + dx).rem_euclid(w), (y + dy).rem_euclid(h)),
+                        EdgeMode::Mirror => (
+                            reflect(x + dx, w as usize) as isize,
+                            reflect(y + dy, h as usize) as isize,
+                        ),
+                    };
+                    if (0..w).contains(&i)
+                        && (0..h).contains(&j)
+                        && game.get(GridPos(i as usize, j as usize))
+                    {
+                        neighbors += 1;
+                    }
+                }
+            }
+            next.push(rule.next(game.get(GridPos(x as usize, y as usize)), neighbors));
+        }
+    }
+    next
+}
+
+#[test]
+fn test_engines_agree_with_reference() {
+    let rules = [
+        "B3/S23",
+        "B36/S23",
+        "B2/S",
+        "B0/S8",
+        "B3678/S34678",
+        "B1/S012345678",
+        "R2,C0,M0,S2..5,B4..6,NM",
+        "R3,C0,M0,S3..7,B3,NN",
+    ];
+    for (i, rule) in rules.into_iter().enumerate() {
+        for edges in [EdgeMode::Bounded, EdgeMode::Toroidal, EdgeMode::Mirror] {
+            // The last is wider than a tile, with a narrow one at the end.
+            for size in [(1, 1), (1, 7), (7, 1), (16, 9), (70, 9)] {
+                let rule = Rule::parse(rule).unwrap();
+                let result = verify_engines(size.0, size.1, i as u64, rule, edges, 20);
+                assert_eq!(result, Ok(()), "{rule} {edges:?} {size:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_divergence_is_reported() {
+    let blank = Frame::of(&GameOfLife::new_empty((3, 3)));
+    let divergence = Divergence {
+        engine: "step",
+        generation: 3,
+        cells: vec![(1, 2, true)],
+        expected: blank.clone(),
+        actual: blank,
+    };
+    assert_eq!(
+        divergence.to_string(),
+        "engine \"step\" diverged at generation 3 in 1 cells:\n  (1, 2): expected live, got dead\n"
+    );
+}
+
+#[test]
+fn test_divergence_report_shows_both_boards() {
+    // An engine that goes wrong in one cell, at generation 5.
+    let engines: &[(&str, Engine)] = &[
+        ("step", |game| stepping(game.clone(), GameOfLife::step)),
+        ("broken", |game| {
+            stepping(game.clone(), |game| {
+                game.step();
+                if game.generation() == 5 {
+                    game.set(GridPos(2, 3), !game.get(GridPos(2, 3)));
+                }
+            })
+        }),
+    ];
+    let rule = Rule::default();
+    let err = verify_engines_of(engines, (70, 12), 1, rule, EdgeMode::Toroidal, 10).unwrap_err();
+    assert_eq!((err.engine, err.generation), ("broken", 5));
+    assert_eq!(err.cells.len(), 1);
+    assert_eq!(err.cells, err.expected.diff(&err.actual));
+    let (x, y, expected) = err.cells[0];
+    assert_eq!((x, y), (2, 3));
+
+    let dir = std::env::temp_dir().join(format!("gol-divergence-{}", std::process::id()));
+    let paths = err.write_report(&dir).unwrap();
+    let names = paths
+        .each_ref()
+        .map(|path| path.file_name().unwrap().to_str().unwrap());
+    assert_eq!(
+        names,
+        [
+            "broken-5-expected.rle",
+            "broken-5-actual.rle",
+            "broken-5-diff.txt"
+        ]
+    );
+    let board = |path| {
+        let (pattern, read_rule) = crate::rle::decode(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(read_rule, rule);
+        let mut game = GameOfLife::new_empty((70, 12));
+        game.insert_pattern(&pattern, GridPos(0, 0));
+        game
+    };
+    let (want, got) = (board(&paths[0]), board(&paths[1]));
+    assert_eq!(want.surface(), &err.expected.cells[..]);
+    assert_eq!(
+        (want.get(GridPos(2, 3)), got.get(GridPos(2, 3))),
+        (expected, !expected)
+    );
+    assert_eq!(Frame::of(&want).diff(&Frame::of(&got)).len(), 1);
+    let listing = fs::read_to_string(&paths[2]).unwrap();
+    assert_eq!(
+        listing,
+        format!("{}{}\n", err.heading(), describe(err.cells[0]))
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    // Only the first few cells are shown, the rest counted.
+    let many = Divergence {
+        cells: (0..11).map(|x| (x, 0, true)).collect(),
+        ..*err
+    };
+    let shown = many.to_string();
+    assert_eq!(shown.lines().count(), 1 + SHOWN + 1);
+    assert!(shown.ends_with("  and 3 more\n"));
+}